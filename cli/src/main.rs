@@ -0,0 +1,310 @@
+//! `tf` — TunnelFiles 的无界面命令行工具
+//!
+//! 这是工作区里独立于 Tauri/IPC 层的第二个二进制 crate（`cli`），通过路径依赖直接
+//! 链接主 crate（此处以 `tunnelfiles::` 引用）暴露的 `services`/`models`，复用与
+//! 桌面端完全相同的 Profile 存储、OS 凭据读取、HostKey 校验与 SFTP 操作代码，
+//! 不经过 `#[tauri::command]`/IPC 这一层。子命令对应桌面端最常用的几个操作：
+//!
+//!   tf ls    <profile> <path>              列出远程目录
+//!   tf get   <profile> <remote> <local>    下载单个文件
+//!   tf put   <profile> <local> <remote>    上传单个文件
+//!   tf mirror <profile> <local_dir> <remote_dir>   把本地目录单向镜像到远程
+//!
+//! `<profile>` 既可以是 Profile 的 `id`，也可以是 `name`（人类可读的连接名），
+//! 方便脚本里直接写在桌面端保存过的连接名。
+//!
+//! 说明：首次连接一台未被信任过的主机时，`SessionManager::connect` 会返回
+//! `NeedHostKeyConfirm` 而不是直接建联——这里选择如实报错并提示先在桌面端完成一次
+//! 交互式确认，而不是无人值守地自动信任，理由与 GUI 端完全一致（避免把中间人
+//! 攻击的指纹校验形同虚设）。本 crate 目前没有自己的 `Cargo.toml`（仓库里还没有
+//! 任何 crate 带 manifest，见 `src-tauri/`），真正接入构建时需要在工作区根新增
+//! `Cargo.toml`（`[workspace] members = ["src-tauri", "cli"]`）并让本 crate 以路径
+//! 依赖引用 `src-tauri` 作为库 target。
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+use tunnelfiles::models::error::{AppError, AppResult, ErrorCode};
+use tunnelfiles::models::profile::Profile;
+use tunnelfiles::services::session_manager::{ConnectStatus, SessionManager};
+use tunnelfiles::services::sftp_service::SftpService;
+use tunnelfiles::services::storage_service::Database;
+
+/// 单个文件传输时的分块大小
+const CHUNK_SIZE: u64 = 256 * 1024;
+/// 未在 Profile/设置里覆盖时使用的连接超时
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Parser)]
+#[command(name = "tf", about = "TunnelFiles 无界面命令行：对已保存的 Profile 做脚本化 SFTP 操作")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出远程目录内容
+    Ls {
+        profile: String,
+        #[arg(default_value = "/")]
+        path: String,
+    },
+    /// 下载单个远程文件到本地
+    Get {
+        profile: String,
+        remote: String,
+        local: PathBuf,
+    },
+    /// 上传单个本地文件到远程
+    Put {
+        profile: String,
+        local: PathBuf,
+        remote: String,
+    },
+    /// 把本地目录单向镜像（只增量上传，不删除远程多出的文件）到远程目录
+    Mirror {
+        profile: String,
+        local_dir: PathBuf,
+        remote_dir: String,
+    },
+}
+
+fn resolve_profile(db: &Database, needle: &str) -> AppResult<Profile> {
+    if let Some(profile) = db.profile_get(needle)? {
+        return Ok(profile);
+    }
+    db.profile_list()?
+        .into_iter()
+        .find(|p| p.name == needle)
+        .ok_or_else(|| AppError::not_found(format!("未找到名为或 ID 为 \"{}\" 的 Profile", needle)))
+}
+
+/// 建立会话；遇到尚未信任过的主机直接报错退出，引导用户先用桌面端确认一次指纹
+fn connect(db: &Database, session_manager: &SessionManager, profile: &Profile) -> AppResult<String> {
+    let timeout_secs = db
+        .settings_load()
+        .map(|s| s.connection_timeout_secs)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+    match session_manager.connect(db, profile, None, None, timeout_secs)? {
+        ConnectStatus::Connected(result) => Ok(result.session_id),
+        ConnectStatus::NeedHostKeyConfirm(pending) => Err(AppError::new(
+            ErrorCode::HostkeyMismatch,
+            format!(
+                "主机 {}:{} 尚未被信任（指纹 {}），请先在 TunnelFiles 桌面端连接一次并确认指纹",
+                pending.host, pending.port, pending.fingerprint
+            ),
+        )
+        .with_retryable(false)),
+    }
+}
+
+fn cmd_ls(db: &Database, session_manager: &SessionManager, profile: &str, path: &str) -> AppResult<()> {
+    let profile = resolve_profile(db, profile)?;
+    let session_id = connect(db, session_manager, &profile)?;
+    let managed = session_manager.get_session(&session_id)?;
+    let sftp = managed.sftp()?;
+
+    let entries = SftpService::list_dir(&sftp, path, None, false)?;
+    for entry in entries {
+        let kind = if entry.is_dir { "d" } else { "-" };
+        println!("{}\t{}\t{}", kind, entry.size.unwrap_or(0), entry.name);
+    }
+    Ok(())
+}
+
+fn cmd_get(
+    db: &Database,
+    session_manager: &SessionManager,
+    profile: &str,
+    remote: &str,
+    local: &Path,
+) -> AppResult<()> {
+    let profile = resolve_profile(db, profile)?;
+    let session_id = connect(db, session_manager, &profile)?;
+    let managed = session_manager.get_session(&session_id)?;
+    let sftp = managed.sftp()?;
+
+    let stat = SftpService::stat(&sftp, remote)?;
+    let total = stat.size.unwrap_or(0);
+
+    let mut file = fs::File::create(local)
+        .map_err(|e| AppError::local_io_error(format!("无法创建本地文件 {}: {}", local.display(), e)))?;
+
+    let mut offset = 0u64;
+    while offset < total {
+        let length = CHUNK_SIZE.min(total - offset);
+        let chunk = SftpService::read_file_chunk(&sftp, remote, offset, length)?;
+        if chunk.is_empty() {
+            break;
+        }
+        file.write_all(&chunk)
+            .map_err(|e| AppError::local_io_error(format!("写入本地文件失败: {}", e)))?;
+        offset += chunk.len() as u64;
+    }
+
+    eprintln!("已下载 {} -> {} ({} 字节)", remote, local.display(), offset);
+    Ok(())
+}
+
+fn cmd_put(
+    db: &Database,
+    session_manager: &SessionManager,
+    profile: &str,
+    local: &Path,
+    remote: &str,
+) -> AppResult<()> {
+    let profile = resolve_profile(db, profile)?;
+    let session_id = connect(db, session_manager, &profile)?;
+    let managed = session_manager.get_session(&session_id)?;
+    let sftp = managed.sftp()?;
+
+    upload_file(&sftp, local, remote)?;
+    eprintln!("已上传 {} -> {}", local.display(), remote);
+    Ok(())
+}
+
+fn upload_file(sftp: &ssh2::Sftp, local: &Path, remote: &str) -> AppResult<()> {
+    let mut file = fs::File::open(local)
+        .map_err(|e| AppError::local_io_error(format!("无法打开本地文件 {}: {}", local.display(), e)))?;
+
+    let mut offset = 0u64;
+    let mut buf = vec![0u8; CHUNK_SIZE as usize];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| AppError::local_io_error(format!("读取本地文件失败: {}", e)))?;
+        if n == 0 {
+            break;
+        }
+        SftpService::write_file_chunk(sftp, remote, offset, &buf[..n], false)?;
+        offset += n as u64;
+    }
+    Ok(())
+}
+
+/// 递归枚举 `dir` 下的全部文件，返回相对 `dir` 的路径（使用 `/` 分隔）
+fn walk_local_dir(dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+
+    while let Some(relative) = stack.pop() {
+        let absolute = dir.join(&relative);
+        let read_dir = fs::read_dir(&absolute)
+            .map_err(|e| AppError::local_io_error(format!("无法读取本地目录 {}: {}", absolute.display(), e)))?;
+
+        for entry in read_dir {
+            let entry = entry
+                .map_err(|e| AppError::local_io_error(format!("读取目录项失败: {}", e)))?;
+            let child_relative = relative.join(entry.file_name());
+            let file_type = entry
+                .file_type()
+                .map_err(|e| AppError::local_io_error(format!("获取文件类型失败: {}", e)))?;
+            if file_type.is_dir() {
+                stack.push(child_relative);
+            } else if file_type.is_file() {
+                files.push(child_relative);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn cmd_mirror(
+    db: &Database,
+    session_manager: &SessionManager,
+    profile: &str,
+    local_dir: &Path,
+    remote_dir: &str,
+) -> AppResult<()> {
+    let profile = resolve_profile(db, profile)?;
+    let session_id = connect(db, session_manager, &profile)?;
+    let managed = session_manager.get_session(&session_id)?;
+    let sftp = managed.sftp()?;
+
+    let files = walk_local_dir(local_dir)?;
+    let remote_dir = remote_dir.trim_end_matches('/');
+
+    let mut created_dirs = std::collections::HashSet::new();
+    let mut uploaded = 0usize;
+
+    for relative in files {
+        if let Some(parent) = relative.parent().filter(|p| !p.as_os_str().is_empty()) {
+            ensure_remote_dir(&sftp, remote_dir, parent, &mut created_dirs)?;
+        }
+
+        let remote_path = format!("{}/{}", remote_dir, relative.to_string_lossy().replace('\\', "/"));
+        let local_path = local_dir.join(&relative);
+        upload_file(&sftp, &local_path, &remote_path)?;
+        uploaded += 1;
+    }
+
+    eprintln!(
+        "镜像完成：{} -> {}，共上传 {} 个文件",
+        local_dir.display(),
+        remote_dir,
+        uploaded
+    );
+    Ok(())
+}
+
+/// 按需逐级创建远程目录（已存在则忽略），`created` 记录本次运行里已经建过的相对路径，
+/// 避免对同一个目录反复调用 `mkdir`
+fn ensure_remote_dir(
+    sftp: &ssh2::Sftp,
+    remote_base: &str,
+    relative_dir: &Path,
+    created: &mut std::collections::HashSet<PathBuf>,
+) -> AppResult<()> {
+    let mut accumulated = PathBuf::new();
+    for component in relative_dir.components() {
+        accumulated.push(component);
+        if created.contains(&accumulated) {
+            continue;
+        }
+
+        let remote_path = format!(
+            "{}/{}",
+            remote_base,
+            accumulated.to_string_lossy().replace('\\', "/")
+        );
+        match SftpService::mkdir(sftp, &remote_path) {
+            Ok(()) => {}
+            Err(e) if e.code == ErrorCode::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+        created.insert(accumulated.clone());
+    }
+    Ok(())
+}
+
+fn run(cli: Cli) -> AppResult<()> {
+    let db = Database::init()?;
+    let session_manager = SessionManager::new();
+
+    match cli.command {
+        Command::Ls { profile, path } => cmd_ls(&db, &session_manager, &profile, &path),
+        Command::Get { profile, remote, local } => cmd_get(&db, &session_manager, &profile, &remote, &local),
+        Command::Put { profile, local, remote } => cmd_put(&db, &session_manager, &profile, &local, &remote),
+        Command::Mirror { profile, local_dir, remote_dir } => {
+            cmd_mirror(&db, &session_manager, &profile, &local_dir, &remote_dir)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {}", e.message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Audit 相关命令
+//!
+//! - audit_run: 对指定会话执行远程安全审计，生成 HTML 报告
+
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::audit::AuditReport;
+use crate::models::error::{AppError, AppResult};
+use crate::services::security_audit::{audit_session, render_and_save_report};
+use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::Database;
+
+/// 对指定 session 跑一轮只读安全审计，生成 HTML 报告并返回报告结果
+/// （包含结构化发现列表）及报告文件路径
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRunResult {
+    pub report: AuditReport,
+    pub report_path: String,
+}
+
+#[tauri::command]
+pub async fn audit_run(
+    db: State<'_, Arc<Database>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    session_id: String,
+) -> AppResult<AuditRunResult> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::info!(session_id = %session_id, "开始远程安全审计");
+
+    let db = (*db).clone();
+    let session_manager = (*session_manager).clone();
+    let sid = session_id.clone();
+
+    let (report, report_path) = tokio::task::spawn_blocking(move || -> AppResult<_> {
+        let managed = session_manager.get_session(&sid)?;
+        let profile_id = managed.profile_id.clone();
+        let sid_for_audit = sid.clone();
+        managed.with_session(move |session| {
+            let report = audit_session(&db, session, &sid_for_audit, &profile_id)?;
+            let path = render_and_save_report(&report)?;
+            Ok((report, path))
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(crate::models::error::ErrorCode::Unknown, format!("安全审计任务失败: {}", e)))??;
+
+    tracing::info!(session_id = %session_id, path = %report_path.display(), "安全审计完成");
+
+    Ok(AuditRunResult {
+        report,
+        report_path: report_path.to_string_lossy().to_string(),
+    })
+}
@@ -0,0 +1,80 @@
+//! 远程命令执行相关命令
+//!
+//! - command_run: 执行一次性远程命令
+//! - command_write_stdin: 向命令写入标准输入
+//! - command_kill: 终止命令
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+
+use crate::models::command::CommandInfo;
+use crate::models::error::{AppError, AppResult};
+use crate::services::command_service::CommandManager;
+use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::Database;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRunInput {
+    pub session_id: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+/// 执行一次性远程命令
+#[tauri::command]
+pub async fn command_run(
+    app: AppHandle,
+    db: State<'_, Arc<Database>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    command_manager: State<'_, Arc<CommandManager>>,
+    input: CommandRunInput,
+) -> AppResult<CommandInfo> {
+    tracing::info!(session_id = %input.session_id, command = %input.command, "执行远程命令");
+
+    command_manager.create_command(
+        app,
+        &db,
+        session_manager.inner().clone(),
+        &input.session_id,
+        &input.command,
+        &input.args,
+        input.cwd.as_deref(),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStdinInput {
+    pub command_id: String,
+    pub data: String, // Base64 编码
+}
+
+/// 向命令写入标准输入
+#[tauri::command]
+pub async fn command_write_stdin(
+    command_manager: State<'_, Arc<CommandManager>>,
+    input: CommandStdinInput,
+) -> AppResult<()> {
+    let data = BASE64
+        .decode(&input.data)
+        .map_err(|e| AppError::invalid_argument(format!("Base64 解码失败: {}", e)))?;
+
+    command_manager.write_stdin(&input.command_id, &data)
+}
+
+/// 终止命令
+#[tauri::command]
+pub async fn command_kill(
+    command_manager: State<'_, Arc<CommandManager>>,
+    command_id: String,
+) -> AppResult<()> {
+    tracing::info!(command_id = %command_id, "终止远程命令");
+    command_manager.kill(&command_id)
+}
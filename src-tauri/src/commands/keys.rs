@@ -0,0 +1,54 @@
+//! 密钥管理相关命令
+//!
+//! - key_generate: 在应用内生成 SSH 密钥对
+//! - key_list: 列出所有托管密钥
+//! - key_export_public: 导出指定托管密钥的公钥
+//! - key_delete: 删除托管密钥
+
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::error::AppResult;
+use crate::models::key::{KeyAlgorithm, ManagedKey};
+use crate::services::key_manager;
+use crate::services::storage_service::Database;
+
+/// 生成密钥对的输入参数
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyGenerateInput {
+    /// 密钥名称，便于在 Profile 里选择
+    pub name: String,
+    pub algorithm: KeyAlgorithm,
+    /// 可选 passphrase，留空则生成未加密私钥
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+/// 生成一对新的 SSH 密钥，私钥托管在系统安全存储中
+#[tauri::command]
+pub async fn key_generate(
+    db: State<'_, Arc<Database>>,
+    input: KeyGenerateInput,
+) -> AppResult<ManagedKey> {
+    tracing::info!(name = %input.name, algorithm = ?input.algorithm, "生成托管密钥");
+    key_manager::create_managed_key(&db, &input.name, input.algorithm, input.passphrase.as_deref())
+}
+
+/// 列出所有托管密钥
+#[tauri::command]
+pub async fn key_list(db: State<'_, Arc<Database>>) -> AppResult<Vec<ManagedKey>> {
+    key_manager::list_managed_keys(&db)
+}
+
+/// 导出指定托管密钥的公钥内容
+#[tauri::command]
+pub async fn key_export_public(db: State<'_, Arc<Database>>, key_id: String) -> AppResult<String> {
+    key_manager::export_public_key(&db, &key_id)
+}
+
+/// 删除托管密钥
+#[tauri::command]
+pub async fn key_delete(db: State<'_, Arc<Database>>, key_id: String) -> AppResult<()> {
+    key_manager::delete_managed_key(&db, &key_id)
+}
@@ -4,16 +4,21 @@
 //! - profile_get: 获取单个连接配置
 //! - profile_upsert: 创建/更新连接配置
 //! - profile_delete: 删除连接配置
+//! - profile_export / profile_import: 导出/导入可跨机迁移的 Profile 备份文件
+//! - profile_import_uri / profile_export_uri: 解析/生成单条 `ssh://` 连接 URI
 
 use std::sync::Arc;
 use tauri::State;
+use tokio::task::spawn_blocking;
 
-use crate::models::error::{AppError, AppResult};
-use crate::models::profile::{Profile, ProfileInput};
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::profile::{Auth, AuthInput, Profile, ProfileInput};
+use crate::services::key_manager;
 use crate::services::security_service::{
-    credential_delete_for_profile, credential_store_passphrase, credential_store_password,
+    credential_delete_for_profile, credential_get, credential_store_passphrase,
+    credential_store_password, credential_store_private_key,
 };
-use crate::services::storage_service::Database;
+use crate::services::storage_service::{Database, ProfileExportEntry, ProfileMergeStrategy};
 
 /// 获取所有连接配置
 #[tauri::command]
@@ -63,60 +68,118 @@ pub async fn profile_upsert(
         (uuid::Uuid::new_v4().to_string(), now)
     };
 
-    // 处理密码存储
-    let password_ref = if input.remember_password {
-        // 检查是否提供了新密码（非空字符串）
-        let has_new_password = input
-            .password
-            .as_ref()
-            .map(|p| !p.is_empty())
-            .unwrap_or(false);
-
-        if has_new_password {
-            // 存储新密码
-            Some(credential_store_password(
-                &profile_id,
-                input.password.as_ref()
-                    .ok_or_else(|| AppError::invalid_argument("Password required when remember_password is set"))?,
-            )?)
-        } else {
-            // 没有新密码，保持现有的 password_ref（更新时不清除）
-            if let Some(ref id) = input.id {
-                db.profile_get(id)?.and_then(|p| p.password_ref)
+    // 按认证方式处理凭据存储；每个变体只处理自己用得上的字段，不再需要像过去
+    // 那样对密码/私钥/passphrase 三组逻辑各自做一次"这个认证方式用不用得上"的判断
+    let auth = match &input.auth {
+        AuthInput::Password {
+            password,
+            remember_password,
+        } => {
+            let password_ref = if *remember_password {
+                // 检查是否提供了新密码（非空字符串）
+                let has_new_password = password.as_ref().map(|p| !p.is_empty()).unwrap_or(false);
+
+                if has_new_password {
+                    // 存储新密码
+                    Some(credential_store_password(
+                        &db,
+                        &profile_id,
+                        password
+                            .as_ref()
+                            .ok_or_else(|| {
+                                AppError::invalid_argument(
+                                    "Password required when remember_password is set",
+                                )
+                            })?
+                            .as_str(),
+                    )?)
+                } else if let Some(ref id) = input.id {
+                    // 没有新密码，保持现有的 password_ref（更新时不清除）
+                    db.profile_get(id)?.and_then(|p| p.auth.password_ref().map(String::from))
+                } else {
+                    None
+                }
             } else {
                 None
-            }
+            };
+
+            Auth::Password { password_ref }
         }
-    } else {
-        None
-    };
+        AuthInput::Key {
+            private_key_path,
+            private_key_content,
+            remember_private_key,
+            managed_key_id,
+            passphrase,
+            remember_passphrase,
+        } => {
+            // 处理 passphrase 存储
+            let passphrase_ref = if *remember_passphrase {
+                let has_new_passphrase =
+                    passphrase.as_ref().map(|p| !p.is_empty()).unwrap_or(false);
 
-    // 处理 passphrase 存储
-    let passphrase_ref = if input.remember_passphrase {
-        // 检查是否提供了新 passphrase（非空字符串）
-        let has_new_passphrase = input
-            .passphrase
-            .as_ref()
-            .map(|p| !p.is_empty())
-            .unwrap_or(false);
-
-        if has_new_passphrase {
-            // 存储新 passphrase
-            Some(credential_store_passphrase(
-                &profile_id,
-                input.passphrase.as_ref()
-                    .ok_or_else(|| AppError::invalid_argument("Passphrase required when remember_passphrase is set"))?,
-            )?)
-        } else {
-            // 没有新 passphrase，保持现有的 passphrase_ref
-            if let Some(ref id) = input.id {
-                db.profile_get(id)?.and_then(|p| p.passphrase_ref)
+                if has_new_passphrase {
+                    Some(credential_store_passphrase(
+                        &db,
+                        &profile_id,
+                        passphrase
+                            .as_ref()
+                            .ok_or_else(|| {
+                                AppError::invalid_argument(
+                                    "Passphrase required when remember_passphrase is set",
+                                )
+                            })?
+                            .as_str(),
+                    )?)
+                } else if let Some(ref id) = input.id {
+                    db.profile_get(id)?.and_then(|p| p.auth.passphrase_ref().map(String::from))
+                } else {
+                    None
+                }
             } else {
                 None
+            };
+
+            // 处理私钥内容存储
+            let private_key_ref = if let Some(key_id) = managed_key_id {
+                // 引用一个应用内托管密钥：直接复用它在安全存储里的引用，不再单独存一份
+                Some(key_manager::managed_key_private_key_ref(&db, key_id)?)
+            } else if *remember_private_key {
+                let has_new_content = private_key_content
+                    .as_ref()
+                    .map(|k| !k.is_empty())
+                    .unwrap_or(false);
+
+                if has_new_content {
+                    Some(credential_store_private_key(
+                        &db,
+                        &profile_id,
+                        private_key_content.as_ref().ok_or_else(|| {
+                            AppError::invalid_argument(
+                                "Private key content required when remember_private_key is set",
+                            )
+                        })?,
+                        passphrase.as_ref().map(|p| p.as_str()),
+                    )?)
+                } else if let Some(ref id) = input.id {
+                    // 没有新私钥内容，保持现有的 private_key_ref（更新时不清除）
+                    db.profile_get(id)?.and_then(|p| p.auth.private_key_ref().map(String::from))
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            Auth::Key {
+                private_key_path: private_key_path.clone(),
+                private_key_ref,
+                managed_key_id: managed_key_id.clone(),
+                passphrase_ref,
             }
         }
-    } else {
-        None
+        AuthInput::Agent => Auth::Agent,
+        AuthInput::KeyboardInteractive => Auth::KeyboardInteractive,
     };
 
     // 构建 Profile
@@ -126,11 +189,11 @@ pub async fn profile_upsert(
         host: input.host,
         port: input.port,
         username: input.username,
-        auth_type: input.auth_type,
-        password_ref,
-        private_key_path: input.private_key_path,
-        passphrase_ref,
+        auth,
         initial_path: input.initial_path,
+        host_key_algorithms: input.host_key_algorithms,
+        kex_algorithms: input.kex_algorithms,
+        ciphers: input.ciphers,
         created_at,
         updated_at: now,
     };
@@ -149,7 +212,7 @@ pub async fn profile_upsert(
 #[tauri::command]
 pub async fn profile_delete(db: State<'_, Arc<Database>>, profile_id: String) -> AppResult<()> {
     // 先删除关联的凭据
-    if let Err(e) = credential_delete_for_profile(&profile_id) {
+    if let Err(e) = credential_delete_for_profile(&db, &profile_id) {
         tracing::warn!(
             profile_id = %profile_id,
             error = %e,
@@ -171,3 +234,231 @@ pub async fn profile_delete(db: State<'_, Arc<Database>>, profile_id: String) ->
 
     Ok(())
 }
+
+/// 导出 Profile 输入
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileExportInput {
+    /// 导出文件写入路径
+    pub path: String,
+    /// 是否一并导出明文凭据（从系统钥匙串解析）
+    pub include_secrets: bool,
+    /// `include_secrets` 为 true 时用于加密整份导出文件的密码
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+/// 导出所有 Profile 为可跨机迁移的 JSON 文件
+///
+/// `include_secrets=false` 时只保留 `password_ref`/`passphrase_ref`/`private_key_ref` 引用
+/// 句柄（明文留在本机系统钥匙串中，换机器后这些引用不可用，需重新设置凭据）；`include_secrets=true`
+/// 时从系统钥匙串解析出明文凭据一并导出，要求提供 `passphrase`，整份文件会用它重新加密
+/// （与 `security_export_backup` 相同的 scrypt + AES-256-GCM 方案）。
+#[tauri::command]
+pub async fn profile_export(
+    db: State<'_, Arc<Database>>,
+    input: ProfileExportInput,
+) -> AppResult<()> {
+    tracing::info!(path = %input.path, include_secrets = input.include_secrets, "导出 Profile");
+
+    if input.include_secrets && input.passphrase.as_deref().unwrap_or_default().is_empty() {
+        return Err(AppError::invalid_argument(
+            "include_secrets 为 true 时必须提供导出密码",
+        ));
+    }
+
+    let profiles = db.profile_list()?;
+    let mut entries = Vec::with_capacity(profiles.len());
+    for p in &profiles {
+        let mut entry = ProfileExportEntry {
+            id: p.id.clone(),
+            name: p.name.clone(),
+            host: p.host.clone(),
+            port: p.port,
+            username: p.username.clone(),
+            auth: p.auth.clone(),
+            initial_path: p.initial_path.clone(),
+            host_key_algorithms: p.host_key_algorithms.clone(),
+            kex_algorithms: p.kex_algorithms.clone(),
+            ciphers: p.ciphers.clone(),
+            created_at: p.created_at,
+            updated_at: p.updated_at,
+            password_plaintext: None,
+            passphrase_plaintext: None,
+            private_key_plaintext: None,
+        };
+
+        if input.include_secrets {
+            if let Some(r) = p.auth.password_ref() {
+                entry.password_plaintext = credential_get(&db, r)?;
+            }
+            if let Some(r) = p.auth.passphrase_ref() {
+                entry.passphrase_plaintext = credential_get(&db, r)?;
+            }
+            if let Some(r) = p.auth.private_key_ref() {
+                entry.private_key_plaintext = credential_get(&db, r)?;
+            }
+        }
+
+        entries.push(entry);
+    }
+
+    let db = db.inner().clone();
+    let path = input.path.clone();
+    let passphrase = input.passphrase.clone();
+    spawn_blocking(move || db.profiles_export_write(&path, entries, passphrase.as_deref()))
+        .await
+        .map_err(|e| {
+            AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e))
+        })??;
+
+    tracing::info!(path = %input.path, profile_count = profiles.len(), "Profile 导出完成");
+
+    Ok(())
+}
+
+/// 导入 Profile 输入
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileImportInput {
+    /// 导出文件路径
+    pub path: String,
+    /// 合并策略
+    pub merge_strategy: ProfileMergeStrategy,
+    /// 文件已加密（`include_secrets=true` 导出）时用于解密的密码
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+/// 从 [`profile_export`] 生成的文件导入 Profile，返回导入的条目数
+///
+/// - `Replace`：先清空本地所有 Profile，再整体写入文件内容
+/// - `Merge`：按 id 合并，已存在的 id 用文件内容覆盖并重新生成 `created_at`/`updated_at`，
+///   不存在的 id 作为新记录插入并保留文件中的时间戳
+///
+/// 文件带有明文凭据时，解密出的明文会写回*本机*系统钥匙串并生成新的 ref（原机器的 ref
+/// 在新机器上未必存在）。
+#[tauri::command]
+pub async fn profile_import(
+    db: State<'_, Arc<Database>>,
+    input: ProfileImportInput,
+) -> AppResult<usize> {
+    tracing::info!(path = %input.path, "导入 Profile");
+
+    let db_clone = db.inner().clone();
+    let path = input.path.clone();
+    let passphrase = input.passphrase.clone();
+    let entries =
+        spawn_blocking(move || db_clone.profiles_import_read(&path, passphrase.as_deref()))
+            .await
+            .map_err(|e| {
+                AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e))
+            })??;
+
+    if matches!(input.merge_strategy, ProfileMergeStrategy::Replace) {
+        db.profile_delete_all()?;
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut imported = 0usize;
+
+    for entry in entries {
+        let existing = if matches!(input.merge_strategy, ProfileMergeStrategy::Merge) {
+            db.profile_get(&entry.id)?
+        } else {
+            None
+        };
+
+        // 明文凭据只在各自对应的 auth 变体下才有意义（密码明文配 Password，
+        // passphrase/私钥明文配 Key），按变体重新组装，而不是像过去那样把三组
+        // 明文无条件套进同一个 Profile——旧写法在 auth_type 与携带的明文不匹配时
+        // 会悄悄丢弃数据，现在这种不匹配在类型上就不可能发生
+        let auth = match entry.auth {
+            Auth::Password { password_ref } => {
+                let password_ref = match entry.password_plaintext {
+                    Some(plaintext) => Some(credential_store_password(&db, &entry.id, &plaintext)?),
+                    None => password_ref,
+                };
+                Auth::Password { password_ref }
+            }
+            Auth::Key {
+                private_key_path,
+                private_key_ref,
+                managed_key_id,
+                passphrase_ref,
+            } => {
+                let passphrase_ref = match entry.passphrase_plaintext {
+                    Some(plaintext) => {
+                        Some(credential_store_passphrase(&db, &entry.id, &plaintext)?)
+                    }
+                    None => passphrase_ref,
+                };
+                let private_key_ref = match entry.private_key_plaintext {
+                    Some(plaintext) => {
+                        Some(credential_store_private_key(&db, &entry.id, &plaintext, None)?)
+                    }
+                    None => private_key_ref,
+                };
+                Auth::Key {
+                    private_key_path,
+                    private_key_ref,
+                    managed_key_id,
+                    passphrase_ref,
+                }
+            }
+            other => other,
+        };
+
+        let (created_at, updated_at) = if existing.is_some() {
+            (now, now)
+        } else {
+            (entry.created_at, entry.updated_at)
+        };
+
+        let profile = Profile {
+            id: entry.id,
+            name: entry.name,
+            host: entry.host,
+            port: entry.port,
+            username: entry.username,
+            auth,
+            initial_path: entry.initial_path,
+            host_key_algorithms: entry.host_key_algorithms,
+            kex_algorithms: entry.kex_algorithms,
+            ciphers: entry.ciphers,
+            created_at,
+            updated_at,
+        };
+        db.profile_upsert(&profile)?;
+        imported += 1;
+    }
+
+    tracing::info!(path = %input.path, imported, "Profile 导入完成");
+
+    Ok(imported)
+}
+
+/// 解析一条 `ssh://user@host[:port][/path]` 连接 URI，返回校验通过的 [`ProfileInput`]
+///
+/// 不会直接保存，调用方应在表单中展示解析结果，让用户确认/补充认证方式后再调用
+/// `profile_upsert`
+#[tauri::command]
+pub async fn profile_import_uri(uri: String) -> AppResult<ProfileInput> {
+    let input: ProfileInput = uri
+        .parse()
+        .map_err(|msg: String| AppError::invalid_argument(msg))?;
+    input
+        .validate()
+        .map_err(|msg| AppError::new(ErrorCode::InvalidArgument, msg))?;
+    Ok(input)
+}
+
+/// 将已保存的 Profile 序列化为 `ssh://user@host[:port][/path]` 连接 URI，从不包含
+/// 密码/私钥等机密材料
+#[tauri::command]
+pub async fn profile_export_uri(db: State<'_, Arc<Database>>, id: String) -> AppResult<String> {
+    let profile = db
+        .profile_get(&id)?
+        .ok_or_else(|| AppError::not_found(format!("Profile {} 不存在", id)))?;
+    Ok(profile.to_string())
+}
@@ -0,0 +1,80 @@
+//! 目录同步计划相关命令
+//!
+//! - schedule_create: 新建计划
+//! - schedule_list: 列出所有计划
+//! - schedule_delete: 删除计划
+//! - schedule_set_enabled: 启用/禁用计划
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::State;
+
+use crate::models::error::AppResult;
+use crate::models::schedule::{ScheduleRecurrence, SyncSchedule};
+use crate::models::transfer_task::TransferDirection;
+use crate::services::schedule_service::ScheduleManager;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleCreateInput {
+    pub session_id: String,
+    pub local_dir: String,
+    pub remote_dir: String,
+    pub direction: TransferDirection,
+    #[serde(default)]
+    pub mirror: bool,
+    pub recurrence: ScheduleRecurrence,
+}
+
+/// 新建目录同步计划
+#[tauri::command]
+pub async fn schedule_create(
+    schedule_manager: State<'_, Arc<ScheduleManager>>,
+    input: ScheduleCreateInput,
+) -> AppResult<SyncSchedule> {
+    tracing::info!(
+        session_id = %input.session_id,
+        local_dir = %input.local_dir,
+        remote_dir = %input.remote_dir,
+        "新建同步计划"
+    );
+
+    schedule_manager.create_schedule(
+        input.session_id,
+        input.local_dir,
+        input.remote_dir,
+        input.direction,
+        input.mirror,
+        input.recurrence,
+    )
+}
+
+/// 列出所有同步计划
+#[tauri::command]
+pub async fn schedule_list(
+    schedule_manager: State<'_, Arc<ScheduleManager>>,
+) -> AppResult<Vec<SyncSchedule>> {
+    schedule_manager.list_schedules()
+}
+
+/// 删除同步计划
+#[tauri::command]
+pub async fn schedule_delete(
+    schedule_manager: State<'_, Arc<ScheduleManager>>,
+    schedule_id: String,
+) -> AppResult<()> {
+    tracing::info!(schedule_id = %schedule_id, "删除同步计划");
+    schedule_manager.delete_schedule(&schedule_id)
+}
+
+/// 启用/禁用同步计划
+#[tauri::command]
+pub async fn schedule_set_enabled(
+    schedule_manager: State<'_, Arc<ScheduleManager>>,
+    schedule_id: String,
+    enabled: bool,
+) -> AppResult<()> {
+    tracing::info!(schedule_id = %schedule_id, enabled, "更新同步计划启用状态");
+    schedule_manager.set_enabled(&schedule_id, enabled)
+}
@@ -0,0 +1,41 @@
+//! Search 相关命令
+//!
+//! - search_start: 发起远程搜索
+//! - search_cancel: 取消搜索
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+
+use crate::models::error::{AppError, AppResult};
+use crate::models::search::SearchQuery;
+use crate::services::search_service::SearchManager;
+use crate::services::session_manager::SessionManager;
+
+/// 发起一次远程搜索，立即返回 search_id，结果通过 `search:result`/`search:status` 事件推送
+#[tauri::command]
+pub async fn search_start(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    search_manager: State<'_, Arc<SearchManager>>,
+    session_id: String,
+    query: SearchQuery,
+) -> AppResult<String> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::info!(session_id = %session_id, root = %query.root_path, "发起远程搜索");
+
+    search_manager.start_search(app, session_manager.inner().clone(), session_id, query)
+}
+
+/// 取消搜索
+#[tauri::command]
+pub async fn search_cancel(
+    search_manager: State<'_, Arc<SearchManager>>,
+    search_id: String,
+) -> AppResult<()> {
+    tracing::info!(search_id = %search_id, "取消搜索");
+    search_manager.cancel_search(&search_id)
+}
@@ -2,12 +2,22 @@
 //!
 //! - security_trust_hostkey: 信任服务器指纹
 //! - security_remove_hostkey: 移除信任的指纹
+//! - security_check_hostkey: 返回结构化的 HostKeyVerdict（而不是裸 `Option<String>`）
+//! - security_export_backup / security_import_backup: 加密备份与恢复
+//! - security_import_openssh_preview / security_import_openssh_apply: 导入 OpenSSH 配置
+//! - security_validate_private_key: 校验 OpenSSH 私钥文件与 passphrase
+//! - security_known_hosts_import / security_known_hosts_export: 与标准 OpenSSH known_hosts 文件互导
+//! - security_vault_backup_shares / security_vault_recover_from_shares: 密钥库主密钥的 Shamir 备份与恢复
 
 use std::sync::Arc;
 use tauri::State;
+use tokio::task::spawn_blocking;
 
-use crate::models::error::AppResult;
-use crate::services::security_service::trust_hostkey;
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::sensitive::Sensitive;
+use crate::services::key_service::{self, KeyInfo};
+use crate::services::security_service::{host_key_verdict, trust_hostkey, HostKeyVerdict};
+use crate::services::storage_service::import::{ImportedKnownHost, ImportedProfile, ImportSummary};
 use crate::services::storage_service::Database;
 
 /// 信任 HostKey 输入
@@ -17,7 +27,11 @@ pub struct TrustHostKeyInput {
     pub host: String,
     pub port: u16,
     pub key_type: String,
-    pub fingerprint: String,
+    /// 密钥指纹；用 [`Sensitive`] 包裹，避免这个输入结构体被意外 `{:?}` 或打到日志里
+    pub fingerprint: Sensitive<String>,
+    /// 原始公钥（base64），首次连接确认时由前端透传给镜像模式/`known_hosts_export` 使用
+    #[serde(default)]
+    pub public_key_b64: Option<Sensitive<String>>,
 }
 
 /// 信任服务器 HostKey
@@ -40,7 +54,8 @@ pub async fn security_trust_hostkey(
         &input.host,
         input.port,
         &input.key_type,
-        &input.fingerprint,
+        input.fingerprint.as_str(),
+        input.public_key_b64.as_ref().map(|k| k.as_str()),
     )?;
 
     Ok(())
@@ -62,13 +77,189 @@ pub async fn security_remove_hostkey(
     Ok(removed)
 }
 
-/// 检查 HostKey 是否已信任
+/// 检查服务器出示的 HostKey 相对于 known_hosts 的信任状态
+///
+/// 返回结构化的 [`HostKeyVerdict`] 而不是裸 `Option<String>`——调用方不需要再猜测
+/// "没有记录"和"记录了但已经变了"两种完全不同的情况，`Mismatch` 可以被 UI 单独
+/// 渲染为强警告
 #[tauri::command]
 pub async fn security_check_hostkey(
     db: State<'_, Arc<Database>>,
     host: String,
     port: u16,
-) -> AppResult<Option<String>> {
-    let fingerprint = db.known_host_check(&host, port)?;
-    Ok(fingerprint)
+    key_type: String,
+    fingerprint: Sensitive<String>,
+) -> AppResult<HostKeyVerdict> {
+    host_key_verdict(&db, &host, port, &key_type, fingerprint.as_str())
+}
+
+/// 导出 profiles/known_hosts/settings 为加密备份归档（JSON 字符串，可直接写入文件）
+///
+/// scrypt 密钥派生较耗时，放到阻塞线程池中执行，避免占用异步运行时
+#[tauri::command]
+pub async fn security_export_backup(
+    db: State<'_, Arc<Database>>,
+    passphrase: String,
+) -> AppResult<String> {
+    tracing::info!("导出加密备份");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || db.export_encrypted(&passphrase))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 从加密备份归档恢复 profiles/known_hosts/settings
+#[tauri::command]
+pub async fn security_import_backup(
+    db: State<'_, Arc<Database>>,
+    archive: String,
+    passphrase: String,
+) -> AppResult<()> {
+    tracing::info!("导入加密备份");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || db.import_encrypted(&archive, &passphrase))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 解析 OpenSSH `config`/`known_hosts` 生成导入预览，供前端展示勾选列表，不写入数据库
+///
+/// 不传路径时分别默认为 `~/.ssh/config`、`~/.ssh/known_hosts`
+#[tauri::command]
+pub async fn security_import_openssh_preview(
+    db: State<'_, Arc<Database>>,
+    ssh_config_path: Option<String>,
+    known_hosts_path: Option<String>,
+) -> AppResult<ImportSummary> {
+    tracing::info!("解析 OpenSSH 配置以生成导入预览");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || {
+        db.import_openssh_preview(ssh_config_path.as_deref(), known_hosts_path.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 落库用户在导入预览中勾选确认的 profiles/known_hosts
+#[tauri::command]
+pub async fn security_import_openssh_apply(
+    db: State<'_, Arc<Database>>,
+    profiles: Vec<ImportedProfile>,
+    known_hosts: Vec<ImportedKnownHost>,
+) -> AppResult<()> {
+    tracing::info!(
+        profile_count = profiles.len(),
+        known_host_count = known_hosts.len(),
+        "导入 OpenSSH 配置"
+    );
+
+    db.import_openssh_apply(&profiles, &known_hosts)
+}
+
+/// 从标准 OpenSSH `known_hosts` 文件批量导入，返回导入的条目数（受信 + 撤销）
+///
+/// 不经过 `security_import_openssh_preview` 的勾选环节，直接全量导入；
+/// `@revoked` 行会落库为拒绝条目，之后连接会被直接拒绝，而不是当作普通信任记录
+#[tauri::command]
+pub async fn security_known_hosts_import(
+    db: State<'_, Arc<Database>>,
+    path: String,
+) -> AppResult<usize> {
+    tracing::info!(path = %path, "从 known_hosts 文件批量导入");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || db.known_hosts_import(&path))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 将已信任的 HostKey 导出为标准 OpenSSH `known_hosts` 文件，返回导出的条目数
+///
+/// 早期版本遗留的仅有指纹、没有原始公钥的记录无法重建出合法的 key 行，会被跳过
+#[tauri::command]
+pub async fn security_known_hosts_export(
+    db: State<'_, Arc<Database>>,
+    path: String,
+) -> AppResult<usize> {
+    tracing::info!(path = %path, "导出 known_hosts 文件");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || db.known_hosts_export(&path))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 校验私钥输入
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatePrivateKeyInput {
+    /// 私钥文件路径
+    pub path: String,
+    /// passphrase（未提供时仅判断是否需要 passphrase，不尝试解密）
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+/// 校验 OpenSSH 私钥文件，在 Profile 保存/发起连接前供前端预检查
+///
+/// 解密计算（bcrypt_pbkdf）较耗时，放到阻塞线程池中执行
+#[tauri::command]
+pub async fn security_validate_private_key(
+    input: ValidatePrivateKeyInput,
+) -> AppResult<KeyInfo> {
+    tracing::debug!(path = %input.path, "校验私钥文件");
+
+    spawn_blocking(move || {
+        key_service::validate_private_key(
+            std::path::Path::new(&input.path),
+            input.passphrase.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 密钥库 Shamir 备份份额生成输入
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultBackupSharesInput {
+    /// 恢复时需要凑齐的最少份额数
+    pub threshold: u8,
+    /// 当次生成的份额总数
+    pub total: u8,
+}
+
+/// 把已解锁的密钥库主密钥拆分成 `total` 份 Shamir 份额，凑齐任意 `threshold` 份即可恢复
+///
+/// 返回的每个字符串都可以直接复制保存或生成二维码；丢失部分份额（只要剩余数量
+/// 不低于 `threshold`）不影响恢复，但任何一份泄露出去的份额都不会单独泄露密钥
+#[tauri::command]
+pub async fn security_vault_backup_shares(
+    db: State<'_, Arc<Database>>,
+    input: VaultBackupSharesInput,
+) -> AppResult<Vec<String>> {
+    tracing::info!(
+        threshold = input.threshold,
+        total = input.total,
+        "生成密钥库 Shamir 备份份额"
+    );
+
+    db.vault_backup_shares(input.threshold, input.total)
+}
+
+/// 用一组 Shamir 份额重建密钥库主密钥并解锁，无需原始主密码
+///
+/// 供用户更换设备、忘记主密码时使用；份额数量不足、下标重复或被篡改时返回错误，
+/// 不会把错误密钥误判为解锁成功
+#[tauri::command]
+pub async fn security_vault_recover_from_shares(
+    db: State<'_, Arc<Database>>,
+    shares: Vec<String>,
+) -> AppResult<()> {
+    tracing::info!(share_count = shares.len(), "尝试通过 Shamir 份额恢复密钥库");
+
+    db.vault_recover_from_shares(&shares)
 }
@@ -3,6 +3,9 @@
 //! - session_connect: 连接到服务器
 //! - session_disconnect: 断开连接
 //! - session_connect_after_trust: HostKey 确认后继续连接
+//! - session_connect_after_interactive: keyboard-interactive 质询作答后继续连接
+//! - session_list_agent_identities: 列出本机 SSH agent 当前持有的身份
+//! - session_set_limits: 调整会话池容量上限（全局 / 单 Profile），立即生效
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -10,9 +13,11 @@ use tauri::{AppHandle, Emitter, State};
 
 use crate::models::error::{AppError, AppResult};
 use crate::models::profile::RecentConnection;
-use crate::services::session_manager::{ConnectStatus, SessionManager};
+use crate::services::session_manager::{ConnectStatus, SessionManager, SessionManagerConfig, SshFamily};
+use crate::services::sftp_service::WatcherState;
 use crate::services::storage_service::Database;
 use crate::services::terminal_manager::TerminalManager;
+use crate::services::watch_service::WatchManager;
 
 /// 连接输入参数
 #[derive(Debug, Deserialize)]
@@ -41,8 +46,34 @@ pub struct SessionConnectResult {
     /// 服务器指纹
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_fingerprint: Option<String>,
+    /// 探测到的远程主机操作系统族（"unix" / "windows"），供前端渲染路径分隔符
+    /// 和引用规则
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_family: Option<String>,
+    /// 服务器原始公钥（base64），仅首次连接待确认时返回；前端确认信任时原样回传
+    /// 给 `security_trust_hostkey`，供镜像模式/`known_hosts_export` 使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_public_key: Option<String>,
     /// 是否需要确认 HostKey
     pub need_host_key_confirm: bool,
+    /// 是否需要用户回答 keyboard-interactive 质询
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub need_interactive_response: bool,
+    /// 服务器下发的 keyboard-interactive 说明文字（可能为空字符串）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interactive_instructions: Option<String>,
+    /// keyboard-interactive 待回答的提示列表，前端确认作答后原样回传对应顺序的
+    /// `responses` 给 `session_connect_after_interactive`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interactive_prompts: Option<Vec<InteractivePromptPayload>>,
+}
+
+/// keyboard-interactive 的一条提示（供前端渲染输入框）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractivePromptPayload {
+    pub label: String,
+    pub echo: bool,
 }
 
 /// 会话状态事件 payload
@@ -50,7 +81,7 @@ pub struct SessionConnectResult {
 #[serde(rename_all = "camelCase")]
 pub struct SessionStatusPayload {
     pub session_id: String,
-    pub status: String, // "connected" | "disconnected" | "error"
+    pub status: String, // "connected" | "disconnected" | "reconnecting" | "reconnected" | "lost" | "error"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
@@ -81,6 +112,7 @@ fn record_recent_connection(db: &Database, profile: &Profile) {
         host: profile.host.clone(),
         username: profile.username.clone(),
         connected_at: chrono::Utc::now().timestamp_millis(),
+        visit_count: 1,
     };
     if let Err(e) = db.recent_connection_add(&recent) {
         tracing::warn!(error = %e, "记录最近连接失败");
@@ -101,7 +133,19 @@ fn build_connected_result(result: ConnectResult) -> SessionConnectResult {
         session_id: Some(result.session_id),
         home_path: Some(result.home_path),
         server_fingerprint: Some(result.fingerprint),
+        remote_family: Some(family_str(result.family).to_string()),
+        server_public_key: None,
         need_host_key_confirm: false,
+        need_interactive_response: false,
+        interactive_instructions: None,
+        interactive_prompts: None,
+    }
+}
+
+fn family_str(family: SshFamily) -> &'static str {
+    match family {
+        SshFamily::Unix => "unix",
+        SshFamily::Windows => "windows",
     }
 }
 
@@ -115,7 +159,7 @@ fn build_connected_result(result: ConnectResult) -> SessionConnectResult {
 ///    - 首次连接：返回 need_host_key_confirm=true，前端弹窗确认
 ///    - 已信任：继续
 ///    - 不匹配：返回错误
-/// 5. 认证（密码/Key）
+/// 5. 认证（密码/Key/SSH agent）
 /// 6. 创建 SFTP Channel
 /// 7. 返回 session_id
 #[tauri::command]
@@ -161,7 +205,35 @@ pub async fn session_connect(
                 session_id: None,
                 home_path: None,
                 server_fingerprint: Some(pending.fingerprint),
+                remote_family: None,
+                server_public_key: Some(pending.public_key_b64),
                 need_host_key_confirm: true,
+                need_interactive_response: false,
+                interactive_instructions: None,
+                interactive_prompts: None,
+            })
+        }
+        ConnectStatus::NeedInteractiveResponse(pending) => {
+            tracing::info!(profile_id = %input.profile_id, prompt_count = pending.prompts.len(), "keyboard-interactive 需要用户作答");
+            Ok(SessionConnectResult {
+                session_id: None,
+                home_path: None,
+                server_fingerprint: None,
+                remote_family: None,
+                server_public_key: None,
+                need_host_key_confirm: false,
+                need_interactive_response: true,
+                interactive_instructions: Some(pending.instructions),
+                interactive_prompts: Some(
+                    pending
+                        .prompts
+                        .into_iter()
+                        .map(|p| InteractivePromptPayload {
+                            label: p.label,
+                            echo: p.echo,
+                        })
+                        .collect(),
+                ),
             })
         }
         ConnectStatus::Connected(result) => {
@@ -189,6 +261,7 @@ pub async fn session_connect_after_trust(
     let (profile, timeout_secs) = prepare_connection(&db, &input.profile_id)?;
 
     // 执行连接
+    let db_clone = (*db).clone();
     let profile_clone = profile.clone();
     let password = input.password.clone();
     let passphrase = input.passphrase.clone();
@@ -196,6 +269,7 @@ pub async fn session_connect_after_trust(
 
     let result = tokio::task::spawn_blocking(move || {
         session_manager_clone.connect_after_trust(
+            &db_clone,
             &profile_clone,
             password.as_deref(),
             passphrase.as_deref(),
@@ -215,6 +289,52 @@ pub async fn session_connect_after_trust(
     Ok(build_connected_result(result))
 }
 
+/// keyboard-interactive 作答输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractiveConnectInput {
+    /// Profile ID
+    pub profile_id: String,
+    /// 按服务器提示原始顺序填写的答案
+    pub responses: Vec<String>,
+}
+
+/// keyboard-interactive 质询作答后继续连接
+///
+/// 前端在 `session_connect` 返回 `need_interactive_response: true` 后展示
+/// `interactive_prompts` 收集用户输入，再调用此命令把答案喂回去
+#[tauri::command]
+pub async fn session_connect_after_interactive(
+    app: AppHandle,
+    db: State<'_, Arc<Database>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: InteractiveConnectInput,
+) -> AppResult<SessionConnectResult> {
+    tracing::info!(profile_id = %input.profile_id, "keyboard-interactive 已作答，继续连接");
+
+    let (profile, timeout_secs) = prepare_connection(&db, &input.profile_id)?;
+
+    let db_clone = (*db).clone();
+    let profile_clone = profile.clone();
+    let responses = input.responses.clone();
+    let session_manager_clone = (*session_manager).clone();
+
+    let result = tokio::task::spawn_blocking(move || {
+        session_manager_clone.connect_after_interactive(&db_clone, &profile_clone, responses, timeout_secs)
+    })
+    .await
+    .map_err(|e| {
+        AppError::new(
+            crate::models::error::ErrorCode::Unknown,
+            format!("连接任务失败: {}", e),
+        )
+    })??;
+
+    finalize_connection(&app, &db, &profile, &result);
+    tracing::info!(session_id = %result.session_id, profile_id = %input.profile_id, "连接成功（keyboard-interactive 已作答）");
+    Ok(build_connected_result(result))
+}
+
 /// 断开连接
 ///
 /// 关闭 SSH 会话，释放资源（同时清理关联的终端）
@@ -223,6 +343,8 @@ pub async fn session_disconnect(
     app: AppHandle,
     session_manager: State<'_, Arc<SessionManager>>,
     terminal_manager: State<'_, Arc<TerminalManager>>,
+    watch_manager: State<'_, Arc<WatchManager>>,
+    watcher_state: State<'_, Arc<WatcherState>>,
     session_id: String,
 ) -> AppResult<()> {
     tracing::info!(session_id = %session_id, "断开连接");
@@ -232,6 +354,10 @@ pub async fn session_disconnect(
         tracing::warn!(session_id = %session_id, error = %e, "清理关联终端失败");
     }
 
+    // 停止关联的目录监视器
+    watch_manager.unwatch_by_session(&session_id);
+    watcher_state.unwatch_by_session(&session_id);
+
     session_manager.close_session(&session_id)?;
 
     // 发送断开事件
@@ -278,3 +404,78 @@ pub async fn session_list(
 ) -> AppResult<Vec<String>> {
     session_manager.list_sessions()
 }
+
+/// SSH agent 中的一个身份
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentIdentityInfo {
+    /// 身份备注（通常是 key 的 comment，如 user@host）
+    pub comment: String,
+    /// 公钥原始内容（base64），供 UI 展示指纹或与已知 profile 比对
+    pub public_key_b64: String,
+}
+
+/// 列出本机 SSH agent 当前持有的身份
+///
+/// 不依赖任何 Profile，只连接本机 agent（Unix `SSH_AUTH_SOCK`/Windows Pageant），
+/// 供用户在选择 Agent 认证方式、发起连接前确认 agent 里有哪些 key
+#[tauri::command]
+pub async fn session_list_agent_identities(
+    session_manager: State<'_, Arc<SessionManager>>,
+) -> AppResult<Vec<AgentIdentityInfo>> {
+    let session_manager_clone = (*session_manager).clone();
+
+    let identities = tokio::task::spawn_blocking(move || session_manager_clone.list_agent_identities())
+        .await
+        .map_err(|e| {
+            AppError::new(
+                crate::models::error::ErrorCode::Unknown,
+                format!("列出 SSH agent 身份任务失败: {}", e),
+            )
+        })??;
+
+    Ok(identities
+        .into_iter()
+        .map(|identity| AgentIdentityInfo {
+            comment: identity.comment,
+            public_key_b64: identity.public_key_b64,
+        })
+        .collect())
+}
+
+/// 会话池容量上限配置输入，字段语义见 [`SessionManagerConfig`]
+///
+/// 运行期立即生效，不落库——重启应用后恢复默认的"不限制"，避免用户临时调高
+/// 上限排查问题后忘记改回来，反而长期弱化了限制
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLimitsInput {
+    #[serde(default)]
+    pub max_sessions: Option<u32>,
+    #[serde(default)]
+    pub max_sessions_per_profile: Option<u32>,
+    #[serde(default)]
+    pub evict_lru_on_limit: bool,
+}
+
+/// 调整会话池容量上限
+#[tauri::command]
+pub async fn session_set_limits(
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: SessionLimitsInput,
+) -> AppResult<()> {
+    tracing::info!(
+        max_sessions = ?input.max_sessions,
+        max_sessions_per_profile = ?input.max_sessions_per_profile,
+        evict_lru_on_limit = input.evict_lru_on_limit,
+        "会话池容量上限已更新"
+    );
+
+    session_manager.set_config(SessionManagerConfig {
+        max_sessions: input.max_sessions,
+        max_sessions_per_profile: input.max_sessions_per_profile,
+        evict_lru_on_limit: input.evict_lru_on_limit,
+    });
+
+    Ok(())
+}
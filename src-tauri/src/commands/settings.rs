@@ -1,45 +1,131 @@
 //! Settings 相关命令
 //!
-//! - settings_get: 获取设置
+//! - settings_get: 获取分层合并后的生效设置及字段来源
 //! - settings_set: 更新设置
 //! - export_diagnostics: 导出诊断包
+//! - system_stats: 获取运行时指标当前快照与滚动历史
 
 use std::sync::Arc;
+use serde::Serialize;
 use tauri::State;
 
 use crate::models::error::{AppError, AppResult};
-use crate::models::settings::{Settings, SettingsPatch};
+use crate::models::settings::{EffectiveSettings, Settings, SettingsPatch};
+use crate::services::config_loader::ConfigLoader;
+use crate::services::session_manager::SessionManager;
 use crate::services::storage_service::Database;
-use crate::utils::logging::export_diagnostic_package;
+use crate::services::system_monitor::{SystemMonitor, SystemSnapshot};
+use crate::services::terminal_manager::TerminalManager;
+use crate::services::transfer_manager::TransferManager;
+use crate::utils::logging::{export_diagnostic_package, reload_log_level, LogReloadHandle};
 
-/// 获取当前设置
+/// 获取当前生效设置
+///
+/// 按 `Settings::default()` -> `tunnelfiles.toml` -> `TUNNELFILES_*` 环境变量 ->
+/// 数据库覆盖的优先级分层合并，返回值附带每个字段的来源归属，供 UI 提示
+/// "此项由部署文件/环境变量锁定"
 #[tauri::command]
-pub async fn settings_get(db: State<'_, Arc<Database>>) -> AppResult<Settings> {
-    tracing::debug!("获取设置");
-    db.settings_load()
+pub async fn settings_get(db: State<'_, Arc<Database>>) -> AppResult<EffectiveSettings> {
+    tracing::debug!("获取生效设置");
+    ConfigLoader::load(&db)
 }
 
 /// 更新设置
 ///
-/// 接受部分更新（patch），只更新提供的字段
+/// 接受部分更新（patch），只更新提供的字段。`patch.log_level` 非空时会同步热替换
+/// 当前生效的 tracing 过滤器，修改日志级别不再需要重启应用
 #[tauri::command]
 pub async fn settings_set(
     db: State<'_, Arc<Database>>,
+    log_reload: State<'_, LogReloadHandle>,
     patch: SettingsPatch,
 ) -> AppResult<Settings> {
     tracing::debug!("更新设置");
-    db.settings_update(&patch)
+    let updated = db.settings_update(&patch)?;
+
+    if let Some(ref level) = patch.log_level {
+        reload_log_level(&log_reload, level.to_tracing_level());
+    }
+
+    Ok(updated)
+}
+
+/// 运行时指标快照 + 滚动历史
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemStats {
+    pub current: SystemSnapshot,
+    pub history: Vec<SystemSnapshot>,
+}
+
+/// 采样一次运行时指标（CPU/内存/会话数/终端数/传输吞吐等）
+///
+/// 涉及 `sysinfo` 系统调用，放到 `spawn_blocking` 中执行，避免阻塞 async 运行时
+async fn sample_system_stats(
+    system_monitor: &Arc<SystemMonitor>,
+    session_manager: &Arc<SessionManager>,
+    terminal_manager: &Arc<TerminalManager>,
+    transfer_manager: &Arc<TransferManager>,
+) -> AppResult<SystemStats> {
+    let transfer_tasks = transfer_manager.list_tasks().await;
+
+    let system_monitor = system_monitor.clone();
+    let session_manager = session_manager.clone();
+    let terminal_manager = terminal_manager.clone();
+    let current = tokio::task::spawn_blocking(move || {
+        system_monitor.snapshot(&session_manager, &terminal_manager, &transfer_tasks)
+    })
+    .await
+    .map_err(|e| AppError::new(crate::models::error::ErrorCode::Unknown, format!("采样运行时指标任务失败: {}", e)))?;
+
+    Ok(SystemStats { current, history: vec![] })
+}
+
+/// 获取运行时指标当前快照与滚动历史
+///
+/// 供设置页的"运行时状态"面板展示 CPU/内存/会话数等趋势，不依赖诊断包导出
+#[tauri::command]
+pub async fn system_stats(
+    system_monitor: State<'_, Arc<SystemMonitor>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+) -> AppResult<SystemStats> {
+    let mut stats = sample_system_stats(
+        &system_monitor,
+        &session_manager,
+        &terminal_manager,
+        &transfer_manager,
+    )
+    .await?;
+    stats.history = system_monitor.history();
+    Ok(stats)
 }
 
 /// 导出诊断包
 ///
-/// 打包日志文件和配置摘要（脱敏）为 zip 文件
-/// 返回生成的文件路径
+/// 打包日志文件和配置摘要（脱敏）为 zip 文件，附带一份运行时指标快照
+/// （`metrics.json`）。返回生成的文件路径
 #[tauri::command]
-pub async fn export_diagnostics() -> AppResult<String> {
+pub async fn export_diagnostics(
+    system_monitor: State<'_, Arc<SystemMonitor>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+) -> AppResult<String> {
     tracing::info!("开始导出诊断包");
 
-    let path = export_diagnostic_package()
+    let mut stats = sample_system_stats(
+        &system_monitor,
+        &session_manager,
+        &terminal_manager,
+        &transfer_manager,
+    )
+    .await?;
+    stats.history = system_monitor.history();
+    let metrics_json = serde_json::to_string_pretty(&stats).ok();
+
+    let path = export_diagnostic_package(metrics_json.as_deref())
         .map_err(|e| AppError::local_io_error(format!("导出诊断包失败: {}", e)))?;
 
     let path_str = path.to_string_lossy().to_string();
@@ -2,25 +2,45 @@
 //!
 //! - sftp_list_dir: 列出目录内容
 //! - sftp_stat: 获取文件信息
+//! - sftp_readlink / sftp_symlink: 读取/创建符号链接
+//! - sftp_canonicalize: 解析路径在远程文件系统上的真实绝对路径（结合符号链接解析）
 //! - sftp_mkdir: 创建目录
 //! - sftp_rename: 重命名/移动
 //! - sftp_delete: 删除
 //! - sftp_chmod: 修改权限
+//! - sftp_chmod_recursive: 递归修改权限，支持符号权限表达式
 //! - sftp_get_dir_stats: 获取目录统计信息
+//! - sftp_compute_directory_stats_parallel: 用多条并发 SFTP 连接统计大目录，定时上报进度
+//! - sftp_find_duplicate_files: 按大小分桶再按内容哈希查找子树下的重复文件
 //! - sftp_delete_recursive: 递归删除目录
+//! - sftp_watch: 监视远程路径，轮询检测变更
+//! - sftp_unwatch: 停止监视
+//! - sftp_read_file / sftp_write_file: 分块读写文件内容，支持断点续传
+//! - sftp_copy_recursive: 递归复制文件或目录到新的远程路径
+//! - sftp_cancel_operation: 取消一个仍在进行中的递归删除/复制操作
+//! - sftp_download_archive: 将远程目录/文件打包为本地 tar/zip 归档并下载
+//! - sftp_sync_recursive: 将一个远程目录增量/全量镜像到另一个远程目录
+//!
+//! sftp_list_dir / sftp_get_dir_stats / sftp_delete_recursive 都支持传入 gitignore
+//! 风格的 exclude_patterns（见 [`crate::services::exclude_matcher::ExcludeMatcher`]）
+//! 过滤/跳过匹配到的条目
 //!
 //! 所有 SFTP 操作都使用 spawn_blocking 避免阻塞 Tokio 运行时
 
 use std::sync::Arc;
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, State};
 use tokio::task::spawn_blocking;
 
-use crate::models::error::AppError;
+use crate::models::error::{AppError, ErrorCode};
 use crate::models::file_entry::{FileEntry, SortSpec};
+use crate::services::exclude_matcher::ExcludeMatcher;
+use crate::services::operation_registry::OperationRegistry;
 use crate::services::session_manager::SessionManager;
-use crate::services::sftp_service::SftpService;
+use crate::services::sftp_service::{SftpService, SymlinkMode, WatcherState, SFTP_CHUNK_SIZE};
+use crate::services::storage_service::Database;
 
 /// 目录统计信息（用于删除确认对话框）
 #[derive(Debug, Clone, Serialize)]
@@ -32,6 +52,80 @@ pub struct DirectoryStats {
     pub dir_count: u64,
     /// 总大小（字节）
     pub total_size: u64,
+    /// `follow_symlinks` 为 true 时遇到的失效符号链接；为 false（默认跳过符号链接）
+    /// 时恒为空
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symlink_issues: Vec<SymlinkIssue>,
+}
+
+/// 递归遍历时发现的一条失效符号链接
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymlinkIssue {
+    /// 链接自身的路径
+    pub path: String,
+    /// 链接的原始目标文本（`readlink` 返回值），解析失败时为空
+    pub target: Option<String>,
+    pub kind: SymlinkIssueKind,
+}
+
+/// [`SymlinkIssue`] 的失效原因
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkIssueKind {
+    /// 跳转次数耗尽（`MAX_SYMLINK_JUMPS`），或目标指回正在遍历的祖先目录
+    Circular,
+    /// 目标不存在（`stat` 返回 SFTP(2)）
+    Broken,
+}
+
+/// 并发统计目录大小输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryStatsParallelInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 要统计的目录路径
+    pub path: String,
+    /// 同时工作的 worker 数量（每个 worker 独立开一条辅助 SFTP 连接），默认 4，
+    /// 超过 [`crate::services::sftp_service::SftpService::MAX_DIRECTORY_STATS_CONCURRENCY`]
+    /// 会被截断
+    pub concurrency: Option<u8>,
+}
+
+/// `sftp_compute_directory_stats_parallel` 进度事件，按固定时间间隔（而非逐条目）节流推送
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryStatsProgress {
+    /// 被统计的根路径（用作事件标识）
+    pub path: String,
+    /// 已完成 `readdir` 的条目数
+    pub entries_checked: u64,
+    /// 已发现但尚未 `readdir` 的目录数（工作队列长度）
+    pub entries_queued: u64,
+}
+
+/// 查找重复文件输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindDuplicateFilesInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 要扫描的子树根路径
+    pub path: String,
+}
+
+/// `sftp_find_duplicate_files` 哈希阶段进度事件，按固定时间间隔（而非逐条目）节流推送；
+/// 只覆盖阶段二（按 size 分桶后逐个算内容哈希），阶段一的目录遍历不单独报告进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateScanProgress {
+    /// 被扫描的根路径（用作事件标识）
+    pub path: String,
+    /// 已计算哈希的候选文件数
+    pub files_hashed: u64,
+    /// 候选文件总数（size 分桶后、长度 > 1 的桶里的文件数之和）
+    pub total_candidates: u64,
 }
 
 /// 递归删除输入参数
@@ -42,6 +136,9 @@ pub struct RecursiveDeleteInput {
     pub session_id: String,
     /// 要删除的路径（文件或目录）
     pub path: String,
+    /// gitignore 风格的排除模式，匹配到的条目（及其子树）不会被删除
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
 }
 
 /// 递归删除进度事件
@@ -76,17 +173,28 @@ pub struct RecursiveDeleteResult {
     pub deleted_dirs: u64,
     /// 删除失败的项
     pub failures: Vec<DeleteFailure>,
+    /// 是否因 `sftp_cancel_operation` 提前终止
+    pub cancelled: bool,
+    /// 取消时尚未处理的路径（未取消时为空）
+    pub remaining_paths: Vec<String>,
 }
 
 /// 列出远程目录内容
 ///
-/// 返回目录下所有文件和子目录，支持排序
+/// 返回目录下所有文件和子目录，支持排序；`follow_symlinks` 为 true 时，目录项为
+/// 符号链接的条目会额外跟随解析目标的元数据（大小/mtime/mode/is_dir），否则
+/// 报告链接自身的属性——两种情况下 `is_symlink`/`symlink_target` 始终如实反映
+///
+/// `exclude_patterns` 为 gitignore 风格的排除模式（`*`/`**`/`?`/字符类/`!` 取反），
+/// 匹配到的条目不会出现在返回结果里
 #[tauri::command]
 pub async fn sftp_list_dir(
     session_manager: State<'_, Arc<SessionManager>>,
     session_id: String,
     path: String,
     sort: Option<SortSpec>,
+    follow_symlinks: Option<bool>,
+    exclude_patterns: Option<Vec<String>>,
 ) -> Result<Vec<FileEntry>, AppError> {
     if session_id.trim().is_empty() {
         return Err(AppError::invalid_argument("会话 ID 不能为空"));
@@ -99,15 +207,21 @@ pub async fn sftp_list_dir(
     );
 
     let session = session_manager.get_session(&session_id)?;
+    let follow_symlinks = follow_symlinks.unwrap_or(false);
+    let exclude = ExcludeMatcher::new(&exclude_patterns.unwrap_or_default());
 
-    let entries = spawn_blocking(move || SftpService::list_dir(&session.sftp, &path, sort))
-        .await
-        .map_err(|e| {
-            AppError::new(
-                crate::models::error::ErrorCode::Unknown,
-                format!("spawn_blocking failed: {}", e),
-            )
-        })??;
+    let entries = spawn_blocking(move || {
+        session.with_sftp(move |sftp| {
+            SftpService::list_dir(sftp, &path, Some(&exclude), sort, follow_symlinks)
+        })
+    })
+    .await
+    .map_err(|e| {
+        AppError::new(
+            crate::models::error::ErrorCode::Unknown,
+            format!("spawn_blocking failed: {}", e),
+        )
+    })??;
 
     tracing::debug!(
         session_id = %session_id,
@@ -119,6 +233,9 @@ pub async fn sftp_list_dir(
 }
 
 /// 获取文件/目录信息
+///
+/// 若路径本身是符号链接，`is_symlink` 为 true，`symlink_target` 携带原始目标文本；
+/// 其余字段（size/mtime/mode/is_dir）跟随解析目标（悬空链接时退回链接自身属性）
 #[tauri::command]
 pub async fn sftp_stat(
     session_manager: State<'_, Arc<SessionManager>>,
@@ -137,7 +254,7 @@ pub async fn sftp_stat(
 
     let session = session_manager.get_session(&session_id)?;
 
-    let entry = spawn_blocking(move || SftpService::stat(&session.sftp, &path))
+    let entry = spawn_blocking(move || session.with_sftp(move |sftp| SftpService::stat(sftp, &path)))
         .await
         .map_err(|e| {
             AppError::new(
@@ -149,6 +266,105 @@ pub async fn sftp_stat(
     Ok(entry)
 }
 
+/// 读取符号链接指向的原始目标路径（不跟随、不解析）
+#[tauri::command]
+pub async fn sftp_readlink(
+    session_manager: State<'_, Arc<SessionManager>>,
+    session_id: String,
+    path: String,
+) -> Result<String, AppError> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %session_id,
+        path = %path,
+        "读取符号链接目标"
+    );
+
+    let session = session_manager.get_session(&session_id)?;
+
+    let target =
+        spawn_blocking(move || session.with_sftp(move |sftp| SftpService::readlink(sftp, &path)))
+        .await
+        .map_err(|e| {
+            AppError::new(
+                crate::models::error::ErrorCode::Unknown,
+                format!("spawn_blocking failed: {}", e),
+            )
+        })??;
+
+    Ok(target)
+}
+
+/// 解析路径在远程文件系统上的真实绝对路径（结合词法规范化与符号链接解析）
+#[tauri::command]
+pub async fn sftp_canonicalize(
+    session_manager: State<'_, Arc<SessionManager>>,
+    session_id: String,
+    path: String,
+) -> Result<String, AppError> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::debug!(session_id = %session_id, path = %path, "解析真实路径");
+
+    let session = session_manager.get_session(&session_id)?;
+
+    let resolved = spawn_blocking(move || {
+        session.with_sftp(move |sftp| SftpService::canonicalize(sftp, &path))
+    })
+    .await
+    .map_err(|e| {
+        AppError::new(
+            crate::models::error::ErrorCode::Unknown,
+            format!("spawn_blocking failed: {}", e),
+        )
+    })??;
+
+    Ok(resolved)
+}
+
+/// 创建符号链接 `link_path` -> `target`
+#[tauri::command]
+pub async fn sftp_symlink(
+    session_manager: State<'_, Arc<SessionManager>>,
+    session_id: String,
+    target: String,
+    link_path: String,
+) -> Result<(), AppError> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if target.trim().is_empty() || link_path.trim().is_empty() {
+        return Err(AppError::invalid_argument("路径不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %session_id,
+        target = %target,
+        link_path = %link_path,
+        "创建符号链接"
+    );
+
+    let session = session_manager.get_session(&session_id)?;
+
+    spawn_blocking(move || session.with_sftp(move |sftp| SftpService::symlink(sftp, &target, &link_path)))
+        .await
+        .map_err(|e| {
+            AppError::new(
+                crate::models::error::ErrorCode::Unknown,
+                format!("spawn_blocking failed: {}", e),
+            )
+        })??;
+
+    tracing::info!(session_id = %session_id, "符号链接创建成功");
+
+    Ok(())
+}
+
 /// 创建远程目录
 #[tauri::command]
 pub async fn sftp_mkdir(
@@ -169,7 +385,7 @@ pub async fn sftp_mkdir(
     let session = session_manager.get_session(&session_id)?;
     let path_clone = path.clone();
 
-    spawn_blocking(move || SftpService::mkdir(&session.sftp, &path_clone))
+    spawn_blocking(move || session.with_sftp(move |sftp| SftpService::mkdir(sftp, &path_clone)))
         .await
         .map_err(|e| {
             AppError::new(
@@ -210,7 +426,7 @@ pub async fn sftp_rename(
     let from_clone = from_path.clone();
     let to_clone = to_path.clone();
 
-    spawn_blocking(move || SftpService::rename(&session.sftp, &from_clone, &to_clone))
+    spawn_blocking(move || session.with_sftp(move |sftp| SftpService::rename(sftp, &from_clone, &to_clone)))
         .await
         .map_err(|e| {
             AppError::new(
@@ -251,7 +467,7 @@ pub async fn sftp_delete(
     let session = session_manager.get_session(&session_id)?;
     let path_clone = path.clone();
 
-    spawn_blocking(move || SftpService::delete(&session.sftp, &path_clone, is_dir))
+    spawn_blocking(move || session.with_sftp(move |sftp| SftpService::delete(sftp, &path_clone, is_dir)))
         .await
         .map_err(|e| {
             AppError::new(
@@ -325,32 +541,35 @@ pub async fn sftp_chmod(
     let session = session_manager.get_session(&input.session_id)?;
     let session_id = input.session_id.clone();
     let paths = input.paths.clone();
+    let paths_for_err = paths.clone();
     let mode = input.mode;
 
     let result = spawn_blocking(move || {
-        let mut success_count = 0;
-        let mut failures = Vec::new();
-
-        for path in &paths {
-            match SftpService::chmod(&session.sftp, path, mode) {
-                Ok(()) => {
-                    success_count += 1;
-                    tracing::debug!(path = %path, mode = format!("{:o}", mode), "权限修改成功");
-                }
-                Err(e) => {
-                    tracing::warn!(path = %path, error = %e, "权限修改失败");
-                    failures.push(ChmodFailure {
-                        path: path.clone(),
-                        error: e.message.clone(),
-                    });
+        session.with_sftp(move |sftp| {
+            let mut success_count = 0;
+            let mut failures = Vec::new();
+
+            for path in &paths {
+                match SftpService::chmod(sftp, path, mode) {
+                    Ok(()) => {
+                        success_count += 1;
+                        tracing::debug!(path = %path, mode = format!("{:o}", mode), "权限修改成功");
+                    }
+                    Err(e) => {
+                        tracing::warn!(path = %path, error = %e, "权限修改失败");
+                        failures.push(ChmodFailure {
+                            path: path.clone(),
+                            error: e.message.clone(),
+                        });
+                    }
                 }
             }
-        }
 
-        ChmodResult {
-            success_count,
-            failures,
-        }
+            Ok(ChmodResult {
+                success_count,
+                failures,
+            })
+        })
     })
     .await
     .map_err(|e| {
@@ -358,7 +577,17 @@ pub async fn sftp_chmod(
             crate::models::error::ErrorCode::Unknown,
             format!("spawn_blocking failed: {}", e),
         )
-    })?;
+    })?
+    .unwrap_or_else(|e| ChmodResult {
+        success_count: 0,
+        failures: paths_for_err
+            .iter()
+            .map(|path| ChmodFailure {
+                path: path.clone(),
+                error: e.message.clone(),
+            })
+            .collect(),
+    });
 
     tracing::info!(
         session_id = %session_id,
@@ -370,14 +599,106 @@ pub async fn sftp_chmod(
     Ok(result)
 }
 
+/// `sftp_chmod_recursive` 的遍历范围过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChmodTarget {
+    All,
+    DirsOnly,
+    FilesOnly,
+}
+
+impl From<ChmodTarget> for crate::services::sftp_service::ChmodTarget {
+    fn from(target: ChmodTarget) -> Self {
+        match target {
+            ChmodTarget::All => crate::services::sftp_service::ChmodTarget::All,
+            ChmodTarget::DirsOnly => crate::services::sftp_service::ChmodTarget::DirsOnly,
+            ChmodTarget::FilesOnly => crate::services::sftp_service::ChmodTarget::FilesOnly,
+        }
+    }
+}
+
+/// 递归 chmod 输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChmodRecursiveInput {
+    /// 会话 ID
+    pub session_id: String,
+    pub path: String,
+    /// 符号权限表达式，如 `u+rwX,go-w`
+    pub symbolic_mode: String,
+    #[serde(default = "default_chmod_target")]
+    pub target: ChmodTarget,
+}
+
+fn default_chmod_target() -> ChmodTarget {
+    ChmodTarget::All
+}
+
+/// 递归修改目录树下所有条目的权限，支持符号权限表达式（如 `u+rwX,go-w`）
+///
+/// 每个条目按自身当前权限相对计算最终 mode，跳过符号链接本身；`target` 可选地
+/// 把处理范围收窄到只改目录或只改文件。单项失败记录到返回结果的 `failures`，
+/// 不中断整体遍历
+#[tauri::command]
+pub async fn sftp_chmod_recursive(
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: ChmodRecursiveInput,
+) -> Result<ChmodResult, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if input.symbolic_mode.trim().is_empty() {
+        return Err(AppError::invalid_argument("符号权限表达式不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        path = %input.path,
+        symbolic_mode = %input.symbolic_mode,
+        "递归修改权限"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let session_id = input.session_id.clone();
+    let path = input.path.clone();
+    let symbolic_mode = input.symbolic_mode.clone();
+    let target = crate::services::sftp_service::ChmodTarget::from(input.target);
+
+    let result = spawn_blocking(move || {
+        session.with_sftp(move |sftp| {
+            SftpService::chmod_recursive(sftp, &path, &symbolic_mode, target)
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::info!(
+        session_id = %session_id,
+        path = %input.path,
+        success_count = result.success_count,
+        failure_count = result.failures.len(),
+        "递归 chmod 完成"
+    );
+
+    Ok(result)
+}
+
 /// 获取目录统计信息
 ///
-/// 用于删除确认对话框显示文件数量和总大小
+/// 用于删除确认对话框显示文件数量和总大小；`follow_symlinks` 为 true 时会解析目录树中
+/// 的符号链接一并计入统计，遇到的循环/断链会记录在返回值的 `symlink_issues` 里而不是
+/// 静默跳过——默认（false/省略）维持跳过符号链接的旧行为
+///
+/// `exclude_patterns` 为 gitignore 风格的排除模式，匹配到的条目（及其子树）既不计入
+/// 统计也不会被展开，可用来例如统计时跳过 `node_modules`
 #[tauri::command]
 pub async fn sftp_get_dir_stats(
     session_manager: State<'_, Arc<SessionManager>>,
     session_id: String,
     path: String,
+    follow_symlinks: Option<bool>,
+    exclude_patterns: Option<Vec<String>>,
 ) -> Result<DirectoryStats, AppError> {
     if session_id.trim().is_empty() {
         return Err(AppError::invalid_argument("会话 ID 不能为空"));
@@ -391,16 +712,25 @@ pub async fn sftp_get_dir_stats(
 
     let session = session_manager.get_session(&session_id)?;
     let path_clone = path.clone();
+    let mode = if follow_symlinks.unwrap_or(false) {
+        SymlinkMode::Follow
+    } else {
+        SymlinkMode::Skip
+    };
+    let exclude = ExcludeMatcher::new(&exclude_patterns.unwrap_or_default());
 
-    let stats =
-        spawn_blocking(move || SftpService::get_directory_stats(&session.sftp, &path_clone))
-            .await
-            .map_err(|e| {
-                AppError::new(
-                    crate::models::error::ErrorCode::Unknown,
-                    format!("spawn_blocking failed: {}", e),
-                )
-            })??;
+    let stats = spawn_blocking(move || {
+        session.with_sftp(move |sftp| {
+            SftpService::get_directory_stats(sftp, &path_clone, mode, Some(&exclude))
+        })
+    })
+    .await
+    .map_err(|e| {
+        AppError::new(
+            crate::models::error::ErrorCode::Unknown,
+            format!("spawn_blocking failed: {}", e),
+        )
+    })??;
 
     tracing::debug!(
         session_id = %session_id,
@@ -414,59 +744,847 @@ pub async fn sftp_get_dir_stats(
     Ok(stats)
 }
 
-/// 递归删除目录
+/// 并发统计目录大小，用多条独立 SFTP 连接同时对一棵大目录树 `readdir`
 ///
-/// 删除目录及其所有内容，通过事件发送删除进度
+/// 进度按固定时间间隔（而非逐条目）通过 `directoryStats:progress` 事件推送；不跟随
+/// 符号链接，返回值的 `symlink_issues` 恒为空——需要检测符号链接循环请改用
+/// `sftp_get_dir_stats` 并传 `followSymlinks: true`
 #[tauri::command]
-pub async fn sftp_delete_recursive(
+pub async fn sftp_compute_directory_stats_parallel(
     app: AppHandle,
+    db: State<'_, Arc<Database>>,
     session_manager: State<'_, Arc<SessionManager>>,
-    input: RecursiveDeleteInput,
-) -> Result<RecursiveDeleteResult, AppError> {
+    input: DirectoryStatsParallelInput,
+) -> Result<DirectoryStats, AppError> {
     if input.session_id.trim().is_empty() {
         return Err(AppError::invalid_argument("会话 ID 不能为空"));
     }
 
-    if input.path.trim().is_empty() {
-        return Err(AppError::invalid_argument("路径不能为空"));
+    tracing::debug!(
+        session_id = %input.session_id,
+        path = %input.path,
+        concurrency = ?input.concurrency,
+        "并发统计目录大小"
+    );
+
+    let session_manager = session_manager.inner().clone();
+    let db = db.inner().clone();
+    let session_id = input.session_id.clone();
+    let path = input.path.clone();
+    let concurrency = input.concurrency.unwrap_or(4);
+
+    let stats = spawn_blocking(move || {
+        let app_clone = app.clone();
+        let progress_callback: Box<dyn Fn(DirectoryStatsProgress) + Send> =
+            Box::new(move |progress| {
+                if let Err(e) = app_clone.emit("directoryStats:progress", &progress) {
+                    tracing::warn!(error = %e, "发送目录统计进度事件失败");
+                }
+            });
+
+        SftpService::compute_directory_stats_parallel(
+            &session_manager,
+            &db,
+            &session_id,
+            &path,
+            concurrency,
+            Some(progress_callback),
+        )
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        path = %input.path,
+        file_count = stats.file_count,
+        dir_count = stats.dir_count,
+        total_size = stats.total_size,
+        "并发目录统计完成"
+    );
+
+    Ok(stats)
+}
+
+/// 查找子树下内容完全相同的重复文件：先按大小分桶排除显然不同的文件，再对候选逐个
+/// 流式读取内容算 SHA-256，按摘要确认
+///
+/// 哈希阶段的进度按固定时间间隔（而非逐文件）通过 `duplicateScan:progress` 事件推送
+#[tauri::command]
+pub async fn sftp_find_duplicate_files(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: FindDuplicateFilesInput,
+) -> Result<Vec<Vec<FileEntry>>, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
     }
 
     tracing::debug!(
         session_id = %input.session_id,
         path = %input.path,
-        "递归删除"
+        "查找重复文件"
     );
 
     let session = session_manager.get_session(&input.session_id)?;
-    let session_id = input.session_id.clone();
     let path = input.path.clone();
 
-    let result = spawn_blocking(move || {
-        // 创建进度回调，通过 Tauri 事件发送进度
+    let groups = spawn_blocking(move || {
         let app_clone = app.clone();
-        let progress_callback: Box<dyn Fn(DeleteProgress) + Send> = Box::new(move |progress| {
-            if let Err(e) = app_clone.emit("delete:progress", &progress) {
-                tracing::warn!(error = %e, "发送删除进度事件失败");
-            }
-        });
+        let progress_callback: Box<dyn Fn(DuplicateScanProgress) + Send> =
+            Box::new(move |progress| {
+                if let Err(e) = app_clone.emit("duplicateScan:progress", &progress) {
+                    tracing::warn!(error = %e, "发送重复文件扫描进度事件失败");
+                }
+            });
 
-        SftpService::delete_recursive(&session.sftp, &path, Some(progress_callback))
+        session.with_sftp(move |sftp| {
+            SftpService::find_duplicate_files(sftp, &path, Some(progress_callback))
+        })
     })
     .await
-    .map_err(|e| {
-        AppError::new(
-            crate::models::error::ErrorCode::Unknown,
-            format!("spawn_blocking failed: {}", e),
-        )
-    })??;
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
 
-    tracing::info!(
-        session_id = %session_id,
+    tracing::debug!(
+        session_id = %input.session_id,
         path = %input.path,
-        deleted_files = result.deleted_files,
-        deleted_dirs = result.deleted_dirs,
-        failures = result.failures.len(),
-        "递归删除完成"
+        group_count = groups.len(),
+        "重复文件扫描完成"
+    );
+
+    Ok(groups)
+}
+
+/// 递归删除开始事件，携带可传给 `sftp_cancel_operation` 的 operation_id
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteStarted {
+    pub operation_id: String,
+    pub path: String,
+}
+
+/// 递归删除目录
+///
+/// 删除目录及其所有内容，通过事件发送删除进度；开始时立即发送一次 `delete:started`
+/// 事件携带 operation_id，随时可用 `sftp_cancel_operation` 中止，中止后返回的结果里
+/// `cancelled` 为 true 且 `remaining_paths` 记录尚未删除的项
+///
+/// `input.exclude_patterns` 为 gitignore 风格的排除模式，匹配到的条目（及其子树）会
+/// 被从待删除集合里摘掉，可用来删除构建目录的同时保留 `*.lock` 这类文件
+#[tauri::command]
+pub async fn sftp_delete_recursive(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    operation_registry: State<'_, Arc<OperationRegistry>>,
+    input: RecursiveDeleteInput,
+) -> Result<RecursiveDeleteResult, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    if input.path.trim().is_empty() {
+        return Err(AppError::invalid_argument("路径不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        path = %input.path,
+        "递归删除"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let session_id = input.session_id.clone();
+    let path = input.path.clone();
+    let exclude = ExcludeMatcher::new(&input.exclude_patterns);
+
+    let (operation_id, cancel_flag) = operation_registry.register()?;
+    app.emit(
+        "delete:started",
+        &DeleteStarted {
+            operation_id: operation_id.clone(),
+            path: path.clone(),
+        },
+    )
+    .ok();
+
+    let result = spawn_blocking(move || {
+        // 创建进度回调，通过 Tauri 事件发送进度
+        let app_clone = app.clone();
+        let progress_callback: Box<dyn Fn(DeleteProgress) + Send> = Box::new(move |progress| {
+            if let Err(e) = app_clone.emit("delete:progress", &progress) {
+                tracing::warn!(error = %e, "发送删除进度事件失败");
+            }
+        });
+
+        session.with_sftp(move |sftp| {
+            SftpService::delete_recursive(
+                sftp,
+                &path,
+                Some(&exclude),
+                Some(progress_callback),
+                Some(cancel_flag),
+            )
+        })
+    })
+    .await
+    .map_err(|e| {
+        AppError::new(
+            crate::models::error::ErrorCode::Unknown,
+            format!("spawn_blocking failed: {}", e),
+        )
+    })??;
+
+    tracing::info!(
+        session_id = %session_id,
+        path = %input.path,
+        deleted_files = result.deleted_files,
+        deleted_dirs = result.deleted_dirs,
+        failures = result.failures.len(),
+        cancelled = result.cancelled,
+        "递归删除完成"
+    );
+
+    Ok(result)
+}
+
+/// sftp_watch 变更事件类型
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// sftp_watch 变更事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChangeEvent {
+    pub watch_id: String,
+    pub path: String,
+    pub kind: FsChangeKind,
+}
+
+/// 监视一个远程路径，轮询检测变更并通过 `fs:change` 事件推送
+///
+/// 若只需要列出目录后自动刷新（而非更复杂的目录树监视），优先用这个接口；
+/// 需要递归深度/重命名识别的场景见 `watch_start`
+#[tauri::command]
+pub async fn sftp_watch(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    watcher_state: State<'_, Arc<WatcherState>>,
+    session_id: String,
+    path: String,
+    poll_interval_ms: Option<u64>,
+) -> Result<String, AppError> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::info!(
+        session_id = %session_id,
+        path = %path,
+        "开始监视远程路径 (sftp_watch)"
+    );
+
+    watcher_state.watch(
+        app,
+        session_manager.inner().clone(),
+        session_id,
+        path,
+        poll_interval_ms,
+    )
+}
+
+/// 停止 sftp_watch 监视
+#[tauri::command]
+pub async fn sftp_unwatch(
+    watcher_state: State<'_, Arc<WatcherState>>,
+    watch_id: String,
+) -> Result<(), AppError> {
+    tracing::info!(watch_id = %watch_id, "停止监视 (sftp_unwatch)");
+    watcher_state.unwatch(&watch_id)
+}
+
+/// 分块传输进度事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgressEvent {
+    pub path: String,
+    pub bytes_done: u64,
+    pub total_bytes: u64,
+}
+
+/// sftp_read_file 返回结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadFileResult {
+    /// base64 编码的文件内容
+    pub data: String,
+    /// 本次读取后的偏移 (= 调用前 offset + 实际读取字节数)，可作为下一次调用的 offset 继续分页读取
+    pub offset: u64,
+    /// 实际读取的字节数，可能小于请求的 length（已到达文件末尾）
+    pub bytes_read: u64,
+}
+
+/// 分块读取远程文件内容
+///
+/// 从 `offset` 开始读取至多 `length` 字节，内部以 [`SFTP_CHUNK_SIZE`] 为单位分块读取
+/// 并通过 `transfer:progress` 事件汇报进度，返回 base64 编码内容与读取后的偏移。
+/// 断点续传：调用方可先用 `sftp_stat` 获知已下载的本地大小，以其作为 `offset` 继续读取
+#[tauri::command]
+pub async fn sftp_read_file(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    session_id: String,
+    path: String,
+    offset: u64,
+    length: u64,
+) -> Result<ReadFileResult, AppError> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if length == 0 {
+        return Err(AppError::invalid_argument("length 必须大于 0"));
+    }
+
+    tracing::debug!(
+        session_id = %session_id,
+        path = %path,
+        offset,
+        length,
+        "分块读取远程文件"
+    );
+
+    let session = session_manager.get_session(&session_id)?;
+    let path_clone = path.clone();
+
+    let result = spawn_blocking(move || -> Result<ReadFileResult, AppError> {
+        session.with_sftp(move |sftp| {
+            let mut buf: Vec<u8> = Vec::with_capacity(length as usize);
+            let mut current_offset = offset;
+            let mut remaining = length;
+
+            while remaining > 0 {
+                let chunk_len = remaining.min(SFTP_CHUNK_SIZE);
+                let chunk =
+                    SftpService::read_file_chunk(sftp, &path_clone, current_offset, chunk_len)?;
+                let chunk_bytes = chunk.len() as u64;
+
+                buf.extend_from_slice(&chunk);
+                current_offset += chunk_bytes;
+                remaining -= chunk_bytes;
+
+                app.emit(
+                    "transfer:progress",
+                    &TransferProgressEvent {
+                        path: path_clone.clone(),
+                        bytes_done: buf.len() as u64,
+                        total_bytes: length,
+                    },
+                )
+                .ok();
+
+                if chunk_bytes == 0 {
+                    break; // 已到达文件末尾
+                }
+            }
+
+            Ok(ReadFileResult {
+                data: BASE64.encode(&buf),
+                offset: current_offset,
+                bytes_read: buf.len() as u64,
+            })
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    Ok(result)
+}
+
+/// sftp_write_file 输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteFileInput {
+    pub session_id: String,
+    pub path: String,
+    /// 写入起始偏移；`append` 为 true 时忽略
+    pub offset: u64,
+    /// base64 编码的待写入数据
+    pub data: String,
+    /// 追加写入，忽略 offset，始终写到文件末尾
+    #[serde(default)]
+    pub append: bool,
+}
+
+/// 分块写入远程文件内容
+///
+/// 内部以 [`SFTP_CHUNK_SIZE`] 为单位分块写入并通过 `transfer:progress` 事件汇报进度，
+/// 返回写入完成后的偏移。断点续传：调用方可先用 `sftp_stat` 获知远程已写入的部分文件大小，
+/// 以其作为 `offset`（或直接传 `append: true`）继续写入剩余数据
+#[tauri::command]
+pub async fn sftp_write_file(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: WriteFileInput,
+) -> Result<u64, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    let data = BASE64
+        .decode(&input.data)
+        .map_err(|e| AppError::invalid_argument(format!("data 不是合法的 base64: {}", e)))?;
+    let total_bytes = data.len() as u64;
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        path = %input.path,
+        offset = input.offset,
+        len = total_bytes,
+        append = input.append,
+        "分块写入远程文件"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let path = input.path.clone();
+    let session_id = input.session_id.clone();
+    let append = input.append;
+    let start_offset = input.offset;
+
+    let final_offset = spawn_blocking(move || -> Result<u64, AppError> {
+        session.with_sftp(move |sftp| {
+            let mut written: u64 = 0;
+
+            for chunk in data.chunks(SFTP_CHUNK_SIZE as usize) {
+                let chunk_offset = start_offset + written;
+                SftpService::write_file_chunk(sftp, &path, chunk_offset, chunk, append)?;
+                written += chunk.len() as u64;
+
+                app.emit(
+                    "transfer:progress",
+                    &TransferProgressEvent {
+                        path: path.clone(),
+                        bytes_done: written,
+                        total_bytes,
+                    },
+                )
+                .ok();
+            }
+
+            Ok(start_offset + written)
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::info!(
+        session_id = %session_id,
+        path = %input.path,
+        bytes = total_bytes,
+        "远程文件分块写入完成"
+    );
+
+    Ok(final_offset)
+}
+
+/// 递归复制输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecursiveCopyInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 源路径（文件或目录）
+    pub src_path: String,
+    /// 目标路径，必须尚不存在
+    pub dst_path: String,
+}
+
+/// 递归复制进度事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    /// 复制任务 ID (使用源路径作为标识)
+    pub path: String,
+    /// 已复制的文件/目录数
+    pub copied_count: u64,
+    /// 总文件/目录数
+    pub total_count: u64,
+    /// 当前正在复制的路径
+    pub current_path: String,
+}
+
+/// 复制失败项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 递归复制结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecursiveCopyResult {
+    /// 成功复制的文件数
+    pub copied_files: u64,
+    /// 成功创建的目录数
+    pub copied_dirs: u64,
+    /// 复制失败的项
+    pub failures: Vec<CopyFailure>,
+    /// 是否因 `sftp_cancel_operation` 提前终止
+    pub cancelled: bool,
+    /// 取消时尚未处理的源路径（未取消时为空）
+    pub remaining_paths: Vec<String>,
+}
+
+/// 递归复制开始事件，携带可传给 `sftp_cancel_operation` 的 operation_id
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyStarted {
+    pub operation_id: String,
+    pub src_path: String,
+}
+
+/// 递归复制文件或目录到新的远程路径
+///
+/// 用于同一会话内的原地复制，或 `rename` 因跨文件系统 (EXDEV) 失败时的远程到远程移动：
+/// 先 mkdir 再逐个写入，通过事件发送复制进度；开始时立即发送一次 `copy:started` 事件
+/// 携带 operation_id，可用 `sftp_cancel_operation` 中止
+#[tauri::command]
+pub async fn sftp_copy_recursive(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    operation_registry: State<'_, Arc<OperationRegistry>>,
+    input: RecursiveCopyInput,
+) -> Result<RecursiveCopyResult, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if input.src_path.trim().is_empty() || input.dst_path.trim().is_empty() {
+        return Err(AppError::invalid_argument("路径不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        src = %input.src_path,
+        dst = %input.dst_path,
+        "递归复制"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let session_id = input.session_id.clone();
+    let src_path = input.src_path.clone();
+    let dst_path = input.dst_path.clone();
+
+    let (operation_id, cancel_flag) = operation_registry.register()?;
+    app.emit(
+        "copy:started",
+        &CopyStarted {
+            operation_id: operation_id.clone(),
+            src_path: src_path.clone(),
+        },
+    )
+    .ok();
+
+    let result = spawn_blocking(move || {
+        // 创建进度回调，通过 Tauri 事件发送进度
+        let app_clone = app.clone();
+        let progress_callback: Box<dyn Fn(CopyProgress) + Send> = Box::new(move |progress| {
+            if let Err(e) = app_clone.emit("copy:progress", &progress) {
+                tracing::warn!(error = %e, "发送复制进度事件失败");
+            }
+        });
+
+        session.with_sftp(move |sftp| {
+            SftpService::copy_recursive(
+                sftp,
+                &src_path,
+                &dst_path,
+                Some(progress_callback),
+                Some(cancel_flag),
+            )
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::info!(
+        session_id = %session_id,
+        src = %input.src_path,
+        dst = %input.dst_path,
+        copied_files = result.copied_files,
+        copied_dirs = result.copied_dirs,
+        failures = result.failures.len(),
+        cancelled = result.cancelled,
+        "递归复制完成"
+    );
+
+    Ok(result)
+}
+
+/// 取消一个仍在进行中的递归删除/复制操作
+///
+/// 幂等：操作不存在或已结束时静默成功；取消是协作式的，实际停止时机取决于
+/// 对应递归函数下一次检查取消标志（删除/复制各处理完一项后检查一次）
+#[tauri::command]
+pub async fn sftp_cancel_operation(
+    operation_registry: State<'_, Arc<OperationRegistry>>,
+    operation_id: String,
+) -> Result<(), AppError> {
+    tracing::info!(operation_id = %operation_id, "请求取消操作");
+    operation_registry.cancel(&operation_id)
+}
+
+/// 归档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+}
+
+/// 下载归档输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadArchiveInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 要打包的远程路径（文件或目录）
+    pub remote_path: String,
+    /// 本地归档文件的完整路径（含文件名）
+    pub local_path: String,
+    /// 归档格式
+    pub format: ArchiveFormat,
+}
+
+/// 归档打包进度事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveProgress {
+    /// 已打包的文件数
+    pub files_done: u64,
+    /// 总文件数
+    pub total_files: u64,
+    /// 当前正在打包的远程路径
+    pub current_path: String,
+    /// 已写入归档的字节数（仅计入成功打包的文件）
+    pub bytes_done: u64,
+}
+
+/// 归档打包失败项（通常是远程读取失败）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 归档打包结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveResult {
+    /// 已处理的文件数（含失败项）
+    pub files_done: u64,
+    /// 总文件数
+    pub total_files: u64,
+    /// 打包失败的项
+    pub failures: Vec<ArchiveFailure>,
+}
+
+/// 将远程目录或单个文件打包为本地 tar/zip 归档（一次操作取代逐文件传输）
+///
+/// 复用 `transfer_download_dir` 同款的目录遍历逻辑枚举远程文件（跳过符号链接），
+/// 逐个通过分块读取拉取内容后写入本地归档，保留相对路径与 Unix 权限位；
+/// 通过 `archive:progress` 事件汇报进度，单个文件读取失败会记录到 `failures`
+/// 并继续打包其余文件，不会中断整体操作
+#[tauri::command]
+pub async fn sftp_download_archive(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: DownloadArchiveInput,
+) -> Result<ArchiveResult, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if input.remote_path.trim().is_empty() || input.local_path.trim().is_empty() {
+        return Err(AppError::invalid_argument("路径不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        remote_path = %input.remote_path,
+        local_path = %input.local_path,
+        format = ?input.format,
+        "打包下载"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let session_id = input.session_id.clone();
+    let remote_path = input.remote_path.clone();
+    let local_path = input.local_path.clone();
+    let format = input.format;
+
+    let result = spawn_blocking(move || {
+        let app_clone = app.clone();
+        let progress_callback: Box<dyn Fn(ArchiveProgress) + Send> = Box::new(move |progress| {
+            if let Err(e) = app_clone.emit("archive:progress", &progress) {
+                tracing::warn!(error = %e, "发送归档打包进度事件失败");
+            }
+        });
+
+        session.with_sftp(move |sftp| {
+            SftpService::download_archive(
+                sftp,
+                &remote_path,
+                &local_path,
+                format,
+                Some(progress_callback),
+            )
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::info!(
+        session_id = %session_id,
+        remote_path = %input.remote_path,
+        local_path = %input.local_path,
+        files_done = result.files_done,
+        total_files = result.total_files,
+        failures = result.failures.len(),
+        "打包下载完成"
+    );
+
+    Ok(result)
+}
+
+/// 目录镜像同步模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    /// 按大小和 mtime 与目标比对，只拷贝新增/变化的文件，并删除目标中源已不存在的文件
+    Incremental,
+    /// 不比对，拷贝源树中的每一个文件
+    Full,
+}
+
+impl From<SyncMode> for crate::services::sftp_service::SyncMode {
+    fn from(mode: SyncMode) -> Self {
+        match mode {
+            SyncMode::Incremental => crate::services::sftp_service::SyncMode::Incremental,
+            SyncMode::Full => crate::services::sftp_service::SyncMode::Full,
+        }
+    }
+}
+
+/// 目录镜像同步输入参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRecursiveInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 源路径（目录）
+    pub src_path: String,
+    /// 目标路径（目录，可尚不存在）
+    pub dst_path: String,
+    pub mode: SyncMode,
+}
+
+/// 目录镜像同步进度事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    /// 同步任务 ID（使用源路径作为标识）
+    pub path: String,
+    /// 已处理的新增/删除项数
+    pub processed_count: u64,
+    /// 待处理的新增/删除项总数
+    pub total_count: u64,
+    /// 当前正在处理的路径
+    pub current_path: String,
+}
+
+/// 同步失败项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 目录镜像同步结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// 成功拷贝（新增或更新）的文件数
+    pub copied: u64,
+    /// 源、目标已一致，未改动的文件数
+    pub skipped: u64,
+    /// 因源已不存在而从目标删除的文件数
+    pub deleted: u64,
+    /// 同步失败的项
+    pub failures: Vec<SyncFailure>,
+}
+
+/// 将远程目录 `src_path` 镜像到远程目录 `dst_path`
+///
+/// `Incremental` 模式按大小/mtime 比对增量同步，适合周期性备份；`Full` 模式无条件
+/// 拷贝源树的每个文件。通过 `sync:progress` 事件汇报进度；单项失败记录到
+/// `failures` 并继续同步其余文件，不中断整体操作
+#[tauri::command]
+pub async fn sftp_sync_recursive(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    input: SyncRecursiveInput,
+) -> Result<SyncReport, AppError> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if input.src_path.trim().is_empty() || input.dst_path.trim().is_empty() {
+        return Err(AppError::invalid_argument("路径不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        src = %input.src_path,
+        dst = %input.dst_path,
+        mode = ?input.mode,
+        "目录镜像同步"
+    );
+
+    let session = session_manager.get_session(&input.session_id)?;
+    let session_id = input.session_id.clone();
+    let src_path = input.src_path.clone();
+    let dst_path = input.dst_path.clone();
+    let mode = crate::services::sftp_service::SyncMode::from(input.mode);
+
+    let result = spawn_blocking(move || {
+        let app_clone = app.clone();
+        let progress_callback: Box<dyn Fn(SyncProgress) + Send> = Box::new(move |progress| {
+            if let Err(e) = app_clone.emit("sync:progress", &progress) {
+                tracing::warn!(error = %e, "发送同步进度事件失败");
+            }
+        });
+
+        session.with_sftp(move |sftp| {
+            SftpService::sync_recursive(sftp, &src_path, &dst_path, mode, Some(progress_callback), None)
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    tracing::info!(
+        session_id = %session_id,
+        src = %input.src_path,
+        dst = %input.dst_path,
+        copied = result.copied,
+        skipped = result.skipped,
+        deleted = result.deleted,
+        failures = result.failures.len(),
+        "目录镜像同步完成"
     );
 
     Ok(result)
@@ -0,0 +1,20 @@
+//! Shutdown 相关命令
+//!
+//! - shutdown_prepare: 前端主动发起的优雅退出，排空终端/会话/在途传输后再关窗口
+
+use std::sync::Arc;
+
+use crate::models::error::AppResult;
+use crate::services::shutdown::{ShutdownCoordinator, ShutdownSummary};
+
+/// 前端在调用 `window.close()`/`app.exit()` 之前主动发起的优雅关闭
+///
+/// 与 `RunEvent::ExitRequested`/Ctrl-C 信号走的是同一个 [`ShutdownCoordinator`]，
+/// 三者任意一个先触发都会完成排空，其余的调用直接拿到全零的幂等结果
+#[tauri::command]
+pub async fn shutdown_prepare(
+    coordinator: tauri::State<'_, Arc<ShutdownCoordinator>>,
+) -> AppResult<ShutdownSummary> {
+    tracing::info!("收到前端发起的优雅关闭请求");
+    Ok(coordinator.drain().await)
+}
@@ -3,12 +3,14 @@
 use std::sync::Arc;
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, State};
+use tokio::task::spawn_blocking;
 
-use crate::models::error::{AppError, AppResult};
+use crate::models::error::{AppError, AppResult, ErrorCode};
 use crate::models::terminal::TerminalInfo;
 use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::Database;
 use crate::services::terminal_manager::TerminalManager;
 
 #[derive(Debug, Deserialize)]
@@ -17,12 +19,19 @@ pub struct TerminalOpenInput {
     pub session_id: String,
     pub cols: Option<u16>,
     pub rows: Option<u16>,
+    /// 输出节流间隔（毫秒），不传则使用默认值
+    pub output_throttle_ms: Option<u64>,
+    /// 单次 emit 前允许累积的最大字节数，不传则使用默认值
+    pub output_buffer_limit: Option<usize>,
+    /// 滚动缓冲区容量（字节），不传则使用默认值
+    pub scrollback_cap: Option<usize>,
 }
 
 /// 打开终端
 #[tauri::command]
 pub async fn terminal_open(
     app: AppHandle,
+    db: State<'_, Arc<Database>>,
     session_manager: State<'_, Arc<SessionManager>>,
     terminal_manager: State<'_, Arc<TerminalManager>>,
     input: TerminalOpenInput,
@@ -31,10 +40,14 @@ pub async fn terminal_open(
 
     terminal_manager.open(
         app,
+        &db,
         session_manager.inner().clone(),
         &input.session_id,
         input.cols,
         input.rows,
+        input.output_throttle_ms,
+        input.output_buffer_limit,
+        input.scrollback_cap,
     )
 }
 
@@ -85,11 +98,121 @@ pub async fn terminal_close(
     terminal_manager.close(&terminal_id)
 }
 
-/// 通过 sessionId 获取终端
+/// 通过 sessionId 获取该会话下当前打开的全部终端 id（同一会话可能有多个标签页/分屏）
 #[tauri::command]
 pub async fn terminal_get_by_session(
     terminal_manager: State<'_, Arc<TerminalManager>>,
     session_id: String,
-) -> AppResult<Option<String>> {
+) -> AppResult<Vec<String>> {
     Ok(terminal_manager.get_terminal_by_session(&session_id))
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalExecInput {
+    pub session_id: String,
+    pub command: String,
+}
+
+/// 非交互式命令执行结果（stdout/stderr 为 base64 编码的原始字节）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalExecResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+/// 非交互式执行一条远程命令并阻塞等待完整结果
+///
+/// 用于文件浏览器里 `stat`/`ls -l` 这类一次性查询：比起 `terminal_open` 打开一整个
+/// PTY 终端更轻量，命令独立于任何已打开的终端（不占用 `terminals` 映射），
+/// 执行完毕后返回完整 stdout/stderr 及退出码，不经过交互式输出事件流
+#[tauri::command]
+pub async fn terminal_exec(
+    db: State<'_, Arc<Database>>,
+    session_manager: State<'_, Arc<SessionManager>>,
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    input: TerminalExecInput,
+) -> AppResult<TerminalExecResult> {
+    if input.session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+    if input.command.trim().is_empty() {
+        return Err(AppError::invalid_argument("命令不能为空"));
+    }
+
+    tracing::debug!(
+        session_id = %input.session_id,
+        command = %input.command,
+        "执行一次性命令"
+    );
+
+    let db = db.inner().clone();
+    let session_manager = session_manager.inner().clone();
+    let terminal_manager = terminal_manager.inner().clone();
+    let session_id = input.session_id.clone();
+    let command = input.command.clone();
+
+    let output = spawn_blocking(move || {
+        terminal_manager.exec(&db, session_manager, &session_id, &command)
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))??;
+
+    Ok(TerminalExecResult {
+        stdout: BASE64.encode(&output.stdout),
+        stderr: BASE64.encode(&output.stderr),
+        exit_code: output.exit_code,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalStartRecordingInput {
+    pub terminal_id: String,
+    /// 本地 cast 文件的完整路径（含文件名）
+    pub path: String,
+}
+
+/// 开始将终端会话录制为 asciicast v2 格式的 cast 文件
+#[tauri::command]
+pub async fn terminal_start_recording(
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    input: TerminalStartRecordingInput,
+) -> AppResult<()> {
+    tracing::info!(terminal_id = %input.terminal_id, path = %input.path, "开始录制终端");
+    terminal_manager.start_recording(&input.terminal_id, &input.path)
+}
+
+/// 停止终端会话录制
+#[tauri::command]
+pub async fn terminal_stop_recording(
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    terminal_id: String,
+) -> AppResult<()> {
+    tracing::info!(terminal_id = %terminal_id, "停止录制终端");
+    terminal_manager.stop_recording(&terminal_id)
+}
+
+/// 回放一个终端录制文件，通过 `terminal:output` / `terminal:status` 事件按录制时的
+/// 时间间隔重放；返回本次回放使用的 terminal_id，与真实终端无关，仅用于区分事件流
+#[tauri::command]
+pub async fn terminal_replay(
+    app: AppHandle,
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    path: String,
+) -> AppResult<String> {
+    tracing::info!(path = %path, "回放终端录制");
+    terminal_manager.replay(app, &path)
+}
+
+/// 获取终端的滚动缓冲区（base64 编码的原始输出），用于重连/重新挂载终端面板时恢复历史
+#[tauri::command]
+pub async fn terminal_get_scrollback(
+    terminal_manager: State<'_, Arc<TerminalManager>>,
+    terminal_id: String,
+) -> AppResult<String> {
+    let data = terminal_manager.get_scrollback(&terminal_id)?;
+    Ok(BASE64.encode(&data))
+}
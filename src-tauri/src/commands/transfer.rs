@@ -1,12 +1,18 @@
 //! Transfer 相关命令
+//!
+//! - transfers_list / transfers_stats / transfers_prune: 查询/聚合/清理持久化的传输历史记录
 
 use std::sync::Arc;
 
 use tauri::{AppHandle, State};
+use tokio::task::spawn_blocking;
 
-use crate::models::error::AppResult;
-use crate::models::transfer_task::TransferTask;
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::transfer_task::{DirTransferResult, TransferTask};
 use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::{
+    Database, TransferHistoryFilter, TransferHistoryRecord, TransferStats,
+};
 use crate::services::transfer_manager::TransferManager;
 
 /// 后台执行传输任务
@@ -52,6 +58,32 @@ pub async fn transfer_upload(
     Ok(task_id)
 }
 
+/// 非阻塞上传文件：并发槽位已满时立即返回 Busy 错误，不排队等待
+#[tauri::command]
+pub async fn transfer_try_upload(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    session_id: String,
+    local_path: String,
+    remote_dir: String,
+) -> AppResult<String> {
+    tracing::info!(session_id = %session_id, local_path = %local_path, remote_dir = %remote_dir, "非阻塞上传文件");
+
+    let task_id = transfer_manager
+        .try_create_upload(session_id, local_path, remote_dir)
+        .await?;
+
+    spawn_transfer_task(
+        app,
+        transfer_manager.inner().clone(),
+        session_manager.inner().clone(),
+        task_id.clone(),
+    );
+
+    Ok(task_id)
+}
+
 /// 下载文件
 #[tauri::command]
 pub async fn transfer_download(
@@ -78,7 +110,7 @@ pub async fn transfer_download(
     Ok(task_id)
 }
 
-/// 下载目录（递归）
+/// 下载目录（递归）。所有文件任务挂载在一个批量父任务下，返回批量任务 ID 及各文件子任务 ID
 #[tauri::command]
 pub async fn transfer_download_dir(
     app: AppHandle,
@@ -87,10 +119,10 @@ pub async fn transfer_download_dir(
     session_id: String,
     remote_path: String,
     local_dir: String,
-) -> AppResult<Vec<String>> {
+) -> AppResult<DirTransferResult> {
     tracing::info!(session_id = %session_id, remote_path = %remote_path, local_dir = %local_dir, "下载目录");
 
-    let task_ids = transfer_manager
+    let result = transfer_manager
         .create_download_dir(
             session_manager.inner().clone(),
             session_id,
@@ -99,7 +131,77 @@ pub async fn transfer_download_dir(
         )
         .await?;
 
-    // 为每个任务启动传输
+    // 为每个文件任务启动传输（批量父任务本身不执行）
+    for task_id in &result.task_ids {
+        spawn_transfer_task(
+            app.clone(),
+            transfer_manager.inner().clone(),
+            session_manager.inner().clone(),
+            task_id.clone(),
+        );
+    }
+
+    Ok(result)
+}
+
+/// 上传目录（递归）。所有文件任务挂载在一个批量父任务下，返回批量任务 ID 及各文件子任务 ID
+#[tauri::command]
+pub async fn transfer_upload_dir(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    session_id: String,
+    local_path: String,
+    remote_dir: String,
+) -> AppResult<DirTransferResult> {
+    tracing::info!(session_id = %session_id, local_path = %local_path, remote_dir = %remote_dir, "上传目录");
+
+    let result = transfer_manager
+        .create_upload_dir(
+            session_manager.inner().clone(),
+            session_id,
+            local_path,
+            remote_dir,
+        )
+        .await?;
+
+    // 为每个文件任务启动传输（批量父任务本身不执行）
+    for task_id in &result.task_ids {
+        spawn_transfer_task(
+            app.clone(),
+            transfer_manager.inner().clone(),
+            session_manager.inner().clone(),
+            task_id.clone(),
+        );
+    }
+
+    Ok(result)
+}
+
+/// 增量同步下载目录：仅下载远程侧新增或已变化的文件
+#[tauri::command]
+pub async fn transfer_download_dir_sync(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    session_id: String,
+    remote_path: String,
+    local_dir: String,
+    mirror: bool,
+) -> AppResult<Vec<String>> {
+    tracing::info!(session_id = %session_id, remote_path = %remote_path, local_dir = %local_dir, mirror, "增量同步下载目录");
+
+    let task_ids = transfer_manager
+        .create_download_dir_sync(
+            &app,
+            session_manager.inner().clone(),
+            session_id,
+            remote_path,
+            local_dir,
+            mirror,
+        )
+        .await?;
+
     for task_id in &task_ids {
         spawn_transfer_task(
             app.clone(),
@@ -112,28 +214,30 @@ pub async fn transfer_download_dir(
     Ok(task_ids)
 }
 
-/// 上传目录（递归）
+/// 增量同步上传目录：仅上传本地侧新增或已变化的文件
 #[tauri::command]
-pub async fn transfer_upload_dir(
+pub async fn transfer_upload_dir_sync(
     app: AppHandle,
     session_manager: State<'_, Arc<SessionManager>>,
     transfer_manager: State<'_, Arc<TransferManager>>,
     session_id: String,
     local_path: String,
     remote_dir: String,
+    mirror: bool,
 ) -> AppResult<Vec<String>> {
-    tracing::info!(session_id = %session_id, local_path = %local_path, remote_dir = %remote_dir, "上传目录");
+    tracing::info!(session_id = %session_id, local_path = %local_path, remote_dir = %remote_dir, mirror, "增量同步上传目录");
 
     let task_ids = transfer_manager
-        .create_upload_dir(
+        .create_upload_dir_sync(
+            &app,
             session_manager.inner().clone(),
             session_id,
             local_path,
             remote_dir,
+            mirror,
         )
         .await?;
 
-    // 为每个任务启动传输
     for task_id in &task_ids {
         spawn_transfer_task(
             app.clone(),
@@ -146,6 +250,32 @@ pub async fn transfer_upload_dir(
     Ok(task_ids)
 }
 
+/// 设置单个任务的限速覆盖值（字节/秒）。传 None 清除覆盖，改用全局默认限速。
+#[tauri::command]
+pub async fn transfer_set_speed_limit(
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    task_id: String,
+    speed_limit_bytes_per_sec: Option<u64>,
+) -> AppResult<()> {
+    tracing::info!(task_id = %task_id, speed_limit_bytes_per_sec, "设置任务限速");
+    transfer_manager
+        .set_task_speed_limit(&task_id, speed_limit_bytes_per_sec)
+        .await
+}
+
+/// 设置单个任务的校验和校验覆盖值。传 None 清除覆盖，改用全局开关与大小阈值自动判断。
+#[tauri::command]
+pub async fn transfer_set_verify_checksum(
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    task_id: String,
+    verify: Option<bool>,
+) -> AppResult<()> {
+    tracing::info!(task_id = %task_id, verify, "设置任务校验和校验覆盖");
+    transfer_manager
+        .set_task_verify_checksum(&task_id, verify)
+        .await
+}
+
 /// 取消传输
 #[tauri::command]
 pub async fn transfer_cancel(
@@ -178,6 +308,30 @@ pub async fn transfer_retry(
     Ok(new_task_id)
 }
 
+/// 重试批量任务下所有失败的子任务（目录递归传输）
+#[tauri::command]
+pub async fn transfer_retry_batch(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    transfer_manager: State<'_, Arc<TransferManager>>,
+    batch_id: String,
+) -> AppResult<Vec<String>> {
+    tracing::info!(batch_id = %batch_id, "重试批量任务中失败的子任务");
+
+    let new_task_ids = transfer_manager.retry_batch(&batch_id).await?;
+
+    for task_id in &new_task_ids {
+        spawn_transfer_task(
+            app.clone(),
+            transfer_manager.inner().clone(),
+            session_manager.inner().clone(),
+            task_id.clone(),
+        );
+    }
+
+    Ok(new_task_ids)
+}
+
 /// 获取任务列表
 #[tauri::command]
 pub async fn transfer_list(
@@ -195,11 +349,51 @@ pub async fn transfer_get(
     Ok(transfer_manager.get_task(&task_id).await)
 }
 
-/// 清理已完成的任务
+/// 清理已完成的任务。max_age_ms 为空时清理全部已完成任务。
 #[tauri::command]
 pub async fn transfer_cleanup(
     transfer_manager: State<'_, Arc<TransferManager>>,
+    max_age_ms: Option<i64>,
 ) -> AppResult<()> {
-    transfer_manager.cleanup_completed().await;
-    Ok(())
+    transfer_manager.cleanup_completed(max_age_ms).await
+}
+
+/// 按条件查询传输历史记录（持久化日志，区别于 [`transfer_list`] 返回的内存中活跃任务）
+#[tauri::command]
+pub async fn transfers_list(
+    db: State<'_, Arc<Database>>,
+    filter: TransferHistoryFilter,
+) -> AppResult<Vec<TransferHistoryRecord>> {
+    let db = db.inner().clone();
+    spawn_blocking(move || db.transfers_list(&filter))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 汇总传输历史统计：字节数、成功/失败/取消计数、平均吞吐率
+#[tauri::command]
+pub async fn transfers_stats(
+    db: State<'_, Arc<Database>>,
+    profile_id: Option<String>,
+    since: Option<i64>,
+) -> AppResult<TransferStats> {
+    let db = db.inner().clone();
+    spawn_blocking(move || db.transfers_stats(profile_id.as_deref(), since))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
+}
+
+/// 清理传输历史记录，返回删除的行数。`older_than`/`keep_last_n` 至少需要指定一个
+#[tauri::command]
+pub async fn transfers_prune(
+    db: State<'_, Arc<Database>>,
+    older_than: Option<i64>,
+    keep_last_n: Option<i64>,
+) -> AppResult<usize> {
+    tracing::info!(older_than, keep_last_n, "清理传输历史记录");
+
+    let db = db.inner().clone();
+    spawn_blocking(move || db.transfers_prune(older_than, keep_last_n))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("spawn_blocking failed: {}", e)))?
 }
@@ -0,0 +1,60 @@
+//! Watch 相关命令
+//!
+//! - watch_start: 开始监视远程目录
+//! - watch_stop: 停止监视
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+
+use crate::models::error::{AppError, AppResult};
+use crate::models::watch::WatchInfo;
+use crate::services::session_manager::SessionManager;
+use crate::services::watch_service::{WatchManager, WatchOptions};
+
+/// 开始监视远程目录
+///
+/// 若 `session_id` + `path` 已存在监视器，直接返回现有实例
+#[tauri::command]
+pub async fn watch_start(
+    app: AppHandle,
+    session_manager: State<'_, Arc<SessionManager>>,
+    watch_manager: State<'_, Arc<WatchManager>>,
+    session_id: String,
+    path: String,
+    recursive_depth: Option<u32>,
+    poll_interval_ms: Option<u64>,
+    debounce_ms: Option<u64>,
+) -> AppResult<WatchInfo> {
+    if session_id.trim().is_empty() {
+        return Err(AppError::invalid_argument("会话 ID 不能为空"));
+    }
+
+    tracing::info!(
+        session_id = %session_id,
+        path = %path,
+        "开始监视远程目录"
+    );
+
+    watch_manager.watch(
+        app,
+        session_manager.inner().clone(),
+        session_id,
+        path,
+        WatchOptions {
+            recursive_depth,
+            poll_interval_ms,
+            debounce_ms,
+        },
+    )
+}
+
+/// 停止监视
+#[tauri::command]
+pub async fn watch_stop(
+    watch_manager: State<'_, Arc<WatchManager>>,
+    watch_id: String,
+) -> AppResult<()> {
+    tracing::info!(watch_id = %watch_id, "停止监视");
+    watch_manager.unwatch(&watch_id)
+}
@@ -8,15 +8,25 @@
 
 use std::sync::Arc;
 
+use tauri::Emitter;
+
 pub mod commands;
 pub mod models;
 pub mod services;
 pub mod utils;
 
+use services::command_service::CommandManager;
+use services::operation_registry::OperationRegistry;
+use services::schedule_service::ScheduleManager;
+use services::search_service::SearchManager;
 use services::session_manager::SessionManager;
+use services::sftp_service::WatcherState;
+use services::shutdown::ShutdownCoordinator;
 use services::storage_service::Database;
+use services::system_monitor::SystemMonitor;
 use services::terminal_manager::TerminalManager;
 use services::transfer_manager::TransferManager;
+use services::watch_service::WatchManager;
 use utils::logging::init_logging;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -35,71 +45,521 @@ pub fn run() {
     let settings = db.settings_load().unwrap_or_default();
     let log_level = settings.log_level.to_tracing_level();
 
-    if let Err(e) = init_logging(log_level) {
-        eprintln!("Failed to initialize logging: {}", e);
-    }
+    let log_reload_handle = init_logging(log_level);
 
     // 3. 初始化会话管理器
     let session_manager = Arc::new(SessionManager::new());
 
-    // 4. 初始化传输管理器
-    let transfer_manager = Arc::new(TransferManager::new(settings.max_concurrent_transfers));
+    // 4. 初始化传输管理器（会从数据库加载未完成的任务，但不会自动执行）
+    let transfer_manager = Arc::new(TransferManager::new(
+        settings.max_concurrent_transfers,
+        settings.transfer_retry_count,
+        db.clone(),
+        settings.parallel_transfer_threshold_mb as u64 * 1024 * 1024,
+        settings.parallel_transfer_streams,
+        settings.preserve_file_metadata,
+        settings.speed_limit_kbps as u64 * 1024,
+        settings.verify_transfer_checksum,
+        settings.checksum_command.clone(),
+        settings.checksum_verify_min_size_mb as u64 * 1024 * 1024,
+        settings.pipeline_window_size,
+        settings.max_open_local_files,
+    ));
 
     // 5. 初始化终端管理器
     let terminal_manager = Arc::new(TerminalManager::new());
 
-    // 6. 构建 Tauri 应用
+    // 6. 初始化远程目录监视管理器
+    let watch_manager = Arc::new(WatchManager::new());
+
+    // 6.1 初始化 sftp_watch 使用的轻量监视器池
+    let watcher_state = Arc::new(WatcherState::new());
+
+    // 6.2 初始化递归删除/复制操作的取消注册表
+    let operation_registry = Arc::new(OperationRegistry::new());
+
+    // 7. 初始化远程搜索管理器
+    let search_manager = Arc::new(SearchManager::new());
+
+    // 8. 初始化远程命令执行管理器
+    let command_manager = Arc::new(CommandManager::new());
+
+    // 9. 初始化目录同步计划管理器（会从数据库加载已持久化的计划）
+    let schedule_manager = Arc::new(ScheduleManager::new(db.clone()));
+
+    // 9.1 初始化运行时指标采样器（诊断导出、system_stats 命令共用）
+    let system_monitor = Arc::new(SystemMonitor::new());
+
+    // 9.2 初始化优雅关闭协调器：退出前排空终端/会话/在途传输，供退出事件、
+    // Ctrl-C 信号与前端主动发起的 shutdown_prepare 共用，详见 services::shutdown
+    let shutdown_coordinator = Arc::new(ShutdownCoordinator::new(
+        session_manager.clone(),
+        terminal_manager.clone(),
+        transfer_manager.clone(),
+    ));
+    // `setup` 闭包是 `move` 的，这里提前拷贝一份给 Ctrl-C 任务用，让外层的
+    // `shutdown_coordinator` 留给 `run()` 里的 `RunEvent::ExitRequested` 处理器
+    let shutdown_coordinator_for_signal = shutdown_coordinator.clone();
+
+    // 10. 构建 Tauri 应用
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .manage(db)
-        .manage(session_manager)
-        .manage(transfer_manager)
-        .manage(terminal_manager)
+        .manage(db.clone())
+        .manage(log_reload_handle)
+        .manage(session_manager.clone())
+        .manage(transfer_manager.clone())
+        .manage(terminal_manager.clone())
+        .manage(watch_manager)
+        .manage(watcher_state)
+        .manage(operation_registry)
+        .manage(search_manager)
+        .manage(command_manager)
+        .manage(schedule_manager.clone())
+        .manage(shutdown_coordinator.clone())
+        .manage(system_monitor.clone())
+        .setup(move |app| {
+            let app_handle = app.handle().clone();
+
+            // 重新派发启动时从数据库恢复的 Waiting 任务
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let session_manager = session_manager.clone();
+                let transfer_manager = transfer_manager.clone();
+                async move {
+                    let waiting_task_ids: Vec<String> = transfer_manager
+                        .list_tasks()
+                        .await
+                        .into_iter()
+                        .filter(|t| {
+                            t.status == models::transfer_task::TransferStatus::Waiting
+                                && !t.is_batch
+                        })
+                        .map(|t| t.task_id)
+                        .collect();
+                    for task_id in waiting_task_ids {
+                        let app_handle = app_handle.clone();
+                        let session_manager = session_manager.clone();
+                        let transfer_manager = transfer_manager.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = transfer_manager
+                                .execute_task(app_handle, session_manager, task_id)
+                                .await
+                            {
+                                tracing::error!(error = %e, "恢复的传输任务执行失败");
+                            }
+                        });
+                    }
+                }
+            });
+
+            // 退避重试调度器：周期性检查到期的 Failed 任务并重新派发
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let session_manager = session_manager.clone();
+                let transfer_manager = transfer_manager.clone();
+                async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+                    loop {
+                        interval.tick().await;
+                        let due_task_ids = transfer_manager.scheduler_tick().await;
+                        for task_id in due_task_ids {
+                            let app_handle = app_handle.clone();
+                            let session_manager = session_manager.clone();
+                            let transfer_manager = transfer_manager.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = transfer_manager
+                                    .execute_task(app_handle, session_manager, task_id)
+                                    .await
+                                {
+                                    tracing::error!(error = %e, "退避重试任务执行失败");
+                                }
+                            });
+                        }
+                    }
+                }
+            });
+
+            // 目录同步计划调度器：周期性检查到期的计划，扫描差异并入队传输任务
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let session_manager = session_manager.clone();
+                let transfer_manager = transfer_manager.clone();
+                let schedule_manager = schedule_manager.clone();
+                async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+                    loop {
+                        interval.tick().await;
+                        schedule_manager
+                            .run_due_schedules(
+                                app_handle.clone(),
+                                session_manager.clone(),
+                                transfer_manager.clone(),
+                            )
+                            .await;
+                    }
+                }
+            });
+
+            // 会话健康检查：周期性发送 SSH keepalive 并探测活跃会话，发现失联时
+            // 暂停该会话下的在途传输、按指数退避自动透明重连，超过最大重试次数则
+            // 放弃并把会话标记为彻底丢失；重连成功后恢复因连接中断而暂停的传输任务
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let db = db.clone();
+                let session_manager = session_manager.clone();
+                let transfer_manager = transfer_manager.clone();
+                async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+                    loop {
+                        interval.tick().await;
+
+                        let Ok(session_ids) = session_manager.list_sessions() else {
+                            continue;
+                        };
+
+                        for session_id in session_ids {
+                            // 主动发送一次 keepalive：libssh2 只负责记录间隔，不会自己发包
+                            {
+                                let session_manager = session_manager.clone();
+                                let session_id = session_id.clone();
+                                let _ = tokio::task::spawn_blocking(move || {
+                                    session_manager.send_keepalive(&session_id)
+                                })
+                                .await;
+                            }
+
+                            let is_alive = {
+                                let session_manager = session_manager.clone();
+                                let session_id = session_id.clone();
+                                tokio::task::spawn_blocking(move || {
+                                    session_manager.is_session_alive(&session_id)
+                                })
+                                .await
+                                .unwrap_or(false)
+                            };
+
+                            if is_alive {
+                                session_manager.record_reconnect_success(&session_id);
+                                continue;
+                            }
+
+                            if !session_manager.should_attempt_reconnect(&session_id) {
+                                // 仍在本轮退避等待期内，跳过，下个 tick 再看
+                                continue;
+                            }
+
+                            let app_handle = app_handle.clone();
+                            let db = db.clone();
+                            let session_manager = session_manager.clone();
+                            let transfer_manager = transfer_manager.clone();
+                            tokio::spawn(async move {
+                                transfer_manager
+                                    .pause_running_tasks_for_session(&session_id)
+                                    .await;
+
+                                app_handle
+                                    .emit(
+                                        "session:status",
+                                        &commands::session::SessionStatusPayload {
+                                            session_id: session_id.clone(),
+                                            status: "reconnecting".to_string(),
+                                            message: None,
+                                        },
+                                    )
+                                    .ok();
+
+                                let reconnect_result = {
+                                    let session_manager = session_manager.clone();
+                                    let db = db.clone();
+                                    let session_id = session_id.clone();
+                                    // 在进入 spawn_blocking 前捕获运行时句柄，供
+                                    // `reconnect_session` 内部桥接到 `retry_with_backoff`
+                                    // 重试 TCP 连接/握手这类瞬时失败（与 FileHandleGuard 相同的桥接方式）
+                                    let rt_handle = tokio::runtime::Handle::current();
+                                    tokio::task::spawn_blocking(move || {
+                                        session_manager.reconnect_session(&db, &session_id, &rt_handle)
+                                    })
+                                    .await
+                                };
+
+                                match reconnect_result {
+                                    Ok(Ok(())) => {
+                                        tracing::info!(session_id = %session_id, "会话自动重连成功");
+                                        session_manager.record_reconnect_success(&session_id);
+                                        app_handle
+                                            .emit(
+                                                "session:status",
+                                                &commands::session::SessionStatusPayload {
+                                                    session_id: session_id.clone(),
+                                                    status: "reconnected".to_string(),
+                                                    message: None,
+                                                },
+                                            )
+                                            .ok();
+
+                                        let resumed_task_ids = transfer_manager
+                                            .resume_tasks_for_session(&session_id)
+                                            .await;
+                                        for task_id in resumed_task_ids {
+                                            let app_handle = app_handle.clone();
+                                            let session_manager = session_manager.clone();
+                                            let transfer_manager = transfer_manager.clone();
+                                            tokio::spawn(async move {
+                                                if let Err(e) = transfer_manager
+                                                    .execute_task(
+                                                        app_handle,
+                                                        session_manager,
+                                                        task_id,
+                                                    )
+                                                    .await
+                                                {
+                                                    tracing::error!(error = %e, "重连后恢复的传输任务执行失败");
+                                                }
+                                            });
+                                        }
+                                    }
+                                    Ok(Err(e)) => {
+                                        let attempts = session_manager.record_reconnect_failure(&session_id);
+                                        tracing::warn!(
+                                            session_id = %session_id,
+                                            attempts,
+                                            error = %e,
+                                            "会话自动重连失败"
+                                        );
+
+                                        if attempts >= services::session_manager::MAX_RECONNECT_ATTEMPTS {
+                                            tracing::error!(
+                                                session_id = %session_id,
+                                                "会话多次重连均失败，已放弃并标记为彻底丢失"
+                                            );
+                                            let _ = session_manager.close_session(&session_id);
+                                            app_handle
+                                                .emit(
+                                                    "session:status",
+                                                    &commands::session::SessionStatusPayload {
+                                                        session_id: session_id.clone(),
+                                                        status: "lost".to_string(),
+                                                        message: Some(e.message.clone()),
+                                                    },
+                                                )
+                                                .ok();
+                                        } else {
+                                            app_handle
+                                                .emit(
+                                                    "session:status",
+                                                    &commands::session::SessionStatusPayload {
+                                                        session_id: session_id.clone(),
+                                                        status: "error".to_string(),
+                                                        message: Some(e.message.clone()),
+                                                    },
+                                                )
+                                                .ok();
+                                        }
+                                    }
+                                    Err(e) => {
+                                        tracing::error!(session_id = %session_id, error = %e, "会话重连任务执行失败");
+                                    }
+                                }
+                            });
+                        }
+                    }
+                }
+            });
+
+            // 终端空闲超时 reaper：周期性关闭长时间无输入/输出的终端连接
+            // （TTL 取自设置，实时生效；0 表示禁用）
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let db = db.clone();
+                let terminal_manager = terminal_manager.clone();
+                async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+
+                        let app_handle = app_handle.clone();
+                        let db = db.clone();
+                        let terminal_manager = terminal_manager.clone();
+                        tokio::task::spawn_blocking(move || {
+                            let idle_ttl_secs = db
+                                .settings_load()
+                                .map(|s| s.terminal_idle_timeout_secs as u64)
+                                .unwrap_or(0);
+                            terminal_manager.reap_idle(&app_handle, idle_ttl_secs);
+                        })
+                        .await
+                        .ok();
+                    }
+                }
+            });
+
+            // 数据库维护：周期性执行 WAL checkpoint、传输历史清理与按需 VACUUM
+            tokio::spawn({
+                let db = db.clone();
+                async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+                    loop {
+                        interval.tick().await;
+
+                        let db = db.clone();
+                        tokio::task::spawn_blocking(move || match db.run_maintenance() {
+                            Ok(metrics) => {
+                                tracing::debug!(?metrics, "数据库维护任务完成");
+                            }
+                            Err(e) => {
+                                tracing::warn!(error = %e, "数据库维护任务失败");
+                            }
+                        })
+                        .await
+                        .ok();
+                    }
+                }
+            });
+
+            // OS 信号处理器：Ctrl-C（以及类 Unix 下的终端挂断）直接杀进程会留下
+            // 孤儿 PTY 子进程和半截的传输任务；这里先跑一遍优雅关闭排空，再真正退出
+            tokio::spawn({
+                let app_handle = app_handle.clone();
+                let shutdown_coordinator = shutdown_coordinator_for_signal.clone();
+                async move {
+                    if tokio::signal::ctrl_c().await.is_err() {
+                        return;
+                    }
+                    tracing::info!("收到 Ctrl-C，开始优雅关闭");
+                    shutdown_coordinator.drain().await;
+                    app_handle.exit(0);
+                }
+            });
+
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Profile 命令
             commands::profile::profile_list,
             commands::profile::profile_get,
             commands::profile::profile_upsert,
             commands::profile::profile_delete,
+            commands::profile::profile_export,
+            commands::profile::profile_import,
+            commands::profile::profile_import_uri,
+            commands::profile::profile_export_uri,
+            // 密钥管理命令
+            commands::keys::key_generate,
+            commands::keys::key_list,
+            commands::keys::key_export_public,
+            commands::keys::key_delete,
             // Session 命令
             commands::session::session_connect,
             commands::session::session_connect_after_trust,
+            commands::session::session_connect_after_interactive,
             commands::session::session_disconnect,
             commands::session::session_info,
             commands::session::session_list,
+            commands::session::session_list_agent_identities,
+            commands::session::session_set_limits,
             // Security 命令
             commands::security::security_trust_hostkey,
             commands::security::security_remove_hostkey,
             commands::security::security_check_hostkey,
+            commands::security::security_export_backup,
+            commands::security::security_import_backup,
+            commands::security::security_import_openssh_preview,
+            commands::security::security_import_openssh_apply,
+            commands::security::security_validate_private_key,
+            commands::security::security_known_hosts_import,
+            commands::security::security_known_hosts_export,
+            commands::security::security_vault_backup_shares,
+            commands::security::security_vault_recover_from_shares,
             // Settings 命令
             commands::settings::settings_get,
             commands::settings::settings_set,
             commands::settings::export_diagnostics,
+            commands::settings::system_stats,
             // SFTP 命令
             commands::sftp::sftp_list_dir,
             commands::sftp::sftp_stat,
+            commands::sftp::sftp_readlink,
+            commands::sftp::sftp_canonicalize,
+            commands::sftp::sftp_symlink,
             commands::sftp::sftp_mkdir,
             commands::sftp::sftp_rename,
             commands::sftp::sftp_delete,
+            commands::sftp::sftp_watch,
+            commands::sftp::sftp_unwatch,
+            commands::sftp::sftp_read_file,
+            commands::sftp::sftp_write_file,
+            commands::sftp::sftp_copy_recursive,
+            commands::sftp::sftp_cancel_operation,
+            commands::sftp::sftp_download_archive,
+            commands::sftp::sftp_sync_recursive,
+            commands::sftp::sftp_chmod_recursive,
+            commands::sftp::sftp_compute_directory_stats_parallel,
+            commands::sftp::sftp_find_duplicate_files,
             // Transfer 命令
             commands::transfer::transfer_upload,
+            commands::transfer::transfer_try_upload,
             commands::transfer::transfer_upload_dir,
             commands::transfer::transfer_download,
             commands::transfer::transfer_download_dir,
+            commands::transfer::transfer_upload_dir_sync,
+            commands::transfer::transfer_download_dir_sync,
+            commands::transfer::transfer_set_speed_limit,
+            commands::transfer::transfer_set_verify_checksum,
             commands::transfer::transfer_cancel,
             commands::transfer::transfer_retry,
+            commands::transfer::transfer_retry_batch,
             commands::transfer::transfer_list,
             commands::transfer::transfer_get,
             commands::transfer::transfer_cleanup,
+            commands::transfer::transfers_list,
+            commands::transfer::transfers_stats,
+            commands::transfer::transfers_prune,
             // Terminal 命令
             commands::terminal::terminal_open,
             commands::terminal::terminal_input,
             commands::terminal::terminal_resize,
             commands::terminal::terminal_close,
             commands::terminal::terminal_get_by_session,
+            commands::terminal::terminal_exec,
+            commands::terminal::terminal_start_recording,
+            commands::terminal::terminal_stop_recording,
+            commands::terminal::terminal_replay,
+            commands::terminal::terminal_get_scrollback,
+            // Watch 命令
+            commands::watch::watch_start,
+            commands::watch::watch_stop,
+            // Search 命令
+            commands::search::search_start,
+            commands::search::search_cancel,
+            // Command 命令
+            commands::command::command_run,
+            commands::command::command_write_stdin,
+            commands::command::command_kill,
+            // Schedule 命令
+            commands::schedule::schedule_create,
+            commands::schedule::schedule_list,
+            commands::schedule::schedule_delete,
+            commands::schedule::schedule_set_enabled,
+            // Audit 命令
+            commands::audit::audit_run,
+            // Shutdown 命令
+            commands::shutdown::shutdown_prepare,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |app_handle, event| {
+            // 窗口关闭/应用退出请求：先拦住默认的立即退出，后台跑完优雅关闭排空
+            // 再真正退出，这样用户点关闭按钮时不会留下孤儿 PTY 或被腰斩的传输
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                api.prevent_default();
+                let app_handle = app_handle.clone();
+                let shutdown_coordinator = shutdown_coordinator.clone();
+                tokio::spawn(async move {
+                    shutdown_coordinator.drain().await;
+                    app_handle.exit(0);
+                });
+            }
+        });
 }
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// 安全审计发现项的严重程度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSeverity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl AuditSeverity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditSeverity::Info => "Info",
+            AuditSeverity::Low => "Low",
+            AuditSeverity::Medium => "Medium",
+            AuditSeverity::High => "High",
+            AuditSeverity::Critical => "Critical",
+        }
+    }
+}
+
+/// 一条安全审计发现
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    /// 分类，如 "SSH 配置"、"文件权限"、"监听端口"
+    pub category: String,
+    pub severity: AuditSeverity,
+    pub title: String,
+    /// 支撑这条发现的原始命令输出片段
+    pub evidence: String,
+    pub remediation: String,
+}
+
+/// 一次完整的远程安全审计结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub session_id: String,
+    pub host: String,
+    /// 审计时间 (Unix 时间戳毫秒)
+    pub generated_at: i64,
+    pub findings: Vec<AuditFinding>,
+}
@@ -0,0 +1,52 @@
+//! 远程命令执行相关数据模型
+
+use serde::{Deserialize, Serialize};
+
+/// 命令执行状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandStatus {
+    Running,
+    Success,
+    Failed,
+    Killed,
+}
+
+/// 输出所属的流
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandOutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// 命令信息（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandInfo {
+    pub command_id: String,
+    pub session_id: String,
+}
+
+/// 命令输出事件 payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOutputPayload {
+    pub command_id: String,
+    pub stream: CommandOutputStream,
+    /// Base64 编码的输出数据
+    pub data: String,
+}
+
+/// 命令状态事件 payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStatusPayload {
+    pub command_id: String,
+    pub status: CommandStatus,
+    /// 进程退出码，仅 Success/Failed 时有值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
@@ -2,11 +2,12 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// 错误码枚举
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ErrorCode {
     AuthFailed,
     HostkeyMismatch,
+    HostkeyRevoked,
     Timeout,
     NetworkLost,
     NotFound,
@@ -17,11 +18,104 @@ pub enum ErrorCode {
     RemoteIoError,
     Canceled,
     InvalidArgument,
+    ChecksumMismatch,
+    Busy,
+    TooManySessions,
+    /// 远程文件系统空间耗尽（`LIBSSH2_FX_NO_SPACE_ON_FILESYSTEM`）或无可用介质
+    /// （`LIBSSH2_FX_NO_MEDIA`）——两者对用户而言都是"写不进去，得先腾地方"
+    DiskFull,
+    /// 远程账号配额已用尽（`LIBSSH2_FX_QUOTA_EXCEEDED`），与 [`Self::DiskFull`]
+    /// 的区别在于这是账号级限制而非磁盘真的满了，但同样不是重试能解决的
+    QuotaExceeded,
+    /// 远程连接已断开（`LIBSSH2_FX_NO_CONNECTION`/`CONNECTION_LOST`），区别于
+    /// [`Self::NetworkLost`]：这是 SFTP 子系统报告的连接丢失，而不是本地检测到的
+    ConnectionLost,
+    /// 远程服务器不支持请求的操作（`LIBSSH2_FX_OP_UNSUPPORTED`）
+    Unsupported,
+    /// 文件句柄已失效（`LIBSSH2_FX_INVALID_HANDLE`），通常是远程因超时等原因关闭了
+    /// 句柄——重新打开文件再试一次往往能恢复
+    StaleHandle,
+    /// 加密私钥解密失败：密码错误，或加密数据被篡改导致 AEAD 校验不通过——与服务端
+    /// 拒绝认证（[`Self::AuthFailed`]）是完全不同的原因，重试同一个密码没有意义
+    KeyDecryptFailed,
+    /// 私钥文件本身无法解析：格式不合法，或是我们不支持的私钥格式/加密套件
+    /// （如某些实现的加密 OpenSSH 私钥使用了我们未实现的 cipher）
+    KeyParseError,
     Unknown,
 }
 
+impl ErrorCode {
+    /// 稳定的整数错误码，供不想做字符串匹配的调用方（如前端 TS）使用
+    ///
+    /// 取值固定落在 JSON-RPC 为"服务端自定义错误"保留的 `-32000..-32099` 区间内
+    /// （该标准自己的 `-32600`/`-32601` 等请求/方法级错误不会落在这个区间，两者
+    /// 可以共存）。每个变体的数字一旦分配就不能更改——旧版本前端可能已经把这个
+    /// 数字存进本地缓存或按数字做持久化的路由判断，改号等于悄悄破坏兼容性。新增
+    /// 变体只能在末尾追加新数字，绝不能复用或重排已分配的号段
+    pub fn as_i32(&self) -> i32 {
+        match self {
+            ErrorCode::AuthFailed => -32000,
+            ErrorCode::HostkeyMismatch => -32001,
+            ErrorCode::HostkeyRevoked => -32002,
+            ErrorCode::Timeout => -32003,
+            ErrorCode::NetworkLost => -32004,
+            ErrorCode::NotFound => -32005,
+            ErrorCode::PermissionDenied => -32006,
+            ErrorCode::DirNotEmpty => -32007,
+            ErrorCode::AlreadyExists => -32008,
+            ErrorCode::LocalIoError => -32009,
+            ErrorCode::RemoteIoError => -32010,
+            ErrorCode::Canceled => -32011,
+            ErrorCode::InvalidArgument => -32012,
+            ErrorCode::ChecksumMismatch => -32013,
+            ErrorCode::Busy => -32014,
+            ErrorCode::TooManySessions => -32015,
+            ErrorCode::DiskFull => -32016,
+            ErrorCode::QuotaExceeded => -32017,
+            ErrorCode::ConnectionLost => -32018,
+            ErrorCode::Unsupported => -32019,
+            ErrorCode::StaleHandle => -32020,
+            ErrorCode::Unknown => -32021,
+            ErrorCode::KeyDecryptFailed => -32022,
+            ErrorCode::KeyParseError => -32023,
+        }
+    }
+
+    /// [`Self::as_i32`] 的反函数，未知数字返回 `None`（例如收到了比当前版本更新的
+    /// 数字——新版本后端升级、前端还没跟上时可能发生）
+    pub fn from_i32(code: i32) -> Option<Self> {
+        Some(match code {
+            -32000 => ErrorCode::AuthFailed,
+            -32001 => ErrorCode::HostkeyMismatch,
+            -32002 => ErrorCode::HostkeyRevoked,
+            -32003 => ErrorCode::Timeout,
+            -32004 => ErrorCode::NetworkLost,
+            -32005 => ErrorCode::NotFound,
+            -32006 => ErrorCode::PermissionDenied,
+            -32007 => ErrorCode::DirNotEmpty,
+            -32008 => ErrorCode::AlreadyExists,
+            -32009 => ErrorCode::LocalIoError,
+            -32010 => ErrorCode::RemoteIoError,
+            -32011 => ErrorCode::Canceled,
+            -32012 => ErrorCode::InvalidArgument,
+            -32013 => ErrorCode::ChecksumMismatch,
+            -32014 => ErrorCode::Busy,
+            -32015 => ErrorCode::TooManySessions,
+            -32016 => ErrorCode::DiskFull,
+            -32017 => ErrorCode::QuotaExceeded,
+            -32018 => ErrorCode::ConnectionLost,
+            -32019 => ErrorCode::Unsupported,
+            -32020 => ErrorCode::StaleHandle,
+            -32021 => ErrorCode::Unknown,
+            -32022 => ErrorCode::KeyDecryptFailed,
+            -32023 => ErrorCode::KeyParseError,
+            _ => return None,
+        })
+    }
+}
+
 /// 统一错误模型
-#[derive(Debug, Clone, Serialize, Deserialize, Error)]
+#[derive(Debug, Clone, Deserialize, Error)]
 #[error("{message}")]
 pub struct AppError {
     pub code: ErrorCode,
@@ -32,6 +126,18 @@ pub struct AppError {
     pub retryable: Option<bool>,
 }
 
+/// `#[tauri::command]` 的 `Err` 分支经由 `Serialize` 序列化后原样发给前端——这是
+/// 唯一的出口，把它接到 [`AppError::to_rpc_error`] 上，就等于让所有命令统一返回
+/// JSON-RPC 风格的错误信封，不需要逐个命令手动转换
+impl Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_rpc_error().serialize(serializer)
+    }
+}
+
 impl AppError {
     pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
@@ -52,6 +158,21 @@ impl AppError {
         self
     }
 
+    /// 转成 JSON-RPC 风格的错误信封：`code` 是 [`ErrorCode::as_i32`] 的稳定数字，
+    /// `data.code` 保留原本的 `SCREAMING_SNAKE_CASE` 字符串方便人读日志，两者同时
+    /// 存在是为了让前端既能用数字做稳定匹配，又不用在调试时对照一张数字表
+    pub fn to_rpc_error(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.code.as_i32(),
+            "message": self.message,
+            "data": {
+                "code": self.code,
+                "detail": self.detail,
+                "retryable": self.retryable,
+            }
+        })
+    }
+
     // 便捷构造方法
     pub fn auth_failed(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::AuthFailed, message)
@@ -61,6 +182,13 @@ impl AppError {
         Self::new(ErrorCode::HostkeyMismatch, message)
     }
 
+    /// 主机密钥已被用户/管理员标记为撤销（如从 OpenSSH `known_hosts` 的
+    /// `@revoked` 行导入），与 [`Self::hostkey_mismatch`] 的区别在于：
+    /// 这是明确的拒绝名单，而不是"指纹变了，可能是攻击也可能是服务器重装"
+    pub fn hostkey_revoked(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::HostkeyRevoked, message).with_retryable(false)
+    }
+
     pub fn timeout(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::Timeout, message).with_retryable(true)
     }
@@ -100,6 +228,56 @@ impl AppError {
     pub fn invalid_argument(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::InvalidArgument, message)
     }
+
+    pub fn checksum_mismatch(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ChecksumMismatch, message).with_retryable(true)
+    }
+
+    pub fn busy(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Busy, message).with_retryable(true)
+    }
+
+    /// 会话池已达到上限（全局或单 Profile），不可重试——需要用户先断开一些
+    /// 会话，或调整 `SessionManagerConfig` 里的上限
+    pub fn too_many_sessions(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::TooManySessions, message).with_retryable(false)
+    }
+
+    /// 远程磁盘空间耗尽或无可用介质，需要用户先清理空间，重试无意义
+    pub fn disk_full(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::DiskFull, message).with_retryable(false)
+    }
+
+    /// 远程账号配额已用尽，需要用户/管理员处理配额，重试无意义
+    pub fn quota_exceeded(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::QuotaExceeded, message).with_retryable(false)
+    }
+
+    /// SFTP 子系统报告连接已断开，与 [`Self::network_lost`] 同属"可以重连再试"的一类
+    pub fn connection_lost(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ConnectionLost, message).with_retryable(true)
+    }
+
+    /// 远程服务器不支持请求的操作，换种方式调用也不会成功，重试无意义
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unsupported, message).with_retryable(false)
+    }
+
+    /// 文件句柄已失效（通常是远程超时关闭了句柄），重新打开文件再操作一次往往能恢复
+    pub fn stale_handle(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::StaleHandle, message).with_retryable(true)
+    }
+
+    /// 加密私钥解密失败（密码错误或数据被篡改），重试同一个密码没有意义，需要用户
+    /// 重新输入密码
+    pub fn key_decrypt_failed(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::KeyDecryptFailed, message).with_retryable(false)
+    }
+
+    /// 私钥文件无法解析（格式不合法或使用了不支持的套件），换个密码也无济于事
+    pub fn key_parse_error(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::KeyParseError, message).with_retryable(false)
+    }
 }
 
 // 从 ssh2::Error 转换
@@ -131,7 +309,36 @@ impl From<ssh2::Error> for AppError {
                 // LIBSSH2_FX_FILE_ALREADY_EXISTS
                 AppError::already_exists(message)
             }
-            _ => AppError::new(ErrorCode::RemoteIoError, message),
+            ssh2::ErrorCode::SFTP(8) => {
+                // LIBSSH2_FX_OP_UNSUPPORTED
+                AppError::unsupported(message)
+            }
+            ssh2::ErrorCode::SFTP(9) => {
+                // LIBSSH2_FX_INVALID_HANDLE
+                AppError::stale_handle(message)
+            }
+            ssh2::ErrorCode::SFTP(10) => {
+                // LIBSSH2_FX_NO_CONNECTION
+                AppError::connection_lost(message)
+            }
+            ssh2::ErrorCode::SFTP(13) => {
+                // LIBSSH2_FX_NO_MEDIA
+                AppError::disk_full(message)
+            }
+            ssh2::ErrorCode::SFTP(14) => {
+                // LIBSSH2_FX_NO_SPACE_ON_FILESYSTEM
+                AppError::disk_full(message)
+            }
+            ssh2::ErrorCode::SFTP(15) => {
+                // LIBSSH2_FX_QUOTA_EXCEEDED
+                AppError::quota_exceeded(message)
+            }
+            // 未识别的 SFTP/Session 错误码：保留原始数字码在 detail 里，而不是
+            // 彻底丢弃成一个无法区分来源的 RemoteIoError
+            ssh2::ErrorCode::SFTP(code) => AppError::new(ErrorCode::RemoteIoError, message)
+                .with_detail(format!("SFTP 错误码: {}", code)),
+            ssh2::ErrorCode::Session(code) => AppError::new(ErrorCode::RemoteIoError, message)
+                .with_detail(format!("SSH 会话错误码: {}", code)),
         }
     }
 }
@@ -216,4 +423,86 @@ mod tests {
         let json = serde_json::to_string(&code).unwrap();
         assert_eq!(json, "\"HOSTKEY_MISMATCH\"");
     }
+
+    /// 数字一旦分配就硬编码在这里：这个断言挂了，说明有人改动或重排了已分配的号段
+    #[test]
+    fn test_error_code_numbers_are_pinned() {
+        assert_eq!(ErrorCode::AuthFailed.as_i32(), -32000);
+        assert_eq!(ErrorCode::HostkeyMismatch.as_i32(), -32001);
+        assert_eq!(ErrorCode::HostkeyRevoked.as_i32(), -32002);
+        assert_eq!(ErrorCode::Timeout.as_i32(), -32003);
+        assert_eq!(ErrorCode::NetworkLost.as_i32(), -32004);
+        assert_eq!(ErrorCode::NotFound.as_i32(), -32005);
+        assert_eq!(ErrorCode::PermissionDenied.as_i32(), -32006);
+        assert_eq!(ErrorCode::DirNotEmpty.as_i32(), -32007);
+        assert_eq!(ErrorCode::AlreadyExists.as_i32(), -32008);
+        assert_eq!(ErrorCode::LocalIoError.as_i32(), -32009);
+        assert_eq!(ErrorCode::RemoteIoError.as_i32(), -32010);
+        assert_eq!(ErrorCode::Canceled.as_i32(), -32011);
+        assert_eq!(ErrorCode::InvalidArgument.as_i32(), -32012);
+        assert_eq!(ErrorCode::ChecksumMismatch.as_i32(), -32013);
+        assert_eq!(ErrorCode::Busy.as_i32(), -32014);
+        assert_eq!(ErrorCode::TooManySessions.as_i32(), -32015);
+        assert_eq!(ErrorCode::DiskFull.as_i32(), -32016);
+        assert_eq!(ErrorCode::QuotaExceeded.as_i32(), -32017);
+        assert_eq!(ErrorCode::ConnectionLost.as_i32(), -32018);
+        assert_eq!(ErrorCode::Unsupported.as_i32(), -32019);
+        assert_eq!(ErrorCode::StaleHandle.as_i32(), -32020);
+        assert_eq!(ErrorCode::Unknown.as_i32(), -32021);
+        assert_eq!(ErrorCode::KeyDecryptFailed.as_i32(), -32022);
+        assert_eq!(ErrorCode::KeyParseError.as_i32(), -32023);
+    }
+
+    #[test]
+    fn test_error_code_as_i32_from_i32_round_trip() {
+        let all = [
+            ErrorCode::AuthFailed,
+            ErrorCode::HostkeyMismatch,
+            ErrorCode::HostkeyRevoked,
+            ErrorCode::Timeout,
+            ErrorCode::NetworkLost,
+            ErrorCode::NotFound,
+            ErrorCode::PermissionDenied,
+            ErrorCode::DirNotEmpty,
+            ErrorCode::AlreadyExists,
+            ErrorCode::LocalIoError,
+            ErrorCode::RemoteIoError,
+            ErrorCode::Canceled,
+            ErrorCode::InvalidArgument,
+            ErrorCode::ChecksumMismatch,
+            ErrorCode::Busy,
+            ErrorCode::TooManySessions,
+            ErrorCode::DiskFull,
+            ErrorCode::QuotaExceeded,
+            ErrorCode::ConnectionLost,
+            ErrorCode::Unsupported,
+            ErrorCode::StaleHandle,
+            ErrorCode::Unknown,
+            ErrorCode::KeyDecryptFailed,
+            ErrorCode::KeyParseError,
+        ];
+        for code in all {
+            assert_eq!(ErrorCode::from_i32(code.as_i32()), Some(code));
+        }
+    }
+
+    #[test]
+    fn test_error_code_from_i32_rejects_unknown_number() {
+        assert_eq!(ErrorCode::from_i32(-1), None);
+        assert_eq!(ErrorCode::from_i32(-32099), None);
+    }
+
+    #[test]
+    fn test_to_rpc_error_envelope_shape() {
+        let err = AppError::auth_failed("认证失败")
+            .with_detail("密码错误")
+            .with_retryable(false);
+        let rpc = err.to_rpc_error();
+
+        assert_eq!(rpc["code"], serde_json::json!(-32000));
+        assert_eq!(rpc["message"], serde_json::json!("认证失败"));
+        assert_eq!(rpc["data"]["code"], serde_json::json!("AUTH_FAILED"));
+        assert_eq!(rpc["data"]["detail"], serde_json::json!("密码错误"));
+        assert_eq!(rpc["data"]["retryable"], serde_json::json!(false));
+    }
 }
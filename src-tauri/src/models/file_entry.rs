@@ -15,6 +15,12 @@ pub struct FileEntry {
     /// 文件权限 (Unix mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<u32>,
+    /// 是否是符号链接（由 lstat 判断，不跟随）
+    #[serde(default)]
+    pub is_symlink: bool,
+    /// 符号链接指向的原始目标路径（非符号链接时为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
 }
 
 /// 排序字段
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// 密钥算法
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAlgorithm {
+    Ed25519,
+    Rsa2048,
+    Rsa4096,
+}
+
+/// 应用内托管的 SSH 密钥对
+///
+/// 私钥本身不落在这个结构体里——实际内容托管在系统安全存储/软件密钥库中
+/// （与 `Auth::Key::private_key_ref` 同一套方案），这里只保存可以公开展示的元数据，
+/// 供 `Auth::Key::managed_key_id` 引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedKey {
+    pub id: String,
+    /// 用户起的名称，便于在 Profile 里选择
+    pub name: String,
+    /// 密钥类型，如 `ssh-ed25519`、`ssh-rsa`
+    pub key_type: String,
+    /// 公钥内容（OpenSSH 单行格式，可直接追加到远程 `authorized_keys`）
+    pub public_key: String,
+    /// SHA256 指纹
+    pub fingerprint: String,
+    /// 私钥是否加密（需要 passphrase 才能使用）
+    pub encrypted: bool,
+    pub created_at: i64,
+}
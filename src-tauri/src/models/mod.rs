@@ -1,13 +1,27 @@
+pub mod audit;
+pub mod command;
 pub mod error;
 pub mod file_entry;
+pub mod key;
 pub mod profile;
+pub mod schedule;
+pub mod search;
+pub mod sensitive;
 pub mod settings;
 pub mod terminal;
 pub mod transfer_task;
+pub mod watch;
 
+pub use audit::*;
+pub use command::*;
 pub use error::*;
 pub use file_entry::*;
+pub use key::*;
 pub use profile::*;
+pub use schedule::*;
+pub use search::*;
+pub use sensitive::*;
 pub use settings::*;
 pub use terminal::*;
 pub use transfer_task::*;
+pub use watch::*;
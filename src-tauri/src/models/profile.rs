@@ -1,11 +1,129 @@
+use crate::models::sensitive::Sensitive;
 use serde::{Deserialize, Serialize};
 
-/// 认证方式
+/// 认证方式及其专属凭据
+///
+/// 早期版本用一个扁平的 `AuthType` 加几个互斥的 `Option` 字段
+/// （`password_ref`/`private_key_path`/`passphrase_ref`...）表示认证信息，这样
+/// `auth_type: Password` 却带着 `private_key_path` 这种没有意义的组合在类型上是
+/// 可以构造出来的，只能靠 `ProfileInput::validate` 人工把关。改成 tagged enum
+/// 后每个变体只携带自己用得上的字段，非法组合在类型层面就不可能出现。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Auth {
+    Password {
+        /// 密码引用 (指向系统安全存储的 key)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        password_ref: Option<String>,
+    },
+    Key {
+        /// 私钥路径（文件系统上的私钥文件，与 `private_key_ref`/`managed_key_id` 三选一）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key_path: Option<String>,
+        /// 私钥内容引用（指向系统安全存储，私钥文本本身托管在 keychain/密钥库，不落盘）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key_ref: Option<String>,
+        /// 引用的应用内托管密钥 ID（`key_generate` 创建），保存时会解析成对应的
+        /// `private_key_ref`，这里只是记一笔是哪个托管密钥，供 UI 展示
+        #[serde(skip_serializing_if = "Option::is_none")]
+        managed_key_id: Option<String>,
+        /// passphrase 引用
+        #[serde(skip_serializing_if = "Option::is_none")]
+        passphrase_ref: Option<String>,
+    },
+    /// 通过本机运行的 SSH agent（如 ssh-agent/Pageant）认证，不需要在 Profile 中
+    /// 保存任何密钥或口令——agent 持有私钥并代为完成签名
+    Agent,
+    /// keyboard-interactive（服务器发起的质询-应答，如 OTP 验证码、PAM 提示、
+    /// Duo 推送确认），具体要回答什么在握手前并不知道，需要连接时动态向用户要答案
+    #[serde(rename = "keyboardInteractive")]
+    KeyboardInteractive,
+}
+
+impl Auth {
+    /// 落库用的 `auth_type` 列值，与 `profiles` 表的 CHECK 约束一一对应
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            Auth::Password { .. } => "password",
+            Auth::Key { .. } => "key",
+            Auth::Agent => "agent",
+            Auth::KeyboardInteractive => "keyboard_interactive",
+        }
+    }
+
+    pub fn password_ref(&self) -> Option<&str> {
+        match self {
+            Auth::Password { password_ref } => password_ref.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn private_key_path(&self) -> Option<&str> {
+        match self {
+            Auth::Key { private_key_path, .. } => private_key_path.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn private_key_ref(&self) -> Option<&str> {
+        match self {
+            Auth::Key { private_key_ref, .. } => private_key_ref.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn passphrase_ref(&self) -> Option<&str> {
+        match self {
+            Auth::Key { passphrase_ref, .. } => passphrase_ref.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn managed_key_id(&self) -> Option<&str> {
+        match self {
+            Auth::Key { managed_key_id, .. } => managed_key_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// 按 `profiles` 表的扁平列拼回 `Auth`；DB schema 本身没有改动（仍然是一个
+    /// `auth_type` 列 + 几个可空的凭据引用列），只是读出来之后不再是互相独立的
+    /// 字段，而是按 `auth_type` 组装成对应的变体。未知的 `auth_type` 按 Password
+    /// 处理，兼容早期 `parse_auth_type` 的兜底语义。
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_columns(
+        auth_type: &str,
+        password_ref: Option<String>,
+        private_key_path: Option<String>,
+        passphrase_ref: Option<String>,
+        private_key_ref: Option<String>,
+        managed_key_id: Option<String>,
+    ) -> Self {
+        match auth_type {
+            "key" => Auth::Key {
+                private_key_path,
+                private_key_ref,
+                managed_key_id,
+                passphrase_ref,
+            },
+            "agent" => Auth::Agent,
+            "keyboard_interactive" => Auth::KeyboardInteractive,
+            _ => Auth::Password { password_ref },
+        }
+    }
+}
+
+/// `ssh_config`/批量导入场景下的认证方式标记——这类场景只是在预览"即将生成哪些
+/// Profile"，从不携带真实凭据，不需要 [`Auth`] 那一整套按变体携带字段的复杂度，
+/// 扁平的判别值就够用
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum AuthType {
     Password,
     Key,
+    Agent,
+    #[serde(rename = "keyboardInteractive")]
+    KeyboardInteractive,
 }
 
 /// 连接配置
@@ -17,25 +135,66 @@ pub struct Profile {
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub auth_type: AuthType,
-    /// 密码引用 (指向系统安全存储的 key)
+    pub auth: Auth,
+    /// 初始远程路径
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub password_ref: Option<String>,
-    /// 私钥路径
+    pub initial_path: Option<String>,
+    /// 允许的 HostKey 算法（逗号分隔，如 `ssh-rsa,ssh-ed25519`），为空时使用
+    /// libssh2 的安全默认值；仅用于兼容只提供旧算法的服务器
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub private_key_path: Option<String>,
-    /// passphrase 引用
+    pub host_key_algorithms: Option<String>,
+    /// 允许的密钥交换算法（逗号分隔），语义同 `host_key_algorithms`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub passphrase_ref: Option<String>,
-    /// 初始远程路径
+    pub kex_algorithms: Option<String>,
+    /// 允许的对称加密算法（逗号分隔），语义同 `host_key_algorithms`，
+    /// 同时应用于上行和下行两个方向
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub initial_path: Option<String>,
+    pub ciphers: Option<String>,
     /// 创建时间 (Unix 时间戳毫秒)
     pub created_at: i64,
     /// 更新时间 (Unix 时间戳毫秒)
     pub updated_at: i64,
 }
 
+/// 创建/更新连接配置的认证输入，与 [`Auth`] 一一对应，但携带的是"这次要不要
+/// 记住"的原始凭据（密码/私钥内容明文），而不是已经落库的引用
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuthInput {
+    Password {
+        /// 密码 (仅用于输入，不会存储在 Profile 中)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        password: Option<Sensitive<String>>,
+        /// 是否记住密码
+        #[serde(default)]
+        remember_password: bool,
+    },
+    Key {
+        /// 私钥路径（与 `private_key_content`/`managed_key_id` 三选一）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key_path: Option<String>,
+        /// 私钥内容 (仅用于输入，托管在系统安全存储，不会落盘)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key_content: Option<String>,
+        /// 是否记住私钥内容（为 false 时 `private_key_content` 仅用于本次保存时的格式校验，不落库）
+        #[serde(default)]
+        remember_private_key: bool,
+        /// 引用应用内托管密钥（`key_generate` 创建）而不是粘贴/指定路径，与
+        /// `private_key_path`/`private_key_content` 三选一
+        #[serde(skip_serializing_if = "Option::is_none")]
+        managed_key_id: Option<String>,
+        /// passphrase (仅用于输入)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        passphrase: Option<Sensitive<String>>,
+        /// 是否记住 passphrase
+        #[serde(default)]
+        remember_passphrase: bool,
+    },
+    Agent,
+    #[serde(rename = "keyboardInteractive")]
+    KeyboardInteractive,
+}
+
 /// 创建/更新连接配置的输入
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -46,29 +205,27 @@ pub struct ProfileInput {
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub auth_type: AuthType,
-    /// 密码 (仅用于输入，不会存储在 Profile 中)
+    pub auth: AuthInput,
+    /// 初始远程路径
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub password: Option<String>,
-    /// 是否记住密码
-    #[serde(default)]
-    pub remember_password: bool,
-    /// 私钥路径
+    pub initial_path: Option<String>,
+    /// 允许的 HostKey 算法（逗号分隔），语义同 [`Profile::host_key_algorithms`]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub private_key_path: Option<String>,
-    /// passphrase (仅用于输入)
+    pub host_key_algorithms: Option<String>,
+    /// 允许的密钥交换算法（逗号分隔），语义同 [`Profile::kex_algorithms`]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub passphrase: Option<String>,
-    /// 是否记住 passphrase
-    #[serde(default)]
-    pub remember_passphrase: bool,
-    /// 初始远程路径
+    pub kex_algorithms: Option<String>,
+    /// 允许的对称加密算法（逗号分隔），语义同 [`Profile::ciphers`]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub initial_path: Option<String>,
+    pub ciphers: Option<String>,
 }
 
 impl ProfileInput {
     /// 验证输入参数
+    ///
+    /// `auth` 已经是 tagged enum，各变体互斥的凭据组合在类型上就不可能拼错，
+    /// 这里只需要再校验 Key 变体里"私钥来源三选一不能一个都没填"这一条，
+    /// 不再需要人工检查 auth_type 与凭据字段是否匹配
     pub fn validate(&self) -> Result<(), String> {
         // 名称不能为空
         if self.name.trim().is_empty() {
@@ -95,15 +252,121 @@ impl ProfileInput {
             return Err("用户名不能为空".to_string());
         }
 
-        // Key 认证时必须提供私钥路径
-        if self.auth_type == AuthType::Key && self.private_key_path.is_none() {
-            return Err("Key 认证方式需要提供私钥路径".to_string());
+        // Key 认证时必须提供私钥路径、直接粘贴私钥内容，或引用一个应用内托管密钥（三选一）
+        if let AuthInput::Key {
+            private_key_path,
+            private_key_content,
+            managed_key_id,
+            ..
+        } = &self.auth
+        {
+            if private_key_path.is_none() && private_key_content.is_none() && managed_key_id.is_none() {
+                return Err("Key 认证方式需要提供私钥路径、私钥内容，或选择一个托管密钥".to_string());
+            }
         }
 
         Ok(())
     }
 }
 
+impl std::str::FromStr for ProfileInput {
+    type Err = String;
+
+    /// 解析标准 SSH 连接 URI：`ssh://user@host[:port][/initial/path]`
+    ///
+    /// 参照 distant 的 `distant://[username]:{key}@{host}:{port}` 凭据字符串思路，给用户一条
+    /// 可以直接粘贴分享的连接目标；`auth` 固定为 [`AuthInput::Agent`]，因为 URI 里从不
+    /// 携带密码/私钥这类机密材料，具体认证方式留给用户在导入后的表单里调整。
+    /// IPv6 字面量需要按 RFC 3986 用方括号包住，如 `ssh://user@[::1]:22`
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let rest = uri
+            .strip_prefix("ssh://")
+            .ok_or_else(|| "URI 必须以 ssh:// 开头".to_string())?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], Some(rest[idx..].to_string())),
+            None => (rest, None),
+        };
+
+        let (userinfo, host_port) = match authority.rfind('@') {
+            Some(idx) => (Some(&authority[..idx]), &authority[idx + 1..]),
+            None => (None, authority),
+        };
+
+        let username = match userinfo {
+            Some(u) if !u.is_empty() => u.to_string(),
+            _ => return Err("URI 缺少用户名".to_string()),
+        };
+
+        if host_port.is_empty() {
+            return Err("URI 缺少主机地址".to_string());
+        }
+
+        let (host, port) = if let Some(after_bracket) = host_port.strip_prefix('[') {
+            let close = after_bracket
+                .find(']')
+                .ok_or_else(|| "IPv6 地址缺少右方括号".to_string())?;
+            let host = after_bracket[..close].to_string();
+            let port = match after_bracket[close + 1..].strip_prefix(':') {
+                Some(p) => p.parse::<u16>().map_err(|_| "端口号不合法".to_string())?,
+                None => 22,
+            };
+            (host, port)
+        } else {
+            match host_port.rsplit_once(':') {
+                Some((h, p)) => (
+                    h.to_string(),
+                    p.parse::<u16>().map_err(|_| "端口号不合法".to_string())?,
+                ),
+                None => (host_port.to_string(), 22),
+            }
+        };
+
+        if host.is_empty() {
+            return Err("URI 缺少主机地址".to_string());
+        }
+
+        let initial_path = path.filter(|p| p != "/" && !p.is_empty());
+
+        Ok(ProfileInput {
+            id: None,
+            name: format!("{}@{}", username, host),
+            host,
+            port,
+            username,
+            auth: AuthInput::Agent,
+            initial_path,
+            host_key_algorithms: None,
+            kex_algorithms: None,
+            ciphers: None,
+        })
+    }
+}
+
+impl std::fmt::Display for Profile {
+    /// 序列化为 `ssh://user@host[:port][/initial/path]`，与 [`ProfileInput::from_str`] 互逆；
+    /// 从不携带密码/私钥等机密材料，端口为默认值 22 时省略
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let host_part = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        write!(f, "ssh://{}@{}", self.username, host_part)?;
+        if self.port != 22 {
+            write!(f, ":{}", self.port)?;
+        }
+        if let Some(path) = self.initial_path.as_deref().filter(|p| !p.is_empty()) {
+            if path.starts_with('/') {
+                write!(f, "{}", path)?;
+            } else {
+                write!(f, "/{}", path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// 最近连接记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -114,4 +377,6 @@ pub struct RecentConnection {
     pub host: String,
     pub username: String,
     pub connected_at: i64,
+    /// 累计连接次数，用于 frecency 排序（见 `recent_connections_list`）
+    pub visit_count: i32,
 }
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::transfer_task::TransferDirection;
+
+/// 同步计划的触发规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ScheduleRecurrence {
+    /// 每隔固定分钟数触发一次
+    EveryMinutes { minutes: u32 },
+    /// 每天在指定时间触发一次（24 小时制，本地时间）
+    DailyAt { hour: u8, minute: u8 },
+}
+
+/// 目录同步计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSchedule {
+    pub schedule_id: String,
+    pub session_id: String,
+    pub local_dir: String,
+    pub remote_dir: String,
+    pub direction: TransferDirection,
+    /// 单向镜像：同步后删除目标侧多出的文件
+    pub mirror: bool,
+    pub recurrence: ScheduleRecurrence,
+    pub enabled: bool,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_at: Option<i64>,
+    pub next_run_at: i64,
+}
+
+/// 一次计划运行的结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleRunStatus {
+    Started,
+    /// 两侧未发现差异，本次运行未入队任何任务
+    Skipped,
+    Completed,
+    Failed,
+}
+
+/// 计划运行状态事件 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRunPayload {
+    pub schedule_id: String,
+    pub status: ScheduleRunStatus,
+    /// Completed 时入队的传输任务数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files_queued: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
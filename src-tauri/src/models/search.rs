@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// 搜索查询参数
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    /// 搜索的根目录
+    pub root_path: String,
+    /// 匹配模式（正则或字面量，取决于 is_regex）
+    pub pattern: String,
+    /// 是否将 pattern 当作正则表达式
+    #[serde(default)]
+    pub is_regex: bool,
+    /// 是否区分大小写
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// 文件名包含过滤（glob），为空表示不限制
+    #[serde(default)]
+    pub include_globs: Vec<String>,
+    /// 文件名排除过滤（glob）
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// 最大递归深度，None 表示不限制
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// 最多返回的匹配数，达到后自动停止
+    #[serde(default)]
+    pub max_results: Option<u32>,
+    /// 是否搜索文件内容；为 false 时仅匹配文件名
+    #[serde(default = "default_search_contents")]
+    pub search_contents: bool,
+}
+
+fn default_search_contents() -> bool {
+    true
+}
+
+/// 单条匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub path: String,
+    /// 内容匹配的行号（从 1 开始）；文件名匹配时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<u32>,
+    /// 匹配所在行的文本；文件名匹配时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    /// 匹配行在文件中的字节偏移量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,
+}
+
+/// 搜索状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchStatus {
+    Running,
+    Completed,
+    Canceled,
+    Error,
+}
+
+/// 增量结果批次事件 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultBatchPayload {
+    pub search_id: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// 搜索状态事件 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchStatusPayload {
+    pub search_id: String,
+    pub status: SearchStatus,
+    /// 已找到的匹配总数
+    pub matched_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
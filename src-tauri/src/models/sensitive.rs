@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 包裹敏感数据（密码、passphrase、指纹等）的 newtype，防止被 `{:?}`/`tracing` 等不经意
+/// 打印出明文——`Debug`/`Display` 统一渲染为 `***REDACTED***`，序列化/反序列化原样透传，
+/// 不影响短暂驻留在内存中的输入/查询流程
+///
+/// 思路借鉴 Lemmy 标记数据库敏感字段的 `Sensitive<T>` 做法
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// 显式取出内部明文；调用处的命名强调"这里确实需要拿到真实值"，
+    /// 不会像 `Deref`/`Display` 那样被无意中打到日志里
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl Sensitive<String> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "***REDACTED***")
+    }
+}
+
+impl<T> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "***REDACTED***")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_and_display_redact() {
+        let secret = Sensitive::new("hunter2".to_string());
+        assert_eq!(format!("{:?}", secret), "***REDACTED***");
+        assert_eq!(format!("{}", secret), "***REDACTED***");
+        assert_eq!(secret.expose_secret(), "hunter2");
+    }
+
+    #[test]
+    fn test_serde_roundtrip_is_transparent() {
+        let secret = Sensitive::new("hunter2".to_string());
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json, "\"hunter2\"");
+
+        let restored: Sensitive<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.expose_secret(), "hunter2");
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// 日志级别
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Error,
@@ -46,6 +46,38 @@ pub struct Settings {
     pub transfer_retry_count: u8,
     /// 日志级别
     pub log_level: LogLevel,
+    /// 触发多流并行传输的文件大小阈值 (MB)，单个文件达到此大小才会尝试并行传输
+    pub parallel_transfer_threshold_mb: u32,
+    /// 多流并行传输的流数量 (1-8)；为 1 时等同于禁用并行传输
+    pub parallel_transfer_streams: u8,
+    /// 传输完成后是否将源文件的权限和修改时间应用到目标文件
+    pub preserve_file_metadata: bool,
+    /// 全局限速 (KB/s)，0 表示不限速
+    pub speed_limit_kbps: u32,
+    /// 传输成功后是否校验远程文件的校验和
+    pub verify_transfer_checksum: bool,
+    /// 远程计算校验和使用的命令（如 `sha256sum`、`shasum -a 256`），需输出 sha256 十六进制摘要
+    pub checksum_command: String,
+    /// 触发校验和校验的文件大小阈值 (MB)；`verify_transfer_checksum` 开启时，
+    /// 仅达到此大小的文件才会在传输完成后校验，避免小文件也承担一次额外的远程
+    /// 摘要计算开销。单个任务可通过 `TransferTask::verify_checksum_override` 覆盖此默认值。
+    pub checksum_verify_min_size_mb: u32,
+    /// 单流传输时本地读写两侧预读/预写缓冲的深度（块数），大于 1 时读取与写入在
+    /// 独立线程中重叠进行，缓解高延迟链路下"读一块等一块"造成的管道空闲
+    pub pipeline_window_size: u8,
+    /// 同时打开的本地文件句柄数上限 (1-64)，独立于 `max_concurrent_transfers`；
+    /// 用于在单个传输任务内部开启多个本地文件句柄（多流并行传输、预读/预写线程）时，
+    /// 避免总句柄数超出操作系统的文件描述符上限 (EMFILE)
+    pub max_open_local_files: u32,
+    /// 终端空闲超时时间（秒），超过此时长无输入/输出的终端会被自动关闭；0 表示禁用
+    pub terminal_idle_timeout_secs: u32,
+    /// 传输历史保留天数，维护任务清理早于 `now - retention_days` 的记录
+    /// （见 `Database::run_maintenance`）
+    pub retention_days: u32,
+    /// "镜像模式"目标文件路径；配置后，新信任的 HostKey 会追加写入此
+    /// OpenSSH `known_hosts` 文件，供系统自带的 ssh/scp 等工具识别
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_hosts_mirror_path: Option<String>,
 }
 
 impl Default for Settings {
@@ -56,6 +88,80 @@ impl Default for Settings {
             connection_timeout_secs: 30,
             transfer_retry_count: 2,
             log_level: LogLevel::Info,
+            parallel_transfer_threshold_mb: 32,
+            parallel_transfer_streams: 4,
+            preserve_file_metadata: true,
+            speed_limit_kbps: 0,
+            verify_transfer_checksum: false,
+            checksum_command: "sha256sum".to_string(),
+            checksum_verify_min_size_mb: 10,
+            pipeline_window_size: 4,
+            max_open_local_files: 16,
+            terminal_idle_timeout_secs: 0,
+            retention_days: 90,
+            known_hosts_mirror_path: None,
+        }
+    }
+}
+
+impl Settings {
+    /// 将 `patch` 中提供的字段合并进当前配置，未提供的字段保持不变
+    ///
+    /// 数值字段的取值范围约束（clamp/min/max）集中在这里，供数据库覆盖层
+    /// （[`crate::services::storage_service::Database::settings_update`]）与
+    /// 配置文件/环境变量覆盖层（[`crate::services::config_loader::ConfigLoader`]）共用，
+    /// 避免两处各自实现一套范围校验后出现偏差
+    pub fn apply_patch(&mut self, patch: &SettingsPatch) {
+        if let Some(v) = &patch.default_download_dir {
+            self.default_download_dir = Some(v.clone());
+        }
+        if let Some(v) = patch.max_concurrent_transfers {
+            self.max_concurrent_transfers = v.clamp(1, 6);
+        }
+        if let Some(v) = patch.connection_timeout_secs {
+            self.connection_timeout_secs = v.clamp(5, 300);
+        }
+        if let Some(v) = patch.transfer_retry_count {
+            self.transfer_retry_count = v.min(5);
+        }
+        if let Some(v) = &patch.log_level {
+            self.log_level = v.clone();
+        }
+        if let Some(v) = patch.parallel_transfer_threshold_mb {
+            self.parallel_transfer_threshold_mb = v.max(1);
+        }
+        if let Some(v) = patch.parallel_transfer_streams {
+            self.parallel_transfer_streams = v.clamp(1, 8);
+        }
+        if let Some(v) = patch.preserve_file_metadata {
+            self.preserve_file_metadata = v;
+        }
+        if let Some(v) = patch.speed_limit_kbps {
+            self.speed_limit_kbps = v;
+        }
+        if let Some(v) = patch.verify_transfer_checksum {
+            self.verify_transfer_checksum = v;
+        }
+        if let Some(v) = &patch.checksum_command {
+            self.checksum_command = v.clone();
+        }
+        if let Some(v) = patch.checksum_verify_min_size_mb {
+            self.checksum_verify_min_size_mb = v;
+        }
+        if let Some(v) = patch.pipeline_window_size {
+            self.pipeline_window_size = v.clamp(1, 32);
+        }
+        if let Some(v) = patch.max_open_local_files {
+            self.max_open_local_files = v.clamp(1, 64);
+        }
+        if let Some(v) = patch.terminal_idle_timeout_secs {
+            self.terminal_idle_timeout_secs = v;
+        }
+        if let Some(v) = patch.retention_days {
+            self.retention_days = v.clamp(1, 3650);
+        }
+        if let Some(v) = &patch.known_hosts_mirror_path {
+            self.known_hosts_mirror_path = if v.is_empty() { None } else { Some(v.clone()) };
         }
     }
 }
@@ -74,4 +180,131 @@ pub struct SettingsPatch {
     pub transfer_retry_count: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub log_level: Option<LogLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_transfer_threshold_mb: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_transfer_streams: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preserve_file_metadata: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_limit_kbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_transfer_checksum: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_verify_min_size_mb: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline_window_size: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_open_local_files: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_idle_timeout_secs: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+    /// 传入空字符串表示关闭镜像模式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_hosts_mirror_path: Option<String>,
+}
+
+/// 分层配置的来源层级，数值靠后的层级覆盖靠前的层级
+/// （见 [`crate::services::config_loader::ConfigLoader`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    #[default]
+    Default,
+    File,
+    Env,
+    Database,
+}
+
+/// 与 [`Settings`] 字段一一对应的来源归属表，标注每个生效值来自哪一层，
+/// 供前端在设置界面提示"此项由部署文件/环境变量锁定"
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsProvenance {
+    pub default_download_dir: ConfigSource,
+    pub max_concurrent_transfers: ConfigSource,
+    pub connection_timeout_secs: ConfigSource,
+    pub transfer_retry_count: ConfigSource,
+    pub log_level: ConfigSource,
+    pub parallel_transfer_threshold_mb: ConfigSource,
+    pub parallel_transfer_streams: ConfigSource,
+    pub preserve_file_metadata: ConfigSource,
+    pub speed_limit_kbps: ConfigSource,
+    pub verify_transfer_checksum: ConfigSource,
+    pub checksum_command: ConfigSource,
+    pub checksum_verify_min_size_mb: ConfigSource,
+    pub pipeline_window_size: ConfigSource,
+    pub max_open_local_files: ConfigSource,
+    pub terminal_idle_timeout_secs: ConfigSource,
+    pub retention_days: ConfigSource,
+    pub known_hosts_mirror_path: ConfigSource,
+}
+
+impl SettingsProvenance {
+    /// 把 `patch` 中出现的字段标记为来自 `source`，其余字段不受影响
+    pub fn mark_patch(&mut self, patch: &SettingsPatch, source: ConfigSource) {
+        if patch.default_download_dir.is_some() {
+            self.default_download_dir = source;
+        }
+        if patch.max_concurrent_transfers.is_some() {
+            self.max_concurrent_transfers = source;
+        }
+        if patch.connection_timeout_secs.is_some() {
+            self.connection_timeout_secs = source;
+        }
+        if patch.transfer_retry_count.is_some() {
+            self.transfer_retry_count = source;
+        }
+        if patch.log_level.is_some() {
+            self.log_level = source;
+        }
+        if patch.parallel_transfer_threshold_mb.is_some() {
+            self.parallel_transfer_threshold_mb = source;
+        }
+        if patch.parallel_transfer_streams.is_some() {
+            self.parallel_transfer_streams = source;
+        }
+        if patch.preserve_file_metadata.is_some() {
+            self.preserve_file_metadata = source;
+        }
+        if patch.speed_limit_kbps.is_some() {
+            self.speed_limit_kbps = source;
+        }
+        if patch.verify_transfer_checksum.is_some() {
+            self.verify_transfer_checksum = source;
+        }
+        if patch.checksum_command.is_some() {
+            self.checksum_command = source;
+        }
+        if patch.checksum_verify_min_size_mb.is_some() {
+            self.checksum_verify_min_size_mb = source;
+        }
+        if patch.pipeline_window_size.is_some() {
+            self.pipeline_window_size = source;
+        }
+        if patch.max_open_local_files.is_some() {
+            self.max_open_local_files = source;
+        }
+        if patch.terminal_idle_timeout_secs.is_some() {
+            self.terminal_idle_timeout_secs = source;
+        }
+        if patch.retention_days.is_some() {
+            self.retention_days = source;
+        }
+        if patch.known_hosts_mirror_path.is_some() {
+            self.known_hosts_mirror_path = source;
+        }
+    }
+}
+
+/// `settings_get` 返回的生效配置视图：合并后的值 + 每个字段的来源归属
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveSettings {
+    #[serde(flatten)]
+    pub settings: Settings,
+    pub provenance: SettingsProvenance,
 }
@@ -36,4 +36,7 @@ pub struct TerminalStatusPayload {
     pub status: TerminalStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 输出 channel 持续拥塞时被丢弃的累计字节数；正常状态下为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_bytes: Option<u64>,
 }
@@ -55,6 +55,40 @@ pub struct TransferTask {
     /// 完成时间 (Unix 时间戳毫秒)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<i64>,
+    /// 断点续传的起始偏移量（字节）。创建任务时为 None，失败后若通过一致性校验则在重试时设置。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_offset: Option<u64>,
+    /// 本地源文件的修改时间 (Unix 时间戳毫秒)，用于断点续传前校验本地文件未被修改。
+    /// 仅上传时在任务创建时捕获；下载方向的一致性改为校验本地部分文件大小。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_mtime: Option<i64>,
+    /// 本任务的限速覆盖值 (字节/秒)。为 `None` 时使用管理器的全局限速设置；
+    /// 为 `Some(0)` 时表示本任务显式不限速，忽略全局设置。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_limit_bytes_per_sec: Option<u64>,
+    /// 本次执行是否从 `resume_offset` 续传（而非从零开始）。与 `resume_offset.is_some()`
+    /// 等价，单独暴露此字段便于前端无需判断 `Option` 即可展示“续传中”还是“重新开始”
+    pub resumable: bool,
+    /// 已自动重试次数
+    pub retry_count: u8,
+    /// 下一次自动重试的计划时间 (Unix 时间戳毫秒)。为 `Some` 时表示当前的 `Failed` 状态
+    /// 只是退避等待中的中间态，任务会在该时间点自动被重新排队，而非真正的终态失败
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry_at: Option<i64>,
+    /// 所属批量任务的 ID。目录递归传输时，每个文件任务都指向其父批量任务；
+    /// 普通单文件任务与批量任务本身此字段为 `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_task_id: Option<String>,
+    /// 是否为批量任务（目录递归传输的聚合父任务）。批量任务不对应任何具体文件，
+    /// 不会被 [`crate::services::transfer_manager::TransferManager::execute_task`] 派发执行，
+    /// 其 `status`/`transferred`/`total` 由子任务驱动聚合（见 `recompute_batch`）
+    #[serde(default)]
+    pub is_batch: bool,
+    /// 本任务的校验和校验覆盖值。为 `None` 时按管理器的全局开关与
+    /// `checksum_verify_min_size_mb` 大小阈值自动判断是否校验；为 `Some(true)`/`Some(false)`
+    /// 时无条件对本任务启用/禁用校验，忽略全局开关与大小阈值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_checksum_override: Option<bool>,
 }
 
 /// 传输进度事件 payload
@@ -78,4 +112,30 @@ pub struct TransferStatusPayload {
     pub error_code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// 传输成功且启用了校验和校验时，本次传输内容的 SHA-256 摘要
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+/// 目录增量同步完成后的结果摘要事件 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirSyncResultPayload {
+    pub session_id: String,
+    /// 两侧已一致、未创建传输任务的文件数
+    pub skipped: u32,
+    /// 新增或已变化、已创建传输任务的文件数
+    pub transferred: u32,
+    /// 镜像模式下删除的目标侧多余文件数
+    pub deleted: u32,
+}
+
+/// 目录递归传输创建结果：批量父任务 ID 及其下每个文件的子任务 ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirTransferResult {
+    /// 聚合父任务 ID，用于跟踪整个目录传输的合计进度与状态
+    pub batch_id: String,
+    /// 按文件创建的子任务 ID
+    pub task_ids: Vec<String>,
 }
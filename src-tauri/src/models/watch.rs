@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::file_entry::FileEntry;
+
+/// 监视事件类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchEventKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+/// 远程目录监视信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchInfo {
+    pub watch_id: String,
+    pub session_id: String,
+    pub path: String,
+}
+
+/// 监视变更事件 payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchEventPayload {
+    pub watch_id: String,
+    pub session_id: String,
+    pub path: String,
+    pub kind: WatchEventKind,
+    /// 发生变更的条目路径
+    pub entry_path: String,
+    /// Renamed 事件中变更前的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// 变更后的条目信息（Removed 事件中为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<FileEntry>,
+}
@@ -0,0 +1,380 @@
+//! 远程命令执行管理器
+//!
+//! 与 [`crate::services::terminal_manager::TerminalManager`] 的 PTY 交互式 shell 不同，
+//! 这里针对的是一次性的非交互命令执行：在独立的非阻塞 SSH session 上打开 exec 通道，
+//! 分别读取 stdout/stderr 并以事件流推送，命令退出后携带退出码上报 Success/Failed。
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Instant;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ssh2::{Channel, Session};
+use tauri::{AppHandle, Emitter};
+
+use crate::models::command::{
+    CommandInfo, CommandOutputPayload, CommandOutputStream, CommandStatus, CommandStatusPayload,
+};
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::Database;
+
+const READ_BUFFER_SIZE: usize = 8192;
+const OUTPUT_THROTTLE_MS: u64 = 16;
+const OUTPUT_BUFFER_LIMIT: usize = 4096;
+
+/// 托管的命令实例（包含独立的非阻塞 SSH session，做法与 ManagedTerminal 一致）
+struct ManagedCommand {
+    command_id: String,
+    session_id: String,
+    #[allow(dead_code)]
+    ssh_session: Arc<Session>,
+    channel: Mutex<Channel>,
+    shutdown: AtomicBool,
+}
+
+/// 远程命令执行管理器
+pub struct CommandManager {
+    commands: RwLock<HashMap<String, Arc<ManagedCommand>>>,
+}
+
+impl CommandManager {
+    pub fn new() -> Self {
+        Self {
+            commands: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 在远程主机上执行一条命令，立即返回 command_id
+    ///
+    /// 输出通过 `command:output` 事件流式推送，结束后通过 `command:status` 事件
+    /// 上报退出码
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_command(
+        &self,
+        app: AppHandle,
+        db: &Database,
+        session_manager: Arc<SessionManager>,
+        session_id: &str,
+        cmd: &str,
+        args: &[String],
+        cwd: Option<&str>,
+    ) -> AppResult<CommandInfo> {
+        if cmd.trim().is_empty() {
+            return Err(AppError::invalid_argument("命令不能为空"));
+        }
+
+        // 复用与 Terminal 相同的独立 session 创建逻辑，避免占用共享的 SFTP session
+        let ssh_session = session_manager.create_terminal_session(db, session_id, None)?;
+
+        let mut channel = ssh_session.channel_session().map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("无法创建 channel: {}", e))
+        })?;
+
+        let command_line = Self::build_command_line(cmd, args, cwd);
+        channel
+            .exec(&command_line)
+            .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("执行命令失败: {}", e)))?;
+
+        // channel 创建完成后切换到非阻塞模式，供输出读取线程轮询
+        ssh_session.set_blocking(false);
+
+        let command_id = uuid::Uuid::new_v4().to_string();
+        let managed = Arc::new(ManagedCommand {
+            command_id: command_id.clone(),
+            session_id: session_id.to_string(),
+            ssh_session,
+            channel: Mutex::new(channel),
+            shutdown: AtomicBool::new(false),
+        });
+
+        {
+            let mut commands = self
+                .commands
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取命令池锁"))?;
+            commands.insert(command_id.clone(), managed.clone());
+        }
+
+        self.start_output_reader(app, managed);
+
+        tracing::info!(session_id = %session_id, command_id = %command_id, command = %command_line, "远程命令已启动");
+
+        Ok(CommandInfo {
+            command_id,
+            session_id: session_id.to_string(),
+        })
+    }
+
+    /// 拼接命令行：先 cd 到 cwd（若指定），再执行 cmd + args，各部分均做 shell 转义
+    fn build_command_line(cmd: &str, args: &[String], cwd: Option<&str>) -> String {
+        let mut parts = vec![Self::shell_quote(cmd)];
+        parts.extend(args.iter().map(|a| Self::shell_quote(a)));
+        let invocation = parts.join(" ");
+
+        match cwd {
+            Some(dir) if !dir.trim().is_empty() => {
+                format!("cd {} && {}", Self::shell_quote(dir), invocation)
+            }
+            _ => invocation,
+        }
+    }
+
+    /// 以单引号包裹参数，转义内部的单引号，防止用户输入被解释为额外的 shell 命令
+    fn shell_quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+
+    fn start_output_reader(&self, app: AppHandle, command: Arc<ManagedCommand>) {
+        thread::spawn(move || {
+            let mut stdout_buffer = vec![0u8; READ_BUFFER_SIZE];
+            let mut stderr_buffer = vec![0u8; READ_BUFFER_SIZE];
+            let mut stdout_acc = Vec::with_capacity(OUTPUT_BUFFER_LIMIT * 2);
+            let mut stderr_acc = Vec::with_capacity(OUTPUT_BUFFER_LIMIT * 2);
+            let mut last_emit = Instant::now();
+
+            loop {
+                if command.shutdown.load(Ordering::Relaxed) {
+                    tracing::info!(command_id = %command.command_id, "收到 shutdown 信号，终止命令输出读取线程");
+                    break;
+                }
+
+                let (stdout_n, stderr_n, eof) = {
+                    let mut channel = match command.channel.lock() {
+                        Ok(c) => c,
+                        Err(e) => {
+                            tracing::error!(command_id = %command.command_id, error = %e, "channel mutex 已中毒，终止读取线程");
+                            break;
+                        }
+                    };
+
+                    let stdout_n = Self::read_nonblocking(&mut channel, &mut stdout_buffer);
+                    let stderr_n = Self::read_nonblocking_stderr(&mut channel, &mut stderr_buffer);
+                    (stdout_n, stderr_n, channel.eof())
+                };
+
+                match stdout_n {
+                    Ok(n) if n > 0 => stdout_acc.extend_from_slice(&stdout_buffer[..n]),
+                    Err(()) => break,
+                    _ => {}
+                }
+                match stderr_n {
+                    Ok(n) if n > 0 => stderr_acc.extend_from_slice(&stderr_buffer[..n]),
+                    Err(()) => break,
+                    _ => {}
+                }
+
+                let should_emit = last_emit.elapsed().as_millis() as u64 >= OUTPUT_THROTTLE_MS
+                    || stdout_acc.len() >= OUTPUT_BUFFER_LIMIT
+                    || stderr_acc.len() >= OUTPUT_BUFFER_LIMIT;
+
+                if should_emit {
+                    Self::flush_stream(
+                        &app,
+                        &command.command_id,
+                        CommandOutputStream::Stdout,
+                        &mut stdout_acc,
+                    );
+                    Self::flush_stream(
+                        &app,
+                        &command.command_id,
+                        CommandOutputStream::Stderr,
+                        &mut stderr_acc,
+                    );
+                    last_emit = Instant::now();
+                }
+
+                if eof && stdout_n == Ok(0) && stderr_n == Ok(0) {
+                    break;
+                }
+
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+
+            Self::flush_stream(
+                &app,
+                &command.command_id,
+                CommandOutputStream::Stdout,
+                &mut stdout_acc,
+            );
+            Self::flush_stream(
+                &app,
+                &command.command_id,
+                CommandOutputStream::Stderr,
+                &mut stderr_acc,
+            );
+
+            let was_killed = command.shutdown.load(Ordering::Relaxed);
+            let status_payload = if was_killed {
+                CommandStatusPayload {
+                    command_id: command.command_id.clone(),
+                    status: CommandStatus::Killed,
+                    exit_code: None,
+                    message: Some("命令已被终止".to_string()),
+                }
+            } else {
+                let exit_code = {
+                    let mut channel = command.channel.lock().ok();
+                    channel.as_mut().and_then(|c| {
+                        c.wait_close().ok();
+                        c.exit_status().ok()
+                    })
+                };
+                match exit_code {
+                    Some(0) => CommandStatusPayload {
+                        command_id: command.command_id.clone(),
+                        status: CommandStatus::Success,
+                        exit_code: Some(0),
+                        message: None,
+                    },
+                    Some(code) => CommandStatusPayload {
+                        command_id: command.command_id.clone(),
+                        status: CommandStatus::Failed,
+                        exit_code: Some(code),
+                        message: None,
+                    },
+                    None => CommandStatusPayload {
+                        command_id: command.command_id.clone(),
+                        status: CommandStatus::Failed,
+                        exit_code: None,
+                        message: Some("无法获取退出码".to_string()),
+                    },
+                }
+            };
+
+            app.emit("command:status", &status_payload).ok();
+
+            tracing::info!(command_id = %command.command_id, "命令输出读取线程已退出");
+        });
+    }
+
+    /// 非阻塞读取 stdout；`WouldBlock`/`TimedOut` 视为本轮无数据，返回 Ok(0)
+    fn read_nonblocking(channel: &mut Channel, buffer: &mut [u8]) -> Result<usize, ()> {
+        match channel.read(buffer) {
+            Ok(n) => Ok(n),
+            Err(e) => Self::map_nonblocking_err(e),
+        }
+    }
+
+    fn read_nonblocking_stderr(channel: &mut Channel, buffer: &mut [u8]) -> Result<usize, ()> {
+        match channel.stderr().read(buffer) {
+            Ok(n) => Ok(n),
+            Err(e) => Self::map_nonblocking_err(e),
+        }
+    }
+
+    fn map_nonblocking_err(e: std::io::Error) -> Result<usize, ()> {
+        match e.kind() {
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => Ok(0),
+            _ => Err(()),
+        }
+    }
+
+    fn flush_stream(
+        app: &AppHandle,
+        command_id: &str,
+        stream: CommandOutputStream,
+        acc: &mut Vec<u8>,
+    ) {
+        if acc.is_empty() {
+            return;
+        }
+        let payload = CommandOutputPayload {
+            command_id: command_id.to_string(),
+            stream,
+            data: BASE64.encode(&acc),
+        };
+        app.emit("command:output", &payload).ok();
+        acc.clear();
+    }
+
+    /// 向命令的 stdin 写入数据
+    pub fn write_stdin(&self, command_id: &str, data: &[u8]) -> AppResult<()> {
+        let command = self.get_command(command_id)?;
+
+        let mut channel = command
+            .channel
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取 channel 锁"))?;
+
+        channel.write_all(data).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("写入 stdin 失败: {}", e))
+        })?;
+        channel.flush().map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("刷新 stdin 失败: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// 终止命令（尽力而为：关闭 exec 通道，远程进程可能要到下一次读写失败才会真正退出）
+    pub fn kill(&self, command_id: &str) -> AppResult<()> {
+        let command = self.get_command(command_id)?;
+
+        command.shutdown.store(true, Ordering::Relaxed);
+
+        if let Ok(mut channel) = command.channel.lock() {
+            channel.close().ok();
+        }
+
+        tracing::info!(command_id = %command_id, "命令已发送终止信号");
+        Ok(())
+    }
+
+    fn get_command(&self, command_id: &str) -> AppResult<Arc<ManagedCommand>> {
+        let commands = self
+            .commands
+            .read()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取命令池锁"))?;
+
+        commands
+            .get(command_id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found(format!("命令不存在: {}", command_id)))
+    }
+}
+
+impl Default for CommandManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: CommandManager 可以安全地跨线程共享，原因如下：
+// 1. `commands` 使用 RwLock 保护，提供线程安全的访问
+// 2. ManagedCommand 中的 Channel 使用 Mutex 保护，ssh2::Session 虽然不是 Send/Sync，
+//    但每个 ManagedCommand 的 Session 仅在其专属的输出读取线程中通过 Mutex<Channel> 访问，
+//    写入操作 (write_stdin/kill) 同样经由该 Mutex 序列化，做法与 TerminalManager 一致
+unsafe impl Send for CommandManager {}
+unsafe impl Sync for CommandManager {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_manager_creation() {
+        let manager = CommandManager::new();
+        assert!(manager.kill("nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_build_command_line_without_cwd() {
+        let line = CommandManager::build_command_line("ls", &["-la".to_string()], None);
+        assert_eq!(line, "'ls' '-la'");
+    }
+
+    #[test]
+    fn test_build_command_line_with_cwd() {
+        let line = CommandManager::build_command_line("ls", &[], Some("/tmp/my dir"));
+        assert_eq!(line, "cd '/tmp/my dir' && 'ls'");
+    }
+
+    #[test]
+    fn test_shell_quote_escapes_single_quote() {
+        assert_eq!(CommandManager::shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,156 @@
+//! 分层配置加载
+//!
+//! 按优先级从低到高合并四层配置（后一层覆盖前一层同名字段）：
+//! 1. `Settings::default()` 内置默认值
+//! 2. 应用数据目录下的 `tunnelfiles.toml`（部署级默认值，不存在时跳过）
+//! 3. `TUNNELFILES_*` 环境变量（CI/容器场景覆盖部署文件）
+//! 4. 数据库中的用户设置（`Database::settings_load`，界面里修改设置的落地层）
+//!
+//! 每一层都先解析为 [`SettingsPatch`]，再用 [`Settings::apply_patch`] 折叠进当前值，
+//! 这样四层共用同一套字段范围校验（clamp/min/max），不会出现文件/环境变量层绕过校验的情况。
+
+use std::fs;
+
+use serde_json::{Map, Value};
+
+use crate::models::error::{AppError, AppResult};
+use crate::models::settings::{
+    ConfigSource, EffectiveSettings, Settings, SettingsPatch, SettingsProvenance,
+};
+use crate::services::storage_service::{get_config_file_path, Database};
+
+/// 分层配置加载器
+pub struct ConfigLoader;
+
+impl ConfigLoader {
+    /// 依次合并默认值 -> 配置文件 -> 环境变量 -> 数据库，返回生效配置及每个字段的来源归属
+    pub fn load(db: &Database) -> AppResult<EffectiveSettings> {
+        let mut settings = Settings::default();
+        let mut provenance = SettingsProvenance::default();
+
+        if let Some(patch) = Self::load_file_layer()? {
+            settings.apply_patch(&patch);
+            provenance.mark_patch(&patch, ConfigSource::File);
+        }
+
+        let env_patch = Self::load_env_layer()?;
+        settings.apply_patch(&env_patch);
+        provenance.mark_patch(&env_patch, ConfigSource::Env);
+
+        let db_patch = db.settings_load_as_patch()?;
+        settings.apply_patch(&db_patch);
+        provenance.mark_patch(&db_patch, ConfigSource::Database);
+
+        Ok(EffectiveSettings {
+            settings,
+            provenance,
+        })
+    }
+
+    /// 解析 `tunnelfiles.toml`，文件不存在时返回 `None`（不是错误）
+    fn load_file_layer() -> AppResult<Option<SettingsPatch>> {
+        let path = get_config_file_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| {
+            AppError::local_io_error(format!("读取配置文件 {} 失败: {}", path.display(), e))
+        })?;
+
+        let patch: SettingsPatch = toml::from_str(&content).map_err(|e| {
+            AppError::invalid_argument(format!("解析配置文件 {} 失败: {}", path.display(), e))
+        })?;
+
+        Ok(Some(patch))
+    }
+
+    /// 解析 `TUNNELFILES_*` 环境变量。字段名沿用 [`SettingsPatch`] 的 camelCase 映射，
+    /// 环境变量名是其 SCREAMING_SNAKE_CASE 形式（如 `maxConcurrentTransfers` ->
+    /// `TUNNELFILES_MAX_CONCURRENT_TRANSFERS`）
+    fn load_env_layer() -> AppResult<SettingsPatch> {
+        const STRING_VARS: &[(&str, &str)] = &[
+            ("TUNNELFILES_DEFAULT_DOWNLOAD_DIR", "defaultDownloadDir"),
+            ("TUNNELFILES_LOG_LEVEL", "logLevel"),
+            ("TUNNELFILES_CHECKSUM_COMMAND", "checksumCommand"),
+            (
+                "TUNNELFILES_KNOWN_HOSTS_MIRROR_PATH",
+                "knownHostsMirrorPath",
+            ),
+        ];
+        const BOOL_VARS: &[(&str, &str)] = &[
+            ("TUNNELFILES_PRESERVE_FILE_METADATA", "preserveFileMetadata"),
+            (
+                "TUNNELFILES_VERIFY_TRANSFER_CHECKSUM",
+                "verifyTransferChecksum",
+            ),
+        ];
+        const NUMBER_VARS: &[(&str, &str)] = &[
+            (
+                "TUNNELFILES_MAX_CONCURRENT_TRANSFERS",
+                "maxConcurrentTransfers",
+            ),
+            (
+                "TUNNELFILES_CONNECTION_TIMEOUT_SECS",
+                "connectionTimeoutSecs",
+            ),
+            ("TUNNELFILES_TRANSFER_RETRY_COUNT", "transferRetryCount"),
+            (
+                "TUNNELFILES_PARALLEL_TRANSFER_THRESHOLD_MB",
+                "parallelTransferThresholdMb",
+            ),
+            (
+                "TUNNELFILES_PARALLEL_TRANSFER_STREAMS",
+                "parallelTransferStreams",
+            ),
+            ("TUNNELFILES_SPEED_LIMIT_KBPS", "speedLimitKbps"),
+            (
+                "TUNNELFILES_CHECKSUM_VERIFY_MIN_SIZE_MB",
+                "checksumVerifyMinSizeMb",
+            ),
+            ("TUNNELFILES_PIPELINE_WINDOW_SIZE", "pipelineWindowSize"),
+            ("TUNNELFILES_MAX_OPEN_LOCAL_FILES", "maxOpenLocalFiles"),
+            (
+                "TUNNELFILES_TERMINAL_IDLE_TIMEOUT_SECS",
+                "terminalIdleTimeoutSecs",
+            ),
+            ("TUNNELFILES_RETENTION_DAYS", "retentionDays"),
+        ];
+
+        let mut fields = Map::new();
+
+        for (env_name, field_name) in STRING_VARS {
+            if let Ok(v) = std::env::var(env_name) {
+                fields.insert(field_name.to_string(), Value::String(v));
+            }
+        }
+
+        for (env_name, field_name) in BOOL_VARS {
+            if let Ok(v) = std::env::var(env_name) {
+                let parsed: bool = v.parse().map_err(|_| {
+                    AppError::invalid_argument(format!(
+                        "环境变量 {} 不是合法的布尔值: {}",
+                        env_name, v
+                    ))
+                })?;
+                fields.insert(field_name.to_string(), Value::Bool(parsed));
+            }
+        }
+
+        for (env_name, field_name) in NUMBER_VARS {
+            if let Ok(v) = std::env::var(env_name) {
+                let parsed: u64 = v.parse().map_err(|_| {
+                    AppError::invalid_argument(format!(
+                        "环境变量 {} 不是合法的数字: {}",
+                        env_name, v
+                    ))
+                })?;
+                fields.insert(field_name.to_string(), Value::Number(parsed.into()));
+            }
+        }
+
+        serde_json::from_value(Value::Object(fields)).map_err(|e| {
+            AppError::invalid_argument(format!("解析 TUNNELFILES_* 环境变量失败: {}", e))
+        })
+    }
+}
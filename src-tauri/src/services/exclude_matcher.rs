@@ -0,0 +1,307 @@
+//! gitignore 风格的排除模式匹配，用于目录列表/递归删除/目录统计按路径过滤条目
+//!
+//! 规则按声明顺序依次匹配，最后一条匹配上的规则决定结果（`!` 前缀的规则匹配上表示
+//! 重新包含），与 `.gitignore` 的覆盖语义一致。不处理"父目录已被排除、子项无法再被
+//! `!` 规则重新包含"这类边界情况——调用方（各遍历函数）本来就不会展开已排除的目录，
+//! 这条边界情况自然不会发生，无需在匹配器里单独处理。
+
+/// 编译好的一条排除规则
+struct ExcludeRule {
+    /// `!` 前缀：匹配上时表示重新包含而不是排除
+    negate: bool,
+    /// 末尾 `/`：只能匹配目录
+    dir_only: bool,
+    /// 模式里出现了除末尾外的 `/`（或以 `/` 开头）：只从根开始匹配，不能从任意深度开始
+    anchored: bool,
+    /// 按 `/` 切分后的各段，`**` 是允许跨越任意多段的特殊段
+    segments: Vec<Segment>,
+}
+
+enum Segment {
+    /// `**`：匹配零或多个路径段
+    DoubleStar,
+    /// 单个路径段内的 glob（不跨越 `/`），如 `*.lock`、`build-?`、`[a-z]*`
+    Glob(Vec<GlobToken>),
+}
+
+#[derive(Clone)]
+enum GlobToken {
+    Char(char),
+    /// `?`：恰好一个字符
+    AnyChar,
+    /// `*`：任意长度（含零），但不跨越 `/`（段内已经按 `/` 切分，天然满足）
+    AnyRun,
+    /// `[abc]` / `[a-z]` / `[!abc]`
+    Class { negate: bool, ranges: Vec<(char, char)> },
+}
+
+/// gitignore 风格的排除模式集合
+#[derive(Default)]
+pub struct ExcludeMatcher {
+    rules: Vec<ExcludeRule>,
+}
+
+impl ExcludeMatcher {
+    /// 编译一组模式。空白行会被忽略；语法不合法的字符类（缺少闭合 `]`）按字面量处理，
+    /// 不会让整个模式集合编译失败
+    pub fn new(patterns: &[String]) -> Self {
+        let rules = patterns
+            .iter()
+            .map(|p| p.as_str())
+            .filter(|p| !p.trim().is_empty())
+            .map(Self::compile_rule)
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 判断 `relative_path`（相对操作根目录，`/` 分隔，不以 `/` 开头或结尾）是否被排除
+    ///
+    /// 依次跑完所有规则，记录最后一条匹配上的规则的 negate 状态；没有任何规则匹配时
+    /// 默认不排除
+    pub fn is_excluded(&self, relative_path: &str, is_dir: bool) -> bool {
+        let path_segments: Vec<&str> = relative_path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut excluded = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if Self::rule_matches(rule, &path_segments) {
+                excluded = !rule.negate;
+            }
+        }
+        excluded
+    }
+
+    fn rule_matches(rule: &ExcludeRule, path_segments: &[&str]) -> bool {
+        if rule.anchored {
+            Self::seg_match(&rule.segments, path_segments)
+        } else {
+            // 未锚定：等价于在规则前面隐式加一个 `**/`，允许从任意深度开始匹配
+            (0..=path_segments.len()).any(|start| Self::seg_match(&rule.segments, &path_segments[start..]))
+        }
+    }
+
+    fn seg_match(rule_segments: &[Segment], path_segments: &[&str]) -> bool {
+        match rule_segments.first() {
+            None => path_segments.is_empty(),
+            Some(Segment::DoubleStar) => {
+                Self::seg_match(&rule_segments[1..], path_segments)
+                    || (!path_segments.is_empty()
+                        && Self::seg_match(rule_segments, &path_segments[1..]))
+            }
+            Some(Segment::Glob(tokens)) => {
+                !path_segments.is_empty()
+                    && Self::glob_match(tokens, path_segments[0])
+                    && Self::seg_match(&rule_segments[1..], &path_segments[1..])
+            }
+        }
+    }
+
+    fn glob_match(tokens: &[GlobToken], name: &str) -> bool {
+        let chars: Vec<char> = name.chars().collect();
+        Self::glob_match_rec(tokens, &chars)
+    }
+
+    fn glob_match_rec(tokens: &[GlobToken], name: &[char]) -> bool {
+        match tokens.first() {
+            None => name.is_empty(),
+            Some(GlobToken::AnyRun) => {
+                Self::glob_match_rec(&tokens[1..], name)
+                    || (!name.is_empty() && Self::glob_match_rec(tokens, &name[1..]))
+            }
+            Some(GlobToken::AnyChar) => {
+                !name.is_empty() && Self::glob_match_rec(&tokens[1..], &name[1..])
+            }
+            Some(GlobToken::Class { negate, ranges }) => {
+                !name.is_empty()
+                    && ranges.iter().any(|(lo, hi)| name[0] >= *lo && name[0] <= *hi) != *negate
+                    && Self::glob_match_rec(&tokens[1..], &name[1..])
+            }
+            Some(GlobToken::Char(c)) => {
+                !name.is_empty() && name[0] == *c && Self::glob_match_rec(&tokens[1..], &name[1..])
+            }
+        }
+    }
+
+    fn compile_rule(pattern: &str) -> ExcludeRule {
+        let (negate, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        // 除末尾外还出现 `/`（或以 `/` 开头）才算锚定；单纯一个不含 `/` 的模式（如
+        // `*.lock`）可以在任意深度匹配
+        let anchored = pattern.starts_with('/') || pattern.trim_start_matches('/').contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        let segments = pattern
+            .split('/')
+            .map(|seg| {
+                if seg == "**" {
+                    Segment::DoubleStar
+                } else {
+                    Segment::Glob(Self::compile_glob_segment(seg))
+                }
+            })
+            .collect();
+
+        ExcludeRule {
+            negate,
+            dir_only,
+            anchored,
+            segments,
+        }
+    }
+
+    fn compile_glob_segment(segment: &str) -> Vec<GlobToken> {
+        let chars: Vec<char> = segment.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '*' => {
+                    tokens.push(GlobToken::AnyRun);
+                    i += 1;
+                }
+                '?' => {
+                    tokens.push(GlobToken::AnyChar);
+                    i += 1;
+                }
+                '[' => {
+                    if let Some((class, consumed)) = Self::parse_class(&chars[i..]) {
+                        tokens.push(class);
+                        i += consumed;
+                    } else {
+                        // 没有闭合的 `]`：把 `[` 当普通字符处理，不让整个模式编译失败
+                        tokens.push(GlobToken::Char('['));
+                        i += 1;
+                    }
+                }
+                c => {
+                    tokens.push(GlobToken::Char(c));
+                    i += 1;
+                }
+            }
+        }
+
+        tokens
+    }
+
+    /// 解析从 `[` 开始的一个字符类，返回编译结果与消耗的字符数（含首尾的 `[`/`]`）
+    fn parse_class(chars: &[char]) -> Option<(GlobToken, usize)> {
+        let mut i = 1;
+        let negate = matches!(chars.get(i), Some('!') | Some('^'));
+        if negate {
+            i += 1;
+        }
+
+        let mut ranges = Vec::new();
+        let body_start = i;
+        while i < chars.len() && chars[i] != ']' {
+            if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|c| *c != ']') {
+                ranges.push((chars[i], chars[i + 2]));
+                i += 3;
+            } else {
+                ranges.push((chars[i], chars[i]));
+                i += 1;
+            }
+        }
+
+        if i >= chars.len() || chars[i] != ']' || i == body_start {
+            return None;
+        }
+
+        Some((GlobToken::Class { negate, ranges }, i + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExcludeMatcher;
+
+    fn patterns(pats: &[&str]) -> ExcludeMatcher {
+        ExcludeMatcher::new(&pats.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_simple_glob_matches_at_any_depth() {
+        let m = patterns(&["*.lock"]);
+        assert!(m.is_excluded("Cargo.lock", false));
+        assert!(m.is_excluded("nested/deep/Cargo.lock", false));
+        assert!(!m.is_excluded("Cargo.toml", false));
+    }
+
+    #[test]
+    fn test_dir_only_trailing_slash_does_not_match_files() {
+        let m = patterns(&["build/"]);
+        assert!(m.is_excluded("build", true));
+        assert!(!m.is_excluded("build", false));
+    }
+
+    #[test]
+    fn test_anchored_leading_slash_only_matches_from_root() {
+        let m = patterns(&["/target"]);
+        assert!(m.is_excluded("target", true));
+        assert!(!m.is_excluded("nested/target", true));
+    }
+
+    #[test]
+    fn test_double_star_crosses_directory_boundaries() {
+        let m = patterns(&["a/**/b"]);
+        assert!(m.is_excluded("a/b", false));
+        assert!(m.is_excluded("a/x/b", false));
+        assert!(m.is_excluded("a/x/y/b", false));
+        assert!(!m.is_excluded("a/b/c", false));
+    }
+
+    #[test]
+    fn test_negation_re_includes_after_earlier_exclude() {
+        let m = patterns(&["*.lock", "!important.lock"]);
+        assert!(m.is_excluded("Cargo.lock", false));
+        assert!(!m.is_excluded("important.lock", false));
+    }
+
+    #[test]
+    fn test_later_pattern_overrides_earlier_one() {
+        let m = patterns(&["node_modules", "!node_modules", "node_modules"]);
+        assert!(m.is_excluded("node_modules", true));
+    }
+
+    #[test]
+    fn test_character_class_matches_range() {
+        let m = patterns(&["file[0-9].txt"]);
+        assert!(m.is_excluded("file3.txt", false));
+        assert!(!m.is_excluded("fileA.txt", false));
+    }
+
+    #[test]
+    fn test_negated_character_class() {
+        let m = patterns(&["file[!0-9].txt"]);
+        assert!(!m.is_excluded("file3.txt", false));
+        assert!(m.is_excluded("fileA.txt", false));
+    }
+
+    #[test]
+    fn test_unmatched_bracket_falls_back_to_literal() {
+        let m = patterns(&["weird[tag"]);
+        assert!(m.is_excluded("weird[tag", false));
+    }
+
+    #[test]
+    fn test_empty_matcher_excludes_nothing() {
+        let m = patterns(&[]);
+        assert!(m.is_empty());
+        assert!(!m.is_excluded("anything", false));
+    }
+}
@@ -0,0 +1,2145 @@
+//! 协议无关的文件传输抽象
+//!
+//! 定义 [`FileTransfer`] trait，把目录列表/增删改/文件读写这类操作从具体协议中抽离出来，
+//! 目前提供三个实现：
+//! - [`SftpFileTransfer`]：基于 `ssh2` 的 SFTP 后端
+//! - [`ScpFileTransfer`]：复用同一条 SSH 连接，但文件读写走 `ssh2` 的 SCP 通道
+//!   （`scp_recv`/`scp_send`），目录列表/增删改等元数据操作仍借助内部的 SFTP 子系统完成
+//!   —— SCP 协议本身不支持这些操作。批量上传/下载大文件时 SCP 没有 SFTP 逐包请求-应答
+//!   的往返开销，吞吐更高，代价是 `open_write` 必须预先知道文件大小
+//! - [`FtpFileTransfer`]：基于 `suppaftp` 的 FTP/FTPS 后端，把 FTP 应答码映射为统一的
+//!   [`AppError`]（如 550 -> `NotFound`，553 -> `PermissionDenied`），调用方无需关心后端协议
+//!
+//! 范围说明：这一层是新增的、可插拔的能力，`session_manager`/`transfer_manager`/
+//! `commands/transfer.rs` 等现有调用路径暂未迁移到这个 trait 之上 —— 它们继续使用
+//! `SessionManager`（负责主机指纹校验、凭据缓存、断线重连等生产级能力，本 trait 不重复
+//! 实现）。这里只是把"这些调用路径已经在对 SFTP 做的事情"抽象成一套协议无关的接口，
+//! 为将来接入更多协议（或在测试里用同一套断言跑不同后端）打基础。
+//!
+//! `ssh2::Session`/`ssh2::Sftp` 是 `!Send`（libssh2 非线程安全），因此 [`SftpFileTransfer`]
+//! 和调用方约定与 `session_manager.rs` 中 `AuxiliarySftpConnection` 相同的规则：一个实例
+//! 只能被单个线程独占使用，所有方法调用都必须放在 `tokio::task::spawn_blocking` 里执行。
+//!
+//! [`SftpFileTransfer`] 和 [`ScpFileTransfer`] 不直接持有 `ssh2::Session`，而是通过
+//! [`SshSession`] 间接访问——这一层转发抽象让本模块不绑死在 libssh2 上，详见
+//! `ssh_session.rs` 的模块文档。
+//!
+//! 两者都可以选择性地接入 [`crate::services::ssh_pool::SshConnectionPool`]（见
+//! `with_pool`）：`connect` 时不再每次都新建 TCP 连接 + 握手 + 认证，而是从池子里借一条
+//! 已认证的连接，`disconnect` 时随 `SessionHandle` 一起 drop 归还。不调用 `with_pool` 的
+//! 实例行为不变，仍然每次 `connect` 都独占一条新连接。
+//!
+//! `connect`/认证失败/单文件传输都打了 `tracing` 日志（`debug!` 记录握手步骤与字节数/
+//! 耗时，`warn!` 记录认证失败与连接失败），字段里只出现 host/port/username，密码、私钥
+//! 内容、passphrase 永远不会被写进日志。
+//!
+//! [`FileTransfer::upload_dir_with_progress`]/`download_dir_with_progress` 在
+//! `upload_dir`/`download_dir` 基础上增加了 [`Progress`] 回调（64KB 分块驱动）、
+//! 权限保留（[`FileTransfer::set_mode`]）和符号链接处理策略（[`SymlinkPolicy`]，仅
+//! 上传方向可配置，下载方向受限于 `Entry` 不携带符号链接信息，详见其文档）。
+//! [`FileTransfer::remove_dir_all_with_progress`] 同理给 [`FileTransfer::remove_dir_all`]
+//! 加上了按项计数的 [`DeleteProgress`] 回调。
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::net::TcpStream;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use suppaftp::{FtpError, FtpStream};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::services::ssh_pool::{PoolCredential, PooledConnection, SshConnectionPool};
+use crate::services::ssh_session::SshSession;
+
+/// [`SftpFileTransfer`]/[`ScpFileTransfer`] 持有的会话句柄：要么是自己独占新建的
+/// [`SshSession`]，要么是从 [`SshConnectionPool`] 借来的 [`PooledConnection`]——后者
+/// drop 时会自动归还给池子，前者 drop 时直接关闭连接，调用方完全不用区分这两种情况。
+enum SessionHandle {
+    Owned(SshSession),
+    Pooled(PooledConnection),
+}
+
+impl Deref for SessionHandle {
+    type Target = SshSession;
+
+    fn deref(&self) -> &SshSession {
+        match self {
+            Self::Owned(session) => session,
+            Self::Pooled(conn) => &**conn,
+        }
+    }
+}
+
+/// 远程目录条目，区分文件与目录
+///
+/// 比 [`crate::models::file_entry::FileEntry`] 更薄：只保留 `FileTransfer` 各后端都能
+/// 提供的公共字段，不包含排序、符号链接等 SFTP 特有细节
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    File {
+        name: String,
+        path: String,
+        size: Option<u64>,
+        mode: Option<u32>,
+        mtime: Option<i64>,
+    },
+    Directory {
+        name: String,
+        path: String,
+        mode: Option<u32>,
+        mtime: Option<i64>,
+    },
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::File { name, .. } | Entry::Directory { name, .. } => name,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Entry::File { path, .. } | Entry::Directory { path, .. } => path,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Entry::Directory { .. })
+    }
+}
+
+/// 递归操作中某一个子路径失败的记录，携带路径方便调用方定位
+#[derive(Debug, Clone)]
+pub struct RecursiveOpFailure {
+    pub path: String,
+    pub error: AppError,
+}
+
+/// 递归操作（[`FileTransfer::remove_dir_all`]/`upload_dir`/`download_dir`）的结果
+///
+/// 单个子路径失败不会中止整个递归，会被收集进 `failures`，调用方可以据此判断
+/// 是否需要重试或提示用户
+#[derive(Debug, Clone, Default)]
+pub struct RecursiveOpResult {
+    pub succeeded: u64,
+    pub failures: Vec<RecursiveOpFailure>,
+}
+
+impl RecursiveOpResult {
+    fn merge(&mut self, other: RecursiveOpResult) {
+        self.succeeded += other.succeeded;
+        self.failures.extend(other.failures);
+    }
+}
+
+/// 目录/文件传输进度回调，按文件粒度驱动
+///
+/// `on_file_start`/`on_file_done` 各触发一次，`on_bytes` 在传输过程中每写出一个分块就
+/// 触发一次，携带的是本次分块的增量字节数而不是累计值——调用方在 `on_bytes` 里自行累加
+/// 即可驱动总进度条，用 `on_file_start`/`on_file_done` 驱动单文件进度条。
+pub trait Progress {
+    fn on_file_start(&mut self, path: &str, total: Option<u64>);
+    fn on_bytes(&mut self, delta: u64);
+    fn on_file_done(&mut self, path: &str);
+}
+
+/// 空实现：调用方不关心进度时用 `&mut NoopProgress` 占位，省得每个调用点都写一个
+/// 什么都不做的闭包
+#[derive(Debug, Default)]
+pub struct NoopProgress;
+
+impl Progress for NoopProgress {
+    fn on_file_start(&mut self, _path: &str, _total: Option<u64>) {}
+    fn on_bytes(&mut self, _delta: u64) {}
+    fn on_file_done(&mut self, _path: &str) {}
+}
+
+/// [`FileTransfer::remove_dir_all_with_progress`] 进度回调
+///
+/// 删除没有"字节"这个维度，只有计数，所以没有复用 [`Progress`]（会平白多出一堆用不上的
+/// 字节相关字段）：收集阶段结束、总数已知时触发一次 `on_total_known`，此后每删除完
+/// （无论成功失败）一个文件或目录就触发一次 `on_item_done`。
+pub trait DeleteProgress {
+    fn on_total_known(&mut self, total: u64);
+    fn on_item_done(&mut self, path: &str, done: u64, total: u64);
+}
+
+/// 与 [`NoopProgress`] 同理：调用方不关心删除进度时的占位实现
+#[derive(Debug, Default)]
+pub struct NoopDeleteProgress;
+
+impl DeleteProgress for NoopDeleteProgress {
+    fn on_total_known(&mut self, _total: u64) {}
+    fn on_item_done(&mut self, _path: &str, _done: u64, _total: u64) {}
+}
+
+/// [`FileTransfer::upload_dir_with_progress`] 遇到符号链接时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// 跳过符号链接，既不当目录递归也不当文件传输——与 [`FileTransfer::upload_dir`] 的
+    /// 默认行为一致
+    Skip,
+    /// 跟随符号链接，按其指向的实际文件/目录内容传输
+    Follow,
+    /// 不跟随内容，只在对端用 [`FileTransfer::symlink`] 重建同名符号链接
+    CopyAsLink,
+}
+
+/// 可读的远程文件句柄，读取结束后必须调用 `finish` 让后端完成收尾工作
+/// （SFTP 无需额外操作；FTP 需要关闭数据连接并读取最终应答）
+pub trait TransferReader: Read {
+    fn finish(self: Box<Self>) -> AppResult<()>;
+}
+
+/// 可写的远程文件句柄，语义同 [`TransferReader::finish`]
+pub trait TransferWriter: Write {
+    fn finish(self: Box<Self>) -> AppResult<()>;
+}
+
+/// 协议无关的文件传输接口
+///
+/// 未要求 `Send`：`SftpFileTransfer` 包装的 `ssh2` 类型本身是 `!Send`，若在 trait 上
+/// 强加 `Send` 约束，SFTP 实现就得靠 `unsafe impl` 伪造一个跨线程共享的承诺，而这并不是
+/// 该类型的真实使用方式（它必须被单线程独占）。调用方按本模块文档顶部的约定，在
+/// `spawn_blocking` 的专用线程里构造、使用并丢弃实现该 trait 的实例即可。
+pub trait FileTransfer {
+    /// 建立连接（SFTP: TCP + SSH 握手 + 认证 + 打开 SFTP 通道；FTP: TCP + 可选 TLS + 登录）
+    fn connect(&mut self) -> AppResult<()>;
+
+    /// 主动断开连接，释放底层资源
+    fn disconnect(&mut self) -> AppResult<()>;
+
+    /// 返回当前工作目录的绝对路径
+    fn pwd(&mut self) -> AppResult<String>;
+
+    /// 列出目录内容（不保证顺序，排序由调用方处理）
+    fn list_dir(&mut self, path: &str) -> AppResult<Vec<Entry>>;
+
+    /// 获取单个路径的元信息
+    fn stat(&mut self, path: &str) -> AppResult<Entry>;
+
+    fn mkdir(&mut self, path: &str) -> AppResult<()>;
+
+    fn rename(&mut self, from: &str, to: &str) -> AppResult<()>;
+
+    fn remove_file(&mut self, path: &str) -> AppResult<()>;
+
+    /// 删除空目录；与 `SftpService::delete` 一致，不做递归删除
+    fn remove_dir(&mut self, path: &str) -> AppResult<()>;
+
+    /// 设置远程文件权限（POSIX mode 位，如 `0o644`）
+    ///
+    /// FTP 协议没有标准化的 chmod 机制，该后端总是返回错误；这是 best-effort 操作，调用方
+    /// （如 [`Self::upload_file_with_progress`]）应当容忍失败，不应让整个文件传输因此中止。
+    fn set_mode(&mut self, path: &str, mode: u32) -> AppResult<()>;
+
+    /// 在远程创建一个指向 `target` 的符号链接
+    ///
+    /// 供 [`Self::upload_dir_with_progress`] 在 [`SymlinkPolicy::CopyAsLink`] 策略下使用；
+    /// FTP 协议不支持创建符号链接，该后端总是返回错误。
+    fn symlink(&mut self, path: &str, target: &str) -> AppResult<()>;
+
+    /// 以读模式打开远程文件，返回的句柄生命周期与 `&mut self` 绑定
+    fn open_read<'a>(&'a mut self, path: &str) -> AppResult<Box<dyn TransferReader + 'a>>;
+
+    /// 以写模式打开远程文件（不存在则创建，存在则截断）
+    ///
+    /// `size_hint` 对 SFTP/FTP 后端无意义（逐包/流式写入不需要预知长度），会被忽略；
+    /// [`ScpFileTransfer`] 则强制要求提供，因为 SCP 协议的 `scp_send` 必须在发起传输前
+    /// 告知对端文件大小
+    fn open_write<'a>(
+        &'a mut self,
+        path: &str,
+        size_hint: Option<u64>,
+    ) -> AppResult<Box<dyn TransferWriter + 'a>>;
+
+    /// 上传单个本地文件，自动用本地文件大小作为 `open_write` 的 `size_hint`
+    /// （SFTP/FTP 会忽略它；没有这个提示 SCP 后端根本无法工作）
+    fn upload_file(&mut self, local: &Path, remote: &str) -> AppResult<()> {
+        let started = std::time::Instant::now();
+        let metadata = std::fs::metadata(local).map_err(AppError::from)?;
+        let mut src = std::fs::File::open(local).map_err(AppError::from)?;
+        let mut writer = self.open_write(remote, Some(metadata.len()))?;
+        let bytes = io::copy(&mut src, &mut writer).map_err(AppError::from)?;
+        writer.finish()?;
+        tracing::debug!(
+            remote = %remote,
+            bytes,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "文件上传完成"
+        );
+        Ok(())
+    }
+
+    /// 下载单个远程文件到本地路径
+    fn download_file(&mut self, remote: &str, local: &Path) -> AppResult<()> {
+        let started = std::time::Instant::now();
+        let mut reader = self.open_read(remote)?;
+        let mut dst = std::fs::File::create(local).map_err(AppError::from)?;
+        let bytes = io::copy(&mut reader, &mut dst).map_err(AppError::from)?;
+        reader.finish()?;
+        tracing::debug!(
+            remote = %remote,
+            bytes,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "文件下载完成"
+        );
+        Ok(())
+    }
+
+    /// 递归删除目录：广度优先收集子文件与子目录，文件全部删除后再按路径长度降序
+    /// （即由深到浅）删除目录本身，与 `sftp_service::delete_recursive` 的收集顺序一致
+    ///
+    /// 依赖各后端 `list_dir` 对目录项使用"条目自身属性"而非"跟随符号链接解析后的属性"
+    /// （SFTP 的 `readdir` 正是如此）：指向目录的符号链接在 `Entry::is_dir()` 下为
+    /// `false`，因此会走 `remove_file` 分支被当作普通文件删除，不会被当成目录递归进去，
+    /// 也就不存在符号链接循环的风险。单个文件/目录删除失败会被收集进返回结果，
+    /// 不会让其余路径的清理半途而废。
+    fn remove_dir_all(&mut self, path: &str) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+        let mut dirs_to_remove = vec![path.to_string()];
+        let mut stack = vec![path.to_string()];
+
+        while let Some(current) = stack.pop() {
+            let entries = match self.list_dir(&current) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure { path: current, error: e });
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                if entry.is_dir() {
+                    stack.push(entry.path().to_string());
+                    dirs_to_remove.push(entry.path().to_string());
+                } else {
+                    match self.remove_file(entry.path()) {
+                        Ok(()) => result.succeeded += 1,
+                        Err(e) => result.failures.push(RecursiveOpFailure {
+                            path: entry.path().to_string(),
+                            error: e,
+                        }),
+                    }
+                }
+            }
+        }
+
+        dirs_to_remove.sort_by_key(|d| std::cmp::Reverse(d.len()));
+        for dir in dirs_to_remove {
+            match self.remove_dir(&dir) {
+                Ok(()) => result.succeeded += 1,
+                Err(e) => result
+                    .failures
+                    .push(RecursiveOpFailure { path: dir, error: e }),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 带进度回调的递归删除，收集顺序与删除顺序与 [`Self::remove_dir_all`] 完全一致
+    /// （文件全删完再按路径长度降序删目录），区别只在于收集阶段结束、总数已知后调用
+    /// `progress.on_total_known`，此后每处理完一项（不论成功失败）都调用一次
+    /// `progress.on_item_done`，供调用方驱动一个"已删除 N / 共 M 项"的进度条
+    fn remove_dir_all_with_progress(
+        &mut self,
+        path: &str,
+        progress: &mut dyn DeleteProgress,
+    ) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+        let mut dirs_to_remove = vec![path.to_string()];
+        let mut files_to_remove = Vec::new();
+        let mut stack = vec![path.to_string()];
+
+        while let Some(current) = stack.pop() {
+            let entries = match self.list_dir(&current) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure { path: current, error: e });
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                if entry.is_dir() {
+                    stack.push(entry.path().to_string());
+                    dirs_to_remove.push(entry.path().to_string());
+                } else {
+                    files_to_remove.push(entry.path().to_string());
+                }
+            }
+        }
+
+        dirs_to_remove.sort_by_key(|d| std::cmp::Reverse(d.len()));
+        let total = (files_to_remove.len() + dirs_to_remove.len()) as u64;
+        progress.on_total_known(total);
+        let mut done = 0u64;
+
+        for file in files_to_remove {
+            match self.remove_file(&file) {
+                Ok(()) => result.succeeded += 1,
+                Err(e) => result.failures.push(RecursiveOpFailure {
+                    path: file.clone(),
+                    error: e,
+                }),
+            }
+            done += 1;
+            progress.on_item_done(&file, done, total);
+        }
+
+        for dir in dirs_to_remove {
+            match self.remove_dir(&dir) {
+                Ok(()) => result.succeeded += 1,
+                Err(e) => result.failures.push(RecursiveOpFailure {
+                    path: dir.clone(),
+                    error: e,
+                }),
+            }
+            done += 1;
+            progress.on_item_done(&dir, done, total);
+        }
+
+        Ok(result)
+    }
+
+    /// 递归上传本地目录，在远程按相同结构用 `mkdir` 创建目录后逐个上传文件
+    ///
+    /// 不跟随本地符号链接（既不当目录递归，也不当文件上传），避免链接循环
+    fn upload_dir(&mut self, local: &Path, remote: &str) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+
+        match self.mkdir(remote) {
+            Ok(()) => result.succeeded += 1,
+            Err(e) if e.code == ErrorCode::AlreadyExists => {}
+            Err(e) => {
+                result.failures.push(RecursiveOpFailure {
+                    path: remote.to_string(),
+                    error: e,
+                });
+                return Ok(result);
+            }
+        }
+
+        let read_dir = match std::fs::read_dir(local) {
+            Ok(rd) => rd,
+            Err(e) => {
+                result.failures.push(RecursiveOpFailure {
+                    path: local.display().to_string(),
+                    error: AppError::from(e),
+                });
+                return Ok(result);
+            }
+        };
+
+        for dir_entry in read_dir {
+            let dir_entry = match dir_entry {
+                Ok(e) => e,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure {
+                        path: local.display().to_string(),
+                        error: AppError::from(e),
+                    });
+                    continue;
+                }
+            };
+
+            let file_type = match dir_entry.file_type() {
+                Ok(ft) => ft,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure {
+                        path: dir_entry.path().display().to_string(),
+                        error: AppError::from(e),
+                    });
+                    continue;
+                }
+            };
+
+            if file_type.is_symlink() {
+                continue;
+            }
+
+            let name = dir_entry.file_name().to_string_lossy().to_string();
+            let remote_child = format!("{}/{}", remote.trim_end_matches('/'), name);
+
+            if file_type.is_dir() {
+                let sub_result = self.upload_dir(&dir_entry.path(), &remote_child)?;
+                result.merge(sub_result);
+            } else {
+                match self.upload_file(&dir_entry.path(), &remote_child) {
+                    Ok(()) => result.succeeded += 1,
+                    Err(e) => result.failures.push(RecursiveOpFailure {
+                        path: remote_child,
+                        error: e,
+                    }),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 递归下载远程目录，本地用 `create_dir_all` 镜像目录结构后逐个下载文件
+    fn download_dir(&mut self, remote: &str, local: &Path) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+
+        if let Err(e) = std::fs::create_dir_all(local) {
+            result.failures.push(RecursiveOpFailure {
+                path: local.display().to_string(),
+                error: AppError::from(e),
+            });
+            return Ok(result);
+        }
+        result.succeeded += 1;
+
+        let entries = self.list_dir(remote)?;
+        for entry in entries {
+            let local_child = local.join(entry.name());
+            match entry {
+                Entry::Directory { path, .. } => {
+                    let sub_result = self.download_dir(&path, &local_child)?;
+                    result.merge(sub_result);
+                }
+                Entry::File { path, .. } => match self.download_file(&path, &local_child) {
+                    Ok(()) => result.succeeded += 1,
+                    Err(e) => result.failures.push(RecursiveOpFailure { path, error: e }),
+                },
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 复制远程文件/目录
+    ///
+    /// 默认实现是纯粹的流式拷贝：逐个文件下载到内存再上传，对所有后端都成立，代价是一次
+    /// 完整的网络往返。[`SftpFileTransfer`] 覆盖了这个方法，优先尝试在远端执行 shell
+    /// `cp -r` 来避免往返，仅在 exec 被禁用时才退回这个默认实现。
+    fn copy(&mut self, src: &str, dst: &str) -> AppResult<()> {
+        stream_copy(self, src, dst)
+    }
+
+    /// 按 64KB 分块上传单个文件，每写出一块调用一次 `progress.on_bytes`，并把本地文件的
+    /// 权限位同步到远端（通过 [`Self::set_mode`]，失败会被忽略——保留权限是锦上添花，
+    /// FTP 后端本就不支持）
+    fn upload_file_with_progress(
+        &mut self,
+        local: &Path,
+        remote: &str,
+        progress: &mut dyn Progress,
+    ) -> AppResult<()> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let metadata = std::fs::metadata(local).map_err(AppError::from)?;
+        let mut src = std::fs::File::open(local).map_err(AppError::from)?;
+        let mut writer = self.open_write(remote, Some(metadata.len()))?;
+
+        progress.on_file_start(remote, Some(metadata.len()));
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = src.read(&mut buf).map_err(AppError::from)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n]).map_err(AppError::from)?;
+            progress.on_bytes(n as u64);
+        }
+        writer.finish()?;
+        progress.on_file_done(remote);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = self.set_mode(remote, metadata.permissions().mode() & 0o777);
+        }
+
+        Ok(())
+    }
+
+    /// 按 64KB 分块下载单个远程文件，每读出一块调用一次 `progress.on_bytes`，并把远程
+    /// `stat` 报告的权限位同步到本地文件（同样是 best-effort，失败不影响下载本身）
+    fn download_file_with_progress(
+        &mut self,
+        remote: &str,
+        local: &Path,
+        progress: &mut dyn Progress,
+    ) -> AppResult<()> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let remote_entry = self.stat(remote)?;
+        let total = match &remote_entry {
+            Entry::File { size, .. } => *size,
+            Entry::Directory { .. } => None,
+        };
+
+        let mut reader = self.open_read(remote)?;
+        let mut dst = std::fs::File::create(local).map_err(AppError::from)?;
+
+        progress.on_file_start(remote, total);
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = reader.read(&mut buf).map_err(AppError::from)?;
+            if n == 0 {
+                break;
+            }
+            dst.write_all(&buf[..n]).map_err(AppError::from)?;
+            progress.on_bytes(n as u64);
+        }
+        reader.finish()?;
+        progress.on_file_done(remote);
+
+        #[cfg(unix)]
+        if let Entry::File { mode: Some(mode), .. } = remote_entry {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(local, std::fs::Permissions::from_mode(mode & 0o777));
+        }
+
+        Ok(())
+    }
+
+    /// 带进度回调与符号链接策略的递归上传，在 [`Self::upload_dir`] 的目录遍历逻辑之上，
+    /// 把文件传输换成 [`Self::upload_file_with_progress`]（64KB 分块 + 进度 + 保留权限），
+    /// 并用 `symlink_policy` 取代固定的"总是跳过符号链接"行为
+    fn upload_dir_with_progress(
+        &mut self,
+        local: &Path,
+        remote: &str,
+        symlink_policy: SymlinkPolicy,
+        progress: &mut dyn Progress,
+    ) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+
+        match self.mkdir(remote) {
+            Ok(()) => result.succeeded += 1,
+            Err(e) if e.code == ErrorCode::AlreadyExists => {}
+            Err(e) => {
+                result.failures.push(RecursiveOpFailure {
+                    path: remote.to_string(),
+                    error: e,
+                });
+                return Ok(result);
+            }
+        }
+
+        let read_dir = match std::fs::read_dir(local) {
+            Ok(rd) => rd,
+            Err(e) => {
+                result.failures.push(RecursiveOpFailure {
+                    path: local.display().to_string(),
+                    error: AppError::from(e),
+                });
+                return Ok(result);
+            }
+        };
+
+        for dir_entry in read_dir {
+            let dir_entry = match dir_entry {
+                Ok(e) => e,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure {
+                        path: local.display().to_string(),
+                        error: AppError::from(e),
+                    });
+                    continue;
+                }
+            };
+
+            let file_type = match dir_entry.file_type() {
+                Ok(ft) => ft,
+                Err(e) => {
+                    result.failures.push(RecursiveOpFailure {
+                        path: dir_entry.path().display().to_string(),
+                        error: AppError::from(e),
+                    });
+                    continue;
+                }
+            };
+
+            let name = dir_entry.file_name().to_string_lossy().to_string();
+            let remote_child = format!("{}/{}", remote.trim_end_matches('/'), name);
+
+            if file_type.is_symlink() {
+                match symlink_policy {
+                    SymlinkPolicy::Skip => {}
+                    SymlinkPolicy::Follow => match std::fs::metadata(dir_entry.path()) {
+                        Ok(target_metadata) if target_metadata.is_dir() => {
+                            let sub_result = self.upload_dir_with_progress(
+                                &dir_entry.path(),
+                                &remote_child,
+                                symlink_policy,
+                                progress,
+                            )?;
+                            result.merge(sub_result);
+                        }
+                        Ok(_) => {
+                            match self.upload_file_with_progress(
+                                &dir_entry.path(),
+                                &remote_child,
+                                progress,
+                            ) {
+                                Ok(()) => result.succeeded += 1,
+                                Err(e) => result.failures.push(RecursiveOpFailure {
+                                    path: remote_child,
+                                    error: e,
+                                }),
+                            }
+                        }
+                        Err(e) => result.failures.push(RecursiveOpFailure {
+                            path: remote_child,
+                            error: AppError::from(e),
+                        }),
+                    },
+                    SymlinkPolicy::CopyAsLink => match std::fs::read_link(dir_entry.path()) {
+                        Ok(target) => {
+                            match self.symlink(&remote_child, &target.to_string_lossy()) {
+                                Ok(()) => result.succeeded += 1,
+                                Err(e) => result.failures.push(RecursiveOpFailure {
+                                    path: remote_child,
+                                    error: e,
+                                }),
+                            }
+                        }
+                        Err(e) => result.failures.push(RecursiveOpFailure {
+                            path: remote_child,
+                            error: AppError::from(e),
+                        }),
+                    },
+                }
+                continue;
+            }
+
+            if file_type.is_dir() {
+                let sub_result = self.upload_dir_with_progress(
+                    &dir_entry.path(),
+                    &remote_child,
+                    symlink_policy,
+                    progress,
+                )?;
+                result.merge(sub_result);
+            } else {
+                match self.upload_file_with_progress(&dir_entry.path(), &remote_child, progress) {
+                    Ok(()) => result.succeeded += 1,
+                    Err(e) => result.failures.push(RecursiveOpFailure {
+                        path: remote_child,
+                        error: e,
+                    }),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 带进度回调的递归下载，每个文件走 [`Self::download_file_with_progress`]
+    ///
+    /// 不像 `upload_dir_with_progress` 那样接受 `SymlinkPolicy`：[`Entry`] 不携带"是否为
+    /// 符号链接"这一位信息——SFTP `readdir` 返回的是 lstat 结果，指向目录的符号链接已经被
+    /// 上游表示成 `Entry::File` 而非 `Entry::Directory`（见模块内 `remove_dir_all` 的文档），
+    /// 指向文件的符号链接与普通文件在 `Entry` 层面完全无法区分，因此这里只能按 `Entry`
+    /// 本身的类型处理，等价于对目录符号链接固定使用 `Follow`。
+    fn download_dir_with_progress(
+        &mut self,
+        remote: &str,
+        local: &Path,
+        progress: &mut dyn Progress,
+    ) -> AppResult<RecursiveOpResult> {
+        let mut result = RecursiveOpResult::default();
+
+        if let Err(e) = std::fs::create_dir_all(local) {
+            result.failures.push(RecursiveOpFailure {
+                path: local.display().to_string(),
+                error: AppError::from(e),
+            });
+            return Ok(result);
+        }
+        result.succeeded += 1;
+
+        let entries = self.list_dir(remote)?;
+        for entry in entries {
+            let local_child = local.join(entry.name());
+            match entry {
+                Entry::Directory { path, .. } => {
+                    let sub_result =
+                        self.download_dir_with_progress(&path, &local_child, progress)?;
+                    result.merge(sub_result);
+                }
+                Entry::File { path, .. } => {
+                    match self.download_file_with_progress(&path, &local_child, progress) {
+                        Ok(()) => result.succeeded += 1,
+                        Err(e) => result.failures.push(RecursiveOpFailure { path, error: e }),
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// [`FileTransfer::copy`] 默认实现背后的流式拷贝逻辑，独立成自由函数是因为
+/// `SftpFileTransfer` 覆盖 `copy` 后，仍需要在 exec 不可用时退回到同一套逻辑——
+/// trait 没有"调用父类默认实现"这回事，只能共享同一个自由函数
+fn stream_copy<T: FileTransfer + ?Sized>(transfer: &mut T, src: &str, dst: &str) -> AppResult<()> {
+    match transfer.stat(src)? {
+        Entry::Directory { .. } => {
+            transfer.mkdir(dst)?;
+            for entry in transfer.list_dir(src)? {
+                let child_dst = format!("{}/{}", dst.trim_end_matches('/'), entry.name());
+                stream_copy(transfer, entry.path(), &child_dst)?;
+            }
+            Ok(())
+        }
+        Entry::File { .. } => {
+            let mut buffer = Vec::new();
+            {
+                let mut reader = transfer.open_read(src)?;
+                reader.read_to_end(&mut buffer).map_err(AppError::from)?;
+                reader.finish()?;
+            }
+            let mut writer = transfer.open_write(dst, Some(buffer.len() as u64))?;
+            writer.write_all(&buffer).map_err(AppError::from)?;
+            writer.finish()
+        }
+    }
+}
+
+/// shell 单引号转义："'" -> "'\''"，这是在单引号字符串里插入字面单引号的标准写法
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// ================================================================
+// SFTP 后端
+// ================================================================
+
+/// 基于 `ssh2` 的 [`FileTransfer`] 实现
+///
+/// 独立于 [`crate::services::session_manager::SessionManager`]：后者还负责主机指纹
+/// 校验、凭据缓存与自动重连，这些生产级关注点不属于本 trait 的职责范围，因此这里只做
+/// 最基础的"密码或私钥认证后打开一个 SFTP 通道"。
+pub struct SftpFileTransfer {
+    host: String,
+    port: u16,
+    username: String,
+    password: Option<String>,
+    private_key_path: Option<PathBuf>,
+    connect_timeout: Duration,
+    session: Option<SessionHandle>,
+    sftp: Option<ssh2::Sftp>,
+    /// `copy` 是否优先尝试在远端执行 `cp -r`，默认 `true`；服务器禁用 exec 或调用方
+    /// 显式用 [`SftpFileTransfer::with_exec_copy`] 关闭后，退回逐字节流式拷贝
+    prefer_exec_copy: bool,
+    /// 设置后，`connect` 改为从连接池借一条已认证的连接，而不是每次都新建
+    pool: Option<Arc<SshConnectionPool>>,
+}
+
+// SAFETY: SftpFileTransfer 手动实现 Send（不实现 Sync），与 session_manager.rs 中
+// `AuxiliarySftpConnection` 的理由完全一致：`ssh2::Session`/`ssh2::Sftp` 是 `!Send`，
+// 因为底层 libssh2 C 库非线程安全；但本类型的每个实例在整个生命周期内只会被 `move`
+// 进恰好一个专用线程（调用方必须在 `spawn_blocking` 中构造并使用，参见本模块文档），
+// 从不会有第二个线程持有同一实例的引用，所以只需要 Send，不需要 Sync。
+unsafe impl Send for SftpFileTransfer {}
+
+impl SftpFileTransfer {
+    pub fn with_password(
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: username.into(),
+            password: Some(password.into()),
+            private_key_path: None,
+            connect_timeout: Duration::from_secs(10),
+            session: None,
+            sftp: None,
+            prefer_exec_copy: true,
+            pool: None,
+        }
+    }
+
+    pub fn with_private_key(
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: username.into(),
+            password: None,
+            private_key_path: Some(private_key_path.into()),
+            connect_timeout: Duration::from_secs(10),
+            session: None,
+            sftp: None,
+            prefer_exec_copy: true,
+            pool: None,
+        }
+    }
+
+    /// 控制 [`FileTransfer::copy`] 是否优先尝试在远端执行 `cp -r`；关闭后始终走
+    /// 流式拷贝（下载到内存再上传），用于 exec 被禁用、或调用方明确不信任远端 shell
+    /// 行为的场景
+    pub fn with_exec_copy(mut self, enabled: bool) -> Self {
+        self.prefer_exec_copy = enabled;
+        self
+    }
+
+    /// 接入一个共享的连接池：`connect` 之后不再每次新建 TCP+SSH 连接，而是从池子里
+    /// 借一条已认证的连接，`disconnect`/drop 时自动归还
+    pub fn with_pool(mut self, pool: Arc<SshConnectionPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 按当前配置的密码/私钥构造一份连接池凭据
+    fn pool_credential(&self) -> AppResult<PoolCredential> {
+        if let Some(password) = &self.password {
+            Ok(PoolCredential::Password(password.clone()))
+        } else if let Some(key_path) = &self.private_key_path {
+            Ok(PoolCredential::PrivateKey(key_path.clone()))
+        } else {
+            Err(AppError::invalid_argument("未提供密码或私钥"))
+        }
+    }
+
+    fn sftp(&self) -> AppResult<&ssh2::Sftp> {
+        self.sftp
+            .as_ref()
+            .ok_or_else(|| AppError::new(ErrorCode::Unknown, "SFTP 通道尚未建立，请先调用 connect"))
+    }
+
+    fn map_error(e: ssh2::Error, path: &str) -> AppError {
+        if e.code() == ssh2::ErrorCode::SFTP(2) {
+            AppError::not_found(format!("路径不存在: {}", path))
+        } else {
+            AppError::from(e)
+        }
+    }
+
+    fn stat_to_entry(name: String, path: String, stat: ssh2::FileStat) -> Entry {
+        let mode = stat.perm;
+        let mtime = stat.mtime.map(|t| t as i64);
+        if stat.is_dir() {
+            Entry::Directory {
+                name,
+                path,
+                mode,
+                mtime,
+            }
+        } else {
+            Entry::File {
+                name,
+                path,
+                size: stat.size,
+                mode,
+                mtime,
+            }
+        }
+    }
+
+    /// 尝试在远端执行 shell `cp -r -- <src> <dst>` 完成服务端复制
+    ///
+    /// 返回 `None` 表示在 exec 这一层就没能跑起来（多半是服务器出于安全考虑禁用了
+    /// exec），调用方应当退回流式拷贝；返回 `Some(..)` 表示 `cp` 本身执行完了——
+    /// 无论成功还是非零退出，都应当如实返回给最终调用方，不应该再退回流式拷贝掩盖
+    /// 真实的错误（比如源路径真的不存在）。
+    fn try_copy_via_exec(&mut self, src: &str, dst: &str) -> Option<AppResult<()>> {
+        let session = self.session.as_deref()?;
+
+        let mut channel = session.channel_session().ok()?;
+        let cmd = format!("cp -r -- {} {}", shell_quote(src), shell_quote(dst));
+        if channel.exec(&cmd).is_err() {
+            return None;
+        }
+
+        let mut stderr_output = String::new();
+        let _ = channel.stderr().read_to_string(&mut stderr_output);
+
+        if channel.wait_close().is_err() {
+            return None;
+        }
+
+        let exit_status = match channel.exit_status() {
+            Ok(status) => status,
+            Err(e) => return Some(Err(AppError::from(e))),
+        };
+
+        if exit_status == 0 {
+            Some(Ok(()))
+        } else {
+            Some(Err(Self::map_cp_exit_error(exit_status, &stderr_output, src)))
+        }
+    }
+
+    /// 把 `cp` 的非零退出码映射为统一的 [`AppError`]，靠 stderr 文案粗略区分
+    /// 找不到文件与权限不足这两种最常见的情况
+    fn map_cp_exit_error(exit_status: i32, stderr: &str, path: &str) -> AppError {
+        if stderr.contains("No such file or directory") {
+            AppError::not_found(format!("路径不存在: {}", path))
+        } else if stderr.contains("Permission denied") {
+            AppError::permission_denied(stderr.trim().to_string())
+        } else {
+            AppError::new(
+                ErrorCode::RemoteIoError,
+                format!("cp 命令执行失败 (exit code {}): {}", exit_status, stderr.trim()),
+            )
+        }
+    }
+
+    /// 断点续传上传：若远端已存在同名文件，从其当前大小处续传，而不是每次都从零
+    /// 重新上传一遍整个文件
+    ///
+    /// 续传前会用 [`Self::verify_overlapping_prefix`] 按 `chunk_size` 分块比对本地文件与
+    /// 远端已有内容的重叠前缀；一旦发现不一致（比如上次中断后本地文件被改过），说明不能
+    /// 简单地在远端文件末尾续写，会退回 [`FileTransfer::upload_file`] 重新完整上传一遍。
+    pub fn resume_upload(&mut self, local: &Path, remote: &str, chunk_size: usize) -> AppResult<()> {
+        let started = std::time::Instant::now();
+        let local_len = std::fs::metadata(local).map_err(AppError::from)?.len();
+        let remote_len = match self.stat(remote) {
+            Ok(Entry::File { size: Some(size), .. }) => size,
+            Ok(_) | Err(_) => 0,
+        };
+
+        if remote_len >= local_len {
+            tracing::debug!(remote = %remote, local_len, remote_len, "续传上传：远端已是最新，无需传输");
+            return Ok(());
+        }
+
+        if remote_len > 0 && !self.verify_overlapping_prefix(local, remote, remote_len, chunk_size)? {
+            tracing::warn!(remote = %remote, remote_len, "续传上传：远端已有内容与本地文件不一致，回退为完整上传");
+            return FileTransfer::upload_file(self, local, remote);
+        }
+
+        let mut src = std::fs::File::open(local).map_err(AppError::from)?;
+        src.seek(SeekFrom::Start(remote_len)).map_err(AppError::from)?;
+
+        let mut remote_file = self
+            .sftp()?
+            .open_mode(
+                Path::new(remote),
+                ssh2::OpenFlags::WRITE | ssh2::OpenFlags::CREATE,
+                0o644,
+                ssh2::OpenType::File,
+            )
+            .map_err(|e| Self::map_error(e, remote))?;
+        remote_file
+            .seek(SeekFrom::Start(remote_len))
+            .map_err(AppError::from)?;
+
+        let mut buf = vec![0u8; chunk_size.max(1)];
+        let mut transferred = 0u64;
+        loop {
+            let n = src.read(&mut buf).map_err(AppError::from)?;
+            if n == 0 {
+                break;
+            }
+            remote_file.write_all(&buf[..n]).map_err(AppError::from)?;
+            transferred += n as u64;
+        }
+        remote_file.flush().map_err(AppError::from)?;
+
+        tracing::debug!(
+            remote = %remote,
+            resumed_from = remote_len,
+            bytes = transferred,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "续传上传完成"
+        );
+        Ok(())
+    }
+
+    /// 断点续传下载：若本地已存在部分文件，从其当前大小处续传
+    ///
+    /// 与 [`Self::resume_upload`] 对称：先比对本地已有前缀与远端对应字节是否一致，一致
+    /// 才从断点处继续读取远端剩余部分追加写入本地文件，否则回退为完整下载。
+    pub fn resume_download(&mut self, remote: &str, local: &Path, chunk_size: usize) -> AppResult<()> {
+        let started = std::time::Instant::now();
+        let remote_len = match self.stat(remote)? {
+            Entry::File { size: Some(size), .. } => size,
+            _ => return FileTransfer::download_file(self, remote, local),
+        };
+        let local_len = std::fs::metadata(local).map(|m| m.len()).unwrap_or(0);
+
+        if local_len >= remote_len {
+            tracing::debug!(remote = %remote, local_len, remote_len, "续传下载：本地已是最新，无需传输");
+            return Ok(());
+        }
+
+        if local_len > 0 && !self.verify_overlapping_prefix(local, remote, local_len, chunk_size)? {
+            tracing::warn!(remote = %remote, local_len, "续传下载：本地已有内容与远端不一致，回退为完整下载");
+            return FileTransfer::download_file(self, remote, local);
+        }
+
+        let mut remote_file = self
+            .sftp()?
+            .open(Path::new(remote))
+            .map_err(|e| Self::map_error(e, remote))?;
+        remote_file
+            .seek(SeekFrom::Start(local_len))
+            .map_err(AppError::from)?;
+
+        let mut dst = std::fs::OpenOptions::new()
+            .write(true)
+            .open(local)
+            .map_err(AppError::from)?;
+        dst.seek(SeekFrom::Start(local_len)).map_err(AppError::from)?;
+
+        let mut buf = vec![0u8; chunk_size.max(1)];
+        let mut transferred = 0u64;
+        loop {
+            let n = remote_file.read(&mut buf).map_err(AppError::from)?;
+            if n == 0 {
+                break;
+            }
+            dst.write_all(&buf[..n]).map_err(AppError::from)?;
+            transferred += n as u64;
+        }
+
+        tracing::debug!(
+            remote = %remote,
+            resumed_from = local_len,
+            bytes = transferred,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "续传下载完成"
+        );
+        Ok(())
+    }
+
+    /// 按 `chunk_size` 分块重新读取本地文件与远端文件的前 `overlap_len` 字节，逐块比较
+    /// SHA256 哈希，确认重叠区域字节一致后才能安全地在断点处续传
+    fn verify_overlapping_prefix(
+        &mut self,
+        local: &Path,
+        remote: &str,
+        overlap_len: u64,
+        chunk_size: usize,
+    ) -> AppResult<bool> {
+        let mut local_file = std::fs::File::open(local).map_err(AppError::from)?;
+        let mut remote_file = self
+            .sftp()?
+            .open(Path::new(remote))
+            .map_err(|e| Self::map_error(e, remote))?;
+
+        let chunk_size = chunk_size.max(1);
+        let mut local_buf = vec![0u8; chunk_size];
+        let mut remote_buf = vec![0u8; chunk_size];
+        let mut remaining = overlap_len;
+
+        while remaining > 0 {
+            let want = remaining.min(chunk_size as u64) as usize;
+
+            read_exact_n(&mut local_file, &mut local_buf[..want]).map_err(AppError::from)?;
+            read_exact_n(&mut remote_file, &mut remote_buf[..want]).map_err(AppError::from)?;
+
+            if Sha256::digest(&local_buf[..want]) != Sha256::digest(&remote_buf[..want]) {
+                return Ok(false);
+            }
+
+            remaining -= want as u64;
+        }
+
+        Ok(true)
+    }
+}
+
+/// 循环读取直到填满 `buf`，处理 `ssh2`/`std::fs` 的 `read` 可能一次只返回部分字节的情况
+fn read_exact_n<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "提前遇到 EOF"));
+        }
+        filled += n;
+    }
+    Ok(())
+}
+
+impl FileTransfer for SftpFileTransfer {
+    fn connect(&mut self) -> AppResult<()> {
+        let session = if let Some(pool) = &self.pool {
+            tracing::debug!(host = %self.host, port = self.port, username = %self.username, "正在从连接池借用 SSH 会话");
+            let credential = self.pool_credential()?;
+            let conn = pool
+                .acquire(self.host.clone(), self.port, self.username.clone(), credential)
+                .map_err(|e| {
+                    tracing::warn!(host = %self.host, port = self.port, error = %e, "连接池借用失败");
+                    e
+                })?;
+            SessionHandle::Pooled(conn)
+        } else {
+            let addr = format!("{}:{}", self.host, self.port);
+            tracing::debug!(addr = %addr, "正在建立 TCP 连接");
+            let socket_addr = addr
+                .parse()
+                .map_err(|e| AppError::invalid_argument(format!("无效的地址: {}", e)))?;
+
+            let tcp = TcpStream::connect_timeout(&socket_addr, self.connect_timeout)
+                .map_err(|e| {
+                    tracing::warn!(addr = %addr, error = %e, "无法建立 TCP 连接");
+                    AppError::network_lost(format!("无法连接到服务器: {}", e))
+                })?;
+            tcp.set_read_timeout(Some(self.connect_timeout))?;
+            tcp.set_write_timeout(Some(self.connect_timeout))?;
+
+            tracing::debug!("正在进行 SSH 握手");
+            let mut session = SshSession::connect_libssh2(tcp, self.connect_timeout).map_err(|e| {
+                tracing::warn!(addr = %addr, error = %e, "SSH 握手失败或超时");
+                AppError::network_lost(format!("SSH 握手失败: {}", e))
+            })?;
+
+            if let Some(password) = &self.password {
+                session.userauth_password(&self.username, password).map_err(|e| {
+                    tracing::warn!(username = %self.username, error = %e, "密码认证失败");
+                    AppError::auth_failed(format!("密码认证失败: {}", e))
+                })?;
+            } else if let Some(key_path) = &self.private_key_path {
+                session
+                    .userauth_pubkey_file(&self.username, None, key_path, None)
+                    .map_err(|e| {
+                        tracing::warn!(username = %self.username, error = %e, "密钥认证失败");
+                        AppError::auth_failed(format!("密钥认证失败: {}", e))
+                    })?;
+            } else {
+                return Err(AppError::invalid_argument("未提供密码或私钥"));
+            }
+
+            tracing::info!(host = %self.host, port = self.port, username = %self.username, "SSH 会话已建立并认证成功");
+            SessionHandle::Owned(session)
+        };
+
+        let sftp = session.sftp().map_err(|e| {
+            AppError::new(
+                ErrorCode::RemoteIoError,
+                format!("无法创建 SFTP 通道: {}", e),
+            )
+        })?;
+
+        self.session = Some(session);
+        self.sftp = Some(sftp);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> AppResult<()> {
+        tracing::debug!(host = %self.host, port = self.port, "断开 SFTP 会话");
+        self.sftp = None;
+        self.session = None;
+        Ok(())
+    }
+
+    fn pwd(&mut self) -> AppResult<String> {
+        let sftp = self.sftp()?;
+        let path = sftp
+            .realpath(Path::new("."))
+            .map_err(|e| Self::map_error(e, "."))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    fn list_dir(&mut self, path: &str) -> AppResult<Vec<Entry>> {
+        let sftp = self.sftp()?;
+        let path_obj = Path::new(path);
+        let entries = sftp
+            .readdir(path_obj)
+            .map_err(|e| Self::map_error(e, path))?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(path_buf, stat)| {
+                let name = path_buf.file_name()?.to_str()?.to_string();
+                if name == "." || name == ".." {
+                    return None;
+                }
+                let full_path = path_buf.to_string_lossy().to_string();
+                Some(Self::stat_to_entry(name, full_path, stat))
+            })
+            .collect())
+    }
+
+    fn stat(&mut self, path: &str) -> AppResult<Entry> {
+        let sftp = self.sftp()?;
+        let path_obj = Path::new(path);
+        let stat = sftp.stat(path_obj).map_err(|e| Self::map_error(e, path))?;
+        let name = path_obj
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path)
+            .to_string();
+        Ok(Self::stat_to_entry(name, path.to_string(), stat))
+    }
+
+    fn mkdir(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.mkdir(Path::new(path), 0o755)
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.rename(Path::new(from), Path::new(to), None)
+            .map_err(|e| Self::map_error(e, from))
+    }
+
+    fn remove_file(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.unlink(Path::new(path))
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn remove_dir(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.rmdir(Path::new(path))
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn set_mode(&mut self, path: &str, mode: u32) -> AppResult<()> {
+        let stat = ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode),
+            atime: None,
+            mtime: None,
+        };
+        self.sftp()?
+            .setstat(Path::new(path), stat)
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn symlink(&mut self, path: &str, target: &str) -> AppResult<()> {
+        self.sftp()?
+            .symlink(Path::new(path), Path::new(target))
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn open_read<'a>(&'a mut self, path: &str) -> AppResult<Box<dyn TransferReader + 'a>> {
+        let sftp = self.sftp.as_ref().ok_or_else(|| {
+            AppError::new(ErrorCode::Unknown, "SFTP 通道尚未建立，请先调用 connect")
+        })?;
+        let file = sftp
+            .open(Path::new(path))
+            .map_err(|e| Self::map_error(e, path))?;
+        Ok(Box::new(SftpReader(file)))
+    }
+
+    fn open_write<'a>(
+        &'a mut self,
+        path: &str,
+        _size_hint: Option<u64>,
+    ) -> AppResult<Box<dyn TransferWriter + 'a>> {
+        let sftp = self.sftp.as_ref().ok_or_else(|| {
+            AppError::new(ErrorCode::Unknown, "SFTP 通道尚未建立，请先调用 connect")
+        })?;
+        let file = sftp
+            .create(Path::new(path))
+            .map_err(|e| Self::map_error(e, path))?;
+        Ok(Box::new(SftpWriter(file)))
+    }
+
+    fn copy(&mut self, src: &str, dst: &str) -> AppResult<()> {
+        if self.prefer_exec_copy {
+            if let Some(result) = self.try_copy_via_exec(src, dst) {
+                return result;
+            }
+            tracing::debug!(src = %src, dst = %dst, "远端 exec 不可用，退回流式拷贝");
+        }
+        stream_copy(self, src, dst)
+    }
+}
+
+struct SftpReader<'a>(ssh2::File<'a>);
+
+impl Read for SftpReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl TransferReader for SftpReader<'_> {
+    fn finish(self: Box<Self>) -> AppResult<()> {
+        // ssh2::File 在 drop 时自动关闭句柄，无需额外操作
+        Ok(())
+    }
+}
+
+struct SftpWriter<'a>(ssh2::File<'a>);
+
+impl Write for SftpWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl TransferWriter for SftpWriter<'_> {
+    fn finish(mut self: Box<Self>) -> AppResult<()> {
+        self.0.flush().map_err(AppError::from)
+    }
+}
+
+// ================================================================
+// SCP 后端
+// ================================================================
+
+/// 基于 `ssh2` SCP 通道的 [`FileTransfer`] 实现
+///
+/// 连接方式与 [`SftpFileTransfer`] 完全一致（同样的认证流程），区别只在于 `open_read`/
+/// `open_write`：这两者改走 `Session::scp_recv`/`scp_send`，避免 SFTP 协议逐个数据包
+/// 往返确认带来的开销，更适合批量传输大文件。目录列表/增删改操作 SCP 协议本身不提供，
+/// 因此仍借助内部维护的 SFTP 子系统实现，与 `SftpFileTransfer` 共用同一套映射逻辑。
+pub struct ScpFileTransfer {
+    host: String,
+    port: u16,
+    username: String,
+    password: Option<String>,
+    private_key_path: Option<PathBuf>,
+    connect_timeout: Duration,
+    session: Option<SessionHandle>,
+    sftp: Option<ssh2::Sftp>,
+    /// 设置后，`connect` 改为从连接池借一条已认证的连接，而不是每次都新建
+    pool: Option<Arc<SshConnectionPool>>,
+}
+
+// SAFETY: 与 SftpFileTransfer 同理 —— 本类型在整个生命周期内只会被 move 进一个专用的
+// spawn_blocking 线程，从不会被多个线程同时持有引用。
+unsafe impl Send for ScpFileTransfer {}
+
+impl ScpFileTransfer {
+    pub fn with_password(
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: username.into(),
+            password: Some(password.into()),
+            private_key_path: None,
+            connect_timeout: Duration::from_secs(10),
+            session: None,
+            sftp: None,
+            pool: None,
+        }
+    }
+
+    pub fn with_private_key(
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: username.into(),
+            password: None,
+            private_key_path: Some(private_key_path.into()),
+            connect_timeout: Duration::from_secs(10),
+            session: None,
+            sftp: None,
+            pool: None,
+        }
+    }
+
+    /// 接入一个共享的连接池：`connect` 之后不再每次新建 TCP+SSH 连接，而是从池子里
+    /// 借一条已认证的连接，`disconnect`/drop 时自动归还
+    pub fn with_pool(mut self, pool: Arc<SshConnectionPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 按当前配置的密码/私钥构造一份连接池凭据
+    fn pool_credential(&self) -> AppResult<PoolCredential> {
+        if let Some(password) = &self.password {
+            Ok(PoolCredential::Password(password.clone()))
+        } else if let Some(key_path) = &self.private_key_path {
+            Ok(PoolCredential::PrivateKey(key_path.clone()))
+        } else {
+            Err(AppError::invalid_argument("未提供密码或私钥"))
+        }
+    }
+
+    fn session(&self) -> AppResult<&SshSession> {
+        self.session
+            .as_deref()
+            .ok_or_else(|| AppError::new(ErrorCode::Unknown, "SSH 会话尚未建立，请先调用 connect"))
+    }
+
+    fn sftp(&self) -> AppResult<&ssh2::Sftp> {
+        self.sftp
+            .as_ref()
+            .ok_or_else(|| AppError::new(ErrorCode::Unknown, "SFTP 通道尚未建立，请先调用 connect"))
+    }
+
+    /// 以指定大小上传远程文件，SCP 协议要求在发起传输前就知道文件长度
+    ///
+    /// 对应 trait 方法 `open_write` 在 `size_hint` 为 `None` 时会直接报错，调用方应优先
+    /// 走这个方法
+    pub fn open_write_sized<'a>(
+        &'a mut self,
+        path: &str,
+        size: u64,
+    ) -> AppResult<Box<dyn TransferWriter + 'a>> {
+        let session = self.session()?;
+        let channel = session
+            .scp_send(Path::new(path), 0o644, size, None)
+            .map_err(|e| SftpFileTransfer::map_error(e, path))?;
+        Ok(Box::new(ScpWriter(channel)))
+    }
+}
+
+impl FileTransfer for ScpFileTransfer {
+    fn connect(&mut self) -> AppResult<()> {
+        let session = if let Some(pool) = &self.pool {
+            tracing::debug!(host = %self.host, port = self.port, username = %self.username, "正在从连接池借用 SSH 会话");
+            let credential = self.pool_credential()?;
+            let conn = pool
+                .acquire(self.host.clone(), self.port, self.username.clone(), credential)
+                .map_err(|e| {
+                    tracing::warn!(host = %self.host, port = self.port, error = %e, "连接池借用失败");
+                    e
+                })?;
+            SessionHandle::Pooled(conn)
+        } else {
+            let addr = format!("{}:{}", self.host, self.port);
+            tracing::debug!(addr = %addr, "正在建立 TCP 连接");
+            let socket_addr = addr
+                .parse()
+                .map_err(|e| AppError::invalid_argument(format!("无效的地址: {}", e)))?;
+
+            let tcp = TcpStream::connect_timeout(&socket_addr, self.connect_timeout)
+                .map_err(|e| {
+                    tracing::warn!(addr = %addr, error = %e, "无法建立 TCP 连接");
+                    AppError::network_lost(format!("无法连接到服务器: {}", e))
+                })?;
+            tcp.set_read_timeout(Some(self.connect_timeout))?;
+            tcp.set_write_timeout(Some(self.connect_timeout))?;
+
+            tracing::debug!("正在进行 SSH 握手");
+            let mut session = SshSession::connect_libssh2(tcp, self.connect_timeout).map_err(|e| {
+                tracing::warn!(addr = %addr, error = %e, "SSH 握手失败或超时");
+                AppError::network_lost(format!("SSH 握手失败: {}", e))
+            })?;
+
+            if let Some(password) = &self.password {
+                session.userauth_password(&self.username, password).map_err(|e| {
+                    tracing::warn!(username = %self.username, error = %e, "密码认证失败");
+                    AppError::auth_failed(format!("密码认证失败: {}", e))
+                })?;
+            } else if let Some(key_path) = &self.private_key_path {
+                session
+                    .userauth_pubkey_file(&self.username, None, key_path, None)
+                    .map_err(|e| {
+                        tracing::warn!(username = %self.username, error = %e, "密钥认证失败");
+                        AppError::auth_failed(format!("密钥认证失败: {}", e))
+                    })?;
+            } else {
+                return Err(AppError::invalid_argument("未提供密码或私钥"));
+            }
+
+            tracing::info!(host = %self.host, port = self.port, username = %self.username, "SSH 会话已建立并认证成功");
+            SessionHandle::Owned(session)
+        };
+
+        let sftp = session.sftp().map_err(|e| {
+            AppError::new(
+                ErrorCode::RemoteIoError,
+                format!("无法创建 SFTP 通道: {}", e),
+            )
+        })?;
+
+        self.session = Some(session);
+        self.sftp = Some(sftp);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> AppResult<()> {
+        tracing::debug!(host = %self.host, port = self.port, "断开 SCP 会话");
+        self.sftp = None;
+        self.session = None;
+        Ok(())
+    }
+
+    fn pwd(&mut self) -> AppResult<String> {
+        let sftp = self.sftp()?;
+        let path = sftp
+            .realpath(Path::new("."))
+            .map_err(|e| SftpFileTransfer::map_error(e, "."))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    fn list_dir(&mut self, path: &str) -> AppResult<Vec<Entry>> {
+        let sftp = self.sftp()?;
+        let entries = sftp
+            .readdir(Path::new(path))
+            .map_err(|e| SftpFileTransfer::map_error(e, path))?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(path_buf, stat)| {
+                let name = path_buf.file_name()?.to_str()?.to_string();
+                if name == "." || name == ".." {
+                    return None;
+                }
+                let full_path = path_buf.to_string_lossy().to_string();
+                Some(SftpFileTransfer::stat_to_entry(name, full_path, stat))
+            })
+            .collect())
+    }
+
+    fn stat(&mut self, path: &str) -> AppResult<Entry> {
+        let sftp = self.sftp()?;
+        let path_obj = Path::new(path);
+        let stat = sftp
+            .stat(path_obj)
+            .map_err(|e| SftpFileTransfer::map_error(e, path))?;
+        let name = path_obj
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path)
+            .to_string();
+        Ok(SftpFileTransfer::stat_to_entry(name, path.to_string(), stat))
+    }
+
+    fn mkdir(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.mkdir(Path::new(path), 0o755)
+            .map_err(|e| SftpFileTransfer::map_error(e, path))
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.rename(Path::new(from), Path::new(to), None)
+            .map_err(|e| SftpFileTransfer::map_error(e, from))
+    }
+
+    fn remove_file(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.unlink(Path::new(path))
+            .map_err(|e| SftpFileTransfer::map_error(e, path))
+    }
+
+    fn remove_dir(&mut self, path: &str) -> AppResult<()> {
+        let sftp = self.sftp()?;
+        sftp.rmdir(Path::new(path))
+            .map_err(|e| SftpFileTransfer::map_error(e, path))
+    }
+
+    fn set_mode(&mut self, path: &str, mode: u32) -> AppResult<()> {
+        let stat = ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode),
+            atime: None,
+            mtime: None,
+        };
+        self.sftp()?
+            .setstat(Path::new(path), stat)
+            .map_err(|e| SftpFileTransfer::map_error(e, path))
+    }
+
+    fn symlink(&mut self, path: &str, target: &str) -> AppResult<()> {
+        self.sftp()?
+            .symlink(Path::new(path), Path::new(target))
+            .map_err(|e| SftpFileTransfer::map_error(e, path))
+    }
+
+    fn open_read<'a>(&'a mut self, path: &str) -> AppResult<Box<dyn TransferReader + 'a>> {
+        let session = self.session()?;
+        let (channel, stat) = session
+            .scp_recv(Path::new(path))
+            .map_err(|e| SftpFileTransfer::map_error(e, path))?;
+        Ok(Box::new(ScpReader {
+            channel,
+            remaining: stat.size(),
+        }))
+    }
+
+    fn open_write<'a>(
+        &'a mut self,
+        path: &str,
+        size_hint: Option<u64>,
+    ) -> AppResult<Box<dyn TransferWriter + 'a>> {
+        let size = size_hint
+            .ok_or_else(|| AppError::invalid_argument("SCP 上传必须提供 size_hint"))?;
+        self.open_write_sized(path, size)
+    }
+}
+
+/// 包裹 `scp_recv` 返回的通道，只读取 `ScpFileStat` 报告的精确字节数
+///
+/// SCP 协议没有文件结束标记，多读/少读都会让后续的 `send_eof`/`wait_eof`/`wait_close`
+/// 协议握手卡死或截断文件，因此必须以 `remaining` 严格限制 `read` 返回的数据量
+struct ScpReader<'a> {
+    channel: ssh2::Channel<'a>,
+    remaining: u64,
+}
+
+impl Read for ScpReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+        let limit = (buf.len() as u64).min(self.remaining) as usize;
+        let read = self.channel.read(&mut buf[..limit])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+impl TransferReader for ScpReader<'_> {
+    fn finish(mut self: Box<Self>) -> AppResult<()> {
+        self.channel.send_eof().map_err(AppError::from)?;
+        self.channel.wait_eof().map_err(AppError::from)?;
+        self.channel.wait_close().map_err(AppError::from)?;
+        Ok(())
+    }
+}
+
+struct ScpWriter<'a>(ssh2::Channel<'a>);
+
+impl Write for ScpWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl TransferWriter for ScpWriter<'_> {
+    fn finish(mut self: Box<Self>) -> AppResult<()> {
+        self.0.flush().map_err(AppError::from)?;
+        self.0.send_eof().map_err(AppError::from)?;
+        self.0.wait_eof().map_err(AppError::from)?;
+        self.0.wait_close().map_err(AppError::from)?;
+        Ok(())
+    }
+}
+
+// ================================================================
+// FTP / FTPS 后端
+// ================================================================
+
+/// 基于 `suppaftp` 的 [`FileTransfer`] 实现
+///
+/// `suppaftp::FtpStream` 基于标准库 `TcpStream`，本身就是 `Send`，不需要像
+/// [`SftpFileTransfer`] 那样手动实现 `unsafe impl Send`。
+pub struct FtpFileTransfer {
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    use_tls: bool,
+    stream: Option<FtpStream>,
+}
+
+impl FtpFileTransfer {
+    pub fn new(
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        password: impl Into<String>,
+        use_tls: bool,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: username.into(),
+            password: password.into(),
+            use_tls,
+            stream: None,
+        }
+    }
+
+    fn stream_mut(&mut self) -> AppResult<&mut FtpStream> {
+        self.stream
+            .as_mut()
+            .ok_or_else(|| AppError::new(ErrorCode::Unknown, "FTP 连接尚未建立，请先调用 connect"))
+    }
+
+    /// 把 FTP 应答码映射为统一的 [`AppError`]，与 `sftp_service.rs` 里
+    /// `map_sftp_error` 对 SFTP 错误码的处理方式保持一致
+    fn map_error(e: FtpError, path: &str) -> AppError {
+        match e {
+            FtpError::UnexpectedResponse(response) => {
+                let code = response.status.code();
+                let message = format!("{}: {}", path, response.body);
+                match code {
+                    530 => AppError::auth_failed(message),
+                    550 => AppError::not_found(format!("路径不存在: {}", path)),
+                    553 => AppError::permission_denied(message),
+                    450 | 451 => AppError::remote_io_error(message),
+                    500 | 501 | 502 => AppError::invalid_argument(message),
+                    _ => AppError::new(ErrorCode::RemoteIoError, message),
+                }
+            }
+            FtpError::ConnectionError(io_err) => AppError::from(io_err),
+            other => AppError::new(ErrorCode::RemoteIoError, other.to_string()),
+        }
+    }
+
+    /// FTP 没有通用的 STAT 命令；用 "尝试 CWD 进去再退回来" 的常见技巧判断是否为目录，
+    /// 失败则视为文件，再用 SIZE/MDTM 补齐大小与修改时间
+    fn probe_is_dir(stream: &mut FtpStream, path: &str) -> bool {
+        if stream.cwd(path).is_ok() {
+            let _ = stream.cdup();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl FileTransfer for FtpFileTransfer {
+    fn connect(&mut self) -> AppResult<()> {
+        let addr = format!("{}:{}", self.host, self.port);
+        let mut stream = FtpStream::connect(&addr).map_err(|e| Self::map_error(e, &addr))?;
+
+        if self.use_tls {
+            stream = stream
+                .into_secure(suppaftp::NativeTlsConnector::new(), &self.host)
+                .map_err(|e| AppError::network_lost(format!("FTPS TLS 握手失败: {}", e)))?;
+        }
+
+        stream
+            .login(&self.username, &self.password)
+            .map_err(|e| Self::map_error(e, &addr))?;
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> AppResult<()> {
+        if let Some(mut stream) = self.stream.take() {
+            let _ = stream.quit();
+        }
+        Ok(())
+    }
+
+    fn pwd(&mut self) -> AppResult<String> {
+        let stream = self.stream_mut()?;
+        stream.pwd().map_err(|e| Self::map_error(e, "."))
+    }
+
+    fn list_dir(&mut self, path: &str) -> AppResult<Vec<Entry>> {
+        let names = {
+            let stream = self.stream_mut()?;
+            stream
+                .nlst(Some(path))
+                .map_err(|e| Self::map_error(e, path))?
+        };
+
+        let mut entries = Vec::with_capacity(names.len());
+        for raw_name in names {
+            let name = raw_name.rsplit('/').next().unwrap_or(&raw_name).to_string();
+            if name.is_empty() || name == "." || name == ".." {
+                continue;
+            }
+            let full_path = format!("{}/{}", path.trim_end_matches('/'), name);
+            entries.push(self.stat(&full_path)?);
+        }
+        Ok(entries)
+    }
+
+    fn stat(&mut self, path: &str) -> AppResult<Entry> {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        let stream = self.stream_mut()?;
+
+        if Self::probe_is_dir(stream, path) {
+            return Ok(Entry::Directory {
+                name,
+                path: path.to_string(),
+                mode: None,
+                mtime: None,
+            });
+        }
+
+        let size = stream.size(path).ok().map(|s| s as u64);
+        let mtime = stream.mdtm(path).ok().map(|t| t.and_utc().timestamp());
+
+        if size.is_none() && mtime.is_none() {
+            return Err(AppError::not_found(format!("路径不存在: {}", path)));
+        }
+
+        Ok(Entry::File {
+            name,
+            path: path.to_string(),
+            size,
+            mode: None,
+            mtime,
+        })
+    }
+
+    fn mkdir(&mut self, path: &str) -> AppResult<()> {
+        self.stream_mut()?
+            .mkdir(path)
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> AppResult<()> {
+        self.stream_mut()?
+            .rename(from, to)
+            .map_err(|e| Self::map_error(e, from))
+    }
+
+    fn remove_file(&mut self, path: &str) -> AppResult<()> {
+        self.stream_mut()?
+            .rm(path)
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn remove_dir(&mut self, path: &str) -> AppResult<()> {
+        self.stream_mut()?
+            .rmdir(path)
+            .map_err(|e| Self::map_error(e, path))
+    }
+
+    fn set_mode(&mut self, path: &str, _mode: u32) -> AppResult<()> {
+        // FTP 协议没有标准化的 chmod 机制（`SITE CHMOD` 是部分服务器的非标准扩展），
+        // 保留权限在这个后端上做不到，如实报告不支持而不是假装成功
+        Err(AppError::invalid_argument(format!(
+            "FTP 后端不支持设置文件权限: {}",
+            path
+        )))
+    }
+
+    fn symlink(&mut self, path: &str, _target: &str) -> AppResult<()> {
+        Err(AppError::invalid_argument(format!(
+            "FTP 协议不支持创建符号链接: {}",
+            path
+        )))
+    }
+
+    fn open_read<'a>(&'a mut self, path: &str) -> AppResult<Box<dyn TransferReader + 'a>> {
+        let stream = self.stream_mut()?;
+        let data_stream = stream
+            .retr_as_stream(path)
+            .map_err(|e| Self::map_error(e, path))?;
+        Ok(Box::new(FtpReader {
+            data_stream: Some(data_stream),
+            stream,
+        }))
+    }
+
+    fn open_write<'a>(
+        &'a mut self,
+        path: &str,
+        _size_hint: Option<u64>,
+    ) -> AppResult<Box<dyn TransferWriter + 'a>> {
+        let stream = self.stream_mut()?;
+        let data_stream = stream
+            .put_with_stream(path)
+            .map_err(|e| Self::map_error(e, path))?;
+        Ok(Box::new(FtpWriter {
+            data_stream: Some(data_stream),
+            stream,
+        }))
+    }
+}
+
+struct FtpReader<'a> {
+    data_stream: Option<suppaftp::DataStream>,
+    stream: &'a mut FtpStream,
+}
+
+impl Read for FtpReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data_stream
+            .as_mut()
+            .expect("data_stream 只在 finish 中被取走")
+            .read(buf)
+    }
+}
+
+impl TransferReader for FtpReader<'_> {
+    fn finish(mut self: Box<Self>) -> AppResult<()> {
+        if let Some(data_stream) = self.data_stream.take() {
+            self.stream
+                .finalize_retr_stream(data_stream)
+                .map_err(|e| FtpFileTransfer::map_error(e, ""))?;
+        }
+        Ok(())
+    }
+}
+
+struct FtpWriter<'a> {
+    data_stream: Option<suppaftp::DataStream>,
+    stream: &'a mut FtpStream,
+}
+
+impl Write for FtpWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.data_stream
+            .as_mut()
+            .expect("data_stream 只在 finish 中被取走")
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.data_stream
+            .as_mut()
+            .expect("data_stream 只在 finish 中被取走")
+            .flush()
+    }
+}
+
+impl TransferWriter for FtpWriter<'_> {
+    fn finish(mut self: Box<Self>) -> AppResult<()> {
+        if let Some(data_stream) = self.data_stream.take() {
+            self.stream
+                .finalize_put_stream(data_stream)
+                .map_err(|e| FtpFileTransfer::map_error(e, ""))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// 纯内存实现，只把 [`FileTransfer::remove_dir_all_with_progress`] 依赖的
+    /// `list_dir`/`remove_file`/`remove_dir` 接上一棵固定的目录树；其余方法本测试用不到，
+    /// 保持 `unimplemented!()` 占位即可
+    struct FakeTree {
+        children: HashMap<String, Vec<Entry>>,
+    }
+
+    impl FileTransfer for FakeTree {
+        fn connect(&mut self) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn disconnect(&mut self) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn pwd(&mut self) -> AppResult<String> {
+            unimplemented!()
+        }
+        fn list_dir(&mut self, path: &str) -> AppResult<Vec<Entry>> {
+            Ok(self.children.get(path).cloned().unwrap_or_default())
+        }
+        fn stat(&mut self, _path: &str) -> AppResult<Entry> {
+            unimplemented!()
+        }
+        fn mkdir(&mut self, _path: &str) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn rename(&mut self, _from: &str, _to: &str) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn remove_file(&mut self, _path: &str) -> AppResult<()> {
+            Ok(())
+        }
+        fn remove_dir(&mut self, _path: &str) -> AppResult<()> {
+            Ok(())
+        }
+        fn set_mode(&mut self, _path: &str, _mode: u32) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn symlink(&mut self, _path: &str, _target: &str) -> AppResult<()> {
+            unimplemented!()
+        }
+        fn open_read<'a>(&'a mut self, _path: &str) -> AppResult<Box<dyn TransferReader + 'a>> {
+            unimplemented!()
+        }
+        fn open_write<'a>(
+            &'a mut self,
+            _path: &str,
+            _size_hint: Option<u64>,
+        ) -> AppResult<Box<dyn TransferWriter + 'a>> {
+            unimplemented!()
+        }
+    }
+
+    fn file_entry(path: &str) -> Entry {
+        Entry::File {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            size: Some(0),
+            mode: None,
+            mtime: None,
+        }
+    }
+
+    fn dir_entry(path: &str) -> Entry {
+        Entry::Directory {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            mode: None,
+            mtime: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingProgress {
+        total: Option<u64>,
+        done_calls: Vec<u64>,
+    }
+
+    impl DeleteProgress for RecordingProgress {
+        fn on_total_known(&mut self, total: u64) {
+            self.total = Some(total);
+        }
+        fn on_item_done(&mut self, _path: &str, done: u64, _total: u64) {
+            self.done_calls.push(done);
+        }
+    }
+
+    #[test]
+    fn test_remove_dir_all_with_progress_reports_total_and_monotonic_done() {
+        let mut tree = FakeTree {
+            children: HashMap::from([
+                (
+                    "/root".to_string(),
+                    vec![file_entry("/root/a.txt"), dir_entry("/root/sub")],
+                ),
+                ("/root/sub".to_string(), vec![file_entry("/root/sub/b.txt")]),
+            ]),
+        };
+
+        let mut progress = RecordingProgress::default();
+        let result = tree
+            .remove_dir_all_with_progress("/root", &mut progress)
+            .unwrap();
+
+        // 2 个文件（a.txt, sub/b.txt）+ 2 个目录（sub, root 自身）= 4 项
+        assert_eq!(progress.total, Some(4));
+        assert_eq!(result.succeeded, 4);
+        assert!(result.failures.is_empty());
+        assert_eq!(progress.done_calls, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_remove_dir_all_with_progress_on_empty_dir_reports_total_one() {
+        let mut tree = FakeTree {
+            children: HashMap::from([("/empty".to_string(), vec![])]),
+        };
+
+        let mut progress = RecordingProgress::default();
+        let result = tree
+            .remove_dir_all_with_progress("/empty", &mut progress)
+            .unwrap();
+
+        // 没有子项，只需要删除 /empty 自身这一项
+        assert_eq!(progress.total, Some(1));
+        assert_eq!(result.succeeded, 1);
+        assert_eq!(progress.done_calls, vec![1]);
+    }
+}
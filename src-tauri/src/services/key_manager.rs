@@ -0,0 +1,293 @@
+//! 密钥管理服务
+//!
+//! 负责:
+//! - 在应用内生成 SSH 密钥对（Ed25519/RSA），不需要用户另外安装/调用 `ssh-keygen`
+//! - 管理密钥的生命周期：列出、导出公钥、删除
+//!
+//! 私钥内容本身不归这个模块持久化——生成后立即交给 `security_service` 托管（与 Profile
+//! 手动粘贴的私钥共用同一套 keyring/软件密钥库降级方案），这里的 `Database::managed_key_*`
+//! 只落库可以公开展示的元数据（类型、公钥、指纹），`ManagedKey.id` 才是这份私钥在
+//! 安全存储里的关联线索。
+
+use ssh_key::private::{KeypairData, RsaKeypair};
+use ssh_key::rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::key::{KeyAlgorithm, ManagedKey};
+use crate::services::security_service::{credential_delete, credential_get_private_key};
+use crate::services::storage_service::Database;
+
+/// `RsaKeypair::random` 的位数
+const RSA_2048_BITS: usize = 2048;
+const RSA_4096_BITS: usize = 4096;
+
+/// 生成出的密钥对（私钥文本只在这里短暂经过，立即交给调用方存入安全存储，不落盘）
+struct GeneratedKeypair {
+    private_key_openssh: String,
+    public_key_openssh: String,
+    fingerprint: String,
+    key_type: String,
+}
+
+/// 生成一对新的 SSH 密钥
+fn generate_keypair(
+    algorithm: KeyAlgorithm,
+    comment: &str,
+    passphrase: Option<&str>,
+) -> AppResult<GeneratedKeypair> {
+    let mut private_key = match algorithm {
+        KeyAlgorithm::Ed25519 => PrivateKey::random(&mut OsRng, Algorithm::Ed25519)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("生成 Ed25519 密钥失败: {}", e)))?,
+        KeyAlgorithm::Rsa2048 | KeyAlgorithm::Rsa4096 => {
+            let bits = if algorithm == KeyAlgorithm::Rsa2048 {
+                RSA_2048_BITS
+            } else {
+                RSA_4096_BITS
+            };
+            let keypair = RsaKeypair::random(&mut OsRng, bits).map_err(|e| {
+                AppError::new(ErrorCode::Unknown, format!("生成 RSA-{} 密钥失败: {}", bits, e))
+            })?;
+            PrivateKey::new(KeypairData::from(keypair), comment)
+                .map_err(|e| AppError::new(ErrorCode::Unknown, format!("构造 RSA 私钥失败: {}", e)))?
+        }
+    };
+
+    private_key.set_comment(comment);
+
+    if let Some(pp) = passphrase.filter(|p| !p.is_empty()) {
+        private_key = private_key
+            .encrypt(&mut OsRng, pp)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("私钥加密失败: {}", e)))?;
+    }
+
+    let private_key_openssh = private_key
+        .to_openssh(LineEnding::LF)
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("私钥序列化失败: {}", e)))?
+        .to_string();
+
+    let public_key = private_key.public_key();
+    let public_key_openssh = public_key
+        .to_openssh()
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("公钥序列化失败: {}", e)))?;
+    let fingerprint = public_key.fingerprint(HashAlg::Sha256).to_string();
+    let key_type = public_key.algorithm().to_string();
+
+    Ok(GeneratedKeypair {
+        private_key_openssh,
+        public_key_openssh,
+        fingerprint,
+        key_type,
+    })
+}
+
+/// 生成一个新的托管密钥：创建密钥对、把私钥交给安全存储、把元数据落库
+///
+/// 返回的 [`ManagedKey`] 不含私钥内容本身
+pub fn create_managed_key(
+    db: &Database,
+    name: &str,
+    algorithm: KeyAlgorithm,
+    passphrase: Option<&str>,
+) -> AppResult<ManagedKey> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid_argument("密钥名称不能为空"));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let comment = name.to_string();
+
+    let generated = generate_keypair(algorithm, &comment, passphrase)?;
+
+    let private_key_ref = crate::services::security_service::credential_store_managed_key(
+        db,
+        &id,
+        &generated.private_key_openssh,
+    )?;
+
+    let managed_key = ManagedKey {
+        id,
+        name: name.to_string(),
+        key_type: generated.key_type,
+        public_key: generated.public_key_openssh,
+        fingerprint: generated.fingerprint,
+        encrypted: passphrase.map(|p| !p.is_empty()).unwrap_or(false),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+
+    if let Err(e) = db.managed_key_insert(&managed_key, &private_key_ref) {
+        // 落库失败时回滚已写入的私钥，避免安全存储里留下一条没有元数据指向的孤儿凭据
+        let _ = credential_delete(db, &private_key_ref);
+        return Err(e);
+    }
+
+    tracing::info!(key_id = %managed_key.id, key_type = %managed_key.key_type, "托管密钥已生成");
+
+    Ok(managed_key)
+}
+
+/// 列出所有托管密钥
+pub fn list_managed_keys(db: &Database) -> AppResult<Vec<ManagedKey>> {
+    db.managed_key_list()
+}
+
+/// 导出指定托管密钥的公钥内容
+pub fn export_public_key(db: &Database, key_id: &str) -> AppResult<String> {
+    db.managed_key_get(key_id)?
+        .map(|(key, _private_key_ref)| key.public_key)
+        .ok_or_else(|| AppError::not_found(format!("托管密钥 {} 不存在", key_id)))
+}
+
+/// 删除托管密钥：同时清理安全存储里的私钥内容与元数据
+pub fn delete_managed_key(db: &Database, key_id: &str) -> AppResult<()> {
+    let (_, private_key_ref) = db
+        .managed_key_get(key_id)?
+        .ok_or_else(|| AppError::not_found(format!("托管密钥 {} 不存在", key_id)))?;
+
+    credential_delete(db, &private_key_ref)?;
+    db.managed_key_delete(key_id)?;
+
+    tracing::info!(key_id = %key_id, "托管密钥已删除");
+
+    Ok(())
+}
+
+/// 获取托管密钥的私钥内容，供 `profile_upsert` 把 `managed_key_id` 解析成
+/// `Auth::Key::private_key_ref` 时使用
+pub fn managed_key_private_key_ref(db: &Database, key_id: &str) -> AppResult<String> {
+    let (_, private_key_ref) = db
+        .managed_key_get(key_id)?
+        .ok_or_else(|| AppError::not_found(format!("托管密钥 {} 不存在", key_id)))?;
+
+    // 顺带确认私钥仍然可读（密钥库未解锁等情况下应尽早报错，而不是等到真正连接时才发现）
+    credential_get_private_key(db, &private_key_ref)?
+        .ok_or_else(|| AppError::auth_failed("托管密钥的私钥内容丢失，请重新生成"))?;
+
+    Ok(private_key_ref)
+}
+
+/// 用私钥认证失败时的上下文：底层 `ssh2::Error` 本身分不清"密码错了"和"这把私钥
+/// 格式/加密套件我们不支持"——两者在 libssh2 里报的是同一句模糊的错误消息。携带
+/// 原始私钥文本（如果能拿到）让我们可以自己用 `ssh_key` 重新解析一遍，靠密钥结构
+/// 本身（是否能解析出来、是否标记为已加密）做出更可靠的判断
+pub struct KeyAuthFailure {
+    pub source: ssh2::Error,
+    /// 私钥的原始 OpenSSH 文本；仅当私钥托管在安全存储里（`userauth_pubkey_memory`）
+    /// 时才能拿到，私钥文件路径（`userauth_pubkey_file`）场景下为 `None`
+    pub key_text: Option<String>,
+    pub had_passphrase: bool,
+}
+
+impl From<KeyAuthFailure> for AppError {
+    fn from(failure: KeyAuthFailure) -> Self {
+        let parsed = failure
+            .key_text
+            .as_deref()
+            .and_then(|text| PrivateKey::from_openssh(text).ok());
+
+        let detail = parsed.as_ref().and_then(|key| {
+            let algorithm = key.algorithm().to_string();
+            let comment = key.comment();
+            if !comment.is_empty() {
+                Some(format!("算法: {}，注释: {}", algorithm, comment))
+            } else {
+                Some(format!("算法: {}", algorithm))
+            }
+        });
+
+        let mut err = match parsed.as_ref().map(|key| key.is_encrypted()) {
+            // 能解析出结构且确认是加密私钥：libssh2 拒绝基本就是密码不对
+            Some(true) => AppError::key_decrypt_failed(if failure.had_passphrase {
+                "私钥密码错误，无法解密私钥"
+            } else {
+                "该私钥已加密，需要提供密码"
+            }),
+            // 能解析出结构、确认并未加密，libssh2 仍然拒绝：这把私钥本身是个能被
+            // 正常识别的格式，不存在"解析不了"的问题，更可能是服务器本身不接受
+            // 这把公钥（没加进 authorized_keys、算法被服务器禁用等），按服务端认证
+            // 失败处理，而不是误导用户去怀疑私钥文件本身
+            Some(false) => AppError::auth_failed("私钥可正常识别但未加密，服务器拒绝了此次认证，请确认公钥已加入目标服务器的 authorized_keys 且算法受支持"),
+            // 连结构都解析不出来：文件已损坏，或是我们不支持的私钥格式/加密套件
+            None => AppError::key_parse_error("无法解析私钥文件，可能已损坏或格式不受支持"),
+        };
+
+        if let Some(detail) = detail {
+            err = err.with_detail(detail);
+        }
+
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_key_text(encrypted: bool) -> String {
+        let mut key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        key.set_comment("test-comment");
+        if encrypted {
+            key = key.encrypt(&mut OsRng, "correct-horse-battery-staple").unwrap();
+        }
+        key.to_openssh(LineEnding::LF).unwrap().to_string()
+    }
+
+    fn fake_libssh2_error() -> ssh2::Error {
+        ssh2::Error::new(
+            ssh2::ErrorCode::Session(-16),
+            "Unable to extract public key from private key file: Wrong passphrase or invalid/unrecognized private key file format",
+        )
+    }
+
+    #[test]
+    fn test_encrypted_key_without_passphrase_is_decrypt_failed() {
+        let err = AppError::from(KeyAuthFailure {
+            source: fake_libssh2_error(),
+            key_text: Some(sample_key_text(true)),
+            had_passphrase: false,
+        });
+        assert_eq!(err.code, ErrorCode::KeyDecryptFailed);
+    }
+
+    #[test]
+    fn test_encrypted_key_with_wrong_passphrase_is_decrypt_failed_with_algorithm_detail() {
+        let err = AppError::from(KeyAuthFailure {
+            source: fake_libssh2_error(),
+            key_text: Some(sample_key_text(true)),
+            had_passphrase: true,
+        });
+        assert_eq!(err.code, ErrorCode::KeyDecryptFailed);
+        assert!(err.detail.unwrap().contains("ssh-ed25519"));
+    }
+
+    #[test]
+    fn test_unencrypted_key_rejected_by_server_is_auth_failed() {
+        let err = AppError::from(KeyAuthFailure {
+            source: fake_libssh2_error(),
+            key_text: Some(sample_key_text(false)),
+            had_passphrase: false,
+        });
+        assert_eq!(err.code, ErrorCode::AuthFailed);
+        assert!(err.detail.unwrap().contains("ssh-ed25519"));
+    }
+
+    #[test]
+    fn test_unparseable_key_text_is_parse_error() {
+        let err = AppError::from(KeyAuthFailure {
+            source: fake_libssh2_error(),
+            key_text: Some("not a real private key".to_string()),
+            had_passphrase: false,
+        });
+        assert_eq!(err.code, ErrorCode::KeyParseError);
+    }
+
+    #[test]
+    fn test_missing_key_text_falls_back_to_parse_error() {
+        let err = AppError::from(KeyAuthFailure {
+            source: fake_libssh2_error(),
+            key_text: None,
+            had_passphrase: false,
+        });
+        assert_eq!(err.code, ErrorCode::KeyParseError);
+    }
+}
@@ -1,9 +1,28 @@
+pub mod command_service;
+pub mod config_loader;
+pub mod exclude_matcher;
+pub mod file_transfer;
+pub mod key_manager;
+pub mod key_service;
+pub mod operation_registry;
+pub mod port_forward;
+pub mod retry;
+pub mod schedule_service;
+pub mod search_service;
+pub mod security_audit;
 pub mod security_service;
 pub mod session_manager;
 pub mod sftp_service;
+pub mod shamir;
+pub mod shutdown;
+pub mod ssh_config;
+pub mod ssh_pool;
+pub mod ssh_session;
 pub mod storage_service;
+pub mod system_monitor;
 pub mod terminal_manager;
 pub mod transfer_manager;
+pub mod watch_service;
 
 // Re-exports
 pub use security_service::*;
@@ -0,0 +1,66 @@
+//! 长时间运行的递归操作（删除/复制，未来可扩展到归档打包）取消支持
+//!
+//! 与 [`crate::services::search_service::SearchManager`] 的取消机制一致：操作开始前
+//! 注册一个 operation_id 及其对应的 `AtomicBool` 取消标志，标志的克隆随后被传入
+//! 阻塞的递归函数；`sftp_cancel_operation` 只是把标志置位，真正的提前退出点在各
+//! 递归函数内部（每处理一项前检查一次）。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+
+/// 可取消的长时间操作注册表
+pub struct OperationRegistry {
+    /// operation_id -> 取消标志
+    operations: RwLock<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        Self {
+            operations: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 注册一个新操作，返回生成的 operation_id 及其取消标志
+    pub fn register(&self) -> AppResult<(String, Arc<AtomicBool>)> {
+        let operation_id = uuid::Uuid::new_v4().to_string();
+        let canceled = Arc::new(AtomicBool::new(false));
+
+        let mut operations = self
+            .operations
+            .write()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取操作注册表锁"))?;
+        operations.insert(operation_id.clone(), canceled.clone());
+
+        Ok((operation_id, canceled))
+    }
+
+    /// 取消一个操作（幂等；操作不存在或已结束时静默成功）
+    pub fn cancel(&self, operation_id: &str) -> AppResult<()> {
+        let operations = self
+            .operations
+            .read()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取操作注册表锁"))?;
+        if let Some(canceled) = operations.get(operation_id) {
+            canceled.store(true, Ordering::Relaxed);
+            tracing::info!(operation_id = %operation_id, "取消信号已发送");
+        }
+        Ok(())
+    }
+}
+
+impl Default for OperationRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: OperationRegistry 可以安全地跨线程共享，原因如下：
+// 1. `operations` 使用 RwLock 保护，提供线程安全的访问
+// 2. 取消标志本身是 Arc<AtomicBool>，其克隆被递归函数所在的阻塞线程持有，
+//    注册表里保留的只是另一份克隆，二者通过原子操作通信，不存在数据竞争
+unsafe impl Send for OperationRegistry {}
+unsafe impl Sync for OperationRegistry {}
@@ -0,0 +1,364 @@
+//! TCP/IP 端口转发（crate 名字里的"tunnel"）
+//!
+//! 每一路转发独占一个 [`SshSession`]，跑在专属的后台线程里——这和
+//! `session_manager.rs` 里 Terminal/SFTP 各用各的 session（而不是共享同一个连接做不同
+//! 的事）是同一个道理：libssh2 要求对同一个 session 的所有 channel 操作
+//! （创建 channel、读写）都串行执行，`terminal_manager.rs` 的 `SharedHostSession::io_lock`
+//! 就是给"一个 session 服务多个用途"场景踩过的坑。这里选择更简单也更容易验证正确性
+//! 的方案：一路转发 = 一个独占的 session，线程内部既拥有 session 又拥有它衍生出的所有
+//! channel，完全不需要跨线程共享/加锁。如果要同时开多路转发，调用方应当各开一个
+//! session（成本不高——参见 [`crate::services::ssh_pool::SshConnectionPool`]）。
+//!
+//! session 切到非阻塞模式后，一个线程用一个轮询循环同时服务该 session 上的所有转发
+//! 连接：每一轮对每条连接各尝试搬运一批字节，一轮下来没有任何连接有进展时短暂 sleep
+//! 避免忙等。吞吐因此是分时复用而非真正并行，但正确性优先；往后如果要榨取更高吞吐，
+//! 需要引入更精细的事件驱动 I/O，这里先不做。
+//!
+//! 提供两个方向：
+//! - [`LocalForward`]：本地转发。绑定一个本地 `TcpListener`，每来一个连接就请求远端
+//!   通过 direct-tcpip channel 连到目标地址，双向搬运字节。
+//! - [`forward_listen`]：远程转发。请求 SSH 服务器在它那一侧监听一个端口，每个到来的
+//!   连接都对应一个 channel，本地再发起一个到目标地址的 TCP 连接，双向搬运字节。
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use ssh2::Channel;
+
+use crate::models::error::{AppError, AppResult};
+use crate::services::ssh_session::SshSession;
+
+/// 每次轮询里单条连接最多搬运的字节数，避免一条大流量连接长期独占轮询线程
+const PUMP_CHUNK_SIZE: usize = 16 * 1024;
+/// 轮询一轮下来没有任何连接搬运到数据时的休眠间隔，避免忙等占满 CPU
+const POLL_IDLE_INTERVAL: Duration = Duration::from_millis(5);
+
+/// 一条转发连接：一端是本地 `TcpStream`，另一端是 SSH channel
+struct ForwardPair<'sess> {
+    tcp: TcpStream,
+    channel: Channel<'sess>,
+}
+
+/// 尝试在一条转发连接上双向搬运一批数据
+///
+/// 返回 `true` 表示这一轮搬运了至少一个字节，调用方可以据此决定是否需要休眠；
+/// 当任意一侧确认关闭时返回 `Err`，调用方应当丢弃这条连接。
+fn pump_once(pair: &mut ForwardPair<'_>) -> Result<bool, ()> {
+    let mut progressed = false;
+    let mut buf = [0u8; PUMP_CHUNK_SIZE];
+
+    match pair.tcp.read(&mut buf) {
+        Ok(0) => return Err(()), // 本地连接已关闭
+        Ok(n) => {
+            if write_all_best_effort(&mut pair.channel, &buf[..n]).is_err() {
+                return Err(());
+            }
+            progressed = true;
+        }
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        Err(_) => return Err(()),
+    }
+
+    match pair.channel.read(&mut buf) {
+        Ok(0) => {
+            if pair.channel.eof() {
+                return Err(());
+            }
+        }
+        Ok(n) => {
+            if write_all_best_effort(&mut pair.tcp, &buf[..n]).is_err() {
+                return Err(());
+            }
+            progressed = true;
+        }
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        Err(_) => return Err(()),
+    }
+
+    Ok(progressed)
+}
+
+/// 非阻塞模式下 `write` 也可能返回 `WouldBlock`，这里简单重试直到写完或者真的出错——
+/// 转发场景下每次搬运的数据量有上限（`PUMP_CHUNK_SIZE`），重试不会无限期阻塞轮询线程
+fn write_all_best_effort<W: Write>(writer: &mut W, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        match writer.write(data) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero)),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn pump_all(pairs: &mut Vec<ForwardPair<'_>>) -> bool {
+    let mut progressed = false;
+    pairs.retain_mut(|pair| match pump_once(pair) {
+        Ok(made_progress) => {
+            progressed |= made_progress;
+            true
+        }
+        Err(()) => false,
+    });
+    progressed
+}
+
+/// 本地端口转发：绑定本地地址，把每个接入连接通过 direct-tcpip channel 转发到远端
+/// 可达的目标地址
+pub struct LocalForward {
+    local_addr: std::net::SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl LocalForward {
+    /// 绑定 `bind_host:bind_port`，后台线程持续 accept 并转发到
+    /// `target_host:target_port`（目标地址从 SSH 服务器的网络视角可达，不必是本机能
+    /// 直连的）。`session` 会被这一路转发独占，调用方不应再用它做别的事情
+    /// （见模块文档）
+    pub fn bind(
+        mut session: SshSession,
+        bind_host: &str,
+        bind_port: u16,
+        target_host: String,
+        target_port: u16,
+    ) -> AppResult<Self> {
+        let listener = TcpListener::bind((bind_host, bind_port))
+            .map_err(|e| AppError::local_io_error(format!("绑定本地转发端口失败: {}", e)))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| AppError::local_io_error(format!("设置本地监听为非阻塞失败: {}", e)))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| AppError::local_io_error(format!("读取本地监听地址失败: {}", e)))?;
+        session.set_blocking(false);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_for_thread = stop.clone();
+
+        let handle = thread::spawn(move || {
+            let mut pairs: Vec<ForwardPair<'_>> = Vec::new();
+
+            while !stop_for_thread.load(Ordering::Relaxed) {
+                let mut progressed = accept_new_local_connections(
+                    &listener,
+                    &session,
+                    &target_host,
+                    target_port,
+                    &mut pairs,
+                );
+
+                progressed |= pump_all(&mut pairs);
+
+                if !progressed {
+                    thread::sleep(POLL_IDLE_INTERVAL);
+                }
+            }
+
+            tracing::debug!(local_addr = %local_addr, "本地端口转发线程已退出");
+        });
+
+        tracing::info!(
+            local_addr = %local_addr,
+            target_host = %target_host,
+            target_port,
+            "本地端口转发已启动"
+        );
+
+        Ok(Self {
+            local_addr,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// 实际绑定到的本地地址（`bind_port` 传 0 让系统分配端口时用得上）
+    pub fn local_addr(&self) -> std::net::SocketAddr {
+        self.local_addr
+    }
+
+    /// 停止转发：通知后台线程退出并等待它结束
+    pub fn stop(mut self) {
+        self.stop_inner();
+    }
+
+    fn stop_inner(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for LocalForward {
+    fn drop(&mut self) {
+        self.stop_inner();
+    }
+}
+
+fn accept_new_local_connections<'sess>(
+    listener: &TcpListener,
+    session: &'sess SshSession,
+    target_host: &str,
+    target_port: u16,
+    pairs: &mut Vec<ForwardPair<'sess>>,
+) -> bool {
+    match listener.accept() {
+        Ok((tcp, peer_addr)) => {
+            if tcp.set_nonblocking(true).is_err() {
+                return false;
+            }
+            match session.direct_tcpip(target_host, target_port, None) {
+                Ok(channel) => {
+                    tracing::debug!(peer = %peer_addr, target_host, target_port, "接受本地转发连接");
+                    pairs.push(ForwardPair { tcp, channel });
+                    true
+                }
+                Err(e) => {
+                    tracing::warn!(peer = %peer_addr, error = %e, "创建 direct-tcpip channel 失败，放弃该连接");
+                    false
+                }
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => false,
+        Err(e) => {
+            tracing::warn!(error = %e, "本地转发 accept 失败");
+            false
+        }
+    }
+}
+
+/// 远程端口转发：请求 SSH 服务器在它那一侧监听 `remote_port`，把每个到来的连接
+/// 转发到本地（相对本进程而言）可达的 `target_host:target_port`
+pub struct RemoteForward {
+    bound_port: u16,
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl RemoteForward {
+    /// 服务器实际绑定的端口（`remote_port` 传 0 时由服务器分配，这里能读到分配结果）
+    pub fn bound_port(&self) -> u16 {
+        self.bound_port
+    }
+
+    /// 停止转发：通知后台线程退出并等待它结束
+    pub fn stop(mut self) {
+        self.stop_inner();
+    }
+
+    fn stop_inner(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for RemoteForward {
+    fn drop(&mut self) {
+        self.stop_inner();
+    }
+}
+
+/// 发起远程转发，见 [`RemoteForward`]。`session` 会被这一路转发独占，调用方不应
+/// 再用它做别的事情（见模块文档）
+pub fn forward_listen(
+    mut session: SshSession,
+    remote_port: u16,
+    target_host: String,
+    target_port: u16,
+) -> AppResult<RemoteForward> {
+    session.set_blocking(false);
+
+    // `forward_listen` 只应该在服务器上实际请求一次监听——`remote_port` 传 0 时由
+    // 服务器分配端口，调用两次可能分到两个不同的端口。真正长期持有 listener 的是
+    // 下面的后台线程，因此通过一次性 channel 把它探测到的 bound_port（或失败原因）
+    // 传回调用方，而不是在这里先单独 probe 一次
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<u16, String>>();
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_for_thread = stop.clone();
+
+    let handle = thread::spawn(move || {
+        let mut listener = match session.forward_listen(remote_port, None, None) {
+            Ok((listener, bound_port)) => {
+                ready_tx.send(Ok(bound_port)).ok();
+                listener
+            }
+            Err(e) => {
+                ready_tx.send(Err(e.to_string())).ok();
+                return;
+            }
+        };
+        let mut pairs: Vec<ForwardPair<'_>> = Vec::new();
+
+        while !stop_for_thread.load(Ordering::Relaxed) {
+            let mut progressed =
+                accept_new_remote_connections(&mut listener, &target_host, target_port, &mut pairs);
+
+            progressed |= pump_all(&mut pairs);
+
+            if !progressed {
+                thread::sleep(POLL_IDLE_INTERVAL);
+            }
+        }
+
+        tracing::debug!("远程端口转发线程已退出");
+    });
+
+    let bound_port = match ready_rx.recv() {
+        Ok(Ok(bound_port)) => bound_port,
+        Ok(Err(message)) => {
+            handle.join().ok();
+            return Err(AppError::remote_io_error(format!("请求远程监听失败: {}", message)));
+        }
+        Err(_) => {
+            handle.join().ok();
+            return Err(AppError::remote_io_error("远程转发线程未能启动"));
+        }
+    };
+
+    tracing::info!(
+        remote_port = bound_port,
+        target_host,
+        target_port,
+        "远程端口转发已启动"
+    );
+
+    Ok(RemoteForward {
+        bound_port,
+        stop,
+        handle: Some(handle),
+    })
+}
+
+fn accept_new_remote_connections<'sess>(
+    listener: &mut ssh2::Listener<'sess>,
+    target_host: &str,
+    target_port: u16,
+    pairs: &mut Vec<ForwardPair<'sess>>,
+) -> bool {
+    match listener.accept() {
+        Ok(channel) => match TcpStream::connect((target_host, target_port)) {
+            Ok(tcp) => {
+                if tcp.set_nonblocking(true).is_err() {
+                    return false;
+                }
+                tracing::debug!(target_host, target_port, "接受远程转发连接");
+                pairs.push(ForwardPair { tcp, channel });
+                true
+            }
+            Err(e) => {
+                tracing::warn!(target_host, target_port, error = %e, "连接转发目标失败，放弃该连接");
+                false
+            }
+        },
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,192 @@
+//! 通用重试执行器：依据 [`AppError::retryable`](crate::models::error::AppError) 标志位
+//! 自动重试临时性错误，退避策略采用 full jitter 指数退避
+//! （`delay = rand_uniform(0, min(cap, base * 2^attempt))`），避免大量调用方在固定
+//! 延迟后同时撞回服务器造成惊群效应。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use rand::RngCore;
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+
+/// 针对单个 [`ErrorCode`] 的重试参数覆盖，例如让 `NetworkLost` 比 `RemoteIoError`
+/// 重试得更积极
+#[derive(Debug, Clone)]
+pub struct RetryOverride {
+    pub base: Duration,
+    pub cap: Duration,
+    pub max_attempts: u32,
+}
+
+/// 重试策略：默认的退避参数，外加按错误码的覆盖
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub base: Duration,
+    pub cap: Duration,
+    pub max_attempts: u32,
+    overrides: HashMap<ErrorCode, RetryOverride>,
+}
+
+impl RetryPolicy {
+    pub fn new(base: Duration, cap: Duration, max_attempts: u32) -> Self {
+        Self {
+            base,
+            cap,
+            max_attempts,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn with_override(mut self, code: ErrorCode, over: RetryOverride) -> Self {
+        self.overrides.insert(code, over);
+        self
+    }
+
+    fn params_for(&self, code: &ErrorCode) -> (Duration, Duration, u32) {
+        match self.overrides.get(code) {
+            Some(o) => (o.base, o.cap, o.max_attempts),
+            None => (self.base, self.cap, self.max_attempts),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(200), Duration::from_secs(10), 5)
+    }
+}
+
+/// 反复调用 `op`，直到成功、命中该错误码的 `max_attempts`，或遇到不可重试的错误
+///
+/// 不可重试的错误（`retryable != Some(true)`，例如认证失败、主机密钥不匹配、权限
+/// 拒绝、文件不存在）立即原样返回，不会等待。`Canceled` 即便被标记为可重试也会
+/// 立即短路返回——用户已经明确要求停止，继续重试只会让取消操作显得没有响应
+pub async fn retry_with_backoff<T, F, Fut>(policy: &RetryPolicy, mut op: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut attempts_made: u32 = 0;
+    loop {
+        attempts_made += 1;
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if e.code == ErrorCode::Canceled || e.retryable != Some(true) {
+                    return Err(e);
+                }
+
+                let (base, cap, max_attempts) = policy.params_for(&e.code);
+                if attempts_made >= max_attempts {
+                    return Err(e);
+                }
+
+                tokio::time::sleep(full_jitter_delay(base, cap, attempts_made)).await;
+            }
+        }
+    }
+}
+
+fn full_jitter_delay(base: Duration, cap: Duration, attempt: u32) -> Duration {
+    let exp = base.saturating_mul(1u32 << attempt.min(31));
+    let upper_ms = exp.min(cap).as_millis().max(1) as u64;
+
+    let mut buf = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut buf);
+    let jitter_ms = u64::from_le_bytes(buf) % (upper_ms + 1);
+
+    Duration::from_millis(jitter_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(Duration::from_millis(1), Duration::from_millis(5), 5);
+
+        let result: AppResult<&'static str> = retry_with_backoff(&policy, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(AppError::network_lost("连接暂时丢失"))
+            } else {
+                Ok("ok")
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_propagates_immediately() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(Duration::from_millis(1), Duration::from_millis(5), 5);
+
+        let result: AppResult<()> = retry_with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(AppError::auth_failed("密码错误"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_canceled_short_circuits_even_if_retryable() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(Duration::from_millis(1), Duration::from_millis(5), 5);
+
+        let result: AppResult<()> = retry_with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(AppError::canceled().with_retryable(true))
+        })
+        .await;
+
+        assert_eq!(result.unwrap_err().code, ErrorCode::Canceled);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(Duration::from_millis(1), Duration::from_millis(5), 3);
+
+        let result: AppResult<()> = retry_with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(AppError::network_lost("持续断线"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_per_error_code_override_takes_precedence() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(Duration::from_secs(60), Duration::from_secs(60), 1).with_override(
+            ErrorCode::NetworkLost,
+            RetryOverride {
+                base: Duration::from_millis(1),
+                cap: Duration::from_millis(5),
+                max_attempts: 3,
+            },
+        );
+
+        let result: AppResult<()> = retry_with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(AppError::network_lost("连接暂时丢失"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,590 @@
+//! 目录同步计划服务
+//!
+//! 负责:
+//! - 持久化计划定义（触发规则、本地/远程目录绑定、同步方向）
+//! - 计算下一次触发时间，到期时按 size+mtime 差异入队传输任务
+//! - 单向镜像模式下清理目标侧多出的文件
+//!
+//! 计划本身不拥有 SSH 连接，差异扫描和任务入队都依赖调用方传入的
+//! `SessionManager`/`TransferManager`，与 `TransferManager` 的退避重试
+//! 调度器（`scheduler_tick`）同样由 `lib.rs` 中的周期性 `tokio::spawn`
+//! 循环驱动。
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::UNIX_EPOCH;
+
+use chrono::{Local, TimeZone, Timelike};
+use ssh2::Sftp;
+use tauri::{AppHandle, Emitter};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::schedule::{
+    ScheduleRecurrence, ScheduleRunPayload, ScheduleRunStatus, SyncSchedule,
+};
+use crate::models::transfer_task::TransferDirection;
+use crate::services::session_manager::SessionManager;
+use crate::services::sftp_service::SftpService;
+use crate::services::storage_service::Database;
+use crate::services::transfer_manager::TransferManager;
+
+/// 判定两侧文件 mtime 一致时允许的误差（秒）
+///
+/// 本地 mtime 精确到毫秒、远程 SFTP mtime 仅精确到秒，比较前各自取整到秒，
+/// 仍保留 1 秒容差以避免因取整或时钟精度差异导致的误判
+const MTIME_TOLERANCE_SECS: i64 = 1;
+
+/// 目录同步计划管理器
+pub struct ScheduleManager {
+    db: Arc<Database>,
+    /// schedule_id -> 计划，启动时从数据库加载，后续操作均同步写回数据库
+    schedules: RwLock<HashMap<String, SyncSchedule>>,
+}
+
+impl ScheduleManager {
+    /// 创建新的计划管理器，启动时从数据库加载所有已持久化的计划
+    pub fn new(db: Arc<Database>) -> Self {
+        let loaded = db.sync_schedules_load().unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "加载同步计划失败，以空列表启动");
+            Vec::new()
+        });
+
+        let mut schedules = HashMap::with_capacity(loaded.len());
+        for schedule in loaded {
+            schedules.insert(schedule.schedule_id.clone(), schedule);
+        }
+
+        Self {
+            db,
+            schedules: RwLock::new(schedules),
+        }
+    }
+
+    /// 新建同步计划
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_schedule(
+        &self,
+        session_id: String,
+        local_dir: String,
+        remote_dir: String,
+        direction: TransferDirection,
+        mirror: bool,
+        recurrence: ScheduleRecurrence,
+    ) -> AppResult<SyncSchedule> {
+        if session_id.trim().is_empty() {
+            return Err(AppError::invalid_argument("会话 ID 不能为空"));
+        }
+        if !Path::new(&local_dir).is_dir() {
+            return Err(AppError::not_found(format!(
+                "本地目录不存在: {}",
+                local_dir
+            )));
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let schedule = SyncSchedule {
+            schedule_id: uuid::Uuid::new_v4().to_string(),
+            session_id,
+            local_dir,
+            remote_dir,
+            direction,
+            mirror,
+            next_run_at: compute_next_run_at(&recurrence, now),
+            recurrence,
+            enabled: true,
+            created_at: now,
+            last_run_at: None,
+        };
+
+        self.db.sync_schedule_upsert(&schedule)?;
+        self.schedules
+            .write()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "计划池锁获取失败"))?
+            .insert(schedule.schedule_id.clone(), schedule.clone());
+
+        tracing::info!(schedule_id = %schedule.schedule_id, "同步计划已创建");
+        Ok(schedule)
+    }
+
+    /// 列出所有同步计划
+    pub fn list_schedules(&self) -> AppResult<Vec<SyncSchedule>> {
+        let schedules = self
+            .schedules
+            .read()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "计划池锁获取失败"))?;
+        Ok(schedules.values().cloned().collect())
+    }
+
+    /// 删除同步计划
+    pub fn delete_schedule(&self, schedule_id: &str) -> AppResult<()> {
+        self.db.sync_schedule_delete(schedule_id)?;
+        self.schedules
+            .write()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "计划池锁获取失败"))?
+            .remove(schedule_id);
+        tracing::info!(schedule_id = %schedule_id, "同步计划已删除");
+        Ok(())
+    }
+
+    /// 启用/禁用同步计划
+    pub fn set_enabled(&self, schedule_id: &str, enabled: bool) -> AppResult<()> {
+        let schedule = {
+            let mut schedules = self
+                .schedules
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "计划池锁获取失败"))?;
+            let schedule = schedules
+                .get_mut(schedule_id)
+                .ok_or_else(|| AppError::not_found(format!("计划不存在: {}", schedule_id)))?;
+            schedule.enabled = enabled;
+            schedule.clone()
+        };
+        self.db.sync_schedule_upsert(&schedule)?;
+        Ok(())
+    }
+
+    /// 调度器单次 tick：找出所有到期的已启用计划并逐个执行
+    ///
+    /// 由 `lib.rs` 中的周期性 `tokio::spawn` 循环驱动，tick 之间的差异扫描
+    /// 与任务入队发生在调用方的 tokio 任务中（内部通过 `spawn_blocking` 执行
+    /// 阻塞的本地/远程 IO），因此本方法不会阻塞调用方所在的运行时。
+    pub async fn run_due_schedules(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        transfer_manager: Arc<TransferManager>,
+    ) {
+        let now = chrono::Utc::now().timestamp_millis();
+        let due: Vec<SyncSchedule> = {
+            let Ok(schedules) = self.schedules.read() else {
+                return;
+            };
+            schedules
+                .values()
+                .filter(|s| s.enabled && s.next_run_at <= now)
+                .cloned()
+                .collect()
+        };
+
+        for schedule in due {
+            self.run_schedule(
+                &app,
+                session_manager.clone(),
+                transfer_manager.clone(),
+                schedule,
+            )
+            .await;
+        }
+    }
+
+    /// 执行一次计划运行：扫描差异、入队传输任务、按需清理镜像目标侧多出的文件
+    async fn run_schedule(
+        &self,
+        app: &AppHandle,
+        session_manager: Arc<SessionManager>,
+        transfer_manager: Arc<TransferManager>,
+        schedule: SyncSchedule,
+    ) {
+        let schedule_id = schedule.schedule_id.clone();
+
+        app.emit(
+            "schedule:status",
+            &ScheduleRunPayload {
+                schedule_id: schedule_id.clone(),
+                status: ScheduleRunStatus::Started,
+                files_queued: None,
+                error_message: None,
+            },
+        )
+        .ok();
+
+        let result = self
+            .execute_once(&session_manager, &transfer_manager, &schedule)
+            .await;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let payload = match &result {
+            Ok(0) => ScheduleRunPayload {
+                schedule_id: schedule_id.clone(),
+                status: ScheduleRunStatus::Skipped,
+                files_queued: Some(0),
+                error_message: None,
+            },
+            Ok(queued) => ScheduleRunPayload {
+                schedule_id: schedule_id.clone(),
+                status: ScheduleRunStatus::Completed,
+                files_queued: Some(*queued),
+                error_message: None,
+            },
+            Err(e) => ScheduleRunPayload {
+                schedule_id: schedule_id.clone(),
+                status: ScheduleRunStatus::Failed,
+                files_queued: None,
+                error_message: Some(e.message.clone()),
+            },
+        };
+
+        if let Err(e) = &result {
+            tracing::warn!(schedule_id = %schedule_id, error = %e, "同步计划运行失败");
+        }
+
+        app.emit("schedule:status", &payload).ok();
+
+        // 无论成功失败都推进下一次触发时间并记录本次运行时间，避免失败计划反复立即重试
+        let updated = {
+            let Ok(mut schedules) = self.schedules.write() else {
+                return;
+            };
+            let Some(stored) = schedules.get_mut(&schedule_id) else {
+                return;
+            };
+            stored.last_run_at = Some(now);
+            stored.next_run_at = compute_next_run_at(&stored.recurrence, now);
+            stored.clone()
+        };
+
+        if let Err(e) = self.db.sync_schedule_upsert(&updated) {
+            tracing::warn!(schedule_id = %schedule_id, error = %e, "持久化同步计划失败");
+        }
+    }
+
+    /// 扫描一次差异并入队传输任务，返回入队的文件数
+    async fn execute_once(
+        &self,
+        session_manager: &Arc<SessionManager>,
+        transfer_manager: &Arc<TransferManager>,
+        schedule: &SyncSchedule,
+    ) -> AppResult<u32> {
+        let session = session_manager.get_session(&schedule.session_id)?;
+
+        let local_dir = schedule.local_dir.clone();
+        let remote_dir = SftpService::normalize_path(&schedule.remote_dir);
+
+        // 本地扫描为同步 IO，远程扫描需要持有 sftp 读锁，两者都放到 spawn_blocking 中
+        let remote_dir_for_blocking = remote_dir.clone();
+        let (local_files, remote_files) = tokio::task::spawn_blocking(move || {
+            let local_files = list_local_files_with_meta(&local_dir)?;
+            let remote_files = session.with_sftp(move |sftp| {
+                ensure_remote_dir_exists(sftp, &remote_dir_for_blocking)?;
+                SftpService::list_dir_recursive_with_meta(sftp, &remote_dir_for_blocking)
+            })?;
+            Ok::<_, AppError>((local_files, remote_files))
+        })
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e)))??;
+
+        let remote_map: HashMap<String, (u64, i64)> = remote_files
+            .into_iter()
+            .map(|(rel, size, mtime)| (rel, (size, mtime)))
+            .collect();
+
+        let mut queued = 0u32;
+
+        match schedule.direction {
+            TransferDirection::Upload => {
+                for (relative, local_path, size, mtime_secs) in &local_files {
+                    if files_match(
+                        remote_map.get(relative).copied(),
+                        Some((*size, *mtime_secs)),
+                    ) {
+                        continue;
+                    }
+
+                    let remote_path = join_remote(&remote_dir, relative);
+                    if let Some(parent) = Path::new(&remote_path).parent() {
+                        let parent = parent.to_string_lossy().to_string();
+                        let session = session.clone();
+                        tokio::task::spawn_blocking(move || {
+                            session.with_sftp(move |sftp| ensure_remote_dir_exists(sftp, &parent))
+                        })
+                        .await
+                        .map_err(|e| {
+                            AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e))
+                        })??;
+                    }
+
+                    let file_name = Path::new(relative)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(relative)
+                        .to_string();
+
+                    transfer_manager
+                        .create_sync_file_task(
+                            schedule.session_id.clone(),
+                            TransferDirection::Upload,
+                            local_path.clone(),
+                            remote_path,
+                            file_name,
+                            Some(*size),
+                            Some(mtime_secs * 1000),
+                        )
+                        .await?;
+                    queued += 1;
+                }
+
+                if schedule.mirror {
+                    let local_set: HashSet<&str> =
+                        local_files.iter().map(|(rel, ..)| rel.as_str()).collect();
+                    let extraneous: Vec<&String> = remote_map
+                        .keys()
+                        .filter(|rel| !local_set.contains(rel.as_str()))
+                        .collect();
+                    for relative in extraneous {
+                        let remote_path = join_remote(&remote_dir, relative);
+                        let session = session.clone();
+                        if let Err(e) = tokio::task::spawn_blocking(move || {
+                            session.with_sftp(move |sftp| SftpService::delete(sftp, &remote_path, false))
+                        })
+                        .await
+                        .map_err(|e| {
+                            AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e))
+                        })? {
+                            tracing::warn!(path = %relative, error = %e, "镜像清理远程多余文件失败，跳过");
+                        }
+                    }
+                }
+            }
+            TransferDirection::Download => {
+                for (relative, (size, mtime_secs)) in &remote_map {
+                    let local_meta = local_files
+                        .iter()
+                        .find(|(rel, ..)| rel == relative)
+                        .map(|(_, _, size, mtime)| (*size, *mtime));
+                    if files_match(Some((*size, *mtime_secs)), local_meta) {
+                        continue;
+                    }
+
+                    let local_path = Path::new(&schedule.local_dir).join(relative);
+                    if let Some(parent) = local_path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::LocalIoError,
+                                format!("无法创建本地目录: {}", e),
+                            )
+                        })?;
+                    }
+
+                    let file_name = Path::new(relative)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(relative)
+                        .to_string();
+                    let remote_path = join_remote(&remote_dir, relative);
+
+                    transfer_manager
+                        .create_sync_file_task(
+                            schedule.session_id.clone(),
+                            TransferDirection::Download,
+                            local_path.to_string_lossy().to_string(),
+                            remote_path,
+                            file_name,
+                            Some(*size),
+                            None,
+                        )
+                        .await?;
+                    queued += 1;
+                }
+
+                if schedule.mirror {
+                    for (relative, local_path, ..) in local_files
+                        .iter()
+                        .filter(|(rel, ..)| !remote_map.contains_key(rel))
+                    {
+                        if let Err(e) = std::fs::remove_file(local_path) {
+                            tracing::warn!(path = %relative, error = %e, "镜像清理本地多余文件失败，跳过");
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(queued)
+    }
+}
+
+/// 比较本地/远程两侧的 (size, mtime_secs) 是否视为"未变化"
+///
+/// 任意一侧缺失都视为有差异（新增或已删除）
+fn files_match(a: Option<(u64, i64)>, b: Option<(u64, i64)>) -> bool {
+    match (a, b) {
+        (Some((size_a, mtime_a)), Some((size_b, mtime_b))) => {
+            size_a == size_b && (mtime_a - mtime_b).abs() <= MTIME_TOLERANCE_SECS
+        }
+        _ => false,
+    }
+}
+
+fn join_remote(base: &str, relative: &str) -> String {
+    if base == "/" {
+        format!("/{}", relative)
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), relative)
+    }
+}
+
+/// 确保远程目录存在（递归创建，类似 mkdir -p）
+fn ensure_remote_dir_exists(sftp: &Sftp, path: &str) -> AppResult<()> {
+    let normalized = SftpService::normalize_path(path);
+    if normalized == "/" {
+        return Ok(());
+    }
+
+    let path_obj = Path::new(&normalized);
+    if let Ok(stat) = sftp.stat(path_obj) {
+        return if stat.is_dir() {
+            Ok(())
+        } else {
+            Err(AppError::invalid_argument(format!(
+                "路径已存在但不是目录: {}",
+                normalized
+            )))
+        };
+    }
+
+    if let Some(parent) = path_obj.parent() {
+        let parent_str = parent.to_string_lossy();
+        if parent_str != "/" && !parent_str.is_empty() {
+            ensure_remote_dir_exists(sftp, &parent_str)?;
+        }
+    }
+
+    sftp.mkdir(path_obj, 0o755).or_else(|e| {
+        let is_already_exists =
+            e.code() == ssh2::ErrorCode::SFTP(11) || e.code() == ssh2::ErrorCode::SFTP(4);
+        if is_already_exists {
+            Ok(())
+        } else {
+            Err(AppError::from(e))
+        }
+    })
+}
+
+/// 递归列出本地目录下的所有文件，附带大小与 mtime（秒）
+///
+/// 返回 (relative_path, local_path, size, mtime_secs) 元组列表，跳过符号链接
+fn list_local_files_with_meta(base_path: &str) -> AppResult<Vec<(String, String, u64, i64)>> {
+    let base = Path::new(base_path);
+    let mut files = Vec::new();
+    let mut stack = vec![base.to_path_buf()];
+
+    while let Some(current_path) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&current_path) else {
+            tracing::warn!(path = %current_path.display(), "无法读取目录，跳过");
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            let Ok(metadata) = entry_path.symlink_metadata() else {
+                continue;
+            };
+
+            if metadata.is_symlink() {
+                continue;
+            }
+
+            if metadata.is_dir() {
+                stack.push(entry_path);
+            } else if metadata.is_file() {
+                let Ok(relative) = entry_path.strip_prefix(base) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                let size = metadata.len();
+                let mtime_secs = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0);
+                files.push((
+                    relative,
+                    entry_path.to_string_lossy().to_string(),
+                    size,
+                    mtime_secs,
+                ));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// 根据触发规则计算下一次运行时间（Unix 时间戳毫秒）
+fn compute_next_run_at(recurrence: &ScheduleRecurrence, after: i64) -> i64 {
+    match recurrence {
+        ScheduleRecurrence::EveryMinutes { minutes } => {
+            after + (*minutes).max(1) as i64 * 60 * 1000
+        }
+        ScheduleRecurrence::DailyAt { hour, minute } => {
+            let after_local = Local
+                .timestamp_millis_opt(after)
+                .single()
+                .unwrap_or_else(Local::now);
+
+            let mut candidate = after_local
+                .with_hour(*hour as u32)
+                .and_then(|d| d.with_minute(*minute as u32))
+                .and_then(|d| d.with_second(0))
+                .and_then(|d| d.with_nanosecond(0))
+                .unwrap_or(after_local);
+
+            if candidate <= after_local {
+                candidate += chrono::Duration::days(1);
+            }
+
+            candidate.timestamp_millis()
+        }
+    }
+}
+
+// SAFETY: ScheduleManager 可以安全地跨线程共享，原因如下：
+// 1. `schedules` 使用 RwLock 保护，提供线程安全的访问
+// 2. ScheduleManager 不直接持有 ssh2 的 Session/Sftp，差异扫描通过
+//    `session_manager.get_session()` 按需获取 `Arc<ManagedSession>`，实际的
+//    SFTP 调用经由 `ManagedSession::sftp()` 的读锁在 `spawn_blocking` 中完成，
+//    与 `TransferManager`/`WatchManager` 的做法一致
+unsafe impl Send for ScheduleManager {}
+unsafe impl Sync for ScheduleManager {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_next_run_every_minutes() {
+        let recurrence = ScheduleRecurrence::EveryMinutes { minutes: 15 };
+        let after = 1_700_000_000_000;
+        assert_eq!(
+            compute_next_run_at(&recurrence, after),
+            after + 15 * 60 * 1000
+        );
+    }
+
+    #[test]
+    fn test_compute_next_run_daily_at_is_in_future() {
+        let recurrence = ScheduleRecurrence::DailyAt { hour: 2, minute: 0 };
+        let after = chrono::Utc::now().timestamp_millis();
+        let next = compute_next_run_at(&recurrence, after);
+        assert!(next > after);
+        assert!(next - after <= 24 * 60 * 60 * 1000);
+    }
+
+    #[test]
+    fn test_files_match() {
+        assert!(files_match(Some((10, 100)), Some((10, 100))));
+        assert!(files_match(Some((10, 100)), Some((10, 101))));
+        assert!(!files_match(Some((10, 100)), Some((10, 200))));
+        assert!(!files_match(Some((10, 100)), None));
+        assert!(!files_match(None, None));
+    }
+
+    #[test]
+    fn test_join_remote() {
+        assert_eq!(join_remote("/", "a/b.txt"), "/a/b.txt");
+        assert_eq!(join_remote("/data", "a/b.txt"), "/data/a/b.txt");
+        assert_eq!(join_remote("/data/", "a/b.txt"), "/data/a/b.txt");
+    }
+}
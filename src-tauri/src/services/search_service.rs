@@ -0,0 +1,720 @@
+//! 远程内容搜索服务
+//!
+//! 优先通过 SSH exec 通道调用远程的 `rg`/`grep`（按此优先级探测）做递归搜索，将其
+//! 逐行输出解析为结构化的 [`SearchMatch`]；当远程主机两者都不可用时，退化为基于
+//! SFTP 的客户端遍历。
+//!
+//! MVP 限制：客户端回退遍历仅支持字面量匹配（不支持正则），大文件按 [`FALLBACK_MAX_FILE_BYTES`]
+//! 截断扫描；输出行按 `path:line:byte_offset:text` 用 `splitn(4, ':')` 解析，路径中包含
+//! 字面冒号时会误判，这是可接受的近似。
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use ssh2::{Session, Sftp};
+use tauri::{AppHandle, Emitter};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::search::{
+    SearchMatch, SearchQuery, SearchResultBatchPayload, SearchStatus, SearchStatusPayload,
+};
+use crate::services::session_manager::SessionManager;
+use crate::services::sftp_service::{SftpService, SymlinkMode};
+
+/// 每凑够这么多条匹配就推送一批
+const BATCH_SIZE: usize = 50;
+/// 客户端回退遍历时单个文件最多扫描的字节数，避免大文件拖垮内存
+const FALLBACK_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// 客户端回退遍历的分块读取大小
+const FALLBACK_CHUNK_SIZE: usize = 64 * 1024;
+/// 未显式指定 max_results 时的上限
+const DEFAULT_MAX_RESULTS: u32 = 5000;
+
+/// 远程可用的搜索工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RemoteTool {
+    Rg,
+    Grep,
+    None,
+}
+
+struct ManagedSearch {
+    canceled: Arc<AtomicBool>,
+}
+
+/// 远程内容搜索管理器
+pub struct SearchManager {
+    /// search_id -> 搜索任务
+    searches: RwLock<HashMap<String, Arc<ManagedSearch>>>,
+}
+
+impl SearchManager {
+    pub fn new() -> Self {
+        Self {
+            searches: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 发起一次搜索，立即返回 search_id，搜索在后台线程中进行
+    pub fn start_search(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        session_id: String,
+        query: SearchQuery,
+    ) -> AppResult<String> {
+        if query.pattern.trim().is_empty() {
+            return Err(AppError::invalid_argument("搜索模式不能为空"));
+        }
+
+        let search_id = uuid::Uuid::new_v4().to_string();
+        let canceled = Arc::new(AtomicBool::new(false));
+        let managed = Arc::new(ManagedSearch {
+            canceled: canceled.clone(),
+        });
+
+        {
+            let mut searches = self
+                .searches
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取搜索任务锁"))?;
+            searches.insert(search_id.clone(), managed);
+        }
+
+        tracing::info!(search_id = %search_id, session_id = %session_id, root = %query.root_path, "开始远程搜索");
+
+        {
+            let search_id = search_id.clone();
+            thread::spawn(move || {
+                Self::run_search(
+                    &app,
+                    &session_manager,
+                    &session_id,
+                    &search_id,
+                    query,
+                    &canceled,
+                );
+            });
+        }
+
+        Ok(search_id)
+    }
+
+    /// 取消一次搜索（幂等；搜索已结束时静默成功）
+    pub fn cancel_search(&self, search_id: &str) -> AppResult<()> {
+        let searches = self
+            .searches
+            .read()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取搜索任务锁"))?;
+        if let Some(managed) = searches.get(search_id) {
+            managed.canceled.store(true, Ordering::Relaxed);
+            tracing::info!(search_id = %search_id, "取消信号已发送");
+        }
+        Ok(())
+    }
+
+    /// 执行一次搜索，返回（是否因取消而提前结束的结果, 已匹配数量）
+    ///
+    /// 匹配数量和已 flush 的批次即使在 `result` 为 `Err` 时也反映了错误发生前的真实进度——
+    /// worker 线程的任务闭包必须是 `'static` 的（见 [`ManagedSession::with_session`]/
+    /// [`ManagedSession::with_sftp`]），不能像过去那样直接借用本函数栈上的
+    /// `matched_count`/`batch`，所以这两个值改为闭包内部的局部状态，随任务的
+    /// `Ok`/`Err` 一起带出来
+    fn run_search_inner(
+        app: &AppHandle,
+        session_manager: &Arc<SessionManager>,
+        session_id: &str,
+        search_id: &str,
+        query: SearchQuery,
+        canceled: &Arc<AtomicBool>,
+    ) -> (AppResult<bool>, u32) {
+        let max_results = query.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+        let session = match session_manager.get_session(session_id) {
+            Ok(session) => session,
+            Err(e) => return (Err(e), 0),
+        };
+
+        let tool = match session.with_session(|session_guard| Ok(Self::detect_remote_tool(session_guard))) {
+            Ok(tool) => tool,
+            Err(e) => return (Err(e), 0),
+        };
+
+        let app = app.clone();
+        let search_id_owned = search_id.to_string();
+        let canceled = canceled.clone();
+
+        let outcome = match tool {
+            RemoteTool::Rg | RemoteTool::Grep => session.with_session(move |session_guard| {
+                let mut matched_count: u32 = 0;
+                let mut batch: Vec<SearchMatch> = Vec::with_capacity(BATCH_SIZE);
+                let mut on_match = |m: SearchMatch| {
+                    matched_count += 1;
+                    batch.push(m);
+                    if batch.len() >= BATCH_SIZE {
+                        Self::flush_batch(&app, &search_id_owned, &mut batch);
+                    }
+                };
+
+                let result = Self::search_via_exec(
+                    session_guard,
+                    tool,
+                    &query,
+                    &canceled,
+                    max_results,
+                    &mut on_match,
+                );
+
+                if !batch.is_empty() {
+                    Self::flush_batch(&app, &search_id_owned, &mut batch);
+                }
+
+                Ok((result, matched_count))
+            }),
+            RemoteTool::None => {
+                tracing::info!(search_id = %search_id, "远程未找到 rg/grep，回退到 SFTP 客户端遍历");
+                session.with_sftp(move |sftp| {
+                    let mut matched_count: u32 = 0;
+                    let mut batch: Vec<SearchMatch> = Vec::with_capacity(BATCH_SIZE);
+                    let mut on_match = |m: SearchMatch| {
+                        matched_count += 1;
+                        batch.push(m);
+                        if batch.len() >= BATCH_SIZE {
+                            Self::flush_batch(&app, &search_id_owned, &mut batch);
+                        }
+                    };
+
+                    let result =
+                        Self::search_via_sftp_walk(sftp, &query, &canceled, max_results, &mut on_match);
+
+                    if !batch.is_empty() {
+                        Self::flush_batch(&app, &search_id_owned, &mut batch);
+                    }
+
+                    Ok((result, matched_count))
+                })
+            }
+        };
+
+        match outcome {
+            Ok((result, matched_count)) => (result, matched_count),
+            Err(e) => (Err(e), 0),
+        }
+    }
+
+    fn run_search(
+        app: &AppHandle,
+        session_manager: &Arc<SessionManager>,
+        session_id: &str,
+        search_id: &str,
+        query: SearchQuery,
+        canceled: &Arc<AtomicBool>,
+    ) {
+        let (result, matched_count) =
+            Self::run_search_inner(app, session_manager, session_id, search_id, query, canceled);
+
+        let status_payload = match result {
+            Ok(true) => SearchStatusPayload {
+                search_id: search_id.to_string(),
+                status: SearchStatus::Canceled,
+                matched_count,
+                error_code: None,
+                error_message: None,
+            },
+            Ok(false) => {
+                tracing::info!(search_id = %search_id, matched_count, "搜索完成");
+                SearchStatusPayload {
+                    search_id: search_id.to_string(),
+                    status: SearchStatus::Completed,
+                    matched_count,
+                    error_code: None,
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                tracing::warn!(search_id = %search_id, error = %e, "搜索失败");
+                SearchStatusPayload {
+                    search_id: search_id.to_string(),
+                    status: SearchStatus::Error,
+                    matched_count,
+                    error_code: Some(format!("{:?}", e.code)),
+                    error_message: Some(e.message),
+                }
+            }
+        };
+        app.emit("search:status", &status_payload).ok();
+    }
+
+    fn flush_batch(app: &AppHandle, search_id: &str, batch: &mut Vec<SearchMatch>) {
+        let payload = SearchResultBatchPayload {
+            search_id: search_id.to_string(),
+            matches: std::mem::take(batch),
+        };
+        app.emit("search:result", &payload).ok();
+    }
+
+    /// 探测远程是否存在可用的搜索工具
+    fn detect_remote_tool(session: &Session) -> RemoteTool {
+        if Self::probe_command(session, "rg") {
+            RemoteTool::Rg
+        } else if Self::probe_command(session, "grep") {
+            RemoteTool::Grep
+        } else {
+            RemoteTool::None
+        }
+    }
+
+    fn probe_command(session: &Session, name: &str) -> bool {
+        let Ok(mut channel) = session.channel_session() else {
+            return false;
+        };
+        if channel
+            .exec(&format!("command -v {} >/dev/null 2>&1", name))
+            .is_err()
+        {
+            return false;
+        }
+        let mut discard = String::new();
+        let _ = channel.read_to_string(&mut discard);
+        channel.wait_close().ok();
+        channel.exit_status().unwrap_or(1) == 0
+    }
+
+    /// 使用远程 `rg`/`grep`（文件名搜索时为 `find`）执行搜索
+    ///
+    /// 返回 `true` 表示因取消而提前结束
+    fn search_via_exec(
+        session: &Session,
+        tool: RemoteTool,
+        query: &SearchQuery,
+        canceled: &AtomicBool,
+        max_results: u32,
+        on_match: &mut dyn FnMut(SearchMatch),
+    ) -> AppResult<bool> {
+        let command = if query.search_contents {
+            match tool {
+                RemoteTool::Rg => Self::build_rg_command(query),
+                RemoteTool::Grep => Self::build_grep_command(query),
+                RemoteTool::None => unreachable!(),
+            }
+        } else {
+            Self::build_find_name_command(query)
+        };
+
+        let mut channel = session
+            .channel_session()
+            .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法创建通道: {}", e)))?;
+        channel.exec(&command).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("无法执行搜索命令: {}", e))
+        })?;
+
+        let mut line_buf = String::new();
+        let mut chunk = [0u8; 8192];
+        let mut count: u32 = 0;
+        let mut was_canceled = false;
+
+        'read_loop: loop {
+            if canceled.load(Ordering::Relaxed) {
+                was_canceled = true;
+                break;
+            }
+
+            let bytes_read = match channel.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    return Err(AppError::new(
+                        ErrorCode::RemoteIoError,
+                        format!("读取搜索输出失败: {}", e),
+                    ));
+                }
+            };
+
+            line_buf.push_str(&String::from_utf8_lossy(&chunk[..bytes_read]));
+
+            while let Some(pos) = line_buf.find('\n') {
+                let line = line_buf[..pos].to_string();
+                line_buf.drain(..=pos);
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                if let Some(m) = Self::parse_match_line(&line, query.search_contents) {
+                    on_match(m);
+                    count += 1;
+                    if count >= max_results {
+                        break 'read_loop;
+                    }
+                }
+            }
+        }
+
+        // 最佳努力取消：关闭通道，远程进程可能需要等待自身下一次写入失败才会退出
+        channel.close().ok();
+        channel.wait_close().ok();
+
+        Ok(was_canceled)
+    }
+
+    fn parse_match_line(line: &str, search_contents: bool) -> Option<SearchMatch> {
+        if !search_contents {
+            // find 的输出每行就是一个文件路径
+            return Some(SearchMatch {
+                path: line.to_string(),
+                line_number: None,
+                line: None,
+                byte_offset: None,
+            });
+        }
+
+        // rg/grep 在 -n -b -H/--with-filename 下的输出格式: path:line_number:byte_offset:text
+        let mut parts = line.splitn(4, ':');
+        let path = parts.next()?.to_string();
+        let line_number = parts.next()?.parse::<u32>().ok();
+        let byte_offset = parts.next()?.parse::<u64>().ok();
+        let text = parts.next().unwrap_or("").to_string();
+
+        Some(SearchMatch {
+            path,
+            line_number,
+            line: Some(text),
+            byte_offset,
+        })
+    }
+
+    fn build_rg_command(query: &SearchQuery) -> String {
+        let mut args = vec![
+            "rg".to_string(),
+            "--line-number".to_string(),
+            "--no-heading".to_string(),
+            "--with-filename".to_string(),
+            "--byte-offset".to_string(),
+            "--color=never".to_string(),
+        ];
+        if !query.case_sensitive {
+            args.push("-i".to_string());
+        }
+        if !query.is_regex {
+            args.push("-F".to_string());
+        }
+        if let Some(depth) = query.max_depth {
+            args.push(format!("--max-depth={}", depth));
+        }
+        for glob in &query.include_globs {
+            args.push("-g".to_string());
+            args.push(Self::shell_quote(glob));
+        }
+        for glob in &query.exclude_globs {
+            args.push("-g".to_string());
+            args.push(Self::shell_quote(&format!("!{}", glob)));
+        }
+        args.push("--".to_string());
+        args.push(Self::shell_quote(&query.pattern));
+        args.push(Self::shell_quote(&query.root_path));
+        args.join(" ")
+    }
+
+    fn build_grep_command(query: &SearchQuery) -> String {
+        // GNU grep 没有递归的 --max-depth 选项，这里忽略 max_depth（MVP 限制）
+        let mut args = vec![
+            "grep".to_string(),
+            "-r".to_string(),
+            "-n".to_string(),
+            "-b".to_string(),
+            "-H".to_string(),
+        ];
+        if !query.case_sensitive {
+            args.push("-i".to_string());
+        }
+        args.push(if query.is_regex { "-E" } else { "-F" }.to_string());
+        for glob in &query.include_globs {
+            args.push(Self::shell_quote(&format!("--include={}", glob)));
+        }
+        for glob in &query.exclude_globs {
+            args.push(Self::shell_quote(&format!("--exclude={}", glob)));
+        }
+        args.push("--".to_string());
+        args.push(Self::shell_quote(&query.pattern));
+        args.push(Self::shell_quote(&query.root_path));
+        args.join(" ")
+    }
+
+    fn build_find_name_command(query: &SearchQuery) -> String {
+        let mut cmd = format!("find {}", Self::shell_quote(&query.root_path));
+        if let Some(depth) = query.max_depth {
+            cmd.push_str(&format!(" -maxdepth {}", depth));
+        }
+        cmd.push_str(" -type f");
+
+        if query.is_regex {
+            cmd.push_str(&format!(
+                " -regextype posix-extended -regex {}",
+                Self::shell_quote(&format!(".*{}.*", query.pattern))
+            ));
+        } else {
+            let name_flag = if query.case_sensitive {
+                "-name"
+            } else {
+                "-iname"
+            };
+            cmd.push_str(&format!(
+                " {} {}",
+                name_flag,
+                Self::shell_quote(&format!("*{}*", query.pattern))
+            ));
+        }
+        cmd
+    }
+
+    /// 以单引号包裹参数，转义内部的单引号，防止用户输入的 pattern/路径被解释为 shell 命令
+    fn shell_quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+
+    /// 回退方案：当远程没有 rg/grep 时，通过 SFTP 客户端遍历目录并做字面量匹配
+    ///
+    /// 返回 `true` 表示因取消而提前结束
+    fn search_via_sftp_walk(
+        sftp: &Sftp,
+        query: &SearchQuery,
+        canceled: &AtomicBool,
+        max_results: u32,
+        on_match: &mut dyn FnMut(SearchMatch),
+    ) -> AppResult<bool> {
+        let (files, _symlink_issues) =
+            SftpService::list_dir_recursive(sftp, &query.root_path, SymlinkMode::Skip)?;
+        let pattern = if query.case_sensitive {
+            query.pattern.clone()
+        } else {
+            query.pattern.to_lowercase()
+        };
+
+        let mut count: u32 = 0;
+
+        for (full_path, relative_path) in files {
+            if canceled.load(Ordering::Relaxed) {
+                return Ok(true);
+            }
+
+            if !Self::passes_glob_filters(
+                &relative_path,
+                &query.include_globs,
+                &query.exclude_globs,
+            ) {
+                continue;
+            }
+
+            if !query.search_contents {
+                let name = full_path.rsplit('/').next().unwrap_or(&full_path);
+                let haystack = if query.case_sensitive {
+                    name.to_string()
+                } else {
+                    name.to_lowercase()
+                };
+                if haystack.contains(&pattern) {
+                    on_match(SearchMatch {
+                        path: full_path,
+                        line_number: None,
+                        line: None,
+                        byte_offset: None,
+                    });
+                    count += 1;
+                    if count >= max_results {
+                        return Ok(false);
+                    }
+                }
+                continue;
+            }
+
+            let matches_in_file =
+                Self::scan_file_contents(sftp, &full_path, &pattern, query.case_sensitive)?;
+            for m in matches_in_file {
+                on_match(m);
+                count += 1;
+                if count >= max_results {
+                    return Ok(false);
+                }
+                if canceled.load(Ordering::Relaxed) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// 逐块读取远程文件内容，按行做字面量匹配（不支持正则）
+    fn scan_file_contents(
+        sftp: &Sftp,
+        path: &str,
+        pattern: &str,
+        case_sensitive: bool,
+    ) -> AppResult<Vec<SearchMatch>> {
+        let mut file = match sftp.open(std::path::Path::new(path)) {
+            Ok(f) => f,
+            Err(e) => {
+                tracing::debug!(path = %path, error = %e, "无法打开文件，跳过");
+                return Ok(Vec::new());
+            }
+        };
+
+        let mut matches = Vec::new();
+        let mut buffer = vec![0u8; FALLBACK_CHUNK_SIZE];
+        let mut leftover = String::new();
+        let mut bytes_read_total: u64 = 0;
+        let mut line_number: u32 = 0;
+        let mut line_start_offset: u64 = 0;
+
+        loop {
+            if bytes_read_total >= FALLBACK_MAX_FILE_BYTES {
+                break;
+            }
+
+            let n = match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    tracing::debug!(path = %path, error = %e, "读取文件失败，跳过剩余内容");
+                    break;
+                }
+            };
+            bytes_read_total += n as u64;
+
+            leftover.push_str(&String::from_utf8_lossy(&buffer[..n]));
+
+            while let Some(pos) = leftover.find('\n') {
+                let line = leftover[..pos].to_string();
+                leftover.drain(..=pos);
+                line_number += 1;
+
+                let haystack = if case_sensitive {
+                    line.clone()
+                } else {
+                    line.to_lowercase()
+                };
+                if haystack.contains(pattern) {
+                    matches.push(SearchMatch {
+                        path: path.to_string(),
+                        line_number: Some(line_number),
+                        line: Some(line.clone()),
+                        byte_offset: Some(line_start_offset),
+                    });
+                }
+                line_start_offset += line.len() as u64 + 1;
+            }
+        }
+
+        Ok(matches)
+    }
+
+    fn passes_glob_filters(
+        relative_path: &str,
+        include_globs: &[String],
+        exclude_globs: &[String],
+    ) -> bool {
+        let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+
+        if !include_globs.is_empty() && !include_globs.iter().any(|g| Self::glob_match(g, name)) {
+            return false;
+        }
+        if exclude_globs.iter().any(|g| Self::glob_match(g, name)) {
+            return false;
+        }
+        true
+    }
+
+    /// 简单的 glob 匹配，支持 `*`（任意长度）和 `?`（单字符）
+    fn glob_match(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        Self::glob_match_rec(&pattern, &name)
+    }
+
+    fn glob_match_rec(pattern: &[char], name: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some('*') => {
+                Self::glob_match_rec(&pattern[1..], name)
+                    || (!name.is_empty() && Self::glob_match_rec(pattern, &name[1..]))
+            }
+            Some('?') => !name.is_empty() && Self::glob_match_rec(&pattern[1..], &name[1..]),
+            Some(c) => name.first() == Some(c) && Self::glob_match_rec(&pattern[1..], &name[1..]),
+        }
+    }
+}
+
+impl Default for SearchManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: SearchManager 可以安全地跨线程共享，原因如下：
+// 1. `searches` 使用 RwLock 保护，提供线程安全的访问
+// 2. ManagedSearch 不直接持有 ssh2 的 Session/Sftp，而是通过
+//    `session_manager.get_session()` 按需获取 `Arc<ManagedSession>`，
+//    实际的 exec/SFTP 调用经由 `ManagedSession::session()`/`::sftp()` 的读锁完成
+// 3. 每次搜索在独立的 OS 线程中进行（而非 tokio 任务），做法与 WatchManager/
+//    TerminalManager 一致，避免同步的 ssh2 调用阻塞 tokio 运行时
+unsafe impl Send for SearchManager {}
+unsafe impl Sync for SearchManager {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_manager_creation() {
+        let manager = SearchManager::new();
+        assert!(manager.cancel_search("nonexistent").is_ok());
+    }
+
+    #[test]
+    fn test_shell_quote_escapes_single_quote() {
+        assert_eq!(SearchManager::shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(SearchManager::shell_quote("simple"), "'simple'");
+    }
+
+    #[test]
+    fn test_parse_match_line_content() {
+        let m = SearchManager::parse_match_line("/a/b.txt:12:345:hello world", true).unwrap();
+        assert_eq!(m.path, "/a/b.txt");
+        assert_eq!(m.line_number, Some(12));
+        assert_eq!(m.byte_offset, Some(345));
+        assert_eq!(m.line.as_deref(), Some("hello world"));
+    }
+
+    #[test]
+    fn test_parse_match_line_name_only() {
+        let m = SearchManager::parse_match_line("/a/b.txt", false).unwrap();
+        assert_eq!(m.path, "/a/b.txt");
+        assert!(m.line_number.is_none());
+        assert!(m.line.is_none());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(SearchManager::glob_match("*.txt", "a.txt"));
+        assert!(!SearchManager::glob_match("*.txt", "a.rs"));
+        assert!(SearchManager::glob_match("a?c", "abc"));
+        assert!(!SearchManager::glob_match("a?c", "abbc"));
+    }
+
+    #[test]
+    fn test_passes_glob_filters() {
+        assert!(SearchManager::passes_glob_filters(
+            "src/main.rs",
+            &["*.rs".to_string()],
+            &[]
+        ));
+        assert!(!SearchManager::passes_glob_filters(
+            "src/main.rs",
+            &[],
+            &["*.rs".to_string()]
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+//! 远程主机安全审计
+//!
+//! 在一个已建立的 SSH session 上跑一组只读检查（不落任何修改），把结果渲染成
+//! 一份独立的 HTML 报告，写到与 `export_diagnostic_package` 相同的 app data 目录下。
+//!
+//! 每条检查都遵循同一个形状：执行一条只读命令 -> 按规则判断是否命中 -> 生成
+//! 一条带严重程度、证据原文、修复建议的 [`AuditFinding`]。命令本身执行失败
+//! （工具缺失、权限不足等）不会中断整个审计，只是跳过这一条检查。
+
+use std::io::Read;
+
+use ssh2::Session;
+
+use crate::models::audit::{AuditFinding, AuditReport, AuditSeverity};
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::services::storage_service::{get_app_data_dir, Database};
+
+/// 在远程 session 上执行一条命令并读取完整 stdout（阻塞，调用方负责线程隔离）
+///
+/// 命令执行失败（无法创建通道等）返回 `Err`；命令跑起来但以非零退出码结束
+/// 仍然返回 `Ok`，因为很多检查本就是靠退出码/空输出来判断"未命中"
+fn exec_capture(session: &Session, command: &str) -> AppResult<(String, i32)> {
+    let mut channel = session
+        .channel_session()
+        .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法创建通道: {}", e)))?;
+    channel
+        .exec(command)
+        .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法执行命令: {}", e)))?;
+
+    let mut output = String::new();
+    channel
+        .read_to_string(&mut output)
+        .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法读取输出: {}", e)))?;
+
+    channel.wait_close().ok();
+    let exit_status = channel.exit_status().unwrap_or(-1);
+    Ok((output, exit_status))
+}
+
+/// 世界可写检查覆盖的敏感目录
+const WORLD_WRITABLE_DIRS: &[&str] = &["/etc", "/usr/bin", "/usr/sbin", "/usr/local/bin"];
+
+fn check_world_writable(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let dirs = WORLD_WRITABLE_DIRS.join(" ");
+    let command = format!(
+        "find {} -xdev -type f -perm -0002 2>/dev/null | head -n 50",
+        dirs
+    );
+    let Ok((output, _)) = exec_capture(session, &command) else {
+        return;
+    };
+    let matches: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    if !matches.is_empty() {
+        findings.push(AuditFinding {
+            category: "文件权限".to_string(),
+            severity: AuditSeverity::High,
+            title: "发现敏感目录下存在世界可写文件".to_string(),
+            evidence: matches.join("\n"),
+            remediation: "对列出的文件执行 chmod o-w，移除其他用户的写权限".to_string(),
+        });
+    }
+}
+
+fn check_sshd_config(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let command = "sshd -T 2>/dev/null || cat /etc/ssh/sshd_config 2>/dev/null";
+    let Ok((output, _)) = exec_capture(session, command) else {
+        return;
+    };
+    let lower = output.to_lowercase();
+
+    let has_directive = |name: &str, value: &str| {
+        lower
+            .lines()
+            .any(|l| l.trim().starts_with(&format!("{} {}", name, value)))
+    };
+
+    if has_directive("permitrootlogin", "yes") {
+        findings.push(AuditFinding {
+            category: "SSH 配置".to_string(),
+            severity: AuditSeverity::Critical,
+            title: "sshd 允许 root 直接登录".to_string(),
+            evidence: "PermitRootLogin yes".to_string(),
+            remediation: "在 sshd_config 中设置 PermitRootLogin no 或 prohibit-password".to_string(),
+        });
+    }
+
+    if has_directive("passwordauthentication", "yes") {
+        findings.push(AuditFinding {
+            category: "SSH 配置".to_string(),
+            severity: AuditSeverity::Medium,
+            title: "sshd 允许密码认证".to_string(),
+            evidence: "PasswordAuthentication yes".to_string(),
+            remediation: "改用密钥认证，设置 PasswordAuthentication no".to_string(),
+        });
+    }
+
+    if has_directive("permitemptypasswords", "yes") {
+        findings.push(AuditFinding {
+            category: "SSH 配置".to_string(),
+            severity: AuditSeverity::Critical,
+            title: "sshd 允许空密码登录".to_string(),
+            evidence: "PermitEmptyPasswords yes".to_string(),
+            remediation: "设置 PermitEmptyPasswords no".to_string(),
+        });
+    }
+}
+
+fn check_shadow_permissions(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let Ok((output, status)) = exec_capture(session, "stat -c '%a %U %G' /etc/shadow 2>/dev/null")
+    else {
+        return;
+    };
+    if status != 0 {
+        return;
+    }
+    let trimmed = output.trim();
+    let mode = trimmed.split_whitespace().next().unwrap_or("");
+    let world_readable = mode
+        .chars()
+        .last()
+        .and_then(|c| c.to_digit(8))
+        .map(|last| last & 0b100 != 0)
+        .unwrap_or(false);
+    if world_readable {
+        findings.push(AuditFinding {
+            category: "文件权限".to_string(),
+            severity: AuditSeverity::Critical,
+            title: "/etc/shadow 对其他用户可读".to_string(),
+            evidence: trimmed.to_string(),
+            remediation: "执行 chmod 640 /etc/shadow，确保仅 root 可读".to_string(),
+        });
+    }
+}
+
+fn check_authorized_keys_permissions(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let command = "for f in $(find /root /home -maxdepth 3 -name authorized_keys 2>/dev/null); do stat -c '%a %U %n' \"$f\"; done";
+    let Ok((output, _)) = exec_capture(session, command) else {
+        return;
+    };
+    let loose: Vec<&str> = output
+        .lines()
+        .filter(|line| {
+            let Some(mode) = line.split_whitespace().next() else {
+                return false;
+            };
+            mode.chars()
+                .nth(1)
+                .and_then(|c| c.to_digit(8))
+                .map(|group| group & 0b110 != 0)
+                .unwrap_or(false)
+        })
+        .collect();
+    if !loose.is_empty() {
+        findings.push(AuditFinding {
+            category: "文件权限".to_string(),
+            severity: AuditSeverity::High,
+            title: "authorized_keys 权限过于宽松".to_string(),
+            evidence: loose.join("\n"),
+            remediation: "执行 chmod 600 对应文件，确保仅属主可读写".to_string(),
+        });
+    }
+}
+
+fn check_listening_ports(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let command = "ss -tulpn 2>/dev/null || netstat -tulpn 2>/dev/null";
+    let Ok((output, _)) = exec_capture(session, command) else {
+        return;
+    };
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() > 1 {
+        findings.push(AuditFinding {
+            category: "网络暴露面".to_string(),
+            severity: AuditSeverity::Info,
+            title: "监听端口清单".to_string(),
+            evidence: lines.join("\n"),
+            remediation: "核对清单，关闭不再需要对外暴露的服务".to_string(),
+        });
+    }
+}
+
+fn check_sudoers(session: &Session, findings: &mut Vec<AuditFinding>) {
+    let command = "grep -R -n 'NOPASSWD' /etc/sudoers /etc/sudoers.d 2>/dev/null";
+    let Ok((output, _)) = exec_capture(session, command) else {
+        return;
+    };
+    let matches: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    if !matches.is_empty() {
+        findings.push(AuditFinding {
+            category: "Sudo 配置".to_string(),
+            severity: AuditSeverity::Medium,
+            title: "sudoers 中存在 NOPASSWD 规则".to_string(),
+            evidence: matches.join("\n"),
+            remediation: "确认这些免密 sudo 规则确有必要，否则移除 NOPASSWD".to_string(),
+        });
+    }
+}
+
+/// 对一个已连接的 session 跑完整套只读安全检查
+fn run_checks(session: &Session) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+    check_sshd_config(session, &mut findings);
+    check_shadow_permissions(session, &mut findings);
+    check_authorized_keys_permissions(session, &mut findings);
+    check_world_writable(session, &mut findings);
+    check_listening_ports(session, &mut findings);
+    check_sudoers(session, &mut findings);
+    findings
+}
+
+/// 对指定 session 执行安全审计，返回结构化结果
+pub fn audit_session(db: &Database, session: &Session, session_id: &str, profile_id: &str) -> AppResult<AuditReport> {
+    let host = db
+        .profile_get(profile_id)?
+        .map(|p| format!("{}@{}:{}", p.username, p.host, p.port))
+        .unwrap_or_else(|| profile_id.to_string());
+
+    let mut findings = run_checks(session);
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+
+    Ok(AuditReport {
+        session_id: session_id.to_string(),
+        host,
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        findings,
+    })
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// 将审计结果渲染成一份独立的 HTML 报告并写入 app data 目录，返回文件路径
+pub fn render_and_save_report(report: &AuditReport) -> AppResult<std::path::PathBuf> {
+    let app_dir = get_app_data_dir();
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let output_path = app_dir.join(format!("audit_{}.html", timestamp));
+
+    let summary_rows: String = {
+        let mut counts = [0usize; 5];
+        for f in &report.findings {
+            counts[f.severity as usize] += 1;
+        }
+        [
+            AuditSeverity::Critical,
+            AuditSeverity::High,
+            AuditSeverity::Medium,
+            AuditSeverity::Low,
+            AuditSeverity::Info,
+        ]
+        .iter()
+        .map(|s| format!("<tr><td>{}</td><td>{}</td></tr>", s.label(), counts[*s as usize]))
+        .collect()
+    };
+
+    let mut by_category: Vec<(String, Vec<&AuditFinding>)> = Vec::new();
+    for finding in &report.findings {
+        match by_category.iter_mut().find(|(c, _)| c == &finding.category) {
+            Some((_, list)) => list.push(finding),
+            None => by_category.push((finding.category.clone(), vec![finding])),
+        }
+    }
+
+    let sections: String = by_category
+        .iter()
+        .map(|(category, items)| {
+            let rows: String = items
+                .iter()
+                .map(|f| {
+                    format!(
+                        "<tr class=\"sev-{sev}\"><td>{sev_label}</td><td>{title}</td><td><pre>{evidence}</pre></td><td>{remediation}</td></tr>",
+                        sev = f.severity.label().to_lowercase(),
+                        sev_label = f.severity.label(),
+                        title = escape_html(&f.title),
+                        evidence = escape_html(&f.evidence),
+                        remediation = escape_html(&f.remediation),
+                    )
+                })
+                .collect();
+            format!(
+                "<h2>{}</h2><table class=\"findings\"><tr><th>severity</th><th>title</th><th>evidence</th><th>remediation</th></tr>{}</table>",
+                escape_html(category), rows
+            )
+        })
+        .collect();
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="zh">
+<head>
+<meta charset="utf-8">
+<title>TunnelFiles 安全审计报告</title>
+<style>
+body {{ font-family: -apple-system, sans-serif; margin: 2rem; color: #1a1a1a; }}
+table {{ border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }}
+th, td {{ border: 1px solid #ddd; padding: 6px 10px; text-align: left; vertical-align: top; }}
+th {{ background: #f4f4f4; }}
+pre {{ margin: 0; white-space: pre-wrap; word-break: break-all; }}
+.sev-critical {{ background: #fde2e1; }}
+.sev-high {{ background: #fdecd7; }}
+.sev-medium {{ background: #fff8d6; }}
+.sev-low {{ background: #eef6ec; }}
+.sev-info {{ background: #eef2fb; }}
+</style>
+</head>
+<body>
+<h1>TunnelFiles 安全审计报告</h1>
+<p>Session: {session_id}<br>Host: {host}<br>Generated: {generated_at}</p>
+<h2>Summary</h2>
+<table class="summary"><tr><th>severity</th><th>count</th></tr>{summary_rows}</table>
+{sections}
+</body>
+</html>
+"#,
+        session_id = escape_html(&report.session_id),
+        host = escape_html(&report.host),
+        generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        summary_rows = summary_rows,
+        sections = sections,
+    );
+
+    std::fs::write(&output_path, html)
+        .map_err(|e| AppError::local_io_error(format!("写入审计报告失败: {}", e)))?;
+
+    Ok(output_path)
+}
@@ -4,23 +4,32 @@
 //! - SSH Session 的创建、维护、回收
 //! - Session 池管理
 //! - 连接状态跟踪
-//! - 认证流程（密码/Key）
+//! - 认证流程（密码/Key/SSH agent）
+//! - 主动 keepalive 驱动与断线自动重连的退避状态（实际的周期调度在 `lib.rs` 的后台任务中）
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::io::Read;
 use std::net::TcpStream;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::thread;
 use std::time::{Duration, Instant};
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use crossbeam_channel::{Receiver, Sender};
+use rand::RngCore;
 use sha2::{Digest, Sha256};
-use ssh2::{Session, Sftp};
+use ssh2::{MethodType, Session, Sftp};
 use zeroize::Zeroize;
 
 use crate::models::error::{AppError, AppResult, ErrorCode};
-use crate::models::profile::{AuthType, Profile};
-use crate::services::security_service::{credential_get, verify_hostkey, HostKeyVerifyResult};
+use crate::models::profile::{Auth, Profile};
+use crate::services::retry::{retry_with_backoff, RetryPolicy};
+use crate::services::security_service::{
+    credential_get, credential_get_private_key, verify_hostkey, HostKeyVerifyResult,
+};
+use crate::services::ssh_config;
 use crate::services::storage_service::Database;
 
 /// 缓存的认证凭据（用于 Terminal 等需要独立 session 的场景）
@@ -32,6 +41,10 @@ pub struct CachedCredentials {
     password: Option<String>,
     /// Passphrase（Key 认证）
     passphrase: Option<String>,
+    /// 标记本次认证是否经由 SSH agent 完成——agent 没有可以缓存的密码/passphrase，
+    /// 私钥和签名都在 agent 进程里，重新认证（Terminal、断线重连）时应该重新走一遍
+    /// `auth_agent`，而不是误以为"两个字段都是 None"就代表免密登录
+    is_agent: bool,
 }
 
 impl Drop for CachedCredentials {
@@ -46,29 +59,189 @@ impl Drop for CachedCredentials {
     }
 }
 
+/// 提交给会话专属 worker 线程的任务
+///
+/// `Session`/`Sftp` 依赖的 libssh2 不是线程安全的，过去的做法是把两者塞进 `RwLock`，
+/// 再约定"调用方必须在 spawn_blocking 里用"，靠 `unsafe impl Send/Sync` 承诺这份约定
+/// 不会被违反。现在改为每个会话专属一个 worker 线程，`Session`/`Sftp` 永远只被这一个
+/// 线程触碰，外部只持有 `Sender<Job>`——闭包只能按值捕获 `'static` 数据（不能借用调用
+/// 线程栈上的东西），这样 `Job` 本身就是纯粹的 `Send` 数据，`ManagedSession`/
+/// `SessionManager` 因此天然满足 `Send + Sync`，不需要再写 unsafe impl
+enum Job {
+    /// 只需要 SFTP 句柄的任务（目录列举、stat、上传下载等绝大多数操作）
+    Sftp(
+        Box<dyn FnOnce(&mut Sftp) -> Box<dyn Any + Send> + Send>,
+        Sender<Box<dyn Any + Send>>,
+    ),
+    /// 只需要 Session 句柄的任务（exec 命令、keepalive）
+    Session(
+        Box<dyn FnOnce(&mut Session) -> Box<dyn Any + Send> + Send>,
+        Sender<Box<dyn Any + Send>>,
+    ),
+    /// 同时需要两者的任务（如并行传输里既要收发文件又要走 exec 做校验和比对），
+    /// 一次提交即可拿到两个句柄，不必分两次往返 worker
+    SessionAndSftp(
+        Box<dyn FnOnce(&mut Session, &mut Sftp) -> Box<dyn Any + Send> + Send>,
+        Sender<Box<dyn Any + Send>>,
+    ),
+    /// 重连成功后，让 worker 原地替换其独占持有的句柄；`ack` 在替换完成后收到通知
+    Replace {
+        session: Session,
+        sftp: Sftp,
+        ack: Sender<()>,
+    },
+}
+
+/// 会话专属 worker 线程的主循环：独占持有 `session`/`sftp`，只通过 `rx` 接收任务
+///
+/// `rx.recv()` 返回 `Err` 说明所有 `Sender<Job>` 都已被丢弃（会话已关闭，见
+/// [`SessionManager::close_session`]），循环随之退出，线程自然结束，`session`/`sftp`
+/// 随栈帧一起 drop 掉，SSH 连接随之关闭
+fn run_session_worker(mut session: Session, mut sftp: Sftp, rx: Receiver<Job>) {
+    while let Ok(job) = rx.recv() {
+        match job {
+            Job::Sftp(f, reply) => {
+                let _ = reply.send(f(&mut sftp));
+            }
+            Job::Session(f, reply) => {
+                let _ = reply.send(f(&mut session));
+            }
+            Job::SessionAndSftp(f, reply) => {
+                let _ = reply.send(f(&mut session, &mut sftp));
+            }
+            Job::Replace {
+                session: new_session,
+                sftp: new_sftp,
+                ack,
+            } => {
+                session = new_session;
+                sftp = new_sftp;
+                let _ = ack.send(());
+            }
+        }
+    }
+    tracing::debug!("会话 worker 线程已退出");
+}
+
+/// 把闭包的返回值从 worker 线程回传的 `Box<dyn Any + Send>` 还原成具体类型
+///
+/// 只有 bug 才会导致类型不匹配（同一次调用里装箱和拆箱用的是同一个泛型实参），
+/// 这里不 panic，而是如实返回一个说得清楚的错误，保持与本模块其它"不应该发生"
+/// 分支一致的风格
+fn downcast_reply<T: 'static>(boxed: Box<dyn Any + Send>) -> AppResult<T> {
+    boxed
+        .downcast::<AppResult<T>>()
+        .map(|b| *b)
+        .unwrap_or_else(|_| {
+            Err(AppError::new(
+                ErrorCode::Unknown,
+                "会话 worker 返回类型不匹配（不应当发生）",
+            ))
+        })
+}
+
 /// 托管的 SSH 会话
 pub struct ManagedSession {
     /// 会话 ID
     pub session_id: String,
-    /// SSH Session
-    pub session: Session,
-    /// SFTP Channel
-    pub sftp: Sftp,
+    /// 提交给专属 worker 线程的任务入口；该线程独占持有底层的 `Session`/`Sftp`
+    /// （见 [`run_session_worker`]），是整个进程里唯一会触碰这两个 libssh2 句柄的线程。
+    /// 断线重连时通过 [`Self::replace_handles`] 提交 [`Job::Replace`] 原地替换，
+    /// 引用同一个 `Arc<ManagedSession>` 的调用方无需感知切换
+    worker: Sender<Job>,
     /// 关联的 Profile ID
     pub profile_id: String,
     /// 服务器指纹
     pub fingerprint: String,
     /// 远程 home 目录
     pub home_path: String,
+    /// 远程主机操作系统族，建立连接时探测一次后不再变化
+    pub family: SshFamily,
     /// 创建时间
     pub created_at: Instant,
     /// 最后活动时间
     pub last_activity: RwLock<Instant>,
-    /// 缓存的认证凭据（用于创建 Terminal 等独立 session）
+    /// 缓存的认证凭据（用于创建 Terminal 等独立 session，以及断线重连时重新认证）
     cached_credentials: RwLock<CachedCredentials>,
 }
 
 impl ManagedSession {
+    /// 向专属 worker 线程提交一个只操作 SFTP 句柄的任务，阻塞等待结果
+    ///
+    /// 闭包必须是 `'static` 的：它会被装箱后投递给 worker 线程执行，只能按值捕获
+    /// （clone/move）数据，不能借用调用线程栈上的东西。对于耗时较长的操作（如整个
+    /// 文件传输），应当把全部步骤放进同一次 `with_sftp` 调用里做完——这样重连逻辑
+    /// 提交的 [`Job::Replace`] 会在任务队列里排在后面，直到本次任务完成才会被处理，
+    /// 效果与过去"全程持有读锁"等价。
+    pub fn with_sftp<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Sftp) -> AppResult<T> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+        self.worker
+            .send(Job::Sftp(Box::new(move |sftp| Box::new(f(sftp))), reply_tx))
+            .map_err(|_| AppError::network_lost("会话 worker 线程已退出，连接可能已失联"))?;
+        let boxed = reply_rx
+            .recv()
+            .map_err(|_| AppError::network_lost("会话 worker 线程未响应，连接可能已失联"))?;
+        downcast_reply(boxed)
+    }
+
+    /// 同 [`Self::with_sftp`]，但操作 SSH Session 句柄，用于执行独立的 exec 命令
+    /// （如远程 grep/rg 搜索、keepalive）
+    pub fn with_session<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Session) -> AppResult<T> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+        self.worker
+            .send(Job::Session(
+                Box::new(move |session| Box::new(f(session))),
+                reply_tx,
+            ))
+            .map_err(|_| AppError::network_lost("会话 worker 线程已退出，连接可能已失联"))?;
+        let boxed = reply_rx
+            .recv()
+            .map_err(|_| AppError::network_lost("会话 worker 线程未响应，连接可能已失联"))?;
+        downcast_reply(boxed)
+    }
+
+    /// 同时需要 Session 和 Sftp 的任务一次性提交，避免分两次往返 worker
+    pub fn with_session_and_sftp<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Session, &mut Sftp) -> AppResult<T> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+        self.worker
+            .send(Job::SessionAndSftp(
+                Box::new(move |session, sftp| Box::new(f(session, sftp))),
+                reply_tx,
+            ))
+            .map_err(|_| AppError::network_lost("会话 worker 线程已退出，连接可能已失联"))?;
+        let boxed = reply_rx
+            .recv()
+            .map_err(|_| AppError::network_lost("会话 worker 线程未响应，连接可能已失联"))?;
+        downcast_reply(boxed)
+    }
+
+    /// 重连成功后，让 worker 原地替换其独占持有的 Session/Sftp 句柄
+    fn replace_handles(&self, new_session: Session, new_sftp: Sftp) -> AppResult<()> {
+        let (ack_tx, ack_rx) = crossbeam_channel::bounded(1);
+        self.worker
+            .send(Job::Replace {
+                session: new_session,
+                sftp: new_sftp,
+                ack: ack_tx,
+            })
+            .map_err(|_| AppError::network_lost("会话 worker 线程已退出，连接可能已失联"))?;
+        ack_rx
+            .recv()
+            .map_err(|_| AppError::network_lost("会话 worker 线程未响应，连接可能已失联"))
+    }
+
     /// 更新最后活动时间
     pub fn touch(&self) {
         if let Ok(mut last) = self.last_activity.write() {
@@ -99,6 +272,15 @@ impl ManagedSession {
             .ok()
             .and_then(|creds| creds.passphrase.clone())
     }
+
+    /// 本次认证是否经由 SSH agent 完成——为 true 时没有可用的缓存密码/passphrase，
+    /// 重新认证应当重新走一遍 agent 流程
+    pub fn is_agent_auth(&self) -> bool {
+        self.cached_credentials
+            .read()
+            .map(|creds| creds.is_agent)
+            .unwrap_or(false)
+    }
 }
 
 /// 连接结果
@@ -109,6 +291,18 @@ pub struct ConnectResult {
     pub home_path: String,
     /// 服务器指纹
     pub fingerprint: String,
+    /// 探测到的远程主机操作系统族
+    pub family: SshFamily,
+}
+
+/// 远程主机操作系统族，决定用什么命令探测 home 目录，以及前端渲染路径时
+/// 应该用什么分隔符和引用规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshFamily {
+    /// Unix-like（Linux/macOS/BSD 等），绝大多数服务器属于这一类
+    Unix,
+    /// Windows（OpenSSH for Windows，默认 shell 通常是 cmd.exe 或 PowerShell）
+    Windows,
 }
 
 /// HostKey 需要确认的信息
@@ -123,6 +317,39 @@ pub struct HostKeyPending {
     pub fingerprint: String,
     /// 密钥类型
     pub key_type: String,
+    /// 原始公钥（base64），供确认信任后写入镜像文件 / 支持 `known_hosts_export`
+    pub public_key_b64: String,
+}
+
+/// SSH agent 中的一个身份
+pub struct AgentIdentity {
+    /// 身份备注（通常是 key 的 comment，如 user@host）
+    pub comment: String,
+    /// 公钥原始内容（base64）
+    pub public_key_b64: String,
+}
+
+/// keyboard-interactive 认证里服务器要求回答的一条提示
+pub struct InteractivePrompt {
+    /// 提示文案（如 "Password: "、"Verification code: "）
+    pub label: String,
+    /// 是否应当回显用户输入，false 时前端应当像密码框一样遮盖
+    pub echo: bool,
+}
+
+/// keyboard-interactive 认证需要用户作答的质询
+///
+/// 具体要问什么在握手前并不知道，只有真正向服务器发起一次 keyboard-interactive
+/// 请求才能拿到，因此这里把探测到的内容原样带出去，交给前端展示并收集答案后
+/// 通过 [`SessionManager::connect_after_interactive`] 继续
+pub struct NeedInteractiveResponse {
+    /// Profile ID
+    pub profile_id: String,
+    /// 服务器下发的说明文字（可能为空字符串）
+    pub instructions: String,
+    /// 待回答的提示列表，顺序需要与回传给 `connect_after_interactive` 的
+    /// `responses` 一一对应
+    pub prompts: Vec<InteractivePrompt>,
 }
 
 /// 连接状态
@@ -131,6 +358,16 @@ pub enum ConnectStatus {
     Connected(ConnectResult),
     /// 需要确认 HostKey
     NeedHostKeyConfirm(HostKeyPending),
+    /// 需要用户回答 keyboard-interactive 质询（OTP 验证码、PAM 提示、Duo 推送
+    /// 确认等）
+    NeedInteractiveResponse(NeedInteractiveResponse),
+}
+
+/// 认证流程的结果：要么直接拿到可以缓存的凭据，要么需要用户先回答
+/// keyboard-interactive 质询
+enum AuthOutcome {
+    Authenticated(CachedCredentials),
+    NeedsInteractive(NeedInteractiveResponse),
 }
 
 /// 认证失败记录
@@ -144,12 +381,46 @@ const AUTH_FAILURE_THRESHOLD: u32 = 5;
 /// 认证失败锁定时间（秒）
 const AUTH_LOCKOUT_SECS: u64 = 300;
 
+/// 自动重连的退避状态（key: session_id）
+struct ReconnectState {
+    /// 自上次重连成功以来已经尝试的次数
+    attempts: u32,
+    /// 下一次允许尝试重连的时间点，在此之前的健康检查 tick 直接跳过该会话
+    next_allowed_at: Instant,
+}
+
+/// 自动重连放弃前的最大尝试次数，超过后会话被视为彻底失联（"lost"）并从池中移除，
+/// 不再由健康检查循环自动重试——此时通常意味着设备长时间休眠或网络环境已经变化，
+/// 需要用户重新手动发起连接
+pub(crate) const MAX_RECONNECT_ATTEMPTS: u32 = 6;
+/// 指数退避的基准间隔（秒），第 N 次失败后等待 `min(MAX, BASE * 2^(N-1))` 再抖动
+const RECONNECT_BACKOFF_BASE_SECS: u64 = 2;
+/// 指数退避的上限（秒），避免笔记本休眠很久后醒来时排队等上几个小时
+const RECONNECT_BACKOFF_MAX_SECS: u64 = 120;
+
+/// 会话池容量上限相关配置，运行期可通过 [`SessionManager::set_config`]/
+/// [`SessionManager::set_max_sessions`] 调整，不需要重启应用
+#[derive(Debug, Clone, Default)]
+pub struct SessionManagerConfig {
+    /// 全局会话数上限，`None` 表示不限制
+    pub max_sessions: Option<u32>,
+    /// 单个 Profile 同时持有的会话数上限，`None` 表示不限制
+    pub max_sessions_per_profile: Option<u32>,
+    /// 达到上限时的处理方式：true 淘汰最久未活动的会话腾出位置，
+    /// false（默认）直接拒绝新连接，返回 `ErrorCode::TooManySessions`
+    pub evict_lru_on_limit: bool,
+}
+
 /// 会话管理器
 pub struct SessionManager {
     /// 会话池
     sessions: RwLock<HashMap<String, Arc<ManagedSession>>>,
     /// 认证失败计数 (key: profile_id)
     auth_failures: RwLock<HashMap<String, AuthFailureRecord>>,
+    /// 自动重连退避状态 (key: session_id)
+    reconnect_state: RwLock<HashMap<String, ReconnectState>>,
+    /// 会话池容量上限配置
+    config: RwLock<SessionManagerConfig>,
 }
 
 impl SessionManager {
@@ -158,6 +429,22 @@ impl SessionManager {
         Self {
             sessions: RwLock::new(HashMap::new()),
             auth_failures: RwLock::new(HashMap::new()),
+            reconnect_state: RwLock::new(HashMap::new()),
+            config: RwLock::new(SessionManagerConfig::default()),
+        }
+    }
+
+    /// 替换会话池容量上限配置
+    pub fn set_config(&self, config: SessionManagerConfig) {
+        if let Ok(mut guard) = self.config.write() {
+            *guard = config;
+        }
+    }
+
+    /// 单独调整全局会话数上限，其余配置项保持不变
+    pub fn set_max_sessions(&self, max_sessions: Option<u32>) {
+        if let Ok(mut guard) = self.config.write() {
+            guard.max_sessions = max_sessions;
         }
     }
 
@@ -177,13 +464,18 @@ impl SessionManager {
         passphrase: Option<&str>,
         timeout_secs: u64,
     ) -> AppResult<ConnectStatus> {
+        // 0. 用 ~/.ssh/config 里匹配的 Host 块填一遍 Profile 本来缺省的字段（真实
+        // 地址、用户名、端口、私钥路径），这样 Profile.host 填一个 OpenSSH 配置
+        // 别名也能连上；Profile 里已经明确填过的字段始终优先，不会被覆盖
+        let profile = &ssh_config::resolve_profile(profile);
+
         let timeout = Duration::from_secs(timeout_secs);
 
         // 1. 建立 SSH 连接
-        let session = self.establish_ssh_session(&profile.host, profile.port, timeout)?;
+        let session = self.establish_ssh_session(profile, timeout)?;
 
         // 2. 获取并验证 HostKey
-        let (key_type, fingerprint) = self.get_host_key_info(&session)?;
+        let (key_type, fingerprint, public_key_b64) = self.get_host_key_info(&session)?;
         tracing::debug!(
             host = %profile.host,
             port = profile.port,
@@ -208,26 +500,41 @@ impl SessionManager {
                     port: profile.port,
                     fingerprint,
                     key_type,
+                    public_key_b64,
                 }));
             }
             HostKeyVerifyResult::Mismatch { stored, received } => {
-                return Err(AppError::new(ErrorCode::HostkeyMismatch, "服务器主机密钥已更改")
-                    .with_detail(format!(
-                        "存储的指纹: {}\n接收的指纹: {}\n\n这可能表示服务器已重新配置，或存在中间人攻击的风险。",
-                        stored, received
-                    ))
-                    .with_retryable(false));
+                return Err(crate::services::security_service::hostkey_mismatch_error(
+                    &profile.host,
+                    &key_type,
+                    &stored,
+                    &received,
+                ));
+            }
+            HostKeyVerifyResult::Revoked => {
+                return Err(crate::services::security_service::hostkey_revoked_error(
+                    &profile.host,
+                    profile.port,
+                ));
             }
             HostKeyVerifyResult::Matched => {
                 tracing::debug!("HostKey 验证通过");
             }
         }
 
-        // 3. 认证（并获取缓存的凭据）
-        let cached_credentials = self.authenticate(&session, profile, password, passphrase)?;
+        // 3. 认证（并获取缓存的凭据）；keyboard-interactive 可能需要先把服务器的
+        // 质询交还给前端，由用户作答后再走 connect_after_interactive 继续
+        let cached_credentials =
+            match self.authenticate_or_prompt(db, &session, profile, password, passphrase)? {
+                AuthOutcome::Authenticated(creds) => creds,
+                AuthOutcome::NeedsInteractive(pending) => {
+                    return Ok(ConnectStatus::NeedInteractiveResponse(pending));
+                }
+            };
 
         // 4. 完成连接并存储会话（包含缓存的凭据）
-        let result = self.finalize_connection(session, &profile.id, fingerprint, cached_credentials)?;
+        let result =
+            self.finalize_connection(session, &profile.id, fingerprint, cached_credentials)?;
 
         tracing::info!(
             session_id = %result.session_id,
@@ -242,24 +549,43 @@ impl SessionManager {
     /// 在 HostKey 确认后继续连接
     pub fn connect_after_trust(
         &self,
+        db: &Database,
         profile: &Profile,
         password: Option<&str>,
         passphrase: Option<&str>,
         timeout_secs: u64,
     ) -> AppResult<ConnectResult> {
+        // 0. 同 connect()：用 ~/.ssh/config 里匹配的 Host 块补齐缺省字段
+        let profile = &ssh_config::resolve_profile(profile);
+
         let timeout = Duration::from_secs(timeout_secs);
 
         // 1. 建立 SSH 连接
-        let session = self.establish_ssh_session(&profile.host, profile.port, timeout)?;
+        let session = self.establish_ssh_session(profile, timeout)?;
 
         // 2. 获取指纹（已信任，不再验证）
-        let (_, fingerprint) = self.get_host_key_info(&session)?;
+        let (_, fingerprint, _) = self.get_host_key_info(&session)?;
 
         // 3. 认证（并获取缓存的凭据）
-        let cached_credentials = self.authenticate(&session, profile, password, passphrase)?;
+        let cached_credentials =
+            match self.authenticate_or_prompt(db, &session, profile, password, passphrase)? {
+                AuthOutcome::Authenticated(creds) => creds,
+                AuthOutcome::NeedsInteractive(_) => {
+                    // HostKey 刚刚确认完，还没来得及把 keyboard-interactive 的质询
+                    // 交给用户作答——此时主机密钥已经落库，重新发起一次 connect()
+                    // 就会直接命中 NeedInteractiveResponse 分支并拿到真正的提示文案，
+                    // 不需要在这里再引入第三个延续方法
+                    return Err(AppError::new(
+                        ErrorCode::AuthFailed,
+                        "HostKey 已确认，请重新发起连接以获取 keyboard-interactive 提示",
+                    )
+                    .with_retryable(true));
+                }
+            };
 
         // 4. 完成连接并存储会话（包含缓存的凭据）
-        let result = self.finalize_connection(session, &profile.id, fingerprint, cached_credentials)?;
+        let result =
+            self.finalize_connection(session, &profile.id, fingerprint, cached_credentials)?;
 
         tracing::info!(
             session_id = %result.session_id,
@@ -304,10 +630,28 @@ impl SessionManager {
                 "会话已关闭"
             );
         }
+        drop(sessions);
+        self.forget_reconnect_state(session_id);
 
         Ok(())
     }
 
+    /// 关闭当前全部会话，返回实际关闭的数量
+    ///
+    /// 供应用退出时的优雅关闭流程调用（见 `services::shutdown`）；单个会话关闭失败
+    /// 只记录告警，不影响其余会话继续被关闭
+    pub fn close_all_sessions(&self) -> usize {
+        let session_ids = self.list_sessions().unwrap_or_default();
+        let mut closed = 0;
+        for session_id in session_ids {
+            match self.close_session(&session_id) {
+                Ok(()) => closed += 1,
+                Err(e) => tracing::warn!(session_id = %session_id, error = %e, "关闭会话失败"),
+            }
+        }
+        closed
+    }
+
     /// 获取所有会话 ID
     pub fn list_sessions(&self) -> AppResult<Vec<String>> {
         let sessions = self
@@ -320,12 +664,20 @@ impl SessionManager {
 
     /// 为 Terminal 创建独立的 SSH session（阻塞模式，调用方负责后续设置非阻塞）
     ///
-    /// 使用主 session 中缓存的凭据进行认证，避免再次访问系统钥匙串。
+    /// 使用主 session 中缓存的凭据进行认证，避免再次访问系统钥匙串。`existing` 非
+    /// None 时直接返回该已建立的 session（多个 PTY channel 复用同一条 SSH 连接时
+    /// 使用），不会重新拨号或认证。
     pub fn create_terminal_session(
         &self,
         db: &crate::services::storage_service::Database,
         session_id: &str,
-    ) -> AppResult<Session> {
+        existing: Option<Arc<Session>>,
+    ) -> AppResult<Arc<Session>> {
+        if let Some(session) = existing {
+            tracing::debug!(session_id = %session_id, "复用已有 Terminal session（多路复用）");
+            return Ok(session);
+        }
+
         // 获取原始会话信息
         let managed_session = self.get_session(session_id)?;
         let profile_id = &managed_session.profile_id;
@@ -348,26 +700,42 @@ impl SessionManager {
 
         // 建立新的 SSH 连接（默认 30 秒超时）
         let timeout = Duration::from_secs(30);
-        let session = self.establish_ssh_session(&profile.host, profile.port, timeout)?;
+        let session = self.establish_ssh_session(profile, timeout)?;
 
         // 使用缓存的凭据进行认证
-        match profile.auth_type {
-            AuthType::Password => {
+        match profile.auth {
+            Auth::Password { .. } => {
                 self.auth_password(
+                    db,
                     &session,
                     &profile.username,
                     &profile,
                     cached_password.as_deref(),
                 )?;
             }
-            AuthType::Key => {
+            Auth::Key { .. } => {
                 self.auth_key(
+                    db,
                     &session,
                     &profile.username,
                     &profile,
                     cached_passphrase.as_deref(),
                 )?;
             }
+            Auth::Agent => {
+                self.auth_agent(&session, &profile.username)?;
+            }
+            Auth::KeyboardInteractive => {
+                // keyboard-interactive 没有可以无人值守复用的凭据——没有密码/
+                // passphrase 缓存，验证码、Duo 推送这类质询本身就是一次性的，
+                // 不存在"缓存答案"这回事。这里如实报错而不是静默失败，调用方
+                // （打开 Terminal 的入口）捕获后应当引导用户走一次新的
+                // connect_after_interactive 重新作答，而不是让用户看到终端
+                // 打不开却不知道原因
+                return Err(AppError::auth_failed(
+                    "keyboard-interactive 认证无法复用缓存凭据创建 Terminal，请重新连接后再试",
+                ));
+            }
         }
 
         tracing::info!(
@@ -376,16 +744,204 @@ impl SessionManager {
             "Terminal 专用 session 已创建（使用缓存凭据）"
         );
 
-        Ok(session)
+        Ok(Arc::new(session))
+    }
+
+    /// 为多流并行传输创建一个独立的辅助 SFTP 通道
+    ///
+    /// 与 [`Self::create_terminal_session`] 共享同样的思路：`ssh2::Sftp` 绑定在单个
+    /// session 上无法跨线程共享，多流传输的每个 worker 需要各自独立的 session+SFTP
+    /// 通道，因此复用主 session 缓存的凭据重新建立一条连接，而不是争用主 session 的锁。
+    pub fn create_auxiliary_sftp_session(
+        &self,
+        db: &crate::services::storage_service::Database,
+        session_id: &str,
+    ) -> AppResult<AuxiliarySftpConnection> {
+        let session = self.create_terminal_session(db, session_id, None)?;
+
+        let sftp = session.sftp().map_err(|e| {
+            AppError::new(
+                ErrorCode::RemoteIoError,
+                format!("无法创建辅助 SFTP 通道: {}", e),
+            )
+        })?;
+
+        tracing::debug!(session_id = %session_id, "辅助 SFTP 通道已创建（用于多流并行传输）");
+
+        Ok(AuxiliarySftpConnection {
+            _session: session,
+            sftp,
+        })
     }
 
     /// 检查会话是否活跃
     pub fn is_session_alive(&self, session_id: &str) -> bool {
-        if let Ok(session) = self.get_session(session_id) {
-            // 尝试执行简单命令检测连接
-            session.sftp.readdir(Path::new(".")).is_ok()
-        } else {
-            false
+        let Ok(session) = self.get_session(session_id) else {
+            return false;
+        };
+        // 尝试执行简单命令检测连接
+        session
+            .with_sftp(|sftp| Ok(sftp.readdir(Path::new(".")).is_ok()))
+            .unwrap_or(false)
+    }
+
+    /// 透明重连一个已失联的会话
+    ///
+    /// 使用会话原先关联的 Profile 重新建立 SSH+SFTP 连接：
+    /// - 主机密钥必须仍与已信任的记录匹配，否则中止重连（绝不静默信任变更的密钥）
+    /// - 复用会话建立时缓存的密码/passphrase 重新认证，避免再次提示用户输入
+    /// - 认证成功后在同一个 `session_id` 下原地替换底层 `Session`/`Sftp` 句柄，
+    ///   引用该 `Arc<ManagedSession>` 的调用方（包括正在运行的传输任务）无需感知切换
+    ///
+    /// `rt_handle` 由调用方在进入 `spawn_blocking` 前捕获（见 [`FileHandleGuard::acquire`]
+    /// 同样的桥接方式），用于把单次尝试里 TCP 连接/握手这类瞬时失败的重试桥接到
+    /// [`retry_with_backoff`]；会话级别的"要不要再等一轮重连"仍由
+    /// [`Self::should_attempt_reconnect`] 的退避状态决定，两者不冲突——后者决定本次
+    /// 重连值不值得开始，前者决定开始后遇到网络抖动要不要立刻在本次尝试内重试
+    pub fn reconnect_session(
+        &self,
+        db: &Database,
+        session_id: &str,
+        rt_handle: &tokio::runtime::Handle,
+    ) -> AppResult<()> {
+        let managed = self.get_session(session_id)?;
+
+        let profile = db
+            .profile_get(&managed.profile_id)?
+            .ok_or_else(|| AppError::not_found(format!("Profile {} 不存在", managed.profile_id)))?;
+
+        let cached_password = managed.get_cached_password();
+        let cached_passphrase = managed.get_cached_passphrase();
+
+        let (new_session, new_sftp) = rt_handle.block_on(retry_with_backoff(&RetryPolicy::default(), || async {
+            let timeout = Duration::from_secs(30);
+            let new_session = self.establish_ssh_session(profile, timeout)?;
+
+            let (key_type, fingerprint, _) = self.get_host_key_info(&new_session)?;
+            match verify_hostkey(db, &profile.host, profile.port, &key_type, &fingerprint)? {
+                HostKeyVerifyResult::Matched => {}
+                HostKeyVerifyResult::Mismatch { stored, received } => {
+                    return Err(crate::services::security_service::hostkey_mismatch_error(
+                        &profile.host,
+                        &key_type,
+                        &stored,
+                        &received,
+                    ));
+                }
+                HostKeyVerifyResult::Revoked => {
+                    return Err(crate::services::security_service::hostkey_revoked_error(
+                        &profile.host,
+                        profile.port,
+                    ));
+                }
+                HostKeyVerifyResult::FirstConnection(_) => {
+                    return Err(AppError::new(
+                        ErrorCode::HostkeyMismatch,
+                        "重连时遇到未知的服务器主机密钥，已中止自动重连",
+                    )
+                    .with_retryable(false));
+                }
+            }
+
+            self.authenticate(
+                db,
+                &new_session,
+                &profile,
+                cached_password.as_deref(),
+                cached_passphrase.as_deref(),
+            )?;
+
+            let new_sftp = new_session.sftp().map_err(|e| {
+                AppError::new(
+                    ErrorCode::RemoteIoError,
+                    format!("无法创建 SFTP 通道: {}", e),
+                )
+            })?;
+
+            Ok((new_session, new_sftp))
+        }))?;
+
+        managed.replace_handles(new_session, new_sftp)?;
+        managed.touch();
+
+        tracing::info!(
+            session_id = %session_id,
+            profile_id = %profile.id,
+            "会话已透明重连"
+        );
+
+        Ok(())
+    }
+
+    /// 主动发送一次 SSH keepalive（global request）
+    ///
+    /// `set_keepalive` 只是告诉 libssh2 两次 keepalive 之间允许间隔多久，真正的
+    /// 发送动作需要调用方周期性调用本方法来驱动；返回 `Err` 说明连接已经不可写，
+    /// 调用方应将其视为会话已失联
+    pub fn send_keepalive(&self, session_id: &str) -> AppResult<()> {
+        let managed = self.get_session(session_id)?;
+        managed.with_session(|session| {
+            session
+                .keepalive_send()
+                .map(|_| ())
+                .map_err(|e| AppError::network_lost(format!("keepalive 发送失败: {}", e)))
+        })
+    }
+
+    /// 健康检查发现会话失联、准备尝试自动重连前调用：判断是否已经过了退避等待期
+    ///
+    /// 首次失联（没有退避记录）总是允许立即尝试；超过 [`MAX_RECONNECT_ATTEMPTS`]
+    /// 次仍未成功的会话返回 `false`，调用方此时应该放弃重连并把会话标记为彻底丢失
+    pub fn should_attempt_reconnect(&self, session_id: &str) -> bool {
+        let Ok(state) = self.reconnect_state.read() else {
+            return true;
+        };
+        match state.get(session_id) {
+            None => true,
+            Some(record) => {
+                record.attempts < MAX_RECONNECT_ATTEMPTS && Instant::now() >= record.next_allowed_at
+            }
+        }
+    }
+
+    /// 记录一次重连失败，按指数退避（带抖动）安排下一次允许尝试的时间
+    ///
+    /// 返回累计失败次数，调用方据此判断是否已达到 [`MAX_RECONNECT_ATTEMPTS`]
+    /// 而应该放弃（`>=` 时不会再有下一次 `should_attempt_reconnect` 返回 `true`）
+    pub fn record_reconnect_failure(&self, session_id: &str) -> u32 {
+        let mut state = match self.reconnect_state.write() {
+            Ok(s) => s,
+            Err(_) => return MAX_RECONNECT_ATTEMPTS,
+        };
+
+        let record = state.entry(session_id.to_string()).or_insert(ReconnectState {
+            attempts: 0,
+            next_allowed_at: Instant::now(),
+        });
+        record.attempts += 1;
+
+        let backoff_secs = RECONNECT_BACKOFF_BASE_SECS
+            .saturating_mul(1u64 << record.attempts.min(16).saturating_sub(1))
+            .min(RECONNECT_BACKOFF_MAX_SECS);
+        let mut jitter = [0u8; 1];
+        rand::thread_rng().fill_bytes(&mut jitter);
+        let jitter_secs = (jitter[0] as u64) % (backoff_secs / 4 + 1);
+        record.next_allowed_at = Instant::now() + Duration::from_secs(backoff_secs + jitter_secs);
+
+        record.attempts
+    }
+
+    /// 重连成功后清除该会话的退避记录，下次失联重新从第一次退避间隔算起
+    pub fn record_reconnect_success(&self, session_id: &str) {
+        if let Ok(mut state) = self.reconnect_state.write() {
+            state.remove(session_id);
+        }
+    }
+
+    /// 会话被关闭（无论是正常断开还是彻底放弃重连）时清理其退避记录，避免 HashMap 泄漏
+    pub fn forget_reconnect_state(&self, session_id: &str) {
+        if let Ok(mut state) = self.reconnect_state.write() {
+            state.remove(session_id);
         }
     }
 
@@ -429,14 +985,82 @@ impl SessionManager {
     // 内部方法
     // ============================================
 
+    /// 新建会话落库前检查是否超出配置的容量上限（全局 / 单 Profile）
+    ///
+    /// 超出且 `evict_lru_on_limit` 开启时，淘汰一个最久未活动的会话腾出位置
+    /// （单 Profile 超限就只从该 Profile 自己的会话里选，避免误伤其它 Profile）；
+    /// 否则直接拒绝，返回不可重试的 `ErrorCode::TooManySessions`
+    fn enforce_session_limits(&self, profile_id: &str) -> AppResult<()> {
+        let config = self
+            .config
+            .read()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "会话池配置锁获取失败"))?
+            .clone();
+
+        if config.max_sessions.is_none() && config.max_sessions_per_profile.is_none() {
+            return Ok(());
+        }
+
+        loop {
+            let (over_profile, victim_id) = {
+                let sessions = self
+                    .sessions
+                    .read()
+                    .map_err(|_| AppError::new(ErrorCode::Unknown, "会话池锁获取失败"))?;
+
+                let total = sessions.len() as u32;
+                let same_profile = sessions
+                    .values()
+                    .filter(|s| s.profile_id == profile_id)
+                    .count() as u32;
+
+                let over_total = config.max_sessions.is_some_and(|m| total >= m);
+                let over_profile = config.max_sessions_per_profile.is_some_and(|m| same_profile >= m);
+
+                if !over_total && !over_profile {
+                    return Ok(());
+                }
+
+                if !config.evict_lru_on_limit {
+                    return Err(AppError::too_many_sessions(if over_profile {
+                        format!("Profile {} 的并发会话数已达上限", profile_id)
+                    } else {
+                        "会话池已达到全局上限".to_string()
+                    }));
+                }
+
+                // 单 Profile 超限只在该 Profile 自己的会话里挑最久未活动的一个；
+                // 否则（全局超限）从全部会话里挑
+                let victim = sessions
+                    .values()
+                    .filter(|s| !over_profile || s.profile_id == profile_id)
+                    .max_by_key(|s| s.idle_secs())
+                    .map(|s| s.session_id.clone());
+
+                (over_profile, victim)
+            };
+
+            let Some(victim_id) = victim_id else {
+                return Err(AppError::too_many_sessions(if over_profile {
+                    format!("Profile {} 的并发会话数已达上限", profile_id)
+                } else {
+                    "会话池已达到全局上限".to_string()
+                }));
+            };
+
+            tracing::info!(session_id = %victim_id, profile_id = %profile_id, "会话池已达上限，淘汰最久未活动的会话");
+            self.close_session(&victim_id)?;
+        }
+    }
+
     /// 建立 TCP 连接并完成 SSH 握手
-    fn establish_ssh_session(
-        &self,
-        host: &str,
-        port: u16,
-        timeout: Duration,
-    ) -> AppResult<Session> {
-        let addr = format!("{}:{}", host, port);
+    ///
+    /// 握手前会按 `profile` 里的 `host_key_algorithms`/`kex_algorithms`/`ciphers`
+    /// 收紧或放宽算法协商范围（为空则保持 libssh2 的安全默认值不变），用于兼容只
+    /// 提供 `ssh-rsa`/`diffie-hellman-group14-sha1` 等已废弃算法的老旧服务器，
+    /// 同时不影响其它 Profile 的默认安全性
+    fn establish_ssh_session(&self, profile: &Profile, timeout: Duration) -> AppResult<Session> {
+        let addr = format!("{}:{}", profile.host, profile.port);
         tracing::debug!(addr = %addr, "正在建立 TCP 连接");
 
         let tcp = TcpStream::connect_timeout(
@@ -457,10 +1081,35 @@ impl SessionManager {
         tcp.set_write_timeout(Some(timeout))?;
         tcp.set_nodelay(true)?; // 禁用 Nagle 算法，减少终端输入延迟
 
-        tracing::debug!("正在进行 SSH 握手");
         let mut session = Session::new()
             .map_err(|e| AppError::new(ErrorCode::Unknown, format!("无法创建 SSH 会话: {}", e)))?;
 
+        if let Some(algos) = non_empty(&profile.host_key_algorithms) {
+            session
+                .method_pref(MethodType::HostKey, algos)
+                .map_err(|e| {
+                    AppError::new(ErrorCode::InvalidArgument, format!("HostKey 算法偏好无效: {}", e))
+                })?;
+        }
+        if let Some(algos) = non_empty(&profile.kex_algorithms) {
+            session.method_pref(MethodType::Kex, algos).map_err(|e| {
+                AppError::new(ErrorCode::InvalidArgument, format!("KEX 算法偏好无效: {}", e))
+            })?;
+        }
+        if let Some(algos) = non_empty(&profile.ciphers) {
+            session
+                .method_pref(MethodType::CryptCs, algos)
+                .map_err(|e| {
+                    AppError::new(ErrorCode::InvalidArgument, format!("加密算法偏好无效: {}", e))
+                })?;
+            session
+                .method_pref(MethodType::CryptSc, algos)
+                .map_err(|e| {
+                    AppError::new(ErrorCode::InvalidArgument, format!("加密算法偏好无效: {}", e))
+                })?;
+        }
+
+        tracing::debug!("正在进行 SSH 握手");
         session.set_tcp_stream(tcp);
         session
             .handshake()
@@ -481,6 +1130,8 @@ impl SessionManager {
         fingerprint: String,
         cached_credentials: CachedCredentials,
     ) -> AppResult<ConnectResult> {
+        self.enforce_session_limits(profile_id)?;
+
         tracing::debug!("正在创建 SFTP 通道");
         let sftp = session.sftp().map_err(|e| {
             AppError::new(
@@ -489,17 +1140,30 @@ impl SessionManager {
             )
         })?;
 
-        let home_path = self.get_home_path(&session)?;
+        let family = self.detect_family(&session);
+        let home_path = self.get_home_path(&session, family)?;
         let session_id = uuid::Uuid::new_v4().to_string();
 
+        let (worker_tx, worker_rx) = crossbeam_channel::unbounded();
+        let worker_session_id = session_id.clone();
+        thread::Builder::new()
+            .name(format!("ssh-worker-{}", worker_session_id))
+            .spawn(move || run_session_worker(session, sftp, worker_rx))
+            .map_err(|e| {
+                AppError::new(
+                    ErrorCode::Unknown,
+                    format!("无法创建会话 worker 线程: {}", e),
+                )
+            })?;
+
         let now = Instant::now();
         let managed_session = Arc::new(ManagedSession {
             session_id: session_id.clone(),
-            session,
-            sftp,
+            worker: worker_tx,
             profile_id: profile_id.to_string(),
             fingerprint: fingerprint.clone(),
             home_path: home_path.clone(),
+            family,
             created_at: now,
             last_activity: RwLock::new(now),
             cached_credentials: RwLock::new(cached_credentials),
@@ -517,11 +1181,12 @@ impl SessionManager {
             session_id,
             home_path,
             fingerprint,
+            family,
         })
     }
 
-    /// 获取 HostKey 信息
-    fn get_host_key_info(&self, session: &Session) -> AppResult<(String, String)> {
+    /// 获取 HostKey 信息：密钥类型、SHA256 指纹、原始公钥（base64）
+    fn get_host_key_info(&self, session: &Session) -> AppResult<(String, String, String)> {
         let (key, key_type) = session
             .host_key()
             .ok_or_else(|| AppError::new(ErrorCode::Unknown, "无法获取服务器主机密钥"))?;
@@ -541,8 +1206,9 @@ impl SessionManager {
         hasher.update(key);
         let hash = hasher.finalize();
         let fingerprint = format!("SHA256:{}", BASE64.encode(hash));
+        let public_key_b64 = BASE64.encode(key);
 
-        Ok((key_type_str.to_string(), fingerprint))
+        Ok((key_type_str.to_string(), fingerprint, public_key_b64))
     }
 
     /// 执行认证并返回缓存的凭据
@@ -551,6 +1217,7 @@ impl SessionManager {
     /// 避免多次访问系统钥匙串。
     fn authenticate(
         &self,
+        db: &Database,
         session: &Session,
         profile: &Profile,
         password: Option<&str>,
@@ -559,21 +1226,41 @@ impl SessionManager {
         // 检查是否被锁定
         self.check_auth_lockout(&profile.id)?;
 
-        let result = match profile.auth_type {
-            AuthType::Password => {
-                let pwd = self.auth_password(session, &profile.username, profile, password)?;
+        let result = match profile.auth {
+            Auth::Password { .. } => {
+                let pwd = self.auth_password(db, session, &profile.username, profile, password)?;
                 Ok(CachedCredentials {
                     password: Some(pwd),
                     passphrase: None,
+                    is_agent: false,
                 })
             }
-            AuthType::Key => {
-                let pp = self.auth_key(session, &profile.username, profile, passphrase)?;
+            Auth::Key { .. } => {
+                let pp = self.auth_key(db, session, &profile.username, profile, passphrase)?;
                 Ok(CachedCredentials {
                     password: None,
                     passphrase: pp,
+                    is_agent: false,
+                })
+            }
+            Auth::Agent => {
+                self.auth_agent(session, &profile.username)?;
+                Ok(CachedCredentials {
+                    password: None,
+                    passphrase: None,
+                    is_agent: true,
                 })
             }
+            Auth::KeyboardInteractive => {
+                // 不应该走到这里：keyboard-interactive 的提示内容要等一次真正的
+                // 握手才知道，connect()/connect_after_trust() 里在调用本方法之前
+                // 就已经用 `authenticate_or_prompt` 分流掉了，走不到这个 match 臂；
+                // 这里只是让 match 保持穷尽，并给出一个说得清楚的错误而不是 panic
+                Err(AppError::new(
+                    ErrorCode::InvalidArgument,
+                    "keyboard-interactive 认证需要先探测服务器提示，请使用 connect_after_interactive",
+                ))
+            }
         };
 
         // 记录认证结果
@@ -586,6 +1273,134 @@ impl SessionManager {
         result
     }
 
+    /// 在 [`Self::authenticate`] 之上再包一层，专门处理 keyboard-interactive：
+    /// 探测服务器的质询内容，能问出提示就转成 [`AuthOutcome::NeedsInteractive`]
+    /// 交还给上层，其余认证方式原样委托给 `authenticate`
+    fn authenticate_or_prompt(
+        &self,
+        db: &Database,
+        session: &Session,
+        profile: &Profile,
+        password: Option<&str>,
+        passphrase: Option<&str>,
+    ) -> AppResult<AuthOutcome> {
+        if !matches!(profile.auth, Auth::KeyboardInteractive) {
+            return self
+                .authenticate(db, session, profile, password, passphrase)
+                .map(AuthOutcome::Authenticated);
+        }
+
+        self.check_auth_lockout(&profile.id)?;
+
+        let outcome = match self.probe_keyboard_interactive(session, profile) {
+            Ok(Some(pending)) => Ok(AuthOutcome::NeedsInteractive(pending)),
+            Ok(None) => Ok(AuthOutcome::Authenticated(CachedCredentials {
+                password: None,
+                passphrase: None,
+                is_agent: false,
+            })),
+            Err(e) => Err(e),
+        };
+
+        // 质询本身不算认证失败，只有探测报错（如服务器一个提示都没给）才计入失败次数
+        if let Err(ref e) = outcome {
+            tracing::warn!(profile_id = %profile.id, error = %e, "keyboard-interactive 探测失败");
+            self.record_auth_failure(&profile.id);
+        }
+
+        outcome
+    }
+
+    /// 探测 keyboard-interactive 的质询内容
+    ///
+    /// 服务器要问什么在发起请求前并不知道，这里先用空答案"问一次"：`prompt()`
+    /// 回调会原样记录下 instructions/prompts，通常会因为答案为空而认证失败——
+    /// 这正是预期行为，调用方据此转入 [`ConnectStatus::NeedInteractiveResponse`]
+    /// 等真正的用户输入。极少数服务器配置下 keyboard-interactive 不需要用户输入
+    /// 就能过（比如只是走个形式），此时直接返回 `Ok(None)` 表示已经认证成功
+    fn probe_keyboard_interactive(
+        &self,
+        session: &Session,
+        profile: &Profile,
+    ) -> AppResult<Option<NeedInteractiveResponse>> {
+        tracing::debug!(username = %profile.username, "正在探测 keyboard-interactive 提示");
+
+        let mut recorder = PromptRecorder::default();
+        let result = session.userauth_keyboard_interactive(&profile.username, &mut recorder);
+
+        if session.authenticated() {
+            tracing::info!(username = %profile.username, "keyboard-interactive 无需用户输入即认证成功");
+            return Ok(None);
+        }
+
+        if recorder.prompts.is_empty() {
+            let detail = result.err().map(|e| e.to_string()).unwrap_or_default();
+            return Err(AppError::auth_failed(format!(
+                "keyboard-interactive 认证失败，服务器未返回任何提示: {}",
+                detail
+            )));
+        }
+
+        Ok(Some(NeedInteractiveResponse {
+            profile_id: profile.id.clone(),
+            instructions: recorder.instructions,
+            prompts: recorder.prompts,
+        }))
+    }
+
+    /// keyboard-interactive 收到用户作答后继续连接
+    ///
+    /// HostKey 只会在校验通过之后才会走到 [`ConnectStatus::NeedInteractiveResponse`]，
+    /// 这里重新建立一条 TCP+SSH 连接，把收集到的答案按服务器提示的原始顺序喂回
+    /// `userauth_keyboard_interactive` 的回调。这条新 session 本身可以正常复用
+    /// （和密码/Key 认证建立的 session 没有区别），只是没有可以缓存的凭据——
+    /// 验证码、Duo 推送这类质询本身就是一次性的，重连/创建 Terminal 时无法照搬，
+    /// 只能重新走一轮作答
+    pub fn connect_after_interactive(
+        &self,
+        // 与 connect()/connect_after_trust() 保持同样的签名，便于 commands 层统一
+        // 传参；keyboard-interactive 没有可以从数据库读取的已保存凭据，这里用不上
+        _db: &Database,
+        profile: &Profile,
+        responses: Vec<String>,
+        timeout_secs: u64,
+    ) -> AppResult<ConnectResult> {
+        let timeout = Duration::from_secs(timeout_secs);
+
+        let session = self.establish_ssh_session(profile, timeout)?;
+        let (_, fingerprint, _) = self.get_host_key_info(&session)?;
+
+        self.check_auth_lockout(&profile.id)?;
+
+        let mut responder = PromptResponder::new(responses);
+        let auth_result =
+            session.userauth_keyboard_interactive(&profile.username, &mut responder);
+
+        if auth_result.is_err() || !session.authenticated() {
+            self.record_auth_failure(&profile.id);
+            tracing::warn!(username = %profile.username, "keyboard-interactive 作答认证失败");
+            return Err(AppError::auth_failed("keyboard-interactive 认证失败，请检查填写的答案"));
+        }
+        self.clear_auth_failures(&profile.id);
+
+        let cached_credentials = CachedCredentials {
+            password: None,
+            passphrase: None,
+            is_agent: false,
+        };
+        let result =
+            self.finalize_connection(session, &profile.id, fingerprint, cached_credentials)?;
+
+        tracing::info!(
+            session_id = %result.session_id,
+            profile_id = %profile.id,
+            host = %profile.host,
+            "SSH 会话已建立（keyboard-interactive）"
+        );
+
+        Ok(result)
+    }
+
     /// 检查是否被锁定
     fn check_auth_lockout(&self, profile_id: &str) -> AppResult<()> {
         let failures = self
@@ -642,6 +1457,7 @@ impl SessionManager {
     /// 返回使用的密码，用于缓存以便后续创建独立 session
     fn auth_password(
         &self,
+        db: &Database,
         session: &Session,
         username: &str,
         profile: &Profile,
@@ -652,8 +1468,8 @@ impl SessionManager {
         // 优先使用临时密码，否则从 Keychain 获取
         let password = if let Some(pwd) = temp_password {
             pwd.to_string()
-        } else if let Some(ref pwd_ref) = profile.password_ref {
-            credential_get(pwd_ref)?
+        } else if let Some(pwd_ref) = profile.auth.password_ref() {
+            credential_get(db, pwd_ref)?
                 .ok_or_else(|| AppError::auth_failed("密码未保存，请重新输入"))?
         } else {
             return Err(AppError::auth_failed("需要提供密码"));
@@ -679,6 +1495,7 @@ impl SessionManager {
     /// 返回使用的 passphrase（如果有），用于缓存以便后续创建独立 session
     fn auth_key(
         &self,
+        db: &Database,
         session: &Session,
         username: &str,
         profile: &Profile,
@@ -686,56 +1503,71 @@ impl SessionManager {
     ) -> AppResult<Option<String>> {
         tracing::debug!(username = %username, "正在进行 Key 认证");
 
-        let key_path = profile
-            .private_key_path
-            .as_ref()
-            .ok_or_else(|| AppError::auth_failed("未配置私钥路径"))?;
-
-        let key_path = Path::new(key_path);
-        if !key_path.exists() {
-            return Err(AppError::not_found(format!(
-                "私钥文件不存在: {}",
-                key_path.display()
-            )));
-        }
-
-        // 检查私钥文件权限（仅 Unix 系统）
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            if let Ok(metadata) = std::fs::metadata(key_path) {
-                let mode = metadata.permissions().mode();
-                // 检查是否有 group/other 可读权限
-                if mode & 0o077 != 0 {
-                    tracing::warn!(
-                        key_path = %key_path.display(),
-                        mode = format!("{:o}", mode),
-                        "私钥文件权限过宽，建议设置为 600 或 400"
-                    );
-                }
-            }
-        }
-
         // 获取 passphrase（如果需要）
         let passphrase = if let Some(pp) = temp_passphrase {
             Some(pp.to_string())
-        } else if let Some(ref pp_ref) = profile.passphrase_ref {
-            credential_get(pp_ref)?
+        } else if let Some(pp_ref) = profile.auth.passphrase_ref() {
+            credential_get(db, pp_ref)?
         } else {
             None
         };
 
         let has_passphrase = passphrase.is_some();
-        let result = session.userauth_pubkey_file(username, None, key_path, passphrase.as_deref());
 
-        result.map_err(|e| {
-            tracing::warn!(error = %e, "Key 认证失败");
-            let msg = if has_passphrase {
-                "Key 认证失败，请检查私钥文件和密码"
-            } else {
-                "Key 认证失败，请检查私钥文件（可能需要 passphrase）"
-            };
-            AppError::auth_failed(msg)
+        let result = if let Some(key_ref) = profile.auth.private_key_ref() {
+            // 私钥内容托管在系统安全存储/密钥库中，全程不落地到文件系统
+            let armored_key = credential_get_private_key(db, key_ref)?
+                .ok_or_else(|| AppError::auth_failed("私钥未保存，请重新导入"))?;
+            session
+                .userauth_pubkey_memory(username, None, &armored_key, passphrase.as_deref())
+                .map_err(|source| crate::services::key_manager::KeyAuthFailure {
+                    source,
+                    key_text: Some(armored_key.clone()),
+                    had_passphrase: has_passphrase,
+                })
+        } else {
+            let key_path = profile
+                .auth
+                .private_key_path()
+                .ok_or_else(|| AppError::auth_failed("未配置私钥路径"))?;
+
+            let key_path = Path::new(key_path);
+            if !key_path.exists() {
+                return Err(AppError::not_found(format!(
+                    "私钥文件不存在: {}",
+                    key_path.display()
+                )));
+            }
+
+            // 检查私钥文件权限（仅 Unix 系统）
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Ok(metadata) = std::fs::metadata(key_path) {
+                    let mode = metadata.permissions().mode();
+                    // 检查是否有 group/other 可读权限
+                    if mode & 0o077 != 0 {
+                        tracing::warn!(
+                            key_path = %key_path.display(),
+                            mode = format!("{:o}", mode),
+                            "私钥文件权限过宽，建议设置为 600 或 400"
+                        );
+                    }
+                }
+            }
+
+            session
+                .userauth_pubkey_file(username, None, key_path, passphrase.as_deref())
+                .map_err(|source| crate::services::key_manager::KeyAuthFailure {
+                    source,
+                    key_text: None,
+                    had_passphrase: has_passphrase,
+                })
+        };
+
+        result.map_err(|failure| {
+            tracing::warn!(error = %failure.source, "Key 认证失败");
+            AppError::from(failure)
         })?;
 
         if !session.authenticated() {
@@ -746,15 +1578,143 @@ impl SessionManager {
         Ok(passphrase)
     }
 
+    /// SSH agent 认证
+    ///
+    /// 依次尝试 agent 持有的每一个身份（identity），使用第一个认证成功的；
+    /// 不涉及任何需要缓存的凭据——私钥和签名都由 agent 进程持有/完成。
+    fn auth_agent(&self, session: &Session, username: &str) -> AppResult<()> {
+        tracing::debug!(username = %username, "正在进行 SSH agent 认证");
+
+        let mut agent = session
+            .agent()
+            .map_err(|e| AppError::auth_failed(format!("无法连接 SSH agent: {}", e)))?;
+        agent
+            .connect()
+            .map_err(|e| AppError::auth_failed(format!("连接 SSH agent 失败: {}", e)))?;
+        agent
+            .list_identities()
+            .map_err(|e| AppError::auth_failed(format!("列出 SSH agent 身份失败: {}", e)))?;
+
+        let identities = agent
+            .identities()
+            .map_err(|e| AppError::auth_failed(format!("读取 SSH agent 身份失败: {}", e)))?;
+        if identities.is_empty() {
+            return Err(AppError::auth_failed("SSH agent 中没有可用的身份"));
+        }
+
+        let mut last_error = None;
+        for identity in &identities {
+            match agent.userauth(username, identity) {
+                Ok(()) if session.authenticated() => {
+                    tracing::info!(
+                        username = %username,
+                        comment = %identity.comment(),
+                        "SSH agent 认证成功"
+                    );
+                    return Ok(());
+                }
+                Ok(()) => {}
+                Err(e) => last_error = Some(e.to_string()),
+            }
+        }
+
+        tracing::warn!(username = %username, error = ?last_error, "SSH agent 认证失败");
+        Err(AppError::auth_failed(
+            "SSH agent 中没有身份能通过服务器认证",
+        ))
+    }
+
+    /// 列出本机正在运行的 SSH agent（Unix `SSH_AUTH_SOCK`/Windows Pageant 命名管道）
+    /// 当前持有的全部身份
+    ///
+    /// 不依赖任何 Profile 或已建立的连接——只用于 UI 在用户选择 Agent 认证方式、
+    /// 发起真正连接前展示 agent 提供了哪些 key，帮用户确认是不是想要的那一个；
+    /// agent 为空或未运行时返回空列表，不是错误
+    pub fn list_agent_identities(&self) -> AppResult<Vec<AgentIdentity>> {
+        let session = Session::new()
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("无法创建 SSH 会话: {}", e)))?;
+
+        let mut agent = session
+            .agent()
+            .map_err(|e| AppError::auth_failed(format!("无法连接 SSH agent: {}", e)))?;
+        agent
+            .connect()
+            .map_err(|e| AppError::auth_failed(format!("连接 SSH agent 失败，请确认已启动 ssh-agent: {}", e)))?;
+        agent
+            .list_identities()
+            .map_err(|e| AppError::auth_failed(format!("列出 SSH agent 身份失败: {}", e)))?;
+
+        let identities = agent
+            .identities()
+            .map_err(|e| AppError::auth_failed(format!("读取 SSH agent 身份失败: {}", e)))?;
+
+        Ok(identities
+            .iter()
+            .map(|identity| AgentIdentity {
+                comment: identity.comment().to_string(),
+                public_key_b64: BASE64.encode(identity.blob()),
+            })
+            .collect())
+    }
+
     /// 获取远程 home 目录
-    fn get_home_path(&self, session: &Session) -> AppResult<String> {
-        // 执行 echo $HOME 获取 home 目录
+    fn get_home_path(&self, session: &Session, family: SshFamily) -> AppResult<String> {
+        let command = match family {
+            SshFamily::Unix => "echo $HOME",
+            SshFamily::Windows => "echo %USERPROFILE%",
+        };
+        let output = self.exec_probe(session, command)?;
+        let home = output.trim().trim_matches('"');
+
+        // Windows 下用的是变量替换而不是真的 shell 展开，cmd.exe 找不到
+        // USERPROFILE 时会原样回显 "%USERPROFILE%"，需要当成没取到处理
+        let unresolved = home.is_empty() || home.eq_ignore_ascii_case("%USERPROFILE%");
+
+        Ok(if unresolved {
+            match family {
+                SshFamily::Unix => "/",
+                SshFamily::Windows => "C:\\",
+            }
+        } else {
+            home
+        }
+        .to_string())
+    }
+
+    /// 探测远程主机操作系统族
+    ///
+    /// 优先尝试 `uname -s`（绝大多数 Unix-like 服务器都支持），没有输出（说明
+    /// 默认 shell 是 cmd.exe/PowerShell，没有这个命令）时依次尝试 cmd.exe 的
+    /// `echo %OS%` 和 PowerShell 的 `$env:OS`——两者在 Windows 上都会回显
+    /// `Windows_NT`，默认 shell 具体是哪一个取决于服务器的 OpenSSH 配置，都试一遍
+    /// 才能覆盖常见场景；两边都探测不到时按 Unix 处理，与这之前 `echo $HOME` 的行为一致
+    fn detect_family(&self, session: &Session) -> SshFamily {
+        if let Ok(output) = self.exec_probe(session, "uname -s") {
+            if !output.trim().is_empty() {
+                return SshFamily::Unix;
+            }
+        }
+
+        for probe in ["echo %OS%", "$env:OS"] {
+            if let Ok(output) = self.exec_probe(session, probe) {
+                if output.trim().eq_ignore_ascii_case("windows_nt") {
+                    return SshFamily::Windows;
+                }
+            }
+        }
+
+        SshFamily::Unix
+    }
+
+    /// 执行一条命令并返回标准输出（trim 前的原始内容），用于探测远程环境信息；
+    /// 命令本身是否"成功"（退出码）不重要，这里只关心有没有拿到输出
+    fn exec_probe(&self, session: &Session, command: &str) -> AppResult<String> {
         let mut channel = session
             .channel_session()
             .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法创建通道: {}", e)))?;
 
         channel
-            .exec("echo $HOME")
+            .exec(command)
             .map_err(|e| AppError::new(ErrorCode::RemoteIoError, format!("无法执行命令: {}", e)))?;
 
         let mut output = String::new();
@@ -764,8 +1724,7 @@ impl SessionManager {
 
         channel.wait_close().ok();
 
-        let home = output.trim();
-        Ok(if home.is_empty() { "/" } else { home }.to_string())
+        Ok(output)
     }
 }
 
@@ -775,52 +1734,92 @@ impl Default for SessionManager {
     }
 }
 
-// SAFETY: SessionManager 手动实现 Send 和 Sync
-//
-// 背景:
-// - `ssh2::Session` 和 `ssh2::Sftp` 类型是 `!Send` 和 `!Sync`，因为底层的
-//   libssh2 C 库不是线程安全的。这导致包含它们的 `ManagedSession` 也是 `!Send + !Sync`。
-// - 然而，`SessionManager` 需要作为 Tauri State 跨线程共享，因此需要 `Send + Sync`。
-//
-// 为什么这是安全的:
-//
-// 1. 数据结构安全性:
-//    - `SessionManager` 只包含 `RwLock<HashMap<String, Arc<ManagedSession>>>`
-//    - `RwLock` 和 `HashMap` 本身是 `Send + Sync`（当内容类型满足条件时）
-//    - 问题仅来自 `ManagedSession` 内部的 `Session` 和 `Sftp`
-//
-// 2. 访问模式安全性:
-//    - SessionManager 的公共 API 只返回 `Arc<ManagedSession>` 的克隆引用
-//    - 调用者获取到 Arc 后，必须在 `tokio::task::spawn_blocking` 中执行所有
-//      SSH/SFTP 操作，确保这些操作在单个专用线程上顺序执行
-//    - 参见: src/commands/session.rs 和 src/services/transfer_manager.rs
-//
-// 3. 内部字段安全性:
-//    - `ManagedSession::last_activity` 和 `cached_credentials` 使用 `RwLock` 保护
-//    - 其他字段（`session_id`, `profile_id` 等）是不可变的 `String`/`Instant`
-//    - `Session` 和 `Sftp` 字段虽然是 `!Send`，但只在 `spawn_blocking` 闭包中使用
-//
-// 不变量 (Invariants):
-// - 所有对 `session.sftp` 或 `session.session` 的方法调用必须在 `spawn_blocking` 中
-// - 永远不要在异步上下文中直接调用 ssh2 的同步方法
-// - 修改此模块时必须维护这些不变量
-//
-// 违反安全性的情况（请勿这样做）:
-// ```ignore
-// // 错误: 在 async 函数中直接调用 sftp 方法
-// async fn bad_example(session: Arc<ManagedSession>) {
-//     session.sftp.stat(path); // 这会阻塞 tokio 运行时且不是线程安全的
-// }
+/// 把"未设置"和"设置成空字符串"都当作没有自定义算法偏好，交由 libssh2 使用默认值
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|s| !s.trim().is_empty())
+}
+
+/// 记录服务器 keyboard-interactive 质询内容的探测器
+///
+/// `prompt()` 回调只负责原样记录 instructions/prompts 并返回空答案——目的只是
+/// 在不知道真实提示前先"问一次"触发服务器把质询发过来，答案对不对不重要，
+/// 调用方会根据 `session.authenticated()` 的结果判断是否需要转入
+/// [`NeedInteractiveResponse`]
+#[derive(Default)]
+struct PromptRecorder {
+    instructions: String,
+    prompts: Vec<InteractivePrompt>,
+}
+
+impl ssh2::KeyboardInteractivePrompt for PromptRecorder {
+    fn prompt<'a>(
+        &mut self,
+        _username: &str,
+        instructions: &str,
+        prompts: &[ssh2::Prompt<'a>],
+    ) -> Vec<String> {
+        self.instructions = instructions.to_string();
+        self.prompts = prompts
+            .iter()
+            .map(|p| InteractivePrompt {
+                label: p.text.to_string(),
+                echo: p.echo,
+            })
+            .collect();
+        vec![String::new(); prompts.len()]
+    }
+}
+
+/// 把预先收集好的答案按服务器提示顺序喂回 `userauth_keyboard_interactive`
+///
+/// 服务器可能分多轮调用 `prompt()`（比如先确认用户名，再单独问验证码），这里用
+/// `index` 游标累计消费，保证跨轮次也不会把答案喂错位置
+struct PromptResponder {
+    responses: Vec<String>,
+    index: usize,
+}
+
+impl PromptResponder {
+    fn new(responses: Vec<String>) -> Self {
+        Self { responses, index: 0 }
+    }
+}
+
+impl ssh2::KeyboardInteractivePrompt for PromptResponder {
+    fn prompt<'a>(
+        &mut self,
+        _username: &str,
+        _instructions: &str,
+        prompts: &[ssh2::Prompt<'a>],
+    ) -> Vec<String> {
+        prompts
+            .iter()
+            .map(|_| {
+                let answer = self.responses.get(self.index).cloned().unwrap_or_default();
+                self.index += 1;
+                answer
+            })
+            .collect()
+    }
+}
+
+/// 多流并行传输使用的辅助连接，由 [`SessionManager::create_auxiliary_sftp_session`] 创建
+///
+/// 持有独立的 `Session` + `Sftp`（而非与主 session 共用），
+/// `_session` 字段本身从不被读取，仅用于在整个实例存活期间保持底层连接不被关闭。
+pub struct AuxiliarySftpConnection {
+    _session: Arc<Session>,
+    pub sftp: Sftp,
+}
+
+// SAFETY: AuxiliarySftpConnection 手动实现 Send（不实现 Sync）
 //
-// // 正确: 使用 spawn_blocking
-// async fn good_example(session: Arc<ManagedSession>) {
-//     tokio::task::spawn_blocking(move || {
-//         session.sftp.stat(path) // 在专用线程中安全执行
-//     }).await
-// }
-// ```
-unsafe impl Send for SessionManager {}
-unsafe impl Sync for SessionManager {}
+// `ssh2::Session`/`ssh2::Sftp` 是 `!Send`，原因同上（libssh2 非线程安全）。
+// 多流并行传输（见 `transfer_manager.rs` 的 `do_upload_parallel`/`do_download_parallel`）
+// 为每个 worker 线程创建一个独立的 `AuxiliarySftpConnection`，整个实例被 `move` 进
+// 该 worker 线程后由其独占使用，生命周期内绝不会有第二个线程持有同一实例的引用，
+// 因此只需要 Send（允许跨线程转移所有权），不需要 Sync（不允许跨线程共享引用）。
+unsafe impl Send for AuxiliarySftpConnection {}
 
 #[cfg(test)]
 mod tests {
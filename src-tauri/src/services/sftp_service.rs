@@ -6,17 +6,117 @@
 //! - 路径处理与安全验证
 
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
-
-use ssh2::Sftp;
-
-use crate::models::error::{AppError, AppResult};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use ssh2::{OpenFlags, OpenType, Sftp};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::sftp::{FsChangeEvent, FsChangeKind, SymlinkIssue, SymlinkIssueKind};
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::services::exclude_matcher::ExcludeMatcher;
+use crate::services::session_manager::SessionManager;
+use crate::services::storage_service::Database;
 
 // Unix 文件类型常量
 const S_IFMT: u32 = 0o170000; // 文件类型掩码
 const S_IFLNK: u32 = 0o120000; // 符号链接
 use crate::models::file_entry::{FileEntry, SortField, SortOrder, SortSpec};
 
+/// sftp_read_file / sftp_write_file 分块传输时的单块大小
+pub const SFTP_CHUNK_SIZE: u64 = 32 * 1024;
+
+/// 递归遍历跟随符号链接时允许的最大跳转次数，超过后判定为循环链接而不是无限跟随下去
+const MAX_SYMLINK_JUMPS: u32 = 20;
+
+/// `list_dir_recursive`/`get_directory_stats` 递归遍历时对符号链接的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkMode {
+    /// 跳过所有符号链接，不跟随也不计入统计/结果（旧行为）
+    Skip,
+    /// 解析链接目标并当作目标本身继续遍历；用跳转预算和祖先路径集合防止无限递归，
+    /// 异常链接记录为 [`SymlinkIssue`] 而不是静默丢弃
+    Follow,
+}
+
+/// `sync_recursive` 镜像远程目录树时的比对策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    /// 按大小和 mtime 与目标比对，只拷贝新增/变化的文件，并删除目标中源已不存在的文件
+    Incremental,
+    /// 不比对，拷贝源树中的每一个文件（目标已存在的同名文件会被覆盖）
+    Full,
+}
+
+/// `chmod_recursive` 遍历时处理哪些条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChmodTarget {
+    /// 目录和文件都处理
+    All,
+    /// 只处理目录
+    DirsOnly,
+    /// 只处理文件
+    FilesOnly,
+}
+
+/// 沿符号链接跳转解析出的最终结果
+enum SymlinkResolution {
+    /// 在 `max_jumps` 次跳转内解析到一个存在的非链接目标
+    Resolved(std::path::PathBuf, ssh2::FileStat),
+    /// 目标不存在（某一跳 `lstat` 返回 SFTP(2)）
+    Broken,
+    /// 跳转次数耗尽，判定为循环链接
+    Circular,
+}
+
+/// 沿 `start` 处的符号链接逐跳解析，直到遇到非链接目标、目标不存在，或用尽 `max_jumps` 跳
+fn resolve_symlink_chain(
+    sftp: &Sftp,
+    start: &Path,
+    max_jumps: u32,
+) -> AppResult<SymlinkResolution> {
+    let mut current = start.to_path_buf();
+    let mut jumps_left = max_jumps;
+
+    loop {
+        let lstat = match sftp.lstat(&current) {
+            Ok(s) => s,
+            Err(e) if e.code() == ssh2::ErrorCode::SFTP(2) => return Ok(SymlinkResolution::Broken),
+            Err(e) => return Err(AppError::from(e)),
+        };
+
+        let is_symlink = lstat
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+        if !is_symlink {
+            return Ok(SymlinkResolution::Resolved(current, lstat));
+        }
+
+        if jumps_left == 0 {
+            return Ok(SymlinkResolution::Circular);
+        }
+        jumps_left -= 1;
+
+        let target = sftp.readlink(&current).map_err(AppError::from)?;
+        current = if target.is_absolute() {
+            target
+        } else {
+            current
+                .parent()
+                .unwrap_or_else(|| Path::new("/"))
+                .join(target)
+        };
+    }
+}
+
 /// 将 SFTP 错误映射为 AppError，处理常见错误码
 fn map_sftp_error(e: ssh2::Error, path: &str) -> AppError {
     if e.code() == ssh2::ErrorCode::SFTP(2) {
@@ -26,6 +126,203 @@ fn map_sftp_error(e: ssh2::Error, path: &str) -> AppError {
     }
 }
 
+/// 符号权限表达式里的 who（修改对象）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolicWho {
+    User,
+    Group,
+    Other,
+}
+
+/// 符号权限表达式里的操作符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolicOp {
+    Add,
+    Remove,
+    Set,
+}
+
+/// 一条符号权限子句携带的权限位（未出现的字符对应的位保持不变）
+#[derive(Debug, Clone, Copy, Default)]
+struct SymbolicPerms {
+    read: bool,
+    write: bool,
+    execute: bool,
+    /// `X`：目录恒置位，文件仅在已有任意执行位时才置位
+    special_x: bool,
+    /// `s`：按 who 置 setuid/setgid
+    setid: bool,
+    /// `t`：粘滞位
+    sticky: bool,
+}
+
+/// 解析好的一条符号权限子句，例如 `u+rwX` 对应 `who=[User] op=Add perms={read,write,special_x}`
+#[derive(Debug, Clone)]
+struct SymbolicClause {
+    /// 为空表示未指定 who，等价于 `a`（全部）
+    who: Vec<SymbolicWho>,
+    op: SymbolicOp,
+    perms: SymbolicPerms,
+}
+
+const SETUID: u32 = 0o4000;
+const SETGID: u32 = 0o2000;
+const STICKY_BIT: u32 = 0o1000;
+const U_R: u32 = 0o400;
+const U_W: u32 = 0o200;
+const U_X: u32 = 0o100;
+const G_R: u32 = 0o040;
+const G_W: u32 = 0o020;
+const G_X: u32 = 0o010;
+const O_R: u32 = 0o004;
+const O_W: u32 = 0o002;
+const O_X: u32 = 0o001;
+
+/// 解析逗号分隔的符号权限表达式，如 `u+rwX,go-w`
+///
+/// 语法：`[ugoa]* [+-=] [rwxXst]*`；省略 who 等价于 `a`（对 user/group/other 都生效）
+fn parse_symbolic_clauses(spec: &str) -> AppResult<Vec<SymbolicClause>> {
+    let mut clauses = Vec::new();
+
+    for raw in spec.split(',') {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(AppError::invalid_argument(format!(
+                "符号权限表达式包含空子句: {}",
+                spec
+            )));
+        }
+
+        let mut chars = raw.chars().peekable();
+
+        let mut who = Vec::new();
+        while let Some(&c) = chars.peek() {
+            match c {
+                'u' => who.push(SymbolicWho::User),
+                'g' => who.push(SymbolicWho::Group),
+                'o' => who.push(SymbolicWho::Other),
+                'a' => {} // 显式 a：留空 who，后续统一按「全部」处理
+                _ => break,
+            }
+            chars.next();
+        }
+
+        let op = match chars.next() {
+            Some('+') => SymbolicOp::Add,
+            Some('-') => SymbolicOp::Remove,
+            Some('=') => SymbolicOp::Set,
+            _ => {
+                return Err(AppError::invalid_argument(format!(
+                    "符号权限表达式缺少操作符(+/-/=): {}",
+                    raw
+                )))
+            }
+        };
+
+        let mut perms = SymbolicPerms::default();
+        for c in chars {
+            match c {
+                'r' => perms.read = true,
+                'w' => perms.write = true,
+                'x' => perms.execute = true,
+                'X' => perms.special_x = true,
+                's' => perms.setid = true,
+                't' => perms.sticky = true,
+                other => {
+                    return Err(AppError::invalid_argument(format!(
+                        "符号权限表达式包含非法字符 '{}': {}",
+                        other, raw
+                    )))
+                }
+            }
+        }
+
+        clauses.push(SymbolicClause { who, op, perms });
+    }
+
+    Ok(clauses)
+}
+
+/// 将符号权限子句依次应用到 `current_perm`，返回最终数值 mode（含 setuid/setgid/sticky 位）
+///
+/// `X` 相对整个表达式只判定一次：是否已有任意执行位取 `current_perm`（应用任何子句之前）
+/// 的状态，与真实 chmod 行为一致，避免前一条子句加上的 `x` 影响后一条子句里 `X` 的判定
+fn apply_symbolic_clauses(clauses: &[SymbolicClause], current_perm: u32, is_dir: bool) -> u32 {
+    let had_any_exec = current_perm & (U_X | G_X | O_X) != 0;
+    let mut perm = current_perm;
+
+    for clause in clauses {
+        let whos: Vec<SymbolicWho> = if clause.who.is_empty() {
+            vec![SymbolicWho::User, SymbolicWho::Group, SymbolicWho::Other]
+        } else {
+            clause.who.clone()
+        };
+
+        let execute_bit = clause.perms.execute
+            || (clause.perms.special_x && (is_dir || had_any_exec));
+
+        for who in &whos {
+            let (r_bit, w_bit, x_bit) = match who {
+                SymbolicWho::User => (U_R, U_W, U_X),
+                SymbolicWho::Group => (G_R, G_W, G_X),
+                SymbolicWho::Other => (O_R, O_W, O_X),
+            };
+
+            let mut bits = 0u32;
+            if clause.perms.read {
+                bits |= r_bit;
+            }
+            if clause.perms.write {
+                bits |= w_bit;
+            }
+            if execute_bit {
+                bits |= x_bit;
+            }
+
+            match clause.op {
+                SymbolicOp::Add => perm |= bits,
+                SymbolicOp::Remove => perm &= !bits,
+                SymbolicOp::Set => {
+                    perm &= !(r_bit | w_bit | x_bit);
+                    perm |= bits;
+                }
+            }
+        }
+
+        if clause.perms.setid {
+            let applies_uid = whos.contains(&SymbolicWho::User);
+            let applies_gid = whos.contains(&SymbolicWho::Group);
+            match clause.op {
+                SymbolicOp::Remove => {
+                    if applies_uid {
+                        perm &= !SETUID;
+                    }
+                    if applies_gid {
+                        perm &= !SETGID;
+                    }
+                }
+                SymbolicOp::Add | SymbolicOp::Set => {
+                    if applies_uid {
+                        perm |= SETUID;
+                    }
+                    if applies_gid {
+                        perm |= SETGID;
+                    }
+                }
+            }
+        }
+
+        if clause.perms.sticky {
+            match clause.op {
+                SymbolicOp::Add | SymbolicOp::Set => perm |= STICKY_BIT,
+                SymbolicOp::Remove => perm &= !STICKY_BIT,
+            }
+        }
+    }
+
+    perm
+}
+
 /// SFTP 服务
 pub struct SftpService;
 
@@ -79,8 +376,116 @@ impl SftpService {
         Ok(())
     }
 
+    /// 结合词法规范化与实时符号链接解析，得到路径在远程文件系统上的真实绝对路径
+    ///
+    /// `normalize_path` 只在字符串层面清理 `.`/`..`/重复的 `/`，如果路径中间某个组件
+    /// 本身是符号链接，后面的 `..` 就可能沿着链接跳出预期之外的目录——字符串层面看
+    /// 不出这一点。这里优先尝试服务器原生的 `realpath`（更快，但不是所有 SFTP 实现都
+    /// 支持），失败则退回逐段解析：依次 `lstat` 每一段，遇到符号链接就 `readlink` 取
+    /// 目标并展开继续解析，用 [`MAX_SYMLINK_JUMPS`] 限制跳转次数避免循环链接
+    pub fn canonicalize(sftp: &Sftp, path: &str) -> AppResult<String> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        if let Ok(resolved) = sftp.realpath(Path::new(&normalized)) {
+            return Ok(Self::normalize_path(&resolved.to_string_lossy()));
+        }
+
+        Self::canonicalize_by_component(sftp, &normalized)
+    }
+
+    /// [`Self::canonicalize`] 在服务器不支持 `realpath` 时的逐段解析实现
+    fn canonicalize_by_component(sftp: &Sftp, normalized: &str) -> AppResult<String> {
+        let mut remaining: Vec<String> = Vec::new();
+        Self::push_path_components(&mut remaining, normalized);
+
+        let mut resolved = String::from("/");
+        let mut jumps_left = MAX_SYMLINK_JUMPS;
+
+        while let Some(component) = remaining.pop() {
+            if component == "." {
+                continue;
+            }
+            if component == ".." {
+                if resolved != "/" {
+                    let idx = resolved.rfind('/').unwrap_or(0).max(1);
+                    resolved.truncate(idx);
+                }
+                continue;
+            }
+
+            let candidate = if resolved == "/" {
+                format!("/{}", component)
+            } else {
+                format!("{}/{}", resolved, component)
+            };
+
+            let lstat = match sftp.lstat(Path::new(&candidate)) {
+                Ok(s) => s,
+                Err(e) if e.code() == ssh2::ErrorCode::SFTP(2) => {
+                    // 这一段及之后尚不存在（比如正规化一个即将创建的路径），无需也
+                    // 无法继续解析符号链接，原样拼回剩余部分后返回
+                    resolved = candidate;
+                    while let Some(rest) = remaining.pop() {
+                        resolved.push('/');
+                        resolved.push_str(&rest);
+                    }
+                    return Ok(resolved);
+                }
+                Err(e) => return Err(AppError::from(e)),
+            };
+
+            let is_symlink = lstat
+                .perm
+                .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                .unwrap_or(false);
+            if !is_symlink {
+                resolved = candidate;
+                continue;
+            }
+
+            if jumps_left == 0 {
+                return Err(AppError::invalid_argument(format!(
+                    "符号链接层级过深或存在循环: {}",
+                    candidate
+                )));
+            }
+            jumps_left -= 1;
+
+            let target = sftp.readlink(Path::new(&candidate)).map_err(AppError::from)?;
+            let target_str = target.to_string_lossy().to_string();
+            if target.is_absolute() {
+                resolved = "/".to_string();
+            }
+            Self::push_path_components(&mut remaining, &target_str);
+        }
+
+        Ok(resolved)
+    }
+
+    /// 将 `path` 按 `/` 拆分成组件，倒序压入 `stack`（末尾弹出即为从前到后的顺序），
+    /// 供 [`Self::canonicalize_by_component`] 把符号链接目标的组件插到待处理栈顶
+    fn push_path_components(stack: &mut Vec<String>, path: &str) {
+        let components: Vec<String> = path
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string())
+            .collect();
+        stack.extend(components.into_iter().rev());
+    }
+
     /// 列出目录内容
-    pub fn list_dir(sftp: &Sftp, path: &str, sort: Option<SortSpec>) -> AppResult<Vec<FileEntry>> {
+    ///
+    /// `exclude` 非空时，按条目相对于 `path` 的名称（即条目自身的文件名，这里没有更深
+    /// 层级）过滤掉匹配到的条目
+    pub fn list_dir(
+        sftp: &Sftp,
+        path: &str,
+        exclude: Option<&ExcludeMatcher>,
+        sort: Option<SortSpec>,
+        follow_symlinks: bool,
+    ) -> AppResult<Vec<FileEntry>> {
         let normalized = Self::normalize_path(path);
         Self::validate_path(&normalized)?;
 
@@ -114,9 +519,42 @@ impl SftpService {
                     return None;
                 }
 
+                if let Some(matcher) = exclude {
+                    if matcher.is_excluded(&name, file_stat.is_dir()) {
+                        return None;
+                    }
+                }
+
                 let full_path = path_buf.to_string_lossy().to_string();
 
-                Some(Self::file_stat_to_entry(name, full_path, file_stat))
+                let is_symlink = file_stat
+                    .perm
+                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                    .unwrap_or(false);
+
+                if !is_symlink {
+                    return Some(Self::file_stat_to_entry(name, full_path, file_stat, false, None));
+                }
+
+                // 符号链接：readlink 获取原始目标文本；follow_symlinks 时再额外
+                // 跟随解析目标的元数据（悬空链接时退回链接自身属性）
+                let symlink_target = sftp
+                    .readlink(&path_buf)
+                    .ok()
+                    .map(|p| p.to_string_lossy().to_string());
+                let entry_stat = if follow_symlinks {
+                    sftp.stat(&path_buf).unwrap_or(file_stat)
+                } else {
+                    file_stat
+                };
+
+                Some(Self::file_stat_to_entry(
+                    name,
+                    full_path,
+                    entry_stat,
+                    true,
+                    symlink_target,
+                ))
             })
             .collect();
 
@@ -295,8 +733,17 @@ impl SftpService {
     ///
     /// 用于删除确认对话框显示
     /// 使用迭代而非递归，避免栈溢出
-    /// 跳过符号链接防止无限循环
-    pub fn get_directory_stats(sftp: &Sftp, path: &str) -> AppResult<DirectoryStats> {
+    /// `mode` 为 [`SymlinkMode::Skip`] 时符号链接被直接跳过（旧行为）；为 `Follow` 时
+    /// 解析链接目标计入统计，循环/断链记录进返回值的 `symlink_issues`
+    ///
+    /// `exclude` 非空时，匹配到的条目（按相对 `path` 的路径）既不计入统计也不会被
+    /// 展开——跳过一个目录就等于跳过它底下的一切，与 `node_modules` 这类场景的直觉一致
+    pub fn get_directory_stats(
+        sftp: &Sftp,
+        path: &str,
+        mode: SymlinkMode,
+        exclude: Option<&ExcludeMatcher>,
+    ) -> AppResult<DirectoryStats> {
         let normalized = Self::normalize_path(path);
         Self::validate_path(&normalized)?;
 
@@ -327,17 +774,20 @@ impl SftpService {
                 file_count: 1,
                 dir_count: 0,
                 total_size: stat.size.unwrap_or(0),
+                symlink_issues: vec![],
             });
         }
 
         let mut file_count: u64 = 0;
         let mut dir_count: u64 = 0;
         let mut total_size: u64 = 0;
+        let mut symlink_issues: Vec<SymlinkIssue> = vec![];
 
-        // 使用栈进行迭代遍历（避免递归导致栈溢出）
-        let mut stack = vec![normalized.clone()];
+        // 使用栈进行迭代遍历（避免递归导致栈溢出）；每项携带从根到自己的祖先路径，
+        // 仅 Follow 模式下用于识别指回祖先目录的循环链接
+        let mut stack = vec![(normalized.clone(), Rc::new(Vec::<String>::new()))];
 
-        while let Some(current_path) = stack.pop() {
+        while let Some((current_path, ancestors)) = stack.pop() {
             let current_obj = Path::new(&current_path);
 
             let entries = match sftp.readdir(current_obj) {
@@ -366,23 +816,80 @@ impl SftpService {
                     }
                 };
 
-                // 跳过符号链接
+                if let Some(matcher) = exclude {
+                    let relative = full_path
+                        .strip_prefix(&normalized)
+                        .map(|r| r.trim_start_matches('/'))
+                        .unwrap_or(&full_path);
+                    if matcher.is_excluded(relative, entry_lstat.is_dir()) {
+                        continue;
+                    }
+                }
+
                 let is_symlink = entry_lstat
                     .perm
                     .map(|mode| (mode & S_IFMT) == S_IFLNK)
                     .unwrap_or(false);
 
-                if is_symlink {
+                if !is_symlink {
+                    if entry_lstat.is_dir() {
+                        dir_count += 1;
+                        let mut next_ancestors = (*ancestors).clone();
+                        next_ancestors.push(full_path.clone());
+                        stack.push((full_path, Rc::new(next_ancestors)));
+                    } else {
+                        file_count += 1;
+                        total_size += entry_lstat.size.unwrap_or(0);
+                    }
+                    continue;
+                }
+
+                if mode == SymlinkMode::Skip {
                     tracing::debug!(path = %full_path, "跳过符号链接");
                     continue;
                 }
 
-                if entry_lstat.is_dir() {
-                    dir_count += 1;
-                    stack.push(full_path);
-                } else {
-                    file_count += 1;
-                    total_size += entry_lstat.size.unwrap_or(0);
+                let target = sftp
+                    .readlink(&path_buf)
+                    .ok()
+                    .map(|p| p.to_string_lossy().to_string());
+
+                match resolve_symlink_chain(sftp, &path_buf, MAX_SYMLINK_JUMPS)? {
+                    SymlinkResolution::Broken => {
+                        symlink_issues.push(SymlinkIssue {
+                            path: full_path,
+                            target,
+                            kind: SymlinkIssueKind::Broken,
+                        });
+                    }
+                    SymlinkResolution::Circular => {
+                        symlink_issues.push(SymlinkIssue {
+                            path: full_path,
+                            target,
+                            kind: SymlinkIssueKind::Circular,
+                        });
+                    }
+                    SymlinkResolution::Resolved(resolved_path, resolved_stat) => {
+                        let canonical = resolved_path.to_string_lossy().to_string();
+
+                        if resolved_stat.is_dir() {
+                            if ancestors.iter().any(|a| a == &canonical) {
+                                symlink_issues.push(SymlinkIssue {
+                                    path: full_path,
+                                    target,
+                                    kind: SymlinkIssueKind::Circular,
+                                });
+                                continue;
+                            }
+                            dir_count += 1;
+                            let mut next_ancestors = (*ancestors).clone();
+                            next_ancestors.push(canonical.clone());
+                            stack.push((canonical, Rc::new(next_ancestors)));
+                        } else {
+                            file_count += 1;
+                            total_size += resolved_stat.size.unwrap_or(0);
+                        }
+                    }
                 }
             }
         }
@@ -391,18 +898,298 @@ impl SftpService {
             file_count,
             dir_count,
             total_size,
+            symlink_issues,
+        })
+    }
+
+    /// [`Self::compute_directory_stats_parallel`] 允许的最大并发 worker 数——每个 worker
+    /// 独立开一条全新的 TCP+SSH 连接（见 `SessionManager::create_auxiliary_sftp_session`），
+    /// 不设上限的话调用方传入一个很大的值会把目标服务器的并发连接数限制打爆
+    pub const MAX_DIRECTORY_STATS_CONCURRENCY: u8 = 16;
+
+    /// 并发统计目录大小（文件数、目录数、总大小），用多条独立 SFTP 连接对同一棵目录树
+    /// 并行 `readdir`，比 [`Self::get_directory_stats`] 的单连接串行遍历更快地扫完大目录
+    ///
+    /// 策略：维护一个共享的目录工作队列（`Mutex<VecDeque<String>>`），`concurrency` 个
+    /// worker 各自用 [`SessionManager::create_auxiliary_sftp_session`] 开一条独立连接，
+    /// 从队列取目录 `readdir`，把子目录重新塞回队列、文件累加进共享原子计数
+    /// （`file_count`/`dir_count`/`total_size`/`entries_checked`）；`pending` 记录"已发现
+    /// 但尚未 `readdir` 完毕"的目录数，归零时所有 worker 自然退出，不需要额外的停止信号
+    ///
+    /// 进度回调按固定时间间隔（而非逐条目）触发，避免大目录下事件刷屏
+    ///
+    /// 限制：不跟随符号链接（等价于 [`Self::get_directory_stats`] 的 [`SymlinkMode::Skip`]），
+    /// 返回的 [`DirectoryStats::symlink_issues`] 恒为空——并发场景下要让多个 worker 共享
+    /// 判断循环链接所需的祖先路径集合，复杂度收益不成正比；需要检测符号链接循环请用
+    /// 串行的 [`Self::get_directory_stats`]
+    pub fn compute_directory_stats_parallel(
+        session_manager: &Arc<SessionManager>,
+        db: &Arc<Database>,
+        session_id: &str,
+        path: &str,
+        concurrency: u8,
+        progress_callback: Option<DirectoryStatsProgressCallback>,
+    ) -> AppResult<DirectoryStats> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+        let concurrency = concurrency.clamp(1, Self::MAX_DIRECTORY_STATS_CONCURRENCY) as usize;
+
+        let root_stat = {
+            let session = session_manager.get_session(session_id)?;
+            let normalized_for_stat = normalized.clone();
+            session.with_sftp(move |sftp| {
+                sftp.stat(Path::new(&normalized_for_stat))
+                    .map_err(|e| map_sftp_error(e, &normalized_for_stat))
+            })?
+        };
+
+        if !root_stat.is_dir() {
+            return Ok(DirectoryStats {
+                file_count: 1,
+                dir_count: 0,
+                total_size: root_stat.size.unwrap_or(0),
+                symlink_issues: vec![],
+            });
+        }
+
+        let queue: Mutex<VecDeque<String>> = Mutex::new(VecDeque::from([normalized.clone()]));
+        let queue_cond = Condvar::new();
+        // 已发现但尚未 readdir 完毕的目录数，初始为 1（根目录本身）；归零时所有已发现的
+        // 目录都处理完了，worker 据此判断可以退出，而不需要单独的"停止信号"
+        let pending = AtomicI64::new(1);
+
+        let file_count = AtomicU64::new(0);
+        let dir_count = AtomicU64::new(0);
+        let total_size = AtomicU64::new(0);
+        let entries_checked = AtomicU64::new(0);
+        let read_failures: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        let mut connections = Vec::with_capacity(concurrency);
+        for _ in 0..concurrency {
+            connections.push(session_manager.create_auxiliary_sftp_session(db, session_id)?);
+        }
+
+        // 重新绑定为共享引用再传给 `move` 闭包：`move` 会把捕获到的路径按值移动，
+        // 若直接捕获 `queue`/`pending` 等 `Mutex`/`Atomic` 本身，第一个 worker 闭包就会
+        // 把它们整个移走，其余 worker 无法再共享访问。先取引用（`&T` 是 `Copy`），
+        // `move` 移动的就只是这份引用的拷贝，`conn` 本身仍按值整体移入各自的闭包
+        let queue_ref = &queue;
+        let queue_cond_ref = &queue_cond;
+        let pending_ref = &pending;
+        let file_count_ref = &file_count;
+        let dir_count_ref = &dir_count;
+        let total_size_ref = &total_size;
+        let entries_checked_ref = &entries_checked;
+        let read_failures_ref = &read_failures;
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = connections
+                .into_iter()
+                .map(|conn| {
+                    let queue = queue_ref;
+                    let queue_cond = queue_cond_ref;
+                    let pending = pending_ref;
+                    let file_count = file_count_ref;
+                    let dir_count = dir_count_ref;
+                    let total_size = total_size_ref;
+                    let entries_checked = entries_checked_ref;
+                    let read_failures = read_failures_ref;
+                    scope.spawn(move || {
+                        let sftp = &conn.sftp;
+                        loop {
+                            let current_path = {
+                                let mut q = queue.lock().unwrap();
+                                loop {
+                                    if let Some(dir) = q.pop_front() {
+                                        break Some(dir);
+                                    }
+                                    if pending.load(AtomicOrdering::Acquire) == 0 {
+                                        break None;
+                                    }
+                                    q = queue_cond
+                                        .wait_timeout(q, Duration::from_millis(50))
+                                        .unwrap()
+                                        .0;
+                                }
+                            };
+
+                            let Some(current_path) = current_path else {
+                                break;
+                            };
+
+                            match sftp.readdir(Path::new(&current_path)) {
+                                Ok(entries) => {
+                                    let mut new_dirs = Vec::new();
+                                    for (path_buf, stat) in entries {
+                                        let file_name =
+                                            path_buf.file_name().and_then(|n| n.to_str());
+                                        if matches!(file_name, None | Some(".") | Some("..")) {
+                                            continue;
+                                        }
+                                        entries_checked.fetch_add(1, AtomicOrdering::Relaxed);
+
+                                        let is_symlink = stat
+                                            .perm
+                                            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                                            .unwrap_or(false);
+                                        if is_symlink {
+                                            // 与 get_directory_stats 的 Skip 模式一致：
+                                            // 既不计数也不跟随
+                                            continue;
+                                        }
+
+                                        if stat.is_dir() {
+                                            dir_count.fetch_add(1, AtomicOrdering::Relaxed);
+                                            new_dirs.push(path_buf.to_string_lossy().to_string());
+                                        } else {
+                                            file_count.fetch_add(1, AtomicOrdering::Relaxed);
+                                            total_size.fetch_add(
+                                                stat.size.unwrap_or(0),
+                                                AtomicOrdering::Relaxed,
+                                            );
+                                        }
+                                    }
+
+                                    if !new_dirs.is_empty() {
+                                        pending.fetch_add(
+                                            new_dirs.len() as i64,
+                                            AtomicOrdering::AcqRel,
+                                        );
+                                        queue.lock().unwrap().extend(new_dirs);
+                                        queue_cond.notify_all();
+                                    }
+                                }
+                                Err(e) => {
+                                    read_failures
+                                        .lock()
+                                        .unwrap()
+                                        .push(format!("{}: {}", current_path, e));
+                                }
+                            }
+
+                            pending.fetch_sub(1, AtomicOrdering::AcqRel);
+                            queue_cond.notify_all();
+                        }
+                    })
+                })
+                .collect();
+
+            // 固定间隔轮询上报进度，而不是让每个 worker 在每条目录项上都触发一次回调
+            if let Some(callback) = &progress_callback {
+                let progress_interval = Duration::from_millis(200);
+                while !handles.iter().all(|h| h.is_finished()) {
+                    thread::sleep(progress_interval);
+                    callback(DirectoryStatsProgress {
+                        path: normalized.clone(),
+                        entries_checked: entries_checked.load(AtomicOrdering::Relaxed),
+                        entries_queued: queue.lock().unwrap().len() as u64,
+                    });
+                }
+            }
+
+            for handle in handles {
+                if let Err(panic) = handle.join() {
+                    tracing::warn!(
+                        path = %normalized,
+                        "compute_directory_stats_parallel 的 worker 线程 panic: {:?}",
+                        panic
+                    );
+                }
+            }
+        });
+
+        if let Some(callback) = &progress_callback {
+            callback(DirectoryStatsProgress {
+                path: normalized.clone(),
+                entries_checked: entries_checked.load(AtomicOrdering::Relaxed),
+                entries_queued: 0,
+            });
+        }
+
+        for failure in read_failures.into_inner().unwrap() {
+            tracing::warn!(path = %normalized, error = %failure, "compute_directory_stats_parallel 读取目录失败，已跳过");
+        }
+
+        Ok(DirectoryStats {
+            file_count: file_count.into_inner(),
+            dir_count: dir_count.into_inner(),
+            total_size: total_size.into_inner(),
+            symlink_issues: vec![],
         })
     }
 
+    /// 判断删除前重新 `lstat` 得到的类型是否仍与收集阶段记录的类型一致
+    ///
+    /// `expected_is_dir` 为收集阶段记下的类型（true = 真实目录，false = 文件或符号链接）；
+    /// 只要最新状态的「是否为未跟随链接的真实目录」和预期不一致，就认为发生了调包
+    fn is_delete_type_unchanged(fresh: &ssh2::FileStat, expected_is_dir: bool) -> bool {
+        let is_symlink = fresh
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+        let is_dir = !is_symlink && fresh.is_dir();
+        is_dir == expected_is_dir
+    }
+
+    /// 删除某个目录前，确认从根到它的整条链路没有任何一段被换成符号链接
+    ///
+    /// 做法：用 [`Self::canonicalize`]（跟随符号链接逐段解析，优先走服务器 `realpath`）
+    /// 重新解析一遍 `path`，只要结果和我们自己基于字符串算出的规范化路径不一致，就说明
+    /// 链路上有组件被调包——可能是 `path` 自己，也可能是它的某个祖先目录，都视为不安全，
+    /// 调用方应当放弃对这个路径（及其之下的所有条目）做删除
+    fn path_chain_unchanged(sftp: &Sftp, path: &str) -> bool {
+        match Self::canonicalize(sftp, path) {
+            Ok(resolved) => resolved == Self::normalize_path(path),
+            Err(_) => false,
+        }
+    }
+
+    /// 某个 `lstat` 结果是否代表一个应当继续递归进入的真实目录
+    ///
+    /// 符号链接恒返回 false（即使目标是目录）：`delete_recursive` 的收集阶段据此把
+    /// 符号链接始终当作叶子只删除链接本身、绝不压回待遍历的栈，这正是它不受符号链接
+    /// 循环影响的原因——栈里永远不会出现同一个目录两次
+    fn is_recursable_dir(lstat: &ssh2::FileStat) -> bool {
+        let is_symlink = lstat
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+        !is_symlink && lstat.is_dir()
+    }
+
     /// 递归删除目录及其所有内容
     ///
     /// 策略：深度优先遍历，先删除文件和子目录，最后删除目录本身
-    /// 符号链接：只删除链接本身，不跟随
+    /// 符号链接：只删除链接本身，不跟随。收集阶段用 [`Self::is_recursable_dir`]
+    /// 判断是否继续下探，符号链接恒不满足条件，因此永远不会被压回遍历栈——
+    /// 天然不受符号链接循环影响，无需额外的祖先路径/跳转预算检测
     /// 错误处理：记录失败项但继续删除其他文件
+    /// 取消：若 `cancel_flag` 在处理某一项前已被置位，立即停止并返回 `cancelled: true`
+    /// 的部分结果，`remaining_paths` 记录尚未删除的项
+    ///
+    /// TOCTOU 防护（对应 `std::fs::remove_dir_all` 的 CVE-2022-21658）：收集阶段记录的
+    /// 类型到真正调用 `unlink`/`rmdir` 之间存在时间窗口，攻击者可能把已收集的目录替换
+    /// 成指向树外的符号链接。因此每个条目在删除前都会重新 `lstat` 一次，类型若与收集时
+    /// 不符（目录变链接/文件，或反过来）就放弃这一项、记为 [`DeleteFailure`]，绝不会对
+    /// 一个现在解析出来是符号链接的路径调用 `rmdir`
+    ///
+    /// 光靠叶子节点的 `lstat` 还不够：所有文件会在任何目录被删除之前先被删光（见下方
+    /// 两个循环——文件循环整体跑完，目录循环才开始），这段时间里子树内尚未轮到删除的
+    /// 祖先目录仍然原封不动地躺在那里，足够攻击者把其中某一层换成指向树外的符号链接。
+    /// 叶子自身的 `lstat` 看不到祖先被换的这件事，顺着被调包的祖先 `unlink`/`rmdir`
+    /// 叶子路径时照样会逃出规范化后的根目录。因此每个条目删除前还会额外用
+    /// [`Self::path_chain_unchanged`] 把从根到该条目的整条链路重新解析一遍（文件检查
+    /// 其所在目录，目录检查自身），链路上任何一段被换成符号链接都会被判定为不安全
+    ///
+    /// `exclude` 非空时，收集阶段匹配到的条目（按相对 `path` 的路径）既不会被删除也
+    /// 不会展开，相当于把这棵子树从待删除集合里连根摘掉——可以用来删除构建目录的同时
+    /// 保留 `*.lock` 这类文件
     pub fn delete_recursive(
         sftp: &Sftp,
         path: &str,
+        exclude: Option<&ExcludeMatcher>,
         progress_callback: Option<DeleteProgressCallback>,
+        cancel_flag: Option<Arc<AtomicBool>>,
     ) -> AppResult<RecursiveDeleteResult> {
         let normalized = Self::normalize_path(path);
         Self::validate_delete_path(&normalized)?;
@@ -426,6 +1213,8 @@ impl SftpService {
                 deleted_files: 1,
                 deleted_dirs: 0,
                 failures: vec![],
+                cancelled: false,
+                remaining_paths: vec![],
             });
         }
 
@@ -436,6 +1225,8 @@ impl SftpService {
                 deleted_files: 1,
                 deleted_dirs: 0,
                 failures: vec![],
+                cancelled: false,
+                remaining_paths: vec![],
             });
         }
 
@@ -472,18 +1263,25 @@ impl SftpService {
                     }
                 };
 
-                // 检查是否是符号链接
-                let is_symlink = entry_lstat
-                    .perm
-                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
-                    .unwrap_or(false);
+                if let Some(matcher) = exclude {
+                    let relative = full_path
+                        .strip_prefix(&normalized)
+                        .map(|r| r.trim_start_matches('/'))
+                        .unwrap_or(&full_path);
+                    if matcher.is_excluded(relative, entry_lstat.is_dir()) {
+                        tracing::debug!(path = %full_path, "匹配排除模式，跳过删除");
+                        continue;
+                    }
+                }
 
-                if is_symlink || !entry_lstat.is_dir() {
-                    files.push(full_path);
-                } else {
+                if Self::is_recursable_dir(&entry_lstat) {
                     // 目录：先递归进入，稍后删除
                     stack.push(full_path.clone());
                     dirs.push(full_path);
+                } else {
+                    // 文件或符号链接：只删除链接本身，绝不跟随——这正是 delete_recursive
+                    // 天然不受符号链接循环影响的原因：符号链接永远不会被压回 stack
+                    files.push(full_path);
                 }
             }
         }
@@ -505,22 +1303,72 @@ impl SftpService {
         let mut last_progress_time = std::time::Instant::now();
         let progress_interval = std::time::Duration::from_millis(200);
 
+        // 文件所在目录的链路校验结果缓存：同一目录下往往有多个文件，避免对同一个
+        // 祖先目录重复调用 path_chain_unchanged（底层是一次 realpath/逐段 lstat）
+        let mut parent_chain_cache: HashMap<String, bool> = HashMap::new();
+
         // 先删除文件和符号链接
-        for file_path in files {
-            let file_obj = Path::new(&file_path);
-            match sftp.unlink(file_obj) {
-                Ok(()) => {
-                    deleted_files += 1;
-                    deleted_count += 1;
-                    tracing::debug!(path = %file_path, "删除文件成功");
-                }
-                Err(e) => {
-                    tracing::warn!(path = %file_path, error = %e, "删除文件失败");
-                    failures.push(DeleteFailure {
-                        path: file_path.clone(),
-                        error: e.message().to_string(),
-                    });
-                    deleted_count += 1; // 仍然计入进度
+        for idx in 0..files.len() {
+            if cancel_flag
+                .as_ref()
+                .is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+            {
+                let mut remaining_paths = files[idx..].to_vec();
+                remaining_paths.extend(dirs);
+                tracing::info!(path = %normalized, remaining = remaining_paths.len(), "递归删除已取消");
+                return Ok(RecursiveDeleteResult {
+                    deleted_files,
+                    deleted_dirs,
+                    failures,
+                    cancelled: true,
+                    remaining_paths,
+                });
+            }
+
+            let file_path = &files[idx];
+            let file_obj = Path::new(file_path);
+
+            // 收集阶段到现在可能已经过去一段时间，重新 lstat 一次确认类型没有被调包
+            // （比如原本是文件/链接，现在变成了真实目录），避免对调包后的路径做出
+            // 预期之外的删除行为
+            let leaf_type_changed = match sftp.lstat(file_obj) {
+                Ok(fresh) => !Self::is_delete_type_unchanged(&fresh, false),
+                Err(_) => false, // 已不存在或无法访问，交给 unlink 走正常错误路径
+            };
+
+            // 叶子之外，所在目录本身也可能在这轮删除开始后才被换成符号链接——这个
+            // 目录要等到下面的目录循环才会被删除，此刻依然原封不动地留在原地
+            let parent_unchanged = match Path::new(file_path).parent().and_then(|p| p.to_str()) {
+                Some(parent) if !parent.is_empty() => *parent_chain_cache
+                    .entry(parent.to_string())
+                    .or_insert_with(|| Self::path_chain_unchanged(sftp, parent)),
+                _ => true,
+            };
+
+            let type_changed = leaf_type_changed || !parent_unchanged;
+
+            if type_changed {
+                tracing::warn!(path = %file_path, "删除前类型已变化或所在目录链路被调包，拒绝删除");
+                failures.push(DeleteFailure {
+                    path: file_path.clone(),
+                    error: "删除前类型已变化，或其所在目录已被替换为符号链接，已拒绝删除".to_string(),
+                });
+                deleted_count += 1; // 仍然计入进度
+            } else {
+                match sftp.unlink(file_obj) {
+                    Ok(()) => {
+                        deleted_files += 1;
+                        deleted_count += 1;
+                        tracing::debug!(path = %file_path, "删除文件成功");
+                    }
+                    Err(e) => {
+                        tracing::warn!(path = %file_path, error = %e, "删除文件失败");
+                        failures.push(DeleteFailure {
+                            path: file_path.clone(),
+                            error: e.message().to_string(),
+                        });
+                        deleted_count += 1; // 仍然计入进度
+                    }
                 }
             }
 
@@ -531,7 +1379,7 @@ impl SftpService {
                         path: normalized.clone(),
                         deleted_count,
                         total_count,
-                        current_path: file_path,
+                        current_path: file_path.clone(),
                     });
                     last_progress_time = std::time::Instant::now();
                 }
@@ -539,21 +1387,59 @@ impl SftpService {
         }
 
         // 再删除目录（从最深的开始）
-        for dir_path in dirs {
-            let dir_obj = Path::new(&dir_path);
-            match sftp.rmdir(dir_obj) {
-                Ok(()) => {
-                    deleted_dirs += 1;
-                    deleted_count += 1;
-                    tracing::debug!(path = %dir_path, "删除目录成功");
+        for idx in 0..dirs.len() {
+            if cancel_flag
+                .as_ref()
+                .is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+            {
+                let remaining_paths = dirs[idx..].to_vec();
+                tracing::info!(path = %normalized, remaining = remaining_paths.len(), "递归删除已取消");
+                return Ok(RecursiveDeleteResult {
+                    deleted_files,
+                    deleted_dirs,
+                    failures,
+                    cancelled: true,
+                    remaining_paths,
+                });
+            }
+
+            let dir_path = &dirs[idx];
+            let dir_obj = Path::new(dir_path);
+
+            // 同样地，rmdir 前重新确认它仍然是一个真实目录：一旦它现在解析为符号链接
+            // （或根本不再是目录），绝不调用 rmdir，否则可能顺着链接删到预期目录树之外。
+            // 光看叶子自己还不够——它的某个祖先目录也可能在这轮删除期间被换成符号
+            // 链接，因此还要用 path_chain_unchanged 把整条链路重新校验一遍
+            let type_changed = match sftp.lstat(dir_obj) {
+                Ok(fresh) => {
+                    !Self::is_delete_type_unchanged(&fresh, true)
+                        || !Self::path_chain_unchanged(sftp, dir_path)
                 }
-                Err(e) => {
-                    tracing::warn!(path = %dir_path, error = %e, "删除目录失败");
-                    failures.push(DeleteFailure {
-                        path: dir_path.clone(),
-                        error: e.message().to_string(),
-                    });
-                    deleted_count += 1; // 仍然计入进度
+                Err(_) => false, // 已不存在或无法访问，交给 rmdir 走正常错误路径
+            };
+
+            if type_changed {
+                tracing::warn!(path = %dir_path, "删除前类型已变化或链路被调包（疑似被替换为符号链接），拒绝删除");
+                failures.push(DeleteFailure {
+                    path: dir_path.clone(),
+                    error: "删除前类型已变化，疑似被替换为符号链接，已拒绝删除".to_string(),
+                });
+                deleted_count += 1; // 仍然计入进度
+            } else {
+                match sftp.rmdir(dir_obj) {
+                    Ok(()) => {
+                        deleted_dirs += 1;
+                        deleted_count += 1;
+                        tracing::debug!(path = %dir_path, "删除目录成功");
+                    }
+                    Err(e) => {
+                        tracing::warn!(path = %dir_path, error = %e, "删除目录失败");
+                        failures.push(DeleteFailure {
+                            path: dir_path.clone(),
+                            error: e.message().to_string(),
+                        });
+                        deleted_count += 1; // 仍然计入进度
+                    }
                 }
             }
 
@@ -564,7 +1450,7 @@ impl SftpService {
                         path: normalized.clone(),
                         deleted_count,
                         total_count,
-                        current_path: dir_path,
+                        current_path: dir_path.clone(),
                     });
                     last_progress_time = std::time::Instant::now();
                 }
@@ -585,6 +1471,8 @@ impl SftpService {
             deleted_files,
             deleted_dirs,
             failures,
+            cancelled: false,
+            remaining_paths: vec![],
         })
     }
 
@@ -670,178 +1558,1617 @@ impl SftpService {
         Ok(())
     }
 
-    /// 递归列出目录下的所有文件
+    /// 递归修改目录树下所有条目的权限，支持 `u+rwX,go-w` 风格的符号权限表达式
     ///
-    /// 返回 (remote_path, relative_path) 元组列表，仅包含文件（不含目录）
-    /// relative_path 相对于输入的 base_path
-    /// 注意：会跳过符号链接以避免无限循环
-    pub fn list_dir_recursive(sftp: &Sftp, base_path: &str) -> AppResult<Vec<(String, String)>> {
+    /// 每个条目的最终数值 mode 由该条目自身当前的 `perm`（`lstat` 获得）结合符号表达式
+    /// 解析得到，因此 `+`/`-`/`X` 都是相对每个条目各自现状计算的，不是整棵树套用同一个
+    /// 数值。跳过符号链接本身（对链接 chmod 实际上改的是目标，容易产生歧义）；`target`
+    /// 可选地把处理范围收窄到只改目录或只改文件。单项失败记录到 `failures`，不中断
+    /// 整体遍历（与 `delete_recursive`/`copy_recursive` 一致）
+    pub fn chmod_recursive(
+        sftp: &Sftp,
+        path: &str,
+        symbolic_mode: &str,
+        target: ChmodTarget,
+    ) -> AppResult<ChmodResult> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let clauses = parse_symbolic_clauses(symbolic_mode)?;
+
+        let mut success_count: usize = 0;
+        let mut failures: Vec<ChmodFailure> = Vec::new();
+        let mut stack = vec![normalized];
+
+        while let Some(current_path) = stack.pop() {
+            let current_obj = Path::new(&current_path);
+
+            let lstat = match sftp.lstat(current_obj) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!(path = %current_path, error = %e, "无法获取文件信息");
+                    failures.push(ChmodFailure {
+                        path: current_path,
+                        error: e.message().to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let is_symlink = lstat
+                .perm
+                .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                .unwrap_or(false);
+            let is_dir = !is_symlink && lstat.is_dir();
+
+            if is_symlink {
+                tracing::debug!(path = %current_path, "跳过符号链接");
+                continue;
+            }
+
+            let should_process = match target {
+                ChmodTarget::All => true,
+                ChmodTarget::DirsOnly => is_dir,
+                ChmodTarget::FilesOnly => !is_dir,
+            };
+
+            if should_process {
+                let current_perm = lstat.perm.unwrap_or(0o644) & 0o7777;
+                let new_perm = apply_symbolic_clauses(&clauses, current_perm, is_dir);
+                match Self::chmod(sftp, &current_path, new_perm) {
+                    Ok(()) => success_count += 1,
+                    Err(e) => failures.push(ChmodFailure {
+                        path: current_path.clone(),
+                        error: e.message.clone(),
+                    }),
+                }
+            }
+
+            if is_dir {
+                let entries = match sftp.readdir(current_obj) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        tracing::warn!(path = %current_path, error = %e, "无法读取目录");
+                        continue;
+                    }
+                };
+
+                for (path_buf, _) in entries {
+                    let file_name = path_buf.file_name().and_then(|n| n.to_str());
+                    if matches!(file_name, None | Some(".") | Some("..")) {
+                        continue;
+                    }
+                    stack.push(path_buf.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        Ok(ChmodResult {
+            success_count,
+            failures,
+        })
+    }
+
+    /// 递归列出目录下的所有文件
+    ///
+    /// 返回 (remote_path, relative_path) 元组列表，仅包含文件（不含目录），以及遍历中
+    /// 发现的符号链接问题列表。`mode` 为 [`SymlinkMode::Skip`] 时符号链接被直接跳过
+    /// （旧行为）；为 `Follow` 时解析链接目标当作目标本身列入结果，循环/断链记录进
+    /// 第二个返回值而不是静默跳过
+    pub fn list_dir_recursive(
+        sftp: &Sftp,
+        base_path: &str,
+        mode: SymlinkMode,
+    ) -> AppResult<(Vec<(String, String)>, Vec<SymlinkIssue>)> {
+        let normalized = Self::normalize_path(base_path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
+
+        // 确认是目录（使用 lstat 检查是否为符号链接）
+        let lstat = sftp
+            .lstat(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+
+        // 检查是否是符号链接
+        let is_symlink = lstat
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+        if is_symlink {
+            return Err(AppError::invalid_argument("不支持下载符号链接"));
+        }
+
+        let stat = sftp
+            .stat(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+        if !stat.is_dir() {
+            return Err(AppError::invalid_argument(format!(
+                "指定的路径是文件而非目录: {}",
+                normalized
+            )));
+        }
+
+        let mut files = Vec::new();
+        let mut symlink_issues: Vec<SymlinkIssue> = vec![];
+        let mut stack = vec![(normalized.clone(), Rc::new(Vec::<String>::new()))];
+
+        while let Some((current_path, ancestors)) = stack.pop() {
+            let current_obj = Path::new(&current_path);
+
+            let entries = match sftp.readdir(current_obj) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!(path = %current_path, error = %e, "无法读取目录，跳过");
+                    continue;
+                }
+            };
+
+            for (path_buf, _) in entries {
+                // 过滤 . 和 ..
+                let file_name = path_buf.file_name().and_then(|n| n.to_str());
+                if matches!(file_name, None | Some(".") | Some("..")) {
+                    continue;
+                }
+
+                let full_path = path_buf.to_string_lossy().to_string();
+
+                // 使用 lstat 检查每个条目（避免跟随符号链接）
+                let lstat = match sftp.lstat(&path_buf) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!(path = %full_path, error = %e, "无法获取文件信息，跳过");
+                        continue;
+                    }
+                };
+
+                let is_symlink = lstat
+                    .perm
+                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                    .unwrap_or(false);
+
+                if !is_symlink {
+                    if lstat.is_dir() {
+                        let mut next_ancestors = (*ancestors).clone();
+                        next_ancestors.push(full_path.clone());
+                        stack.push((full_path, Rc::new(next_ancestors)));
+                    } else {
+                        Self::push_relative_file(&mut files, &normalized, full_path);
+                    }
+                    continue;
+                }
+
+                if mode == SymlinkMode::Skip {
+                    tracing::debug!(path = %full_path, "跳过符号链接");
+                    continue;
+                }
+
+                let target = sftp
+                    .readlink(&path_buf)
+                    .ok()
+                    .map(|p| p.to_string_lossy().to_string());
+
+                match resolve_symlink_chain(sftp, &path_buf, MAX_SYMLINK_JUMPS)? {
+                    SymlinkResolution::Broken => {
+                        symlink_issues.push(SymlinkIssue {
+                            path: full_path,
+                            target,
+                            kind: SymlinkIssueKind::Broken,
+                        });
+                    }
+                    SymlinkResolution::Circular => {
+                        symlink_issues.push(SymlinkIssue {
+                            path: full_path,
+                            target,
+                            kind: SymlinkIssueKind::Circular,
+                        });
+                    }
+                    SymlinkResolution::Resolved(resolved_path, resolved_stat) => {
+                        let canonical = resolved_path.to_string_lossy().to_string();
+
+                        if resolved_stat.is_dir() {
+                            if ancestors.iter().any(|a| a == &canonical) {
+                                symlink_issues.push(SymlinkIssue {
+                                    path: full_path,
+                                    target,
+                                    kind: SymlinkIssueKind::Circular,
+                                });
+                                continue;
+                            }
+                            let mut next_ancestors = (*ancestors).clone();
+                            next_ancestors.push(canonical.clone());
+                            stack.push((canonical, Rc::new(next_ancestors)));
+                        } else {
+                            Self::push_relative_file(&mut files, &normalized, full_path);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((files, symlink_issues))
+    }
+
+    /// 把 `full_path` 相对于 `base` 的部分记入 `files`；前缀不匹配（如 Follow 模式下
+    /// 符号链接指向了 base 之外的位置）时记录日志并丢弃，不中断整个遍历
+    fn push_relative_file(files: &mut Vec<(String, String)>, base: &str, full_path: String) {
+        let relative = match full_path.strip_prefix(base) {
+            Some(rel) => rel.trim_start_matches('/').to_string(),
+            None => {
+                tracing::error!(full_path = %full_path, base = %base, "路径前缀不匹配，跳过");
+                return;
+            }
+        };
+        files.push((full_path, relative));
+    }
+
+    /// 递归列出目录下的所有文件，附带大小与 mtime（用于目录同步时的差异比较）
+    ///
+    /// 返回 (relative_path, size, mtime_secs) 元组列表，relative_path 相对于 base_path，
+    /// 使用 `/` 分隔；跳过符号链接
+    pub fn list_dir_recursive_with_meta(
+        sftp: &Sftp,
+        base_path: &str,
+    ) -> AppResult<Vec<(String, u64, i64)>> {
         let normalized = Self::normalize_path(base_path);
         Self::validate_path(&normalized)?;
 
         let path_obj = Path::new(&normalized);
-
-        // 确认是目录（使用 lstat 检查是否为符号链接）
+        let stat = sftp
+            .stat(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+        if !stat.is_dir() {
+            return Err(AppError::invalid_argument(format!(
+                "指定的路径是文件而非目录: {}",
+                normalized
+            )));
+        }
+
+        let mut files = Vec::new();
+        let mut stack = vec![normalized.clone()];
+
+        while let Some(current_path) = stack.pop() {
+            let current_obj = Path::new(&current_path);
+
+            let entries = match sftp.readdir(current_obj) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!(path = %current_path, error = %e, "无法读取目录，跳过");
+                    continue;
+                }
+            };
+
+            for (path_buf, file_stat) in entries {
+                let file_name = path_buf.file_name().and_then(|n| n.to_str());
+                if matches!(file_name, None | Some(".") | Some("..")) {
+                    continue;
+                }
+
+                let full_path = path_buf.to_string_lossy().to_string();
+
+                let is_symlink = file_stat
+                    .perm
+                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                    .unwrap_or(false);
+                if is_symlink {
+                    tracing::debug!(path = %full_path, "跳过符号链接");
+                    continue;
+                }
+
+                if file_stat.is_dir() {
+                    stack.push(full_path);
+                } else {
+                    let relative = match full_path.strip_prefix(&normalized) {
+                        Some(rel) => rel.trim_start_matches('/').to_string(),
+                        None => {
+                            tracing::error!(
+                                full_path = %full_path,
+                                base = %normalized,
+                                "路径前缀不匹配，跳过"
+                            );
+                            continue;
+                        }
+                    };
+                    let size = file_stat.size.unwrap_or(0);
+                    let mtime = file_stat.mtime.map(|t| t as i64).unwrap_or(0);
+                    files.push((relative, size, mtime));
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// 查找子树下内容完全相同的重复文件，两阶段确认：先按 `size` 分桶排除显然不同的
+    /// 文件，再对剩下的候选逐个流式读取内容算 SHA-256，按摘要再分桶——同大小不代表
+    /// 同内容，必须读完整个文件才能下结论
+    ///
+    /// 跳过符号链接（与 [`Self::list_dir_recursive_with_meta`] 一致）。进度回调只在
+    /// 阶段二（哈希计算，耗时的部分）按固定时间间隔触发，阶段一的分桶只是一次
+    /// 目录遍历，不单独汇报进度
+    ///
+    /// 返回值中每个内层 `Vec` 都是一组确认重复的文件（长度 >= 2），顺序不保证
+    pub fn find_duplicate_files(
+        sftp: &Sftp,
+        path: &str,
+        progress_callback: Option<DuplicateScanProgressCallback>,
+    ) -> AppResult<Vec<Vec<FileEntry>>> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let entries = Self::collect_file_entries_recursive(sftp, &normalized)?;
+
+        let mut size_buckets: HashMap<u64, Vec<FileEntry>> = HashMap::new();
+        for entry in entries {
+            size_buckets.entry(entry.size.unwrap_or(0)).or_default().push(entry);
+        }
+
+        let candidates: Vec<FileEntry> = size_buckets
+            .into_values()
+            .filter(|bucket| bucket.len() > 1)
+            .flatten()
+            .collect();
+        let total_candidates = candidates.len() as u64;
+
+        let mut hash_buckets: HashMap<String, Vec<FileEntry>> = HashMap::new();
+        let mut files_hashed: u64 = 0;
+        let mut last_progress_time = std::time::Instant::now();
+        let progress_interval = Duration::from_millis(200);
+
+        for entry in candidates {
+            match Self::hash_remote_file(sftp, &entry.path) {
+                Ok(digest) => hash_buckets.entry(digest).or_default().push(entry),
+                Err(e) => {
+                    tracing::warn!(path = %entry.path, error = %e.message, "计算远程文件哈希失败，跳过");
+                }
+            }
+            files_hashed += 1;
+
+            if let Some(ref callback) = progress_callback {
+                if last_progress_time.elapsed() >= progress_interval {
+                    callback(DuplicateScanProgress {
+                        path: normalized.clone(),
+                        files_hashed,
+                        total_candidates,
+                    });
+                    last_progress_time = std::time::Instant::now();
+                }
+            }
+        }
+
+        if let Some(ref callback) = progress_callback {
+            callback(DuplicateScanProgress {
+                path: normalized.clone(),
+                files_hashed,
+                total_candidates,
+            });
+        }
+
+        Ok(hash_buckets
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect())
+    }
+
+    /// 递归收集子树下所有普通文件的 [`FileEntry`]（跳过符号链接），供
+    /// [`Self::find_duplicate_files`] 按 size 分桶用
+    fn collect_file_entries_recursive(sftp: &Sftp, base_path: &str) -> AppResult<Vec<FileEntry>> {
+        let stat = sftp
+            .stat(Path::new(base_path))
+            .map_err(|e| map_sftp_error(e, base_path))?;
+        if !stat.is_dir() {
+            return Err(AppError::invalid_argument(format!(
+                "指定的路径是文件而非目录: {}",
+                base_path
+            )));
+        }
+
+        let mut files = Vec::new();
+        let mut stack = vec![base_path.to_string()];
+
+        while let Some(current_path) = stack.pop() {
+            let entries = match sftp.readdir(Path::new(&current_path)) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!(path = %current_path, error = %e, "无法读取目录，跳过");
+                    continue;
+                }
+            };
+
+            for (path_buf, file_stat) in entries {
+                let file_name = path_buf.file_name().and_then(|n| n.to_str());
+                if matches!(file_name, None | Some(".") | Some("..")) {
+                    continue;
+                }
+                let name = file_name.unwrap_or_default().to_string();
+                let full_path = path_buf.to_string_lossy().to_string();
+
+                let is_symlink = file_stat
+                    .perm
+                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                    .unwrap_or(false);
+                if is_symlink {
+                    tracing::debug!(path = %full_path, "跳过符号链接");
+                    continue;
+                }
+
+                if file_stat.is_dir() {
+                    stack.push(full_path);
+                } else {
+                    files.push(Self::file_stat_to_entry(name, full_path, file_stat, false, None));
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// 流式读取远程文件全部内容并计算 SHA-256 摘要（十六进制）
+    fn hash_remote_file(sftp: &Sftp, path: &str) -> AppResult<String> {
+        let mut hasher = Sha256::new();
+        let mut offset: u64 = 0;
+
+        loop {
+            let chunk = Self::read_file_chunk(sftp, path, offset, SFTP_CHUNK_SIZE)?;
+            if chunk.is_empty() {
+                break;
+            }
+            let chunk_len = chunk.len() as u64;
+            hasher.update(&chunk);
+            offset += chunk_len;
+            if chunk_len < SFTP_CHUNK_SIZE {
+                break;
+            }
+        }
+
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// 获取文件/目录信息
+    pub fn stat(sftp: &Sftp, path: &str) -> AppResult<FileEntry> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
+
+        // 先用 lstat 判断路径本身是否是符号链接
+        let lstat = sftp
+            .lstat(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+        let is_symlink = lstat
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+
+        // 提取文件名
+        let name = path_obj
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        if !is_symlink {
+            return Ok(Self::file_stat_to_entry(name, normalized, lstat, false, None));
+        }
+
+        // 符号链接：readlink 获取原始目标文本，再跟随解析目标的元数据
+        // （悬空链接时退回链接自身属性，与 list_dir 一致）
+        let symlink_target = sftp
+            .readlink(path_obj)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+        let resolved_stat = sftp.stat(path_obj).unwrap_or(lstat);
+
+        Ok(Self::file_stat_to_entry(
+            name,
+            normalized,
+            resolved_stat,
+            true,
+            symlink_target,
+        ))
+    }
+
+    /// 读取符号链接指向的原始目标路径（不跟随、不解析）
+    pub fn readlink(sftp: &Sftp, path: &str) -> AppResult<String> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
+        let target = sftp
+            .readlink(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+
+        Ok(target.to_string_lossy().to_string())
+    }
+
+    /// 创建符号链接，`link_path` 指向 `target`
+    ///
+    /// `target` 不做路径规范化/存在性校验：符号链接允许指向尚不存在的路径，
+    /// 且可以是相对路径，由远程文件系统在解析时处理
+    pub fn symlink(sftp: &Sftp, target: &str, link_path: &str) -> AppResult<()> {
+        let link_normalized = Self::normalize_path(link_path);
+        Self::validate_path(&link_normalized)?;
+
+        sftp.symlink(Path::new(&link_normalized), Path::new(target))
+            .map_err(AppError::from)?;
+
+        Ok(())
+    }
+
+    /// 从远程文件指定偏移读取至多 `length` 字节
+    ///
+    /// 返回的 `Vec` 长度可能小于 `length`（已到达文件末尾）。仅负责单次读取，
+    /// 不在内部分块；调用方如需分块传输以汇报进度，应自行多次调用并递增 offset
+    pub fn read_file_chunk(sftp: &Sftp, path: &str, offset: u64, length: u64) -> AppResult<Vec<u8>> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
+
+        let mut file = sftp
+            .open(path_obj)
+            .map_err(|e| map_sftp_error(e, &normalized))?;
+
+        file.seek(SeekFrom::Start(offset)).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("定位远程文件失败: {}", e))
+                .with_retryable(true)
+        })?;
+
+        let mut buf = vec![0u8; length as usize];
+        let mut total_read = 0usize;
+
+        while total_read < buf.len() {
+            let n = file.read(&mut buf[total_read..]).map_err(|e| {
+                AppError::new(ErrorCode::RemoteIoError, format!("读取远程文件失败: {}", e))
+                    .with_retryable(true)
+            })?;
+            if n == 0 {
+                break;
+            }
+            total_read += n;
+        }
+
+        buf.truncate(total_read);
+        Ok(buf)
+    }
+
+    /// 向远程文件指定偏移写入数据
+    ///
+    /// `append` 为 true 时忽略 `offset`，始终写到文件当前末尾（每次调用独立打开文件句柄，
+    /// 因此多次 append 调用之间的先后顺序由调用方保证）；文件不存在时自动创建
+    pub fn write_file_chunk(
+        sftp: &Sftp,
+        path: &str,
+        offset: u64,
+        data: &[u8],
+        append: bool,
+    ) -> AppResult<()> {
+        let normalized = Self::normalize_path(path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
+
+        let mut flags = OpenFlags::WRITE | OpenFlags::CREATE;
+        if append {
+            flags |= OpenFlags::APPEND;
+        }
+
+        let mut file = sftp
+            .open_mode(path_obj, flags, 0o644, OpenType::File)
+            .map_err(|e| {
+                let msg = format!("无法打开远程文件写入: {}", e);
+                if msg.contains("Permission denied") {
+                    AppError::permission_denied("无权限写入远程文件")
+                } else {
+                    AppError::new(ErrorCode::RemoteIoError, msg).with_retryable(true)
+                }
+            })?;
+
+        if !append {
+            file.seek(SeekFrom::Start(offset)).map_err(|e| {
+                AppError::new(ErrorCode::RemoteIoError, format!("定位远程文件失败: {}", e))
+                    .with_retryable(true)
+            })?;
+        }
+
+        file.write_all(data).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("写入远程文件失败: {}", e))
+                .with_retryable(true)
+        })?;
+
+        Ok(())
+    }
+
+    /// 将 ssh2::FileStat 转换为 FileEntry
+    fn file_stat_to_entry(
+        name: String,
+        path: String,
+        stat: ssh2::FileStat,
+        is_symlink: bool,
+        symlink_target: Option<String>,
+    ) -> FileEntry {
+        FileEntry {
+            name,
+            path,
+            is_dir: stat.is_dir(),
+            size: stat.size,
+            mtime: stat.mtime.map(|t| t as i64),
+            mode: stat.perm,
+            is_symlink,
+            symlink_target,
+        }
+    }
+
+    /// 排序文件条目（目录优先）
+    fn sort_entries(entries: &mut [FileEntry], sort: &SortSpec) {
+        entries.sort_by(|a, b| {
+            // 目录优先
+            match (a.is_dir, b.is_dir) {
+                (true, false) => return Ordering::Less,
+                (false, true) => return Ordering::Greater,
+                _ => {}
+            }
+
+            // 按字段排序
+            let ordering = match sort.field {
+                SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortField::Size => {
+                    let a_size = a.size.unwrap_or(0);
+                    let b_size = b.size.unwrap_or(0);
+                    a_size.cmp(&b_size)
+                }
+                SortField::Mtime => {
+                    let a_time = a.mtime.unwrap_or(0);
+                    let b_time = b.mtime.unwrap_or(0);
+                    a_time.cmp(&b_time)
+                }
+            };
+
+            // 应用升降序
+            match sort.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+    }
+
+    /// 复制单个文件：通过分块读写循环在远程侧完成流式拷贝，随后应用源文件的权限
+    ///
+    /// 源文件为空时，分块读取循环不会产生任何写入，这里显式创建空目标文件
+    fn copy_single_file(sftp: &Sftp, src: &str, dst: &str, mode: Option<u32>) -> AppResult<()> {
+        let mut offset: u64 = 0;
+        let mut wrote_any = false;
+
+        loop {
+            let chunk = Self::read_file_chunk(sftp, src, offset, SFTP_CHUNK_SIZE)?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            Self::write_file_chunk(sftp, dst, offset, &chunk, false)?;
+            wrote_any = true;
+
+            let chunk_len = chunk.len() as u64;
+            offset += chunk_len;
+            if chunk_len < SFTP_CHUNK_SIZE {
+                break;
+            }
+        }
+
+        if !wrote_any {
+            sftp.create(Path::new(dst)).map_err(AppError::from)?;
+        }
+
+        if let Some(mode) = mode {
+            Self::chmod(sftp, dst, mode & 0o777)?;
+        }
+
+        Ok(())
+    }
+
+    /// 递归复制文件或目录到新的远程路径
+    ///
+    /// 策略：先用 [`Self::get_directory_stats`] 统计源目录规模用于进度汇报的 total_count，
+    /// 再深度优先遍历源树：逐层 `mkdir` 重建目录结构，文件通过 [`Self::copy_single_file`]
+    /// 分块读写完成拷贝，每个文件/目录复制后应用源的 Unix 权限。
+    /// 错误处理：单项失败记录到 `failures` 但不中断整体复制（与 `delete_recursive` 一致）
+    /// 取消：与 `delete_recursive` 同样的 `cancel_flag` 约定；复制是按目录逐层展开的，
+    /// 取消时 `remaining_paths` 只能给出尚未进入的源目录（尚在遍历栈中），而非逐个文件
+    pub fn copy_recursive(
+        sftp: &Sftp,
+        src_path: &str,
+        dst_path: &str,
+        progress_callback: Option<CopyProgressCallback>,
+        cancel_flag: Option<Arc<AtomicBool>>,
+    ) -> AppResult<RecursiveCopyResult> {
+        let src_normalized = Self::normalize_path(src_path);
+        let dst_normalized = Self::normalize_path(dst_path);
+        Self::validate_path(&src_normalized)?;
+        Self::validate_path(&dst_normalized)?;
+
+        let src_obj = Path::new(&src_normalized);
+
+        let src_lstat = sftp
+            .lstat(src_obj)
+            .map_err(|e| map_sftp_error(e, &src_normalized))?;
+
+        let is_symlink = src_lstat
+            .perm
+            .map(|mode| (mode & S_IFMT) == S_IFLNK)
+            .unwrap_or(false);
+        if is_symlink {
+            return Err(AppError::invalid_argument("不支持复制符号链接"));
+        }
+
+        if sftp.stat(Path::new(&dst_normalized)).is_ok() {
+            return Err(AppError::already_exists(format!(
+                "目标路径已存在: {}",
+                dst_normalized
+            )));
+        }
+
+        if !src_lstat.is_dir() {
+            Self::copy_single_file(sftp, &src_normalized, &dst_normalized, src_lstat.perm)?;
+            return Ok(RecursiveCopyResult {
+                copied_files: 1,
+                copied_dirs: 0,
+                failures: vec![],
+                cancelled: false,
+                remaining_paths: vec![],
+            });
+        }
+
+        // 统计总项数（含根目录自身）用于进度汇报
+        let stats = Self::get_directory_stats(sftp, &src_normalized, SymlinkMode::Skip, None)?;
+        let total_count = stats.file_count + stats.dir_count + 1;
+
+        let mut copied_count: u64 = 0;
+        let mut copied_files: u64 = 0;
+        let mut copied_dirs: u64 = 0;
+        let mut failures: Vec<CopyFailure> = Vec::new();
+
+        let mut last_progress_time = std::time::Instant::now();
+        let progress_interval = std::time::Duration::from_millis(200);
+
+        // 创建根目录
+        match sftp.mkdir(
+            Path::new(&dst_normalized),
+            (src_lstat.perm.unwrap_or(0o755) & 0o777) as i32,
+        ) {
+            Ok(()) => {
+                copied_dirs += 1;
+                copied_count += 1;
+            }
+            Err(e) => {
+                failures.push(CopyFailure {
+                    path: dst_normalized.clone(),
+                    error: e.message().to_string(),
+                });
+                copied_count += 1;
+            }
+        }
+
+        if let Some(ref callback) = progress_callback {
+            callback(CopyProgress {
+                path: src_normalized.clone(),
+                copied_count,
+                total_count,
+                current_path: dst_normalized.clone(),
+            });
+            last_progress_time = std::time::Instant::now();
+        }
+
+        // 深度优先遍历：只有成功创建目标目录的子树才会继续展开，
+        // 避免向尚不存在的父目录下写入文件产生连锁失败
+        let mut stack = vec![(src_normalized.clone(), dst_normalized.clone())];
+
+        while let Some((current_src, current_dst)) = stack.pop() {
+            if cancel_flag
+                .as_ref()
+                .is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+            {
+                let mut remaining_paths = vec![current_src];
+                remaining_paths.extend(stack.into_iter().map(|(src, _)| src));
+                tracing::info!(path = %src_normalized, remaining = remaining_paths.len(), "递归复制已取消");
+                return Ok(RecursiveCopyResult {
+                    copied_files,
+                    copied_dirs,
+                    failures,
+                    cancelled: true,
+                    remaining_paths,
+                });
+            }
+
+            let entries = match sftp.readdir(Path::new(&current_src)) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!(path = %current_src, error = %e, "无法读取目录");
+                    continue;
+                }
+            };
+
+            for (path_buf, _) in entries {
+                let file_name = path_buf.file_name().and_then(|n| n.to_str());
+                if matches!(file_name, None | Some(".") | Some("..")) {
+                    continue;
+                }
+                let name = file_name.unwrap_or_default();
+
+                let entry_src = path_buf.to_string_lossy().to_string();
+                let entry_dst = format!("{}/{}", current_dst.trim_end_matches('/'), name);
+
+                let entry_lstat = match sftp.lstat(&path_buf) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!(path = %entry_src, error = %e, "无法获取文件信息");
+                        continue;
+                    }
+                };
+
+                let is_symlink = entry_lstat
+                    .perm
+                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
+                    .unwrap_or(false);
+                if is_symlink {
+                    tracing::debug!(path = %entry_src, "跳过符号链接");
+                    continue;
+                }
+
+                if entry_lstat.is_dir() {
+                    match sftp.mkdir(
+                        Path::new(&entry_dst),
+                        (entry_lstat.perm.unwrap_or(0o755) & 0o777) as i32,
+                    ) {
+                        Ok(()) => {
+                            copied_dirs += 1;
+                            copied_count += 1;
+                            stack.push((entry_src.clone(), entry_dst.clone()));
+                        }
+                        Err(e) => {
+                            failures.push(CopyFailure {
+                                path: entry_dst.clone(),
+                                error: e.message().to_string(),
+                            });
+                            copied_count += 1;
+                        }
+                    }
+                } else {
+                    match Self::copy_single_file(sftp, &entry_src, &entry_dst, entry_lstat.perm) {
+                        Ok(()) => {
+                            copied_files += 1;
+                            copied_count += 1;
+                        }
+                        Err(e) => {
+                            failures.push(CopyFailure {
+                                path: entry_dst.clone(),
+                                error: e.message,
+                            });
+                            copied_count += 1;
+                        }
+                    }
+                }
+
+                if let Some(ref callback) = progress_callback {
+                    if last_progress_time.elapsed() >= progress_interval {
+                        callback(CopyProgress {
+                            path: src_normalized.clone(),
+                            copied_count,
+                            total_count,
+                            current_path: entry_src,
+                        });
+                        last_progress_time = std::time::Instant::now();
+                    }
+                }
+            }
+        }
+
+        if let Some(ref callback) = progress_callback {
+            callback(CopyProgress {
+                path: src_normalized.clone(),
+                copied_count,
+                total_count,
+                current_path: String::new(),
+            });
+        }
+
+        Ok(RecursiveCopyResult {
+            copied_files,
+            copied_dirs,
+            failures,
+            cancelled: false,
+            remaining_paths: vec![],
+        })
+    }
+
+    /// 确保 `path` 的所有父目录都存在，逐级 `mkdir`（权限 755），已存在则跳过
+    ///
+    /// 与 [`Self::mkdir`] 不同：后者要求父目录已存在，这里是 `mkdir -p` 语义，
+    /// 供 [`Self::sync_recursive`] 在目标树里按需补齐中间目录
+    fn ensure_parent_dirs(sftp: &Sftp, path: &str) -> AppResult<()> {
+        let Some(parent) = Path::new(path).parent() else {
+            return Ok(());
+        };
+        let parent_str = parent.to_string_lossy().to_string();
+        if parent_str.is_empty() || parent_str == "/" {
+            return Ok(());
+        }
+
+        if let Ok(stat) = sftp.stat(parent) {
+            return if stat.is_dir() {
+                Ok(())
+            } else {
+                Err(AppError::invalid_argument(format!(
+                    "路径已存在但不是目录: {}",
+                    parent_str
+                )))
+            };
+        }
+
+        Self::ensure_parent_dirs(sftp, &parent_str)?;
+
+        sftp.mkdir(parent, 0o755).or_else(|e| {
+            if e.code() == ssh2::ErrorCode::SFTP(11) || e.code() == ssh2::ErrorCode::SFTP(4) {
+                Ok(()) // 已被创建（比如并发的上一轮 sync），忽略
+            } else {
+                Err(AppError::from(e))
+            }
+        })
+    }
+
+    /// 将 `src` 镜像到 `dst`，模仿经典的「先扫全量再对比落地」备份流程
+    ///
+    /// `Incremental` 模式：用 [`Self::list_dir_recursive_with_meta`] 分别枚举源、目标树的
+    /// 文件清单（相对路径 + 大小 + mtime），源里大小或 mtime 与目标不一致（或目标没有）的
+    /// 记入 `addition_list`，目标里源已不存在的记入 `deletion_list`；只处理这两份差异，
+    /// 其余文件保持不动（`skipped`）。`Full` 模式不比对，`addition_list` 就是源的全部文件，
+    /// `deletion_list` 为空。
+    /// 目录结构通过 [`Self::ensure_parent_dirs`] 按需补齐，不会预先镜像一份空目录树。
+    /// 与 `delete_recursive`/`copy_recursive` 一致：单项失败记录到 `failures` 但不中断整体
+    /// 同步；`cancel_flag` 在处理每一项前检查一次
+    pub fn sync_recursive(
+        sftp: &Sftp,
+        src_path: &str,
+        dst_path: &str,
+        mode: SyncMode,
+        progress_callback: Option<SyncProgressCallback>,
+        cancel_flag: Option<Arc<AtomicBool>>,
+    ) -> AppResult<SyncReport> {
+        let src_normalized = Self::normalize_path(src_path);
+        let dst_normalized = Self::normalize_path(dst_path);
+        Self::validate_path(&src_normalized)?;
+        Self::validate_path(&dst_normalized)?;
+
+        let src_files = Self::list_dir_recursive_with_meta(sftp, &src_normalized)?;
+
+        let dst_files = if sftp.stat(Path::new(&dst_normalized)).is_ok() {
+            Self::list_dir_recursive_with_meta(sftp, &dst_normalized)?
+        } else {
+            Vec::new()
+        };
+
+        let (addition_list, deletion_list): (Vec<String>, Vec<String>) = match mode {
+            SyncMode::Full => (
+                src_files
+                    .iter()
+                    .map(|(relative, _, _)| relative.clone())
+                    .collect(),
+                Vec::new(),
+            ),
+            SyncMode::Incremental => {
+                let dst_index: HashMap<&str, (u64, i64)> = dst_files
+                    .iter()
+                    .map(|(relative, size, mtime)| (relative.as_str(), (*size, *mtime)))
+                    .collect();
+                let src_index: HashSet<&str> =
+                    src_files.iter().map(|(relative, _, _)| relative.as_str()).collect();
+
+                let additions = src_files
+                    .iter()
+                    .filter(|(relative, size, mtime)| {
+                        match dst_index.get(relative.as_str()) {
+                            Some((dst_size, dst_mtime)) => dst_size != size || dst_mtime != mtime,
+                            None => true,
+                        }
+                    })
+                    .map(|(relative, _, _)| relative.clone())
+                    .collect();
+
+                let deletions = dst_files
+                    .iter()
+                    .filter(|(relative, _, _)| !src_index.contains(relative.as_str()))
+                    .map(|(relative, _, _)| relative.clone())
+                    .collect();
+
+                (additions, deletions)
+            }
+        };
+
+        let total_count = (addition_list.len() + deletion_list.len()) as u64;
+        let skipped = (src_files.len() as u64).saturating_sub(addition_list.len() as u64);
+        let mut processed_count: u64 = 0;
+        let mut copied: u64 = 0;
+        let mut deleted: u64 = 0;
+        let mut failures: Vec<SyncFailure> = Vec::new();
+
+        let mut last_progress_time = std::time::Instant::now();
+        let progress_interval = std::time::Duration::from_millis(200);
+
+        for relative in &addition_list {
+            if cancel_flag
+                .as_ref()
+                .is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+            {
+                tracing::info!(src = %src_normalized, dst = %dst_normalized, "同步已取消");
+                return Ok(SyncReport {
+                    copied,
+                    skipped,
+                    deleted,
+                    failures,
+                });
+            }
+
+            let src_full = format!("{}/{}", src_normalized.trim_end_matches('/'), relative);
+            let dst_full = format!("{}/{}", dst_normalized.trim_end_matches('/'), relative);
+
+            let outcome = Self::ensure_parent_dirs(sftp, &dst_full).and_then(|()| {
+                // 目标已存在同名文件时先删除，避免新内容比旧内容短时残留旧的尾部字节
+                if sftp.stat(Path::new(&dst_full)).is_ok() {
+                    sftp.unlink(Path::new(&dst_full)).map_err(AppError::from)?;
+                }
+                let perm = sftp.lstat(Path::new(&src_full)).ok().and_then(|s| s.perm);
+                Self::copy_single_file(sftp, &src_full, &dst_full, perm)
+            });
+
+            match outcome {
+                Ok(()) => {
+                    copied += 1;
+                    tracing::debug!(path = %dst_full, "同步写入成功");
+                }
+                Err(e) => {
+                    tracing::warn!(path = %dst_full, error = %e.message, "同步写入失败");
+                    failures.push(SyncFailure {
+                        path: dst_full.clone(),
+                        error: e.message,
+                    });
+                }
+            }
+            processed_count += 1;
+
+            if let Some(ref callback) = progress_callback {
+                if last_progress_time.elapsed() >= progress_interval {
+                    callback(SyncProgress {
+                        path: src_normalized.clone(),
+                        processed_count,
+                        total_count,
+                        current_path: dst_full,
+                    });
+                    last_progress_time = std::time::Instant::now();
+                }
+            }
+        }
+
+        for relative in &deletion_list {
+            if cancel_flag
+                .as_ref()
+                .is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+            {
+                tracing::info!(src = %src_normalized, dst = %dst_normalized, "同步已取消");
+                return Ok(SyncReport {
+                    copied,
+                    skipped,
+                    deleted,
+                    failures,
+                });
+            }
+
+            let dst_full = format!("{}/{}", dst_normalized.trim_end_matches('/'), relative);
+            match sftp.unlink(Path::new(&dst_full)) {
+                Ok(()) => {
+                    deleted += 1;
+                    tracing::debug!(path = %dst_full, "同步删除成功");
+                }
+                Err(e) => {
+                    tracing::warn!(path = %dst_full, error = %e, "同步删除失败");
+                    failures.push(SyncFailure {
+                        path: dst_full.clone(),
+                        error: e.message().to_string(),
+                    });
+                }
+            }
+            processed_count += 1;
+
+            if let Some(ref callback) = progress_callback {
+                if last_progress_time.elapsed() >= progress_interval {
+                    callback(SyncProgress {
+                        path: src_normalized.clone(),
+                        processed_count,
+                        total_count,
+                        current_path: dst_full,
+                    });
+                    last_progress_time = std::time::Instant::now();
+                }
+            }
+        }
+
+        if let Some(ref callback) = progress_callback {
+            callback(SyncProgress {
+                path: src_normalized.clone(),
+                processed_count,
+                total_count,
+                current_path: String::new(),
+            });
+        }
+
+        Ok(SyncReport {
+            copied,
+            skipped,
+            deleted,
+            failures,
+        })
+    }
+
+    /// 读取单个远程文件的完整内容及其 Unix 权限位，供归档打包使用
+    ///
+    /// 分块读取循环与 [`Self::copy_single_file`] 一致；不同于分块传输命令，这里需要
+    /// 完整内容一次性写入 tar/zip 条目，因此聚合到内存而非边读边落盘
+    fn read_remote_file_for_archive(sftp: &Sftp, path: &str) -> AppResult<(Vec<u8>, u32)> {
+        let mode = sftp
+            .lstat(Path::new(path))
+            .map_err(|e| map_sftp_error(e, path))?
+            .perm
+            .unwrap_or(0o644)
+            & 0o777;
+
+        let mut content = Vec::new();
+        let mut offset: u64 = 0;
+
+        loop {
+            let chunk = Self::read_file_chunk(sftp, path, offset, SFTP_CHUNK_SIZE)?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let chunk_len = chunk.len() as u64;
+            content.extend_from_slice(&chunk);
+            offset += chunk_len;
+            if chunk_len < SFTP_CHUNK_SIZE {
+                break;
+            }
+        }
+
+        Ok((content, mode))
+    }
+
+    /// 按节流间隔（或最后一项）通过回调汇报归档打包进度
+    fn emit_archive_progress(
+        progress_callback: &Option<ArchiveProgressCallback>,
+        last_progress_time: &mut std::time::Instant,
+        progress_interval: Duration,
+        files_done: u64,
+        total_files: u64,
+        current_path: &str,
+        bytes_done: u64,
+    ) {
+        let Some(callback) = progress_callback else {
+            return;
+        };
+        if files_done < total_files && last_progress_time.elapsed() < progress_interval {
+            return;
+        }
+        callback(ArchiveProgress {
+            files_done,
+            total_files,
+            current_path: current_path.to_string(),
+            bytes_done,
+        });
+        *last_progress_time = std::time::Instant::now();
+    }
+
+    /// 将远程目录或单个文件打包为本地 tar/zip 归档
+    ///
+    /// 复用 [`Self::list_dir_recursive`] 的目录遍历逻辑枚举文件（已跳过符号链接），
+    /// 逐个通过 [`Self::read_remote_file_for_archive`] 读取内容并写入本地归档，保留
+    /// 相对路径与 Unix 权限位；单个文件读取或写入失败记录到 `failures` 后继续，
+    /// 不中断整体打包（与 `delete_recursive`/`copy_recursive` 的失败收集方式一致）
+    pub fn download_archive(
+        sftp: &Sftp,
+        remote_path: &str,
+        local_path: &str,
+        format: ArchiveFormat,
+        progress_callback: Option<ArchiveProgressCallback>,
+    ) -> AppResult<ArchiveResult> {
+        let normalized = Self::normalize_path(remote_path);
+        Self::validate_path(&normalized)?;
+
+        let path_obj = Path::new(&normalized);
         let lstat = sftp
             .lstat(path_obj)
             .map_err(|e| map_sftp_error(e, &normalized))?;
 
-        // 检查是否是符号链接
         let is_symlink = lstat
             .perm
             .map(|mode| (mode & S_IFMT) == S_IFLNK)
             .unwrap_or(false);
         if is_symlink {
-            return Err(AppError::invalid_argument("不支持下载符号链接"));
+            return Err(AppError::invalid_argument("不支持打包符号链接"));
         }
 
-        let stat = sftp
-            .stat(path_obj)
-            .map_err(|e| map_sftp_error(e, &normalized))?;
-        if !stat.is_dir() {
-            return Err(AppError::invalid_argument(format!(
-                "指定的路径是文件而非目录: {}",
-                normalized
-            )));
-        }
+        // (relative_path, full_path)：目录时复用现有递归遍历，单个文件时相对路径取其文件名
+        let entries: Vec<(String, String)> = if lstat.is_dir() {
+            Self::list_dir_recursive(sftp, &normalized, SymlinkMode::Skip)?
+                .0
+                .into_iter()
+                .map(|(full_path, relative)| (relative, full_path))
+                .collect()
+        } else {
+            let name = path_obj
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(&normalized)
+                .to_string();
+            vec![(name, normalized.clone())]
+        };
 
-        let mut files = Vec::new();
-        let mut stack = vec![normalized.clone()];
+        let total_files = entries.len() as u64;
 
-        while let Some(current_path) = stack.pop() {
-            let current_obj = Path::new(&current_path);
+        let local_file = std::fs::File::create(local_path)
+            .map_err(|e| AppError::local_io_error(format!("无法创建本地归档文件: {}", e)))?;
 
-            let entries = match sftp.readdir(current_obj) {
-                Ok(entries) => entries,
-                Err(e) => {
-                    tracing::warn!(path = %current_path, error = %e, "无法读取目录，跳过");
-                    continue;
-                }
-            };
+        let mut failures: Vec<ArchiveFailure> = Vec::new();
+        let mut files_done: u64 = 0;
+        let mut bytes_done: u64 = 0;
+        let mut last_progress_time = std::time::Instant::now();
+        let progress_interval = Duration::from_millis(200);
+
+        match format {
+            ArchiveFormat::Tar => {
+                let mut builder = tar::Builder::new(local_file);
+
+                for (relative_path, full_path) in entries {
+                    files_done += 1;
+
+                    match Self::read_remote_file_for_archive(sftp, &full_path) {
+                        Ok((content, mode)) => {
+                            let mut header = tar::Header::new_gnu();
+                            header.set_size(content.len() as u64);
+                            header.set_mode(mode);
+                            header.set_cksum();
+
+                            match builder.append_data(&mut header, &relative_path, content.as_slice()) {
+                                Ok(()) => bytes_done += content.len() as u64,
+                                Err(e) => failures.push(ArchiveFailure {
+                                    path: full_path,
+                                    error: e.to_string(),
+                                }),
+                            }
+                        }
+                        Err(e) => failures.push(ArchiveFailure {
+                            path: full_path,
+                            error: e.message().to_string(),
+                        }),
+                    }
 
-            for (path_buf, _) in entries {
-                // 过滤 . 和 ..
-                let file_name = path_buf.file_name().and_then(|n| n.to_str());
-                if matches!(file_name, None | Some(".") | Some("..")) {
-                    continue;
+                    Self::emit_archive_progress(
+                        &progress_callback,
+                        &mut last_progress_time,
+                        progress_interval,
+                        files_done,
+                        total_files,
+                        &relative_path,
+                        bytes_done,
+                    );
                 }
 
-                let full_path = path_buf.to_string_lossy().to_string();
-
-                // 使用 lstat 检查每个条目（避免跟随符号链接）
-                let lstat = match sftp.lstat(&path_buf) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        tracing::warn!(path = %full_path, error = %e, "无法获取文件信息，跳过");
-                        continue;
+                builder
+                    .finish()
+                    .map_err(|e| AppError::local_io_error(format!("写入 tar 归档失败: {}", e)))?;
+            }
+            ArchiveFormat::Zip => {
+                let mut writer = zip::ZipWriter::new(local_file);
+
+                for (relative_path, full_path) in entries {
+                    files_done += 1;
+
+                    match Self::read_remote_file_for_archive(sftp, &full_path) {
+                        Ok((content, mode)) => {
+                            let options = zip::write::SimpleFileOptions::default()
+                                .compression_method(zip::CompressionMethod::Deflated)
+                                .unix_permissions(mode);
+
+                            if let Err(e) = writer.start_file(&relative_path, options) {
+                                failures.push(ArchiveFailure {
+                                    path: full_path,
+                                    error: e.to_string(),
+                                });
+                            } else if let Err(e) = writer.write_all(&content) {
+                                failures.push(ArchiveFailure {
+                                    path: full_path,
+                                    error: e.to_string(),
+                                });
+                            } else {
+                                bytes_done += content.len() as u64;
+                            }
+                        }
+                        Err(e) => failures.push(ArchiveFailure {
+                            path: full_path,
+                            error: e.message().to_string(),
+                        }),
                     }
-                };
 
-                // 跳过符号链接
-                let is_symlink = lstat
-                    .perm
-                    .map(|mode| (mode & S_IFMT) == S_IFLNK)
-                    .unwrap_or(false);
-                if is_symlink {
-                    tracing::debug!(path = %full_path, "跳过符号链接");
-                    continue;
+                    Self::emit_archive_progress(
+                        &progress_callback,
+                        &mut last_progress_time,
+                        progress_interval,
+                        files_done,
+                        total_files,
+                        &relative_path,
+                        bytes_done,
+                    );
                 }
 
-                if lstat.is_dir() {
-                    stack.push(full_path);
-                } else {
-                    // 计算相对路径
-                    let relative = match full_path.strip_prefix(&normalized) {
-                        Some(rel) => rel.trim_start_matches('/').to_string(),
-                        None => {
-                            tracing::error!(
-                                full_path = %full_path,
-                                base = %normalized,
-                                "路径前缀不匹配，跳过"
-                            );
-                            continue;
-                        }
-                    };
-                    files.push((full_path, relative));
-                }
+                writer
+                    .finish()
+                    .map_err(|e| AppError::local_io_error(format!("写入 zip 归档失败: {}", e)))?;
             }
         }
 
-        Ok(files)
+        Ok(ArchiveResult {
+            files_done,
+            total_files,
+            failures,
+        })
     }
+}
 
-    /// 获取文件/目录信息
-    pub fn stat(sftp: &Sftp, path: &str) -> AppResult<FileEntry> {
-        let normalized = Self::normalize_path(path);
-        Self::validate_path(&normalized)?;
+use crate::commands::sftp::{
+    ArchiveFailure, ArchiveFormat, ArchiveProgress, ArchiveResult, ChmodFailure, ChmodResult,
+    CopyFailure, CopyProgress, DeleteFailure, DeleteProgress, DirectoryStats,
+    DirectoryStatsProgress, DuplicateScanProgress, RecursiveCopyResult, RecursiveDeleteResult,
+    SyncFailure, SyncProgress, SyncReport,
+};
 
-        let path_obj = Path::new(&normalized);
+/// 递归删除进度回调类型
+pub type DeleteProgressCallback = Box<dyn Fn(DeleteProgress) + Send>;
 
-        // 使用 stat 而非 lstat，自动解析符号链接
-        let file_stat = sftp
-            .stat(path_obj)
-            .map_err(|e| map_sftp_error(e, &normalized))?;
+/// 递归复制进度回调类型
+pub type CopyProgressCallback = Box<dyn Fn(CopyProgress) + Send>;
 
-        // 提取文件名
-        let name = path_obj
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
+/// 归档打包进度回调类型
+pub type ArchiveProgressCallback = Box<dyn Fn(ArchiveProgress) + Send>;
+
+/// 目录镜像同步进度回调类型
+pub type SyncProgressCallback = Box<dyn Fn(SyncProgress) + Send>;
+
+/// `compute_directory_stats_parallel` 进度回调类型
+pub type DirectoryStatsProgressCallback = Box<dyn Fn(DirectoryStatsProgress) + Send>;
 
-        Ok(Self::file_stat_to_entry(name, normalized, file_stat))
+/// `find_duplicate_files` 哈希阶段进度回调类型
+pub type DuplicateScanProgressCallback = Box<dyn Fn(DuplicateScanProgress) + Send>;
+
+/// sftp_watch 默认轮询间隔
+const DEFAULT_SFTP_WATCH_POLL_INTERVAL_MS: u64 = 1000;
+
+/// 单个条目在某一轮轮询中的可比较属性，用于与上一轮快照比较差异
+type SftpWatchAttrs = (bool, u64, i64, Option<u32>);
+
+/// 一个 sftp_watch 监视器的句柄
+struct SftpWatchHandle {
+    watch_id: String,
+    session_id: String,
+    path: String,
+    shutdown: Arc<AtomicBool>,
+}
+
+/// `sftp_watch` / `sftp_unwatch` 使用的监视器池
+///
+/// 与 [`crate::services::watch_service::WatchManager`] 功能上有所重叠，但更简单：
+/// 只比较被监视路径直接子项的 (size, mtime, mode)，不支持递归深度、不做重命名识别，
+/// 用于前端以最小开销自动刷新单个文件列表面板的场景（另一套监视器面向更复杂的目录树监视需求）
+pub struct WatcherState {
+    /// watch_id -> 监视器句柄
+    watches: RwLock<HashMap<String, Arc<SftpWatchHandle>>>,
+}
+
+impl WatcherState {
+    pub fn new() -> Self {
+        Self {
+            watches: RwLock::new(HashMap::new()),
+        }
     }
 
-    /// 将 ssh2::FileStat 转换为 FileEntry
-    fn file_stat_to_entry(name: String, path: String, stat: ssh2::FileStat) -> FileEntry {
-        FileEntry {
-            name,
-            path,
-            is_dir: stat.is_dir(),
-            size: stat.size,
-            mtime: stat.mtime.map(|t| t as i64),
-            mode: stat.perm,
+    /// 开始监视一个远程路径（目录或文件均可，内部按 `list_dir` 的直接子项比较）
+    pub fn watch(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        session_id: String,
+        path: String,
+        poll_interval_ms: Option<u64>,
+    ) -> AppResult<String> {
+        let normalized_path = SftpService::normalize_path(&path);
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = Arc::new(SftpWatchHandle {
+            watch_id: watch_id.clone(),
+            session_id: session_id.clone(),
+            path: normalized_path.clone(),
+            shutdown: shutdown.clone(),
+        });
+
+        {
+            let mut watches = self
+                .watches
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器池锁"))?;
+            watches.insert(watch_id.clone(), handle.clone());
         }
+
+        self.start_poll_thread(app, session_manager, handle, poll_interval_ms);
+
+        tracing::info!(
+            session_id = %session_id,
+            path = %normalized_path,
+            watch_id = %watch_id,
+            "sftp_watch 监视器已启动"
+        );
+
+        Ok(watch_id)
     }
 
-    /// 排序文件条目（目录优先）
-    fn sort_entries(entries: &mut [FileEntry], sort: &SortSpec) {
-        entries.sort_by(|a, b| {
-            // 目录优先
-            match (a.is_dir, b.is_dir) {
-                (true, false) => return Ordering::Less,
-                (false, true) => return Ordering::Greater,
-                _ => {}
+    /// 停止一个监视器
+    pub fn unwatch(&self, watch_id: &str) -> AppResult<()> {
+        let handle = {
+            let mut watches = self
+                .watches
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器池锁"))?;
+            watches.remove(watch_id)
+        };
+
+        if let Some(handle) = handle {
+            handle.shutdown.store(true, AtomicOrdering::Relaxed);
+            tracing::info!(watch_id = %watch_id, path = %handle.path, "sftp_watch 监视器已停止");
+        }
+
+        Ok(())
+    }
+
+    /// 停止某个会话下的所有监视器（会话关闭时调用）
+    pub fn unwatch_by_session(&self, session_id: &str) -> usize {
+        let watch_ids: Vec<String> = {
+            let Ok(watches) = self.watches.read() else {
+                return 0;
+            };
+            watches
+                .values()
+                .filter(|w| w.session_id == session_id)
+                .map(|w| w.watch_id.clone())
+                .collect()
+        };
+
+        for watch_id in &watch_ids {
+            if let Err(e) = self.unwatch(watch_id) {
+                tracing::warn!(watch_id = %watch_id, error = %e, "停止 sftp_watch 监视器失败");
             }
+        }
 
-            // 按字段排序
-            let ordering = match sort.field {
-                SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                SortField::Size => {
-                    let a_size = a.size.unwrap_or(0);
-                    let b_size = b.size.unwrap_or(0);
-                    a_size.cmp(&b_size)
+        watch_ids.len()
+    }
+
+    fn start_poll_thread(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        handle: Arc<SftpWatchHandle>,
+        poll_interval_ms: Option<u64>,
+    ) {
+        let poll_interval =
+            Duration::from_millis(poll_interval_ms.unwrap_or(DEFAULT_SFTP_WATCH_POLL_INTERVAL_MS));
+
+        thread::spawn(move || {
+            let mut previous: Option<HashMap<String, SftpWatchAttrs>> = None;
+
+            loop {
+                if handle.shutdown.load(AtomicOrdering::Relaxed) {
+                    break;
                 }
-                SortField::Mtime => {
-                    let a_time = a.mtime.unwrap_or(0);
-                    let b_time = b.mtime.unwrap_or(0);
-                    a_time.cmp(&b_time)
+
+                let Ok(session) = session_manager.get_session(&handle.session_id) else {
+                    tracing::info!(
+                        watch_id = %handle.watch_id,
+                        session_id = %handle.session_id,
+                        "关联会话已不存在，sftp_watch 监视器自动停止"
+                    );
+                    break;
+                };
+
+                let watch_path = handle.path.clone();
+                let snapshot_result =
+                    session
+                        .with_sftp(move |sftp| SftpService::list_dir(sftp, &watch_path, None, None, false));
+
+                match snapshot_result {
+                    Ok(entries) => {
+                        let current: HashMap<String, SftpWatchAttrs> = entries
+                            .into_iter()
+                            .map(|e| {
+                                (
+                                    e.path.clone(),
+                                    (e.is_dir, e.size.unwrap_or(0), e.mtime.unwrap_or(0), e.mode),
+                                )
+                            })
+                            .collect();
+
+                        if let Some(previous_snapshot) = previous.take() {
+                            // 本轮轮询内检测到的所有变更一次性发出，而不是每发现
+                            // 一条就立即推送——将同一轮里的多次变化合并为一批，
+                            // 避免前端在短时间内收到大量零散刷新事件
+                            let mut events = Vec::new();
+
+                            for (path, attrs) in &current {
+                                match previous_snapshot.get(path) {
+                                    None => events.push(FsChangeEvent {
+                                        watch_id: handle.watch_id.clone(),
+                                        path: path.clone(),
+                                        kind: FsChangeKind::Created,
+                                    }),
+                                    Some(prev_attrs) if prev_attrs != attrs => {
+                                        events.push(FsChangeEvent {
+                                            watch_id: handle.watch_id.clone(),
+                                            path: path.clone(),
+                                            kind: FsChangeKind::Modified,
+                                        })
+                                    }
+                                    _ => {}
+                                }
+                            }
+
+                            for path in previous_snapshot.keys() {
+                                if !current.contains_key(path) {
+                                    events.push(FsChangeEvent {
+                                        watch_id: handle.watch_id.clone(),
+                                        path: path.clone(),
+                                        kind: FsChangeKind::Removed,
+                                    });
+                                }
+                            }
+
+                            for event in events {
+                                app.emit("fs:change", &event).ok();
+                            }
+                        }
+
+                        previous = Some(current);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            watch_id = %handle.watch_id,
+                            path = %handle.path,
+                            error = %e,
+                            "sftp_watch 轮询读取失败，跳过本轮"
+                        );
+                    }
                 }
-            };
 
-            // 应用升降序
-            match sort.order {
-                SortOrder::Asc => ordering,
-                SortOrder::Desc => ordering.reverse(),
+                thread::sleep(poll_interval);
             }
+
+            tracing::info!(watch_id = %handle.watch_id, "sftp_watch 轮询线程已退出");
         });
     }
 }
 
-use crate::commands::sftp::{DeleteFailure, DeleteProgress, DirectoryStats, RecursiveDeleteResult};
+impl Default for WatcherState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-/// 递归删除进度回调类型
-pub type DeleteProgressCallback = Box<dyn Fn(DeleteProgress) + Send>;
+// SAFETY: WatcherState 可以安全地跨线程共享，理由与 WatchManager 相同（见
+// watch_service.rs 中的说明）：内部状态由 RwLock 保护，轮询在独立 OS 线程中进行，
+// 实际 SFTP 调用经由 `ManagedSession::sftp()` 的读锁完成
+unsafe impl Send for WatcherState {}
+unsafe impl Sync for WatcherState {}
 
 #[cfg(test)]
 mod tests {
@@ -915,6 +3242,8 @@ mod tests {
                 size: Some(100),
                 mtime: None,
                 mode: None,
+                is_symlink: false,
+                symlink_target: None,
             },
             FileEntry {
                 name: "alpha.txt".to_string(),
@@ -923,6 +3252,8 @@ mod tests {
                 size: Some(200),
                 mtime: None,
                 mode: None,
+                is_symlink: false,
+                symlink_target: None,
             },
             FileEntry {
                 name: "folder".to_string(),
@@ -931,6 +3262,8 @@ mod tests {
                 size: None,
                 mtime: None,
                 mode: None,
+                is_symlink: false,
+                symlink_target: None,
             },
         ];
 
@@ -957,6 +3290,8 @@ mod tests {
                 size: Some(100),
                 mtime: None,
                 mode: None,
+                is_symlink: false,
+                symlink_target: None,
             },
             FileEntry {
                 name: "large.txt".to_string(),
@@ -965,6 +3300,8 @@ mod tests {
                 size: Some(1000),
                 mtime: None,
                 mode: None,
+                is_symlink: false,
+                symlink_target: None,
             },
         ];
 
@@ -990,6 +3327,7 @@ mod tests {
             file_count: 10,
             dir_count: 3,
             total_size: 1024,
+            symlink_issues: vec![],
         };
         assert_eq!(stats.file_count, 10);
         assert_eq!(stats.dir_count, 3);
@@ -1006,6 +3344,8 @@ mod tests {
                 path: "/test/file.txt".to_string(),
                 error: "Permission denied".to_string(),
             }],
+            cancelled: false,
+            remaining_paths: vec![],
         };
         assert_eq!(result.deleted_files, 5);
         assert_eq!(result.deleted_dirs, 2);
@@ -1044,4 +3384,136 @@ mod tests {
         assert!(result_dot.is_err(), "Should reject '.'");
         assert!(result_dotdot.is_err(), "Should reject '..'");
     }
+
+    fn fake_stat(perm: Option<u32>, is_dir: bool) -> ssh2::FileStat {
+        // ssh2::FileStat only derives "is dir" from S_IFDIR bits in `perm` via `is_dir()`,
+        // so a directory fixture needs S_IFDIR set explicitly alongside the regular mode bits
+        const S_IFDIR: u32 = 0o040000;
+        let perm = match (perm, is_dir) {
+            (Some(p), true) => Some(p | S_IFDIR),
+            (Some(p), false) => Some(p),
+            (None, _) => None,
+        };
+        ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm,
+            atime: None,
+            mtime: None,
+        }
+    }
+
+    #[test]
+    fn test_delete_type_unchanged_dir_stays_dir() {
+        let fresh = fake_stat(Some(0o755), true);
+        assert!(SftpService::is_delete_type_unchanged(&fresh, true));
+    }
+
+    #[test]
+    fn test_delete_type_unchanged_file_stays_file() {
+        let fresh = fake_stat(Some(0o644), false);
+        assert!(SftpService::is_delete_type_unchanged(&fresh, false));
+    }
+
+    #[test]
+    fn test_delete_type_changed_dir_swapped_for_symlink() {
+        // Collection phase saw a real directory; by rmdir time it now resolves as a
+        // symlink (S_IFLNK) — this is exactly the swap the TOCTOU check must catch
+        let swapped = fake_stat(Some(S_IFLNK | 0o777), false);
+        assert!(!SftpService::is_delete_type_unchanged(&swapped, true));
+    }
+
+    #[test]
+    fn test_delete_type_changed_file_swapped_for_dir() {
+        // Collection phase saw a file/symlink; by unlink time it now resolves as a
+        // real directory — unlink must not proceed against it
+        let swapped = fake_stat(Some(0o755), true);
+        assert!(!SftpService::is_delete_type_unchanged(&swapped, false));
+    }
+
+    #[test]
+    fn test_is_recursable_dir_true_for_plain_directory() {
+        let dir = fake_stat(Some(0o755), true);
+        assert!(SftpService::is_recursable_dir(&dir));
+    }
+
+    #[test]
+    fn test_is_recursable_dir_false_for_symlink() {
+        // Even a symlink pointing at a directory must be treated as a leaf here —
+        // following it is what would let a symlink cycle recurse forever
+        let symlink = fake_stat(Some(S_IFLNK | 0o777), false);
+        assert!(!SftpService::is_recursable_dir(&symlink));
+    }
+
+    #[test]
+    fn test_is_recursable_dir_false_for_plain_file() {
+        let file = fake_stat(Some(0o644), false);
+        assert!(!SftpService::is_recursable_dir(&file));
+    }
+
+    #[test]
+    fn test_symbolic_mode_relative_add_and_remove() {
+        let clauses = parse_symbolic_clauses("u+rwX,go-w").expect("should parse");
+        // 0o644 文件：没有执行位 -> X 不生效；go 的 w 位被去掉
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o644, false), 0o644);
+        // 0o644 目录：X 对目录恒生效 -> u 加上 x
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o644, true), 0o744);
+    }
+
+    #[test]
+    fn test_symbolic_mode_capital_x_needs_existing_exec_bit_for_files() {
+        let clauses = parse_symbolic_clauses("a+X").expect("should parse");
+        // 文件已有执行位（比如 0o744）-> X 对所有 who 生效
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o744, false), 0o755);
+        // 文件完全没有执行位 -> X 不生效，mode 不变
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o644, false), 0o644);
+    }
+
+    #[test]
+    fn test_symbolic_mode_set_operator_replaces_bits() {
+        let clauses = parse_symbolic_clauses("o=r").expect("should parse");
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o777, false), 0o774);
+    }
+
+    #[test]
+    fn test_symbolic_mode_setuid_and_sticky() {
+        let clauses = parse_symbolic_clauses("u+s,+t").expect("should parse");
+        assert_eq!(apply_symbolic_clauses(&clauses, 0o755, false), 0o4755 | STICKY_BIT);
+    }
+
+    #[test]
+    fn test_symbolic_mode_rejects_missing_operator() {
+        assert!(parse_symbolic_clauses("urwx").is_err());
+    }
+
+    #[test]
+    fn test_symbolic_mode_rejects_unknown_perm_char() {
+        assert!(parse_symbolic_clauses("u+z").is_err());
+    }
+
+    #[test]
+    fn test_push_path_components_pops_in_path_order() {
+        let mut stack = Vec::new();
+        SftpService::push_path_components(&mut stack, "/a/b/c");
+        let mut popped = Vec::new();
+        while let Some(c) = stack.pop() {
+            popped.push(c);
+        }
+        assert_eq!(popped, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_push_path_components_onto_existing_stack_resolves_before_rest() {
+        // 模拟符号链接解析：已有 remaining=["rest"]（栈底），把目标的组件压到栈顶，
+        // 弹出顺序应先是目标的组件，再是原本剩下的 "rest"
+        let mut stack = Vec::new();
+        SftpService::push_path_components(&mut stack, "rest");
+        SftpService::push_path_components(&mut stack, "/target/path");
+        let mut popped = Vec::new();
+        while let Some(c) = stack.pop() {
+            popped.push(c);
+        }
+        assert_eq!(popped, vec!["target", "path", "rest"]);
+    }
 }
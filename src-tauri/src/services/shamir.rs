@@ -0,0 +1,280 @@
+//! Shamir 秘密共享（GF(256)）
+//!
+//! 把密钥库主密钥拆分成 n 份，任意凑齐其中 k 份即可还原出原始密钥，凑不满 k 份则
+//! 理论上不泄露任何信息——用于让用户在更换设备、忘记主密码时仍能恢复已保存的凭据，
+//! 而不必把恢复能力完全寄托在单一的外部保管方（云同步、纸质密码之类）上
+//!
+//! 每个密钥字节被当成一个 k-1 次多项式的常数项，在 x = 1..=n 处求值得到 n 份
+//! "份额字节"；恢复时对任意 k 个份额做 x = 0 处的 Lagrange 插值。字节级别的
+//! GF(256) 运算复用 AES 所用的既约多项式 0x11b
+
+use std::collections::HashSet;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+
+use crate::models::error::{AppError, AppResult};
+
+/// GF(256) 乘法表的生成元，与 AES S-box 推导使用同一个（3）
+const GF_GENERATOR: u8 = 0x03;
+
+/// 份额序列化字符串的固定前缀，用于在反序列化时快速拒绝格式不对/版本不符的输入
+const SHARE_PREFIX: &str = "tf-shard";
+const SHARE_VERSION: &str = "v1";
+
+/// 以 [`GF_GENERATOR`] 为底的离散对数/指数表，把 GF(256) 乘法转换成"对数相加、查表"，
+/// 避免每次乘法都做一次多项式长除法取模
+struct GfTables {
+    exp: [u8; 256],
+    log: [u8; 256],
+}
+
+fn gf_tables() -> GfTables {
+    let mut exp = [0u8; 256];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    for i in 0..255u16 {
+        exp[i as usize] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11b;
+        }
+    }
+    exp[255] = exp[0];
+    GfTables { exp, log }
+}
+
+fn gf_mul(tables: &GfTables, a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let sum = tables.log[a as usize] as u16 + tables.log[b as usize] as u16;
+    tables.exp[(sum % 255) as usize]
+}
+
+/// GF(256) 下的除法；`b` 为 0 时调用方有责任保证不会出现（Lagrange 插值中的
+/// 分母只在两个不同的份额下标相减后取得，不可能为 0）
+fn gf_div(tables: &GfTables, a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    let inv_log = (255 - tables.log[b as usize] as u16) % 255;
+    let sum = (tables.log[a as usize] as u16 + inv_log) % 255;
+    tables.exp[sum as usize]
+}
+
+/// 用 Horner 法则在 GF(256) 下求多项式在 `x` 处的值，`coeffs[0]` 是常数项
+fn eval_poly(tables: &GfTables, coeffs: &[u8], x: u8) -> u8 {
+    let mut result = 0u8;
+    for &c in coeffs.iter().rev() {
+        result = gf_mul(tables, result, x) ^ c;
+    }
+    result
+}
+
+/// GF(256) 下对 `x = 0` 处的 Lagrange 插值，`points` 是 `(下标, 该下标处的份额字节)`
+fn lagrange_interpolate_at_zero(tables: &GfTables, points: &[(u8, u8)]) -> u8 {
+    let mut result = 0u8;
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            // x = 0 处求值，分子是连乘 (0 - xj)，GF(256) 的减法就是异或，0 ^ xj = xj
+            numerator = gf_mul(tables, numerator, xj);
+            // 分母是连乘 (xi - xj) = xi ^ xj
+            denominator = gf_mul(tables, denominator, xi ^ xj);
+        }
+        result ^= gf_mul(tables, yi, gf_div(tables, numerator, denominator));
+    }
+    result
+}
+
+/// 一份 Shamir 份额，可序列化成单行字符串供用户复制保存或生成二维码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShamirShare {
+    /// 份额下标（多项式求值时使用的 x，从 1 开始，0 被保留给"原始密钥"本身）
+    pub index: u8,
+    /// 恢复所需的最少份额数
+    pub threshold: u8,
+    /// 当次拆分生成的份额总数
+    pub total: u8,
+    /// 该份额在每个密钥字节处求值得到的字节序列，长度与原始密钥相同
+    pub bytes: Vec<u8>,
+}
+
+impl ShamirShare {
+    /// 序列化成 `tf-shard:v1:<index>:<threshold>:<total>:<base64>` 形式的字符串
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            SHARE_PREFIX,
+            SHARE_VERSION,
+            self.index,
+            self.threshold,
+            self.total,
+            BASE64.encode(&self.bytes)
+        )
+    }
+
+    /// 从 [`encode`](Self::encode) 产出的字符串反序列化，拒绝前缀/版本不符或字段缺失的输入
+    pub fn decode(encoded: &str) -> AppResult<Self> {
+        let parts: Vec<&str> = encoded.trim().split(':').collect();
+        let [prefix, version, index, threshold, total, payload] = parts.as_slice() else {
+            return Err(AppError::invalid_argument("份额格式错误：字段数量不对"));
+        };
+        if *prefix != SHARE_PREFIX || *version != SHARE_VERSION {
+            return Err(AppError::invalid_argument(
+                "份额格式错误：不是合法的 TunnelFiles 备份份额，或版本不受支持",
+            ));
+        }
+
+        let index: u8 = index
+            .parse()
+            .map_err(|_| AppError::invalid_argument("份额格式错误：index 不是合法数字"))?;
+        let threshold: u8 = threshold
+            .parse()
+            .map_err(|_| AppError::invalid_argument("份额格式错误：threshold 不是合法数字"))?;
+        let total: u8 = total
+            .parse()
+            .map_err(|_| AppError::invalid_argument("份额格式错误：total 不是合法数字"))?;
+        let bytes = BASE64
+            .decode(payload)
+            .map_err(|e| AppError::invalid_argument(format!("份额格式错误：base64 解码失败: {}", e)))?;
+
+        Ok(Self {
+            index,
+            threshold,
+            total,
+            bytes,
+        })
+    }
+}
+
+/// 把 `secret` 拆分成 `n` 份 Shamir 份额，凑齐任意 `k` 份即可重建
+///
+/// `k` 至少为 2（否则不构成门限共享），`n` 不能小于 `k`，下标从 1 用到 `n`
+pub fn split_secret(secret: &[u8], k: u8, n: u8) -> AppResult<Vec<ShamirShare>> {
+    if secret.is_empty() {
+        return Err(AppError::invalid_argument("待拆分的密钥不能为空"));
+    }
+    if k < 2 {
+        return Err(AppError::invalid_argument("门限 k 至少为 2，否则不构成秘密共享"));
+    }
+    if n < k {
+        return Err(AppError::invalid_argument("份额总数 n 不能小于门限 k"));
+    }
+
+    let tables = gf_tables();
+    let mut shares: Vec<ShamirShare> = (1..=n)
+        .map(|index| ShamirShare {
+            index,
+            threshold: k,
+            total: n,
+            bytes: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    for &secret_byte in secret {
+        let mut coeffs = Vec::with_capacity(k as usize);
+        coeffs.push(secret_byte);
+        for _ in 1..k {
+            let mut buf = [0u8; 1];
+            rng.fill_bytes(&mut buf);
+            coeffs.push(buf[0]);
+        }
+        for share in shares.iter_mut() {
+            share.bytes.push(eval_poly(&tables, &coeffs, share.index));
+        }
+    }
+
+    Ok(shares)
+}
+
+/// 用任意一组份额重建原始密钥；份额数量不足、下标重复、长度不一致时报错
+///
+/// 份额数量多于门限也能正确重建（它们本就都落在同一条多项式曲线上），
+/// 这里不要求恰好等于 `threshold`
+pub fn combine_shares(shares: &[ShamirShare]) -> AppResult<Vec<u8>> {
+    let threshold = shares
+        .first()
+        .ok_or_else(|| AppError::invalid_argument("未提供任何份额"))?
+        .threshold;
+
+    if shares.len() < threshold as usize {
+        return Err(AppError::invalid_argument(format!(
+            "份额数量不足：需要至少 {} 份，只提供了 {} 份",
+            threshold,
+            shares.len()
+        )));
+    }
+
+    let len = shares[0].bytes.len();
+    let mut seen_indices = HashSet::new();
+    for share in shares {
+        if share.bytes.len() != len {
+            return Err(AppError::invalid_argument("份额长度不一致，可能来自不同的拆分批次"));
+        }
+        if !seen_indices.insert(share.index) {
+            return Err(AppError::invalid_argument(format!(
+                "份额下标 {} 重复",
+                share.index
+            )));
+        }
+    }
+
+    let tables = gf_tables();
+    let mut secret = Vec::with_capacity(len);
+    for byte_idx in 0..len {
+        let points: Vec<(u8, u8)> = shares.iter().map(|s| (s.index, s.bytes[byte_idx])).collect();
+        secret.push(lagrange_interpolate_at_zero(&tables, &points));
+    }
+
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_combine_roundtrip() {
+        let secret = b"0123456789abcdef0123456789abcdef".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // 任意凑齐 3 份，不要求是连续下标
+        let subset = vec![shares[0].clone(), shares[2].clone(), shares[4].clone()];
+        let recovered = combine_shares(&subset).unwrap();
+        assert_eq!(recovered, secret);
+    }
+
+    #[test]
+    fn test_combine_rejects_too_few_shares() {
+        let secret = b"master-key-bytes".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        let subset = vec![shares[0].clone(), shares[1].clone()];
+        assert!(combine_shares(&subset).is_err());
+    }
+
+    #[test]
+    fn test_combine_rejects_duplicate_index() {
+        let secret = b"master-key-bytes".to_vec();
+        let shares = split_secret(&secret, 2, 4).unwrap();
+        let subset = vec![shares[0].clone(), shares[0].clone()];
+        assert!(combine_shares(&subset).is_err());
+    }
+
+    #[test]
+    fn test_share_encode_decode_roundtrip() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let encoded = shares[0].encode();
+        let decoded = ShamirShare::decode(&encoded).unwrap();
+        assert_eq!(decoded, shares[0]);
+    }
+}
@@ -0,0 +1,96 @@
+//! 优雅关闭协调器
+//!
+//! 把分散在 `TerminalManager`/`SessionManager`/`TransferManager` 里各自的收尾方法
+//! （`close_all`/`close_all_sessions`/`pause_running_tasks_for_session`）串成一次
+//! 统一的退出流程，供 `lib.rs` 里的 Tauri `RunEvent::ExitRequested` 处理器和 OS
+//! 信号（Ctrl-C）处理器共用，也通过 `commands::shutdown::shutdown_prepare` 开放给
+//! 前端主动发起。
+//!
+//! 执行顺序：先暂停并 checkpoint 每个会话下在途的传输任务（复用会话断线重连用的
+//! `pause_running_tasks_for_session`，置为可重试的 Failed 状态，断点续传的偏移量
+//! 已随每次进度更新持久化，下次启动后可继续），再关闭该会话下的全部终端，最后断开
+//! 会话本身；全部会话处理完后落盘日志。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::services::session_manager::SessionManager;
+use crate::services::terminal_manager::TerminalManager;
+use crate::services::transfer_manager::TransferManager;
+use crate::utils::logging::flush_logs;
+
+/// 一次优雅关闭排空的结果统计，返回给前端展示进度
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownSummary {
+    pub sessions_closed: usize,
+    pub terminals_closed: usize,
+    pub transfers_paused: usize,
+}
+
+/// 优雅关闭协调器
+///
+/// Tauri 退出事件与 Ctrl-C 信号可能在极端情况下前后脚触发，内部用 `AtomicBool`
+/// 保证 [`Self::drain`] 整个流程只会真正执行一次，第二次调用直接返回空结果
+pub struct ShutdownCoordinator {
+    session_manager: Arc<SessionManager>,
+    terminal_manager: Arc<TerminalManager>,
+    transfer_manager: Arc<TransferManager>,
+    already_drained: AtomicBool,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(
+        session_manager: Arc<SessionManager>,
+        terminal_manager: Arc<TerminalManager>,
+        transfer_manager: Arc<TransferManager>,
+    ) -> Self {
+        Self {
+            session_manager,
+            terminal_manager,
+            transfer_manager,
+            already_drained: AtomicBool::new(false),
+        }
+    }
+
+    /// 执行一次完整的优雅关闭排空，幂等——重复调用第二次起直接返回全零的结果
+    pub async fn drain(&self) -> ShutdownSummary {
+        if self.already_drained.swap(true, Ordering::SeqCst) {
+            tracing::debug!("优雅关闭：已执行过一次，跳过重复排空");
+            return ShutdownSummary::default();
+        }
+
+        tracing::info!("优雅关闭：开始排空终端、会话与在途传输");
+
+        let session_ids = self.session_manager.list_sessions().unwrap_or_default();
+
+        let mut transfers_paused = 0;
+        for session_id in &session_ids {
+            transfers_paused += self
+                .transfer_manager
+                .pause_running_tasks_for_session(session_id)
+                .await
+                .len();
+        }
+
+        let terminals_closed = self.terminal_manager.close_all();
+        let sessions_closed = self.session_manager.close_all_sessions();
+
+        flush_logs();
+
+        tracing::info!(
+            sessions_closed,
+            terminals_closed,
+            transfers_paused,
+            "优雅关闭：排空完成"
+        );
+
+        ShutdownSummary {
+            sessions_closed,
+            terminals_closed,
+            transfers_paused,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! 连接时合并 `~/.ssh/config` 里匹配 Profile host 的 Host 块
+//!
+//! 和 [`crate::services::storage_service::import`] 里批量导入用的解析器不是同一
+//! 份代码——那边是把整个文件的每个 Host 块各自转成一条新 Profile；这里只做一件
+//! 更小的事：给定一个已经存在的 Profile，查它的 `host` 字段是否命中 config 里的
+//! Host 模式，命中的话把 HostName/User/Port/IdentityFile/IdentitiesOnly 填进
+//! Profile 里本来缺省的位置，让用户能直接拿 OpenSSH 的别名当 Profile host 用。
+//!
+//! 只支持连接用得上的这几个指令，也不展开 `Include`——配置别名如果是通过
+//! `Include` 拆到子文件里定义的，这里暂时查不到，需要的话可以参考
+//! `storage_service::import` 里 `resolve_include` 的递归展开补上。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::profile::{Auth, Profile};
+
+/// 默认 SSH 端口，用来判断 `Profile.port` 是不是还停留在"没有特意改过"的默认值——
+/// 只有这种情况下才允许被 config 里的 `Port` 指令覆盖
+const DEFAULT_SSH_PORT: u16 = 22;
+
+/// 从某个 Host 块里解析出来、对连接有用的字段
+#[derive(Debug, Default, Clone)]
+struct SshConfigEntry {
+    host_name: Option<String>,
+    user: Option<String>,
+    port: Option<u16>,
+    identity_file: Option<PathBuf>,
+    identities_only: bool,
+}
+
+impl SshConfigEntry {
+    /// 把 `other` 里"自己还没有"的字段补进来——OpenSSH 的语义是同一个 key
+    /// 第一次出现的值生效，后面匹配到的 Host 块不会覆盖已经有值的字段
+    fn fill_missing(&mut self, other: &SshConfigEntry) {
+        if self.host_name.is_none() {
+            self.host_name = other.host_name.clone();
+        }
+        if self.user.is_none() {
+            self.user = other.user.clone();
+        }
+        if self.port.is_none() {
+            self.port = other.port;
+        }
+        if self.identity_file.is_none() {
+            self.identity_file = other.identity_file.clone();
+        }
+        if !self.identities_only {
+            self.identities_only = other.identities_only;
+        }
+    }
+}
+
+/// 用 `~/.ssh/config` 里匹配的 Host 块填充 `profile` 本来缺省的字段，返回一份
+/// 新的 Profile 供本次连接使用——不会改动数据库里保存的原始 Profile
+///
+/// 只填"缺省"的位置：用户在 Profile 里已经明确填过的字段永远优先，config 只负责
+/// 补洞，因此可以放心地给一个已经填满全部字段的 Profile 调用本函数，不会有任何
+/// 字段被意外覆盖
+pub fn resolve_profile(profile: &Profile) -> Profile {
+    let Some(home) = dirs::home_dir() else {
+        return profile.clone();
+    };
+    let config_path = home.join(".ssh").join("config");
+
+    let Some(entry) = lookup(&config_path, &profile.host) else {
+        return profile.clone();
+    };
+
+    let mut resolved = profile.clone();
+
+    if let Some(host_name) = entry.host_name {
+        resolved.host = host_name;
+    }
+    if resolved.username.is_empty() {
+        if let Some(user) = entry.user {
+            resolved.username = user;
+        }
+    }
+    if resolved.port == DEFAULT_SSH_PORT {
+        if let Some(port) = entry.port {
+            resolved.port = port;
+        }
+    }
+    // 只有 Key 认证、且 Profile 自己没有指定私钥来源（文件路径/托管密钥/托管凭据）
+    // 时才用 config 的 IdentityFile 填坑，避免覆盖用户在 Profile 里选好的密钥
+    if let Auth::Key {
+        private_key_path,
+        private_key_ref,
+        managed_key_id,
+        ..
+    } = &mut resolved.auth
+    {
+        if private_key_path.is_none() && private_key_ref.is_none() && managed_key_id.is_none() {
+            if let Some(identity_file) = entry.identity_file {
+                *private_key_path = Some(identity_file.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    resolved
+}
+
+/// 解析 `path` 指向的 config 文件，合并全部匹配 `host` 的 Host 块
+fn lookup(path: &Path, host: &str) -> Option<SshConfigEntry> {
+    let content = fs::read_to_string(path).ok()?;
+
+    let mut merged = SshConfigEntry::default();
+    let mut found_match = false;
+    let mut current = SshConfigEntry::default();
+    let mut current_matches = false;
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, value) = match line.split_once(char::is_whitespace) {
+            Some((k, v)) => (k, v.trim()),
+            None => continue,
+        };
+
+        if keyword.eq_ignore_ascii_case("host") {
+            if current_matches {
+                merged.fill_missing(&current);
+                found_match = true;
+            }
+            current = SshConfigEntry::default();
+            current_matches = value
+                .split_whitespace()
+                .any(|pattern| host_matches_pattern(host, pattern));
+            continue;
+        }
+
+        if !current_matches {
+            continue;
+        }
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "hostname" => {
+                current.host_name.get_or_insert_with(|| value.to_string());
+            }
+            "user" => {
+                current.user.get_or_insert_with(|| value.to_string());
+            }
+            "port" => {
+                if let Ok(port) = value.parse::<u16>() {
+                    current.port.get_or_insert(port);
+                }
+            }
+            "identityfile" => {
+                current
+                    .identity_file
+                    .get_or_insert_with(|| expand_tilde(value));
+            }
+            "identitiesonly" => {
+                if value.eq_ignore_ascii_case("yes") {
+                    current.identities_only = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if current_matches {
+        merged.fill_missing(&current);
+        found_match = true;
+    }
+
+    found_match.then_some(merged)
+}
+
+/// OpenSSH Host 模式匹配：`*` 匹配任意长度（含空），`?` 匹配单个字符，其余字符
+/// 要求原样相等；不支持 `!` 取反模式，这在连接时的单个 host 查找里用不上
+fn host_matches_pattern(host: &str, pattern: &str) -> bool {
+    fn match_from(host: &[u8], pattern: &[u8]) -> bool {
+        match pattern.first() {
+            None => host.is_empty(),
+            Some(b'*') => {
+                match_from(host, &pattern[1..])
+                    || (!host.is_empty() && match_from(&host[1..], pattern))
+            }
+            Some(b'?') => !host.is_empty() && match_from(&host[1..], &pattern[1..]),
+            Some(c) => !host.is_empty() && host[0] == *c && match_from(&host[1..], &pattern[1..]),
+        }
+    }
+
+    match_from(host.as_bytes(), pattern.as_bytes())
+}
+
+/// 展开 `IdentityFile` 值里的 `~` 前缀
+fn expand_tilde(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
@@ -0,0 +1,358 @@
+//! 有界 SSH 会话连接池
+//!
+//! [`SshConnectionPool`] 按 `host:port:username:凭据指纹` 分桶缓存已认证的 [`SshSession`]，
+//! 供并发场景（如并行 `stat`/`readdir`，或 [`crate::services::file_transfer`] 的多个后端
+//! 实例）复用，避免每次操作都重新走一遍 TCP 连接 + SSH 握手 + 认证这条昂贵的路径。
+//!
+//! 用法：[`SshConnectionPool::acquire`] 返回一个 [`PooledConnection`]（`Deref`/`DerefMut`
+//! 到 [`SshSession`]），用完后正常 drop 即可——健康的连接会被放回空闲队列，超过
+//! `idle_timeout` 未被复用的连接在下次 `acquire` 时会被当作"死连接"丢弃并重新握手，
+//! 这是本模块默认的健康检查策略：libssh2 没有提供代价低廉的存活探测，相比每次都发一个
+//! 探测包，用空闲超时来判断更符合这个协议的开销特点。[`SshConnectionPoolConfig`]
+//! 额外提供 `with_test_on_acquire`，开启后复用空闲连接前会多一次真实的 `channel_session`
+//! 往返探测，能发现"连接还没到空闲超时，但服务器已经把会话杀掉"这种被动策略覆盖不到的
+//! 情况，代价是多一次网络往返，默认关闭。容量/超时/健康检查行为都通过
+//! [`SshConnectionPoolConfig`] 的 `with_*` 链式方法配置。
+//!
+//! 范围说明：目前只有 [`crate::services::file_transfer`] 的 `SftpFileTransfer`/
+//! `ScpFileTransfer`（通过 `with_pool`）接入了这个池子；`tests/integration_tests.rs` 里
+//! 直接用 `ssh2::Session` 的既有测试辅助函数（`create_ssh_session`/`create_sftp_session`）
+//! 暂未迁移——它们被几十个既有测试用例直接调用，整体改造属于后续单独的工作。
+
+use std::collections::{HashMap, VecDeque};
+use std::net::TcpStream;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::services::ssh_session::SshSession;
+
+/// 建立新连接时使用的凭据
+///
+/// 只在创建连接（握手 + 认证）时读取一次；池子本身只保存它的摘要（见
+/// [`PoolCredential::fingerprint`]），不会把密码明文长期留存在 [`PoolKey`] 这个
+/// 一直存活的 `HashMap` key 里。
+#[derive(Clone)]
+pub enum PoolCredential {
+    Password(String),
+    PrivateKey(PathBuf),
+}
+
+impl PoolCredential {
+    fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        match self {
+            Self::Password(password) => {
+                hasher.update(b"password:");
+                hasher.update(password.as_bytes());
+            }
+            Self::PrivateKey(path) => {
+                hasher.update(b"key:");
+                hasher.update(path.to_string_lossy().as_bytes());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    host: String,
+    port: u16,
+    username: String,
+    credential_fingerprint: String,
+}
+
+struct IdleEntry {
+    session: SshSession,
+    idle_since: Instant,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<PoolKey, VecDeque<IdleEntry>>,
+    /// 每个 key 当前"存活"的连接数（空闲 + 借出），用于对齐 `max_size_per_key` 上限
+    active_counts: HashMap<PoolKey, usize>,
+}
+
+/// 连接池的容量与存活策略
+#[derive(Debug, Clone)]
+pub struct SshConnectionPoolConfig {
+    /// 单个 `host:port:username:凭据` 组合最多允许的并发连接数
+    pub max_size_per_key: usize,
+    /// 空闲连接超过这个时长未被复用就视为死连接，下次 acquire 会丢弃并重新握手
+    pub idle_timeout: Duration,
+    /// 新建连接时 TCP 连接 + SSH 握手的超时
+    pub connect_timeout: Duration,
+    /// 复用空闲连接前是否额外做一次主动健康检查（开一个 `channel_session` 立即关闭）
+    ///
+    /// 默认 `false`：只靠 `idle_timeout` 这种被动策略判断连接是否存活（见模块文档）。
+    /// 开启后每次复用都多一次往返开销，换来的是能发现"TCP 连接还在但服务器已经把
+    /// SSH 会话杀掉"这种 `idle_timeout` 覆盖不到的情况。
+    pub test_on_acquire: bool,
+}
+
+impl SshConnectionPoolConfig {
+    /// 链式设置单 key 最大并发连接数
+    pub fn with_max_size_per_key(mut self, max_size_per_key: usize) -> Self {
+        self.max_size_per_key = max_size_per_key;
+        self
+    }
+
+    /// 链式设置空闲超时
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// 链式设置新建连接的超时
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// 链式开启/关闭复用前的主动健康检查
+    pub fn with_test_on_acquire(mut self, test_on_acquire: bool) -> Self {
+        self.test_on_acquire = test_on_acquire;
+        self
+    }
+}
+
+impl Default for SshConnectionPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size_per_key: 4,
+            idle_timeout: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(10),
+            test_on_acquire: false,
+        }
+    }
+}
+
+/// 有界 SSH 会话连接池，按 host/port/username/凭据指纹分桶
+pub struct SshConnectionPool {
+    config: SshConnectionPoolConfig,
+    state: Mutex<PoolState>,
+}
+
+impl SshConnectionPool {
+    pub fn new(config: SshConnectionPoolConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            state: Mutex::new(PoolState::default()),
+        })
+    }
+
+    /// 借出一条已认证的连接：优先复用空闲连接，池子未满时新建，已达上限则报 `Busy`
+    pub fn acquire(
+        self: &Arc<Self>,
+        host: impl Into<String>,
+        port: u16,
+        username: impl Into<String>,
+        credential: PoolCredential,
+    ) -> AppResult<PooledConnection> {
+        let key = PoolKey {
+            host: host.into(),
+            port,
+            username: username.into(),
+            credential_fingerprint: credential.fingerprint(),
+        };
+
+        if let Some(session) = self.try_reuse_idle(&key)? {
+            return Ok(PooledConnection {
+                pool: Arc::clone(self),
+                key,
+                session: Some(session),
+            });
+        }
+
+        self.reserve_slot(&key)?;
+
+        match self.connect_and_authenticate(&key, &credential) {
+            Ok(session) => Ok(PooledConnection {
+                pool: Arc::clone(self),
+                key,
+                session: Some(session),
+            }),
+            Err(e) => {
+                // 新建失败要把刚占的名额还回去，否则池子会被"幽灵连接"占满
+                self.release_slot(&key);
+                Err(e)
+            }
+        }
+    }
+
+    fn try_reuse_idle(&self, key: &PoolKey) -> AppResult<Option<SshSession>> {
+        let mut state = self.lock_state()?;
+
+        let mut expired = 0usize;
+        if let Some(queue) = state.idle.get_mut(key) {
+            let before = queue.len();
+            queue.retain(|entry| entry.idle_since.elapsed() < self.config.idle_timeout);
+            expired = before - queue.len();
+        }
+        if expired > 0 {
+            if let Some(count) = state.active_counts.get_mut(key) {
+                *count = count.saturating_sub(expired);
+            }
+            tracing::debug!(host = %key.host, port = key.port, expired, "丢弃超过空闲超时的连接池连接");
+        }
+
+        loop {
+            let Some(session) = state
+                .idle
+                .get_mut(key)
+                .and_then(|queue| queue.pop_front())
+                .map(|entry| entry.session)
+            else {
+                return Ok(None);
+            };
+
+            if !self.config.test_on_acquire || Self::probe_alive(&session) {
+                tracing::debug!(host = %key.host, port = key.port, username = %key.username, "复用连接池中的空闲会话");
+                return Ok(Some(session));
+            }
+
+            tracing::warn!(host = %key.host, port = key.port, username = %key.username, "连接池健康检查发现连接已失效，丢弃并继续寻找可用连接");
+            if let Some(count) = state.active_counts.get_mut(key) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+
+    /// `test_on_acquire` 开启时的主动健康检查：开一个 `channel_session` 立即丢弃，靠这一次
+    /// 真实的往返确认底层 TCP 连接和 SSH 会话都还活着（仅凭本地状态无法区分"连接已被
+    /// 服务器关闭"和"连接仍然可用"这两种情况）
+    fn probe_alive(session: &SshSession) -> bool {
+        session.channel_session().is_ok()
+    }
+
+    fn reserve_slot(&self, key: &PoolKey) -> AppResult<()> {
+        let mut state = self.lock_state()?;
+        let count = state.active_counts.entry(key.clone()).or_insert(0);
+        if *count >= self.config.max_size_per_key {
+            tracing::warn!(
+                host = %key.host,
+                port = key.port,
+                username = %key.username,
+                max_size = self.config.max_size_per_key,
+                "连接池已达上限，拒绝借用"
+            );
+            return Err(AppError::busy(format!(
+                "SSH 连接池已达上限 ({}/{}): {}@{}:{}",
+                count, self.config.max_size_per_key, key.username, key.host, key.port
+            )));
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    fn release_slot(&self, key: &PoolKey) {
+        if let Ok(mut state) = self.lock_state() {
+            if let Some(count) = state.active_counts.get_mut(key) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+
+    fn connect_and_authenticate(
+        &self,
+        key: &PoolKey,
+        credential: &PoolCredential,
+    ) -> AppResult<SshSession> {
+        let addr = format!("{}:{}", key.host, key.port);
+        tracing::debug!(addr = %addr, username = %key.username, "连接池正在新建 SSH 会话");
+        let socket_addr = addr
+            .parse()
+            .map_err(|e| AppError::invalid_argument(format!("无效的地址: {}", e)))?;
+
+        let tcp = TcpStream::connect_timeout(&socket_addr, self.config.connect_timeout)
+            .map_err(|e| {
+                tracing::warn!(addr = %addr, error = %e, "连接池建立 TCP 连接失败");
+                AppError::network_lost(format!("无法连接到服务器: {}", e))
+            })?;
+        tcp.set_read_timeout(Some(self.config.connect_timeout))?;
+        tcp.set_write_timeout(Some(self.config.connect_timeout))?;
+
+        let mut session = SshSession::connect_libssh2(tcp, self.config.connect_timeout).map_err(|e| {
+            tracing::warn!(addr = %addr, error = %e, "连接池 SSH 握手失败或超时");
+            AppError::network_lost(format!("SSH 握手失败: {}", e))
+        })?;
+
+        match credential {
+            PoolCredential::Password(password) => session
+                .userauth_password(&key.username, password)
+                .map_err(|e| {
+                    tracing::warn!(username = %key.username, error = %e, "连接池密码认证失败");
+                    AppError::auth_failed(format!("密码认证失败: {}", e))
+                })?,
+            PoolCredential::PrivateKey(path) => session
+                .userauth_pubkey_file(&key.username, None, path, None)
+                .map_err(|e| {
+                    tracing::warn!(username = %key.username, error = %e, "连接池密钥认证失败");
+                    AppError::auth_failed(format!("密钥认证失败: {}", e))
+                })?,
+        }
+
+        tracing::info!(addr = %addr, username = %key.username, "连接池新建 SSH 会话认证成功");
+        Ok(session)
+    }
+
+    /// 连接使用完毕后归还：健康的连接放回空闲队列供复用，否则释放名额直接丢弃
+    fn release(&self, key: PoolKey, session: Option<SshSession>) {
+        let Ok(mut state) = self.lock_state() else {
+            return;
+        };
+        match session {
+            Some(session) if session.authenticated() => {
+                state.idle.entry(key).or_default().push_back(IdleEntry {
+                    session,
+                    idle_since: Instant::now(),
+                });
+            }
+            _ => {
+                if let Some(count) = state.active_counts.get_mut(&key) {
+                    *count = count.saturating_sub(1);
+                }
+            }
+        }
+    }
+
+    fn lock_state(&self) -> AppResult<std::sync::MutexGuard<'_, PoolState>> {
+        self.state
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "连接池内部锁已中毒"))
+    }
+}
+
+/// 从连接池借出的连接；`Drop` 时自动把底层 [`SshSession`] 归还给池子
+pub struct PooledConnection {
+    pool: Arc<SshConnectionPool>,
+    key: PoolKey,
+    session: Option<SshSession>,
+}
+
+impl Deref for PooledConnection {
+    type Target = SshSession;
+
+    fn deref(&self) -> &SshSession {
+        self.session.as_ref().expect("session 只在 Drop 中被取走")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut SshSession {
+        self.session.as_mut().expect("session 只在 Drop 中被取走")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let session = self.session.take();
+        self.pool.release(self.key.clone(), session);
+    }
+}
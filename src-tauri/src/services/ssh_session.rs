@@ -0,0 +1,174 @@
+//! SSH 会话后端抽象
+//!
+//! [`SshSession`] 把握手/认证/通道创建这几个动作从具体的 SSH 实现库中抽离出来。这是一个
+//! 纯转发层，不改变任何行为，目的是让 [`crate::services::file_transfer`] 不直接依赖
+//! `ssh2`，未来若要接入纯 Rust 的 SSH 实现（例如在交叉编译 libssh2 困难的环境下），只需
+//! 新增一个变体并实现转发方法，调用方完全不用改动。
+//!
+//! 目前有两个变体：
+//! - `Libssh2`：包裹 `ssh2::Session`，本模块唯一能真正创建和使用的变体。
+//! - [`Russh`](SshSession::Russh)：预留给 `russh`/`thrussh` 这类纯 Rust 实现的扩展点，
+//!   目前是不可构造的占位（内部类型是 [`std::convert::Infallible`]）——`russh` 的 API 是
+//!   async-first 的（`tokio::net::TcpStream` + async 回调 trait），而这个 enum 的所有调用方
+//!   （`file_transfer.rs`、`ssh_pool.rs`）都是同步阻塞、跑在 `spawn_blocking` 专用线程里的；
+//!   简单地在每个方法里包一层 `block_on` 能让类型对齐，但会在调用方已经独占的阻塞线程里
+//!   悄悄嵌套一个 tokio runtime，这类决定需要结合真实的 `russh` 依赖和编译器验证，不能在
+//!   这里凭空猜测着写，所以先占住这个扩展点（枚举变体、match 分支都已就位），真正接入
+//!   留给后续单独的工作。
+//!
+//! 范围说明：目前只有 `file_transfer.rs` 迁移到了这层抽象之上；`session_manager.rs`/
+//! `terminal_manager.rs`/`command_service.rs` 仍直接持有 `ssh2::Session`/`ssh2::Sftp`
+//! ——它们对 Session 的用法（长期持有、RwLock 保护、断线重连原地替换）比这里复杂得多，
+//! 留作后续单独的迁移。
+
+use std::convert::Infallible;
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+use ssh2::{Channel, HostKeyType, ScpFileStat, Session, Sftp};
+
+/// 包装具体 SSH 实现库的会话句柄
+///
+/// 只转发 [`crate::services::file_transfer`] 用到的这一小部分 API：握手、密码/密钥认证、
+/// SFTP 通道创建、SCP 收发通道创建。其余 `ssh2::Session` 方法（如 `channel_session`、
+/// `host_key`）按需在这里继续补充转发方法即可。
+pub enum SshSession {
+    Libssh2(Session),
+    /// 预留扩展点，见模块文档；`Infallible` 让这个变体在运行时不可构造，所有 match 分支
+    /// 里对应的 `match never {}` 在编译期就是死代码，不影响现有行为
+    Russh(Infallible),
+}
+
+impl SshSession {
+    /// 创建一个基于 libssh2 的会话，并完成 TCP 连接、超时设置与 SSH 握手
+    pub fn connect_libssh2(
+        tcp: TcpStream,
+        connect_timeout: Duration,
+    ) -> Result<Self, ssh2::Error> {
+        let mut session = Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(connect_timeout.as_millis() as u32);
+        session.handshake()?;
+        Ok(Self::Libssh2(session))
+    }
+
+    pub fn userauth_password(&mut self, username: &str, password: &str) -> Result<(), ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.userauth_password(username, password),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn userauth_pubkey_file(
+        &mut self,
+        username: &str,
+        pubkey: Option<&Path>,
+        privatekey: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<(), ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => {
+                session.userauth_pubkey_file(username, pubkey, privatekey, passphrase)
+            }
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn authenticated(&self) -> bool {
+        match self {
+            Self::Libssh2(session) => session.authenticated(),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn sftp(&self) -> Result<Sftp, ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.sftp(),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn scp_recv(&self, path: &Path) -> Result<(Channel<'_>, ScpFileStat), ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.scp_recv(path),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn scp_send(
+        &self,
+        path: &Path,
+        mode: i32,
+        size: u64,
+        times: Option<(u64, u64)>,
+    ) -> Result<Channel<'_>, ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.scp_send(path, mode, size, times),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    pub fn host_key(&self) -> Option<(&[u8], HostKeyType)> {
+        match self {
+            Self::Libssh2(session) => session.host_key(),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    /// 打开一个 exec 通道，用于在远端执行 shell 命令（如 `SftpFileTransfer::copy`
+    /// 借此运行 `cp`）。部分服务器出于安全考虑会禁用 exec，这种情况下本方法会返回
+    /// `Err`，调用方应当退回到纯 SFTP/SCP 的数据搬运路径。
+    pub fn channel_session(&self) -> Result<Channel<'_>, ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.channel_session(),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    /// 打开一个 direct-tcpip 转发 channel：请求 SSH 服务器代为连接
+    /// `remote_host:remote_port`（对服务器而言"remote"就是它自己能访问到的目标），
+    /// 数据通过这个 channel 双向搬运。用于 [`crate::services::port_forward`] 里的本地转发。
+    pub fn direct_tcpip(
+        &self,
+        remote_host: &str,
+        remote_port: u16,
+        src: Option<(&str, u16)>,
+    ) -> Result<Channel<'_>, ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => session.channel_direct_tcpip(remote_host, remote_port, src),
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    /// 请求 SSH 服务器在它那一侧监听 `remote_port`，把每个到来的连接包装成一个
+    /// channel 交还给我们；返回服务器实际绑定的端口（`remote_port` 传 0 时由服务器
+    /// 分配）。用于 [`crate::services::port_forward`] 里的远程转发。
+    pub fn forward_listen(
+        &self,
+        remote_port: u16,
+        host: Option<&str>,
+        queue_maxsize: Option<u32>,
+    ) -> Result<(ssh2::Listener<'_>, u16), ssh2::Error> {
+        match self {
+            Self::Libssh2(session) => {
+                session.channel_forward_listen(remote_port, host, queue_maxsize)
+            }
+            Self::Russh(never) => match *never {},
+        }
+    }
+
+    /// 切换底层会话的阻塞模式。端口转发需要在同一个线程里轮询多路连接，因此要把
+    /// session 切到非阻塞模式（见 `port_forward.rs` 模块文档）；SFTP/SCP/Terminal
+    /// 路径则始终使用默认的阻塞模式，不应调用这个方法。
+    pub fn set_blocking(&mut self, blocking: bool) {
+        match self {
+            Self::Libssh2(session) => session.set_blocking(blocking),
+            Self::Russh(never) => match *never {},
+        }
+    }
+}
+
+// SAFETY: 与 file_transfer.rs 中各后端的理由相同：底层 `ssh2::Session` 是 `!Send`，
+// 因为 libssh2 非线程安全；但本类型只会被 move 进恰好一个 spawn_blocking 专用线程。
+unsafe impl Send for SshSession {}
@@ -8,21 +8,70 @@
 //! - Settings JSON 读写
 
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Condvar, Mutex};
 
-use rusqlite::{params, Connection, OptionalExtension};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use rusqlite::{named_params, params, Connection, OpenFlags, OptionalExtension};
+use scrypt::{scrypt, Params};
+use serde::{Deserialize, Serialize};
 
 use crate::models::error::{AppError, AppResult, ErrorCode};
-use crate::models::profile::{AuthType, Profile, RecentConnection};
+use crate::models::key::ManagedKey;
+use crate::models::profile::{Auth, AuthType, Profile, RecentConnection};
+use crate::models::schedule::{ScheduleRecurrence, SyncSchedule};
 use crate::models::settings::{Settings, SettingsPatch};
+use crate::models::transfer_task::{TransferDirection, TransferStatus, TransferTask};
+use crate::services::shamir::{self, ShamirShare};
 
 /// 数据库版本 - 用于迁移
-const DB_VERSION: i32 = 2;
+const DB_VERSION: i32 = 24;
 
 /// 最近连接最大数量
 const MAX_RECENT_CONNECTIONS: i32 = 10;
 
+/// "Frecency" 排序用的 SQL 片段：`visit_count * recency_weight(age)`，recency_weight 按最后
+/// 连接时间距今的天数分桶（≤4 天 100、≤14 天 70、≤31 天 50、≤90 天 30，否则 10）。
+/// `recent_connections_list` 与 `recent_connection_add` 清理旧记录时必须用同一套公式，
+/// 否则排序展示的结果和实际保留下来的行会对不上。
+const FRECENCY_SCORE_SQL: &str = r#"
+    visit_count * (
+        CASE
+            WHEN (:now - connected_at) <= 4 * 86400000 THEN 100
+            WHEN (:now - connected_at) <= 14 * 86400000 THEN 70
+            WHEN (:now - connected_at) <= 31 * 86400000 THEN 50
+            WHEN (:now - connected_at) <= 90 * 86400000 THEN 30
+            ELSE 10
+        END
+    )
+"#;
+
+/// `run_maintenance` 清理 `transfer_history` 时，无论保留期多短都至少保留的最近记录数，
+/// 避免用户刚设置了很短的 `retention_days` 就把当前会话的传输记录清空
+const MAX_TRANSFER_HISTORY_ROWS: i64 = 2000;
+
+/// `run_maintenance` 触发 `VACUUM` 的空闲页阈值：`PRAGMA freelist_count` 超过此值才会整理，
+/// 避免每次维护都做一次全量重写（VACUUM 需要复制整个数据库文件）
+const VACUUM_FREELIST_THRESHOLD: i64 = 2000;
+
+/// [`Database::run_maintenance`] 的执行结果，用于日志记录和前端展示
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceMetrics {
+    /// 本次 `PRAGMA wal_checkpoint(TRUNCATE)` 写回的 WAL 页数
+    pub wal_pages_checkpointed: i64,
+    /// 被清理的 `transfer_history` 行数
+    pub history_rows_pruned: usize,
+    /// 被清理的悬空 `recent_connections` 行数（`profile_id` 已不存在于 `profiles`）
+    pub recent_connections_pruned: usize,
+    /// `VACUUM` 回收的字节数（未触发 `VACUUM` 时为 0）
+    pub bytes_reclaimed: i64,
+}
+
 // ============================================
 // 路径管理
 // ============================================
@@ -49,6 +98,27 @@ pub fn get_known_hosts_path() -> PathBuf {
     get_app_data_dir().join("known_hosts")
 }
 
+/// 获取部署级配置覆盖文件路径（`tunnelfiles.toml`），见 [`crate::services::config_loader`]
+pub fn get_config_file_path() -> PathBuf {
+    get_app_data_dir().join("tunnelfiles.toml")
+}
+
+/// 用户系统里 OpenSSH 客户端配置的默认路径（`~/.ssh/config`），供导入功能使用
+fn default_ssh_config_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ssh")
+        .join("config")
+}
+
+/// 用户系统里 OpenSSH 的默认 known_hosts 路径（`~/.ssh/known_hosts`），供导入功能使用
+fn default_known_hosts_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ssh")
+        .join("known_hosts")
+}
+
 /// 获取日志目录
 pub fn get_logs_dir() -> PathBuf {
     get_app_data_dir().join("logs")
@@ -58,35 +128,149 @@ pub fn get_logs_dir() -> PathBuf {
 // 数据库管理
 // ============================================
 
+/// 只读连接池默认大小
+const DEFAULT_READER_POOL_SIZE: usize = 4;
+
+/// 只读连接池：每条连接以 `SQLITE_OPEN_READ_ONLY` 打开，WAL 模式下可以和 `writer` 上
+/// 进行的写事务完全并发，不会互相阻塞。借出逻辑用 `Mutex<Vec<Connection>>` +
+/// `Condvar` 实现，相当于一个同步信号量——池子为空时 `checkout` 阻塞等待，直到有
+/// 连接被归还。
+struct ReaderPool {
+    idle: Mutex<Vec<Connection>>,
+    available: Condvar,
+}
+
+impl ReaderPool {
+    fn open(db_path: &std::path::Path, size: usize) -> AppResult<Self> {
+        let mut idle = Vec::with_capacity(size);
+        for _ in 0..size {
+            let conn = Connection::open_with_flags(
+                db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(|e| AppError::local_io_error(format!("无法打开只读数据库连接: {}", e)))?;
+            conn.execute_batch("PRAGMA busy_timeout=5000;")?;
+            idle.push(conn);
+        }
+
+        Ok(Self {
+            idle: Mutex::new(idle),
+            available: Condvar::new(),
+        })
+    }
+
+    /// 借出一条只读连接；池子为空时阻塞等待，直到有连接被归还
+    fn checkout(&self) -> AppResult<PooledReader<'_>> {
+        let mut idle = self
+            .idle
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "读连接池锁获取失败"))?;
+
+        while idle.is_empty() {
+            idle = self
+                .available
+                .wait(idle)
+                .map_err(|_| AppError::new(ErrorCode::LocalIoError, "读连接池等待失败"))?;
+        }
+
+        let conn = idle.pop().expect("刚检查过 idle 非空");
+        Ok(PooledReader {
+            conn: Some(conn),
+            pool: self,
+        })
+    }
+}
+
+/// 从 [`ReaderPool`] 借出的只读连接句柄；`Drop` 时自动归还并唤醒下一个等待者
+struct PooledReader<'a> {
+    conn: Option<Connection>,
+    pool: &'a ReaderPool,
+}
+
+impl std::ops::Deref for PooledReader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("checkout 时已填充")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            if let Ok(mut idle) = self.pool.idle.lock() {
+                idle.push(conn);
+                self.pool.available.notify_one();
+            }
+        }
+    }
+}
+
 /// 数据库存储服务
+///
+/// 写操作全部串行化在 `writer` 唯一的互斥锁后面；`SELECT`-only 的查询改走
+/// `reader_pool` 借来的只读连接，WAL 模式下读写可以并发执行，不会被一次慢写操作
+/// （比如批量写 `transfer_history`）卡住。方法通过 [`Self::with_read`]/
+/// [`Self::with_write`] 访问连接，不直接持有锁（事务类操作例外，见 `import_encrypted`）。
 pub struct Database {
-    conn: Mutex<Connection>,
+    writer: Mutex<Connection>,
+    reader_pool: ReaderPool,
+    /// `vault_unlock` 派生出的密钥库主密钥，缓存在内存中直到进程退出；
+    /// 未解锁（`None`）时 `secret_put`/`secret_get` 均返回错误
+    vault_key: Mutex<Option<[u8; 32]>>,
 }
 
 impl Database {
     /// 初始化数据库
     pub fn init() -> AppResult<Self> {
-        let db_path = get_database_path();
+        Self::init_at(&get_database_path())
+    }
+
+    /// 在指定路径初始化数据库（供其他服务的测试用例构造隔离的数据库实例）
+    #[cfg(test)]
+    pub fn init_at(db_path: &std::path::Path) -> AppResult<Self> {
+        Self::open_and_migrate(db_path)
+    }
+
+    /// 在只读连接上执行查询；可以与 `with_write` 并发运行
+    fn with_read<T>(&self, f: impl FnOnce(&Connection) -> AppResult<T>) -> AppResult<T> {
+        let conn = self.reader_pool.checkout()?;
+        f(&conn)
+    }
+
+    /// 在唯一的写连接上执行操作；所有写操作彼此串行化
+    fn with_write<T>(&self, f: impl FnOnce(&Connection) -> AppResult<T>) -> AppResult<T> {
+        let conn = self
+            .writer
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        f(&conn)
+    }
 
+    fn open_and_migrate(db_path: &std::path::Path) -> AppResult<Self> {
         // 确保目录存在
         if let Some(parent) = db_path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|e| AppError::local_io_error(format!("无法创建数据目录: {}", e)))?;
         }
 
-        let conn = Connection::open(&db_path)
+        let conn = Connection::open(db_path)
             .map_err(|e| AppError::local_io_error(format!("无法打开数据库: {}", e)))?;
 
         // 启用 WAL 模式，提升并发性能
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")?;
 
+        // 迁移必须在只读连接池打开之前做完，保证读连接看到的是迁移后的最终 schema
+        Self::migrate(&conn)?;
+
+        let reader_pool = ReaderPool::open(db_path, DEFAULT_READER_POOL_SIZE)?;
+
         let db = Self {
-            conn: Mutex::new(conn),
+            writer: Mutex::new(conn),
+            reader_pool,
+            vault_key: Mutex::new(None),
         };
 
-        // 执行迁移
-        db.migrate()?;
-
         tracing::info!(path = %db_path.display(), "数据库初始化完成");
 
         Ok(db)
@@ -103,6 +287,18 @@ impl Database {
                 connection_timeout_secs INTEGER NOT NULL DEFAULT 30,
                 transfer_retry_count INTEGER NOT NULL DEFAULT 2,
                 log_level TEXT NOT NULL DEFAULT 'info',
+                parallel_transfer_threshold_mb INTEGER NOT NULL DEFAULT 32,
+                parallel_transfer_streams INTEGER NOT NULL DEFAULT 4,
+                preserve_file_metadata INTEGER NOT NULL DEFAULT 1,
+                speed_limit_kbps INTEGER NOT NULL DEFAULT 0,
+                verify_transfer_checksum INTEGER NOT NULL DEFAULT 0,
+                checksum_command TEXT NOT NULL DEFAULT 'sha256sum',
+                checksum_verify_min_size_mb INTEGER NOT NULL DEFAULT 10,
+                pipeline_window_size INTEGER NOT NULL DEFAULT 4,
+                max_open_local_files INTEGER NOT NULL DEFAULT 16,
+                terminal_idle_timeout_secs INTEGER NOT NULL DEFAULT 0,
+                retention_days INTEGER NOT NULL DEFAULT 90,
+                known_hosts_mirror_path TEXT,
                 updated_at INTEGER NOT NULL
             );
             INSERT OR IGNORE INTO settings (id, updated_at) VALUES (1, 0);
@@ -112,15 +308,12 @@ impl Database {
     }
 
     /// 执行数据库迁移
-    fn migrate(&self) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
-
+    /// 执行迁移；在只读连接池打开之前、独占 `conn` 时调用，所以直接拿 `&Connection`
+    /// 而不是走 `with_write`（此时 `Database` 还没构造出来）
+    fn migrate(conn: &Connection) -> AppResult<()> {
         // 确保 settings 表存在（无论版本号如何）
         // 这是为了修复之前版本号已更新但表未创建的问题
-        Self::ensure_settings_table(&conn)?;
+        Self::ensure_settings_table(conn)?;
 
         // 获取当前版本
         let current_version: i32 = conn
@@ -202,256 +395,787 @@ impl Database {
 
         // 版本 1 -> 2: 迁移 settings.json 数据
         if current_version < 2 {
-            self.migrate_settings_from_json(&conn)?;
+            Self::migrate_settings_from_json(conn)?;
         }
 
-        // 更新版本号
-        conn.execute_batch(&format!("PRAGMA user_version = {}", DB_VERSION))?;
+        // 版本 2 -> 3: 持久化传输任务队列
+        if current_version < 3 {
+            conn.execute_batch(
+                r#"
+                -- 传输任务队列表（用于崩溃/重启后恢复未完成的任务）
+                CREATE TABLE IF NOT EXISTS transfer_tasks (
+                    task_id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    direction TEXT NOT NULL CHECK(direction IN ('upload', 'download')),
+                    local_path TEXT NOT NULL,
+                    remote_path TEXT NOT NULL,
+                    file_name TEXT NOT NULL,
+                    status TEXT NOT NULL CHECK(status IN ('waiting', 'running', 'success', 'failed', 'canceled')),
+                    transferred INTEGER NOT NULL DEFAULT 0,
+                    total INTEGER,
+                    error_message TEXT,
+                    error_code TEXT,
+                    retryable INTEGER,
+                    created_at INTEGER NOT NULL,
+                    completed_at INTEGER,
+                    resume_offset INTEGER,
+                    source_mtime INTEGER,
+                    retry_count INTEGER NOT NULL DEFAULT 0,
+                    next_attempt_at INTEGER
+                );
 
-        tracing::info!("数据库迁移完成");
+                -- 按状态查询（重启恢复、调度器 tick）
+                CREATE INDEX IF NOT EXISTS idx_transfer_tasks_status
+                ON transfer_tasks(status);
+                "#,
+            )?;
+        }
 
-        Ok(())
-    }
+        // 版本 3 -> 4: 目录同步计划
+        if current_version < 4 {
+            conn.execute_batch(
+                r#"
+                -- 目录同步计划表
+                CREATE TABLE IF NOT EXISTS sync_schedules (
+                    schedule_id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    local_dir TEXT NOT NULL,
+                    remote_dir TEXT NOT NULL,
+                    direction TEXT NOT NULL CHECK(direction IN ('upload', 'download')),
+                    mirror INTEGER NOT NULL DEFAULT 0,
+                    recurrence TEXT NOT NULL,
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    created_at INTEGER NOT NULL,
+                    last_run_at INTEGER,
+                    next_run_at INTEGER NOT NULL
+                );
 
-    // ============================================
-    // Profile 操作
-    // ============================================
+                -- 按到期时间查询（调度器 tick）
+                CREATE INDEX IF NOT EXISTS idx_sync_schedules_next_run
+                ON sync_schedules(next_run_at);
+                "#,
+            )?;
+        }
 
-    /// 获取所有连接配置
-    pub fn profile_list(&self) -> AppResult<Vec<Profile>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        // 版本 4 -> 5: 多流并行传输设置
+        if current_version < 5 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN parallel_transfer_threshold_mb INTEGER NOT NULL DEFAULT 32;
+                ALTER TABLE settings ADD COLUMN parallel_transfer_streams INTEGER NOT NULL DEFAULT 4;
+                "#,
+            )?;
+        }
 
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT id, name, host, port, username, auth_type,
-                   password_ref, private_key_path, passphrase_ref,
-                   initial_path, created_at, updated_at
-            FROM profiles
-            ORDER BY updated_at DESC
-            "#,
-        )?;
+        // 版本 5 -> 6: 传输后保留文件权限/修改时间
+        if current_version < 6 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN preserve_file_metadata INTEGER NOT NULL DEFAULT 1;
+                "#,
+            )?;
+        }
 
-        let profiles = stmt
-            .query_map([], |row| {
-                Ok(Profile {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    host: row.get(2)?,
-                    port: row.get(3)?,
-                    username: row.get(4)?,
-                    auth_type: parse_auth_type(row.get::<_, String>(5)?),
-                    password_ref: row.get(6)?,
-                    private_key_path: row.get(7)?,
-                    passphrase_ref: row.get(8)?,
-                    initial_path: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(profiles)
-    }
+        // 版本 6 -> 7: 传输限速
+        if current_version < 7 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN speed_limit_kbps INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE transfer_tasks ADD COLUMN speed_limit_bytes_per_sec INTEGER;
+                "#,
+            )?;
+        }
 
-    /// 获取单个连接配置
-    pub fn profile_get(&self, id: &str) -> AppResult<Option<Profile>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        // 版本 7 -> 8: 传输后校验和校验
+        if current_version < 8 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN verify_transfer_checksum INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE settings ADD COLUMN checksum_command TEXT NOT NULL DEFAULT 'sha256sum';
+                "#,
+            )?;
+        }
 
-        let profile = conn
-            .query_row(
+        // 版本 8 -> 9: 单流传输读写预读窗口
+        if current_version < 9 {
+            conn.execute_batch(
                 r#"
-                SELECT id, name, host, port, username, auth_type,
-                       password_ref, private_key_path, passphrase_ref,
-                       initial_path, created_at, updated_at
-                FROM profiles
-                WHERE id = ?
+                ALTER TABLE settings ADD COLUMN pipeline_window_size INTEGER NOT NULL DEFAULT 4;
                 "#,
-                [id],
-                |row| {
-                    Ok(Profile {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        host: row.get(2)?,
-                        port: row.get(3)?,
-                        username: row.get(4)?,
-                        auth_type: parse_auth_type(row.get::<_, String>(5)?),
-                        password_ref: row.get(6)?,
-                        private_key_path: row.get(7)?,
-                        passphrase_ref: row.get(8)?,
-                        initial_path: row.get(9)?,
-                        created_at: row.get(10)?,
-                        updated_at: row.get(11)?,
-                    })
-                },
-            )
-            .optional()?;
+            )?;
+        }
 
-        Ok(profile)
-    }
+        // 版本 9 -> 10: 目录递归传输的批量父任务
+        if current_version < 10 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE transfer_tasks ADD COLUMN parent_task_id TEXT;
+                ALTER TABLE transfer_tasks ADD COLUMN is_batch INTEGER NOT NULL DEFAULT 0;
 
-    /// 创建或更新连接配置
-    pub fn profile_upsert(&self, profile: &Profile) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+                CREATE INDEX IF NOT EXISTS idx_transfer_tasks_parent
+                ON transfer_tasks(parent_task_id);
+                "#,
+            )?;
+        }
 
-        conn.execute(
-            r#"
-            INSERT INTO profiles (
-                id, name, host, port, username, auth_type,
-                password_ref, private_key_path, passphrase_ref,
-                initial_path, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(id) DO UPDATE SET
-                name = excluded.name,
-                host = excluded.host,
-                port = excluded.port,
-                username = excluded.username,
-                auth_type = excluded.auth_type,
-                password_ref = excluded.password_ref,
-                private_key_path = excluded.private_key_path,
-                passphrase_ref = excluded.passphrase_ref,
-                initial_path = excluded.initial_path,
-                updated_at = excluded.updated_at
-            "#,
-            params![
-                profile.id,
-                profile.name,
-                profile.host,
-                profile.port,
-                profile.username,
-                profile.auth_type.as_str(),
-                profile.password_ref,
-                profile.private_key_path,
-                profile.passphrase_ref,
-                profile.initial_path,
-                profile.created_at,
-                profile.updated_at,
-            ],
-        )?;
+        // 版本 10 -> 11: 校验和校验的大小阈值与单任务覆盖
+        if current_version < 11 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN checksum_verify_min_size_mb INTEGER NOT NULL DEFAULT 10;
+                ALTER TABLE transfer_tasks ADD COLUMN verify_checksum_override INTEGER;
+                "#,
+            )?;
+        }
 
-        tracing::debug!(profile_id = %profile.id, "Profile 已保存");
+        // 版本 11 -> 12: 限制同时打开的本地文件句柄数
+        if current_version < 12 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN max_open_local_files INTEGER NOT NULL DEFAULT 16;
+                "#,
+            )?;
+        }
 
-        Ok(())
-    }
+        // 版本 12 -> 13: 终端空闲超时自动关闭
+        if current_version < 13 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN terminal_idle_timeout_secs INTEGER NOT NULL DEFAULT 0;
+                "#,
+            )?;
+        }
 
-    /// 删除连接配置
-    pub fn profile_delete(&self, id: &str) -> AppResult<bool> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        // 版本 13 -> 14: 最近连接改为按 frecency 排序，需要记录访问次数
+        if current_version < 14 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE recent_connections ADD COLUMN visit_count INTEGER NOT NULL DEFAULT 1;
+                "#,
+            )?;
+        }
 
-        let affected = conn.execute("DELETE FROM profiles WHERE id = ?", [id])?;
+        // 版本 14 -> 15: 新增传输历史保留天数设置，供 run_maintenance 清理旧记录使用
+        if current_version < 15 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE settings ADD COLUMN retention_days INTEGER NOT NULL DEFAULT 90;
+                "#,
+            )?;
+        }
 
-        if affected > 0 {
-            tracing::info!(profile_id = %id, "Profile 已删除");
+        // 版本 15 -> 16: 为传输历史建立 FTS5 全文索引，支持 transfer_history_search。
+        // 部分精简编译的 SQLite 可能没有 FTS5 模块，此时跳过索引创建并记录警告，
+        // transfer_history_search 在表不存在时会返回明确的错误而不是 panic
+        if current_version < 16 {
+            match conn.execute_batch(
+                r#"
+                CREATE VIRTUAL TABLE transfer_history_fts USING fts5(
+                    id UNINDEXED,
+                    local_path,
+                    remote_path,
+                    error_message
+                );
+                "#,
+            ) {
+                Ok(()) => {
+                    conn.execute_batch(
+                        r#"
+                        CREATE TRIGGER transfer_history_fts_ai AFTER INSERT ON transfer_history BEGIN
+                            INSERT INTO transfer_history_fts(id, local_path, remote_path, error_message)
+                            VALUES (new.id, new.local_path, new.remote_path, new.error_message);
+                        END;
+
+                        CREATE TRIGGER transfer_history_fts_ad AFTER DELETE ON transfer_history BEGIN
+                            DELETE FROM transfer_history_fts WHERE id = old.id;
+                        END;
+
+                        CREATE TRIGGER transfer_history_fts_au AFTER UPDATE ON transfer_history BEGIN
+                            DELETE FROM transfer_history_fts WHERE id = old.id;
+                            INSERT INTO transfer_history_fts(id, local_path, remote_path, error_message)
+                            VALUES (new.id, new.local_path, new.remote_path, new.error_message);
+                        END;
+
+                        INSERT INTO transfer_history_fts(id, local_path, remote_path, error_message)
+                        SELECT id, local_path, remote_path, error_message FROM transfer_history;
+                        "#,
+                    )?;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "当前 SQLite 未编译 FTS5 模块，跳过全文检索索引创建");
+                }
+            }
         }
 
-        Ok(affected > 0)
-    }
+        // 版本 16 -> 17: 新增加密密钥库（vault_meta 存放 scrypt salt/参数，
+        // secrets 存放加密后的凭据），供 vault_unlock/secret_put/secret_get 使用
+        if current_version < 17 {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS vault_meta (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    salt BLOB NOT NULL,
+                    scrypt_log_n INTEGER NOT NULL
+                );
 
-    // ============================================
-    // 最近连接记录
-    // ============================================
+                CREATE TABLE IF NOT EXISTS secrets (
+                    ref TEXT PRIMARY KEY,
+                    nonce BLOB NOT NULL,
+                    ciphertext BLOB NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+                "#,
+            )?;
+        }
 
-    /// 获取最近连接记录
-    pub fn recent_connections_list(&self) -> AppResult<Vec<RecentConnection>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        // 版本 17 -> 18: known_hosts 增加原始公钥字段，供 known_hosts_export/
+        // 镜像模式重建合法的 OpenSSH known_hosts 行（仅存指纹是单向哈希，无法逆推）；
+        // settings 增加镜像模式目标文件路径
+        if current_version < 18 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE known_hosts ADD COLUMN public_key_b64 TEXT;
+                ALTER TABLE settings ADD COLUMN known_hosts_mirror_path TEXT;
+                "#,
+            )?;
+        }
 
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT id, profile_id, profile_name, host, username, connected_at
-            FROM recent_connections
-            ORDER BY connected_at DESC
-            LIMIT ?
-            "#,
-        )?;
+        // 版本 18 -> 19: transfer_history 增加 profile_id，支持按服务器筛选/统计传输历史
+        // （历史记录此前只关联临时的 session_id，同一 profile 多次连接无法串联查询）
+        if current_version < 19 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE transfer_history ADD COLUMN profile_id TEXT;
+                CREATE INDEX IF NOT EXISTS idx_transfer_history_profile_id ON transfer_history(profile_id);
+                "#,
+            )?;
+        }
 
-        let records = stmt
-            .query_map([MAX_RECENT_CONNECTIONS], |row| {
-                Ok(RecentConnection {
-                    id: row.get(0)?,
-                    profile_id: row.get(1)?,
-                    profile_name: row.get(2)?,
-                    host: row.get(3)?,
-                    username: row.get(4)?,
-                    connected_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        // 版本 19 -> 20: 重建 profiles 表——
+        // 1) 放宽 auth_type 的 CHECK 约束以接受 'agent'（SSH agent 认证类型落库时会被旧约束
+        //    直接拒绝，SQLite 不支持就地修改 CHECK 约束，只能重建表）；
+        // 2) 新增 private_key_ref 列，托管加密存储的私钥内容引用（与 password_ref/
+        //    passphrase_ref 同一套方案）
+        if current_version < 20 {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE profiles_new (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    host TEXT NOT NULL,
+                    port INTEGER NOT NULL DEFAULT 22,
+                    username TEXT NOT NULL,
+                    auth_type TEXT NOT NULL CHECK(auth_type IN ('password', 'key', 'agent')),
+                    password_ref TEXT,
+                    private_key_path TEXT,
+                    passphrase_ref TEXT,
+                    private_key_ref TEXT,
+                    initial_path TEXT,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
 
-        Ok(records)
-    }
+                INSERT INTO profiles_new (
+                    id, name, host, port, username, auth_type, password_ref,
+                    private_key_path, passphrase_ref, initial_path, created_at, updated_at
+                )
+                SELECT
+                    id, name, host, port, username, auth_type, password_ref,
+                    private_key_path, passphrase_ref, initial_path, created_at, updated_at
+                FROM profiles;
+
+                DROP TABLE profiles;
+                ALTER TABLE profiles_new RENAME TO profiles;
+                "#,
+            )?;
+        }
 
-    /// 添加最近连接记录
-    pub fn recent_connection_add(&self, record: &RecentConnection) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        // 版本 20 -> 21: 新增应用内托管密钥表（`key_generate` 在应用内生成的密钥对，
+        // 私钥内容托管在安全存储，这里只存可公开展示的元数据）；profiles 增加
+        // managed_key_id 列，供 Key 认证引用一个托管密钥而不是手填私钥路径/内容
+        if current_version < 21 {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS managed_keys (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    key_type TEXT NOT NULL,
+                    public_key TEXT NOT NULL,
+                    fingerprint TEXT NOT NULL,
+                    private_key_ref TEXT NOT NULL,
+                    encrypted INTEGER NOT NULL DEFAULT 0,
+                    created_at INTEGER NOT NULL
+                );
 
-        // 删除该 profile 的旧记录（保持最新的在顶部）
-        conn.execute(
-            "DELETE FROM recent_connections WHERE profile_id = ?",
-            [&record.profile_id],
-        )?;
+                ALTER TABLE profiles ADD COLUMN managed_key_id TEXT;
+                "#,
+            )?;
+        }
 
-        // 插入新记录
-        conn.execute(
-            r#"
-            INSERT INTO recent_connections (
-                id, profile_id, profile_name, host, username, connected_at
-            ) VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-            params![
-                record.id,
-                record.profile_id,
-                record.profile_name,
-                record.host,
-                record.username,
-                record.connected_at,
-            ],
-        )?;
+        // 版本 21 -> 22: 重建 profiles 表，放宽 auth_type 的 CHECK 约束以接受
+        // 'keyboard_interactive'（键盘交互式认证落库时会被旧约束直接拒绝，
+        // SQLite 不支持就地修改 CHECK 约束，只能重建表，做法与版本 19 -> 20 一致）
+        if current_version < 22 {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE profiles_new (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    host TEXT NOT NULL,
+                    port INTEGER NOT NULL DEFAULT 22,
+                    username TEXT NOT NULL,
+                    auth_type TEXT NOT NULL CHECK(auth_type IN ('password', 'key', 'agent', 'keyboard_interactive')),
+                    password_ref TEXT,
+                    private_key_path TEXT,
+                    passphrase_ref TEXT,
+                    private_key_ref TEXT,
+                    managed_key_id TEXT,
+                    initial_path TEXT,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
 
-        // 清理超出限制的旧记录
-        conn.execute(
-            r#"
-            DELETE FROM recent_connections
-            WHERE id NOT IN (
-                SELECT id FROM recent_connections
-                ORDER BY connected_at DESC
-                LIMIT ?
-            )
-            "#,
-            [MAX_RECENT_CONNECTIONS],
-        )?;
+                INSERT INTO profiles_new (
+                    id, name, host, port, username, auth_type, password_ref,
+                    private_key_path, passphrase_ref, private_key_ref, managed_key_id,
+                    initial_path, created_at, updated_at
+                )
+                SELECT
+                    id, name, host, port, username, auth_type, password_ref,
+                    private_key_path, passphrase_ref, private_key_ref, managed_key_id,
+                    initial_path, created_at, updated_at
+                FROM profiles;
+
+                DROP TABLE profiles;
+                ALTER TABLE profiles_new RENAME TO profiles;
+                "#,
+            )?;
+        }
+
+        // 版本 22 -> 23: 新增 HostKey/KEX/加密算法偏好，供连接老旧服务器时
+        // opt-in 兼容已废弃的算法（不影响 CHECK 约束，直接 ALTER TABLE 即可）
+        if current_version < 23 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE profiles ADD COLUMN host_key_algorithms TEXT;
+                ALTER TABLE profiles ADD COLUMN kex_algorithms TEXT;
+                ALTER TABLE profiles ADD COLUMN ciphers TEXT;
+                "#,
+            )?;
+        }
+
+        // 版本 23 -> 24: known_hosts 增加 revoked 标记，支持 known_hosts_import 将
+        // `@revoked` 行落库为拒绝条目，而不是像此前那样直接丢弃——
+        // 撤销条目与信任条目复用同一张表/同一套 (host, port) 唯一约束，
+        // 由 verify_hostkey 在查到 revoked = 1 时直接拒绝，不再走 Matched/Mismatch 分支
+        if current_version < 24 {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE known_hosts ADD COLUMN revoked INTEGER NOT NULL DEFAULT 0;
+                "#,
+            )?;
+        }
+
+        // 更新版本号
+        conn.execute_batch(&format!("PRAGMA user_version = {}", DB_VERSION))?;
+
+        tracing::info!("数据库迁移完成");
 
         Ok(())
     }
 
+    // ============================================
+    // Profile 操作
+    // ============================================
+
+    /// 获取所有连接配置
+    pub fn profile_list(&self) -> AppResult<Vec<Profile>> {
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT id, name, host, port, username, auth_type,
+                       password_ref, private_key_path, passphrase_ref,
+                       initial_path, created_at, updated_at, private_key_ref,
+                       managed_key_id, host_key_algorithms, kex_algorithms, ciphers
+                FROM profiles
+                ORDER BY updated_at DESC
+                "#,
+            )?;
+
+            let profiles = stmt
+                .query_map([], |row| {
+                    Ok(Profile {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        host: row.get(2)?,
+                        port: row.get(3)?,
+                        username: row.get(4)?,
+                        auth: Auth::from_columns(
+                            &row.get::<_, String>(5)?,
+                            row.get(6)?,
+                            row.get(7)?,
+                            row.get(8)?,
+                            row.get(12)?,
+                            row.get(13)?,
+                        ),
+                        initial_path: row.get(9)?,
+                        created_at: row.get(10)?,
+                        updated_at: row.get(11)?,
+                        host_key_algorithms: row.get(14)?,
+                        kex_algorithms: row.get(15)?,
+                        ciphers: row.get(16)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(profiles)
+        })
+    }
+
+    /// 获取单个连接配置
+    pub fn profile_get(&self, id: &str) -> AppResult<Option<Profile>> {
+        self.with_read(|conn| {
+            let profile = conn
+                .query_row(
+                    r#"
+                    SELECT id, name, host, port, username, auth_type,
+                           password_ref, private_key_path, passphrase_ref,
+                           initial_path, created_at, updated_at, private_key_ref,
+                           managed_key_id, host_key_algorithms, kex_algorithms, ciphers
+                    FROM profiles
+                    WHERE id = ?
+                    "#,
+                    [id],
+                    |row| {
+                        Ok(Profile {
+                            id: row.get(0)?,
+                            name: row.get(1)?,
+                            host: row.get(2)?,
+                            port: row.get(3)?,
+                            username: row.get(4)?,
+                            auth: Auth::from_columns(
+                                &row.get::<_, String>(5)?,
+                                row.get(6)?,
+                                row.get(7)?,
+                                row.get(8)?,
+                                row.get(12)?,
+                                row.get(13)?,
+                            ),
+                            initial_path: row.get(9)?,
+                            created_at: row.get(10)?,
+                            updated_at: row.get(11)?,
+                            host_key_algorithms: row.get(14)?,
+                            kex_algorithms: row.get(15)?,
+                            ciphers: row.get(16)?,
+                        })
+                    },
+                )
+                .optional()?;
+
+            Ok(profile)
+        })
+    }
+
+    /// 创建或更新连接配置
+    pub fn profile_upsert(&self, profile: &Profile) -> AppResult<()> {
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO profiles (
+                    id, name, host, port, username, auth_type,
+                    password_ref, private_key_path, passphrase_ref,
+                    initial_path, created_at, updated_at, private_key_ref,
+                    managed_key_id, host_key_algorithms, kex_algorithms, ciphers
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    host = excluded.host,
+                    port = excluded.port,
+                    username = excluded.username,
+                    auth_type = excluded.auth_type,
+                    password_ref = excluded.password_ref,
+                    private_key_path = excluded.private_key_path,
+                    passphrase_ref = excluded.passphrase_ref,
+                    initial_path = excluded.initial_path,
+                    updated_at = excluded.updated_at,
+                    private_key_ref = excluded.private_key_ref,
+                    managed_key_id = excluded.managed_key_id,
+                    host_key_algorithms = excluded.host_key_algorithms,
+                    kex_algorithms = excluded.kex_algorithms,
+                    ciphers = excluded.ciphers
+                "#,
+                params![
+                    profile.id,
+                    profile.name,
+                    profile.host,
+                    profile.port,
+                    profile.username,
+                    profile.auth.type_str(),
+                    profile.auth.password_ref(),
+                    profile.auth.private_key_path(),
+                    profile.auth.passphrase_ref(),
+                    profile.initial_path,
+                    profile.created_at,
+                    profile.updated_at,
+                    profile.auth.private_key_ref(),
+                    profile.auth.managed_key_id(),
+                    profile.host_key_algorithms,
+                    profile.kex_algorithms,
+                    profile.ciphers,
+                ],
+            )?;
+
+            tracing::debug!(profile_id = %profile.id, "Profile 已保存");
+
+            Ok(())
+        })
+    }
+
+    /// 删除连接配置
+    pub fn profile_delete(&self, id: &str) -> AppResult<bool> {
+        self.with_write(|conn| {
+            let affected = conn.execute("DELETE FROM profiles WHERE id = ?", [id])?;
+
+            if affected > 0 {
+                tracing::info!(profile_id = %id, "Profile 已删除");
+            }
+
+            Ok(affected > 0)
+        })
+    }
+
+    // ============================================
+    // 托管密钥 (managed_keys)
+    // ============================================
+
+    /// 插入一条托管密钥元数据
+    ///
+    /// `private_key_ref` 由调用方（`key_manager::create_managed_key`）先存入安全存储后传入，
+    /// 这里只落公开元数据，私钥内容本身不经过这张表
+    pub fn managed_key_insert(&self, key: &ManagedKey, private_key_ref: &str) -> AppResult<()> {
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO managed_keys (
+                    id, name, key_type, public_key, fingerprint,
+                    private_key_ref, encrypted, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    key.id,
+                    key.name,
+                    key.key_type,
+                    key.public_key,
+                    key.fingerprint,
+                    private_key_ref,
+                    key.encrypted as i64,
+                    key.created_at,
+                ],
+            )?;
+
+            tracing::debug!(key_id = %key.id, "托管密钥元数据已保存");
+
+            Ok(())
+        })
+    }
+
+    /// 列出所有托管密钥
+    pub fn managed_key_list(&self) -> AppResult<Vec<ManagedKey>> {
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT id, name, key_type, public_key, fingerprint, encrypted, created_at
+                FROM managed_keys
+                ORDER BY created_at DESC
+                "#,
+            )?;
+
+            let keys = stmt
+                .query_map([], |row| {
+                    Ok(ManagedKey {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        key_type: row.get(2)?,
+                        public_key: row.get(3)?,
+                        fingerprint: row.get(4)?,
+                        encrypted: row.get::<_, i64>(5)? != 0,
+                        created_at: row.get(6)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(keys)
+        })
+    }
+
+    /// 获取单个托管密钥及其私钥在安全存储中的引用
+    pub fn managed_key_get(&self, id: &str) -> AppResult<Option<(ManagedKey, String)>> {
+        self.with_read(|conn| {
+            let result = conn
+                .query_row(
+                    r#"
+                    SELECT id, name, key_type, public_key, fingerprint, encrypted,
+                           created_at, private_key_ref
+                    FROM managed_keys
+                    WHERE id = ?
+                    "#,
+                    [id],
+                    |row| {
+                        let key = ManagedKey {
+                            id: row.get(0)?,
+                            name: row.get(1)?,
+                            key_type: row.get(2)?,
+                            public_key: row.get(3)?,
+                            fingerprint: row.get(4)?,
+                            encrypted: row.get::<_, i64>(5)? != 0,
+                            created_at: row.get(6)?,
+                        };
+                        let private_key_ref: String = row.get(7)?;
+                        Ok((key, private_key_ref))
+                    },
+                )
+                .optional()?;
+
+            Ok(result)
+        })
+    }
+
+    /// 删除托管密钥元数据（私钥本身的删除由调用方负责，见 `key_manager::delete_managed_key`）
+    pub fn managed_key_delete(&self, id: &str) -> AppResult<bool> {
+        self.with_write(|conn| {
+            let affected = conn.execute("DELETE FROM managed_keys WHERE id = ?", [id])?;
+
+            if affected > 0 {
+                tracing::info!(key_id = %id, "托管密钥元数据已删除");
+            }
+
+            Ok(affected > 0)
+        })
+    }
+
+    // ============================================
+    // 最近连接记录
+    // ============================================
+
+    /// 获取最近连接记录
+    pub fn recent_connections_list(&self) -> AppResult<Vec<RecentConnection>> {
+        self.with_read(|conn| {
+            let now = chrono::Utc::now().timestamp_millis();
+
+            let query = format!(
+                r#"
+                SELECT id, profile_id, profile_name, host, username, connected_at, visit_count
+                FROM recent_connections
+                ORDER BY {score} DESC, connected_at DESC
+                LIMIT :limit
+                "#,
+                score = FRECENCY_SCORE_SQL
+            );
+            let mut stmt = conn.prepare(&query)?;
+
+            let records = stmt
+                .query_map(
+                    named_params! {":now": now, ":limit": MAX_RECENT_CONNECTIONS},
+                    |row| {
+                        Ok(RecentConnection {
+                            id: row.get(0)?,
+                            profile_id: row.get(1)?,
+                            profile_name: row.get(2)?,
+                            host: row.get(3)?,
+                            username: row.get(4)?,
+                            connected_at: row.get(5)?,
+                            visit_count: row.get(6)?,
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(records)
+        })
+    }
+
+    /// 添加最近连接记录
+    ///
+    /// 同一 profile 再次连接时不再删除重建，而是递增 `visit_count` 并刷新
+    /// `connected_at`，这样频繁使用的主机即使不是"最近一次"也能在列表里按 frecency
+    /// 靠前（见 [`FRECENCY_SCORE_SQL`]）。
+    pub fn recent_connection_add(&self, record: &RecentConnection) -> AppResult<()> {
+        self.with_write(|conn| {
+            let existing_id: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM recent_connections WHERE profile_id = ?",
+                    [&record.profile_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            match existing_id {
+                Some(id) => {
+                    conn.execute(
+                        r#"
+                        UPDATE recent_connections
+                        SET profile_name = ?, host = ?, username = ?, connected_at = ?,
+                            visit_count = visit_count + 1
+                        WHERE id = ?
+                        "#,
+                        params![
+                            record.profile_name,
+                            record.host,
+                            record.username,
+                            record.connected_at,
+                            id,
+                        ],
+                    )?;
+                }
+                None => {
+                    conn.execute(
+                        r#"
+                        INSERT INTO recent_connections (
+                            id, profile_id, profile_name, host, username, connected_at, visit_count
+                        ) VALUES (?, ?, ?, ?, ?, ?, 1)
+                        "#,
+                        params![
+                            record.id,
+                            record.profile_id,
+                            record.profile_name,
+                            record.host,
+                            record.username,
+                            record.connected_at,
+                        ],
+                    )?;
+                }
+            }
+
+            // 清理超出限制的旧记录，按 frecency 分数保留前 MAX_RECENT_CONNECTIONS 条
+            let cleanup_query = format!(
+                r#"
+                DELETE FROM recent_connections
+                WHERE id NOT IN (
+                    SELECT id FROM recent_connections
+                    ORDER BY {score} DESC, connected_at DESC
+                    LIMIT :limit
+                )
+                "#,
+                score = FRECENCY_SCORE_SQL
+            );
+            conn.execute(
+                &cleanup_query,
+                named_params! {":now": record.connected_at, ":limit": MAX_RECENT_CONNECTIONS},
+            )?;
+
+            Ok(())
+        })
+    }
+
     /// 清空最近连接记录
     pub fn recent_connections_clear(&self) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
-
-        conn.execute("DELETE FROM recent_connections", [])?;
+        self.with_write(|conn| {
+            conn.execute("DELETE FROM recent_connections", [])?;
 
-        tracing::info!("最近连接记录已清空");
+            tracing::info!("最近连接记录已清空");
 
-        Ok(())
+            Ok(())
+        })
     }
 
     // ============================================
@@ -460,76 +1184,205 @@ impl Database {
 
     /// 检查 HostKey 是否已信任
     pub fn known_host_check(&self, host: &str, port: u16) -> AppResult<Option<String>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        self.with_read(|conn| {
+            let fingerprint: Option<String> = conn
+                .query_row(
+                    "SELECT fingerprint FROM known_hosts WHERE host = ? AND port = ?",
+                    params![host, port],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(fingerprint)
+        })
+    }
 
-        let fingerprint: Option<String> = conn
-            .query_row(
-                "SELECT fingerprint FROM known_hosts WHERE host = ? AND port = ?",
-                params![host, port],
-                |row| row.get(0),
-            )
-            .optional()?;
+    /// 同 [`Self::known_host_check`]，但连 `key_type` 一并取出，供
+    /// [`crate::services::security_service::host_key_verdict`] 按算法判定是否真的"不匹配"——
+    /// 同一个 host 只要换过密钥类型（如从 `ssh-rsa` 切到 `ssh-ed25519`），指纹必然不同，
+    /// 不应该和真正的中间人攻击混为一谈
+    pub fn known_host_check_full(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> AppResult<Option<(String, String)>> {
+        self.with_read(|conn| {
+            let row: Option<(String, String)> = conn
+                .query_row(
+                    "SELECT key_type, fingerprint FROM known_hosts WHERE host = ? AND port = ?",
+                    params![host, port],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+
+            Ok(row)
+        })
+    }
+
+    /// 取出所有哈希 host 字段的记录（`host` 列以 `|1|` 开头），供
+    /// [`crate::services::security_service::verify_hostkey`] 在明文精确匹配 miss 后逐条重算
+    /// HMAC-SHA1 比对——哈希值本身无法反查，只能遍历候选集合，见 `ssh-keygen -H` 的格式说明
+    pub fn known_hosts_hashed_entries(&self) -> AppResult<Vec<(String, String, String)>> {
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT host, key_type, fingerprint FROM known_hosts WHERE host LIKE '|1|%'",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<rusqlite::Result<Vec<(String, String, String)>>>()?;
 
-        Ok(fingerprint)
+            Ok(rows)
+        })
     }
 
     /// 保存信任的 HostKey
+    ///
+    /// `public_key_b64` 为 `None` 时（如早期版本遗留的仅指纹记录）不会清除已有的公钥，
+    /// 见 UPSERT 中的 `COALESCE`；仅当调用方确实拿到了原始公钥（导入/首次连接）时才会写入，
+    /// 使 [`Self::known_hosts_export`] 和镜像模式在条件允许时能够重建合法的 OpenSSH 行。
     pub fn known_host_trust(
         &self,
         host: &str,
         port: u16,
         key_type: &str,
         fingerprint: &str,
+        public_key_b64: Option<&str>,
     ) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        self.with_write(|conn| {
+            let now = chrono::Utc::now().timestamp_millis();
 
-        let now = chrono::Utc::now().timestamp_millis();
+            conn.execute(
+                r#"
+                INSERT INTO known_hosts (host, port, key_type, fingerprint, trusted_at, public_key_b64)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT(host, port) DO UPDATE SET
+                    key_type = excluded.key_type,
+                    fingerprint = excluded.fingerprint,
+                    trusted_at = excluded.trusted_at,
+                    public_key_b64 = COALESCE(excluded.public_key_b64, known_hosts.public_key_b64)
+                "#,
+                params![host, port, key_type, fingerprint, now, public_key_b64],
+            )?;
 
-        conn.execute(
-            r#"
-            INSERT INTO known_hosts (host, port, key_type, fingerprint, trusted_at)
-            VALUES (?, ?, ?, ?, ?)
-            ON CONFLICT(host, port) DO UPDATE SET
-                key_type = excluded.key_type,
-                fingerprint = excluded.fingerprint,
-                trusted_at = excluded.trusted_at
-            "#,
-            params![host, port, key_type, fingerprint, now],
-        )?;
+            tracing::info!(
+                host = %host,
+                port = port,
+                key_type = %key_type,
+                "HostKey 已信任"
+            );
 
-        tracing::info!(
-            host = %host,
-            port = port,
-            key_type = %key_type,
-            "HostKey 已信任"
-        );
+            Ok(())
+        })?;
+
+        if let Some(key_b64) = public_key_b64 {
+            self.mirror_known_host_if_configured(host, port, key_type, key_b64);
+        }
 
         Ok(())
     }
 
-    /// 移除信任的 HostKey
-    pub fn known_host_remove(&self, host: &str, port: u16) -> AppResult<bool> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+    /// "镜像模式"：若设置中配置了目标文件，追加写入一行合法的 OpenSSH `known_hosts` 记录，
+    /// 使系统自带的 ssh/scp 等工具也能识别这个刚信任的 HostKey
+    ///
+    /// 仅在写锁释放后执行文件 IO；失败时只记录警告，不影响信任操作本身的结果。
+    fn mirror_known_host_if_configured(
+        &self,
+        host: &str,
+        port: u16,
+        key_type: &str,
+        key_b64: &str,
+    ) {
+        let mirror_path = match self.settings_load() {
+            Ok(settings) => match settings.known_hosts_mirror_path {
+                Some(path) if !path.is_empty() => path,
+                _ => return,
+            },
+            Err(e) => {
+                tracing::warn!(error = %e, "镜像模式：读取设置失败，跳过本次同步");
+                return;
+            }
+        };
 
-        let affected = conn.execute(
-            "DELETE FROM known_hosts WHERE host = ? AND port = ?",
-            params![host, port],
-        )?;
+        let host_field = if host.starts_with("|1|") || port == 22 {
+            host.to_string()
+        } else {
+            format!("[{}]:{}", host, port)
+        };
+        let line = format!("{} {} {}\n", host_field, key_type, key_b64);
 
-        if affected > 0 {
-            tracing::info!(host = %host, port = port, "HostKey 已移除");
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&mirror_path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+
+        match result {
+            Ok(_) => tracing::info!(path = %mirror_path, host = %host, "HostKey 已同步到镜像文件"),
+            Err(e) => tracing::warn!(path = %mirror_path, error = %e, "镜像模式：写入失败"),
         }
+    }
+
+    /// 将某个 host 标记为拒绝连接（撤销），供 [`Self::known_hosts_import`] 落库 OpenSSH
+    /// `known_hosts` 中的 `@revoked` 行；`key_type`/`fingerprint` 仅作记录，
+    /// 实际拒绝判断只看 `revoked` 列，见 [`crate::services::security_service::verify_hostkey`]
+    pub fn known_host_revoke(
+        &self,
+        host: &str,
+        port: u16,
+        key_type: &str,
+        fingerprint: &str,
+    ) -> AppResult<()> {
+        self.with_write(|conn| {
+            let now = chrono::Utc::now().timestamp_millis();
+
+            conn.execute(
+                r#"
+                INSERT INTO known_hosts (host, port, key_type, fingerprint, trusted_at, revoked)
+                VALUES (?, ?, ?, ?, ?, 1)
+                ON CONFLICT(host, port) DO UPDATE SET
+                    key_type = excluded.key_type,
+                    fingerprint = excluded.fingerprint,
+                    trusted_at = excluded.trusted_at,
+                    revoked = 1
+                "#,
+                params![host, port, key_type, fingerprint, now],
+            )?;
+
+            tracing::warn!(host = %host, port = port, "HostKey 已标记为撤销（deny）");
+
+            Ok(())
+        })
+    }
+
+    /// 查询某个 host 是否已被标记为撤销（deny）
+    pub fn known_host_is_revoked(&self, host: &str, port: u16) -> AppResult<bool> {
+        self.with_read(|conn| {
+            let revoked: Option<i64> = conn
+                .query_row(
+                    "SELECT revoked FROM known_hosts WHERE host = ? AND port = ?",
+                    params![host, port],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(revoked.unwrap_or(0) != 0)
+        })
+    }
 
-        Ok(affected > 0)
+    /// 移除信任的 HostKey
+    pub fn known_host_remove(&self, host: &str, port: u16) -> AppResult<bool> {
+        self.with_write(|conn| {
+            let affected = conn.execute(
+                "DELETE FROM known_hosts WHERE host = ? AND port = ?",
+                params![host, port],
+            )?;
+
+            if affected > 0 {
+                tracing::info!(host = %host, port = port, "HostKey 已移除");
+            }
+
+            Ok(affected > 0)
+        })
     }
 
     // ============================================
@@ -538,33 +1391,31 @@ impl Database {
 
     /// 记录传输历史
     pub fn transfer_history_add(&self, record: &TransferHistoryRecord) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
-
-        conn.execute(
-            r#"
-            INSERT INTO transfer_history (
-                id, session_id, direction, local_path, remote_path,
-                file_size, status, error_message, started_at, finished_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            params![
-                record.id,
-                record.session_id,
-                record.direction,
-                record.local_path,
-                record.remote_path,
-                record.file_size,
-                record.status,
-                record.error_message,
-                record.started_at,
-                record.finished_at,
-            ],
-        )?;
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO transfer_history (
+                    id, session_id, profile_id, direction, local_path, remote_path,
+                    file_size, status, error_message, started_at, finished_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    record.id,
+                    record.session_id,
+                    record.profile_id,
+                    record.direction,
+                    record.local_path,
+                    record.remote_path,
+                    record.file_size,
+                    record.status,
+                    record.error_message,
+                    record.started_at,
+                    record.finished_at,
+                ],
+            )?;
 
-        Ok(())
+            Ok(())
+        })
     }
 
     /// 更新传输状态
@@ -575,58 +1426,535 @@ impl Database {
         error_message: Option<&str>,
         finished_at: Option<i64>,
     ) -> AppResult<()> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE transfer_history
+                SET status = ?, error_message = ?, finished_at = ?
+                WHERE id = ?
+                "#,
+                params![status, error_message, finished_at, id],
+            )?;
 
-        conn.execute(
-            r#"
-            UPDATE transfer_history
-            SET status = ?, error_message = ?, finished_at = ?
-            WHERE id = ?
-            "#,
-            params![status, error_message, finished_at, id],
-        )?;
-
-        Ok(())
+            Ok(())
+        })
     }
 
     /// 获取传输历史
     pub fn transfer_history_list(&self, limit: i32) -> AppResult<Vec<TransferHistoryRecord>> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT id, session_id, profile_id, direction, local_path, remote_path,
+                       file_size, status, error_message, started_at, finished_at
+                FROM transfer_history
+                ORDER BY started_at DESC
+                LIMIT ?
+                "#,
+            )?;
 
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT id, session_id, direction, local_path, remote_path,
-                   file_size, status, error_message, started_at, finished_at
-            FROM transfer_history
-            ORDER BY started_at DESC
-            LIMIT ?
-            "#,
-        )?;
+            let records = stmt
+                .query_map([limit], |row| {
+                    Ok(TransferHistoryRecord {
+                        id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        profile_id: row.get(2)?,
+                        direction: row.get(3)?,
+                        local_path: row.get(4)?,
+                        remote_path: row.get(5)?,
+                        file_size: row.get(6)?,
+                        status: row.get(7)?,
+                        error_message: row.get(8)?,
+                        started_at: row.get(9)?,
+                        finished_at: row.get(10)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(records)
+        })
+    }
+
+    /// 全文检索传输历史，按 `local_path`/`remote_path`/`error_message` 匹配，结果按 bm25 相关度排序
+    ///
+    /// 查询词按空白切分，每个词都会被当作前缀查询（例如 `report` 能匹配
+    /// `/var/reports/report.csv`，因为 FTS5 默认分词器已按路径分隔符切出 `report` 这个词），
+    /// 词之间按 FTS5 默认的 AND 语义连接。若当前 SQLite 未编译 FTS5 模块（见 [`Self::migrate`]
+    /// 版本 16 的迁移），索引表不存在，此时返回明确的错误而不是底层的 SQL 语法错误。
+    pub fn transfer_history_search(
+        &self,
+        query: &str,
+        limit: i32,
+    ) -> AppResult<Vec<TransferHistoryRecord>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.with_read(|conn| {
+            let fts_available: bool = conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'transfer_history_fts'",
+                    [],
+                    |_| Ok(true),
+                )
+                .optional()?
+                .unwrap_or(false);
+
+            if !fts_available {
+                return Err(AppError::new(
+                    ErrorCode::Unknown,
+                    "当前 SQLite 未编译 FTS5 模块，无法使用全文检索",
+                ));
+            }
+
+            let match_query = build_fts_match_query(query);
+
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT h.id, h.session_id, h.profile_id, h.direction, h.local_path, h.remote_path,
+                       h.file_size, h.status, h.error_message, h.started_at, h.finished_at
+                FROM transfer_history_fts f
+                JOIN transfer_history h ON h.id = f.id
+                WHERE transfer_history_fts MATCH ?
+                ORDER BY bm25(transfer_history_fts)
+                LIMIT ?
+                "#,
+            )?;
+
+            let records = stmt
+                .query_map(params![match_query, limit], |row| {
+                    Ok(TransferHistoryRecord {
+                        id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        profile_id: row.get(2)?,
+                        direction: row.get(3)?,
+                        local_path: row.get(4)?,
+                        remote_path: row.get(5)?,
+                        file_size: row.get(6)?,
+                        status: row.get(7)?,
+                        error_message: row.get(8)?,
+                        started_at: row.get(9)?,
+                        finished_at: row.get(10)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(records)
+        })
+    }
+
+    /// 按条件查询传输历史，字段为 `None` 的条件不参与过滤
+    pub fn transfers_list(
+        &self,
+        filter: &TransferHistoryFilter,
+    ) -> AppResult<Vec<TransferHistoryRecord>> {
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT id, session_id, profile_id, direction, local_path, remote_path,
+                       file_size, status, error_message, started_at, finished_at
+                FROM transfer_history
+                WHERE (?1 IS NULL OR profile_id = ?1)
+                  AND (?2 IS NULL OR direction = ?2)
+                  AND (?3 IS NULL OR status = ?3)
+                  AND (?4 IS NULL OR started_at >= ?4)
+                  AND (?5 IS NULL OR started_at <= ?5)
+                ORDER BY started_at DESC
+                LIMIT ?6
+                "#,
+            )?;
 
-        let records = stmt
-            .query_map([limit], |row| {
-                Ok(TransferHistoryRecord {
-                    id: row.get(0)?,
-                    session_id: row.get(1)?,
-                    direction: row.get(2)?,
-                    local_path: row.get(3)?,
-                    remote_path: row.get(4)?,
-                    file_size: row.get(5)?,
-                    status: row.get(6)?,
-                    error_message: row.get(7)?,
-                    started_at: row.get(8)?,
-                    finished_at: row.get(9)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(records)
+            let records = stmt
+                .query_map(
+                    params![
+                        filter.profile_id,
+                        filter.direction,
+                        filter.status,
+                        filter.since,
+                        filter.until,
+                        filter.limit,
+                    ],
+                    |row| {
+                        Ok(TransferHistoryRecord {
+                            id: row.get(0)?,
+                            session_id: row.get(1)?,
+                            profile_id: row.get(2)?,
+                            direction: row.get(3)?,
+                            local_path: row.get(4)?,
+                            remote_path: row.get(5)?,
+                            file_size: row.get(6)?,
+                            status: row.get(7)?,
+                            error_message: row.get(8)?,
+                            started_at: row.get(9)?,
+                            finished_at: row.get(10)?,
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(records)
+        })
+    }
+
+    /// 汇总传输历史统计：总字节数、成功/失败/取消计数、平均吞吐率
+    ///
+    /// 吞吐率仅基于已结束（`finished_at` 非空）且耗时大于 0 的记录计算，取各记录吞吐率的
+    /// 算术平均；`since` 为空时统计全部历史
+    pub fn transfers_stats(
+        &self,
+        profile_id: Option<&str>,
+        since: Option<i64>,
+    ) -> AppResult<TransferStats> {
+        self.with_read(|conn| {
+            conn.query_row(
+                r#"
+                SELECT
+                    COUNT(*),
+                    COALESCE(SUM(CASE WHEN status = 'success' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN status = 'canceled' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(file_size), 0),
+                    AVG(
+                        CASE
+                            WHEN finished_at IS NOT NULL AND finished_at > started_at
+                            THEN CAST(file_size AS REAL) / ((finished_at - started_at) / 1000.0)
+                        END
+                    )
+                FROM transfer_history
+                WHERE (?1 IS NULL OR profile_id = ?1)
+                  AND (?2 IS NULL OR started_at >= ?2)
+                "#,
+                params![profile_id, since],
+                |row| {
+                    Ok(TransferStats {
+                        total_count: row.get(0)?,
+                        success_count: row.get(1)?,
+                        failed_count: row.get(2)?,
+                        canceled_count: row.get(3)?,
+                        total_bytes: row.get(4)?,
+                        avg_throughput_bytes_per_sec: row.get(5)?,
+                    })
+                },
+            )
+            .map_err(AppError::from)
+        })
+    }
+
+    /// 清理传输历史：`older_than`（毫秒时间戳）删除此时间之前的记录，`keep_last_n` 无论时间
+    /// 都至少保留最近 N 条；两者可同时指定（先按时间过滤，再保底保留最近 N 条），但不能同时为空
+    pub fn transfers_prune(
+        &self,
+        older_than: Option<i64>,
+        keep_last_n: Option<i64>,
+    ) -> AppResult<usize> {
+        if older_than.is_none() && keep_last_n.is_none() {
+            return Err(AppError::invalid_argument(
+                "older_than 和 keep_last_n 至少需要指定一个",
+            ));
+        }
+
+        self.with_write(|conn| {
+            let affected = match (older_than, keep_last_n) {
+                (Some(cutoff), Some(n)) => conn.execute(
+                    r#"
+                    DELETE FROM transfer_history
+                    WHERE started_at < ?1
+                      AND id NOT IN (
+                          SELECT id FROM transfer_history ORDER BY started_at DESC LIMIT ?2
+                      )
+                    "#,
+                    params![cutoff, n],
+                )?,
+                (Some(cutoff), None) => conn.execute(
+                    "DELETE FROM transfer_history WHERE started_at < ?1",
+                    params![cutoff],
+                )?,
+                (None, Some(n)) => conn.execute(
+                    r#"
+                    DELETE FROM transfer_history
+                    WHERE id NOT IN (
+                        SELECT id FROM transfer_history ORDER BY started_at DESC LIMIT ?1
+                    )
+                    "#,
+                    params![n],
+                )?,
+                (None, None) => unreachable!("已在函数开头校验至少指定一项"),
+            };
+
+            Ok(affected)
+        })
+    }
+
+    // ============================================
+    // 传输任务队列（持久化）
+    // ============================================
+
+    /// 创建或更新传输任务行
+    ///
+    /// `retry_count`/`next_attempt_at` 是退避重试调度器的内部状态，不属于 `TransferTask`
+    /// （不会下发给前端），随任务一起持久化以便重启后恢复调度。
+    pub fn transfer_task_upsert(
+        &self,
+        task: &TransferTask,
+        retry_count: u8,
+        next_attempt_at: Option<i64>,
+    ) -> AppResult<()> {
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO transfer_tasks (
+                    task_id, session_id, direction, local_path, remote_path, file_name,
+                    status, transferred, total, error_message, error_code, retryable,
+                    created_at, completed_at, resume_offset, source_mtime,
+                    retry_count, next_attempt_at, speed_limit_bytes_per_sec,
+                    parent_task_id, is_batch, verify_checksum_override
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(task_id) DO UPDATE SET
+                    status = excluded.status,
+                    transferred = excluded.transferred,
+                    total = excluded.total,
+                    error_message = excluded.error_message,
+                    error_code = excluded.error_code,
+                    retryable = excluded.retryable,
+                    completed_at = excluded.completed_at,
+                    resume_offset = excluded.resume_offset,
+                    source_mtime = excluded.source_mtime,
+                    retry_count = excluded.retry_count,
+                    next_attempt_at = excluded.next_attempt_at,
+                    speed_limit_bytes_per_sec = excluded.speed_limit_bytes_per_sec,
+                    parent_task_id = excluded.parent_task_id,
+                    is_batch = excluded.is_batch,
+                    verify_checksum_override = excluded.verify_checksum_override
+                "#,
+                params![
+                    task.task_id,
+                    task.session_id,
+                    task.direction.as_str(),
+                    task.local_path,
+                    task.remote_path,
+                    task.file_name,
+                    task.status.as_str(),
+                    task.transferred,
+                    task.total,
+                    task.error_message,
+                    task.error_code,
+                    task.retryable,
+                    task.created_at,
+                    task.completed_at,
+                    task.resume_offset,
+                    task.source_mtime,
+                    retry_count,
+                    next_attempt_at,
+                    task.speed_limit_bytes_per_sec,
+                    task.parent_task_id,
+                    task.is_batch,
+                    task.verify_checksum_override,
+                ],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// 删除传输任务行
+    pub fn transfer_task_delete(&self, task_id: &str) -> AppResult<()> {
+        self.with_write(|conn| {
+            conn.execute("DELETE FROM transfer_tasks WHERE task_id = ?", [task_id])?;
+
+            Ok(())
+        })
+    }
+
+    /// 加载所有未完成的任务（用于启动时恢复）
+    ///
+    /// Running 任务会被就地降级为 Waiting（上次崩溃时正在传输，需要重新开始/续传），
+    /// 降级后的状态会立即写回数据库。返回 (任务, retry_count, next_attempt_at) 三元组。
+    pub fn transfer_tasks_load_non_terminal(
+        &self,
+    ) -> AppResult<Vec<(TransferTask, u8, Option<i64>)>> {
+        self.with_write(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT task_id, session_id, direction, local_path, remote_path, file_name,
+                       status, transferred, total, error_message, error_code, retryable,
+                       created_at, completed_at, resume_offset, source_mtime,
+                       retry_count, next_attempt_at, speed_limit_bytes_per_sec,
+                       parent_task_id, is_batch, verify_checksum_override
+                FROM transfer_tasks
+                WHERE status IN ('waiting', 'running')
+                "#,
+            )?;
+
+            let rows = stmt
+                .query_map([], |row| {
+                    let status = parse_transfer_status(row.get::<_, String>(6)?);
+                    let retry_count: u8 = row.get(16)?;
+                    let next_attempt_at: Option<i64> = row.get(17)?;
+                    let transferred: u64 = row.get(7)?;
+                    let total: Option<u64> = row.get(8)?;
+                    let resume_offset: Option<u64> = row.get(14)?;
+
+                    // Running 任务在上次运行中被中断，降级为 Waiting 以便重新调度
+                    let restored_status = if status == TransferStatus::Running {
+                        TransferStatus::Waiting
+                    } else {
+                        status
+                    };
+
+                    let task = TransferTask {
+                        task_id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        direction: parse_transfer_direction(row.get::<_, String>(2)?),
+                        local_path: row.get(3)?,
+                        remote_path: row.get(4)?,
+                        file_name: row.get(5)?,
+                        status: restored_status,
+                        transferred,
+                        total,
+                        speed: None,
+                        percent: Some(calculate_loaded_percent(transferred, total)),
+                        error_message: row.get(9)?,
+                        error_code: row.get(10)?,
+                        retryable: row.get(11)?,
+                        created_at: row.get(12)?,
+                        completed_at: row.get(13)?,
+                        resumable: resume_offset.is_some(),
+                        resume_offset,
+                        source_mtime: row.get(15)?,
+                        speed_limit_bytes_per_sec: row.get(18)?,
+                        retry_count,
+                        next_retry_at: next_attempt_at,
+                        parent_task_id: row.get(19)?,
+                        is_batch: row.get(20)?,
+                        verify_checksum_override: row.get(21)?,
+                    };
+
+                    Ok((task, retry_count, next_attempt_at))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // 降级后的状态需要立即写回，避免下次启动再次读到 Running
+            for (task, _, _) in &rows {
+                if task.status == TransferStatus::Waiting {
+                    conn.execute(
+                        "UPDATE transfer_tasks SET status = 'waiting' WHERE task_id = ?",
+                        [&task.task_id],
+                    )?;
+                }
+            }
+
+            Ok(rows)
+        })
+    }
+
+    /// 删除早于指定时间戳的已终结任务（success/failed/canceled），用于队列瘦身
+    ///
+    /// 返回被删除的行数
+    pub fn transfer_tasks_delete_older_than(&self, cutoff_millis: i64) -> AppResult<usize> {
+        self.with_write(|conn| {
+            let affected = conn.execute(
+                r#"
+                DELETE FROM transfer_tasks
+                WHERE status IN ('success', 'failed', 'canceled')
+                  AND completed_at IS NOT NULL
+                  AND completed_at < ?
+                "#,
+                [cutoff_millis],
+            )?;
+
+            Ok(affected)
+        })
+    }
+
+    // ============================================
+    // 目录同步计划
+    // ============================================
+
+    /// 新建或更新同步计划
+    pub fn sync_schedule_upsert(&self, schedule: &SyncSchedule) -> AppResult<()> {
+        self.with_write(|conn| {
+            let recurrence_json = serde_json::to_string(&schedule.recurrence)
+                .map_err(|e| AppError::new(ErrorCode::Unknown, format!("序列化触发规则失败: {}", e)))?;
+
+            conn.execute(
+                r#"
+                INSERT INTO sync_schedules (
+                    schedule_id, session_id, local_dir, remote_dir, direction, mirror,
+                    recurrence, enabled, created_at, last_run_at, next_run_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(schedule_id) DO UPDATE SET
+                    mirror = excluded.mirror,
+                    recurrence = excluded.recurrence,
+                    enabled = excluded.enabled,
+                    last_run_at = excluded.last_run_at,
+                    next_run_at = excluded.next_run_at
+                "#,
+                params![
+                    schedule.schedule_id,
+                    schedule.session_id,
+                    schedule.local_dir,
+                    schedule.remote_dir,
+                    schedule.direction.as_str(),
+                    schedule.mirror,
+                    recurrence_json,
+                    schedule.enabled,
+                    schedule.created_at,
+                    schedule.last_run_at,
+                    schedule.next_run_at,
+                ],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// 删除同步计划，返回是否存在该计划
+    pub fn sync_schedule_delete(&self, schedule_id: &str) -> AppResult<bool> {
+        self.with_write(|conn| {
+            let affected = conn.execute(
+                "DELETE FROM sync_schedules WHERE schedule_id = ?",
+                [schedule_id],
+            )?;
+
+            Ok(affected > 0)
+        })
+    }
+
+    /// 加载所有同步计划（用于启动时恢复）
+    pub fn sync_schedules_load(&self) -> AppResult<Vec<SyncSchedule>> {
+        self.with_read(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT schedule_id, session_id, local_dir, remote_dir, direction, mirror,
+                       recurrence, enabled, created_at, last_run_at, next_run_at
+                FROM sync_schedules
+                "#,
+            )?;
+
+            let rows = stmt
+                .query_map([], |row| {
+                    let recurrence_json: String = row.get(6)?;
+                    let recurrence = serde_json::from_str(&recurrence_json)
+                        .unwrap_or(ScheduleRecurrence::EveryMinutes { minutes: 60 });
+
+                    Ok(SyncSchedule {
+                        schedule_id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        local_dir: row.get(2)?,
+                        remote_dir: row.get(3)?,
+                        direction: parse_transfer_direction(row.get::<_, String>(4)?),
+                        mirror: row.get(5)?,
+                        recurrence,
+                        enabled: row.get(7)?,
+                        created_at: row.get(8)?,
+                        last_run_at: row.get(9)?,
+                        next_run_at: row.get(10)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(rows)
+        })
     }
 
     // ============================================
@@ -644,6 +1972,18 @@ impl Database {
                 connection_timeout_secs = ?,
                 transfer_retry_count = ?,
                 log_level = ?,
+                parallel_transfer_threshold_mb = ?,
+                parallel_transfer_streams = ?,
+                preserve_file_metadata = ?,
+                speed_limit_kbps = ?,
+                verify_transfer_checksum = ?,
+                checksum_command = ?,
+                checksum_verify_min_size_mb = ?,
+                pipeline_window_size = ?,
+                max_open_local_files = ?,
+                terminal_idle_timeout_secs = ?,
+                retention_days = ?,
+                known_hosts_mirror_path = ?,
                 updated_at = ?
             WHERE id = 1
             "#,
@@ -653,138 +1993,1124 @@ impl Database {
                 settings.connection_timeout_secs,
                 settings.transfer_retry_count,
                 settings.log_level.as_str(),
+                settings.parallel_transfer_threshold_mb,
+                settings.parallel_transfer_streams,
+                settings.preserve_file_metadata,
+                settings.speed_limit_kbps,
+                settings.verify_transfer_checksum,
+                settings.checksum_command,
+                settings.checksum_verify_min_size_mb,
+                settings.pipeline_window_size,
+                settings.max_open_local_files,
+                settings.terminal_idle_timeout_secs,
+                settings.retention_days,
+                settings.known_hosts_mirror_path,
                 now,
             ],
         )?;
         Ok(())
     }
 
-    /// 从数据库行解析 Settings（内部方法）
-    fn parse_settings_row(row: &rusqlite::Row) -> rusqlite::Result<Settings> {
-        Ok(Settings {
-            default_download_dir: row.get(0)?,
-            max_concurrent_transfers: row.get(1)?,
-            connection_timeout_secs: row.get(2)?,
-            transfer_retry_count: row.get(3)?,
-            log_level: parse_log_level(row.get::<_, String>(4)?),
-        })
+    /// 从数据库行解析 Settings（内部方法）
+    fn parse_settings_row(row: &rusqlite::Row) -> rusqlite::Result<Settings> {
+        Ok(Settings {
+            default_download_dir: row.get(0)?,
+            max_concurrent_transfers: row.get(1)?,
+            connection_timeout_secs: row.get(2)?,
+            transfer_retry_count: row.get(3)?,
+            log_level: parse_log_level(row.get::<_, String>(4)?),
+            parallel_transfer_threshold_mb: row.get(5)?,
+            parallel_transfer_streams: row.get(6)?,
+            preserve_file_metadata: row.get(7)?,
+            speed_limit_kbps: row.get(8)?,
+            verify_transfer_checksum: row.get(9)?,
+            checksum_command: row.get(10)?,
+            checksum_verify_min_size_mb: row.get(11)?,
+            pipeline_window_size: row.get(12)?,
+            max_open_local_files: row.get(13)?,
+            terminal_idle_timeout_secs: row.get(14)?,
+            retention_days: row.get(15)?,
+            known_hosts_mirror_path: row.get(16)?,
+        })
+    }
+
+    /// 从 JSON 文件迁移 Settings 到数据库
+    fn migrate_settings_from_json(conn: &Connection) -> AppResult<()> {
+        let json_path = get_settings_path();
+
+        if !json_path.exists() {
+            tracing::debug!("无 settings.json 需要迁移");
+            return Ok(());
+        }
+
+        let content = match fs::read_to_string(&json_path) {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!(error = %e, "读取 settings.json 失败，跳过迁移");
+                return Ok(());
+            }
+        };
+
+        let settings: Settings = match serde_json::from_str(&content) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(error = %e, "解析 settings.json 失败，使用默认设置");
+                Settings::default()
+            }
+        };
+
+        Self::save_settings_to_db(conn, &settings)?;
+
+        if let Err(e) = fs::remove_file(&json_path) {
+            tracing::warn!(error = %e, "删除 settings.json 失败");
+        } else {
+            tracing::info!("settings.json 已迁移到数据库并删除");
+        }
+
+        Ok(())
+    }
+
+    /// 加载设置
+    pub fn settings_load(&self) -> AppResult<Settings> {
+        self.with_read(|conn| {
+            let settings = conn.query_row(
+                r#"
+                SELECT default_download_dir, max_concurrent_transfers,
+                       connection_timeout_secs, transfer_retry_count, log_level,
+                       parallel_transfer_threshold_mb, parallel_transfer_streams,
+                       preserve_file_metadata, speed_limit_kbps,
+                       verify_transfer_checksum, checksum_command, checksum_verify_min_size_mb,
+                       pipeline_window_size, max_open_local_files, terminal_idle_timeout_secs,
+                       retention_days, known_hosts_mirror_path
+                FROM settings WHERE id = 1
+                "#,
+                [],
+                Self::parse_settings_row,
+            )?;
+
+            Ok(settings)
+        })
+    }
+
+    /// 把数据库当前设置转换为供 [`crate::services::config_loader::ConfigLoader`] 分层合并
+    /// 使用的"覆盖补丁"：只有与出厂默认值不同的字段才计入覆盖，其余字段为 `None`
+    ///
+    /// `settings` 表所有列都是 `NOT NULL DEFAULT ...`，`settings_load` 读到的永远是一行
+    /// 完整记录，无法直接区分"用户从未改过、仍是出厂默认"和"用户手动改回了默认值"。这里
+    /// 采用与出厂默认值比较的近似策略：只要字段仍等于出厂默认值，就让文件/环境变量层的值
+    /// 生效；一旦用户通过 `settings_set` 把某字段改成了非默认值，该值就会被当作覆盖项参与
+    /// 合并并拥有最高优先级
+    pub fn settings_load_as_patch(&self) -> AppResult<SettingsPatch> {
+        let settings = self.settings_load()?;
+        let default = Settings::default();
+
+        Ok(SettingsPatch {
+            default_download_dir: (settings.default_download_dir != default.default_download_dir)
+                .then(|| settings.default_download_dir.clone())
+                .flatten(),
+            max_concurrent_transfers: (settings.max_concurrent_transfers
+                != default.max_concurrent_transfers)
+                .then_some(settings.max_concurrent_transfers),
+            connection_timeout_secs: (settings.connection_timeout_secs
+                != default.connection_timeout_secs)
+                .then_some(settings.connection_timeout_secs),
+            transfer_retry_count: (settings.transfer_retry_count != default.transfer_retry_count)
+                .then_some(settings.transfer_retry_count),
+            log_level: (settings.log_level != default.log_level)
+                .then(|| settings.log_level.clone()),
+            parallel_transfer_threshold_mb: (settings.parallel_transfer_threshold_mb
+                != default.parallel_transfer_threshold_mb)
+                .then_some(settings.parallel_transfer_threshold_mb),
+            parallel_transfer_streams: (settings.parallel_transfer_streams
+                != default.parallel_transfer_streams)
+                .then_some(settings.parallel_transfer_streams),
+            preserve_file_metadata: (settings.preserve_file_metadata
+                != default.preserve_file_metadata)
+                .then_some(settings.preserve_file_metadata),
+            speed_limit_kbps: (settings.speed_limit_kbps != default.speed_limit_kbps)
+                .then_some(settings.speed_limit_kbps),
+            verify_transfer_checksum: (settings.verify_transfer_checksum
+                != default.verify_transfer_checksum)
+                .then_some(settings.verify_transfer_checksum),
+            checksum_command: (settings.checksum_command != default.checksum_command)
+                .then(|| settings.checksum_command.clone()),
+            checksum_verify_min_size_mb: (settings.checksum_verify_min_size_mb
+                != default.checksum_verify_min_size_mb)
+                .then_some(settings.checksum_verify_min_size_mb),
+            pipeline_window_size: (settings.pipeline_window_size != default.pipeline_window_size)
+                .then_some(settings.pipeline_window_size),
+            max_open_local_files: (settings.max_open_local_files != default.max_open_local_files)
+                .then_some(settings.max_open_local_files),
+            terminal_idle_timeout_secs: (settings.terminal_idle_timeout_secs
+                != default.terminal_idle_timeout_secs)
+                .then_some(settings.terminal_idle_timeout_secs),
+            retention_days: (settings.retention_days != default.retention_days)
+                .then_some(settings.retention_days),
+            known_hosts_mirror_path: (settings.known_hosts_mirror_path
+                != default.known_hosts_mirror_path)
+                .then(|| settings.known_hosts_mirror_path.clone())
+                .flatten(),
+        })
+    }
+
+    /// 更新设置
+    pub fn settings_update(&self, patch: &SettingsPatch) -> AppResult<Settings> {
+        self.with_write(|conn| {
+            let mut settings: Settings = conn.query_row(
+                r#"
+                SELECT default_download_dir, max_concurrent_transfers,
+                       connection_timeout_secs, transfer_retry_count, log_level,
+                       parallel_transfer_threshold_mb, parallel_transfer_streams,
+                       preserve_file_metadata, speed_limit_kbps,
+                       verify_transfer_checksum, checksum_command, checksum_verify_min_size_mb,
+                       pipeline_window_size, max_open_local_files, terminal_idle_timeout_secs,
+                       retention_days, known_hosts_mirror_path
+                FROM settings WHERE id = 1
+                "#,
+                [],
+                Self::parse_settings_row,
+            )?;
+
+            settings.apply_patch(patch);
+
+            Self::save_settings_to_db(&conn, &settings)?;
+            tracing::info!("设置已更新");
+
+            Ok(settings)
+        })
+    }
+
+    /// 重置设置为默认值
+    pub fn settings_reset(&self) -> AppResult<Settings> {
+        self.with_write(|conn| {
+            let settings = Settings::default();
+            Self::save_settings_to_db(&conn, &settings)?;
+            tracing::info!("设置已重置为默认值");
+
+            Ok(settings)
+        })
+    }
+
+    // ============================================
+    // 数据库维护
+    // ============================================
+
+    /// 执行一轮数据库维护，在高频传输场景下防止 SQLite 文件无限增长
+    ///
+    /// 依次执行：
+    /// 1. `PRAGMA wal_checkpoint(TRUNCATE)`，把 WAL 文件截断回零大小
+    /// 2. 按 `Settings::retention_days` 清理过期的 `transfer_history`，
+    ///    但无论如何至少保留最近 [`MAX_TRANSFER_HISTORY_ROWS`] 条
+    /// 3. 清理 `profile_id` 已不存在于 `profiles` 表的悬空 `recent_connections`
+    /// 4. 当 `PRAGMA freelist_count` 超过 [`VACUUM_FREELIST_THRESHOLD`] 时执行
+    ///    `VACUUM` 并运行 `PRAGMA optimize`，回收磁盘空间
+    ///
+    /// 全程在单个写锁内完成，期间只读连接池仍可并发提供查询
+    pub fn run_maintenance(&self) -> AppResult<MaintenanceMetrics> {
+        self.with_write(|conn| {
+            let (_busy, wal_pages_checkpointed, _checkpointed): (i64, i64, i64) = conn
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?;
+
+            let retention_days: u32 = conn
+                .query_row(
+                    "SELECT retention_days FROM settings WHERE id = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap_or(90);
+            let cutoff = chrono::Utc::now().timestamp_millis()
+                - retention_days as i64 * 86_400_000;
+
+            let history_rows_pruned = conn.execute(
+                r#"
+                DELETE FROM transfer_history
+                WHERE started_at < ?
+                  AND id NOT IN (
+                      SELECT id FROM transfer_history
+                      ORDER BY started_at DESC
+                      LIMIT ?
+                  )
+                "#,
+                params![cutoff, MAX_TRANSFER_HISTORY_ROWS],
+            )?;
+
+            let recent_connections_pruned = conn.execute(
+                r#"
+                DELETE FROM recent_connections
+                WHERE profile_id NOT IN (SELECT id FROM profiles)
+                "#,
+                [],
+            )?;
+
+            let freelist_count: i64 =
+                conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+
+            let bytes_reclaimed = if freelist_count > VACUUM_FREELIST_THRESHOLD {
+                let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+                let reclaimed = freelist_count * page_size;
+
+                conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+                tracing::info!(freelist_count, bytes_reclaimed = reclaimed, "执行 VACUUM");
+
+                reclaimed
+            } else {
+                0
+            };
+
+            tracing::info!(
+                wal_pages_checkpointed,
+                history_rows_pruned,
+                recent_connections_pruned,
+                bytes_reclaimed,
+                "数据库维护完成"
+            );
+
+            Ok(MaintenanceMetrics {
+                wal_pages_checkpointed,
+                history_rows_pruned,
+                recent_connections_pruned,
+                bytes_reclaimed,
+            })
+        })
+    }
+
+    // ============================================
+    // 加密密钥库（password_ref / passphrase_ref 的 SQLite 落地存储）
+    // ============================================
+
+    /// 用主密码解锁密钥库，派生出的密钥缓存在内存中供本次运行期间的
+    /// `secret_put`/`secret_get` 使用，进程退出后需要重新解锁
+    ///
+    /// `vault_meta` 的 salt/scrypt 参数只在首次调用时生成一次并持久化，
+    /// 之后每次解锁都复用同一份 salt 重新派生——这样同一个主密码才能
+    /// 稳定派生出同一把密钥，解密出此前写入的 `secrets`
+    pub fn vault_unlock(&self, master: &str) -> AppResult<()> {
+        let (salt, log_n) = self.with_write(|conn| {
+            if let Some(row) = conn
+                .query_row(
+                    "SELECT salt, scrypt_log_n FROM vault_meta WHERE id = 1",
+                    [],
+                    |row| {
+                        let salt: Vec<u8> = row.get(0)?;
+                        let log_n: u8 = row.get(1)?;
+                        Ok((salt, log_n))
+                    },
+                )
+                .optional()?
+            {
+                Ok(row)
+            } else {
+                let mut salt = vec![0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let log_n = VAULT_SCRYPT_LOG_N;
+                conn.execute(
+                    "INSERT INTO vault_meta (id, salt, scrypt_log_n) VALUES (1, ?, ?)",
+                    params![salt, log_n],
+                )?;
+                Ok((salt, log_n))
+            }
+        })?;
+
+        let key = derive_vault_key(master, &salt, log_n)?;
+        let mut vault_key = self
+            .vault_key
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "密钥库锁获取失败"))?;
+        *vault_key = Some(key);
+
+        tracing::info!("密钥库已解锁");
+        Ok(())
+    }
+
+    /// 密钥库本次运行期间是否已解锁（`vault_unlock` 成功过）
+    ///
+    /// 供 `security_service` 的系统钥匙串降级逻辑判断：钥匙串不可用但密钥库也未解锁时，
+    /// 应该提示用户解锁而不是把"未解锁"误判为"凭据不存在"。
+    pub fn vault_is_unlocked(&self) -> bool {
+        self.vault_key
+            .lock()
+            .map(|guard| guard.is_some())
+            .unwrap_or(false)
+    }
+
+    /// 取出当前缓存的密钥库密钥；未 `vault_unlock` 时返回错误
+    fn require_vault_key(&self) -> AppResult<[u8; 32]> {
+        let vault_key = self
+            .vault_key
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "密钥库锁获取失败"))?;
+        vault_key
+            .ok_or_else(|| AppError::new(ErrorCode::AuthFailed, "密钥库未解锁，请先调用 vault_unlock"))
+    }
+
+    /// 把当前已解锁的密钥库主密钥拆分成 `n` 份 Shamir 份额，凑齐任意 `k` 份即可重建；
+    /// 用于用户更换设备、忘记主密码时仍能恢复已保存的凭据，而不把恢复能力完全
+    /// 托付给单一的外部保管方。返回的每个字符串都是密钥的等价物，需要像主密码
+    /// 一样妥善保管
+    ///
+    /// 必须先 `vault_unlock` 才能调用——拆分的是派生出的密钥库密钥本身，而不是主密码
+    pub fn vault_backup_shares(&self, k: u8, n: u8) -> AppResult<Vec<String>> {
+        let key = self.require_vault_key()?;
+        let shares = shamir::split_secret(&key, k, n)?;
+        Ok(shares.iter().map(ShamirShare::encode).collect())
+    }
+
+    /// 用一组 [`vault_backup_shares`](Self::vault_backup_shares) 生成的份额重建密钥库
+    /// 主密钥并直接解锁，无需原始主密码
+    ///
+    /// 重建出的密钥会先拿现有的一条 `secrets` 记录尝试解密校验：AEAD 认证标签
+    /// 校验失败说明份额不足或被篡改，此时不会把错误密钥写入内存缓存，避免
+    /// "看起来解锁成功、实际解密全部失败"的情况；密钥库里还没有任何凭据时无法
+    /// 校验，直接信任重建结果
+    pub fn vault_recover_from_shares(&self, shares: &[String]) -> AppResult<()> {
+        let parsed = shares
+            .iter()
+            .map(|s| ShamirShare::decode(s))
+            .collect::<AppResult<Vec<_>>>()?;
+        let key_bytes = shamir::combine_shares(&parsed)?;
+        let key: [u8; 32] = key_bytes.try_into().map_err(|_| {
+            AppError::invalid_argument("重建出的密钥长度不是 32 字节，份额可能不完整或被篡改")
+        })?;
+
+        let probe = self.with_read(|conn| {
+            conn.query_row("SELECT nonce, ciphertext FROM secrets LIMIT 1", [], |row| {
+                let nonce: Vec<u8> = row.get(0)?;
+                let ciphertext: Vec<u8> = row.get(1)?;
+                Ok((nonce, ciphertext))
+            })
+            .optional()
+            .map_err(AppError::from)
+        })?;
+
+        if let Some((nonce, ciphertext)) = probe {
+            let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+            cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+                .map_err(|_| {
+                    AppError::new(ErrorCode::AuthFailed, "恢复失败：份额组合出的密钥无法解密现有凭据")
+                })?;
+        }
+
+        let mut vault_key = self
+            .vault_key
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "密钥库锁获取失败"))?;
+        *vault_key = Some(key);
+
+        tracing::info!("密钥库已通过 Shamir 份额恢复并解锁");
+        Ok(())
+    }
+
+    /// 加密并写入一条凭据（UPSERT）
+    pub fn secret_put(&self, reference: &str, plaintext: &str) -> AppResult<()> {
+        let key = self.require_vault_key()?;
+
+        let mut nonce_bytes = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext.as_bytes())
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("凭据加密失败: {}", e)))?;
+
+        self.with_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO secrets (ref, nonce, ciphertext, updated_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(ref) DO UPDATE SET
+                    nonce = excluded.nonce,
+                    ciphertext = excluded.ciphertext,
+                    updated_at = excluded.updated_at
+                "#,
+                params![
+                    reference,
+                    nonce_bytes.to_vec(),
+                    ciphertext,
+                    chrono::Utc::now().timestamp_millis()
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// 读取并解密一条凭据；`reference` 不存在时返回 `Ok(None)`
+    ///
+    /// GCM 认证标签校验失败（主密码错误，或密文被篡改）时返回
+    /// [`ErrorCode::AuthFailed`]，而不是把底层解密错误原样抛出
+    pub fn secret_get(&self, reference: &str) -> AppResult<Option<String>> {
+        let key = self.require_vault_key()?;
+
+        let row = self.with_read(|conn| {
+            conn.query_row(
+                "SELECT nonce, ciphertext FROM secrets WHERE ref = ?",
+                params![reference],
+                |row| {
+                    let nonce: Vec<u8> = row.get(0)?;
+                    let ciphertext: Vec<u8> = row.get(1)?;
+                    Ok((nonce, ciphertext))
+                },
+            )
+            .optional()
+            .map_err(AppError::from)
+        })?;
+
+        let Some((nonce, ciphertext)) = row else {
+            return Ok(None);
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| AppError::new(ErrorCode::AuthFailed, "解密失败：密码错误或凭据数据已损坏"))?;
+
+        let plaintext = String::from_utf8(plaintext)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("凭据内容不是合法 UTF-8: {}", e)))?;
+
+        Ok(Some(plaintext))
+    }
+
+    /// 删除一条凭据，返回是否存在并被删除
+    pub fn secret_delete(&self, reference: &str) -> AppResult<bool> {
+        self.with_write(|conn| {
+            let affected = conn.execute("DELETE FROM secrets WHERE ref = ?", params![reference])?;
+            Ok(affected > 0)
+        })
+    }
+
+    // ============================================
+    // 加密备份与恢复
+    // ============================================
+
+    /// 列出所有已信任的 HostKey（供加密备份使用）
+    fn known_hosts_list(&self) -> AppResult<Vec<KnownHostRecord>> {
+        self.with_read(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT host, port, key_type, fingerprint, trusted_at FROM known_hosts")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(KnownHostRecord {
+                        host: row.get(0)?,
+                        port: row.get(1)?,
+                        key_type: row.get(2)?,
+                        fingerprint: row.get(3)?,
+                        trusted_at: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(rows)
+        })
+    }
+
+    /// 导出 profiles、known_hosts、settings 为加密归档（AES-256-GCM + scrypt）
+    ///
+    /// 密钥通过 scrypt（N=2^15, r=8, p=1）从用户传入的密码派生，盐值与 nonce 各自
+    /// 随机生成一次，随归档一起以 JSON 形式保存——没有密码无法解密，密码本身不落盘。
+    /// `password_ref`/`passphrase_ref` 只是系统钥匙串的引用字符串，真正的密码/
+    /// passphrase 明文不在数据库中，因此归档本身不会泄露凭据。
+    pub fn export_encrypted(&self, passphrase: &str) -> AppResult<String> {
+        let payload = BackupPayload {
+            profiles: self.profile_list()?,
+            known_hosts: self.known_hosts_list()?,
+            settings: self.settings_load()?,
+        };
+
+        let plaintext = serde_json::to_vec(&payload)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("备份数据序列化失败: {}", e)))?;
+
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = derive_backup_key(passphrase, &salt)?;
+
+        let mut nonce_bytes = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext.as_ref())
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("备份加密失败: {}", e)))?;
+
+        let archive = BackupArchive {
+            version: DB_VERSION,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce_bytes),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+
+        tracing::info!(
+            profile_count = payload.profiles.len(),
+            known_host_count = payload.known_hosts.len(),
+            "已生成加密备份"
+        );
+
+        serde_json::to_string(&archive)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("备份归档序列化失败: {}", e)))
+    }
+
+    /// 从 [`Self::export_encrypted`] 生成的归档恢复 profiles、known_hosts、settings
+    ///
+    /// 严格按顺序执行：先重新派生密钥并校验 GCM 认证标签，确认密码正确且归档未被
+    /// 篡改后才开始碰数据库；写入时用单个事务包住 profiles/known_hosts/settings 三
+    /// 部分，任意一步失败则整体回滚，不会留下部分写入的状态。
+    pub fn import_encrypted(&self, archive_json: &str, passphrase: &str) -> AppResult<()> {
+        let archive: BackupArchive = serde_json::from_str(archive_json)
+            .map_err(|e| AppError::invalid_argument(format!("备份归档格式无效: {}", e)))?;
+
+        if archive.version > DB_VERSION {
+            return Err(AppError::invalid_argument(format!(
+                "备份版本 {} 高于当前支持的版本 {}，请先升级应用后再导入",
+                archive.version, DB_VERSION
+            )));
+        }
+
+        let salt = BASE64
+            .decode(&archive.salt)
+            .map_err(|e| AppError::invalid_argument(format!("salt 解码失败: {}", e)))?;
+        let salt: [u8; 16] = salt
+            .try_into()
+            .map_err(|_| AppError::invalid_argument("salt 长度无效"))?;
+
+        let nonce_bytes = BASE64
+            .decode(&archive.nonce)
+            .map_err(|e| AppError::invalid_argument(format!("nonce 解码失败: {}", e)))?;
+        if nonce_bytes.len() != 12 {
+            return Err(AppError::invalid_argument("nonce 长度无效"));
+        }
+
+        let ciphertext = BASE64
+            .decode(&archive.ciphertext)
+            .map_err(|e| AppError::invalid_argument(format!("密文解码失败: {}", e)))?;
+
+        let key = derive_backup_key(passphrase, &salt)?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+        // GCM 认证标签在这里校验；密码错误或归档被篡改都会在此失败，
+        // 失败时直接返回，不会进行任何数据库写入
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+            .map_err(|_| AppError::new(ErrorCode::AuthFailed, "解密失败：密码错误或备份文件已损坏"))?;
+
+        let payload: BackupPayload = serde_json::from_slice(&plaintext)
+            .map_err(|e| AppError::invalid_argument(format!("备份内容解析失败: {}", e)))?;
+
+        let mut conn = self
+            .writer
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+
+        // 复用 profile_upsert/known_host_trust 同样的 upsert 语义，但由于它们各自
+        // 通过 with_write 独立获取写锁，在持有事务期间直接调用会造成重入死锁，因此这里
+        // 内联相同的 SQL，统一包进一个事务
+        let tx = conn.transaction()?;
+
+        for profile in &payload.profiles {
+            tx.execute(
+                r#"
+                INSERT INTO profiles (
+                    id, name, host, port, username, auth_type,
+                    password_ref, private_key_path, passphrase_ref,
+                    initial_path, created_at, updated_at, private_key_ref
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    host = excluded.host,
+                    port = excluded.port,
+                    username = excluded.username,
+                    auth_type = excluded.auth_type,
+                    password_ref = excluded.password_ref,
+                    private_key_path = excluded.private_key_path,
+                    passphrase_ref = excluded.passphrase_ref,
+                    initial_path = excluded.initial_path,
+                    updated_at = excluded.updated_at,
+                    private_key_ref = excluded.private_key_ref
+                "#,
+                params![
+                    profile.id,
+                    profile.name,
+                    profile.host,
+                    profile.port,
+                    profile.username,
+                    profile.auth.type_str(),
+                    profile.auth.password_ref(),
+                    profile.auth.private_key_path(),
+                    profile.auth.passphrase_ref(),
+                    profile.initial_path,
+                    profile.created_at,
+                    profile.updated_at,
+                    profile.auth.private_key_ref(),
+                ],
+            )?;
+        }
+
+        for host in &payload.known_hosts {
+            tx.execute(
+                r#"
+                INSERT INTO known_hosts (host, port, key_type, fingerprint, trusted_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(host, port) DO UPDATE SET
+                    key_type = excluded.key_type,
+                    fingerprint = excluded.fingerprint,
+                    trusted_at = excluded.trusted_at
+                "#,
+                params![host.host, host.port, host.key_type, host.fingerprint, host.trusted_at],
+            )?;
+        }
+
+        Self::save_settings_to_db(&tx, &payload.settings)?;
+
+        tx.commit()?;
+
+        tracing::info!(
+            profile_count = payload.profiles.len(),
+            known_host_count = payload.known_hosts.len(),
+            "加密备份已导入"
+        );
+
+        Ok(())
+    }
+
+    // ============================================
+    // 从 OpenSSH 配置 / known_hosts 导入
+    // ============================================
+
+    /// 解析 OpenSSH `config`/`known_hosts` 生成导入预览，不写入数据库
+    ///
+    /// 不传路径时使用 `~/.ssh/config`、`~/.ssh/known_hosts`；调用方应先把返回的
+    /// [`import::ImportSummary`] 展示给用户勾选确认，再调用 [`Self::import_openssh_apply`] 落库。
+    pub fn import_openssh_preview(
+        &self,
+        ssh_config_path: Option<&str>,
+        known_hosts_path: Option<&str>,
+    ) -> AppResult<import::ImportSummary> {
+        let ssh_config_path = ssh_config_path
+            .map(PathBuf::from)
+            .unwrap_or_else(default_ssh_config_path);
+        let known_hosts_path = known_hosts_path
+            .map(PathBuf::from)
+            .unwrap_or_else(default_known_hosts_path);
+
+        import::preview(&ssh_config_path, &known_hosts_path)
+    }
+
+    /// 落库用户在预览中确认保留的 profiles/known_hosts
+    ///
+    /// Profile 的 id/created_at/updated_at 是 ssh config 里没有的字段，这里统一生成；
+    /// known_hosts 直接复用 [`Self::known_host_trust`]。
+    pub fn import_openssh_apply(
+        &self,
+        profiles: &[import::ImportedProfile],
+        known_hosts: &[import::ImportedKnownHost],
+    ) -> AppResult<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        for p in profiles {
+            // ssh config 的 Host 块从不携带密码/私钥内容，解析出的 auth_type 只会是
+            // Password（没有 IdentityFile）或 Key（有 IdentityFile），这里仍然穷举
+            // 全部变体以保持匹配语句的完整性
+            let auth = match p.auth_type {
+                AuthType::Key => Auth::Key {
+                    private_key_path: p.private_key_path.clone(),
+                    private_key_ref: None,
+                    managed_key_id: None,
+                    passphrase_ref: None,
+                },
+                AuthType::Password => Auth::Password { password_ref: None },
+                AuthType::Agent => Auth::Agent,
+                AuthType::KeyboardInteractive => Auth::KeyboardInteractive,
+            };
+
+            let profile = Profile {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: p.name.clone(),
+                host: p.host.clone(),
+                port: p.port,
+                username: p.username.clone(),
+                auth,
+                initial_path: None,
+                host_key_algorithms: None,
+                kex_algorithms: None,
+                ciphers: None,
+                created_at: now,
+                updated_at: now,
+            };
+            self.profile_upsert(&profile)?;
+        }
+
+        for host in known_hosts {
+            self.known_host_trust(
+                &host.host,
+                host.port,
+                &host.key_type,
+                &host.fingerprint,
+                Some(&host.public_key_b64),
+            )?;
+        }
+
+        tracing::info!(
+            profile_count = profiles.len(),
+            known_host_count = known_hosts.len(),
+            "OpenSSH 导入已落库"
+        );
+
+        Ok(())
+    }
+
+    /// 从 OpenSSH `known_hosts` 文件批量导入，返回成功落库的条目数（受信 + 撤销）
+    ///
+    /// 与 [`Self::import_openssh_preview`] + [`Self::import_openssh_apply`] 的区别是不经过预览
+    /// 勾选环节，直接全量导入；用于"从标准 known_hosts 同步"这类一次性批量场景。
+    /// `@revoked` 行会作为拒绝条目落库（见 [`Self::known_host_revoke`]），而不是像受信条目
+    /// 那样走 `known_host_trust`——之后即使服务器重新出示一把"看起来正常"的密钥，
+    /// `verify_hostkey` 也会直接拒绝这个 host，不会退回到首次连接/不匹配的常规判断。
+    pub fn known_hosts_import(&self, path: &str) -> AppResult<usize> {
+        let (hosts, revoked_hosts, skipped) = import::parse_known_hosts(Path::new(path))?;
+
+        for host in &hosts {
+            self.known_host_trust(
+                &host.host,
+                host.port,
+                &host.key_type,
+                &host.fingerprint,
+                Some(&host.public_key_b64),
+            )?;
+        }
+
+        for host in &revoked_hosts {
+            self.known_host_revoke(&host.host, host.port, &host.key_type, &host.fingerprint)?;
+        }
+
+        if !revoked_hosts.is_empty() {
+            tracing::warn!(
+                path = %path,
+                revoked = revoked_hosts.len(),
+                "known_hosts 中的 @revoked 条目已作为拒绝名单导入"
+            );
+        }
+        tracing::info!(
+            path = %path,
+            trusted = hosts.len(),
+            revoked = revoked_hosts.len(),
+            skipped,
+            "known_hosts 批量导入完成"
+        );
+
+        Ok(hosts.len() + revoked_hosts.len())
     }
 
-    /// 从 JSON 文件迁移 Settings 到数据库
-    fn migrate_settings_from_json(&self, conn: &Connection) -> AppResult<()> {
-        let json_path = get_settings_path();
+    /// 将已信任的 HostKey 导出为标准 OpenSSH `known_hosts` 文件，返回实际写出的行数
+    ///
+    /// 指纹是单向哈希，无法逆推出公钥；早期版本遗留的仅有指纹、没有 `public_key_b64` 的记录
+    /// 无法重建出合法的 key 行，会被跳过并记录日志，不会中断整体导出。
+    pub fn known_hosts_export(&self, path: &str) -> AppResult<usize> {
+        let rows: Vec<(String, u16, String, Option<String>)> = self.with_read(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT host, port, key_type, public_key_b64 FROM known_hosts")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        let mut lines = String::new();
+        let mut skipped = 0usize;
+        for (host, port, key_type, public_key_b64) in &rows {
+            let Some(key_b64) = public_key_b64 else {
+                skipped += 1;
+                continue;
+            };
+
+            let host_field = if host.starts_with("|1|") || *port == 22 {
+                host.clone()
+            } else {
+                format!("[{}]:{}", host, port)
+            };
+            lines.push_str(&format!("{} {} {}\n", host_field, key_type, key_b64));
+        }
 
-        if !json_path.exists() {
-            tracing::debug!("无 settings.json 需要迁移");
-            return Ok(());
+        std::fs::write(path, lines)
+            .map_err(|e| AppError::local_io_error(format!("写入 known_hosts 文件失败: {}", e)))?;
+
+        if skipped > 0 {
+            tracing::warn!(
+                path = %path,
+                skipped,
+                "known_hosts 导出：跳过了缺少原始公钥的旧记录（仅有指纹，无法重建合法的 key 行）"
+            );
         }
+        tracing::info!(path = %path, exported = rows.len() - skipped, "known_hosts 导出完成");
 
-        let content = match fs::read_to_string(&json_path) {
-            Ok(c) => c,
-            Err(e) => {
-                tracing::warn!(error = %e, "读取 settings.json 失败，跳过迁移");
-                return Ok(());
+        Ok(rows.len() - skipped)
+    }
+
+    // ============================================
+    // Profile 可移植导出/导入
+    // ============================================
+
+    /// 删除所有 Profile，供 `ProfileMergeStrategy::Replace` 在落库前清空本地数据
+    pub fn profile_delete_all(&self) -> AppResult<usize> {
+        self.with_write(|conn| Ok(conn.execute("DELETE FROM profiles", [])?))
+    }
+
+    /// 序列化并写出 Profile 导出文件
+    ///
+    /// `passphrase` 为 `Some` 时整份 `entries` 会先用 `derive_backup_key` 派生出的密钥
+    /// 做 AES-256-GCM 加密（调用方应仅在 `entries` 含有明文凭据时传入）；为 `None` 时
+    /// 直接写出明文 JSON。
+    pub fn profiles_export_write(
+        &self,
+        path: &str,
+        entries: Vec<ProfileExportEntry>,
+        passphrase: Option<&str>,
+    ) -> AppResult<()> {
+        let entry_count = entries.len();
+
+        let body = match passphrase {
+            Some(passphrase) => {
+                let plaintext = serde_json::to_vec(&entries).map_err(|e| {
+                    AppError::new(ErrorCode::Unknown, format!("Profile 数据序列化失败: {}", e))
+                })?;
+
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let key = derive_backup_key(passphrase, &salt)?;
+
+                let mut nonce_bytes = [0u8; 12];
+                rand::thread_rng().fill_bytes(&mut nonce_bytes);
+                let nonce = Nonce::from_slice(&nonce_bytes);
+
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                let ciphertext = cipher
+                    .encrypt(nonce, plaintext.as_ref())
+                    .map_err(|e| AppError::new(ErrorCode::Unknown, format!("导出加密失败: {}", e)))?;
+
+                ProfileExportBody::Encrypted {
+                    salt: BASE64.encode(salt),
+                    nonce: BASE64.encode(nonce_bytes),
+                    ciphertext: BASE64.encode(ciphertext),
+                }
             }
+            None => ProfileExportBody::Plain { profiles: entries },
         };
 
-        let settings: Settings = match serde_json::from_str(&content) {
-            Ok(s) => s,
-            Err(e) => {
-                tracing::warn!(error = %e, "解析 settings.json 失败，使用默认设置");
-                Settings::default()
-            }
+        let file = ProfileExportFile {
+            version: PROFILE_EXPORT_VERSION,
+            body,
         };
 
-        Self::save_settings_to_db(conn, &settings)?;
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| AppError::new(ErrorCode::Unknown, format!("导出文件序列化失败: {}", e)))?;
+        std::fs::write(path, json)
+            .map_err(|e| AppError::local_io_error(format!("写入导出文件失败: {}", e)))?;
 
-        if let Err(e) = fs::remove_file(&json_path) {
-            tracing::warn!(error = %e, "删除 settings.json 失败");
-        } else {
-            tracing::info!("settings.json 已迁移到数据库并删除");
-        }
+        tracing::info!(path = %path, profile_count = entry_count, "Profile 导出完成");
 
         Ok(())
     }
 
-    /// 加载设置
-    pub fn settings_load(&self) -> AppResult<Settings> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
-
-        let settings = conn.query_row(
-            r#"
-            SELECT default_download_dir, max_concurrent_transfers,
-                   connection_timeout_secs, transfer_retry_count, log_level
-            FROM settings WHERE id = 1
-            "#,
-            [],
-            Self::parse_settings_row,
-        )?;
+    /// 读取并解析 [`Self::profiles_export_write`] 生成的文件
+    ///
+    /// 文件已加密时需要提供 `passphrase`；解密出的明文凭据由调用方
+    /// （`commands::profile::profile_import`）负责写回系统钥匙串并生成新的 ref。
+    pub fn profiles_import_read(
+        &self,
+        path: &str,
+        passphrase: Option<&str>,
+    ) -> AppResult<Vec<ProfileExportEntry>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::local_io_error(format!("读取导出文件失败: {}", e)))?;
+        let file: ProfileExportFile = serde_json::from_str(&content)
+            .map_err(|e| AppError::invalid_argument(format!("导出文件格式无效: {}", e)))?;
+
+        // 目前只有 version 1；未来格式变化时在此按版本分别解析，而不是直接拒绝旧文件
+        if file.version > PROFILE_EXPORT_VERSION {
+            return Err(AppError::invalid_argument(format!(
+                "导出文件版本 {} 高于当前支持的版本 {}，请先升级应用后再导入",
+                file.version, PROFILE_EXPORT_VERSION
+            )));
+        }
 
-        Ok(settings)
+        match file.body {
+            ProfileExportBody::Plain { profiles } => Ok(profiles),
+            ProfileExportBody::Encrypted {
+                salt,
+                nonce,
+                ciphertext,
+            } => {
+                let passphrase = passphrase.ok_or_else(|| {
+                    AppError::invalid_argument("该导出文件已加密，需要提供导出密码")
+                })?;
+
+                let salt = BASE64
+                    .decode(&salt)
+                    .map_err(|e| AppError::invalid_argument(format!("salt 解码失败: {}", e)))?;
+                let salt: [u8; 16] = salt
+                    .try_into()
+                    .map_err(|_| AppError::invalid_argument("salt 长度无效"))?;
+                let nonce_bytes = BASE64
+                    .decode(&nonce)
+                    .map_err(|e| AppError::invalid_argument(format!("nonce 解码失败: {}", e)))?;
+                let ciphertext = BASE64
+                    .decode(&ciphertext)
+                    .map_err(|e| AppError::invalid_argument(format!("密文解码失败: {}", e)))?;
+
+                let key = derive_backup_key(passphrase, &salt)?;
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                let plaintext = cipher
+                    .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+                    .map_err(|_| {
+                        AppError::new(ErrorCode::AuthFailed, "解密失败：密码错误或导出文件已损坏")
+                    })?;
+
+                serde_json::from_slice(&plaintext)
+                    .map_err(|e| AppError::invalid_argument(format!("导出内容解析失败: {}", e)))
+            }
+        }
     }
+}
 
-    /// 更新设置
-    pub fn settings_update(&self, patch: &SettingsPatch) -> AppResult<Settings> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+/// scrypt 参数为 N=2^15, r=8, p=1，按请求要求派生 32 字节 AES-256 密钥
+fn derive_backup_key(passphrase: &str, salt: &[u8; 16]) -> AppResult<[u8; 32]> {
+    let params = Params::new(15, 8, 1, 32)
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("scrypt 参数无效: {}", e)))?;
 
-        let mut settings: Settings = conn.query_row(
-            r#"
-            SELECT default_download_dir, max_concurrent_transfers,
-                   connection_timeout_secs, transfer_retry_count, log_level
-            FROM settings WHERE id = 1
-            "#,
-            [],
-            Self::parse_settings_row,
-        )?;
+    let mut key = [0u8; 32];
+    scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("密钥派生失败: {}", e)))?;
 
-        if let Some(v) = &patch.default_download_dir {
-            settings.default_download_dir = Some(v.clone());
-        }
-        if let Some(v) = patch.max_concurrent_transfers {
-            settings.max_concurrent_transfers = v.clamp(1, 6);
-        }
-        if let Some(v) = patch.connection_timeout_secs {
-            settings.connection_timeout_secs = v.clamp(5, 300);
-        }
-        if let Some(v) = patch.transfer_retry_count {
-            settings.transfer_retry_count = v.min(5);
-        }
-        if let Some(v) = &patch.log_level {
-            settings.log_level = v.clone();
-        }
+    Ok(key)
+}
 
-        Self::save_settings_to_db(&conn, &settings)?;
-        tracing::info!("设置已更新");
+/// 密钥库首次解锁时写入 `vault_meta` 的默认 scrypt N 参数（2^15），
+/// 与 [`derive_backup_key`] 保持一致的强度
+const VAULT_SCRYPT_LOG_N: u8 = 15;
 
-        Ok(settings)
-    }
+/// 密钥库专用的 scrypt 密钥派生，`log_n` 来自 `vault_meta`，允许未来在不破坏
+/// 已有密文的前提下为新装机调整强度（已有库沿用写入时的 `scrypt_log_n`）
+fn derive_vault_key(master: &str, salt: &[u8], log_n: u8) -> AppResult<[u8; 32]> {
+    let params = Params::new(log_n, 8, 1, 32)
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("scrypt 参数无效: {}", e)))?;
 
-    /// 重置设置为默认值
-    pub fn settings_reset(&self) -> AppResult<Settings> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::LocalIoError, "数据库锁获取失败"))?;
+    let mut key = [0u8; 32];
+    scrypt(master.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("密钥派生失败: {}", e)))?;
 
-        let settings = Settings::default();
-        Self::save_settings_to_db(&conn, &settings)?;
-        tracing::info!("设置已重置为默认值");
+    Ok(key)
+}
 
-        Ok(settings)
-    }
+/// `profiles_export_write`/`profiles_import_read` 文件格式的 schema 版本，独立于 `DB_VERSION` 演进
+const PROFILE_EXPORT_VERSION: i32 = 1;
+
+/// [`Database::profiles_import_read`]/落库时的合并策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileMergeStrategy {
+    /// 导入前清空本地所有 Profile，完全以导入文件为准
+    Replace,
+    /// 按 id 合并：已存在的 id 会被导入文件覆盖并重新生成时间戳，不存在的新增
+    Merge,
+}
+
+/// 导出文件中的一条 Profile
+///
+/// 是否含明文凭据由调用方（`commands::profile::profile_export`）决定：`password_plaintext`/
+/// `passphrase_plaintext` 填充时从系统钥匙串解析出的明文会随整份文件一起加密；留空时
+/// `password_ref`/`passphrase_ref` 就是原样保留的钥匙串引用句柄。Database 本身不访问
+/// 系统钥匙串，只负责这份结构的序列化与（加密模式下的）AES-GCM 读写。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileExportEntry {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub auth: Auth,
+    pub initial_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_key_algorithms: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kex_algorithms: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ciphers: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_plaintext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passphrase_plaintext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key_plaintext: Option<String>,
+}
+
+/// 导出文件主体：未加密时是明文 JSON；加密时是 AES-256-GCM 密文
+/// （与 [`BackupArchive`] 同一套 scrypt + GCM 方案）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "mode")]
+enum ProfileExportBody {
+    Plain {
+        profiles: Vec<ProfileExportEntry>,
+    },
+    Encrypted {
+        salt: String,
+        nonce: String,
+        ciphertext: String,
+    },
+}
+
+/// Profile 可移植导出文件
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileExportFile {
+    version: i32,
+    body: ProfileExportBody,
+}
+
+/// 加密备份归档（序列化为 JSON 文件/字符串）
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupArchive {
+    /// 归档写入时的 `DB_VERSION`，导入时若高于当前 `DB_VERSION` 则拒绝
+    version: i32,
+    /// scrypt 盐值（16 字节，base64 编码）
+    salt: String,
+    /// AES-256-GCM nonce（12 字节，base64 编码）
+    nonce: String,
+    /// AES-256-GCM 密文（base64 编码），解密后为 `BackupPayload` 的 JSON
+    ciphertext: String,
+}
+
+/// 加密归档内实际承载的数据（GCM 解密后的明文内容）
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupPayload {
+    profiles: Vec<Profile>,
+    known_hosts: Vec<KnownHostRecord>,
+    settings: Settings,
+}
+
+/// 一条已信任的 HostKey 记录（加密备份专用，与 `known_hosts` 表字段一一对应）
+#[derive(Debug, Serialize, Deserialize)]
+struct KnownHostRecord {
+    host: String,
+    port: u16,
+    key_type: String,
+    fingerprint: String,
+    trusted_at: i64,
 }
 
 /// 传输历史记录
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TransferHistoryRecord {
     pub id: String,
     pub session_id: String,
+    /// 发起传输时所用的 Profile ID，早期版本记录（迁移前写入）可能为 None
+    pub profile_id: Option<String>,
     pub direction: String, // "upload" | "download"
     pub local_path: String,
     pub remote_path: String,
@@ -795,18 +3121,39 @@ pub struct TransferHistoryRecord {
     pub finished_at: Option<i64>,
 }
 
+/// [`Database::transfers_list`] 查询过滤条件，字段为 `None` 的条件不参与过滤
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferHistoryFilter {
+    pub profile_id: Option<String>,
+    /// "upload" | "download"
+    pub direction: Option<String>,
+    /// "success" | "failed" | "canceled"
+    pub status: Option<String>,
+    /// 起始时间（毫秒时间戳，含）
+    pub since: Option<i64>,
+    /// 结束时间（毫秒时间戳，含）
+    pub until: Option<i64>,
+    pub limit: i32,
+}
+
+/// [`Database::transfers_stats`] 聚合结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferStats {
+    pub total_count: i64,
+    pub success_count: i64,
+    pub failed_count: i64,
+    pub canceled_count: i64,
+    pub total_bytes: i64,
+    /// 各记录吞吐率（字节/秒）的算术平均；无可用样本（均未结束或耗时为 0）时为 `None`
+    pub avg_throughput_bytes_per_sec: Option<f64>,
+}
+
 // ============================================
 // 辅助函数
 // ============================================
 
-fn parse_auth_type(s: String) -> AuthType {
-    match s.as_str() {
-        "password" => AuthType::Password,
-        "key" => AuthType::Key,
-        _ => AuthType::Password,
-    }
-}
-
 fn parse_log_level(s: String) -> crate::models::settings::LogLevel {
     use crate::models::settings::LogLevel;
     match s.as_str() {
@@ -818,15 +3165,412 @@ fn parse_log_level(s: String) -> crate::models::settings::LogLevel {
     }
 }
 
-impl AuthType {
+fn parse_transfer_direction(s: String) -> TransferDirection {
+    match s.as_str() {
+        "download" => TransferDirection::Download,
+        _ => TransferDirection::Upload,
+    }
+}
+
+fn parse_transfer_status(s: String) -> TransferStatus {
+    match s.as_str() {
+        "waiting" => TransferStatus::Waiting,
+        "running" => TransferStatus::Running,
+        "success" => TransferStatus::Success,
+        "canceled" => TransferStatus::Canceled,
+        _ => TransferStatus::Failed,
+    }
+}
+
+/// 根据已传输字节数和总大小重算百分比（用于从数据库恢复任务，`speed`/`percent` 不落库）
+fn calculate_loaded_percent(transferred: u64, total: Option<u64>) -> u8 {
+    match total {
+        Some(total) if total > 0 => ((transferred as f64 / total as f64) * 100.0) as u8,
+        _ => 0,
+    }
+}
+
+/// 把用户输入的原始查询词转换为 FTS5 `MATCH` 表达式
+///
+/// 按空白切分成词，每个词单独加引号并追加 `*` 做前缀匹配，词之间留空格按 FTS5 默认的
+/// AND 语义连接；引号做了转义，避免用户输入里的双引号破坏查询语法
+fn build_fts_match_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl TransferDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferDirection::Upload => "upload",
+            TransferDirection::Download => "download",
+        }
+    }
+}
+
+impl TransferStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
-            AuthType::Password => "password",
-            AuthType::Key => "key",
+            TransferStatus::Waiting => "waiting",
+            TransferStatus::Running => "running",
+            TransferStatus::Success => "success",
+            TransferStatus::Failed => "failed",
+            TransferStatus::Canceled => "canceled",
         }
     }
 }
 
+/// OpenSSH `~/.ssh/config` 与 `~/.ssh/known_hosts` 的只读解析，供 [`Database::import_openssh_preview`] 使用
+///
+/// 这里只负责把文件解析成候选条目，不接触数据库——真正落库是 [`Database::import_openssh_apply`]
+/// 的职责，这样 UI 可以先展示预览让用户勾选再确认导入。
+pub mod import {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::models::error::{AppError, AppResult, ErrorCode};
+    use crate::models::profile::AuthType;
+
+    /// 从 ssh config 解析出的一个候选 Profile（id/created_at 等落库时再生成）
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImportedProfile {
+        pub name: String,
+        pub host: String,
+        pub port: u16,
+        pub username: String,
+        pub auth_type: AuthType,
+        pub private_key_path: Option<String>,
+    }
+
+    /// 从 known_hosts 解析出的一条候选记录
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImportedKnownHost {
+        pub host: String,
+        pub port: u16,
+        pub key_type: String,
+        pub fingerprint: String,
+        /// 原始公钥（base64），用于 `known_host_trust` 落库后支持 `known_hosts_export`/镜像模式
+        pub public_key_b64: String,
+    }
+
+    /// 导入预览：解析出的候选条目 + 被跳过的行数（通配符 `Host *`、无法识别的行等）
+    #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImportSummary {
+        pub profiles: Vec<ImportedProfile>,
+        pub known_hosts: Vec<ImportedKnownHost>,
+        pub skipped_config_lines: usize,
+        pub skipped_known_hosts_lines: usize,
+        /// `skipped_known_hosts_lines` 中有多少行是 `@revoked` 标记——这些主机的旧密钥已被
+        /// 服务器管理员主动吊销，语义上不同于"当前不支持解析的行"，单独计数方便前端提醒用户
+        pub revoked_known_hosts_lines: usize,
+    }
+
+    /// 解析两个文件生成预览；任一文件不存在时只是跳过对应部分，不视为错误
+    pub fn preview(ssh_config_path: &Path, known_hosts_path: &Path) -> AppResult<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        if ssh_config_path.exists() {
+            let (profiles, skipped) = parse_ssh_config(ssh_config_path)?;
+            summary.profiles = profiles;
+            summary.skipped_config_lines = skipped;
+        }
+
+        if known_hosts_path.exists() {
+            let (hosts, revoked_hosts, skipped) = parse_known_hosts(known_hosts_path)?;
+            summary.known_hosts = hosts;
+            summary.skipped_known_hosts_lines = skipped;
+            summary.revoked_known_hosts_lines = revoked_hosts.len();
+        }
+
+        Ok(summary)
+    }
+
+    fn read_to_string(path: &Path) -> AppResult<String> {
+        fs::read_to_string(path).map_err(|e| {
+            AppError::new(
+                ErrorCode::LocalIoError,
+                format!("读取 {} 失败: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// 解析单个 ssh config 文件，递归展开 `Include` 指令
+    fn parse_ssh_config(path: &Path) -> AppResult<(Vec<ImportedProfile>, usize)> {
+        let mut profiles = Vec::new();
+        let mut skipped = 0usize;
+        parse_ssh_config_into(path, &mut profiles, &mut skipped)?;
+        Ok((profiles, skipped))
+    }
+
+    fn parse_ssh_config_into(
+        path: &Path,
+        profiles: &mut Vec<ImportedProfile>,
+        skipped: &mut usize,
+    ) -> AppResult<()> {
+        let content = read_to_string(path)?;
+
+        // 当前 Host 块的所有 pattern 与已收集到的字段（同一 block 内重复 key 后者覆盖前者）
+        let mut current_names: Vec<String> = Vec::new();
+        let mut fields: HashMap<String, String> = HashMap::new();
+
+        for raw_line in content.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = match line.split_once(char::is_whitespace) {
+                Some((k, r)) => (k, r.trim()),
+                None => (line, ""),
+            };
+            let keyword = keyword.to_ascii_lowercase();
+
+            if keyword == "host" {
+                flush_host_block(&current_names, &fields, profiles);
+                fields.clear();
+                current_names = rest
+                    .split_whitespace()
+                    .filter(|pattern| *pattern != "*")
+                    .map(|s| s.to_string())
+                    .collect();
+                continue;
+            }
+
+            if keyword == "include" {
+                for pattern in rest.split_whitespace() {
+                    for include_path in resolve_include(path, pattern) {
+                        parse_ssh_config_into(&include_path, profiles, skipped)?;
+                    }
+                }
+                continue;
+            }
+
+            if current_names.is_empty() {
+                // Host 块之外（全局配置段）当前不支持，计入跳过
+                *skipped += 1;
+                continue;
+            }
+
+            match keyword.as_str() {
+                "hostname" | "user" | "port" | "identityfile" => {
+                    fields.insert(keyword, rest.to_string());
+                }
+                _ => *skipped += 1,
+            }
+        }
+
+        flush_host_block(&current_names, &fields, profiles);
+
+        Ok(())
+    }
+
+    fn flush_host_block(
+        names: &[String],
+        fields: &HashMap<String, String>,
+        profiles: &mut Vec<ImportedProfile>,
+    ) {
+        if names.is_empty() {
+            return;
+        }
+
+        // 没有 HostName 的 Host 块无法生成可连接的 Profile，直接丢弃
+        let host = match fields.get("hostname") {
+            Some(h) => h.clone(),
+            None => return,
+        };
+
+        let port = fields
+            .get("port")
+            .and_then(|p| p.parse::<u16>().ok())
+            .unwrap_or(22);
+        let username = fields.get("user").cloned().unwrap_or_default();
+        let private_key_path = fields.get("identityfile").map(|p| expand_tilde(p));
+        let auth_type = if private_key_path.is_some() {
+            AuthType::Key
+        } else {
+            AuthType::Password
+        };
+
+        for name in names {
+            profiles.push(ImportedProfile {
+                name: name.clone(),
+                host: host.clone(),
+                port,
+                username: username.clone(),
+                auth_type: auth_type.clone(),
+                private_key_path: private_key_path.clone(),
+            });
+        }
+    }
+
+    /// 把 `IdentityFile` 中的 `~` 展开为绝对路径
+    ///
+    /// `session_manager::auth_key` 把 `private_key_path` 当作现成路径直接使用，不做任何
+    /// 展开，所以必须在导入时就处理好，否则落库后的路径永远无法被找到。
+    fn expand_tilde(path: &str) -> String {
+        if let Some(rest) = path.strip_prefix("~/") {
+            if let Some(home) = dirs::home_dir() {
+                return home.join(rest).to_string_lossy().to_string();
+            }
+        } else if path == "~" {
+            if let Some(home) = dirs::home_dir() {
+                return home.to_string_lossy().to_string();
+            }
+        }
+        path.to_string()
+    }
+
+    /// 展开 `Include` 的路径：相对路径相对于被包含文件所在目录，支持 `前缀*` 这种简单通配
+    fn resolve_include(current_file: &Path, pattern: &str) -> Vec<PathBuf> {
+        let expanded = expand_tilde(pattern);
+        let candidate = PathBuf::from(&expanded);
+        let base = if candidate.is_absolute() {
+            candidate
+        } else {
+            current_file
+                .parent()
+                .map(|dir| dir.join(&candidate))
+                .unwrap_or(candidate)
+        };
+
+        if !base.to_string_lossy().contains('*') {
+            return if base.is_file() { vec![base] } else { vec![] };
+        }
+
+        let (dir, prefix) = match (base.parent(), base.file_name()) {
+            (Some(dir), Some(name)) => (dir.to_path_buf(), name.to_string_lossy().to_string()),
+            _ => return vec![],
+        };
+        let prefix = prefix.trim_end_matches('*').to_string();
+
+        let mut matches: Vec<PathBuf> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_file())
+                    .filter(|p| {
+                        p.file_name()
+                            .map(|n| n.to_string_lossy().starts_with(prefix.as_str()))
+                            .unwrap_or(false)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        matches.sort();
+        matches
+    }
+
+    /// 解析 `known_hosts` 文件，返回 `(受信候选条目, @revoked 候选条目, 跳过行数)`
+    ///
+    /// `@revoked` 行单独返回而不是并入受信候选——两者落库语义完全相反（一个是
+    /// `known_host_trust`，一个是 [`super::Database::known_host_revoke`]），调用方必须能
+    /// 区分对待；`import_openssh_preview`/`import_openssh_apply` 这类"仅导入受信记录"的
+    /// 流程可以直接忽略第二个返回值。
+    pub(super) fn parse_known_hosts(
+        path: &Path,
+    ) -> AppResult<(Vec<ImportedKnownHost>, Vec<ImportedKnownHost>, usize)> {
+        let content = read_to_string(path)?;
+
+        let mut entries = Vec::new();
+        let mut revoked_entries = Vec::new();
+        let mut skipped = 0usize;
+
+        for raw_line in content.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // `@revoked`（主机密钥已被吊销）单独解析为撤销候选；`@cert-authority`
+            // （CA 公钥，信任语义与普通条目不同）当前不支持导入，跳过
+            let (line, is_revoked) = match line.strip_prefix("@revoked") {
+                Some(rest) => (rest.trim(), true),
+                None => (line, false),
+            };
+            if !is_revoked && line.starts_with('@') {
+                skipped += 1;
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let (hosts_field, key_type, key_b64) =
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(h), Some(t), Some(k)) => (h, t, k),
+                    _ => {
+                        skipped += 1;
+                        continue;
+                    }
+                };
+
+            let raw_key = match BASE64.decode(key_b64) {
+                Ok(k) => k,
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let fingerprint = format!("SHA256:{}", BASE64.encode(Sha256::digest(&raw_key)));
+            let out = if is_revoked {
+                &mut revoked_entries
+            } else {
+                &mut entries
+            };
+
+            if hosts_field.starts_with("|1|") {
+                // 哈希过的 host 字段（HMAC-SHA1，无候选主机名无法反推）原样存储，不尝试解码
+                out.push(ImportedKnownHost {
+                    host: hosts_field.to_string(),
+                    port: 22,
+                    key_type: key_type.to_string(),
+                    fingerprint,
+                    public_key_b64: key_b64.to_string(),
+                });
+                continue;
+            }
+
+            for host_token in hosts_field.split(',') {
+                let (host, port) = parse_host_port(host_token);
+                out.push(ImportedKnownHost {
+                    host,
+                    port,
+                    key_type: key_type.to_string(),
+                    fingerprint: fingerprint.clone(),
+                    public_key_b64: key_b64.to_string(),
+                });
+            }
+        }
+
+        Ok((entries, revoked_entries, skipped))
+    }
+
+    /// 解析 `host` 或 `[host]:port` 形式，默认端口 22
+    fn parse_host_port(token: &str) -> (String, u16) {
+        if let Some(rest) = token.strip_prefix('[') {
+            if let Some(end) = rest.find(']') {
+                let host = rest[..end].to_string();
+                let port = rest[end + 1..]
+                    .strip_prefix(':')
+                    .and_then(|p| p.parse::<u16>().ok())
+                    .unwrap_or(22);
+                return (host, port);
+            }
+        }
+        (token.to_string(), 22)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -838,14 +3582,7 @@ mod tests {
         fs::create_dir_all(&temp_dir).unwrap();
 
         let db_path = temp_dir.join("test.db");
-        let conn = Connection::open(&db_path).unwrap();
-        conn.execute_batch("PRAGMA journal_mode=WAL;").unwrap();
-
-        let db = Database {
-            conn: Mutex::new(conn),
-        };
-        db.migrate().unwrap();
-        db
+        Database::open_and_migrate(&db_path).unwrap()
     }
 
     #[test]
@@ -858,11 +3595,13 @@ mod tests {
             host: "192.168.1.1".to_string(),
             port: 22,
             username: "admin".to_string(),
-            auth_type: AuthType::Password,
-            password_ref: Some("test-1-pwd".to_string()),
-            private_key_path: None,
-            passphrase_ref: None,
+            auth: Auth::Password {
+                password_ref: Some("test-1-pwd".to_string()),
+            },
             initial_path: Some("/home/admin".to_string()),
+            host_key_algorithms: None,
+            kex_algorithms: None,
+            ciphers: None,
             created_at: 1000,
             updated_at: 1000,
         };
@@ -874,7 +3613,7 @@ mod tests {
         let loaded = db.profile_get("test-1").unwrap().unwrap();
         assert_eq!(loaded.name, "Test Server");
         assert_eq!(loaded.host, "192.168.1.1");
-        assert_eq!(loaded.auth_type, AuthType::Password);
+        assert!(matches!(loaded.auth, Auth::Password { .. }));
 
         // Update
         let updated_profile = Profile {
@@ -900,6 +3639,93 @@ mod tests {
         assert!(loaded.is_none());
     }
 
+    /// `profiles` 表的 `auth_type` 列带有 CHECK 约束，早期版本只接受 'password'/'key'——
+    /// 这条测试确保 Agent 认证类型能正常落库/读回，不会被约束拒绝
+    #[test]
+    fn test_profile_agent_auth_type_and_private_key_ref() {
+        let db = setup_test_db();
+
+        let profile = Profile {
+            id: "test-agent".to_string(),
+            name: "Agent Server".to_string(),
+            host: "192.168.1.2".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            auth: Auth::Agent,
+            initial_path: None,
+            host_key_algorithms: None,
+            kex_algorithms: None,
+            ciphers: None,
+            created_at: 1000,
+            updated_at: 1000,
+        };
+
+        db.profile_upsert(&profile).unwrap();
+
+        let loaded = db.profile_get("test-agent").unwrap().unwrap();
+        assert!(matches!(loaded.auth, Auth::Agent));
+    }
+
+    /// 同上，确保版本 22 放宽的 CHECK 约束也接受 'keyboard_interactive'
+    #[test]
+    fn test_profile_keyboard_interactive_auth_type() {
+        let db = setup_test_db();
+
+        let profile = Profile {
+            id: "test-2fa".to_string(),
+            name: "2FA Server".to_string(),
+            host: "192.168.1.3".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            auth: Auth::KeyboardInteractive,
+            initial_path: None,
+            host_key_algorithms: None,
+            kex_algorithms: None,
+            ciphers: None,
+            created_at: 1000,
+            updated_at: 1000,
+        };
+
+        db.profile_upsert(&profile).unwrap();
+
+        let loaded = db.profile_get("test-2fa").unwrap().unwrap();
+        assert!(matches!(loaded.auth, Auth::KeyboardInteractive));
+    }
+
+    /// 版本 23 新增的算法偏好字段要能原样落库/读回，空值不应被强制转换成空字符串
+    #[test]
+    fn test_profile_legacy_algorithm_preferences_roundtrip() {
+        let db = setup_test_db();
+
+        let profile = Profile {
+            id: "test-legacy".to_string(),
+            name: "Legacy Server".to_string(),
+            host: "192.168.1.4".to_string(),
+            port: 22,
+            username: "root".to_string(),
+            auth: Auth::Password { password_ref: None },
+            initial_path: None,
+            host_key_algorithms: Some("ssh-rsa,ssh-dss".to_string()),
+            kex_algorithms: Some("diffie-hellman-group14-sha1".to_string()),
+            ciphers: Some("aes128-cbc,3des-cbc".to_string()),
+            created_at: 1000,
+            updated_at: 1000,
+        };
+
+        db.profile_upsert(&profile).unwrap();
+
+        let loaded = db.profile_get("test-legacy").unwrap().unwrap();
+        assert_eq!(
+            loaded.host_key_algorithms,
+            Some("ssh-rsa,ssh-dss".to_string())
+        );
+        assert_eq!(
+            loaded.kex_algorithms,
+            Some("diffie-hellman-group14-sha1".to_string())
+        );
+        assert_eq!(loaded.ciphers, Some("aes128-cbc,3des-cbc".to_string()));
+    }
+
     #[test]
     fn test_recent_connections() {
         let db = setup_test_db();
@@ -911,6 +3737,7 @@ mod tests {
             host: "192.168.1.1".to_string(),
             username: "admin".to_string(),
             connected_at: 1000,
+            visit_count: 1,
         };
 
         db.recent_connection_add(&record).unwrap();
@@ -947,4 +3774,18 @@ mod tests {
         let fingerprint = db.known_host_check("example.com", 22).unwrap();
         assert!(fingerprint.is_none());
     }
+
+    #[test]
+    fn test_known_host_revoke() {
+        let db = setup_test_db();
+
+        assert!(!db.known_host_is_revoked("example.com", 22).unwrap());
+
+        db.known_host_revoke("example.com", 22, "ssh-ed25519", "SHA256:abc123")
+            .unwrap();
+
+        assert!(db.known_host_is_revoked("example.com", 22).unwrap());
+        // 未被撤销的其他 host 不受影响
+        assert!(!db.known_host_is_revoked("other.example.com", 22).unwrap());
+    }
 }
@@ -0,0 +1,141 @@
+//! 运行时指标采样
+//!
+//! 负责:
+//! - 采样当前进程的 CPU/内存占用（`sysinfo`）
+//! - 汇总活跃 SSH 会话数、打开的终端数、进行中传输数与聚合吞吐
+//! - 保留最近若干次采样形成滚动历史，供 `system_stats` 命令与诊断包
+//!   `metrics.json` 展示资源趋势，而不是单次快照
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+
+use serde::Serialize;
+use sysinfo::{Pid, System};
+
+use crate::services::session_manager::SessionManager;
+use crate::services::terminal_manager::TerminalManager;
+
+/// 滚动历史保留的采样个数
+const HISTORY_CAP: usize = 60;
+
+/// 一次运行时指标采样
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemSnapshot {
+    /// 采样时间 (Unix 时间戳毫秒)
+    pub timestamp: i64,
+    /// 进程 CPU 占用百分比（可能超过 100%，多核场景下按 sysinfo 约定）
+    pub process_cpu_percent: f32,
+    /// 进程常驻内存占用（字节）
+    pub process_memory_bytes: u64,
+    /// 活跃 SSH 会话数
+    pub active_sessions: usize,
+    /// 打开的终端数
+    pub open_terminals: usize,
+    /// 进行中（Running）的传输任务数
+    pub in_flight_transfers: usize,
+    /// 进行中传输的聚合吞吐（字节/秒），各任务 `speed` 求和
+    pub throughput_bytes_per_sec: u64,
+    /// 进程打开的文件描述符数；非 Unix 平台或读取失败时为 `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_file_descriptors: Option<u64>,
+}
+
+/// 运行时指标采样器
+pub struct SystemMonitor {
+    system: Mutex<System>,
+    pid: Pid,
+    history: RwLock<VecDeque<SystemSnapshot>>,
+}
+
+impl SystemMonitor {
+    pub fn new() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            pid: Pid::from_u32(std::process::id()),
+            history: RwLock::new(VecDeque::with_capacity(HISTORY_CAP)),
+        }
+    }
+
+    /// 采样一次当前运行时状态，追加到滚动历史并返回
+    ///
+    /// 涉及系统调用（刷新进程信息、读取 `/proc`），调用方应在 `spawn_blocking` 中执行
+    pub fn snapshot(
+        &self,
+        session_manager: &SessionManager,
+        terminal_manager: &TerminalManager,
+        transfer_tasks: &[crate::models::transfer_task::TransferTask],
+    ) -> SystemSnapshot {
+        let (process_cpu_percent, process_memory_bytes) = {
+            let mut system = match self.system.lock() {
+                Ok(s) => s,
+                Err(e) => e.into_inner(),
+            };
+            system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[self.pid]), true);
+            system
+                .process(self.pid)
+                .map(|p| (p.cpu_usage(), p.memory()))
+                .unwrap_or((0.0, 0))
+        };
+
+        let active_sessions = session_manager.list_sessions().map(|s| s.len()).unwrap_or(0);
+        let open_terminals = terminal_manager.terminal_count();
+
+        let running_tasks: Vec<_> = transfer_tasks
+            .iter()
+            .filter(|t| t.status == crate::models::transfer_task::TransferStatus::Running)
+            .collect();
+        let in_flight_transfers = running_tasks.len();
+        let throughput_bytes_per_sec = running_tasks.iter().filter_map(|t| t.speed).sum();
+
+        let snapshot = SystemSnapshot {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            process_cpu_percent,
+            process_memory_bytes,
+            active_sessions,
+            open_terminals,
+            in_flight_transfers,
+            throughput_bytes_per_sec,
+            open_file_descriptors: count_open_fds(),
+        };
+
+        if let Ok(mut history) = self.history.write() {
+            if history.len() >= HISTORY_CAP {
+                history.pop_front();
+            }
+            history.push_back(snapshot.clone());
+        }
+
+        snapshot
+    }
+
+    /// 最近的滚动历史（最多 `HISTORY_CAP` 条，按采样顺序）
+    pub fn history(&self) -> Vec<SystemSnapshot> {
+        self.history
+            .read()
+            .map(|h| h.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for SystemMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 统计当前进程打开的文件描述符数量
+///
+/// 只在 Linux 上通过 `/proc/self/fd` 精确统计；macOS/Windows 没有同等成本的
+/// 标准接口，直接返回 `None` 而不是给出一个不准确的数字
+#[cfg(target_os = "linux")]
+fn count_open_fds() -> Option<u64> {
+    std::fs::read_dir("/proc/self/fd")
+        .ok()
+        .map(|entries| entries.count() as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn count_open_fds() -> Option<u64> {
+    None
+}
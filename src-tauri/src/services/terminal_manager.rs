@@ -1,15 +1,29 @@
 //! 终端管理器 - PTY 创建、输入/输出处理
 //!
 //! Terminal 使用独立的非阻塞 SSH session（与 SFTP 分离）实现毫秒级响应。
+//! 另外支持把会话录制为 asciicast v2 格式的 cast 文件并回放（见 `start_recording`/
+//! `stop_recording`/`replay`）。
+//!
+//! 输出管线分为读取线程与 drain 线程两部分：读取线程只负责从 PTY 读字节、按节流
+//! 参数累积，再推入一个有界 channel；drain 线程独立消费该 channel 完成录制钩子、
+//! base64 编码与 `app.emit`，两者解耦后前端的处理速度不会直接拖慢 PTY 读取。见
+//! `start_output_reader`。
+//!
+//! 同一 `session_id` 下的多个标签页/分屏共享同一条底层 SSH 连接（`SharedHostSession`），
+//! 每个 PTY channel 仍各自拥有独立的 `channel`/读取线程/cols·rows/scrollback；
+//! `session_to_terminal` 因此记录 `session_id -> Vec<terminal_id>`，最后一个子终端
+//! 关闭时才真正释放共享的 session。见 `open`/`close`/`close_by_session`。
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::TrySendError;
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::time::Instant;
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
 use ssh2::{Channel, Session};
 use tauri::{AppHandle, Emitter};
 
@@ -21,14 +35,59 @@ use crate::services::storage_service::Database;
 const DEFAULT_COLS: u16 = 80;
 const DEFAULT_ROWS: u16 = 24;
 const PTY_READ_BUFFER_SIZE: usize = 8192;
-const OUTPUT_THROTTLE_MS: u64 = 16;
-const OUTPUT_BUFFER_LIMIT: usize = 4096;
+/// `output_throttle_ms`/`output_buffer_limit` 未显式指定时使用的默认值
+const DEFAULT_OUTPUT_THROTTLE_MS: u64 = 16;
+const DEFAULT_OUTPUT_BUFFER_LIMIT: usize = 4096;
+/// 读取线程与 drain 线程之间有界 channel 的容量（单位：累积后的数据块个数）
+const OUTPUT_CHANNEL_CAPACITY: usize = 64;
+/// channel 持续满载时的重试间隔
+const OUTPUT_SEND_RETRY_INTERVAL_MS: u64 = 5;
+/// 重试超过这个时长仍无法送入 channel，则丢弃本次数据块并计入 dropped_bytes
+const OUTPUT_SEND_MAX_WAIT_MS: u64 = 2000;
+/// `scrollback_cap` 未显式指定时使用的默认值：保留最近 256 KiB 输出
+const DEFAULT_SCROLLBACK_CAP: usize = 256 * 1024;
+/// asciicast v2 固定版本号
+const ASCIICAST_VERSION: u8 = 2;
+
+/// asciicast v2 录制文件头（单独一行 JSON）
+#[derive(Debug, Serialize, Deserialize)]
+struct AsciicastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+}
+
+/// 一个终端正在进行的录制：每次输出/尺寸调整追加一行 `[t, code, data]` JSON 数组，
+/// `t` 为相对 `started_at` 的秒数
+struct RecordingState {
+    file: std::fs::File,
+    started_at: Instant,
+}
+
+/// 非交互式命令执行的原始结果（stdout/stderr 为未编码的原始字节，由调用方按需编码传输）
+pub struct ExecOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: i32,
+}
+
+/// 多个 PTY channel 共享的底层 SSH session 句柄
+///
+/// libssh2 的 session 本身不是线程安全的：同一 session 下不同 channel 的读写必须
+/// 互斥执行，否则底层 socket 上的数据会错乱。`session` 用 `Arc` 在多个
+/// `ManagedTerminal` 之间共享所有权，`io_lock` 序列化所有 channel 级操作
+/// （创建 channel、read/write、resize），粒度为整个 session 而非单个 channel。
+struct SharedHostSession {
+    session: Arc<Session>,
+    io_lock: Mutex<()>,
+}
 
 /// 托管的终端实例（包含独立的非阻塞 SSH session）
 pub struct ManagedTerminal {
     pub terminal_id: String,
     pub session_id: String,
-    pub ssh_session: Session,
+    host_session: Arc<SharedHostSession>,
     pub channel: Mutex<Channel>,
     pub cols: u16,
     pub rows: u16,
@@ -36,6 +95,18 @@ pub struct ManagedTerminal {
     pub last_activity: RwLock<Instant>,
     /// 通知输出读取线程退出
     pub shutdown: AtomicBool,
+    /// 进行中的录制（未录制时为 None）
+    recording: Mutex<Option<RecordingState>>,
+    /// 本终端的输出节流间隔（毫秒），`open()` 时指定，默认 `DEFAULT_OUTPUT_THROTTLE_MS`
+    output_throttle_ms: u64,
+    /// 本终端单次 emit 前允许累积的最大字节数，默认 `DEFAULT_OUTPUT_BUFFER_LIMIT`
+    output_buffer_limit: usize,
+    /// 输出 channel 持续拥塞、数据块被丢弃时累计的字节数
+    dropped_bytes: AtomicU64,
+    /// 最近输出的滚动缓冲区，用于重连/切换标签页时恢复可见历史
+    scrollback: Mutex<VecDeque<u8>>,
+    /// `scrollback` 的容量上限（字节），默认 `DEFAULT_SCROLLBACK_CAP`
+    scrollback_cap: usize,
 }
 
 impl ManagedTerminal {
@@ -44,12 +115,75 @@ impl ManagedTerminal {
             *last = Instant::now();
         }
     }
+
+    /// 把一段输出追加到滚动缓冲区，超过 `scrollback_cap` 时从头部裁剪
+    fn append_scrollback(&self, data: &[u8]) {
+        let Ok(mut buf) = self.scrollback.lock() else {
+            return;
+        };
+        buf.extend(data.iter().copied());
+        if buf.len() > self.scrollback_cap {
+            let excess = buf.len() - self.scrollback_cap;
+            buf.drain(..excess);
+        }
+    }
+
+    /// 若正在录制，追加一行输出事件；`data` 是 PTY 原始字节，录制时按 UTF-8（有损）转换
+    fn record_output(&self, data: &[u8]) {
+        let Ok(mut guard) = self.recording.lock() else {
+            return;
+        };
+        let Some(state) = guard.as_mut() else {
+            return;
+        };
+
+        let elapsed = state.started_at.elapsed().as_secs_f64();
+        let text = String::from_utf8_lossy(data);
+        match serde_json::to_string(&(elapsed, "o", text.as_ref())) {
+            Ok(line) => {
+                if writeln!(state.file, "{}", line).is_err() {
+                    tracing::warn!(terminal_id = %self.terminal_id, "写入录制文件失败");
+                }
+            }
+            Err(e) => {
+                tracing::warn!(terminal_id = %self.terminal_id, error = %e, "录制事件序列化失败");
+            }
+        }
+    }
+
+    /// 若正在录制，追加一行尺寸调整事件
+    fn record_resize(&self, cols: u16, rows: u16) {
+        let Ok(mut guard) = self.recording.lock() else {
+            return;
+        };
+        let Some(state) = guard.as_mut() else {
+            return;
+        };
+
+        let elapsed = state.started_at.elapsed().as_secs_f64();
+        let geometry = format!("{}x{}", cols, rows);
+        match serde_json::to_string(&(elapsed, "r", geometry.as_str())) {
+            Ok(line) => {
+                if writeln!(state.file, "{}", line).is_err() {
+                    tracing::warn!(terminal_id = %self.terminal_id, "写入录制文件失败");
+                }
+            }
+            Err(e) => {
+                tracing::warn!(terminal_id = %self.terminal_id, error = %e, "录制事件序列化失败");
+            }
+        }
+    }
 }
 
 /// 终端管理器
 pub struct TerminalManager {
     terminals: RwLock<HashMap<String, Arc<ManagedTerminal>>>,
-    session_to_terminal: RwLock<HashMap<String, String>>,
+    /// `session_id -> terminal_id` 列表；一个 session 下可同时挂载多个 PTY（split
+    /// pane / 多标签页），它们共享同一条底层 SSH 连接，见 `host_sessions`
+    session_to_terminal: RwLock<HashMap<String, Vec<String>>>,
+    /// `session_id -> 共享 session 句柄`；同一 session_id 的第一个 `open()` 调用
+    /// 拨号建连并登记于此，后续调用复用它，直到最后一个子终端关闭时移除
+    host_sessions: RwLock<HashMap<String, Arc<SharedHostSession>>>,
 }
 
 impl TerminalManager {
@@ -57,10 +191,23 @@ impl TerminalManager {
         Self {
             terminals: RwLock::new(HashMap::new()),
             session_to_terminal: RwLock::new(HashMap::new()),
+            host_sessions: RwLock::new(HashMap::new()),
         }
     }
 
-    /// 打开终端（已存在则返回现有实例）
+    /// 打开一个新的 PTY 终端
+    ///
+    /// 同一 `session_id` 可重复调用：首次调用会拨号建立 SSH 连接，之后的调用复用
+    /// 该连接（多路复用多个 channel），每次都会创建一个**新的**独立 PTY/标签页，
+    /// 而不是像早期版本那样返回已存在的实例——如需多个分屏/标签页指向同一台主机，
+    /// 直接多次调用 `open()` 即可。
+    ///
+    /// `output_throttle_ms`/`output_buffer_limit` 控制该终端输出累积到 drain 线程
+    /// 之前的节流参数，不传则使用 `DEFAULT_OUTPUT_THROTTLE_MS`/`DEFAULT_OUTPUT_BUFFER_LIMIT`；
+    /// 对输出量很大的会话（例如 `tail -f` 大日志）可以调大 buffer 以减少 emit 次数。
+    /// `scrollback_cap` 控制滚动缓冲区容量（字节），不传则使用 `DEFAULT_SCROLLBACK_CAP`，
+    /// 配合 `get_scrollback` 在重连/切换标签页时恢复可见历史。
+    #[allow(clippy::too_many_arguments)]
     pub fn open(
         &self,
         app: AppHandle,
@@ -69,45 +216,67 @@ impl TerminalManager {
         session_id: &str,
         cols: Option<u16>,
         rows: Option<u16>,
+        output_throttle_ms: Option<u64>,
+        output_buffer_limit: Option<usize>,
+        scrollback_cap: Option<usize>,
     ) -> AppResult<TerminalInfo> {
-        // 检查是否已有终端
-        {
-            let mapping = self.session_to_terminal.read().map_err(|_| {
-                AppError::new(ErrorCode::Unknown, "无法获取终端映射锁")
+        let existing_session = {
+            let host_sessions = self.host_sessions.read().map_err(|_| {
+                AppError::new(ErrorCode::Unknown, "无法获取共享 session 池锁")
             })?;
-            if let Some(terminal_id) = mapping.get(session_id) {
-                tracing::info!(
-                    session_id = %session_id,
-                    terminal_id = %terminal_id,
-                    "终端已存在，返回现有实例"
-                );
-                return Ok(TerminalInfo {
-                    terminal_id: terminal_id.clone(),
-                    session_id: session_id.to_string(),
+            host_sessions.get(session_id).cloned()
+        };
+
+        let host_session = match existing_session {
+            Some(host_session) => host_session,
+            None => {
+                let session = session_manager.create_terminal_session(db, session_id, None)?;
+                let host_session = Arc::new(SharedHostSession {
+                    session,
+                    io_lock: Mutex::new(()),
                 });
-            }
-        }
 
-        let ssh_session = session_manager.create_terminal_session(db, session_id)?;
+                let mut host_sessions = self.host_sessions.write().map_err(|_| {
+                    AppError::new(ErrorCode::Unknown, "无法获取共享 session 池锁")
+                })?;
+                host_sessions
+                    .entry(session_id.to_string())
+                    .or_insert(host_session)
+                    .clone()
+            }
+        };
 
         let cols = cols.unwrap_or(DEFAULT_COLS);
         let rows = rows.unwrap_or(DEFAULT_ROWS);
-        let channel = Self::create_pty_channel(&ssh_session, cols, rows)?;
 
-        // Channel 创建完成后切换到非阻塞模式
-        ssh_session.set_blocking(false);
+        // channel 创建、PTY 请求、切换非阻塞模式都是 session 级操作，持锁期间
+        // 序列化执行，避免与其他共享该 session 的 channel 交叉操作底层 socket
+        let channel = {
+            let _guard = host_session.io_lock.lock().map_err(|_| {
+                AppError::new(ErrorCode::Unknown, "无法获取 session I/O 锁")
+            })?;
+            let channel = Self::create_pty_channel(&host_session.session, cols, rows)?;
+            host_session.session.set_blocking(false);
+            channel
+        };
 
         let terminal_id = uuid::Uuid::new_v4().to_string();
         let managed_terminal = Arc::new(ManagedTerminal {
             terminal_id: terminal_id.clone(),
             session_id: session_id.to_string(),
-            ssh_session,
+            host_session,
             channel: Mutex::new(channel),
             cols,
             rows,
             created_at: Instant::now(),
             last_activity: RwLock::new(Instant::now()),
             shutdown: AtomicBool::new(false),
+            recording: Mutex::new(None),
+            output_throttle_ms: output_throttle_ms.unwrap_or(DEFAULT_OUTPUT_THROTTLE_MS),
+            output_buffer_limit: output_buffer_limit.unwrap_or(DEFAULT_OUTPUT_BUFFER_LIMIT),
+            dropped_bytes: AtomicU64::new(0),
+            scrollback: Mutex::new(VecDeque::new()),
+            scrollback_cap: scrollback_cap.unwrap_or(DEFAULT_SCROLLBACK_CAP),
         });
 
         {
@@ -120,7 +289,10 @@ impl TerminalManager {
             let mut mapping = self.session_to_terminal.write().map_err(|_| {
                 AppError::new(ErrorCode::Unknown, "无法获取终端映射锁")
             })?;
-            mapping.insert(session_id.to_string(), terminal_id.clone());
+            mapping
+                .entry(session_id.to_string())
+                .or_default()
+                .push(terminal_id.clone());
         }
 
         self.start_output_reader(app, managed_terminal.clone());
@@ -139,6 +311,65 @@ impl TerminalManager {
         })
     }
 
+    /// 非交互式执行一条远程命令，阻塞直至命令退出，一次性返回完整 stdout/stderr 与退出码
+    ///
+    /// 与 `open()` 的交互式 PTY（`channel.shell()`）不同：这里在一个独立的一次性 SSH
+    /// session 上打开 exec channel（与 ssh-rs 客户端 `open_exec`/`send_command` 的模式
+    /// 一致），保持阻塞模式读完 stdout/`channel.stderr()` 后 `wait_close()` 取退出码；
+    /// 不登记到 `terminals`/`session_to_terminal`，调用返回后 channel 随之释放，
+    /// 不产生常驻状态——适合文件浏览器里 `stat`/`ls -l` 这类一次性查询，
+    /// 比起打开一整个 PTY 终端更轻量，也不会污染终端 scrollback
+    pub fn exec(
+        &self,
+        db: &Database,
+        session_manager: Arc<SessionManager>,
+        session_id: &str,
+        command: &str,
+    ) -> AppResult<ExecOutput> {
+        let ssh_session = session_manager.create_terminal_session(db, session_id, None)?;
+
+        let mut channel = ssh_session.channel_session().map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("无法创建 channel: {}", e))
+        })?;
+
+        channel.exec(command).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("执行命令失败: {}", e))
+        })?;
+
+        let mut stdout = Vec::new();
+        channel.read_to_end(&mut stdout).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("读取 stdout 失败: {}", e))
+        })?;
+
+        let mut stderr = Vec::new();
+        channel.stderr().read_to_end(&mut stderr).map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("读取 stderr 失败: {}", e))
+        })?;
+
+        channel.wait_close().map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("等待 channel 关闭失败: {}", e))
+        })?;
+
+        let exit_code = channel.exit_status().map_err(|e| {
+            AppError::new(ErrorCode::RemoteIoError, format!("读取退出码失败: {}", e))
+        })?;
+
+        tracing::debug!(
+            session_id = %session_id,
+            command = %command,
+            exit_code,
+            stdout_len = stdout.len(),
+            stderr_len = stderr.len(),
+            "非交互命令执行完成"
+        );
+
+        Ok(ExecOutput {
+            stdout,
+            stderr,
+            exit_code,
+        })
+    }
+
     fn create_pty_channel(session: &Session, cols: u16, rows: u16) -> AppResult<Channel> {
         let mut channel = session.channel_session().map_err(|e| {
             AppError::new(ErrorCode::RemoteIoError, format!("无法创建 channel: {}", e))
@@ -158,12 +389,86 @@ impl TerminalManager {
     }
 
     fn start_output_reader(&self, app: AppHandle, terminal: Arc<ManagedTerminal>) {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(OUTPUT_CHANNEL_CAPACITY);
+
+        // drain 线程：独立消费有界 channel，完成录制钩子、base64 编码与 app.emit，
+        // 把这些较慢的操作从 PTY 读取线程里挪出去
+        {
+            let app = app.clone();
+            let terminal = terminal.clone();
+            thread::spawn(move || {
+                while let Ok(chunk) = rx.recv() {
+                    terminal.record_output(&chunk);
+                    let payload = TerminalOutputPayload {
+                        terminal_id: terminal.terminal_id.clone(),
+                        data: BASE64.encode(&chunk),
+                    };
+                    app.emit("terminal:output", &payload).ok();
+                }
+                tracing::debug!(
+                    terminal_id = %terminal.terminal_id,
+                    "输出 drain 线程已退出"
+                );
+            });
+        }
+
         thread::spawn(move || {
             // 使用初始缓冲区代替硬编码 sleep，避免竞态条件
             // 前端监听器注册前的输出会被缓冲，首次 emit 时一并发送
             let mut buffer = vec![0u8; PTY_READ_BUFFER_SIZE];
             let mut last_emit = Instant::now();
-            let mut accumulated_data = Vec::with_capacity(OUTPUT_BUFFER_LIMIT * 2);
+            let mut accumulated_data = Vec::with_capacity(terminal.output_buffer_limit * 2);
+
+            // 把累积的数据块推入有界 channel；channel 满时退避重试，形成针对远程 shell
+            // 的真实背压。重试超过 OUTPUT_SEND_MAX_WAIT_MS 仍未送入，则放弃本次数据块
+            // 并计入 dropped_bytes——避免持续拥塞时读取线程被无限期阻塞，导致 shutdown
+            // 信号和 close() 都等不到响应
+            let flush = |data: &mut Vec<u8>| {
+                if data.is_empty() {
+                    return;
+                }
+                terminal.append_scrollback(data);
+
+                let chunk_len = data.len() as u64;
+                let mut pending = std::mem::take(data);
+                let mut waited_ms: u64 = 0;
+
+                loop {
+                    match tx.try_send(pending) {
+                        Ok(()) => return,
+                        Err(TrySendError::Full(returned)) => {
+                            if waited_ms >= OUTPUT_SEND_MAX_WAIT_MS
+                                || terminal.shutdown.load(Ordering::Relaxed)
+                            {
+                                let total = terminal
+                                    .dropped_bytes
+                                    .fetch_add(chunk_len, Ordering::Relaxed)
+                                    + chunk_len;
+                                tracing::warn!(
+                                    terminal_id = %terminal.terminal_id,
+                                    bytes = chunk_len,
+                                    total_dropped = total,
+                                    "输出 channel 持续拥塞，丢弃本次数据块"
+                                );
+                                let payload = TerminalStatusPayload {
+                                    terminal_id: terminal.terminal_id.clone(),
+                                    status: TerminalStatus::Connected,
+                                    message: Some("输出过快，部分内容已被丢弃".to_string()),
+                                    dropped_bytes: Some(total),
+                                };
+                                app.emit("terminal:status", &payload).ok();
+                                return;
+                            }
+                            pending = returned;
+                            thread::sleep(std::time::Duration::from_millis(
+                                OUTPUT_SEND_RETRY_INTERVAL_MS,
+                            ));
+                            waited_ms += OUTPUT_SEND_RETRY_INTERVAL_MS;
+                        }
+                        Err(TrySendError::Disconnected(_)) => return,
+                    }
+                }
+            };
 
             loop {
                 // 检查 shutdown 信号
@@ -176,6 +481,19 @@ impl TerminalManager {
                 }
 
                 let read_result = {
+                    // 同一底层 session 可能被其他 channel（其他标签页/分屏）共享，
+                    // 持 io_lock 期间才能安全地读取，避免交叉读写同一 socket
+                    let _io_guard = match terminal.host_session.io_lock.lock() {
+                        Ok(g) => g,
+                        Err(e) => {
+                            tracing::error!(
+                                terminal_id = %terminal.terminal_id,
+                                error = %e,
+                                "Session I/O 锁已中毒，终止读取线程"
+                            );
+                            break;
+                        }
+                    };
                     let mut channel_guard = match terminal.channel.lock() {
                         Ok(c) => c,
                         Err(e) => {
@@ -215,13 +533,7 @@ impl TerminalManager {
                         {
                             // WouldBlock: 立即发送已累积的数据
                             if !accumulated_data.is_empty() {
-                                let data_base64 = BASE64.encode(&accumulated_data);
-                                let payload = TerminalOutputPayload {
-                                    terminal_id: terminal.terminal_id.clone(),
-                                    data: data_base64,
-                                };
-                                app.emit("terminal:output", &payload).ok();
-                                accumulated_data.clear();
+                                flush(&mut accumulated_data);
                                 last_emit = Instant::now();
                             }
                             thread::sleep(std::time::Duration::from_millis(1));
@@ -239,18 +551,11 @@ impl TerminalManager {
                 accumulated_data.extend_from_slice(&buffer[..bytes_read]);
 
                 let should_emit = !accumulated_data.is_empty()
-                    && (last_emit.elapsed().as_millis() as u64 >= OUTPUT_THROTTLE_MS
-                        || accumulated_data.len() >= OUTPUT_BUFFER_LIMIT);
+                    && (last_emit.elapsed().as_millis() as u64 >= terminal.output_throttle_ms
+                        || accumulated_data.len() >= terminal.output_buffer_limit);
 
                 if should_emit {
-                    let data_base64 = BASE64.encode(&accumulated_data);
-                    let payload = TerminalOutputPayload {
-                        terminal_id: terminal.terminal_id.clone(),
-                        data: data_base64,
-                    };
-
-                    app.emit("terminal:output", &payload).ok();
-                    accumulated_data.clear();
+                    flush(&mut accumulated_data);
                     last_emit = Instant::now();
                 }
 
@@ -261,6 +566,7 @@ impl TerminalManager {
                 terminal_id: terminal.terminal_id.clone(),
                 status: TerminalStatus::Disconnected,
                 message: Some("终端已关闭".to_string()),
+                dropped_bytes: None,
             };
             app.emit("terminal:status", &payload).ok();
 
@@ -274,6 +580,9 @@ impl TerminalManager {
     pub fn write_input(&self, terminal_id: &str, data: &[u8]) -> AppResult<()> {
         let terminal = self.get_terminal(terminal_id)?;
 
+        let _io_guard = terminal.host_session.io_lock.lock().map_err(|_| {
+            AppError::new(ErrorCode::Unknown, "无法获取 session I/O 锁")
+        })?;
         let mut channel = terminal.channel.lock().map_err(|_| {
             AppError::new(ErrorCode::Unknown, "无法获取 channel 锁")
         })?;
@@ -293,6 +602,9 @@ impl TerminalManager {
     pub fn resize(&self, terminal_id: &str, cols: u16, rows: u16) -> AppResult<()> {
         let terminal = self.get_terminal(terminal_id)?;
 
+        let _io_guard = terminal.host_session.io_lock.lock().map_err(|_| {
+            AppError::new(ErrorCode::Unknown, "无法获取 session I/O 锁")
+        })?;
         let mut channel = terminal.channel.lock().map_err(|_| {
             AppError::new(ErrorCode::Unknown, "无法获取 channel 锁")
         })?;
@@ -301,6 +613,8 @@ impl TerminalManager {
             AppError::new(ErrorCode::RemoteIoError, format!("调整尺寸失败: {}", e))
         })?;
 
+        terminal.record_resize(cols, rows);
+
         tracing::debug!(
             terminal_id = %terminal_id,
             cols = cols,
@@ -311,6 +625,11 @@ impl TerminalManager {
         Ok(())
     }
 
+    /// 关闭单个 PTY 终端（只影响它自己的 channel）
+    ///
+    /// 仅当这是其 `session_id` 下最后一个子终端时，才会一并释放共享的底层 SSH
+    /// session（`host_sessions` 中移除对应条目）；否则该 session 继续被其余标签页/
+    /// 分屏持有。
     pub fn close(&self, terminal_id: &str) -> AppResult<()> {
         let terminal = {
             let mut terminals = self.terminals.write().map_err(|_| {
@@ -323,14 +642,38 @@ impl TerminalManager {
             // 先发送 shutdown 信号，让输出读取线程退出
             term.shutdown.store(true, Ordering::Relaxed);
 
-            let mut mapping = self.session_to_terminal.write().map_err(|_| {
-                AppError::new(ErrorCode::Unknown, "无法获取终端映射锁")
-            })?;
-            mapping.remove(&term.session_id);
+            let is_last_child = {
+                let mut mapping = self.session_to_terminal.write().map_err(|_| {
+                    AppError::new(ErrorCode::Unknown, "无法获取终端映射锁")
+                })?;
+                let mut last = false;
+                if let Some(siblings) = mapping.get_mut(&term.session_id) {
+                    siblings.retain(|id| id != terminal_id);
+                    if siblings.is_empty() {
+                        mapping.remove(&term.session_id);
+                        last = true;
+                    }
+                }
+                last
+            };
 
-            if let Ok(mut channel) = term.channel.lock() {
-                channel.close().ok();
-                channel.wait_close().ok();
+            {
+                let _io_guard = term.host_session.io_lock.lock().ok();
+                if let Ok(mut channel) = term.channel.lock() {
+                    channel.close().ok();
+                    channel.wait_close().ok();
+                }
+            }
+
+            if is_last_child {
+                let mut host_sessions = self.host_sessions.write().map_err(|_| {
+                    AppError::new(ErrorCode::Unknown, "无法获取共享 session 池锁")
+                })?;
+                host_sessions.remove(&term.session_id);
+                tracing::debug!(
+                    session_id = %term.session_id,
+                    "最后一个子终端已关闭，释放共享 SSH session"
+                );
             }
 
             tracing::info!(
@@ -343,23 +686,231 @@ impl TerminalManager {
         Ok(())
     }
 
-    pub fn get_terminal_by_session(&self, session_id: &str) -> Option<String> {
+    /// 当前打开的终端数量，供 `system_monitor` 采样使用
+    pub fn terminal_count(&self) -> usize {
+        self.terminals.read().map(|t| t.len()).unwrap_or(0)
+    }
+
+    /// 关闭当前打开的全部终端，返回实际关闭的数量
+    ///
+    /// 供应用退出时的优雅关闭流程调用（见 `services::shutdown`），逐个走标准的
+    /// [`Self::close`] 以复用其中共享 SSH session 的释放逻辑
+    pub fn close_all(&self) -> usize {
+        let terminal_ids: Vec<String> = self
+            .terminals
+            .read()
+            .map(|t| t.keys().cloned().collect())
+            .unwrap_or_default();
+
+        let mut closed = 0;
+        for terminal_id in terminal_ids {
+            match self.close(&terminal_id) {
+                Ok(()) => closed += 1,
+                Err(e) => tracing::warn!(terminal_id = %terminal_id, error = %e, "关闭终端失败"),
+            }
+        }
+        closed
+    }
+
+    /// 获取一个 session_id 下当前打开的所有终端 id（可能有多个，见 `open` 的多路复用说明）
+    pub fn get_terminal_by_session(&self, session_id: &str) -> Vec<String> {
         self.session_to_terminal
             .read()
-            .ok()?
-            .get(session_id)
-            .cloned()
+            .ok()
+            .and_then(|mapping| mapping.get(session_id).cloned())
+            .unwrap_or_default()
     }
 
-    /// 根据 session_id 关闭终端
-    /// 复用 get_terminal_by_session + close，避免锁顺序问题
+    /// 根据 session_id 关闭该会话下的全部子终端
     pub fn close_by_session(&self, session_id: &str) -> AppResult<()> {
-        if let Some(terminal_id) = self.get_terminal_by_session(session_id) {
+        for terminal_id in self.get_terminal_by_session(session_id) {
             self.close(&terminal_id)?;
         }
         Ok(())
     }
 
+    /// 获取终端当前的滚动缓冲区快照（最近 `scrollback_cap` 字节的原始输出）
+    ///
+    /// 供前端在重连或切换标签页重新挂载终端面板时一次性拉取已有历史，避免空白屏幕；
+    /// 不会清空缓冲区，可重复调用
+    pub fn get_scrollback(&self, terminal_id: &str) -> AppResult<Vec<u8>> {
+        let terminal = self.get_terminal(terminal_id)?;
+        let buf = terminal
+            .scrollback
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取滚动缓冲区锁"))?;
+        Ok(buf.iter().copied().collect())
+    }
+
+    /// 扫描所有终端，关闭 `last_activity` 超过 `idle_ttl_secs` 的实例
+    ///
+    /// `idle_ttl_secs` 为 0 时视为禁用，直接返回。每次调用是一次性扫描，需由调用方
+    /// （见 `lib.rs` 里的周期性 tokio 任务）定时触发；关闭前先按同样的
+    /// `terminal:status` 事件通道发一条区别于正常关闭的超时提示，再走标准的
+    /// `close()` 流程（置 shutdown、移除映射、关闭 channel）。
+    pub fn reap_idle(&self, app: &AppHandle, idle_ttl_secs: u64) {
+        if idle_ttl_secs == 0 {
+            return;
+        }
+        let idle_ttl = std::time::Duration::from_secs(idle_ttl_secs);
+
+        let expired: Vec<String> = {
+            let Ok(terminals) = self.terminals.read() else {
+                return;
+            };
+            terminals
+                .values()
+                .filter(|t| {
+                    t.last_activity
+                        .read()
+                        .map(|last| last.elapsed() >= idle_ttl)
+                        .unwrap_or(false)
+                })
+                .map(|t| t.terminal_id.clone())
+                .collect()
+        };
+
+        for terminal_id in expired {
+            tracing::info!(
+                terminal_id = %terminal_id,
+                idle_ttl_secs,
+                "终端空闲超时，自动关闭"
+            );
+
+            let payload = TerminalStatusPayload {
+                terminal_id: terminal_id.clone(),
+                status: TerminalStatus::Disconnected,
+                message: Some(format!("空闲超过 {} 秒，已自动关闭连接", idle_ttl_secs)),
+                dropped_bytes: None,
+            };
+            app.emit("terminal:status", &payload).ok();
+
+            if let Err(e) = self.close(&terminal_id) {
+                tracing::warn!(terminal_id = %terminal_id, error = %e, "空闲终端关闭失败");
+            }
+        }
+    }
+
+    /// 开始将终端会话录制为 asciicast v2 格式的 cast 文件
+    ///
+    /// 写入头部 `{"version":2,"width":...,"height":...,"timestamp":...}` 后，后续每次
+    /// `start_output_reader` 向前端 emit 输出前都会追加一行 `[t, "o", data]`，`resize()`
+    /// 则追加 `[t, "r", "<cols>x<rows>"]`；重复调用会覆盖上一次未停止的录制
+    pub fn start_recording(&self, terminal_id: &str, path: &str) -> AppResult<()> {
+        let terminal = self.get_terminal(terminal_id)?;
+
+        let mut file = std::fs::File::create(path)
+            .map_err(|e| AppError::local_io_error(format!("无法创建录制文件: {}", e)))?;
+
+        let header = AsciicastHeader {
+            version: ASCIICAST_VERSION,
+            width: terminal.cols,
+            height: terminal.rows,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        let header_line = serde_json::to_string(&header)
+            .map_err(|e| AppError::local_io_error(format!("录制文件头序列化失败: {}", e)))?;
+        writeln!(file, "{}", header_line)
+            .map_err(|e| AppError::local_io_error(format!("写入录制文件头失败: {}", e)))?;
+
+        let mut recording = terminal
+            .recording
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取录制状态锁"))?;
+        *recording = Some(RecordingState {
+            file,
+            started_at: Instant::now(),
+        });
+
+        tracing::info!(terminal_id = %terminal_id, path = %path, "开始录制终端会话");
+        Ok(())
+    }
+
+    /// 停止终端会话录制；未在录制中时静默成功
+    pub fn stop_recording(&self, terminal_id: &str) -> AppResult<()> {
+        let terminal = self.get_terminal(terminal_id)?;
+
+        let mut recording = terminal
+            .recording
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取录制状态锁"))?;
+        if recording.take().is_some() {
+            tracing::info!(terminal_id = %terminal_id, "停止录制终端会话");
+        }
+
+        Ok(())
+    }
+
+    /// 回放一个 asciicast v2 录制文件
+    ///
+    /// 按事件记录的相对时间间隔依次 sleep 后通过 `terminal:output` 事件重放输出内容
+    /// （"r" 尺寸调整事件不产生输出，忽略），结束时发送一次 `terminal:status` Disconnected。
+    /// 回放不关联任何真实 PTY/会话，返回的 terminal_id 仅用于区分这次回放的事件流，
+    /// 前端可将其当作只读终端面板监听同一套事件
+    pub fn replay(&self, app: AppHandle, path: &str) -> AppResult<String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::local_io_error(format!("无法读取录制文件: {}", e)))?;
+
+        let mut lines = content.lines();
+        let header_line = lines
+            .next()
+            .ok_or_else(|| AppError::invalid_argument("录制文件为空"))?;
+        let header: AsciicastHeader = serde_json::from_str(header_line)
+            .map_err(|e| AppError::invalid_argument(format!("录制文件头解析失败: {}", e)))?;
+
+        let events: Vec<(f64, String, String)> = lines
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+
+        let replay_id = uuid::Uuid::new_v4().to_string();
+
+        tracing::info!(
+            replay_id = %replay_id,
+            path = %path,
+            width = header.width,
+            height = header.height,
+            event_count = events.len(),
+            "开始回放终端录制"
+        );
+
+        let replay_id_clone = replay_id.clone();
+        thread::spawn(move || {
+            let mut last_t = 0.0f64;
+
+            for (t, code, data) in events {
+                let delay = (t - last_t).max(0.0);
+                if delay > 0.0 {
+                    thread::sleep(std::time::Duration::from_secs_f64(delay));
+                }
+                last_t = t;
+
+                if code == "o" {
+                    let payload = TerminalOutputPayload {
+                        terminal_id: replay_id_clone.clone(),
+                        data: BASE64.encode(data.as_bytes()),
+                    };
+                    app.emit("terminal:output", &payload).ok();
+                }
+            }
+
+            let payload = TerminalStatusPayload {
+                terminal_id: replay_id_clone.clone(),
+                status: TerminalStatus::Disconnected,
+                message: Some("回放结束".to_string()),
+                dropped_bytes: None,
+            };
+            app.emit("terminal:status", &payload).ok();
+
+            tracing::info!(replay_id = %replay_id_clone, "终端录制回放结束");
+        });
+
+        Ok(replay_id)
+    }
+
     fn get_terminal(&self, terminal_id: &str) -> AppResult<Arc<ManagedTerminal>> {
         let terminals = self.terminals.read().map_err(|_| {
             AppError::new(ErrorCode::Unknown, "无法获取终端池锁")
@@ -379,12 +930,15 @@ impl Default for TerminalManager {
 }
 
 // SAFETY: TerminalManager 可以安全地跨线程共享，原因如下：
-// 1. terminals 和 session_to_terminal 使用 RwLock 保护，提供线程安全的访问
+// 1. terminals、session_to_terminal、host_sessions 均使用 RwLock 保护，提供
+//    线程安全的访问
 // 2. ManagedTerminal 中的 Channel 使用 Mutex 保护
-// 3. ssh2::Session 虽然不是 Send/Sync，但每个 ManagedTerminal 的 Session 仅在
-//    其专属的 output_reader 线程中通过 channel.read() 访问
-// 4. 写入操作 (write_input) 通过 Mutex<Channel> 序列化，不直接访问 Session
-// 5. 所有 Session 的其他操作 (如 resize) 也通过 Mutex<Channel> 进行
+// 3. ssh2::Session 虽然不是 Send/Sync，但多个 ManagedTerminal 共享同一
+//    SharedHostSession 时，所有涉及该 session 的 channel 级操作（创建 channel、
+//    read/write、resize、close）都必须先获取 SharedHostSession::io_lock，
+//    序列化执行后才允许访问，从而避免多线程交叉操作同一 session
+// 4. 写入操作 (write_input) 通过先 io_lock 后 Mutex<Channel> 的固定顺序获取锁
+// 5. 所有 Session 的其他操作 (如 resize) 也遵循同样的加锁顺序
 unsafe impl Send for TerminalManager {}
 unsafe impl Sync for TerminalManager {}
 
@@ -395,7 +949,7 @@ mod tests {
     #[test]
     fn test_terminal_manager_creation() {
         let manager = TerminalManager::new();
-        assert!(manager.get_terminal_by_session("nonexistent").is_none());
+        assert!(manager.get_terminal_by_session("nonexistent").is_empty());
     }
 
     #[test]
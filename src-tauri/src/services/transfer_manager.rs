@@ -7,22 +7,28 @@
 //! - 取消和重试
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{Read, Write};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
-use ssh2::Sftp;
+use sha2::{Digest, Sha256};
+use ssh2::{OpenFlags, OpenType, Session, Sftp};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{RwLock, Semaphore};
 use tokio_util::sync::CancellationToken;
 
 use crate::models::error::{AppError, AppResult, ErrorCode};
 use crate::models::transfer_task::{
-    TransferDirection, TransferProgressPayload, TransferStatus, TransferStatusPayload, TransferTask,
+    DirSyncResultPayload, DirTransferResult, TransferDirection, TransferProgressPayload,
+    TransferStatus, TransferStatusPayload, TransferTask,
 };
-use crate::services::session_manager::{ManagedSession, SessionManager};
+use crate::services::retry::{retry_with_backoff, RetryPolicy};
+use crate::services::session_manager::{AuxiliarySftpConnection, ManagedSession, SessionManager};
+use crate::services::storage_service::Database;
 
 /// 传输块大小 (64KB)
 const CHUNK_SIZE: usize = 64 * 1024;
@@ -33,6 +39,134 @@ const PROGRESS_THROTTLE_MS: u128 = 200;
 /// 默认重试次数
 const DEFAULT_RETRY_COUNT: u8 = 2;
 
+/// 退避重试的基础延迟 (秒)
+const RETRY_BACKOFF_BASE_SECS: u64 = 5;
+
+/// 退避重试的最大延迟 (秒)
+const RETRY_BACKOFF_MAX_SECS: u64 = 300;
+
+/// 已终结任务的默认保留时长 (毫秒)，超过此时长的已完成任务会被 `cleanup_completed` 清理
+const DEFAULT_RETENTION_MAX_AGE_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// 多流并行传输轮询汇总进度的间隔
+const PARALLEL_POLL_INTERVAL_MS: u64 = 100;
+
+/// 将 `[0, total)` 切分为最多 `streams` 个连续、大小近似相等的左闭右开区间
+///
+/// 当 `total` 小于 `streams` 时，返回的区间数会相应减少
+fn split_ranges(total: u64, streams: u8) -> Vec<(u64, u64)> {
+    let streams = (streams.max(1) as u64).min(total.max(1));
+    let chunk = total / streams;
+    let mut ranges = Vec::with_capacity(streams as usize);
+    let mut start = 0u64;
+    for i in 0..streams {
+        let end = if i == streams - 1 {
+            total
+        } else {
+            start + chunk
+        };
+        if end > start {
+            ranges.push((start, end));
+        }
+        start = end;
+    }
+    ranges
+}
+
+/// 多流并行传输所需的上下文：开启额外 SFTP 通道所需的依赖，以及触发并行的阈值配置
+struct ParallelCtx<'a> {
+    session_manager: &'a Arc<SessionManager>,
+    db: &'a Arc<Database>,
+    /// 触发并行传输的文件大小阈值（字节）
+    threshold_bytes: u64,
+    /// 并行流数量
+    streams: u8,
+    /// 传输完成后是否将源文件的权限和修改时间应用到目标文件
+    preserve_metadata: bool,
+    /// 管理器级别的全局默认限速（字节/秒），0 表示不限速；任务自身的
+    /// `speed_limit_bytes_per_sec` 覆盖值优先于此默认值
+    default_speed_limit_bytes_per_sec: u64,
+    /// 传输成功后是否校验远程文件的校验和
+    verify_checksum: bool,
+    /// 远程计算校验和使用的命令
+    checksum_command: String,
+    /// 触发校验和校验的文件大小阈值（字节）；低于此大小的文件默认跳过校验，
+    /// 除非任务通过 `verify_checksum_override` 显式要求
+    checksum_verify_min_size_bytes: u64,
+    /// 单流传输时本地读写两侧预读/预写缓冲的深度（块数）
+    window_size: u8,
+    /// 限制同时打开的本地文件句柄数，见 [`FileHandleGuard`]
+    fs_semaphore: &'a Arc<Semaphore>,
+    /// 所属 tokio 运行时句柄，用于在 `std::thread::scope` 内的同步线程中
+    /// 阻塞获取 `fs_semaphore` 许可（这些线程不在异步上下文中，无法直接 `.await`）
+    rt_handle: &'a tokio::runtime::Handle,
+}
+
+/// 本地文件句柄访问守卫：持有期间占用 [`ParallelCtx::fs_semaphore`] 的一个许可，
+/// drop 时自动释放；用于在打开本地文件前限流，避免单个传输任务内部（多流并行、
+/// 预读/预写后台线程）同时打开过多文件句柄，导致总句柄数撞上操作系统的
+/// 文件描述符上限 (EMFILE)，与 [`TransferManager::semaphore`] 控制的传输并发数互相独立
+struct FileHandleGuard {
+    _permit: tokio::sync::OwnedSemaphorePermit,
+}
+
+impl FileHandleGuard {
+    /// 通过 `rt_handle` 阻塞地从 `fs_semaphore` 获取一个许可
+    ///
+    /// 调用处运行在 `spawn_blocking` 线程或其内部派生的 `std::thread::scope` 线程中，
+    /// 均不在异步上下文里，因此借助运行时句柄的 [`tokio::runtime::Handle::block_on`]
+    /// 桥接到异步信号量，而不是直接 `.await`
+    fn acquire(rt_handle: &tokio::runtime::Handle, fs_semaphore: &Arc<Semaphore>) -> AppResult<Self> {
+        let permit = rt_handle
+            .block_on(fs_semaphore.clone().acquire_owned())
+            .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取本地文件句柄许可"))?;
+        Ok(Self { _permit: permit })
+    }
+}
+
+/// 计算第 `retry_count` 次退避重试的延迟 (毫秒)，指数退避 + 随机抖动
+///
+/// 抖动取自新生成 UUID 的随机字节，避免为此引入额外的随机数依赖
+fn compute_backoff_delay_ms(retry_count: u8) -> i64 {
+    let exponent = retry_count.min(10) as u32;
+    let base_secs = RETRY_BACKOFF_BASE_SECS.saturating_mul(1u64 << exponent);
+    let capped_secs = base_secs.min(RETRY_BACKOFF_MAX_SECS);
+
+    let jitter_seed = uuid::Uuid::new_v4().as_bytes()[0] as u64;
+    let jitter_secs = jitter_seed % (capped_secs / 4 + 1);
+
+    ((capped_secs + jitter_secs) * 1000) as i64
+}
+
+/// 对单次 SFTP 操作套上 [`retry_with_backoff`]：把同步闭包丢进 `spawn_blocking`
+/// 提交给会话专属 worker 线程执行，失败时按 [`RetryPolicy`] 默认参数退避重试
+///
+/// 仅适用于目录列举、建目录、删除这类可以整体重新发起的一次性操作——真正的大文件
+/// 传输走 [`TransferManager::execute_task`] 自己的按 `retry_count` 续传重试，续传
+/// 依赖已写入的字节偏移量，不能简单地整体重新调用
+async fn run_sftp_op<T, F>(session: Arc<ManagedSession>, f: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: Fn(&mut Sftp) -> AppResult<T> + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    retry_with_backoff(&RetryPolicy::default(), || {
+        let session = session.clone();
+        let f = f.clone();
+        async move {
+            tokio::task::spawn_blocking(move || session.with_sftp(move |sftp| f(sftp)))
+                .await
+                .unwrap_or_else(|e| {
+                    Err(AppError::new(
+                        ErrorCode::Unknown,
+                        format!("任务执行失败: {}", e),
+                    ))
+                })
+        }
+    })
+    .await
+}
+
 /// 进度追踪器
 struct ProgressTracker<'a> {
     app: &'a AppHandle,
@@ -41,23 +175,46 @@ struct ProgressTracker<'a> {
     start_time: Instant,
     last_emit: Instant,
     transferred: u64,
+    /// 与调用方共享的已传输字节计数，供 `execute_task` 在任务结束（无论成功、失败还是取消）
+    /// 后读取，以便断点续传能记录真实的中断位置，而不仅仅是节流后才推送的进度事件
+    shared: Arc<AtomicU64>,
 }
 
 impl<'a> ProgressTracker<'a> {
-    fn new(app: &'a AppHandle, task_id: &'a str, total: u64) -> Self {
+    /// 创建进度追踪器，并从给定的已传输字节数开始（断点续传场景）
+    fn with_initial(
+        app: &'a AppHandle,
+        task_id: &'a str,
+        total: u64,
+        initial: u64,
+        shared: Arc<AtomicU64>,
+    ) -> Self {
         let now = Instant::now();
+        shared.store(initial, Ordering::Relaxed);
         Self {
             app,
             task_id,
             total,
             start_time: now,
             last_emit: now,
-            transferred: 0,
+            transferred: initial,
+            shared,
         }
     }
 
     fn update(&mut self, bytes: u64) {
         self.transferred += bytes;
+        self.shared.store(self.transferred, Ordering::Relaxed);
+        if self.last_emit.elapsed().as_millis() >= PROGRESS_THROTTLE_MS {
+            self.emit();
+            self.last_emit = Instant::now();
+        }
+    }
+
+    /// 将已传输字节数设置为绝对值而非累加，供多流并行传输轮询汇总的共享计数时使用
+    fn set_absolute(&mut self, transferred: u64) {
+        self.transferred = transferred;
+        self.shared.store(self.transferred, Ordering::Relaxed);
         if self.last_emit.elapsed().as_millis() >= PROGRESS_THROTTLE_MS {
             self.emit();
             self.last_emit = Instant::now();
@@ -106,6 +263,134 @@ fn calculate_percent(transferred: u64, total: u64) -> u8 {
     }
 }
 
+/// 令牌桶限速器：按字节/秒平滑限制吞吐，避免突发占满带宽
+///
+/// 每次消费前按距上次补充的实际耗时补充令牌（桶容量等于 `limit_bytes_per_sec`，
+/// 即最多允许 1 秒的突发），令牌不足时休眠至补足；休眠以较短的固定步长分段进行，
+/// 每段之间检查 `cancel_token`，保证取消操作能及时响应而不必等满整个休眠时长
+struct RateLimiter {
+    limit_bytes_per_sec: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(limit_bytes_per_sec: u64) -> Self {
+        Self {
+            limit_bytes_per_sec,
+            tokens: limit_bytes_per_sec as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// 消费 `bytes` 个令牌，令牌不足时阻塞休眠直至补足（或任务被取消提前返回）
+    fn throttle(&mut self, bytes: u64, cancel_token: &CancellationToken) {
+        let elapsed = self.last_refill.elapsed().as_secs_f64();
+        self.last_refill = Instant::now();
+        self.tokens = (self.tokens + elapsed * self.limit_bytes_per_sec as f64)
+            .min(self.limit_bytes_per_sec as f64);
+        self.tokens -= bytes as f64;
+
+        while self.tokens < 0.0 {
+            if cancel_token.is_cancelled() {
+                return;
+            }
+            let wait_secs = (-self.tokens / self.limit_bytes_per_sec as f64).min(0.1);
+            std::thread::sleep(Duration::from_secs_f64(wait_secs));
+            self.tokens += wait_secs * self.limit_bytes_per_sec as f64;
+        }
+    }
+}
+
+/// 计算任务生效的限速值（字节/秒），返回 `None` 表示不限速
+///
+/// 任务自身的 `speed_limit_bytes_per_sec` 覆盖值优先于管理器的全局默认限速；
+/// 最终解析值为 0 时同样视为不限速
+fn effective_speed_limit(task: &TransferTask, parallel_ctx: &ParallelCtx) -> Option<u64> {
+    let limit = task
+        .speed_limit_bytes_per_sec
+        .unwrap_or(parallel_ctx.default_speed_limit_bytes_per_sec);
+    if limit == 0 {
+        None
+    } else {
+        Some(limit)
+    }
+}
+
+/// 判断本次传输完成后是否需要校验校验和
+///
+/// 任务自身的 `verify_checksum_override` 优先于管理器的全局判断逻辑；未设置时，
+/// 仅当管理器全局开关 `verify_checksum` 打开且文件大小达到 `checksum_verify_min_size_bytes`
+/// 阈值才校验，避免小文件也承担一次额外的远程摘要计算开销
+fn effective_verify_checksum(task: &TransferTask, parallel_ctx: &ParallelCtx, total: u64) -> bool {
+    task.verify_checksum_override.unwrap_or_else(|| {
+        parallel_ctx.verify_checksum && total >= parallel_ctx.checksum_verify_min_size_bytes
+    })
+}
+
+/// 按文件大小计算任务应当持有的信号量许可数量，实现粗粒度的带宽公平性
+///
+/// 每 `chunk_threshold` 字节计一份许可，向上取整后夹紧到 `[1, max_permits]`——夹紧上限
+/// 避免单个超大文件的许可需求超过信号量总容量而永远排不上队。只有上传任务在派发时就
+/// 已知总大小，可以参与按体积加权；下载任务此时大小未知（要等 SFTP stat 之后才知道），
+/// 退化为 cost = 1，与加权前的行为一致
+fn permit_cost(task: &TransferTask, chunk_threshold: u64, max_permits: u8) -> u32 {
+    if task.direction != TransferDirection::Upload || chunk_threshold == 0 {
+        return 1;
+    }
+    let total = match task.total {
+        Some(t) if t > 0 => t,
+        _ => return 1,
+    };
+
+    let cost = (total + chunk_threshold - 1) / chunk_threshold;
+    cost.clamp(1, max_permits as u64) as u32
+}
+
+/// 获取本地文件的修改时间 (Unix 时间戳毫秒)
+fn local_mtime_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as i64)
+}
+
+/// 本地文件的修改时间 (Unix 时间戳秒)，供写入 `ssh2::FileStat` 使用
+fn local_mtime_secs(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// 两侧 mtime 相差在此范围内（秒）视为相同，容忍本地文件系统与 SFTP 服务端的时间精度差异
+const MTIME_TOLERANCE_SECS: i64 = 1;
+
+/// 比较两侧的 (size, mtime_secs) 是否视为"未变化"
+///
+/// 任意一侧缺失都视为有差异（新增或已删除）
+fn files_match(a: Option<(u64, i64)>, b: Option<(u64, i64)>) -> bool {
+    match (a, b) {
+        (Some((size_a, mtime_a)), Some((size_b, mtime_b))) => {
+            size_a == size_b && (mtime_a - mtime_b).abs() <= MTIME_TOLERANCE_SECS
+        }
+        _ => false,
+    }
+}
+
+/// 拼接远程基础目录与相对路径
+fn join_remote_path(base: &str, relative: &str) -> String {
+    if base == "/" {
+        format!("/{}", relative)
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), relative)
+    }
+}
+
 /// 序列化错误码
 fn serialize_error_code(code: &crate::models::error::ErrorCode) -> String {
     serde_json::to_string(code)
@@ -118,7 +403,17 @@ fn serialize_error_code(code: &crate::models::error::ErrorCode) -> String {
 struct InternalTask {
     task: TransferTask,
     cancel_token: CancellationToken,
+    /// 总重试次数：前 `DEFAULT_RETRY_COUNT` 次由 `execute_task` 立即重试；
+    /// 超过后转由退避调度器按 `next_attempt_at` 调度，直至达到 `max_retry_attempts`
     retry_count: u8,
+    /// 下一次退避重试的时间点 (Unix 时间戳毫秒)，仅在任务 Failed 且仍可重试时有值
+    next_attempt_at: Option<i64>,
+    /// 本次执行向信号量申请的许可数量，见 [`permit_cost`]；尚未开始执行时为 0
+    permit_cost: u32,
+    /// 本次执行持有的传输信号量许可；与任务状态存放在一起而非绑定在某个调用栈上，
+    /// 使许可的生命周期跟随任务本身而非派发它的那次调用——`execute_task` 内部以
+    /// `tokio::spawn` 派发后即可与调用方脱钩，许可仍在任务完成（或被取消 drop）时才释放
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
 }
 
 /// 传输管理器
@@ -127,19 +422,118 @@ pub struct TransferManager {
     tasks: RwLock<HashMap<String, InternalTask>>,
     /// 并发控制信号量
     semaphore: Arc<Semaphore>,
+    /// 当前生效的最大并发传输数，见 [`Self::set_max_concurrent`]
+    max_concurrent: std::sync::atomic::AtomicU8,
+    /// 持久化存储，用于在崩溃/重启后恢复未完成的任务
+    db: Arc<Database>,
+    /// 退避调度器允许的最大重试次数（来自设置中的 transfer_retry_count）
+    max_retry_attempts: u8,
+    /// 触发多流并行传输的文件大小阈值（字节），低于此值使用单流传输
+    parallel_threshold_bytes: u64,
+    /// 多流并行传输的流数量
+    parallel_streams: u8,
+    /// 传输完成后是否将源文件的权限和修改时间应用到目标文件
+    preserve_metadata: bool,
+    /// 管理器级别的全局默认限速（字节/秒），0 表示不限速
+    default_speed_limit_bytes_per_sec: u64,
+    /// 传输成功后是否校验远程文件的校验和
+    verify_checksum: bool,
+    /// 远程计算校验和使用的命令
+    checksum_command: String,
+    /// 触发校验和校验的文件大小阈值（字节），见 [`effective_verify_checksum`]
+    checksum_verify_min_size_bytes: u64,
+    /// 单流传输时本地读写两侧预读/预写缓冲的深度（块数）
+    window_size: u8,
+    /// 限制同时打开的本地文件句柄数，见 [`FileHandleGuard`]
+    fs_semaphore: Arc<Semaphore>,
+    /// 是否已进入优雅关闭流程；置位后 [`Self::create_task`] 拒绝创建新任务
+    shutting_down: std::sync::atomic::AtomicBool,
 }
 
 impl TransferManager {
     /// 创建新的传输管理器
     ///
     /// max_concurrent: 最大并发传输数 (1-6)
-    pub fn new(max_concurrent: u8) -> Self {
+    /// max_retry_attempts: 退避调度器允许的最大重试次数
+    /// parallel_threshold_bytes: 触发多流并行传输的文件大小阈值（字节）
+    /// parallel_streams: 多流并行传输的流数量
+    /// preserve_metadata: 传输完成后是否将源文件的权限和修改时间应用到目标文件
+    /// default_speed_limit_bytes_per_sec: 全局默认限速（字节/秒），0 表示不限速
+    /// verify_checksum: 传输成功后是否校验远程文件的校验和
+    /// checksum_command: 远程计算校验和使用的命令（如 `sha256sum`）
+    /// checksum_verify_min_size_bytes: 触发校验和校验的文件大小阈值（字节），
+    ///   见 [`effective_verify_checksum`]
+    /// window_size: 单流传输时本地读写两侧预读/预写缓冲的深度（块数）
+    /// max_open_local_files: 同时打开的本地文件句柄数上限 (1-64)，独立于 `max_concurrent`
+    ///
+    /// 会从数据库加载所有未完成的任务（Running 任务会被降级为 Waiting），
+    /// 但不会自动开始执行——调用方需要在拥有 `AppHandle` 后调用
+    /// [`TransferManager::list_tasks`] 取出 Waiting 任务并派发执行。
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        max_concurrent: u8,
+        max_retry_attempts: u8,
+        db: Arc<Database>,
+        parallel_threshold_bytes: u64,
+        parallel_streams: u8,
+        preserve_metadata: bool,
+        default_speed_limit_bytes_per_sec: u64,
+        verify_checksum: bool,
+        checksum_command: String,
+        checksum_verify_min_size_bytes: u64,
+        window_size: u8,
+        max_open_local_files: u32,
+    ) -> Self {
         let max_concurrent = max_concurrent.clamp(1, 6);
 
         Self {
-            tasks: RwLock::new(HashMap::new()),
+            tasks: RwLock::new(Self::restore(&db)),
             semaphore: Arc::new(Semaphore::new(max_concurrent as usize)),
+            max_concurrent: std::sync::atomic::AtomicU8::new(max_concurrent),
+            db,
+            max_retry_attempts,
+            parallel_threshold_bytes,
+            parallel_streams: parallel_streams.max(1),
+            preserve_metadata,
+            default_speed_limit_bytes_per_sec,
+            verify_checksum,
+            checksum_command,
+            checksum_verify_min_size_bytes,
+            window_size: window_size.max(1),
+            fs_semaphore: Arc::new(Semaphore::new(max_open_local_files.clamp(1, 64) as usize)),
+            shutting_down: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    /// 从数据库恢复未完成的任务队列，供 [`Self::new`] 在启动时调用
+    ///
+    /// 加载所有 Waiting/Running 任务；Running 任务说明上次运行被中断（应用崩溃或被关闭），
+    /// 由 [`Database::transfer_tasks_load_non_terminal`] 统一降级为 Waiting 并写回，
+    /// 以便调用方取出 Waiting 任务重新派发执行。加载失败时记录日志并以空队列启动，
+    /// 不会阻塞应用启动。
+    fn restore(db: &Database) -> HashMap<String, InternalTask> {
+        let mut tasks = HashMap::new();
+        match db.transfer_tasks_load_non_terminal() {
+            Ok(rows) => {
+                for (task, retry_count, next_attempt_at) in rows {
+                    tasks.insert(
+                        task.task_id.clone(),
+                        InternalTask {
+                            task,
+                            cancel_token: CancellationToken::new(),
+                            retry_count,
+                            next_attempt_at,
+                            permit_cost: 0,
+                            permit: None,
+                        },
+                    );
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "加载持久化传输任务失败，将以空队列启动");
+            }
         }
+        tasks
     }
 
     /// 创建上传任务
@@ -178,9 +572,10 @@ impl TransferManager {
             format!("{}/{}", remote_dir.trim_end_matches('/'), file_name)
         };
 
-        // 获取文件大小
+        // 获取文件大小和修改时间（用于断点续传时校验源文件未被修改）
         let metadata = std::fs::metadata(&local_path)?;
         let total = metadata.len();
+        let source_mtime = local_mtime_millis(&metadata);
 
         self.create_task(
             session_id,
@@ -189,10 +584,33 @@ impl TransferManager {
             remote_path,
             file_name,
             Some(total),
+            source_mtime,
+            None,
         )
         .await
     }
 
+    /// 非阻塞创建上传任务
+    ///
+    /// 与 [`Self::create_upload`] 行为一致，唯一区别是在入队前先用
+    /// `semaphore.try_acquire()` 探测一次并发槽位：若当前所有许可已被占用，立即返回
+    /// `ErrorCode::Busy` 而不创建任务、不排队等待。适合交互式/CLI 调用方或网关前端按
+    /// tokio 信号量文档推荐的方式主动拒绝请求（shed load），避免 `tasks` 队列无界堆积
+    /// 等待中的任务。探测用的许可只用于判断"现在是否有空位"，探测后立即释放，
+    /// 真正的许可由 [`Self::execute_task`] 在派发执行时按任务体积重新获取
+    pub async fn try_create_upload(
+        &self,
+        session_id: String,
+        local_path: String,
+        remote_dir: String,
+    ) -> AppResult<String> {
+        self.semaphore
+            .try_acquire()
+            .map_err(|_| AppError::busy("当前并发传输已达上限，请稍后重试"))?;
+
+        self.create_upload(session_id, local_path, remote_dir).await
+    }
+
     /// 创建下载任务
     pub async fn create_download(
         &self,
@@ -232,21 +650,24 @@ impl TransferManager {
             remote_path,
             file_name,
             None, // 下载时不知道大小，执行时获取
+            None, // 下载方向不使用 mtime 校验（改为校验本地部分文件大小）
+            None,
         )
         .await
     }
 
     /// 创建目录下载任务（递归下载所有文件）
     ///
-    /// 返回所有创建的任务 ID
+    /// 所有文件任务挂载在一个批量父任务下，批量任务的 ID 与各文件子任务 ID 一并返回，
+    /// 前端据此跟踪整个目录下载的合计进度与状态（见 [`Self::recompute_batch`]）
     pub async fn create_download_dir(
         &self,
         session_manager: Arc<SessionManager>,
         session_id: String,
         remote_path: String,
         local_base_dir: String,
-    ) -> AppResult<Vec<String>> {
-        use crate::services::sftp_service::SftpService;
+    ) -> AppResult<DirTransferResult> {
+        use crate::services::sftp_service::{SftpService, SymlinkMode};
 
         // 验证本地目录存在
         let base_dir = Path::new(&local_base_dir);
@@ -272,17 +693,28 @@ impl TransferManager {
 
         // 获取会话并递归列出文件
         let session = session_manager.get_session(&session_id)?;
-        let files = tokio::task::spawn_blocking({
-            let session = session.clone();
+        let (files, _symlink_issues) = run_sftp_op(session.clone(), {
             let remote = remote_path.clone();
-            move || SftpService::list_dir_recursive(&session.sftp, &remote)
+            move |sftp| SftpService::list_dir_recursive(sftp, &remote, SymlinkMode::Skip)
         })
-        .await
-        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e)))??;
+        .await?;
+
+        let batch_id = self
+            .create_batch_task(
+                session_id.clone(),
+                TransferDirection::Download,
+                base_dir.join(&dir_name).to_string_lossy().to_string(),
+                remote_path.clone(),
+                dir_name.clone(),
+            )
+            .await?;
 
         if files.is_empty() {
             tracing::info!(remote_path = %remote_path, "目录为空，无文件可下载");
-            return Ok(vec![]);
+            return Ok(DirTransferResult {
+                batch_id,
+                task_ids: vec![],
+            });
         }
 
         // 为每个文件创建下载任务
@@ -315,6 +747,8 @@ impl TransferManager {
                     remote_file_path,
                     file_name,
                     None, // 下载时在执行时获取大小
+                    None, // 下载方向不使用 mtime 校验（改为校验本地部分文件大小）
+                    Some(batch_id.clone()),
                 )
                 .await?;
 
@@ -323,23 +757,25 @@ impl TransferManager {
 
         tracing::info!(
             remote_path = %remote_path,
+            batch_id = %batch_id,
             file_count = task_ids.len(),
             "目录下载任务已创建"
         );
 
-        Ok(task_ids)
+        Ok(DirTransferResult { batch_id, task_ids })
     }
 
     /// 创建目录上传任务（递归上传所有文件）
     ///
-    /// 返回所有创建的任务 ID
+    /// 所有文件任务挂载在一个批量父任务下，批量任务的 ID 与各文件子任务 ID 一并返回，
+    /// 前端据此跟踪整个目录上传的合计进度与状态（见 [`Self::recompute_batch`]）
     pub async fn create_upload_dir(
         &self,
         session_manager: Arc<SessionManager>,
         session_id: String,
         local_path: String,
         remote_base_dir: String,
-    ) -> AppResult<Vec<String>> {
+    ) -> AppResult<DirTransferResult> {
         use crate::services::sftp_service::SftpService;
 
         let local_base = Path::new(&local_path);
@@ -360,14 +796,28 @@ impl TransferManager {
             .to_string();
 
         let files = Self::list_local_dir_recursive(&local_path)?;
-        if files.is_empty() {
-            tracing::info!(local_path = %local_path, "目录为空，无文件可上传");
-            return Ok(vec![]);
-        }
 
         let session = session_manager.get_session(&session_id)?;
         let remote_base = SftpService::normalize_path(&remote_base_dir);
 
+        let batch_id = self
+            .create_batch_task(
+                session_id.clone(),
+                TransferDirection::Upload,
+                local_path.clone(),
+                join_remote_path(&remote_base, &dir_name),
+                dir_name.clone(),
+            )
+            .await?;
+
+        if files.is_empty() {
+            tracing::info!(local_path = %local_path, "目录为空，无文件可上传");
+            return Ok(DirTransferResult {
+                batch_id,
+                task_ids: vec![],
+            });
+        }
+
         // 验证远程基础目录存在
         Self::verify_remote_dir(session.clone(), &remote_base).await?;
 
@@ -377,21 +827,17 @@ impl TransferManager {
 
         // 创建所有远程目录
         let parents: Vec<String> = unique_parents.into_iter().collect();
-        tokio::task::spawn_blocking({
-            let session = session.clone();
-            move || {
-                for parent in parents {
-                    Self::ensure_remote_dir_exists(&session.sftp, &parent)?;
-                }
-                Ok::<(), AppError>(())
+        run_sftp_op(session.clone(), move |sftp| {
+            for parent in &parents {
+                Self::ensure_remote_dir_exists(sftp, parent)?;
             }
+            Ok(())
         })
-        .await
-        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e)))??;
+        .await?;
 
         // 创建上传任务
         let mut task_ids = Vec::with_capacity(file_infos.len());
-        for (local_file_path, remote_file_path, file_name, total) in file_infos {
+        for (local_file_path, remote_file_path, file_name, total, source_mtime) in file_infos {
             let task_id = self
                 .create_task(
                     session_id.clone(),
@@ -400,6 +846,8 @@ impl TransferManager {
                     remote_file_path,
                     file_name,
                     Some(total),
+                    source_mtime,
+                    Some(batch_id.clone()),
                 )
                 .await?;
             task_ids.push(task_id);
@@ -407,108 +855,423 @@ impl TransferManager {
 
         tracing::info!(
             local_path = %local_path,
+            batch_id = %batch_id,
             file_count = task_ids.len(),
             "目录上传任务已创建"
         );
 
-        Ok(task_ids)
+        Ok(DirTransferResult { batch_id, task_ids })
     }
 
-    /// 验证远程目录存在
-    async fn verify_remote_dir(session: Arc<ManagedSession>, path: &str) -> AppResult<()> {
-        let path = path.to_string();
-        tokio::task::spawn_blocking(move || {
-            let path_obj = Path::new(&path);
-            let stat = session.sftp.stat(path_obj).map_err(|e| {
-                if e.code() == ssh2::ErrorCode::SFTP(2) {
-                    AppError::not_found(format!("远程目录不存在: {}", path))
-                } else {
-                    AppError::from(e)
-                }
-            })?;
-            if !stat.is_dir() {
-                return Err(AppError::invalid_argument("远程路径不是目录"));
-            }
-            Ok(())
+    /// 增量下载目录：仅为远程侧新增或已变化的文件创建任务，两侧均未变化的文件跳过
+    ///
+    /// `mirror` 为真时，额外删除本地侧存在但远程侧已不存在的文件
+    ///
+    /// 完成后（不等待已创建任务执行完毕，仅统计本次扫描/入队结果）通过
+    /// `transfer:dirsync` 事件推送跳过/入队/删除的文件数量
+    pub async fn create_download_dir_sync(
+        &self,
+        app: &AppHandle,
+        session_manager: Arc<SessionManager>,
+        session_id: String,
+        remote_path: String,
+        local_base_dir: String,
+        mirror: bool,
+    ) -> AppResult<Vec<String>> {
+        use crate::services::sftp_service::SftpService;
+
+        let base_dir = Path::new(&local_base_dir);
+        if !base_dir.exists() {
+            return Err(AppError::not_found(format!(
+                "本地目录不存在: {}",
+                local_base_dir
+            )));
+        }
+        if !base_dir.is_dir() {
+            return Err(AppError::new(
+                ErrorCode::InvalidArgument,
+                "目标路径不是目录",
+            ));
+        }
+
+        let dir_name = Path::new(&remote_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::new(ErrorCode::InvalidArgument, "无效的远程路径"))?
+            .to_string();
+        let local_target_dir = base_dir.join(&dir_name);
+
+        let session = session_manager.get_session(&session_id)?;
+        let remote_files = run_sftp_op(session.clone(), {
+            let remote = remote_path.clone();
+            move |sftp| SftpService::list_dir_recursive_with_meta(sftp, &remote)
         })
-        .await
-        .map_err(|e| AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e)))?
-    }
+        .await?;
 
-    /// 收集上传文件信息
-    ///
-    /// 返回 (文件信息列表, 唯一父目录集合)
-    #[allow(clippy::type_complexity)]
-    fn collect_upload_file_infos(
-        files: &[(String, String)],
-        remote_base: &str,
-        dir_name: &str,
-    ) -> AppResult<(
-        Vec<(String, String, String, u64)>,
-        std::collections::HashSet<String>,
-    )> {
-        let mut file_infos = Vec::with_capacity(files.len());
-        let mut unique_parents = std::collections::HashSet::new();
+        let local_files = if local_target_dir.exists() {
+            Self::list_local_dir_recursive(&local_target_dir.to_string_lossy())?
+        } else {
+            Vec::new()
+        };
+        let local_map: std::collections::HashMap<String, String> = local_files
+            .into_iter()
+            .map(|(local_path, relative)| (relative, local_path))
+            .collect();
 
-        for (local_file_path, relative_path) in files {
-            let remote_file_path = if remote_base == "/" {
-                format!("/{}/{}", dir_name, relative_path)
-            } else {
-                format!(
-                    "{}/{}/{}",
-                    remote_base.trim_end_matches('/'),
-                    dir_name,
-                    relative_path
-                )
-            };
+        let mut task_ids = Vec::new();
+        let mut skipped = 0u32;
+        let mut deleted = 0u32;
 
-            if let Some(parent) = Path::new(&remote_file_path).parent() {
-                unique_parents.insert(parent.to_string_lossy().to_string());
+        for (relative, size, mtime_secs) in &remote_files {
+            let local_meta = local_map
+                .get(relative)
+                .and_then(|p| std::fs::metadata(p).ok())
+                .and_then(|m| local_mtime_millis(&m).map(|mtime| (m.len(), mtime / 1000)));
+
+            if files_match(Some((*size, *mtime_secs)), local_meta) {
+                skipped += 1;
+                continue;
             }
 
-            let file_name = Path::new(relative_path)
+            let local_path = local_target_dir.join(relative);
+            if let Some(parent) = local_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    AppError::new(ErrorCode::LocalIoError, format!("无法创建本地目录: {}", e))
+                })?;
+            }
+
+            let file_name = Path::new(relative)
                 .file_name()
                 .and_then(|n| n.to_str())
-                .unwrap_or("")
+                .unwrap_or(relative)
                 .to_string();
+            let remote_file_path = join_remote_path(&remote_path, relative);
 
-            let total = std::fs::metadata(local_file_path)
-                .map_err(|e| {
-                    AppError::new(ErrorCode::LocalIoError, format!("无法获取文件信息: {}", e))
-                })?
-                .len();
+            let task_id = self
+                .create_task(
+                    session_id.clone(),
+                    TransferDirection::Download,
+                    local_path.to_string_lossy().to_string(),
+                    remote_file_path,
+                    file_name,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            task_ids.push(task_id);
+        }
 
-            file_infos.push((local_file_path.clone(), remote_file_path, file_name, total));
+        if mirror {
+            let remote_set: std::collections::HashSet<&str> =
+                remote_files.iter().map(|(rel, ..)| rel.as_str()).collect();
+            let extraneous: Vec<(String, String)> = local_map
+                .iter()
+                .filter(|(rel, _)| !remote_set.contains(rel.as_str()))
+                .map(|(rel, path)| (rel.clone(), path.clone()))
+                .collect();
+            for (relative, local_path) in extraneous {
+                if let Err(e) = std::fs::remove_file(&local_path) {
+                    tracing::warn!(path = %relative, error = %e, "镜像清理本地多余文件失败，跳过");
+                } else {
+                    deleted += 1;
+                }
+            }
         }
 
-        Ok((file_infos, unique_parents))
+        tracing::info!(
+            remote_path = %remote_path,
+            skipped, transferred = task_ids.len(), deleted,
+            "目录增量下载已扫描"
+        );
+        app.emit(
+            "transfer:dirsync",
+            &DirSyncResultPayload {
+                session_id,
+                skipped,
+                transferred: task_ids.len() as u32,
+                deleted,
+            },
+        )
+        .ok();
+
+        Ok(task_ids)
     }
 
-    /// 递归列出本地目录下的所有文件
+    /// 增量上传目录：仅为本地侧新增或已变化的文件创建任务，两侧均未变化的文件跳过
     ///
-    /// 返回 (local_path, relative_path) 元组列表，仅包含文件（不含目录）
-    /// 跳过符号链接以避免无限循环
-    fn list_local_dir_recursive(base_path: &str) -> AppResult<Vec<(String, String)>> {
-        let base = Path::new(base_path);
-
-        let metadata = std::fs::metadata(base).map_err(|e| {
-            AppError::new(ErrorCode::LocalIoError, format!("无法访问本地路径: {}", e))
-        })?;
+    /// `mirror` 为真时，额外删除远程侧存在但本地侧已不存在的文件
+    ///
+    /// 完成后（不等待已创建任务执行完毕，仅统计本次扫描/入队结果）通过
+    /// `transfer:dirsync` 事件推送跳过/入队/删除的文件数量
+    pub async fn create_upload_dir_sync(
+        &self,
+        app: &AppHandle,
+        session_manager: Arc<SessionManager>,
+        session_id: String,
+        local_path: String,
+        remote_base_dir: String,
+        mirror: bool,
+    ) -> AppResult<Vec<String>> {
+        use crate::services::sftp_service::SftpService;
 
-        if !metadata.is_dir() {
+        let local_base = Path::new(&local_path);
+        if !local_base.exists() {
+            return Err(AppError::not_found(format!(
+                "本地路径不存在: {}",
+                local_path
+            )));
+        }
+        if !local_base.is_dir() {
             return Err(AppError::invalid_argument("指定的路径是文件而非目录"));
         }
 
-        let mut files = Vec::new();
-        let mut stack = vec![base.to_path_buf()];
+        let dir_name = local_base
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::invalid_argument("无效的本地路径"))?
+            .to_string();
 
-        while let Some(current_path) = stack.pop() {
-            let Ok(entries) = std::fs::read_dir(&current_path) else {
-                tracing::warn!(path = %current_path.display(), "无法读取目录，跳过");
-                continue;
-            };
+        let local_files = Self::list_local_dir_recursive(&local_path)?;
 
-            for entry in entries.flatten() {
+        let session = session_manager.get_session(&session_id)?;
+        let remote_base = SftpService::normalize_path(&remote_base_dir);
+        Self::verify_remote_dir(session.clone(), &remote_base).await?;
+
+        let remote_target_dir = join_remote_path(&remote_base, &dir_name);
+        let remote_files = run_sftp_op(session.clone(), {
+            let remote_target_dir = remote_target_dir.clone();
+            move |sftp| {
+                Self::ensure_remote_dir_exists(sftp, &remote_target_dir)?;
+                SftpService::list_dir_recursive_with_meta(sftp, &remote_target_dir)
+            }
+        })
+        .await?;
+
+        let remote_map: std::collections::HashMap<String, (u64, i64)> = remote_files
+            .into_iter()
+            .map(|(rel, size, mtime)| (rel, (size, mtime)))
+            .collect();
+
+        let mut task_ids = Vec::new();
+        let mut skipped = 0u32;
+        let mut deleted = 0u32;
+
+        for (local_file_path, relative) in &local_files {
+            let metadata = std::fs::metadata(local_file_path).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("无法获取文件信息: {}", e))
+            })?;
+            let total = metadata.len();
+            let source_mtime = local_mtime_millis(&metadata);
+
+            if files_match(
+                remote_map.get(relative).copied(),
+                Some((total, source_mtime.unwrap_or(0) / 1000)),
+            ) {
+                skipped += 1;
+                continue;
+            }
+
+            let remote_file_path = join_remote_path(&remote_target_dir, relative);
+            if let Some(parent) = Path::new(&remote_file_path).parent() {
+                let parent = parent.to_string_lossy().to_string();
+                run_sftp_op(session.clone(), move |sftp| {
+                    Self::ensure_remote_dir_exists(sftp, &parent)
+                })
+                .await?;
+            }
+
+            let file_name = Path::new(relative)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(relative)
+                .to_string();
+
+            let task_id = self
+                .create_task(
+                    session_id.clone(),
+                    TransferDirection::Upload,
+                    local_file_path.clone(),
+                    remote_file_path,
+                    file_name,
+                    Some(total),
+                    source_mtime,
+                    None,
+                )
+                .await?;
+            task_ids.push(task_id);
+        }
+
+        if mirror {
+            let local_set: std::collections::HashSet<&str> =
+                local_files.iter().map(|(_, rel)| rel.as_str()).collect();
+            let extraneous: Vec<String> = remote_map
+                .keys()
+                .filter(|rel| !local_set.contains(rel.as_str()))
+                .cloned()
+                .collect();
+            for relative in extraneous {
+                let remote_file_path = join_remote_path(&remote_target_dir, &relative);
+                match run_sftp_op(session.clone(), move |sftp| {
+                    SftpService::delete(sftp, &remote_file_path, false)
+                })
+                .await
+                {
+                    Ok(()) => deleted += 1,
+                    Err(e) => {
+                        tracing::warn!(path = %relative, error = %e, "镜像清理远程多余文件失败，跳过")
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            local_path = %local_path,
+            skipped, transferred = task_ids.len(), deleted,
+            "目录增量上传已扫描"
+        );
+        app.emit(
+            "transfer:dirsync",
+            &DirSyncResultPayload {
+                session_id,
+                skipped,
+                transferred: task_ids.len() as u32,
+                deleted,
+            },
+        )
+        .ok();
+
+        Ok(task_ids)
+    }
+
+    /// 为单个文件创建传输任务，本地/远程路径由调用方拼接完成
+    ///
+    /// 供 `schedule_service` 在目录同步时为每个差异文件创建任务使用，
+    /// 与 `create_upload`/`create_download` 的区别在于调用方已完成路径拼接
+    /// 与本地父目录准备
+    pub async fn create_sync_file_task(
+        &self,
+        session_id: String,
+        direction: TransferDirection,
+        local_path: String,
+        remote_path: String,
+        file_name: String,
+        total: Option<u64>,
+        source_mtime: Option<i64>,
+    ) -> AppResult<String> {
+        self.create_task(
+            session_id,
+            direction,
+            local_path,
+            remote_path,
+            file_name,
+            total,
+            source_mtime,
+            None,
+        )
+        .await
+    }
+
+    /// 验证远程目录存在
+    async fn verify_remote_dir(session: Arc<ManagedSession>, path: &str) -> AppResult<()> {
+        let path = path.to_string();
+        run_sftp_op(session, move |sftp| {
+            let path_obj = Path::new(&path);
+            let stat = sftp.stat(path_obj).map_err(|e| {
+                if e.code() == ssh2::ErrorCode::SFTP(2) {
+                    AppError::not_found(format!("远程目录不存在: {}", path))
+                } else {
+                    AppError::from(e)
+                }
+            })?;
+            if !stat.is_dir() {
+                return Err(AppError::invalid_argument("远程路径不是目录"));
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// 收集上传文件信息
+    ///
+    /// 返回 (文件信息列表, 唯一父目录集合)
+    #[allow(clippy::type_complexity)]
+    fn collect_upload_file_infos(
+        files: &[(String, String)],
+        remote_base: &str,
+        dir_name: &str,
+    ) -> AppResult<(
+        Vec<(String, String, String, u64, Option<i64>)>,
+        std::collections::HashSet<String>,
+    )> {
+        let mut file_infos = Vec::with_capacity(files.len());
+        let mut unique_parents = std::collections::HashSet::new();
+
+        for (local_file_path, relative_path) in files {
+            let remote_file_path = if remote_base == "/" {
+                format!("/{}/{}", dir_name, relative_path)
+            } else {
+                format!(
+                    "{}/{}/{}",
+                    remote_base.trim_end_matches('/'),
+                    dir_name,
+                    relative_path
+                )
+            };
+
+            if let Some(parent) = Path::new(&remote_file_path).parent() {
+                unique_parents.insert(parent.to_string_lossy().to_string());
+            }
+
+            let file_name = Path::new(relative_path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+
+            let metadata = std::fs::metadata(local_file_path).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("无法获取文件信息: {}", e))
+            })?;
+            let total = metadata.len();
+            let source_mtime = local_mtime_millis(&metadata);
+
+            file_infos.push((
+                local_file_path.clone(),
+                remote_file_path,
+                file_name,
+                total,
+                source_mtime,
+            ));
+        }
+
+        Ok((file_infos, unique_parents))
+    }
+
+    /// 递归列出本地目录下的所有文件
+    ///
+    /// 返回 (local_path, relative_path) 元组列表，仅包含文件（不含目录）
+    /// 跳过符号链接以避免无限循环
+    fn list_local_dir_recursive(base_path: &str) -> AppResult<Vec<(String, String)>> {
+        let base = Path::new(base_path);
+
+        let metadata = std::fs::metadata(base).map_err(|e| {
+            AppError::new(ErrorCode::LocalIoError, format!("无法访问本地路径: {}", e))
+        })?;
+
+        if !metadata.is_dir() {
+            return Err(AppError::invalid_argument("指定的路径是文件而非目录"));
+        }
+
+        let mut files = Vec::new();
+        let mut stack = vec![base.to_path_buf()];
+
+        while let Some(current_path) = stack.pop() {
+            let Ok(entries) = std::fs::read_dir(&current_path) else {
+                tracing::warn!(path = %current_path.display(), "无法读取目录，跳过");
+                continue;
+            };
+
+            for entry in entries.flatten() {
                 let entry_path = entry.path();
                 let Ok(metadata) = entry_path.symlink_metadata() else {
                     tracing::warn!(path = %entry_path.display(), "无法获取文件信息，跳过");
@@ -583,6 +1346,10 @@ impl TransferManager {
     }
 
     /// 创建任务（内部方法）
+    ///
+    /// `parent_task_id` 非空时表示本任务是目录递归传输中某个批量任务的子任务，
+    /// 其状态变化会触发该批量任务重新聚合进度（见 [`Self::maybe_update_batch`]）
+    #[allow(clippy::too_many_arguments)]
     async fn create_task(
         &self,
         session_id: String,
@@ -591,7 +1358,17 @@ impl TransferManager {
         remote_path: String,
         file_name: String,
         total: Option<u64>,
+        source_mtime: Option<i64>,
+        parent_task_id: Option<String>,
     ) -> AppResult<String> {
+        if self.shutting_down.load(Ordering::Relaxed) {
+            return Err(AppError::new(
+                ErrorCode::InvalidArgument,
+                "传输管理器正在关闭，无法创建新任务",
+            )
+            .with_retryable(false));
+        }
+
         let task_id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp_millis();
 
@@ -612,12 +1389,24 @@ impl TransferManager {
             retryable: None,
             created_at: now,
             completed_at: None,
+            resume_offset: None,
+            source_mtime,
+            speed_limit_bytes_per_sec: None,
+            resumable: false,
+            retry_count: 0,
+            next_retry_at: None,
+            parent_task_id,
+            is_batch: false,
+            verify_checksum_override: None,
         };
 
         let internal = InternalTask {
             task,
             cancel_token: CancellationToken::new(),
             retry_count: 0,
+            next_attempt_at: None,
+            permit_cost: 0,
+            permit: None,
         };
 
         {
@@ -625,10 +1414,168 @@ impl TransferManager {
             tasks.insert(task_id.clone(), internal);
         }
 
+        self.persist_task(&task_id).await;
+
         tracing::info!(task_id = %task_id, "传输任务已创建");
         Ok(task_id)
     }
 
+    /// 创建批量任务：目录递归传输的聚合父任务
+    ///
+    /// 批量任务本身不对应具体文件，永远停留在 Waiting 直到第一个子任务开始运行；
+    /// 其 `transferred`/`total`/`status` 完全由子任务驱动聚合，不会被
+    /// [`Self::execute_task`] 派发执行
+    async fn create_batch_task(
+        &self,
+        session_id: String,
+        direction: TransferDirection,
+        local_path: String,
+        remote_path: String,
+        file_name: String,
+    ) -> AppResult<String> {
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let task = TransferTask {
+            task_id: task_id.clone(),
+            session_id,
+            direction,
+            local_path,
+            remote_path,
+            file_name,
+            status: TransferStatus::Waiting,
+            transferred: 0,
+            total: None,
+            speed: None,
+            percent: Some(0),
+            error_message: None,
+            error_code: None,
+            retryable: None,
+            created_at: now,
+            completed_at: None,
+            resume_offset: None,
+            source_mtime: None,
+            speed_limit_bytes_per_sec: None,
+            resumable: false,
+            retry_count: 0,
+            next_retry_at: None,
+            parent_task_id: None,
+            is_batch: true,
+        };
+
+        let internal = InternalTask {
+            task,
+            cancel_token: CancellationToken::new(),
+            retry_count: 0,
+            next_attempt_at: None,
+            permit_cost: 0,
+            permit: None,
+        };
+
+        {
+            let mut tasks = self.tasks.write().await;
+            tasks.insert(task_id.clone(), internal);
+        }
+
+        self.persist_task(&task_id).await;
+
+        tracing::info!(task_id = %task_id, "批量任务已创建");
+        Ok(task_id)
+    }
+
+    /// 根据子任务的最新状态重新计算批量任务的聚合进度与状态，并推送
+    /// `transfer:progress`/`transfer:status` 事件（复用单任务的事件，批量任务只是
+    /// 以自己的 `task_id` 作为事件载荷的 `task_id`）
+    ///
+    /// 聚合规则：任一子任务 Running 视为批量 Running；仍有子任务 Waiting 视为批量
+    /// Waiting；全部终结且全部 Success 视为 Success；全部终结且存在 Failed 视为
+    /// Failed；否则（全部终结，至少一个 Canceled 且无 Failed）视为 Canceled
+    async fn recompute_batch(&self, batch_id: &str, app: &AppHandle) {
+        let children: Vec<TransferTask> = {
+            let tasks = self.tasks.read().await;
+            tasks
+                .values()
+                .map(|internal| &internal.task)
+                .filter(|task| task.parent_task_id.as_deref() == Some(batch_id))
+                .cloned()
+                .collect()
+        };
+
+        if children.is_empty() {
+            return;
+        }
+
+        let transferred: u64 = children.iter().map(|t| t.transferred).sum();
+        let total = children.iter().try_fold(0u64, |acc, t| t.total.map(|v| acc + v));
+
+        let status = if children
+            .iter()
+            .any(|t| t.status == TransferStatus::Running)
+        {
+            TransferStatus::Running
+        } else if children
+            .iter()
+            .any(|t| t.status == TransferStatus::Waiting)
+        {
+            TransferStatus::Waiting
+        } else if children.iter().all(|t| t.status == TransferStatus::Success) {
+            TransferStatus::Success
+        } else if children
+            .iter()
+            .any(|t| t.status == TransferStatus::Failed)
+        {
+            TransferStatus::Failed
+        } else {
+            TransferStatus::Canceled
+        };
+
+        let is_terminal = matches!(
+            status,
+            TransferStatus::Success | TransferStatus::Failed | TransferStatus::Canceled
+        );
+
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(batch_id) {
+                internal.task.status = status.clone();
+                internal.task.transferred = transferred;
+                internal.task.total = total;
+                internal.task.percent = Some(calculate_percent(transferred, total.unwrap_or(0)));
+                if is_terminal {
+                    internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+                }
+            }
+        }
+
+        self.persist_task(batch_id).await;
+        self.emit_status(app, batch_id, status, None, None);
+        app.emit(
+            "transfer:progress",
+            &TransferProgressPayload {
+                task_id: batch_id.to_string(),
+                transferred,
+                total: total.unwrap_or(0),
+                speed: 0,
+                percent: calculate_percent(transferred, total.unwrap_or(0)),
+            },
+        )
+        .ok();
+    }
+
+    /// 若任务属于某个批量任务，则在其状态变化后重新聚合该批量任务
+    async fn maybe_update_batch(&self, app: &AppHandle, task_id: &str) {
+        let parent_task_id = {
+            let tasks = self.tasks.read().await;
+            tasks
+                .get(task_id)
+                .and_then(|internal| internal.task.parent_task_id.clone())
+        };
+
+        if let Some(parent_task_id) = parent_task_id {
+            self.recompute_batch(&parent_task_id, app).await;
+        }
+    }
+
     /// 执行传输任务
     pub async fn execute_task(
         &self,
@@ -657,46 +1604,134 @@ impl TransferManager {
             ));
         }
 
+        // 批量任务本身不对应具体文件，不可被派发执行，其状态由子任务聚合驱动
+        if task_clone.is_batch {
+            return Err(AppError::new(
+                ErrorCode::InvalidArgument,
+                "批量任务不可直接执行",
+            ));
+        }
+
         // 获取会话
         let session = session_manager.get_session(&task_clone.session_id)?;
 
-        // 获取信号量许可
-        let semaphore = self.semaphore.clone();
-        let _permit = semaphore
-            .acquire()
+        // 获取信号量许可：按文件大小加权，获取的是 owned 许可并存放在任务状态上
+        // （而非绑定在本次调用的栈帧里），使许可的生命周期跟随任务本身——
+        // 调用方可以把执行过程 `tokio::spawn` 出去后立即返回，许可仍在任务真正
+        // 结束时才释放（见 [`Self::update_status`]、[`Self::update_error`]）
+        let cost = permit_cost(
+            &task_clone,
+            self.parallel_threshold_bytes,
+            self.max_concurrent.load(Ordering::Relaxed),
+        );
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_many_owned(cost)
             .await
             .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取传输许可"))?;
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(&task_id) {
+                internal.permit_cost = cost;
+                internal.permit = Some(permit);
+            }
+        }
 
         // 更新状态为 Running
         self.update_status(&task_id, TransferStatus::Running).await;
-        self.emit_status(&app, &task_id, TransferStatus::Running, None);
+        self.emit_status(&app, &task_id, TransferStatus::Running, None, None);
+        self.maybe_update_batch(&app, &task_id).await;
+
+        // 共享的已传输字节计数: 无论任务最终成功、失败还是取消，都能读取到中断时的真实进度，
+        // 供断点续传使用（见 compute_resume_offset）
+        let shared_progress = Arc::new(AtomicU64::new(task_clone.transferred));
 
         // 执行传输（在阻塞线程中，传递整个 session）
         let result = tokio::task::spawn_blocking({
             let app = app.clone();
             let task = task_clone.clone();
             let cancel_token = cancel_token.clone();
-            move || match task.direction {
-                TransferDirection::Upload => {
-                    Self::do_upload_sync(&app, &session.sftp, &task, &cancel_token)
-                }
-                TransferDirection::Download => {
-                    Self::do_download_sync(&app, &session.sftp, &task, &cancel_token)
-                }
-            }
-        })
+            let shared_progress = shared_progress.clone();
+            let session_manager = session_manager.clone();
+            let db = self.db.clone();
+            let parallel_threshold_bytes = self.parallel_threshold_bytes;
+            let parallel_streams = self.parallel_streams;
+            let preserve_metadata = self.preserve_metadata;
+            let default_speed_limit_bytes_per_sec = self.default_speed_limit_bytes_per_sec;
+            let verify_checksum = self.verify_checksum;
+            let checksum_command = self.checksum_command.clone();
+            let checksum_verify_min_size_bytes = self.checksum_verify_min_size_bytes;
+            let window_size = self.window_size;
+            let fs_semaphore = self.fs_semaphore.clone();
+            let rt_handle = tokio::runtime::Handle::current();
+            move || {
+                // 整个传输过程作为一个 Job 提交给会话专属的 worker 线程，独占 session/sftp
+                // 直到传输完成，确保重连逻辑在提交 Job::Replace 前会被阻塞，直到本次传输
+                // 因句柄失效而失败退出
+                session.with_session_and_sftp(move |ssh_session, sftp| {
+                    let parallel_ctx = ParallelCtx {
+                        session_manager: &session_manager,
+                        db: &db,
+                        threshold_bytes: parallel_threshold_bytes,
+                        streams: parallel_streams,
+                        preserve_metadata,
+                        default_speed_limit_bytes_per_sec,
+                        verify_checksum,
+                        checksum_command,
+                        checksum_verify_min_size_bytes,
+                        window_size,
+                        fs_semaphore: &fs_semaphore,
+                        rt_handle: &rt_handle,
+                    };
+                    match task.direction {
+                        TransferDirection::Upload => Self::do_upload_sync(
+                            &app,
+                            sftp,
+                            ssh_session,
+                            &task,
+                            &cancel_token,
+                            shared_progress,
+                            &parallel_ctx,
+                        ),
+                        TransferDirection::Download => Self::do_download_sync(
+                            &app,
+                            sftp,
+                            ssh_session,
+                            &task,
+                            &cancel_token,
+                            shared_progress,
+                            &parallel_ctx,
+                        ),
+                    }
+                })
+            }
+            // 注: 各同步函数内部会根据 task.resume_offset 决定是从头开始还是续传
+        })
         .await
         .map_err(|e| AppError::new(ErrorCode::Unknown, format!("任务执行失败: {}", e)))?;
 
+        // 记录中断时的真实已传输字节数，无论传输结果如何（断点续传依赖此值）
+        self.update_transferred(&task_id, shared_progress.load(Ordering::Relaxed))
+            .await;
+
         match result {
-            Ok(()) => {
+            Ok(checksum) => {
                 self.update_status(&task_id, TransferStatus::Success).await;
-                self.emit_status(&app, &task_id, TransferStatus::Success, None);
-                tracing::info!(task_id = %task_id, "传输成功");
+                self.emit_status(
+                    &app,
+                    &task_id,
+                    TransferStatus::Success,
+                    None,
+                    checksum.clone(),
+                );
+                self.maybe_update_batch(&app, &task_id).await;
+                tracing::info!(task_id = %task_id, checksum = checksum.as_deref().unwrap_or(""), "传输成功");
             }
             Err(e) if e.code == ErrorCode::Canceled => {
                 self.update_status(&task_id, TransferStatus::Canceled).await;
-                self.emit_status(&app, &task_id, TransferStatus::Canceled, None);
+                self.emit_status(&app, &task_id, TransferStatus::Canceled, None, None);
+                self.maybe_update_batch(&app, &task_id).await;
                 tracing::info!(task_id = %task_id, "传输已取消");
             }
             Err(e) => {
@@ -715,11 +1750,26 @@ impl TransferManager {
                     {
                         let mut tasks = self.tasks.write().await;
                         if let Some(internal) = tasks.get_mut(&task_id) {
+                            // 复用与 retry_task 相同的一致性校验来决定是否续传：
+                            // 自动重试沿用同一个 task_id，若校验通过则保留已传输字节数，
+                            // 避免下次尝试从零重新发送整个文件。
+                            // 校验和不匹配是个例外：此时文件已完整传输但内容有误，续传只会
+                            // 重新确认同一份坏数据，必须强制从零开始整份重发
+                            let resume_offset = if e.code == ErrorCode::ChecksumMismatch {
+                                None
+                            } else {
+                                Self::compute_resume_offset(&internal.task)
+                            };
                             internal.retry_count += 1;
                             internal.task.status = TransferStatus::Waiting;
-                            internal.task.transferred = 0;
+                            internal.task.transferred = resume_offset.unwrap_or(0);
+                            internal.task.resumable = resume_offset.is_some();
+                            internal.task.resume_offset = resume_offset;
+                            internal.task.retry_count = internal.retry_count;
+                            internal.task.next_retry_at = None;
                         }
                     }
+                    self.persist_task(&task_id).await;
 
                     tokio::time::sleep(delay).await;
 
@@ -729,7 +1779,8 @@ impl TransferManager {
 
                 // 最终失败
                 self.update_error(&task_id, &e).await;
-                self.emit_status(&app, &task_id, TransferStatus::Failed, Some(&e));
+                self.emit_status(&app, &task_id, TransferStatus::Failed, Some(&e), None);
+                self.maybe_update_batch(&app, &task_id).await;
                 tracing::error!(task_id = %task_id, error = %e.message, "传输失败");
             }
         }
@@ -737,73 +1788,488 @@ impl TransferManager {
         Ok(())
     }
 
+    /// 以单引号包裹参数，转义内部的单引号，防止远程路径被解释为 shell 命令
+    fn shell_quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+
+    /// 计算本地文件的 SHA-256 摘要（十六进制），用于并行传输路径下无法复用内联
+    /// hasher 的场景，需要额外读一遍已落盘的文件
+    fn hash_local_file(
+        path: &str,
+        rt_handle: &tokio::runtime::Handle,
+        fs_semaphore: &Arc<Semaphore>,
+    ) -> AppResult<String> {
+        let _fs_permit = FileHandleGuard::acquire(rt_handle, fs_semaphore)?;
+        let mut file = File::open(path).map_err(|e| {
+            AppError::new(ErrorCode::LocalIoError, format!("无法打开本地文件: {}", e))
+                .with_retryable(false)
+        })?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            let bytes_read = file.read(&mut buffer).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("读取本地文件失败: {}", e))
+                    .with_retryable(false)
+            })?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// 回退校验：远程校验和命令不可用时，仅比较本地文件大小与传输前记录的期望大小
+    fn verify_size_fallback(local_path: &str, expected_size: u64) -> AppResult<()> {
+        let actual_size = std::fs::metadata(local_path)
+            .map(|m| m.len())
+            .map_err(|e| {
+                AppError::new(
+                    ErrorCode::LocalIoError,
+                    format!("无法读取本地文件信息: {}", e),
+                )
+                .with_retryable(false)
+            })?;
+        if actual_size != expected_size {
+            return Err(AppError::checksum_mismatch(format!(
+                "校验和校验失败：远程不支持校验命令，按大小比对也不一致（本地 {} 字节，期望 {} 字节）",
+                actual_size, expected_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// 传输完成后校验远程文件与本地文件内容是否一致
+    ///
+    /// `local_hash` 为 `Some` 时复用调用方已算好的摘要（单流传输内联计算），否则现场
+    /// 读取本地文件计算。远程侧通过 SSH exec 通道执行 `parallel_ctx.checksum_command`，
+    /// 取其输出的第一个空白分隔片段作为十六进制摘要；远程命令不存在或执行失败时，
+    /// 回退到仅比较文件大小，不让校验和功能本身成为不支持该命令的远程主机的传输失败点。
+    ///
+    /// 返回本地文件的摘要（便于调用方写入 `TransferStatusPayload`）
+    fn verify_after_transfer(
+        session: &Session,
+        parallel_ctx: &ParallelCtx,
+        local_path: &str,
+        remote_path: &str,
+        expected_size: u64,
+        local_hash: Option<String>,
+    ) -> AppResult<String> {
+        let local_hash = match local_hash {
+            Some(h) => h,
+            None => Self::hash_local_file(local_path, parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?,
+        };
+
+        let mut channel = match session.channel_session() {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!(error = %e, "无法创建校验和通道，回退到按文件大小比对");
+                Self::verify_size_fallback(local_path, expected_size)?;
+                return Ok(local_hash);
+            }
+        };
+
+        let command = format!(
+            "{} {}",
+            parallel_ctx.checksum_command,
+            Self::shell_quote(remote_path)
+        );
+        if let Err(e) = channel.exec(&command) {
+            tracing::warn!(error = %e, "执行远程校验和命令失败，回退到按文件大小比对");
+            Self::verify_size_fallback(local_path, expected_size)?;
+            return Ok(local_hash);
+        }
+
+        let mut output = String::new();
+        let read_ok = channel.read_to_string(&mut output).is_ok();
+        channel.wait_close().ok();
+        let exit_ok = channel.exit_status().unwrap_or(1) == 0;
+
+        let remote_hash = output.split_whitespace().next().map(str::to_string);
+
+        let remote_hash = match remote_hash {
+            Some(h) if read_ok && exit_ok => h,
+            _ => {
+                tracing::warn!("远程校验和命令输出异常，回退到按文件大小比对");
+                Self::verify_size_fallback(local_path, expected_size)?;
+                return Ok(local_hash);
+            }
+        };
+
+        if remote_hash.eq_ignore_ascii_case(&local_hash) {
+            Ok(local_hash)
+        } else {
+            Err(AppError::checksum_mismatch(format!(
+                "校验和不匹配：本地 {}，远程 {}",
+                local_hash, remote_hash
+            )))
+        }
+    }
+
+    /// 校验和不匹配时删除已写入的损坏远程文件，不让半成品文件被误当作可用的上传结果；
+    /// 删除失败只记录警告，不覆盖原始的校验错误
+    fn cleanup_corrupt_remote(sftp: &Sftp, remote_path: &str, err: AppError) -> AppError {
+        if err.code == ErrorCode::ChecksumMismatch {
+            if let Err(e) = sftp.unlink(Path::new(remote_path)) {
+                tracing::warn!(remote_path = %remote_path, error = %e, "删除校验失败的远程文件失败");
+            }
+        }
+        err
+    }
+
+    /// 校验和不匹配时删除已写入的损坏本地文件，不让半成品文件被误当作可用的下载结果；
+    /// 删除失败只记录警告，不覆盖原始的校验错误
+    fn cleanup_corrupt_local(local_path: &str, err: AppError) -> AppError {
+        if err.code == ErrorCode::ChecksumMismatch {
+            if let Err(e) = std::fs::remove_file(local_path) {
+                tracing::warn!(local_path = %local_path, error = %e, "删除校验失败的本地文件失败");
+            }
+        }
+        err
+    }
+
     /// 同步执行上传
+    ///
+    /// 若 `task.resume_offset` 为 `Some`，则从该偏移量续传：本地文件 seek 到该位置，
+    /// 远程文件以不截断的写模式打开并 seek 到相同位置。
+    ///
+    /// 全新上传（`resume_offset` 为 `None`）且文件大小达到 `parallel_ctx` 中配置的阈值时，
+    /// 优先尝试 [`Self::do_upload_parallel`] 多流并行上传；该尝试失败时回退到本函数
+    /// 剩余的单流逻辑，而不是让整个任务失败。
+    ///
+    /// 成功时返回本次传输内容的 SHA-256 摘要（仅当 [`effective_verify_checksum`] 为真）
+    /// 将本地源文件的权限和修改时间应用到刚上传完成的远程文件
+    ///
+    /// 元数据保留是锦上添花，失败时只记录警告，不影响传输任务本身的成功状态
+    fn apply_upload_metadata(sftp: &Sftp, local_path: &str, remote_path: &str) {
+        let metadata = match std::fs::metadata(local_path) {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::warn!(local_path = %local_path, error = %e, "读取本地文件元数据失败，跳过权限/时间保留");
+                return;
+            }
+        };
+
+        #[cfg(unix)]
+        let perm = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(metadata.permissions().mode() & 0o777)
+        };
+        #[cfg(not(unix))]
+        let perm: Option<u32> = None;
+
+        let mtime = local_mtime_secs(&metadata);
+        let file_stat = ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm,
+            atime: mtime,
+            mtime,
+        };
+
+        if let Err(e) = sftp.setstat(Path::new(remote_path), file_stat) {
+            tracing::warn!(remote_path = %remote_path, error = %e, "设置远程文件权限/修改时间失败");
+        }
+    }
+
+    /// 将远程源文件的权限和修改时间应用到刚下载完成的本地文件
+    ///
+    /// 元数据保留是锦上添花，失败时只记录警告，不影响传输任务本身的成功状态
+    fn apply_download_metadata(stat: &ssh2::FileStat, local_path: &str) {
+        if let Some(mtime) = stat.mtime {
+            let file_time = filetime::FileTime::from_unix_time(mtime as i64, 0);
+            if let Err(e) = filetime::set_file_mtime(local_path, file_time) {
+                tracing::warn!(local_path = %local_path, error = %e, "设置本地文件修改时间失败");
+            }
+        }
+
+        #[cfg(unix)]
+        if let Some(perm) = stat.perm {
+            use std::os::unix::fs::PermissionsExt;
+            let permissions = std::fs::Permissions::from_mode(perm & 0o777);
+            if let Err(e) = std::fs::set_permissions(local_path, permissions) {
+                tracing::warn!(local_path = %local_path, error = %e, "设置本地文件权限失败");
+            }
+        }
+    }
+
+    /// 在后台线程中持续从本地文件 `reader` 读取数据块，通过深度为 `window_size` 的
+    /// 有界 channel 转交给调用方，使本地磁盘读取与调用方侧的远程写入在高延迟链路下
+    /// 重叠进行，而不是严格串行的"读一块、等远程写完、再读下一块"
+    ///
+    /// channel 收到空 `Vec` 表示读取已到达 EOF；读取出错时发送 `Err` 并结束读取线程
+    ///
+    /// `fs_permit` 随读取线程一起持有至线程结束，确保 `reader` 句柄打开期间
+    /// 始终计入 [`ParallelCtx::fs_semaphore`] 的限额
+    fn spawn_local_read_ahead(
+        mut reader: File,
+        window_size: u8,
+        cancel_token: CancellationToken,
+        fs_permit: FileHandleGuard,
+    ) -> mpsc::Receiver<AppResult<Vec<u8>>> {
+        let capacity = (window_size.max(1) as usize).saturating_sub(1).max(1);
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        std::thread::spawn(move || {
+            let _fs_permit = fs_permit;
+            loop {
+                if cancel_token.is_cancelled() {
+                    let _ = tx.send(Err(AppError::canceled()));
+                    return;
+                }
+
+                let mut buffer = vec![0u8; CHUNK_SIZE];
+                match reader.read(&mut buffer) {
+                    Ok(0) => {
+                        let _ = tx.send(Ok(Vec::new()));
+                        return;
+                    }
+                    Ok(n) => {
+                        buffer.truncate(n);
+                        if tx.send(Ok(buffer)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(AppError::new(
+                            ErrorCode::LocalIoError,
+                            format!("读取本地文件失败: {}", e),
+                        )
+                        .with_retryable(false)));
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
+    /// 在后台线程中持续消费 channel 中到来的数据块并写入本地文件 `writer`，使远程读取
+    /// 与本地磁盘写入在高延迟链路下重叠进行
+    ///
+    /// 调用方通过 `tx` 按到达顺序推送数据块，发送空 `Vec` 或直接丢弃 `tx` 均表示结束；
+    /// 通过返回的 `JoinHandle` 等待写入线程结束并取回其执行结果（写入出错时为 `Err`）
+    ///
+    /// `fs_permit` 随写入线程一起持有至线程结束，确保 `writer` 句柄打开期间
+    /// 始终计入 [`ParallelCtx::fs_semaphore`] 的限额
+    fn spawn_local_write_ahead(
+        mut writer: File,
+        window_size: u8,
+        fs_permit: FileHandleGuard,
+    ) -> (
+        mpsc::SyncSender<Vec<u8>>,
+        std::thread::JoinHandle<AppResult<()>>,
+    ) {
+        let capacity = (window_size.max(1) as usize).saturating_sub(1).max(1);
+        let (tx, rx) = mpsc::sync_channel::<Vec<u8>>(capacity);
+        let handle = std::thread::spawn(move || -> AppResult<()> {
+            let _fs_permit = fs_permit;
+            while let Ok(buffer) = rx.recv() {
+                if buffer.is_empty() {
+                    break;
+                }
+                writer.write_all(&buffer).map_err(|e| {
+                    AppError::new(ErrorCode::LocalIoError, format!("写入本地文件失败: {}", e))
+                        .with_retryable(false)
+                })?;
+            }
+            Ok(())
+        });
+        (tx, handle)
+    }
+
     fn do_upload_sync(
         app: &AppHandle,
         sftp: &Sftp,
+        session: &Session,
         task: &TransferTask,
         cancel_token: &CancellationToken,
-    ) -> AppResult<()> {
+        shared_progress: Arc<AtomicU64>,
+        parallel_ctx: &ParallelCtx,
+    ) -> AppResult<Option<String>> {
         let task_id = &task.task_id;
         let local_path = &task.local_path;
         let remote_path = &task.remote_path;
         let total = task.total.unwrap_or(0);
+        let resume_offset = task.resume_offset.unwrap_or(0);
+
+        if resume_offset == 0 && total >= parallel_ctx.threshold_bytes && parallel_ctx.streams > 1 {
+            match Self::do_upload_parallel(
+                app,
+                task,
+                total,
+                cancel_token,
+                shared_progress.clone(),
+                parallel_ctx,
+            ) {
+                Ok(()) => {
+                    if parallel_ctx.preserve_metadata {
+                        Self::apply_upload_metadata(sftp, local_path, remote_path);
+                    }
+                    if effective_verify_checksum(task, parallel_ctx, total) {
+                        let hash = Self::verify_after_transfer(
+                            session,
+                            parallel_ctx,
+                            local_path,
+                            remote_path,
+                            total,
+                            None,
+                        )
+                        .map_err(|e| Self::cleanup_corrupt_remote(sftp, remote_path, e))?;
+                        return Ok(Some(hash));
+                    }
+                    return Ok(None);
+                }
+                Err(e) if e.code == ErrorCode::Canceled => return Err(e),
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %e.message, "多流并行上传失败，回退到单流传输");
+                    shared_progress.store(0, Ordering::Relaxed);
+                }
+            }
+        }
 
         // 打开本地文件
+        let fs_permit = FileHandleGuard::acquire(parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?;
         let mut local_file = File::open(local_path).map_err(|e| {
             AppError::new(ErrorCode::LocalIoError, format!("无法打开本地文件: {}", e))
                 .with_retryable(false)
         })?;
 
-        // 创建远程文件
-        let mut remote_file = sftp.create(Path::new(remote_path)).map_err(|e| {
-            let msg = format!("无法创建远程文件: {}", e);
-            if msg.contains("Permission denied") {
-                AppError::permission_denied("无权限写入远程文件")
-            } else {
-                AppError::new(ErrorCode::RemoteIoError, msg).with_retryable(true)
-            }
-        })?;
+        let mut remote_file = if resume_offset > 0 {
+            // 续传: 打开已存在的远程文件（不截断），随后 seek 到断点
+            let mut file = sftp
+                .open_mode(
+                    Path::new(remote_path),
+                    OpenFlags::WRITE,
+                    0o644,
+                    OpenType::File,
+                )
+                .map_err(|e| {
+                    let msg = format!("无法打开远程文件续传: {}", e);
+                    if msg.contains("Permission denied") {
+                        AppError::permission_denied("无权限写入远程文件")
+                    } else {
+                        AppError::new(ErrorCode::RemoteIoError, msg).with_retryable(true)
+                    }
+                })?;
 
-        let mut buffer = vec![0u8; CHUNK_SIZE];
-        let mut progress = ProgressTracker::new(app, task_id, total);
+            file.seek(SeekFrom::Start(resume_offset)).map_err(|e| {
+                AppError::new(ErrorCode::RemoteIoError, format!("定位远程文件失败: {}", e))
+                    .with_retryable(true)
+            })?;
+
+            local_file
+                .seek(SeekFrom::Start(resume_offset))
+                .map_err(|e| {
+                    AppError::new(ErrorCode::LocalIoError, format!("定位本地文件失败: {}", e))
+                        .with_retryable(false)
+                })?;
+
+            file
+        } else {
+            // 全新上传: 创建（截断）远程文件
+            sftp.create(Path::new(remote_path)).map_err(|e| {
+                let msg = format!("无法创建远程文件: {}", e);
+                if msg.contains("Permission denied") {
+                    AppError::permission_denied("无权限写入远程文件")
+                } else {
+                    AppError::new(ErrorCode::RemoteIoError, msg).with_retryable(true)
+                }
+            })?
+        };
+
+        let mut progress =
+            ProgressTracker::with_initial(app, task_id, total, resume_offset, shared_progress);
+        let mut rate_limiter = effective_speed_limit(task, parallel_ctx).map(RateLimiter::new);
+        // 仅当续传起点为 0 时内联计算的摘要才覆盖整个文件，才能用于校验；
+        // 从断点续传的情况下摘要不完整，传输完成后会退化为整份重新计算
+        let mut hasher =
+            (effective_verify_checksum(task, parallel_ctx, total) && resume_offset == 0)
+                .then(Sha256::new);
+
+        // 本地文件读取移交给后台线程提前读取，主线程只负责写远程，
+        // 使本地磁盘读取与远程网络写入的耗时在高延迟链路下重叠
+        let read_ahead = Self::spawn_local_read_ahead(
+            local_file,
+            parallel_ctx.window_size,
+            cancel_token.clone(),
+            fs_permit,
+        );
 
         loop {
             if cancel_token.is_cancelled() {
                 return Err(AppError::canceled());
             }
 
-            let bytes_read = local_file.read(&mut buffer).map_err(|e| {
-                AppError::new(ErrorCode::LocalIoError, format!("读取本地文件失败: {}", e))
-                    .with_retryable(false)
-            })?;
+            let buffer = read_ahead.recv().map_err(|_| {
+                AppError::new(ErrorCode::LocalIoError, "本地读取线程异常退出").with_retryable(false)
+            })??;
 
-            if bytes_read == 0 {
+            if buffer.is_empty() {
                 break;
             }
 
-            remote_file.write_all(&buffer[..bytes_read]).map_err(|e| {
+            remote_file.write_all(&buffer).map_err(|e| {
                 AppError::new(ErrorCode::RemoteIoError, format!("写入远程文件失败: {}", e))
                     .with_retryable(true)
             })?;
 
-            progress.update(bytes_read as u64);
+            if let Some(h) = hasher.as_mut() {
+                h.update(&buffer);
+            }
+            progress.update(buffer.len() as u64);
+            if let Some(limiter) = rate_limiter.as_mut() {
+                limiter.throttle(buffer.len() as u64, cancel_token);
+            }
+        }
+
+        if parallel_ctx.preserve_metadata {
+            Self::apply_upload_metadata(sftp, local_path, remote_path);
         }
 
         progress.finish();
-        Ok(())
+
+        if effective_verify_checksum(task, parallel_ctx, total) {
+            let local_hash = hasher.map(|h| format!("{:x}", h.finalize()));
+            let hash = Self::verify_after_transfer(
+                session,
+                parallel_ctx,
+                local_path,
+                remote_path,
+                total,
+                local_hash,
+            )
+            .map_err(|e| Self::cleanup_corrupt_remote(sftp, remote_path, e))?;
+            return Ok(Some(hash));
+        }
+
+        Ok(None)
     }
 
     /// 同步执行下载
+    ///
+    /// 若 `task.resume_offset` 为 `Some`，则从该偏移量续传：远程读取句柄 seek 到该位置，
+    /// 本地文件以追加写模式打开而不是重新创建。
+    ///
+    /// 全新下载（`resume_offset` 为 `None`）且文件大小达到 `parallel_ctx` 中配置的阈值时，
+    /// 优先尝试 [`Self::do_download_parallel`] 多流并行下载；该尝试失败时回退到本函数
+    /// 剩余的单流逻辑，而不是让整个任务失败。
+    ///
+    /// 成功时返回本次传输内容的 SHA-256 摘要（仅当 [`effective_verify_checksum`] 为真）
     fn do_download_sync(
         app: &AppHandle,
         sftp: &Sftp,
+        session: &Session,
         task: &TransferTask,
         cancel_token: &CancellationToken,
-    ) -> AppResult<()> {
+        shared_progress: Arc<AtomicU64>,
+        parallel_ctx: &ParallelCtx,
+    ) -> AppResult<Option<String>> {
         let task_id = &task.task_id;
         let local_path = &task.local_path;
         let remote_path = &task.remote_path;
+        let resume_offset = task.resume_offset.unwrap_or(0);
 
         // 获取远程文件信息
         let stat = sftp.stat(Path::new(remote_path)).map_err(|e| {
@@ -818,6 +2284,41 @@ impl TransferManager {
 
         let total = stat.size.unwrap_or(0);
 
+        if resume_offset == 0 && total >= parallel_ctx.threshold_bytes && parallel_ctx.streams > 1 {
+            match Self::do_download_parallel(
+                app,
+                task,
+                total,
+                cancel_token,
+                shared_progress.clone(),
+                parallel_ctx,
+            ) {
+                Ok(()) => {
+                    if parallel_ctx.preserve_metadata {
+                        Self::apply_download_metadata(&stat, local_path);
+                    }
+                    if effective_verify_checksum(task, parallel_ctx, total) {
+                        let hash = Self::verify_after_transfer(
+                            session,
+                            parallel_ctx,
+                            local_path,
+                            remote_path,
+                            total,
+                            None,
+                        )
+                        .map_err(|e| Self::cleanup_corrupt_local(local_path, e))?;
+                        return Ok(Some(hash));
+                    }
+                    return Ok(None);
+                }
+                Err(e) if e.code == ErrorCode::Canceled => return Err(e),
+                Err(e) => {
+                    tracing::warn!(task_id = %task_id, error = %e.message, "多流并行下载失败，回退到单流传输");
+                    shared_progress.store(0, Ordering::Relaxed);
+                }
+            }
+        }
+
         // 打开远程文件
         let mut remote_file = sftp.open(Path::new(remote_path)).map_err(|e| {
             let msg = format!("{}", e);
@@ -829,37 +2330,469 @@ impl TransferManager {
             }
         })?;
 
-        // 创建本地文件
-        let mut local_file = File::create(local_path).map_err(|e| {
-            AppError::new(ErrorCode::LocalIoError, format!("无法创建本地文件: {}", e))
-                .with_retryable(false)
-        })?;
+        let fs_permit = FileHandleGuard::acquire(parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?;
+        let mut local_file = if resume_offset > 0 {
+            remote_file
+                .seek(SeekFrom::Start(resume_offset))
+                .map_err(|e| {
+                    AppError::new(ErrorCode::RemoteIoError, format!("定位远程文件失败: {}", e))
+                        .with_retryable(true)
+                })?;
 
-        let mut buffer = vec![0u8; CHUNK_SIZE];
-        let mut progress = ProgressTracker::new(app, task_id, total);
+            // 续传: 打开已存在的部分文件，seek 到断点而非追加（避免依赖平台追加语义）
+            let mut file = OpenOptions::new()
+                .write(true)
+                .open(local_path)
+                .map_err(|e| {
+                    AppError::new(ErrorCode::LocalIoError, format!("无法打开本地文件: {}", e))
+                        .with_retryable(false)
+                })?;
 
+            file.seek(SeekFrom::Start(resume_offset)).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("定位本地文件失败: {}", e))
+                    .with_retryable(false)
+            })?;
+
+            file
+        } else {
+            // 全新下载: 创建（截断）本地文件
+            File::create(local_path).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("无法创建本地文件: {}", e))
+                    .with_retryable(false)
+            })?
+        };
+
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut progress =
+            ProgressTracker::with_initial(app, task_id, total, resume_offset, shared_progress);
+        let mut rate_limiter = effective_speed_limit(task, parallel_ctx).map(RateLimiter::new);
+        // 仅当续传起点为 0 时内联计算的摘要才覆盖整个文件，才能用于校验
+        let mut hasher =
+            (effective_verify_checksum(task, parallel_ctx, total) && resume_offset == 0)
+                .then(Sha256::new);
+
+        // 本地文件写入移交给后台线程，主线程只负责读远程，使远程网络读取与本地磁盘
+        // 写入的耗时在高延迟链路下重叠，而不是等上一块写完才发起下一次远程读取
+        let (write_tx, write_handle) =
+            Self::spawn_local_write_ahead(local_file, parallel_ctx.window_size, fs_permit);
+
+        let mut transfer_error: Option<AppError> = None;
         loop {
             if cancel_token.is_cancelled() {
-                drop(local_file);
-                std::fs::remove_file(local_path).ok();
-                return Err(AppError::canceled());
+                transfer_error = Some(AppError::canceled());
+                break;
             }
 
-            let bytes_read = remote_file.read(&mut buffer).map_err(|e| {
-                AppError::new(ErrorCode::RemoteIoError, format!("读取远程文件失败: {}", e))
-                    .with_retryable(true)
-            })?;
+            let bytes_read = match remote_file.read(&mut buffer) {
+                Ok(n) => n,
+                Err(e) => {
+                    transfer_error = Some(
+                        AppError::new(ErrorCode::RemoteIoError, format!("读取远程文件失败: {}", e))
+                            .with_retryable(true),
+                    );
+                    break;
+                }
+            };
 
             if bytes_read == 0 {
                 break;
             }
 
-            local_file.write_all(&buffer[..bytes_read]).map_err(|e| {
-                AppError::new(ErrorCode::LocalIoError, format!("写入本地文件失败: {}", e))
+            if let Some(h) = hasher.as_mut() {
+                h.update(&buffer[..bytes_read]);
+            }
+            progress.update(bytes_read as u64);
+
+            // 写入线程已经因写入失败提前退出，停止继续读取，由下方 join 取回其错误
+            if write_tx.send(buffer[..bytes_read].to_vec()).is_err() {
+                break;
+            }
+
+            if let Some(limiter) = rate_limiter.as_mut() {
+                limiter.throttle(bytes_read as u64, cancel_token);
+            }
+        }
+
+        // 通知写入线程数据已发送完毕，并等待其完成，确保仍在飞行中的缓冲区已落盘
+        // （或取回其写入失败的错误）后再决定任务的最终结果
+        drop(write_tx);
+        let write_result = write_handle.join().unwrap_or_else(|_| {
+            Err(
+                AppError::new(ErrorCode::LocalIoError, "本地写入线程异常退出")
+                    .with_retryable(false),
+            )
+        });
+
+        if let Some(e) = transfer_error {
+            if e.code == ErrorCode::Canceled && resume_offset == 0 {
+                std::fs::remove_file(local_path).ok();
+            }
+            return Err(e);
+        }
+        write_result?;
+
+        if parallel_ctx.preserve_metadata {
+            Self::apply_download_metadata(&stat, local_path);
+        }
+
+        progress.finish();
+
+        if effective_verify_checksum(task, parallel_ctx, total) {
+            let local_hash = hasher.map(|h| format!("{:x}", h.finalize()));
+            let hash = Self::verify_after_transfer(
+                session,
+                parallel_ctx,
+                local_path,
+                remote_path,
+                total,
+                local_hash,
+            )
+            .map_err(|e| Self::cleanup_corrupt_local(local_path, e))?;
+            return Ok(Some(hash));
+        }
+
+        Ok(None)
+    }
+
+    /// 多流并行上传：将文件切分为多个连续字节区间，每个 worker 使用各自独立的
+    /// SFTP 连接并发传输（`ssh2::Sftp` 绑定在单个 session 上，无法跨线程共享），
+    /// 通过共享的 `shared_progress` 原子计数汇总整体进度
+    ///
+    /// 仅用于全新上传；远程文件先用一条独立连接创建（截断），
+    /// 随后各 worker 以非截断模式打开并各自 seek 到所负责区间的起始位置
+    fn do_upload_parallel(
+        app: &AppHandle,
+        task: &TransferTask,
+        total: u64,
+        cancel_token: &CancellationToken,
+        shared_progress: Arc<AtomicU64>,
+        parallel_ctx: &ParallelCtx,
+    ) -> AppResult<()> {
+        let ranges = split_ranges(total, parallel_ctx.streams);
+        if ranges.len() < 2 {
+            return Err(
+                AppError::new(ErrorCode::InvalidArgument, "文件过小，不适合并行传输")
+                    .with_retryable(false),
+            );
+        }
+
+        tracing::info!(task_id = %task.task_id, streams = ranges.len(), total, "启动多流并行上传");
+
+        {
+            let conn = parallel_ctx
+                .session_manager
+                .create_auxiliary_sftp_session(parallel_ctx.db, &task.session_id)?;
+            conn.sftp
+                .create(Path::new(&task.remote_path))
+                .map_err(|e| {
+                    AppError::new(ErrorCode::RemoteIoError, format!("无法创建远程文件: {}", e))
+                        .with_retryable(true)
+                })?;
+        }
+
+        let mut connections = Vec::with_capacity(ranges.len());
+        for _ in &ranges {
+            connections.push(
+                parallel_ctx
+                    .session_manager
+                    .create_auxiliary_sftp_session(parallel_ctx.db, &task.session_id)?,
+            );
+        }
+
+        let local_path = task.local_path.as_str();
+        let remote_path = task.remote_path.as_str();
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = ranges
+                .iter()
+                .copied()
+                .zip(connections)
+                .map(|((start, end), conn)| {
+                    let cancel_token = cancel_token.clone();
+                    let shared_progress = shared_progress.clone();
+                    scope.spawn(move || -> AppResult<()> {
+                        let sftp = &conn.sftp;
+                        let _fs_permit =
+                            FileHandleGuard::acquire(parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?;
+                        let mut local_file = File::open(local_path).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::LocalIoError,
+                                format!("无法打开本地文件: {}", e),
+                            )
+                            .with_retryable(false)
+                        })?;
+                        local_file.seek(SeekFrom::Start(start)).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::LocalIoError,
+                                format!("定位本地文件失败: {}", e),
+                            )
+                            .with_retryable(false)
+                        })?;
+
+                        let mut remote_file = sftp
+                            .open_mode(
+                                Path::new(remote_path),
+                                OpenFlags::WRITE,
+                                0o644,
+                                OpenType::File,
+                            )
+                            .map_err(|e| {
+                                AppError::new(
+                                    ErrorCode::RemoteIoError,
+                                    format!("无法打开远程文件: {}", e),
+                                )
+                                .with_retryable(true)
+                            })?;
+                        remote_file.seek(SeekFrom::Start(start)).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::RemoteIoError,
+                                format!("定位远程文件失败: {}", e),
+                            )
+                            .with_retryable(true)
+                        })?;
+
+                        let mut buffer = vec![0u8; CHUNK_SIZE];
+                        let mut remaining = end - start;
+                        while remaining > 0 {
+                            if cancel_token.is_cancelled() {
+                                return Err(AppError::canceled());
+                            }
+
+                            let to_read = remaining.min(CHUNK_SIZE as u64) as usize;
+                            let bytes_read =
+                                local_file.read(&mut buffer[..to_read]).map_err(|e| {
+                                    AppError::new(
+                                        ErrorCode::LocalIoError,
+                                        format!("读取本地文件失败: {}", e),
+                                    )
+                                    .with_retryable(false)
+                                })?;
+                            if bytes_read == 0 {
+                                break;
+                            }
+
+                            remote_file.write_all(&buffer[..bytes_read]).map_err(|e| {
+                                AppError::new(
+                                    ErrorCode::RemoteIoError,
+                                    format!("写入远程文件失败: {}", e),
+                                )
+                                .with_retryable(true)
+                            })?;
+
+                            remaining -= bytes_read as u64;
+                            shared_progress.fetch_add(bytes_read as u64, Ordering::Relaxed);
+                        }
+
+                        Ok(())
+                    })
+                })
+                .collect();
+
+            Self::join_parallel_workers(
+                app,
+                &task.task_id,
+                total,
+                cancel_token,
+                shared_progress,
+                handles,
+            )
+        })
+    }
+
+    /// 多流并行下载：将远程文件切分为多个连续字节区间，每个 worker 使用各自独立的
+    /// SFTP 连接并发读取，写入本地文件中各自负责的区间（本地文件提前 `set_len`
+    /// 预分配，各 worker 以独立的文件句柄 seek 后写入，互不冲突）
+    ///
+    /// 仅用于全新下载
+    fn do_download_parallel(
+        app: &AppHandle,
+        task: &TransferTask,
+        total: u64,
+        cancel_token: &CancellationToken,
+        shared_progress: Arc<AtomicU64>,
+        parallel_ctx: &ParallelCtx,
+    ) -> AppResult<()> {
+        let ranges = split_ranges(total, parallel_ctx.streams);
+        if ranges.len() < 2 {
+            return Err(
+                AppError::new(ErrorCode::InvalidArgument, "文件过小，不适合并行传输")
+                    .with_retryable(false),
+            );
+        }
+
+        tracing::info!(task_id = %task.task_id, streams = ranges.len(), total, "启动多流并行下载");
+
+        {
+            let _fs_permit =
+                FileHandleGuard::acquire(parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?;
+            let local_file = File::create(&task.local_path).map_err(|e| {
+                AppError::new(ErrorCode::LocalIoError, format!("无法创建本地文件: {}", e))
                     .with_retryable(false)
             })?;
+            local_file.set_len(total).map_err(|e| {
+                AppError::new(
+                    ErrorCode::LocalIoError,
+                    format!("无法预分配本地文件: {}", e),
+                )
+                .with_retryable(false)
+            })?;
+        }
 
-            progress.update(bytes_read as u64);
+        let mut connections = Vec::with_capacity(ranges.len());
+        for _ in &ranges {
+            connections.push(
+                parallel_ctx
+                    .session_manager
+                    .create_auxiliary_sftp_session(parallel_ctx.db, &task.session_id)?,
+            );
+        }
+
+        let local_path = task.local_path.as_str();
+        let remote_path = task.remote_path.as_str();
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = ranges
+                .iter()
+                .copied()
+                .zip(connections)
+                .map(|((start, end), conn)| {
+                    let cancel_token = cancel_token.clone();
+                    let shared_progress = shared_progress.clone();
+                    scope.spawn(move || -> AppResult<()> {
+                        let sftp = &conn.sftp;
+                        let mut remote_file = sftp.open(Path::new(remote_path)).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::RemoteIoError,
+                                format!("无法打开远程文件: {}", e),
+                            )
+                            .with_retryable(true)
+                        })?;
+                        remote_file.seek(SeekFrom::Start(start)).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::RemoteIoError,
+                                format!("定位远程文件失败: {}", e),
+                            )
+                            .with_retryable(true)
+                        })?;
+
+                        let _fs_permit =
+                            FileHandleGuard::acquire(parallel_ctx.rt_handle, parallel_ctx.fs_semaphore)?;
+                        let mut local_file = OpenOptions::new()
+                            .write(true)
+                            .open(local_path)
+                            .map_err(|e| {
+                                AppError::new(
+                                    ErrorCode::LocalIoError,
+                                    format!("无法打开本地文件: {}", e),
+                                )
+                                .with_retryable(false)
+                            })?;
+                        local_file.seek(SeekFrom::Start(start)).map_err(|e| {
+                            AppError::new(
+                                ErrorCode::LocalIoError,
+                                format!("定位本地文件失败: {}", e),
+                            )
+                            .with_retryable(false)
+                        })?;
+
+                        let mut buffer = vec![0u8; CHUNK_SIZE];
+                        let mut remaining = end - start;
+                        while remaining > 0 {
+                            if cancel_token.is_cancelled() {
+                                return Err(AppError::canceled());
+                            }
+
+                            let to_read = remaining.min(CHUNK_SIZE as u64) as usize;
+                            let bytes_read =
+                                remote_file.read(&mut buffer[..to_read]).map_err(|e| {
+                                    AppError::new(
+                                        ErrorCode::RemoteIoError,
+                                        format!("读取远程文件失败: {}", e),
+                                    )
+                                    .with_retryable(true)
+                                })?;
+                            if bytes_read == 0 {
+                                break;
+                            }
+
+                            local_file.write_all(&buffer[..bytes_read]).map_err(|e| {
+                                AppError::new(
+                                    ErrorCode::LocalIoError,
+                                    format!("写入本地文件失败: {}", e),
+                                )
+                                .with_retryable(false)
+                            })?;
+
+                            remaining -= bytes_read as u64;
+                            shared_progress.fetch_add(bytes_read as u64, Ordering::Relaxed);
+                        }
+
+                        Ok(())
+                    })
+                })
+                .collect();
+
+            let result = Self::join_parallel_workers(
+                app,
+                &task.task_id,
+                total,
+                cancel_token,
+                shared_progress,
+                handles,
+            );
+            if result.is_err() {
+                std::fs::remove_file(local_path).ok();
+            }
+            result
+        })
+    }
+
+    /// 等待多流并行传输的所有 worker 线程结束，期间轮询共享进度计数并节流推送
+    /// `transfer:progress` 事件；任一 worker 失败或任务被取消时，仍会等待全部线程
+    /// 退出后再返回，避免遗留孤立线程
+    fn join_parallel_workers<'scope>(
+        app: &AppHandle,
+        task_id: &str,
+        total: u64,
+        cancel_token: &CancellationToken,
+        shared_progress: Arc<AtomicU64>,
+        handles: Vec<std::thread::ScopedJoinHandle<'scope, AppResult<()>>>,
+    ) -> AppResult<()> {
+        let mut progress =
+            ProgressTracker::with_initial(app, task_id, total, 0, shared_progress.clone());
+
+        loop {
+            if handles.iter().all(|h| h.is_finished()) {
+                break;
+            }
+            progress.set_absolute(shared_progress.load(Ordering::Relaxed));
+            if cancel_token.is_cancelled() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(PARALLEL_POLL_INTERVAL_MS));
+        }
+
+        let mut first_err = None;
+        for handle in handles {
+            match handle.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    first_err.get_or_insert(e);
+                }
+                Err(_) => {
+                    first_err.get_or_insert(AppError::new(
+                        ErrorCode::Unknown,
+                        "并行传输 worker 线程异常退出",
+                    ));
+                }
+            }
+        }
+
+        if let Some(e) = first_err {
+            return Err(e);
+        }
+        if cancel_token.is_cancelled() {
+            return Err(AppError::canceled());
         }
 
         progress.finish();
@@ -867,7 +2800,22 @@ impl TransferManager {
     }
 
     /// 取消任务
+    ///
+    /// 若目标是批量任务（目录递归传输的聚合父任务），取消信号会级联发送给其下仍在
+    /// Waiting/Running 的所有子任务，批量任务自身的状态随子任务终结后自动聚合更新
     pub async fn cancel_task(&self, task_id: &str) -> AppResult<()> {
+        let is_batch = {
+            let tasks = self.tasks.read().await;
+            let internal = tasks
+                .get(task_id)
+                .ok_or_else(|| AppError::not_found(format!("任务不存在: {}", task_id)))?;
+            internal.task.is_batch
+        };
+
+        if is_batch {
+            return self.cancel_batch_children(task_id).await;
+        }
+
         let tasks = self.tasks.read().await;
         let internal = tasks
             .get(task_id)
@@ -884,7 +2832,159 @@ impl TransferManager {
         }
     }
 
+    /// 将取消信号级联发送给批量任务下所有仍在 Waiting/Running 的子任务
+    async fn cancel_batch_children(&self, batch_id: &str) -> AppResult<()> {
+        let tasks = self.tasks.read().await;
+        for internal in tasks.values().filter(|internal| {
+            internal.task.parent_task_id.as_deref() == Some(batch_id)
+                && matches!(
+                    internal.task.status,
+                    TransferStatus::Waiting | TransferStatus::Running
+                )
+        }) {
+            internal.cancel_token.cancel();
+        }
+        tracing::info!(batch_id = %batch_id, "批量任务取消信号已级联发送给子任务");
+        Ok(())
+    }
+
+    /// 运行时调整最大并发传输数 (1-6)，返回调整前的值
+    ///
+    /// 调高时直接给信号量补发差额许可；调低时不会打断正在进行的传输——而是在
+    /// 后台任务中异步获取差额个许可并永久吞掉（[`tokio::sync::OwnedSemaphorePermit::forget`]），
+    /// 待正在运行的传输陆续释放许可后，总可用数才会真正降到新上限，此后才不再
+    /// 派发新任务。调用方可据此在设备负载升高时临时限流，而无需重建管理器、
+    /// 丢失已有任务队列
+    pub fn set_max_concurrent(&self, new_limit: u8) -> u8 {
+        let new_limit = new_limit.clamp(1, 6);
+        let previous = self.max_concurrent.swap(new_limit, Ordering::SeqCst);
+
+        match new_limit.cmp(&previous) {
+            std::cmp::Ordering::Greater => {
+                self.semaphore.add_permits((new_limit - previous) as usize);
+            }
+            std::cmp::Ordering::Less => {
+                let delta = (previous - new_limit) as u32;
+                let semaphore = self.semaphore.clone();
+                tokio::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(delta).await {
+                        permits.forget();
+                    }
+                });
+            }
+            std::cmp::Ordering::Equal => {}
+        }
+
+        previous
+    }
+
+    /// 优雅关闭：停止接受新任务，向所有 Running 任务发送取消信号，并等待它们进入
+    /// 终态（Success/Failed/Canceled）后再返回；若等待超过 `timeout` 仍有任务未
+    /// 结束，则放弃等待直接返回（任务会继续在后台运行至自然结束）
+    ///
+    /// 调用后本管理器不会拒绝已创建任务的后续操作（取消、重试等），只是不再允许
+    /// [`Self::create_task`] 创建新任务；这是一次性操作，不支持复位
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+
+        let running_task_ids: Vec<String> = {
+            let tasks = self.tasks.read().await;
+            tasks
+                .values()
+                .filter(|internal| internal.task.status == TransferStatus::Running)
+                .map(|internal| internal.task.task_id.clone())
+                .collect()
+        };
+
+        if running_task_ids.is_empty() {
+            tracing::info!("优雅关闭：无在途任务，直接完成");
+            return;
+        }
+
+        tracing::info!(
+            count = running_task_ids.len(),
+            "优雅关闭：取消所有在途传输任务"
+        );
+        {
+            let tasks = self.tasks.read().await;
+            for task_id in &running_task_ids {
+                if let Some(internal) = tasks.get(task_id) {
+                    internal.cancel_token.cancel();
+                }
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let still_running = {
+                let tasks = self.tasks.read().await;
+                running_task_ids.iter().any(|task_id| {
+                    tasks
+                        .get(task_id)
+                        .map(|internal| internal.task.status == TransferStatus::Running)
+                        .unwrap_or(false)
+                })
+            };
+
+            if !still_running {
+                tracing::info!("优雅关闭：所有在途任务已进入终态");
+                return;
+            }
+
+            if Instant::now() >= deadline {
+                tracing::warn!("优雅关闭：等待超时，仍有任务在途，放弃等待");
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// 设置单个任务的限速覆盖值（字节/秒）
+    ///
+    /// `None` 表示清除覆盖、改用管理器的全局默认限速；`Some(0)` 表示该任务显式不限速。
+    /// `execute_task` 在派发时才会读取该字段构建限速器，因此若任务正在运行，新值仅在
+    /// 任务下一次被执行（如重试或等待队列中尚未开始）时生效，不会影响正在进行中的传输。
+    pub async fn set_task_speed_limit(&self, task_id: &str, limit: Option<u64>) -> AppResult<()> {
+        {
+            let mut tasks = self.tasks.write().await;
+            let internal = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| AppError::not_found(format!("任务不存在: {}", task_id)))?;
+            internal.task.speed_limit_bytes_per_sec = limit;
+        }
+
+        self.persist_task(task_id).await;
+        Ok(())
+    }
+
+    /// 设置单个任务的校验和校验覆盖值
+    ///
+    /// `None` 表示清除覆盖、改由管理器的全局开关与大小阈值自动判断（见
+    /// [`effective_verify_checksum`]）；`Some(true)`/`Some(false)` 表示该任务无条件
+    /// 启用/禁用校验。与限速覆盖一样，仅在任务下一次被执行时生效。
+    pub async fn set_task_verify_checksum(
+        &self,
+        task_id: &str,
+        verify: Option<bool>,
+    ) -> AppResult<()> {
+        {
+            let mut tasks = self.tasks.write().await;
+            let internal = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| AppError::not_found(format!("任务不存在: {}", task_id)))?;
+            internal.task.verify_checksum_override = verify;
+        }
+
+        self.persist_task(task_id).await;
+        Ok(())
+    }
+
     /// 重试失败的任务
+    ///
+    /// 在重新入队前会尝试断点续传：对上传校验本地源文件的大小和 mtime 是否与创建任务时
+    /// 一致，对下载校验本地部分文件的大小是否与已传输字节数一致。一致则从断点续传，
+    /// 否则退化为全新任务（从零开始）。批量任务本身不支持直接重试，请改用 [`Self::retry_batch`]。
     pub async fn retry_task(&self, task_id: &str) -> AppResult<String> {
         let task = {
             let tasks = self.tasks.read().await;
@@ -892,26 +2992,171 @@ impl TransferManager {
                 .get(task_id)
                 .ok_or_else(|| AppError::not_found(format!("任务不存在: {}", task_id)))?;
 
+            if internal.task.is_batch {
+                return Err(AppError::new(
+                    ErrorCode::InvalidArgument,
+                    "批量任务请使用 retry_batch 重试其中失败的子任务",
+                ));
+            }
             if internal.task.status != TransferStatus::Failed {
                 return Err(AppError::new(
                     ErrorCode::InvalidArgument,
                     "只能重试失败的任务",
                 ));
             }
-
-            internal.task.clone()
-        };
-
-        // 创建新任务
-        self.create_task(
-            task.session_id,
-            task.direction,
-            task.local_path,
-            task.remote_path,
-            task.file_name,
-            task.total,
-        )
-        .await
+
+            internal.task.clone()
+        };
+
+        self.requeue_retry(task_id, task).await
+    }
+
+    /// 重试批量任务下所有处于 Failed 状态的子任务，返回新创建的子任务 ID 列表
+    ///
+    /// 只重新入队失败的子任务；已成功/已取消/仍在等待或运行中的子任务保持不变
+    pub async fn retry_batch(&self, batch_id: &str) -> AppResult<Vec<String>> {
+        let failed_children: Vec<TransferTask> = {
+            let tasks = self.tasks.read().await;
+            let batch = tasks
+                .get(batch_id)
+                .ok_or_else(|| AppError::not_found(format!("任务不存在: {}", batch_id)))?;
+            if !batch.task.is_batch {
+                return Err(AppError::new(ErrorCode::InvalidArgument, "目标任务不是批量任务"));
+            }
+
+            tasks
+                .values()
+                .map(|internal| &internal.task)
+                .filter(|task| {
+                    task.parent_task_id.as_deref() == Some(batch_id)
+                        && task.status == TransferStatus::Failed
+                })
+                .cloned()
+                .collect()
+        };
+
+        let mut new_task_ids = Vec::with_capacity(failed_children.len());
+        for task in failed_children {
+            let original_task_id = task.task_id.clone();
+            // 重试前先把旧的 Failed 子任务从批量任务上摘除，否则聚合进度会把它和
+            // 新建的替代任务重复计入同一批量（旧任务仍作为历史记录保留，可被
+            // cleanup_completed 正常清理）
+            self.detach_from_batch(&original_task_id).await;
+            new_task_ids.push(self.requeue_retry(&original_task_id, task).await?);
+        }
+
+        tracing::info!(batch_id = %batch_id, retried = new_task_ids.len(), "批量任务已重新入队失败的子任务");
+
+        Ok(new_task_ids)
+    }
+
+    /// 将任务从其所属的批量任务上摘除（清空 `parent_task_id`），供批量重试时避免
+    /// 旧的 Failed 子任务与新建的替代任务被重复计入同一批量的聚合进度
+    async fn detach_from_batch(&self, task_id: &str) {
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(task_id) {
+                internal.task.parent_task_id = None;
+            }
+        }
+        self.persist_task(task_id).await;
+    }
+
+    /// 将一个失败的任务（单文件任务或批量任务的子任务）重新入队为一个全新的 Waiting 任务，
+    /// 尝试断点续传；`original_task_id` 仅用于日志，新任务保留原任务的 `parent_task_id`，
+    /// 因此属于某个批量任务的子任务重试后仍归属同一批量任务
+    async fn requeue_retry(&self, original_task_id: &str, task: TransferTask) -> AppResult<String> {
+        // 校验和不匹配导致的失败必须整份重发，见 execute_task 中自动重试分支的同一处理
+        let resume_offset = if task.error_code.as_deref()
+            == Some(&serialize_error_code(&ErrorCode::ChecksumMismatch))
+        {
+            None
+        } else {
+            Self::compute_resume_offset(&task)
+        };
+
+        let new_task_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let new_task = TransferTask {
+            task_id: new_task_id.clone(),
+            session_id: task.session_id,
+            direction: task.direction,
+            local_path: task.local_path,
+            remote_path: task.remote_path,
+            file_name: task.file_name,
+            status: TransferStatus::Waiting,
+            transferred: resume_offset.unwrap_or(0),
+            total: task.total,
+            speed: None,
+            percent: Some(0),
+            error_message: None,
+            error_code: None,
+            retryable: None,
+            created_at: now,
+            completed_at: None,
+            resumable: resume_offset.is_some(),
+            resume_offset,
+            source_mtime: task.source_mtime,
+            speed_limit_bytes_per_sec: task.speed_limit_bytes_per_sec,
+            retry_count: 0,
+            next_retry_at: None,
+            parent_task_id: task.parent_task_id,
+            is_batch: false,
+            verify_checksum_override: None,
+        };
+
+        if resume_offset.is_some() {
+            tracing::info!(task_id = %original_task_id, new_task_id = %new_task_id, offset = resume_offset.unwrap(), "断点续传: 从偏移量恢复");
+        } else {
+            tracing::info!(task_id = %original_task_id, new_task_id = %new_task_id, "断点续传校验未通过，全新重试");
+        }
+
+        let internal = InternalTask {
+            task: new_task,
+            cancel_token: CancellationToken::new(),
+            retry_count: 0,
+            next_attempt_at: None,
+            permit_cost: 0,
+            permit: None,
+        };
+
+        {
+            let mut tasks = self.tasks.write().await;
+            tasks.insert(new_task_id.clone(), internal);
+        }
+
+        self.persist_task(&new_task_id).await;
+
+        Ok(new_task_id)
+    }
+
+    /// 计算断点续传的起始偏移量
+    ///
+    /// 返回 `None` 表示一致性校验未通过，应当从零开始全新传输。
+    fn compute_resume_offset(task: &TransferTask) -> Option<u64> {
+        match task.direction {
+            TransferDirection::Upload => {
+                let metadata = std::fs::metadata(&task.local_path).ok()?;
+                if Some(metadata.len()) != task.total {
+                    return None; // 源文件大小已变化，放弃续传
+                }
+                if local_mtime_millis(&metadata) != task.source_mtime {
+                    return None; // 源文件已被修改，放弃续传
+                }
+                if task.transferred == 0 {
+                    return None; // 没有已传输的字节，无需续传
+                }
+                Some(task.transferred)
+            }
+            TransferDirection::Download => {
+                let metadata = std::fs::metadata(&task.local_path).ok()?;
+                if metadata.len() != task.transferred || task.transferred == 0 {
+                    return None; // 本地部分文件与记录的进度不一致，放弃续传
+                }
+                Some(task.transferred)
+            }
+        }
     }
 
     /// 获取任务列表
@@ -927,14 +3172,31 @@ impl TransferManager {
     }
 
     /// 清理已完成的任务
-    pub async fn cleanup_completed(&self) {
-        let mut tasks = self.tasks.write().await;
-        tasks.retain(|_, internal| {
-            !matches!(
-                internal.task.status,
-                TransferStatus::Success | TransferStatus::Canceled
-            )
-        });
+    ///
+    /// 从内存中移除完成时间早于 `max_age_ms`（毫秒）之前的 Success/Canceled 任务，
+    /// 同时清理数据库中所有早于该时间的已终结任务行（含 Failed，即便内存中尚未移除）。
+    /// `max_age_ms` 为 `None` 时使用 [`DEFAULT_RETENTION_MAX_AGE_MS`]。
+    pub async fn cleanup_completed(&self, max_age_ms: Option<i64>) -> AppResult<()> {
+        let max_age_ms = max_age_ms.unwrap_or(DEFAULT_RETENTION_MAX_AGE_MS);
+        let cutoff = chrono::Utc::now().timestamp_millis() - max_age_ms;
+
+        {
+            let mut tasks = self.tasks.write().await;
+            tasks.retain(|_, internal| {
+                let is_done = matches!(
+                    internal.task.status,
+                    TransferStatus::Success | TransferStatus::Canceled
+                );
+                !is_done || internal.task.completed_at.unwrap_or(i64::MAX) >= cutoff
+            });
+        }
+
+        let deleted = self.db.transfer_tasks_delete_older_than(cutoff)?;
+        if deleted > 0 {
+            tracing::info!(deleted, "已清理过期的传输任务记录");
+        }
+
+        Ok(())
     }
 
     // ============================================
@@ -943,28 +3205,204 @@ impl TransferManager {
 
     /// 更新任务状态
     async fn update_status(&self, task_id: &str, status: TransferStatus) {
-        let mut tasks = self.tasks.write().await;
-        if let Some(internal) = tasks.get_mut(task_id) {
-            internal.task.status = status.clone();
-            if matches!(
-                status,
-                TransferStatus::Success | TransferStatus::Failed | TransferStatus::Canceled
-            ) {
-                internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(task_id) {
+                internal.task.status = status.clone();
+                if matches!(
+                    status,
+                    TransferStatus::Success | TransferStatus::Failed | TransferStatus::Canceled
+                ) {
+                    internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+                    // 任务进入终态：释放本次执行持有的传输许可，不必等到函数返回或
+                    // InternalTask 本身被清理（见 cleanup_completed）
+                    internal.permit = None;
+                }
+            }
+        }
+        self.persist_task(task_id).await;
+    }
+
+    /// 更新任务已传输字节数（不改变状态），用于断点续传记录中断位置
+    async fn update_transferred(&self, task_id: &str, transferred: u64) {
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(task_id) {
+                internal.task.transferred = transferred;
             }
         }
+        self.persist_task(task_id).await;
     }
 
-    /// 更新任务错误信息
+    /// 更新任务错误信息，并在可重试且未超过最大重试次数时安排下一次退避重试
     async fn update_error(&self, task_id: &str, error: &AppError) {
-        let mut tasks = self.tasks.write().await;
-        if let Some(internal) = tasks.get_mut(task_id) {
-            internal.task.status = TransferStatus::Failed;
-            internal.task.error_message = Some(error.message.clone());
-            internal.task.error_code = Some(serialize_error_code(&error.code));
-            internal.task.retryable = error.retryable;
-            internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(internal) = tasks.get_mut(task_id) {
+                internal.task.status = TransferStatus::Failed;
+                internal.task.error_message = Some(error.message.clone());
+                internal.task.error_code = Some(serialize_error_code(&error.code));
+                internal.task.retryable = error.retryable;
+                internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+
+                internal.next_attempt_at = if error.retryable.unwrap_or(false)
+                    && internal.retry_count < self.max_retry_attempts
+                {
+                    let delay_ms = compute_backoff_delay_ms(internal.retry_count);
+                    Some(chrono::Utc::now().timestamp_millis() + delay_ms)
+                } else {
+                    None
+                };
+                internal.task.retry_count = internal.retry_count;
+                internal.task.next_retry_at = internal.next_attempt_at;
+                // 本次执行已结束（即便后续会由退避调度器重新派发），释放本次持有的许可
+                internal.permit = None;
+            }
+        }
+        self.persist_task(task_id).await;
+    }
+
+    /// 将任务的当前内存状态写入数据库；写入失败只记录日志，不影响内存中的任务状态
+    async fn persist_task(&self, task_id: &str) {
+        let snapshot = {
+            let tasks = self.tasks.read().await;
+            tasks.get(task_id).map(|internal| {
+                (
+                    internal.task.clone(),
+                    internal.retry_count,
+                    internal.next_attempt_at,
+                )
+            })
+        };
+
+        let Some((task, retry_count, next_attempt_at)) = snapshot else {
+            return;
+        };
+
+        if let Err(e) = self
+            .db
+            .transfer_task_upsert(&task, retry_count, next_attempt_at)
+        {
+            tracing::warn!(task_id = %task_id, error = %e, "持久化传输任务失败");
+        }
+    }
+
+    /// 退避重试调度器的单次 tick
+    ///
+    /// 将 `next_attempt_at` 已到期的 Failed 任务提升为 Waiting 并增加其 `retry_count`，
+    /// 返回被提升的任务 ID 列表。调用方需要自行为每个 ID 派发 [`execute_task`]
+    /// （调度器本身不持有 `AppHandle`/`SessionManager`，无法直接执行传输）。
+    pub async fn scheduler_tick(&self) -> Vec<String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut due = Vec::new();
+
+        {
+            let mut tasks = self.tasks.write().await;
+            for (task_id, internal) in tasks.iter_mut() {
+                if internal.task.status != TransferStatus::Failed {
+                    continue;
+                }
+                let Some(next_attempt_at) = internal.next_attempt_at else {
+                    continue;
+                };
+                if next_attempt_at > now {
+                    continue;
+                }
+
+                internal.retry_count += 1;
+                internal.next_attempt_at = None;
+                internal.task.status = TransferStatus::Waiting;
+                internal.task.error_message = None;
+                internal.task.error_code = None;
+                internal.task.completed_at = None;
+                internal.task.retry_count = internal.retry_count;
+                internal.task.next_retry_at = None;
+                due.push(task_id.clone());
+            }
         }
+
+        for task_id in &due {
+            self.persist_task(task_id).await;
+            tracing::info!(task_id = %task_id, "退避重试到期，任务已重新排队");
+        }
+
+        due
+    }
+
+    /// 会话重连成功后，立即恢复该会话下处于 Failed 状态的任务
+    ///
+    /// 绕过退避调度器的等待窗口，将 `session_id` 匹配的 Failed 任务直接提升为 Waiting，
+    /// 返回被提升的任务 ID 列表。调用方需要自行为每个 ID 派发 [`execute_task`]。
+    pub async fn resume_tasks_for_session(&self, session_id: &str) -> Vec<String> {
+        let mut resumed = Vec::new();
+
+        {
+            let mut tasks = self.tasks.write().await;
+            for (task_id, internal) in tasks.iter_mut() {
+                if internal.task.status != TransferStatus::Failed {
+                    continue;
+                }
+                if internal.task.session_id != session_id {
+                    continue;
+                }
+
+                internal.retry_count += 1;
+                internal.next_attempt_at = None;
+                internal.task.status = TransferStatus::Waiting;
+                internal.task.error_message = None;
+                internal.task.error_code = None;
+                internal.task.completed_at = None;
+                internal.task.retry_count = internal.retry_count;
+                internal.task.next_retry_at = None;
+                resumed.push(task_id.clone());
+            }
+        }
+
+        for task_id in &resumed {
+            self.persist_task(task_id).await;
+            tracing::info!(task_id = %task_id, session_id = %session_id, "会话重连成功，任务已恢复排队");
+        }
+
+        resumed
+    }
+
+    /// 会话健康检查发现失联后，立即暂停该会话下仍在 Running 的任务
+    ///
+    /// 不等待任务自己的读写调用因连接断开而报错——那样每个任务还要各自撞一次超时
+    /// 才会失败。直接标记为 Failed（可重试），等 [`Self::resume_tasks_for_session`]
+    /// 在重连成功后把它们重新排队，从断点续传的偏移量继续，而不是让它们对着一个
+    /// 已确认失联的连接空转。
+    pub async fn pause_running_tasks_for_session(&self, session_id: &str) -> Vec<String> {
+        let mut paused = Vec::new();
+
+        {
+            let mut tasks = self.tasks.write().await;
+            for (task_id, internal) in tasks.iter_mut() {
+                if internal.task.status != TransferStatus::Running {
+                    continue;
+                }
+                if internal.task.session_id != session_id {
+                    continue;
+                }
+
+                internal.task.status = TransferStatus::Failed;
+                internal.task.error_message = Some("会话已断线，等待自动重连".to_string());
+                internal.task.error_code = Some(serialize_error_code(&ErrorCode::NetworkLost));
+                internal.task.retryable = Some(true);
+                internal.task.completed_at = Some(chrono::Utc::now().timestamp_millis());
+                internal.next_attempt_at = None;
+                internal.task.next_retry_at = None;
+                internal.permit = None;
+                paused.push(task_id.clone());
+            }
+        }
+
+        for task_id in &paused {
+            self.persist_task(task_id).await;
+            tracing::info!(task_id = %task_id, session_id = %session_id, "会话失联，任务已暂停");
+        }
+
+        paused
     }
 
     /// 推送状态事件
@@ -974,23 +3412,19 @@ impl TransferManager {
         task_id: &str,
         status: TransferStatus,
         error: Option<&AppError>,
+        checksum: Option<String>,
     ) {
         let payload = TransferStatusPayload {
             task_id: task_id.to_string(),
             status,
             error_code: error.map(|e| serialize_error_code(&e.code)),
             error_message: error.map(|e| e.message.clone()),
+            checksum,
         };
         app.emit("transfer:status", &payload).ok();
     }
 }
 
-impl Default for TransferManager {
-    fn default() -> Self {
-        Self::new(3) // 默认最大并发数
-    }
-}
-
 // 安全性：TransferManager 可以跨线程共享
 unsafe impl Send for TransferManager {}
 unsafe impl Sync for TransferManager {}
@@ -1013,6 +3447,29 @@ mod tests {
         file
     }
 
+    /// 创建使用临时数据库的测试用 TransferManager
+    fn new_test_manager(max_concurrent: u8) -> TransferManager {
+        let db_dir = tempfile::tempdir().unwrap();
+        let db_path = db_dir.path().join("test.db");
+        // 泄漏临时目录句柄以保证数据库文件在测试期间不被清理
+        std::mem::forget(db_dir);
+        let db = Arc::new(Database::init_at(&db_path).unwrap());
+        TransferManager::new(
+            max_concurrent,
+            DEFAULT_RETRY_COUNT,
+            db,
+            32 * 1024 * 1024,
+            4,
+            true,
+            0,
+            false,
+            "sha256sum".to_string(),
+            10 * 1024 * 1024,
+            4,
+            16,
+        )
+    }
+
     // ========== 辅助函数测试 ==========
 
     #[test]
@@ -1069,14 +3526,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_transfer_manager_creation() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let tasks = manager.list_tasks().await;
         assert!(tasks.is_empty());
     }
 
     #[tokio::test]
     async fn test_create_upload_nonexistent_file() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let result = manager
             .create_upload(
                 "session_id".to_string(),
@@ -1090,7 +3547,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_create_upload_success() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"hello world");
 
         let result = manager
@@ -1116,7 +3573,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_create_upload_directory_rejected() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_dir = tempfile::tempdir().unwrap();
 
         let result = manager
@@ -1133,9 +3590,52 @@ mod tests {
         assert!(err.message.contains("不支持上传目录"));
     }
 
+    #[tokio::test]
+    async fn test_try_create_upload_succeeds_when_permits_available() {
+        let manager = new_test_manager(3);
+        let temp_file = create_temp_file(b"hello world");
+
+        let result = manager
+            .try_create_upload(
+                "session_123".to_string(),
+                temp_file.path().to_str().unwrap().to_string(),
+                "/remote/dir".to_string(),
+            )
+            .await;
+
+        assert!(result.is_ok());
+        // 探测许可已在返回前释放，不应占用任何槽位
+        assert_eq!(
+            manager.semaphore.available_permits(),
+            manager.max_concurrent.load(Ordering::SeqCst) as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_create_upload_rejects_busy_without_queuing() {
+        let manager = new_test_manager(1);
+        let temp_file = create_temp_file(b"hello world");
+
+        // 占满唯一的并发槽位
+        let _permit = manager.semaphore.acquire().await.unwrap();
+
+        let result = manager
+            .try_create_upload(
+                "session_123".to_string(),
+                temp_file.path().to_str().unwrap().to_string(),
+                "/remote/dir".to_string(),
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().code, ErrorCode::Busy);
+        // 被拒绝的请求不应该被塞进任务队列里排队等待
+        assert!(manager.list_tasks().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_create_download_invalid_local_dir() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
 
         let result = manager
             .create_download(
@@ -1151,7 +3651,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_create_download_local_path_is_file() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"test");
 
         let result = manager
@@ -1168,7 +3668,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_remote_path_construction_root() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1186,7 +3686,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_remote_path_construction_with_trailing_slash() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1207,14 +3707,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_list_tasks_empty() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let tasks = manager.list_tasks().await;
         assert_eq!(tasks.len(), 0);
     }
 
     #[tokio::test]
     async fn test_list_tasks_multiple() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp1 = create_temp_file(b"test1");
         let temp2 = create_temp_file(b"test2");
 
@@ -1241,14 +3741,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_task_nonexistent() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let task = manager.get_task("nonexistent-id").await;
         assert!(task.is_none());
     }
 
     #[tokio::test]
     async fn test_get_task_exists() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1269,7 +3769,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_update_status_to_running() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1292,7 +3792,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_update_status_to_success_sets_completed_at() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1319,7 +3819,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_update_error_sets_all_fields() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1346,7 +3846,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cancel_nonexistent_task() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let result = manager.cancel_task("nonexistent").await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().code, ErrorCode::NotFound);
@@ -1354,7 +3854,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cancel_waiting_task_sets_token() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1381,7 +3881,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cancel_completed_task_is_idempotent() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1411,7 +3911,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cleanup_removes_success_and_canceled() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp1 = create_temp_file(b"data1");
         let temp2 = create_temp_file(b"data2");
 
@@ -1437,7 +3937,8 @@ mod tests {
             .update_status(&task2, TransferStatus::Canceled)
             .await;
 
-        manager.cleanup_completed().await;
+        // max_age_ms = 0: 立即清理所有已完成任务（不等待默认的保留期）
+        manager.cleanup_completed(Some(0)).await.unwrap();
 
         let tasks = manager.list_tasks().await;
         assert_eq!(tasks.len(), 0);
@@ -1445,7 +3946,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cleanup_keeps_waiting_running_failed() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp1 = create_temp_file(b"data1");
         let temp2 = create_temp_file(b"data2");
         let temp3 = create_temp_file(b"data3");
@@ -1478,7 +3979,7 @@ mod tests {
         manager.update_status(&task2, TransferStatus::Running).await;
         manager.update_status(&task3, TransferStatus::Failed).await;
 
-        manager.cleanup_completed().await;
+        manager.cleanup_completed(None).await.unwrap();
 
         let tasks = manager.list_tasks().await;
         assert_eq!(tasks.len(), 3);
@@ -1488,7 +3989,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_retry_failed_task_creates_new_task() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1516,7 +4017,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_retry_preserves_paths() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1545,7 +4046,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_retry_only_works_on_failed_tasks() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1565,17 +4066,112 @@ mod tests {
 
     #[tokio::test]
     async fn test_retry_nonexistent_task() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let result = manager.retry_task("nonexistent").await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().code, ErrorCode::NotFound);
     }
 
+    // ========== 断点续传测试 ==========
+
+    #[tokio::test]
+    async fn test_retry_resumes_from_offset_when_source_unchanged() {
+        let manager = new_test_manager(3);
+        let temp_file = create_temp_file(b"hello world"); // 11 字节
+
+        let task_id = manager
+            .create_upload(
+                "session_123".to_string(),
+                temp_file.path().to_str().unwrap().to_string(),
+                "/remote/dir".to_string(),
+            )
+            .await
+            .unwrap();
+
+        // 模拟传输中断在第 5 字节处失败
+        manager.update_transferred(&task_id, 5).await;
+        let error = AppError::network_lost("Connection lost");
+        manager.update_error(&task_id, &error).await;
+
+        let new_task_id = manager.retry_task(&task_id).await.unwrap();
+        let new_task = manager.get_task(&new_task_id).await.unwrap();
+
+        assert_eq!(new_task.resume_offset, Some(5));
+        assert_eq!(new_task.transferred, 5);
+    }
+
+    #[tokio::test]
+    async fn test_retry_falls_back_to_zero_when_source_modified() {
+        let manager = new_test_manager(3);
+        let temp_file = create_temp_file(b"hello world");
+
+        let task_id = manager
+            .create_upload(
+                "session_123".to_string(),
+                temp_file.path().to_str().unwrap().to_string(),
+                "/remote/dir".to_string(),
+            )
+            .await
+            .unwrap();
+
+        manager.update_transferred(&task_id, 5).await;
+        let error = AppError::network_lost("Connection lost");
+        manager.update_error(&task_id, &error).await;
+
+        // 源文件内容在失败后被修改，大小和 mtime 均发生变化
+        std::fs::write(temp_file.path(), b"hello world, modified").unwrap();
+
+        let new_task_id = manager.retry_task(&task_id).await.unwrap();
+        let new_task = manager.get_task(&new_task_id).await.unwrap();
+
+        assert_eq!(new_task.resume_offset, None);
+        assert_eq!(new_task.transferred, 0);
+    }
+
+    #[tokio::test]
+    async fn test_compute_resume_offset_download_requires_matching_partial_size() {
+        let temp_file = create_temp_file(b"abcde"); // 本地已下载 5 字节
+
+        let task = TransferTask {
+            task_id: "t1".to_string(),
+            session_id: "s1".to_string(),
+            direction: TransferDirection::Download,
+            local_path: temp_file.path().to_str().unwrap().to_string(),
+            remote_path: "/remote/file.txt".to_string(),
+            file_name: "file.txt".to_string(),
+            status: TransferStatus::Failed,
+            transferred: 5,
+            total: Some(20),
+            speed: None,
+            percent: None,
+            error_message: None,
+            error_code: None,
+            retryable: Some(true),
+            created_at: 0,
+            completed_at: None,
+            resume_offset: None,
+            source_mtime: None,
+            speed_limit_bytes_per_sec: None,
+            resumable: false,
+            retry_count: 0,
+            next_retry_at: None,
+            parent_task_id: None,
+            is_batch: false,
+            verify_checksum_override: None,
+        };
+
+        assert_eq!(TransferManager::compute_resume_offset(&task), Some(5));
+
+        let mut mismatched = task.clone();
+        mismatched.transferred = 999; // 与本地部分文件大小不符
+        assert_eq!(TransferManager::compute_resume_offset(&mismatched), None);
+    }
+
     // ========== 并发测试 ==========
 
     #[tokio::test]
     async fn test_concurrent_task_creation() {
-        let manager = Arc::new(TransferManager::new(3));
+        let manager = Arc::new(new_test_manager(3));
         let mut handles = vec![];
 
         // 并发创建 10 个任务
@@ -1613,7 +4209,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_concurrent_reads_and_writes() {
-        let manager = Arc::new(TransferManager::new(3));
+        let manager = Arc::new(new_test_manager(3));
         let temp_file = create_temp_file(b"data");
 
         let task_id = manager
@@ -1667,7 +4263,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_semaphore_available_permits() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         // 默认是 3（从 settings）
         let available = manager.semaphore.available_permits();
         assert!(available >= 1 && available <= 6); // Settings 范围 1-6
@@ -1675,7 +4271,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_semaphore_acquire_release() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let initial = manager.semaphore.available_permits();
 
         // 获取一个许可
@@ -1693,7 +4289,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_semaphore_blocks_when_exhausted() {
-        let manager = TransferManager::new(3);
+        let manager = new_test_manager(3);
         let max_permits = manager.semaphore.available_permits();
 
         // 获取所有许可
@@ -1728,7 +4324,7 @@ mod tests {
     async fn test_concurrent_semaphore_limit_enforcement() {
         use std::sync::atomic::{AtomicU32, Ordering};
 
-        let manager = Arc::new(TransferManager::new(3));
+        let manager = Arc::new(new_test_manager(3));
         let max_permits = manager.semaphore.available_permits();
         let counter = Arc::new(AtomicU32::new(0));
 
@@ -1753,4 +4349,169 @@ mod tests {
             h.await.unwrap();
         }
     }
+
+    #[tokio::test]
+    async fn test_set_max_concurrent_raises_limit() {
+        let manager = new_test_manager(2);
+        let initial = manager.semaphore.available_permits();
+
+        let previous = manager.set_max_concurrent(5);
+
+        assert_eq!(previous, 2);
+        assert_eq!(manager.semaphore.available_permits(), initial + 3);
+    }
+
+    #[tokio::test]
+    async fn test_set_max_concurrent_lowers_limit_without_killing_running_permits() {
+        let manager = new_test_manager(4);
+
+        // 占用两个许可，模拟两个正在进行的传输
+        let permit1 = manager.semaphore.acquire().await.unwrap();
+        let permit2 = manager.semaphore.acquire().await.unwrap();
+        assert_eq!(manager.semaphore.available_permits(), 2);
+
+        let previous = manager.set_max_concurrent(1);
+        assert_eq!(previous, 4);
+
+        // 在途的两个许可不受影响，未被强行回收
+        assert_eq!(manager.semaphore.available_permits(), 2);
+
+        // 释放后台任务需要吞掉的 3 个许可：等待它们依次被释放后吞掉
+        drop(permit1);
+        drop(permit2);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // 最终总容量降到新上限 1
+        assert_eq!(manager.semaphore.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_max_concurrent_clamps_to_valid_range() {
+        let manager = new_test_manager(3);
+
+        assert_eq!(manager.set_max_concurrent(0), 3);
+        assert_eq!(manager.max_concurrent.load(Ordering::SeqCst), 1);
+
+        assert_eq!(manager.set_max_concurrent(100), 1);
+        assert_eq!(manager.max_concurrent.load(Ordering::SeqCst), 6);
+    }
+
+    // ========== 本地文件句柄信号量测试 ==========
+
+    #[tokio::test]
+    async fn test_fs_semaphore_initial_capacity_matches_setting() {
+        let manager = new_test_manager(2);
+        assert_eq!(manager.fs_semaphore.available_permits(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_fs_semaphore_clamps_to_valid_range() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let db_path = db_dir.path().join("test.db");
+        std::mem::forget(db_dir);
+        let db = Arc::new(Database::init_at(&db_path).unwrap());
+
+        let manager = TransferManager::new(
+            2,
+            DEFAULT_RETRY_COUNT,
+            db,
+            32 * 1024 * 1024,
+            4,
+            true,
+            0,
+            false,
+            "sha256sum".to_string(),
+            10 * 1024 * 1024,
+            4,
+            1000,
+        );
+
+        assert_eq!(manager.fs_semaphore.available_permits(), 64);
+    }
+
+    // ========== 按体积加权的许可成本测试 ==========
+
+    fn task_with(direction: TransferDirection, total: Option<u64>) -> TransferTask {
+        TransferTask {
+            task_id: "t1".to_string(),
+            session_id: "s1".to_string(),
+            direction,
+            local_path: "/local/file.bin".to_string(),
+            remote_path: "/remote/file.bin".to_string(),
+            file_name: "file.bin".to_string(),
+            status: TransferStatus::Waiting,
+            transferred: 0,
+            total,
+            speed: None,
+            percent: Some(0),
+            error_message: None,
+            error_code: None,
+            retryable: None,
+            created_at: 0,
+            completed_at: None,
+            resume_offset: None,
+            source_mtime: None,
+            speed_limit_bytes_per_sec: None,
+            resumable: false,
+            retry_count: 0,
+            next_retry_at: None,
+            parent_task_id: None,
+            is_batch: false,
+            verify_checksum_override: None,
+        }
+    }
+
+    #[test]
+    fn test_permit_cost_small_upload_is_one() {
+        let task = task_with(TransferDirection::Upload, Some(1024));
+        assert_eq!(permit_cost(&task, 32 * 1024 * 1024, 6), 1);
+    }
+
+    #[test]
+    fn test_permit_cost_large_upload_scales_with_size() {
+        let chunk = 32 * 1024 * 1024u64;
+        let task = task_with(TransferDirection::Upload, Some(chunk * 2 + 1));
+        // 刚超过两份阈值一个字节，向上取整为 3 份
+        assert_eq!(permit_cost(&task, chunk, 6), 3);
+    }
+
+    #[test]
+    fn test_permit_cost_clamps_to_max_permits() {
+        let chunk = 32 * 1024 * 1024u64;
+        let task = task_with(TransferDirection::Upload, Some(chunk * 100));
+        assert_eq!(permit_cost(&task, chunk, 6), 6);
+    }
+
+    #[test]
+    fn test_permit_cost_download_is_always_one() {
+        // 下载在派发时尚不知道文件大小，不参与按体积加权
+        let task = task_with(TransferDirection::Download, None);
+        assert_eq!(permit_cost(&task, 32 * 1024 * 1024, 6), 1);
+    }
+
+    #[test]
+    fn test_permit_cost_unknown_size_is_one() {
+        let task = task_with(TransferDirection::Upload, None);
+        assert_eq!(permit_cost(&task, 32 * 1024 * 1024, 6), 1);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_many_respects_weighted_cost() {
+        let manager = new_test_manager(4);
+        assert_eq!(manager.semaphore.available_permits(), 4);
+
+        let permit = manager.semaphore.acquire_many(3).await.unwrap();
+        assert_eq!(manager.semaphore.available_permits(), 1);
+
+        // 剩余容量不足以满足另一个高权重任务，必须等待
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(100),
+            manager.semaphore.acquire_many(2),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        drop(permit);
+        assert_eq!(manager.semaphore.available_permits(), 4);
+    }
 }
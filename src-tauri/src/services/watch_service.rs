@@ -0,0 +1,614 @@
+//! 远程目录监视服务
+//!
+//! SFTP 没有原生的 inotify 机制，这里用轮询模拟变更通知：定期 `readdir`（可选递归到
+//! 指定深度），将本次快照（路径 → 大小/mtime/类型）与上一次快照比较差异，推导出
+//! Created / Modified / Removed / Renamed 事件，并通过 Tauri 事件推送给前端。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ssh2::Sftp;
+use tauri::{AppHandle, Emitter};
+
+use crate::models::error::{AppError, AppResult, ErrorCode};
+use crate::models::file_entry::FileEntry;
+use crate::models::watch::{WatchEventKind, WatchEventPayload, WatchInfo};
+use crate::services::session_manager::SessionManager;
+use crate::services::sftp_service::SftpService;
+
+/// 默认轮询间隔
+const DEFAULT_POLL_INTERVAL_MS: u64 = 2000;
+/// 默认防抖窗口：连续变更在此窗口内会被合并为一次推送
+const DEFAULT_DEBOUNCE_MS: u64 = 300;
+/// 默认递归深度（0 = 仅监视目录本身的直接子项）
+const DEFAULT_RECURSIVE_DEPTH: u32 = 0;
+/// 单次监视允许递归的最大深度，避免误配置拖垮大型目录树
+const MAX_RECURSIVE_DEPTH: u32 = 8;
+
+/// 快照中单个条目的可比较属性
+#[derive(Clone)]
+struct EntrySnapshot {
+    is_dir: bool,
+    size: u64,
+    mtime: i64,
+    entry: FileEntry,
+}
+
+impl PartialEq for EntrySnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_dir == other.is_dir && self.size == other.size && self.mtime == other.mtime
+    }
+}
+
+/// 一次监视请求的参数
+#[derive(Clone)]
+pub struct WatchOptions {
+    pub recursive_depth: Option<u32>,
+    pub poll_interval_ms: Option<u64>,
+    pub debounce_ms: Option<u64>,
+}
+
+/// 托管的监视器
+struct ManagedWatch {
+    watch_id: String,
+    session_id: String,
+    path: String,
+    shutdown: Arc<AtomicBool>,
+}
+
+/// 远程目录监视管理器
+pub struct WatchManager {
+    /// watch_id -> 监视器
+    watches: RwLock<HashMap<String, Arc<ManagedWatch>>>,
+    /// (session_id, path) -> watch_id，用于按 key 去重
+    key_to_watch: RwLock<HashMap<(String, String), String>>,
+}
+
+impl WatchManager {
+    pub fn new() -> Self {
+        Self {
+            watches: RwLock::new(HashMap::new()),
+            key_to_watch: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 开始监视一个远程目录（已存在相同 (session_id, path) 的监视器时直接返回现有实例）
+    pub fn watch(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        session_id: String,
+        path: String,
+        options: WatchOptions,
+    ) -> AppResult<WatchInfo> {
+        let normalized_path = SftpService::normalize_path(&path);
+        let key = (session_id.clone(), normalized_path.clone());
+
+        {
+            let key_to_watch = self
+                .key_to_watch
+                .read()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器映射锁"))?;
+            if let Some(watch_id) = key_to_watch.get(&key) {
+                tracing::info!(
+                    session_id = %session_id,
+                    path = %normalized_path,
+                    watch_id = %watch_id,
+                    "监视器已存在，返回现有实例"
+                );
+                return Ok(WatchInfo {
+                    watch_id: watch_id.clone(),
+                    session_id,
+                    path: normalized_path,
+                });
+            }
+        }
+
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let managed = Arc::new(ManagedWatch {
+            watch_id: watch_id.clone(),
+            session_id: session_id.clone(),
+            path: normalized_path.clone(),
+            shutdown: shutdown.clone(),
+        });
+
+        {
+            let mut watches = self
+                .watches
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器池锁"))?;
+            watches.insert(watch_id.clone(), managed.clone());
+        }
+        {
+            let mut key_to_watch = self
+                .key_to_watch
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器映射锁"))?;
+            key_to_watch.insert(key, watch_id.clone());
+        }
+
+        self.start_poll_thread(app, session_manager, managed, options);
+
+        tracing::info!(
+            session_id = %session_id,
+            path = %normalized_path,
+            watch_id = %watch_id,
+            "远程目录监视已启动"
+        );
+
+        Ok(WatchInfo {
+            watch_id,
+            session_id,
+            path: normalized_path,
+        })
+    }
+
+    /// 停止一个监视器
+    pub fn unwatch(&self, watch_id: &str) -> AppResult<()> {
+        let managed = {
+            let mut watches = self
+                .watches
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器池锁"))?;
+            watches.remove(watch_id)
+        };
+
+        if let Some(managed) = managed {
+            managed.shutdown.store(true, Ordering::Relaxed);
+
+            let mut key_to_watch = self
+                .key_to_watch
+                .write()
+                .map_err(|_| AppError::new(ErrorCode::Unknown, "无法获取监视器映射锁"))?;
+            key_to_watch.remove(&(managed.session_id.clone(), managed.path.clone()));
+
+            tracing::info!(
+                watch_id = %watch_id,
+                session_id = %managed.session_id,
+                path = %managed.path,
+                "监视器已停止"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 停止某个会话下的所有监视器（会话关闭时调用）
+    pub fn unwatch_by_session(&self, session_id: &str) -> usize {
+        let watch_ids: Vec<String> = {
+            let Ok(watches) = self.watches.read() else {
+                return 0;
+            };
+            watches
+                .values()
+                .filter(|w| w.session_id == session_id)
+                .map(|w| w.watch_id.clone())
+                .collect()
+        };
+
+        for watch_id in &watch_ids {
+            if let Err(e) = self.unwatch(watch_id) {
+                tracing::warn!(watch_id = %watch_id, error = %e, "停止监视器失败");
+            }
+        }
+
+        watch_ids.len()
+    }
+
+    fn start_poll_thread(
+        &self,
+        app: AppHandle,
+        session_manager: Arc<SessionManager>,
+        managed: Arc<ManagedWatch>,
+        options: WatchOptions,
+    ) {
+        let poll_interval =
+            Duration::from_millis(options.poll_interval_ms.unwrap_or(DEFAULT_POLL_INTERVAL_MS));
+        let debounce = Duration::from_millis(options.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS));
+        let depth = options
+            .recursive_depth
+            .unwrap_or(DEFAULT_RECURSIVE_DEPTH)
+            .min(MAX_RECURSIVE_DEPTH);
+
+        thread::spawn(move || {
+            let mut previous: Option<HashMap<String, EntrySnapshot>> = None;
+            let mut pending: Vec<WatchEventPayload> = Vec::new();
+            let mut last_change_at: Option<Instant> = None;
+
+            loop {
+                if managed.shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                // 会话已不存在（关闭或已被清理），自动停止监视
+                let Ok(session) = session_manager.get_session(&managed.session_id) else {
+                    tracing::info!(
+                        watch_id = %managed.watch_id,
+                        session_id = %managed.session_id,
+                        "关联会话已不存在，监视器自动停止"
+                    );
+                    break;
+                };
+
+                let watch_path = managed.path.clone();
+                let snapshot_result =
+                    session.with_sftp(move |sftp| Self::snapshot_dir(sftp, &watch_path, depth));
+
+                match snapshot_result {
+                    Ok(current) => {
+                        if let Some(previous_snapshot) = previous.take() {
+                            let events =
+                                Self::diff_snapshots(&managed, &previous_snapshot, &current);
+                            if !events.is_empty() {
+                                pending.extend(events);
+                                last_change_at = Some(Instant::now());
+                            }
+                        }
+                        previous = Some(current);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            watch_id = %managed.watch_id,
+                            path = %managed.path,
+                            error = %e,
+                            "读取监视目录失败，跳过本轮"
+                        );
+                    }
+                }
+
+                let should_flush = !pending.is_empty()
+                    && last_change_at
+                        .map(|t| t.elapsed() >= debounce)
+                        .unwrap_or(false);
+
+                if should_flush {
+                    for event in pending.drain(..) {
+                        app.emit("watch:event", &event).ok();
+                    }
+                    last_change_at = None;
+                }
+
+                thread::sleep(poll_interval);
+            }
+
+            tracing::info!(
+                watch_id = %managed.watch_id,
+                session_id = %managed.session_id,
+                "监视轮询线程已退出"
+            );
+        });
+    }
+
+    /// 对目录做快照，递归到 `depth` 层（0 = 不递归，只看直接子项）
+    fn snapshot_dir(
+        sftp: &Sftp,
+        path: &str,
+        depth: u32,
+    ) -> AppResult<HashMap<String, EntrySnapshot>> {
+        let mut snapshot = HashMap::new();
+        Self::snapshot_dir_into(sftp, path, depth, &mut snapshot)?;
+        Ok(snapshot)
+    }
+
+    fn snapshot_dir_into(
+        sftp: &Sftp,
+        path: &str,
+        depth: u32,
+        snapshot: &mut HashMap<String, EntrySnapshot>,
+    ) -> AppResult<()> {
+        let entries = SftpService::list_dir(sftp, path, None, None, false)?;
+
+        for entry in entries {
+            let is_dir = entry.is_dir;
+            let size = entry.size.unwrap_or(0);
+            let mtime = entry.mtime.unwrap_or(0);
+            let sub_path = entry.path.clone();
+
+            if is_dir && depth > 0 {
+                // 子目录读取失败（如权限不足）不应中断整体快照，跳过即可
+                if let Err(e) = Self::snapshot_dir_into(sftp, &sub_path, depth - 1, snapshot) {
+                    tracing::debug!(path = %sub_path, error = %e, "无法递归监视子目录，跳过");
+                }
+            }
+
+            snapshot.insert(
+                sub_path,
+                EntrySnapshot {
+                    is_dir,
+                    size,
+                    mtime,
+                    entry,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 对比两次快照，推导出变更事件
+    ///
+    /// 对于同一轮中消失的路径和新增的路径，若属性（大小/mtime/类型）完全一致，
+    /// 视为重命名/移动而非"删除+新建"
+    fn diff_snapshots(
+        managed: &ManagedWatch,
+        previous: &HashMap<String, EntrySnapshot>,
+        current: &HashMap<String, EntrySnapshot>,
+    ) -> Vec<WatchEventPayload> {
+        let mut events = Vec::new();
+
+        let mut removed: Vec<&String> = previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .collect();
+        let mut added: Vec<&String> = current
+            .keys()
+            .filter(|path| !previous.contains_key(*path))
+            .collect();
+
+        // 先尝试把消失的路径和新增的路径配对为 rename
+        let mut renamed_removed = Vec::new();
+        let mut renamed_added = Vec::new();
+        for (i, removed_path) in removed.iter().enumerate() {
+            let removed_snapshot = &previous[*removed_path];
+            if let Some((j, added_path)) = added.iter().enumerate().find(|(_, added_path)| {
+                let added_snapshot = &current[**added_path];
+                added_snapshot.is_dir == removed_snapshot.is_dir
+                    && added_snapshot.size == removed_snapshot.size
+                    && added_snapshot.mtime == removed_snapshot.mtime
+            }) {
+                events.push(Self::make_event(
+                    managed,
+                    WatchEventKind::Renamed,
+                    (*added_path).clone(),
+                    Some((*removed_path).clone()),
+                    Some(current[*added_path].entry.clone()),
+                ));
+                renamed_removed.push(i);
+                renamed_added.push(j);
+            }
+        }
+        // 从后往前移除，避免索引错位
+        renamed_removed.sort_unstable();
+        for i in renamed_removed.into_iter().rev() {
+            removed.remove(i);
+        }
+        renamed_added.sort_unstable();
+        for j in renamed_added.into_iter().rev() {
+            added.remove(j);
+        }
+
+        for path in removed {
+            events.push(Self::make_event(
+                managed,
+                WatchEventKind::Removed,
+                path.clone(),
+                None,
+                None,
+            ));
+        }
+
+        for path in added {
+            events.push(Self::make_event(
+                managed,
+                WatchEventKind::Created,
+                path.clone(),
+                None,
+                Some(current[path].entry.clone()),
+            ));
+        }
+
+        for (path, current_snapshot) in current {
+            let Some(previous_snapshot) = previous.get(path) else {
+                continue;
+            };
+            if previous_snapshot != current_snapshot {
+                events.push(Self::make_event(
+                    managed,
+                    WatchEventKind::Modified,
+                    path.clone(),
+                    None,
+                    Some(current_snapshot.entry.clone()),
+                ));
+            }
+        }
+
+        events
+    }
+
+    fn make_event(
+        managed: &ManagedWatch,
+        kind: WatchEventKind,
+        entry_path: String,
+        old_path: Option<String>,
+        entry: Option<FileEntry>,
+    ) -> WatchEventPayload {
+        WatchEventPayload {
+            watch_id: managed.watch_id.clone(),
+            session_id: managed.session_id.clone(),
+            path: managed.path.clone(),
+            kind,
+            entry_path,
+            old_path,
+            entry,
+        }
+    }
+}
+
+impl Default for WatchManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: WatchManager 可以安全地跨线程共享，原因如下：
+// 1. `watches` 和 `key_to_watch` 使用 RwLock 保护，提供线程安全的访问
+// 2. 每个 ManagedWatch 不直接持有 ssh2 的 Session/Sftp，而是通过
+//    `session_manager.get_session()` 按需获取 `Arc<ManagedSession>`，
+//    实际的 SFTP 调用经由 `ManagedSession::sftp()` 的读锁完成
+// 3. 轮询在独立的 OS 线程中进行（而非 tokio 任务），因此在其中调用
+//    同步的 ssh2 方法不会阻塞 tokio 运行时，做法与 TerminalManager 的
+//    输出读取线程一致
+unsafe impl Send for WatchManager {}
+unsafe impl Sync for WatchManager {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_manager_creation() {
+        let manager = WatchManager::new();
+        assert_eq!(manager.unwatch_by_session("nonexistent"), 0);
+    }
+
+    #[test]
+    fn test_unwatch_nonexistent() {
+        let manager = WatchManager::new();
+        assert!(manager.unwatch("nonexistent").is_ok());
+    }
+
+    fn make_entry(path: &str, is_dir: bool, size: u64, mtime: i64) -> FileEntry {
+        FileEntry {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            is_dir,
+            size: Some(size),
+            mtime: Some(mtime),
+            mode: None,
+            is_symlink: false,
+            symlink_target: None,
+        }
+    }
+
+    fn make_managed(watch_id: &str, session_id: &str, path: &str) -> ManagedWatch {
+        ManagedWatch {
+            watch_id: watch_id.to_string(),
+            session_id: session_id.to_string(),
+            path: path.to_string(),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[test]
+    fn test_diff_detects_created_and_removed() {
+        let managed = make_managed("w1", "s1", "/home");
+
+        let mut previous = HashMap::new();
+        previous.insert(
+            "/home/a.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 10,
+                mtime: 100,
+                entry: make_entry("/home/a.txt", false, 10, 100),
+            },
+        );
+
+        let mut current = HashMap::new();
+        current.insert(
+            "/home/b.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 20,
+                mtime: 200,
+                entry: make_entry("/home/b.txt", false, 20, 200),
+            },
+        );
+
+        let events = WatchManager::diff_snapshots(&managed, &previous, &current);
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .any(|e| e.kind == WatchEventKind::Removed && e.entry_path == "/home/a.txt"));
+        assert!(events
+            .iter()
+            .any(|e| e.kind == WatchEventKind::Created && e.entry_path == "/home/b.txt"));
+    }
+
+    #[test]
+    fn test_diff_detects_rename() {
+        let managed = make_managed("w1", "s1", "/home");
+
+        let mut previous = HashMap::new();
+        previous.insert(
+            "/home/old.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 42,
+                mtime: 100,
+                entry: make_entry("/home/old.txt", false, 42, 100),
+            },
+        );
+
+        let mut current = HashMap::new();
+        current.insert(
+            "/home/new.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 42,
+                mtime: 100,
+                entry: make_entry("/home/new.txt", false, 42, 100),
+            },
+        );
+
+        let events = WatchManager::diff_snapshots(&managed, &previous, &current);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, WatchEventKind::Renamed);
+        assert_eq!(events[0].entry_path, "/home/new.txt");
+        assert_eq!(events[0].old_path.as_deref(), Some("/home/old.txt"));
+    }
+
+    #[test]
+    fn test_diff_detects_modified() {
+        let managed = make_managed("w1", "s1", "/home");
+
+        let mut previous = HashMap::new();
+        previous.insert(
+            "/home/a.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 10,
+                mtime: 100,
+                entry: make_entry("/home/a.txt", false, 10, 100),
+            },
+        );
+
+        let mut current = HashMap::new();
+        current.insert(
+            "/home/a.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 11,
+                mtime: 150,
+                entry: make_entry("/home/a.txt", false, 11, 150),
+            },
+        );
+
+        let events = WatchManager::diff_snapshots(&managed, &previous, &current);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, WatchEventKind::Modified);
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let managed = make_managed("w1", "s1", "/home");
+
+        let mut snapshot = HashMap::new();
+        snapshot.insert(
+            "/home/a.txt".to_string(),
+            EntrySnapshot {
+                is_dir: false,
+                size: 10,
+                mtime: 100,
+                entry: make_entry("/home/a.txt", false, 10, 100),
+            },
+        );
+
+        let events = WatchManager::diff_snapshots(&managed, &snapshot.clone(), &snapshot);
+        assert!(events.is_empty());
+    }
+}
@@ -12,12 +12,18 @@ use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
+    reload,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Registry,
 };
 
 use crate::services::storage_service::{get_app_data_dir, get_logs_dir};
 
+/// 日志过滤器的热重载句柄，由 [`init_logging`] 返回并存入 Tauri managed state，
+/// 供 `commands::settings::settings_set` 在用户修改日志级别时原地替换过滤器，
+/// 而不必重启整个 subscriber（控制台/文件输出层完全不受影响）
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
+
 fn level_as_str(level: Level) -> &'static str {
     match level {
         Level::ERROR => "error",
@@ -32,14 +38,38 @@ fn level_as_str(level: Level) -> &'static str {
 ///
 /// - 控制台输出 (开发模式)
 /// - 文件输出 (滚动日志，按天切割)
-pub fn init_logging(level: Level) -> Result<(), Box<dyn std::error::Error>> {
+///
+/// 日志目录创建失败时不再中止整个初始化——退化为只保留控制台输出，总比完全没有
+/// 日志系统、后续所有 `tracing::*` 调用静默丢失要好。环境过滤器包在
+/// [`reload::Layer`] 里，返回的 [`LogReloadHandle`] 交给调用方存入 managed state，
+/// 用于不重启进程热替换日志级别。
+pub fn init_logging(level: Level) -> LogReloadHandle {
     let logs_dir = get_logs_dir();
 
-    // 确保日志目录存在
-    fs::create_dir_all(&logs_dir)?;
-
-    // 创建滚动日志文件 appender (按天切割，保留 7 天)
-    let file_appender = RollingFileAppender::new(Rotation::DAILY, &logs_dir, "tunnelfiles.log");
+    let file_layer = match fs::create_dir_all(&logs_dir) {
+        Ok(()) => {
+            // 创建滚动日志文件 appender (按天切割，保留 7 天)
+            let file_appender = RollingFileAppender::new(Rotation::DAILY, &logs_dir, "tunnelfiles.log");
+            Some(
+                fmt::layer()
+                    .with_writer(file_appender)
+                    .with_target(true)
+                    .with_level(true)
+                    .with_thread_ids(true)
+                    .with_ansi(false)
+                    .with_span_events(FmtSpan::CLOSE)
+                    .json(),
+            )
+        }
+        Err(e) => {
+            eprintln!(
+                "无法创建日志目录 {}: {}，本次运行仅保留控制台输出",
+                logs_dir.display(),
+                e
+            );
+            None
+        }
+    };
 
     // 环境过滤器
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
@@ -48,6 +78,7 @@ pub fn init_logging(level: Level) -> Result<(), Box<dyn std::error::Error>> {
             level_as_str(level)
         ))
     });
+    let (filter_layer, reload_handle) = reload::Layer::new(env_filter);
 
     // 控制台输出层
     let console_layer = fmt::layer()
@@ -58,19 +89,9 @@ pub fn init_logging(level: Level) -> Result<(), Box<dyn std::error::Error>> {
         .with_ansi(true)
         .compact();
 
-    // 文件输出层
-    let file_layer = fmt::layer()
-        .with_writer(file_appender)
-        .with_target(true)
-        .with_level(true)
-        .with_thread_ids(true)
-        .with_ansi(false)
-        .with_span_events(FmtSpan::CLOSE)
-        .json();
-
     // 组合并初始化
     tracing_subscriber::registry()
-        .with(env_filter)
+        .with(filter_layer)
         .with(console_layer)
         .with(file_layer)
         .init();
@@ -81,13 +102,48 @@ pub fn init_logging(level: Level) -> Result<(), Box<dyn std::error::Error>> {
         "日志系统初始化完成"
     );
 
-    Ok(())
+    reload_handle
+}
+
+/// 应用退出前的日志落盘点
+///
+/// 文件输出层用的是 [`RollingFileAppender`] 直接实现的同步 `Write`（没有套
+/// `tracing_appender::non_blocking` 的后台线程缓冲），每条日志在 `tracing::*`
+/// 调用返回前就已经写入了文件描述符，本身并不存在"待刷新"的缓冲区。这里仍然
+/// 保留一个显式调用点，作为优雅关闭流程（见 `services::shutdown`）里"日志已
+/// 落盘，可以安全退出"的信号，并发出最后一条收尾日志
+pub fn flush_logs() {
+    tracing::info!("优雅关闭：日志已落盘");
+}
+
+/// 热替换当前生效的日志过滤器，无需重启进程
+///
+/// 失败（`reload::Handle` 所绑定的 subscriber 已被释放，实践中几乎不会发生）时
+/// 只记录一条告警并保留旧过滤器，不会级联成 `settings_set` 整体失败——持久化的
+/// 设置值与进程内实际生效的过滤器短暂不一致，好过因为日志热更新失败而回滚整个设置
+pub fn reload_log_level(handle: &LogReloadHandle, level: Level) {
+    let new_filter = EnvFilter::new(format!(
+        "tunnelfiles={},ssh2=warn,rusqlite=warn",
+        level_as_str(level)
+    ));
+    match handle.reload(new_filter) {
+        Ok(()) => {
+            tracing::info!(log_level = level_as_str(level), "日志级别已热更新");
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "日志级别热更新失败，继续使用当前过滤器");
+        }
+    }
 }
 
 /// 导出诊断包
 ///
-/// 打包日志文件和配置摘要为 zip 文件
-pub fn export_diagnostic_package() -> Result<PathBuf, Box<dyn std::error::Error>> {
+/// 打包日志文件和配置摘要为 zip 文件；`metrics_json` 非空时额外写入
+/// `metrics.json`（`system_monitor` 采样的当前快照 + 滚动历史，序列化后传入），
+/// 供支持人员查看资源趋势而不只是单次读数
+pub fn export_diagnostic_package(
+    metrics_json: Option<&str>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
     use std::io::Write;
 
     let app_dir = get_app_data_dir();
@@ -152,6 +208,12 @@ pub fn export_diagnostic_package() -> Result<PathBuf, Box<dyn std::error::Error>
         zip.write_all(db_info.as_bytes())?;
     }
 
+    // 添加运行时指标快照（CPU/内存/会话数等滚动历史）
+    if let Some(metrics) = metrics_json {
+        zip.start_file("metrics.json", options)?;
+        zip.write_all(metrics.as_bytes())?;
+    }
+
     zip.finish()?;
 
     tracing::info!(path = %output_path.display(), "诊断包导出完成");
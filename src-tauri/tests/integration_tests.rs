@@ -1139,6 +1139,698 @@ mod transfer_tests {
     }
 }
 
+// ============ SCP 传输测试 ============
+//
+// 覆盖 `services::file_transfer::ScpFileTransfer`，镜像 transfer_tests 里对应的
+// SFTP 用例，验证走 SCP 通道的结果与走 SFTP 通道完全一致。
+
+mod scp_transfer_tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use tunnelfiles_lib::services::file_transfer::{
+        FileTransfer, ScpFileTransfer, TransferReader, TransferWriter,
+    };
+
+    fn connected_scp(server: &TestServer) -> ScpFileTransfer {
+        let mut transfer =
+            ScpFileTransfer::with_password(server.host, server.port, server.username, server.password);
+        transfer.connect().expect("SCP 应该能连接");
+        transfer
+    }
+
+    #[test]
+    fn test_upload_small_file_scp() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let mut transfer = connected_scp(&TEST_SERVER_1);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let remote_path = format!("/home/testuser/uploads/scp_upload_small_{}.txt", timestamp);
+        let content = b"Hello, this is a small test file for SCP upload testing.";
+
+        // 上传
+        {
+            let mut writer = transfer
+                .open_write_sized(&remote_path, content.len() as u64)
+                .expect("应该能以 SCP 方式打开远程文件写入");
+            writer.write_all(content).expect("应该能写入远程文件");
+            writer.finish().expect("应该能完成 SCP 上传握手");
+        }
+
+        // 验证：通过 SCP 读回
+        {
+            let mut reader = transfer
+                .open_read(&remote_path)
+                .expect("应该能以 SCP 方式打开远程文件读取");
+            let mut read_content = Vec::new();
+            reader
+                .read_to_end(&mut read_content)
+                .expect("应该能读取远程文件");
+            assert_eq!(read_content, content, "上传内容应该一致");
+            reader.finish().expect("应该能完成 SCP 下载握手");
+        }
+
+        // 清理
+        transfer.remove_file(&remote_path).ok();
+    }
+
+    #[test]
+    fn test_read_binary_file_scp() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let mut transfer = connected_scp(&TEST_SERVER_1);
+        let mut reader = transfer
+            .open_read("/home/testuser/test-files/random.bin")
+            .expect("应该能以 SCP 方式打开二进制文件");
+
+        let mut buffer = Vec::new();
+        reader
+            .read_to_end(&mut buffer)
+            .expect("应该能读取二进制内容");
+        reader.finish().expect("应该能完成 SCP 下载握手");
+
+        // 验证大小 (100 KB)，与 transfer_tests::test_read_binary_file 的断言一致
+        assert_eq!(buffer.len(), 100 * 1024, "文件大小应该是 100 KB");
+    }
+}
+
+// ============ 递归目录传输测试 ============
+//
+// 覆盖 `FileTransfer` trait 上 `upload_dir`/`download_dir`/`remove_dir_all` 这几个
+// 默认方法，用 SftpFileTransfer 跑一遍完整的"建目录树 -> 上传 -> 下载 -> 递归删除"。
+
+mod recursive_transfer_tests {
+    use super::*;
+    use tunnelfiles_lib::services::file_transfer::{FileTransfer, SftpFileTransfer};
+
+    fn connected_sftp(server: &TestServer) -> SftpFileTransfer {
+        let mut transfer = SftpFileTransfer::with_password(
+            server.host,
+            server.port,
+            server.username,
+            server.password,
+        );
+        transfer.connect().expect("SFTP 应该能连接");
+        transfer
+    }
+
+    /// 在 `base` 临时目录下构建一棵嵌套树：
+    /// base/a.txt, base/sub/b.txt, base/sub/nested/c.txt
+    fn build_local_tree(base: &std::path::Path) {
+        std::fs::create_dir_all(base.join("sub/nested")).unwrap();
+        std::fs::write(base.join("a.txt"), b"root file").unwrap();
+        std::fs::write(base.join("sub/b.txt"), b"sub file").unwrap();
+        std::fs::write(base.join("sub/nested/c.txt"), b"nested file").unwrap();
+    }
+
+    #[test]
+    fn test_recursive_dir_roundtrip_and_delete() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let mut transfer = connected_sftp(&TEST_SERVER_1);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let local_upload_src = std::env::temp_dir().join(format!("tf_recursive_src_{}", timestamp));
+        let local_download_dst =
+            std::env::temp_dir().join(format!("tf_recursive_dst_{}", timestamp));
+        let remote_root = format!("/home/testuser/uploads/recursive_{}", timestamp);
+
+        build_local_tree(&local_upload_src);
+
+        // 上传整棵树
+        let upload_result = transfer
+            .upload_dir(&local_upload_src, &remote_root)
+            .expect("递归上传不应该整体失败");
+        assert!(upload_result.failures.is_empty(), "上传不应该有失败项");
+
+        // 验证远程目录结构：3 个文件 + 2 层子目录都能被 list_dir 发现
+        let root_entries = transfer.list_dir(&remote_root).expect("应该能列出根目录");
+        assert!(root_entries.iter().any(|e| e.name() == "a.txt"));
+        assert!(root_entries.iter().any(|e| e.name() == "sub" && e.is_dir()));
+
+        let sub_entries = transfer
+            .list_dir(&format!("{}/sub", remote_root))
+            .expect("应该能列出 sub 目录");
+        assert!(sub_entries.iter().any(|e| e.name() == "b.txt"));
+        assert!(sub_entries.iter().any(|e| e.name() == "nested" && e.is_dir()));
+
+        // 下载整棵树到另一个本地目录，验证内容完全往返一致
+        let download_result = transfer
+            .download_dir(&remote_root, &local_download_dst)
+            .expect("递归下载不应该整体失败");
+        assert!(download_result.failures.is_empty(), "下载不应该有失败项");
+
+        assert_eq!(
+            std::fs::read(local_download_dst.join("a.txt")).unwrap(),
+            b"root file"
+        );
+        assert_eq!(
+            std::fs::read(local_download_dst.join("sub/b.txt")).unwrap(),
+            b"sub file"
+        );
+        assert_eq!(
+            std::fs::read(local_download_dst.join("sub/nested/c.txt")).unwrap(),
+            b"nested file"
+        );
+
+        // 递归删除远程目录，确认整棵树都被清空
+        let delete_result = transfer
+            .remove_dir_all(&remote_root)
+            .expect("递归删除不应该整体失败");
+        assert!(delete_result.failures.is_empty(), "删除不应该有失败项");
+        assert!(
+            transfer.stat(&remote_root).is_err(),
+            "递归删除后根目录不应该再存在"
+        );
+
+        // 清理本地临时目录
+        std::fs::remove_dir_all(&local_upload_src).ok();
+        std::fs::remove_dir_all(&local_download_dst).ok();
+    }
+
+    #[derive(Default)]
+    struct RecordingProgress {
+        started: Vec<String>,
+        finished: Vec<String>,
+        total_bytes: u64,
+    }
+
+    impl tunnelfiles_lib::services::file_transfer::Progress for RecordingProgress {
+        fn on_file_start(&mut self, path: &str, _total: Option<u64>) {
+            self.started.push(path.to_string());
+        }
+
+        fn on_bytes(&mut self, delta: u64) {
+            self.total_bytes += delta;
+        }
+
+        fn on_file_done(&mut self, path: &str) {
+            self.finished.push(path.to_string());
+        }
+    }
+
+    #[test]
+    fn test_upload_download_dir_with_progress() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        use tunnelfiles_lib::services::file_transfer::SymlinkPolicy;
+
+        let mut transfer = connected_sftp(&TEST_SERVER_1);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let local_upload_src =
+            std::env::temp_dir().join(format!("tf_progress_src_{}", timestamp));
+        let local_download_dst =
+            std::env::temp_dir().join(format!("tf_progress_dst_{}", timestamp));
+        let remote_root = format!("/home/testuser/uploads/progress_{}", timestamp);
+
+        build_local_tree(&local_upload_src);
+
+        let mut upload_progress = RecordingProgress::default();
+        let upload_result = transfer
+            .upload_dir_with_progress(
+                &local_upload_src,
+                &remote_root,
+                SymlinkPolicy::Skip,
+                &mut upload_progress,
+            )
+            .expect("带进度的递归上传不应该整体失败");
+        assert!(upload_result.failures.is_empty(), "上传不应该有失败项");
+        assert_eq!(upload_progress.started.len(), 3, "应该为 3 个文件各触发一次 on_file_start");
+        assert_eq!(upload_progress.finished.len(), 3, "应该为 3 个文件各触发一次 on_file_done");
+        assert_eq!(
+            upload_progress.total_bytes,
+            (b"root file".len() + b"sub file".len() + b"nested file".len()) as u64,
+            "on_bytes 汇报的总字节数应该等于所有文件大小之和"
+        );
+
+        let mut download_progress = RecordingProgress::default();
+        let download_result = transfer
+            .download_dir_with_progress(&remote_root, &local_download_dst, &mut download_progress)
+            .expect("带进度的递归下载不应该整体失败");
+        assert!(download_result.failures.is_empty(), "下载不应该有失败项");
+        assert_eq!(download_progress.started.len(), 3);
+        assert_eq!(download_progress.finished.len(), 3);
+
+        assert_eq!(
+            std::fs::read(local_download_dst.join("a.txt")).unwrap(),
+            b"root file"
+        );
+
+        transfer.remove_dir_all(&remote_root).ok();
+        std::fs::remove_dir_all(&local_upload_src).ok();
+        std::fs::remove_dir_all(&local_download_dst).ok();
+    }
+}
+
+// ============ 服务端 copy 测试 ============
+//
+// 覆盖 `SftpFileTransfer::copy`：默认优先走 exec `cp -r`，测试环境的 Docker 镜像
+// 开了 exec，因此这里实际验证的是 exec 路径；流式回退路径由 `stream_copy` 本身的
+// 递归结构保证（与 `recursive_transfer_tests` 共用同一套 list_dir/open_read/open_write）。
+
+mod server_side_copy_tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use tunnelfiles_lib::services::file_transfer::{FileTransfer, SftpFileTransfer};
+
+    fn connected_sftp(server: &TestServer) -> SftpFileTransfer {
+        let mut transfer = SftpFileTransfer::with_password(
+            server.host,
+            server.port,
+            server.username,
+            server.password,
+        );
+        transfer.connect().expect("SFTP 应该能连接");
+        transfer
+    }
+
+    #[test]
+    fn test_copy_file_via_exec() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let mut transfer = connected_sftp(&TEST_SERVER_1);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let src_path = format!("/home/testuser/uploads/copy_src_{}.txt", timestamp);
+        let dst_path = format!("/home/testuser/uploads/copy_dst_{}.txt", timestamp);
+        let content = b"content to be server-side copied";
+
+        {
+            let mut writer = transfer
+                .open_write(&src_path, None)
+                .expect("应该能创建源文件");
+            writer.write_all(content).expect("应该能写入源文件");
+            writer.finish().expect("应该能完成写入");
+        }
+
+        transfer
+            .copy(&src_path, &dst_path)
+            .expect("远端 cp 复制应该成功");
+
+        let copied_stat = transfer.stat(&dst_path).expect("目标文件应该存在");
+        assert!(!copied_stat.is_dir(), "复制的目标应该是文件");
+
+        let mut reader = transfer.open_read(&dst_path).expect("应该能打开目标文件");
+        let mut copied_content = Vec::new();
+        reader
+            .read_to_end(&mut copied_content)
+            .expect("应该能读取目标文件");
+        assert_eq!(copied_content, content, "复制内容应该一致");
+
+        transfer.remove_file(&src_path).ok();
+        transfer.remove_file(&dst_path).ok();
+    }
+
+    #[test]
+    fn test_copy_directory_recursive_via_exec() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let mut transfer = connected_sftp(&TEST_SERVER_1);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let src_dir = format!("/home/testuser/uploads/copy_dir_src_{}", timestamp);
+        let dst_dir = format!("/home/testuser/uploads/copy_dir_dst_{}", timestamp);
+
+        transfer.mkdir(&src_dir).expect("应该能创建源目录");
+        transfer
+            .mkdir(&format!("{}/sub", src_dir))
+            .expect("应该能创建源子目录");
+        {
+            let mut writer = transfer
+                .open_write(&format!("{}/a.txt", src_dir), None)
+                .expect("应该能创建文件 a.txt");
+            writer.write_all(b"a").expect("应该能写入 a.txt");
+            writer.finish().expect("应该能完成写入");
+        }
+        {
+            let mut writer = transfer
+                .open_write(&format!("{}/sub/b.txt", src_dir), None)
+                .expect("应该能创建文件 b.txt");
+            writer.write_all(b"b").expect("应该能写入 b.txt");
+            writer.finish().expect("应该能完成写入");
+        }
+
+        transfer
+            .copy(&src_dir, &dst_dir)
+            .expect("远端递归 cp 复制应该成功");
+
+        let dst_stat = transfer.stat(&dst_dir).expect("目标目录应该存在");
+        assert!(dst_stat.is_dir(), "复制的目标应该是目录");
+
+        let mut a_reader = transfer
+            .open_read(&format!("{}/a.txt", dst_dir))
+            .expect("应该能打开复制后的 a.txt");
+        let mut a_content = Vec::new();
+        a_reader.read_to_end(&mut a_content).unwrap();
+        assert_eq!(a_content, b"a");
+
+        let mut b_reader = transfer
+            .open_read(&format!("{}/sub/b.txt", dst_dir))
+            .expect("应该能打开复制后的 sub/b.txt");
+        let mut b_content = Vec::new();
+        b_reader.read_to_end(&mut b_content).unwrap();
+        assert_eq!(b_content, b"b");
+
+        transfer.remove_dir_all(&src_dir).ok();
+        transfer.remove_dir_all(&dst_dir).ok();
+    }
+}
+
+// ============ 连接池测试 ============
+
+mod pooled_connection_tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use tunnelfiles_lib::services::file_transfer::{FileTransfer, SftpFileTransfer};
+    use tunnelfiles_lib::services::ssh_pool::{SshConnectionPool, SshConnectionPoolConfig};
+
+    #[test]
+    fn test_concurrent_stat_via_pool() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let pool = SshConnectionPool::new(SshConnectionPoolConfig {
+            max_size_per_key: 5,
+            ..Default::default()
+        });
+
+        let handles: Vec<_> = (0..5)
+            .map(|i| {
+                let pool = Arc::clone(&pool);
+                thread::spawn(move || {
+                    let mut transfer = SftpFileTransfer::with_password(
+                        TEST_SERVER_1.host,
+                        TEST_SERVER_1.port,
+                        TEST_SERVER_1.username,
+                        TEST_SERVER_1.password,
+                    )
+                    .with_pool(pool);
+                    transfer.connect().expect("应该能从连接池获取连接");
+                    let entries = transfer.list_dir("/home/testuser/test-files");
+                    assert!(entries.is_ok(), "并发读取 {} 应该成功", i);
+                    entries.unwrap().len()
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        let first = results[0];
+        for result in &results {
+            assert_eq!(*result, first, "并发读取结果应该一致");
+        }
+    }
+
+    #[test]
+    fn test_pool_enforces_capacity_limit() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let pool = SshConnectionPool::new(SshConnectionPoolConfig {
+            max_size_per_key: 1,
+            ..Default::default()
+        });
+
+        let mut first = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        )
+        .with_pool(Arc::clone(&pool));
+        first.connect().expect("第一个连接应该成功借出");
+
+        let mut second = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        )
+        .with_pool(Arc::clone(&pool));
+        let result = second.connect();
+        assert!(result.is_err(), "超过容量上限时第二个借用应该失败");
+
+        first.disconnect().ok();
+
+        let mut third = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        )
+        .with_pool(Arc::clone(&pool));
+        third.connect().expect("第一个连接归还后应该能再次借出");
+    }
+
+    #[test]
+    fn test_pool_config_builder_roundtrip() {
+        // 不需要 Docker：只验证链式 builder 设置的值确实被保留下来
+        let config = SshConnectionPoolConfig::default()
+            .with_max_size_per_key(8)
+            .with_idle_timeout(std::time::Duration::from_secs(30))
+            .with_connect_timeout(std::time::Duration::from_secs(5))
+            .with_test_on_acquire(true);
+
+        assert_eq!(config.max_size_per_key, 8);
+        assert_eq!(config.idle_timeout, std::time::Duration::from_secs(30));
+        assert_eq!(config.connect_timeout, std::time::Duration::from_secs(5));
+        assert!(config.test_on_acquire);
+    }
+
+    #[test]
+    fn test_pool_reuses_connection_with_test_on_acquire_enabled() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let pool = SshConnectionPool::new(
+            SshConnectionPoolConfig::default()
+                .with_max_size_per_key(2)
+                .with_test_on_acquire(true),
+        );
+
+        let mut first = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        )
+        .with_pool(Arc::clone(&pool));
+        first.connect().expect("健康检查开启时也应该能正常借出连接");
+        first.disconnect().ok();
+
+        let mut second = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        )
+        .with_pool(Arc::clone(&pool));
+        second
+            .connect()
+            .expect("健康检查通过后应该能复用归还的空闲连接");
+    }
+}
+
+// ============ 断点续传测试 ============
+//
+// 覆盖 `SftpFileTransfer::resume_upload`/`resume_download`，验证中断后从断点续传能拼出
+// 与一次性整体传输完全一致的文件，以及本地/远端内容被篡改时能正确回退为完整传输。
+
+mod resume_transfer_tests {
+    use super::*;
+    use std::io::Write as _;
+    use tunnelfiles_lib::services::file_transfer::{FileTransfer, SftpFileTransfer};
+
+    fn predictable_bytes(size: usize) -> Vec<u8> {
+        (0..size).map(|i| ((i * 31 + 7) % 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_resume_upload_continues_from_remote_offset() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let remote_path = format!("/home/testuser/uploads/resume_up_{}.bin", timestamp);
+
+        let original = predictable_bytes(512 * 1024);
+        let local_path = std::env::temp_dir().join(format!("resume_up_local_{}.bin", timestamp));
+        std::fs::write(&local_path, &original).unwrap();
+
+        // 先用原始连接在远端造出"已传了一半"的残留文件
+        let (_session, sftp) = create_sftp_session(&TEST_SERVER_1).unwrap();
+        {
+            let mut remote_file = sftp
+                .create(std::path::Path::new(&remote_path))
+                .expect("应该能创建残留文件");
+            remote_file
+                .write_all(&original[..256 * 1024])
+                .expect("应该能写入前半部分");
+        }
+
+        let mut transfer = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        );
+        transfer.connect().expect("应该能连接");
+        transfer
+            .resume_upload(&local_path, &remote_path, 64 * 1024)
+            .expect("续传上传应该成功");
+
+        let mut remote_file = sftp
+            .open(std::path::Path::new(&remote_path))
+            .expect("应该能打开续传后的文件");
+        let mut downloaded = Vec::new();
+        remote_file.read_to_end(&mut downloaded).expect("应该能读取");
+        assert_eq!(downloaded, original, "续传后的内容应该与原始文件完全一致");
+
+        sftp.unlink(std::path::Path::new(&remote_path)).ok();
+        std::fs::remove_file(&local_path).ok();
+    }
+
+    #[test]
+    fn test_resume_upload_falls_back_when_prefix_mismatches() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let remote_path = format!("/home/testuser/uploads/resume_up_mismatch_{}.bin", timestamp);
+
+        let original = predictable_bytes(256 * 1024);
+        let local_path =
+            std::env::temp_dir().join(format!("resume_up_mismatch_local_{}.bin", timestamp));
+        std::fs::write(&local_path, &original).unwrap();
+
+        let (_session, sftp) = create_sftp_session(&TEST_SERVER_1).unwrap();
+        {
+            // 远端残留内容与本地文件的前缀不一致（全零字节而非 predictable_bytes）
+            let mut remote_file = sftp
+                .create(std::path::Path::new(&remote_path))
+                .expect("应该能创建残留文件");
+            remote_file
+                .write_all(&vec![0u8; 128 * 1024])
+                .expect("应该能写入不一致的前缀");
+        }
+
+        let mut transfer = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        );
+        transfer.connect().expect("应该能连接");
+        transfer
+            .resume_upload(&local_path, &remote_path, 32 * 1024)
+            .expect("前缀不一致时应该回退为完整上传而不是报错");
+
+        let mut remote_file = sftp
+            .open(std::path::Path::new(&remote_path))
+            .expect("应该能打开文件");
+        let mut downloaded = Vec::new();
+        remote_file.read_to_end(&mut downloaded).expect("应该能读取");
+        assert_eq!(downloaded, original, "回退完整上传后内容应该与本地文件一致");
+
+        sftp.unlink(std::path::Path::new(&remote_path)).ok();
+        std::fs::remove_file(&local_path).ok();
+    }
+
+    #[test]
+    fn test_resume_download_continues_from_local_offset() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let remote_path = format!("/home/testuser/uploads/resume_down_{}.bin", timestamp);
+
+        let original = predictable_bytes(512 * 1024);
+        let (_session, sftp) = create_sftp_session(&TEST_SERVER_1).unwrap();
+        {
+            let mut remote_file = sftp
+                .create(std::path::Path::new(&remote_path))
+                .expect("应该能创建远端文件");
+            remote_file.write_all(&original).expect("应该能写入完整内容");
+        }
+
+        // 本地已有"下载了一半"的残留文件
+        let local_path = std::env::temp_dir().join(format!("resume_down_local_{}.bin", timestamp));
+        std::fs::write(&local_path, &original[..300 * 1024]).unwrap();
+
+        let mut transfer = SftpFileTransfer::with_password(
+            TEST_SERVER_1.host,
+            TEST_SERVER_1.port,
+            TEST_SERVER_1.username,
+            TEST_SERVER_1.password,
+        );
+        transfer.connect().expect("应该能连接");
+        transfer
+            .resume_download(&remote_path, &local_path, 64 * 1024)
+            .expect("续传下载应该成功");
+
+        let downloaded = std::fs::read(&local_path).unwrap();
+        assert_eq!(downloaded, original, "续传下载后的本地内容应该与远端完全一致");
+
+        sftp.unlink(std::path::Path::new(&remote_path)).ok();
+        std::fs::remove_file(&local_path).ok();
+    }
+}
+
 // ============ HostKey 测试 ============
 
 mod hostkey_tests {
@@ -1214,3 +1906,134 @@ mod hostkey_tests {
         assert!(!key2.is_empty());
     }
 }
+
+// ============ delete_recursive 祖先目录调包回归测试 ============
+
+/// 针对 CVE-2022-21658 同类手法、但目标是祖先目录而不是叶子的回归测试：
+/// `SftpService::delete_recursive` 会先删完整棵子树的所有文件，才回头逐个删除
+/// 目录（从最深的开始），这段时间窗口足够攻击者把一个尚未轮到删除、但已经被
+/// 清空的祖先目录换成指向树外的符号链接。只对被删条目本身做 `lstat` 看不出这次
+/// 调包——后续组件的路径解析会顺着被换掉的祖先走到树外，`rmdir` 就可能删到完全
+/// 不相干的目录
+mod delete_recursive_security_tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::Path;
+    use std::thread;
+    use tunnelfiles_lib::services::sftp_service::SftpService;
+
+    /// 拖慢文件删除阶段用的诱饵文件数量：数量够多，文件循环才会跑得足够久，
+    /// 给攻击者线程留出充裕的时间窗口在目录循环开始之前完成调包，不必卡着
+    /// 某个毫秒级的精确时机赌概率
+    const DECOY_FILE_COUNT: usize = 80;
+
+    #[test]
+    fn test_delete_recursive_blocks_ancestor_symlink_swap() {
+        if !is_docker_available() {
+            eprintln!("跳过: Docker SSH 服务不可用");
+            return;
+        }
+
+        let (_main_session, sftp) = create_sftp_session(&TEST_SERVER_1).unwrap();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let root = format!("/home/testuser/uploads/tf_swap_root_{}", timestamp);
+        let decoys_dir = format!("{}/decoys", root);
+        let victim = format!("{}/victim", root);
+        let deep = format!("{}/deep", victim);
+        let outside = format!("/home/testuser/uploads/tf_swap_outside_{}", timestamp);
+        let outside_deep = format!("{}/deep", outside);
+        let sentinel = format!("{}/sentinel.txt", outside_deep);
+
+        // 受害子树：root/victim/deep 是个空目录，攻击者要做的就是趁它还没被
+        // 删到之前，把祖先 victim 整个换成指向 outside 的符号链接
+        sftp.mkdir(Path::new(&root), 0o755).unwrap();
+        sftp.mkdir(Path::new(&victim), 0o755).unwrap();
+        sftp.mkdir(Path::new(&deep), 0o755).unwrap();
+
+        // 诱饵子树：一堆不相干的文件，唯一作用是拖长文件删除阶段的耗时，让攻击者
+        // 线程有足够长的窗口完成调包，不需要赌目录循环开始那一瞬间的精确时机
+        sftp.mkdir(Path::new(&decoys_dir), 0o755).unwrap();
+        for i in 0..DECOY_FILE_COUNT {
+            let decoy_path = format!("{}/decoy_{:03}.txt", decoys_dir, i);
+            let mut f = sftp.create(Path::new(&decoy_path)).unwrap();
+            f.write_all(b"decoy").unwrap();
+        }
+
+        // 树外的诱饵目录，结构上特意也有一层叫 deep，这样攻击者把 victim 换成
+        // 指向这里的符号链接之后，"root/victim/deep" 解析出来仍然是一个真实存在
+        // 的目录，只看叶子类型的旧实现会被骗过去
+        sftp.mkdir(Path::new(&outside), 0o755).unwrap();
+        sftp.mkdir(Path::new(&outside_deep), 0o755).unwrap();
+        {
+            let mut f = sftp.create(Path::new(&sentinel)).unwrap();
+            f.write_all(b"outside, should never be touched").unwrap();
+        }
+
+        // 攻击者线程：用独立连接轮询诱饵文件的剩余数量，一旦看到文件循环已经
+        // 删掉了一部分（说明收集阶段早就结束、主线程正忙着处理诱饵文件），
+        // 就把 victim 整个换成指向 outside 的符号链接——此时 victim/deep 这个
+        // 真实目录还没轮到目录循环处理，完全来得及调包
+        let attacker_decoys_dir = decoys_dir.clone();
+        let attacker_victim = victim.clone();
+        let attacker_deep = deep.clone();
+        let attacker_outside = outside.clone();
+        let attacker = thread::spawn(move || {
+            let (_atk_session, atk_sftp) = create_sftp_session(&TEST_SERVER_1).unwrap();
+
+            let remaining_decoys = || {
+                atk_sftp
+                    .readdir(Path::new(&attacker_decoys_dir))
+                    .map(|entries| entries.len())
+                    .unwrap_or(0)
+            };
+
+            let deadline = std::time::Instant::now() + Duration::from_secs(15);
+            while remaining_decoys() > DECOY_FILE_COUNT / 2 {
+                if std::time::Instant::now() > deadline {
+                    panic!("等待诱饵文件删除超时，主线程删除流程可能卡住了");
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            // victim 此刻还是原样的真实目录（只有 deep 一个空子目录），先清空
+            // 再整体换成符号链接
+            atk_sftp.rmdir(Path::new(&attacker_deep)).unwrap();
+            atk_sftp.rmdir(Path::new(&attacker_victim)).unwrap();
+            // ssh2::Sftp::symlink(link_path, target)：第一个参数是新建的链接本身
+            atk_sftp
+                .symlink(Path::new(&attacker_victim), Path::new(&attacker_outside))
+                .unwrap();
+        });
+
+        let result = SftpService::delete_recursive(&sftp, &root, None, None, None)
+            .expect("delete_recursive 不应该整体失败");
+
+        attacker.join().expect("攻击者线程不应该 panic");
+
+        // 核心断言：树外的诱饵目录必须原封不动，哪怕 victim 在删除过程中被替换
+        // 成了指向它的符号链接
+        assert!(
+            sftp.stat(Path::new(&sentinel)).is_ok(),
+            "祖先目录被调包后，树外的哨兵文件不应该被删除——这正是本测试要防住的越界删除"
+        );
+
+        // 被调包的 victim/deep 应该被拒绝删除而不是静默跟着符号链接走到树外
+        assert!(
+            result.failures.iter().any(|f| f.path.contains("victim")),
+            "针对被调包的祖先链路，应当记为删除失败而不是悄悄成功: {:?}",
+            result.failures
+        );
+
+        // 清理：调包后残留的 victim 符号链接、root、outside 诱饵目录都要清掉
+        let _ = sftp.unlink(Path::new(&victim));
+        let _ = sftp.rmdir(Path::new(&decoys_dir));
+        let _ = sftp.rmdir(Path::new(&root));
+        let _ = sftp.unlink(Path::new(&sentinel));
+        let _ = sftp.rmdir(Path::new(&outside_deep));
+        let _ = sftp.rmdir(Path::new(&outside));
+    }
+}
@@ -43,6 +43,7 @@ fn create_test_profile(port: u16) -> Profile {
         password_ref: None,
         private_key_path: None,
         passphrase_ref: None,
+        private_key_ref: None,
         initial_path: None,
         created_at: chrono::Utc::now().timestamp_millis(),
         updated_at: chrono::Utc::now().timestamp_millis(),
@@ -69,7 +70,7 @@ fn connect_with_trust(
                 &pending.key_type,
                 &pending.fingerprint,
             )?;
-            manager.connect_after_trust(profile, Some(PASSWORD), None, 30)
+            manager.connect_after_trust(db, profile, Some(PASSWORD), None, 30)
         }
     }
 }